@@ -0,0 +1,123 @@
+//! SensorReadingStream bellek benchmark'ı
+//!
+//! 1 milyon okumayı history/export handler'ları gibi 1000'lik chunk'lar
+//! halinde stream eder; süreyi criterion ile, en yüksek heap kullanımını
+//! sayan bir global allocator ile ölçer. Heap artışı 10 MB'ı geçerse
+//! benchmark başarısız olur.
+//!
+//! ```text
+//! TEST_DATABASE_URL=postgres://postgres@localhost/rustyflow_test cargo bench -p api-server --bench sensor_reading_stream
+//! ```
+//!
+//! `TEST_DATABASE_URL` yoksa benchmark atlanır.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures_util::StreamExt;
+use sqlx::PgPool;
+use tokio::runtime::Runtime;
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/db.rs"]
+mod db;
+
+use db::SensorReadingStream;
+
+/// Anlık ve en yüksek heap kullanımını sayan allocator
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+/// Benchmark cihazı (gerçek okumalarla karışmaz, sonunda silinir)
+const DEVICE_ID: &str = "bench-sensor-reading-stream";
+
+/// Stream edilen satır sayısı
+const ROWS: i64 = 1_000_000;
+
+/// İzin verilen en yüksek heap artışı
+const MAX_HEAP_BYTES: usize = 10 * 1024 * 1024;
+
+async fn setup(db: &PgPool) {
+    teardown(db).await;
+    sqlx::query(
+        "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp)
+         SELECT $1, 'temperature', random() * 40, '°C',
+                now() - make_interval(secs => ($2 - i)::DOUBLE PRECISION)
+         FROM generate_series(1, $2::BIGINT) AS i",
+    )
+    .bind(DEVICE_ID)
+    .bind(ROWS)
+    .execute(db)
+    .await
+    .unwrap();
+}
+
+async fn teardown(db: &PgPool) {
+    sqlx::query("DELETE FROM sensor_readings WHERE device_id = $1")
+        .bind(DEVICE_ID)
+        .execute(db)
+        .await
+        .unwrap();
+}
+
+/// Tüm satırları chunk'lar halinde oku, sayısını döndür
+async fn stream_all(readings: &SensorReadingStream) -> usize {
+    let mut chunks = readings.fetch().chunks(1000);
+    let mut count = 0;
+    while let Some(chunk) = chunks.next().await {
+        count += chunk.into_iter().map(Result::unwrap).count();
+    }
+    count
+}
+
+fn bench_stream_million_rows(c: &mut Criterion) {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL not set, skipping sensor reading stream benchmark");
+        return;
+    };
+    let rt = Runtime::new().unwrap();
+    let db = rt.block_on(PgPool::connect(&url)).unwrap();
+    rt.block_on(setup(&db));
+    let readings = SensorReadingStream::new(db.clone(), DEVICE_ID, "temperature");
+
+    // Bağlantı havuzu ve runtime'ın kendi ayırmaları ölçüme karışmasın
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let count = rt.block_on(stream_all(&readings));
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+    assert_eq!(count, ROWS as usize);
+    println!("streamed {count} rows, peak heap growth {:.2} MB", peak as f64 / (1024.0 * 1024.0));
+    assert!(peak < MAX_HEAP_BYTES, "peak heap growth {peak} bytes exceeds {MAX_HEAP_BYTES}");
+
+    let mut group = c.benchmark_group("sensor_reading_stream");
+    group.sample_size(10);
+    group.bench_function("stream_1m_rows", |b| b.to_async(&rt).iter(|| stream_all(&readings)));
+    group.finish();
+
+    rt.block_on(teardown(&db));
+}
+
+criterion_group!(benches, bench_stream_million_rows);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+//! sensor_readings index benchmark'ı
+//!
+//! 100k satırlık bir kopya tabloda "cihazın son 100 okuması" sorgusunu
+//! `sensor_readings_device_type_ts` index'i olmadan ve olduğunda ölçer.
+//!
+//! ```text
+//! TEST_DATABASE_URL=postgres://postgres@localhost/rustyflow_test cargo bench -p api-server
+//! ```
+//!
+//! `TEST_DATABASE_URL` yoksa benchmark atlanır.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use sqlx::PgPool;
+use tokio::runtime::Runtime;
+
+/// Geçici benchmark tablosu (gerçek veriye dokunulmaz)
+const TABLE: &str = "bench_sensor_readings";
+
+/// Eklenen satır sayısı
+const ROWS: i64 = 100_000;
+
+/// Ölçülen sorgu: tek cihaz + sensör tipi, en yeni 100 okuma
+const LATEST_SQL: &str = "SELECT id, value, timestamp FROM bench_sensor_readings
+     WHERE device_id = 'device-42' AND sensor_type = 'temperature'
+     ORDER BY timestamp DESC LIMIT 100";
+
+/// Tabloyu oluştur ve 100 cihaz × 3 sensör tipine dağılmış okumalarla doldur
+async fn setup(db: &PgPool) {
+    sqlx::query(&format!("DROP TABLE IF EXISTS {TABLE}")).execute(db).await.unwrap();
+    sqlx::query(&format!("CREATE TABLE {TABLE} (LIKE sensor_readings INCLUDING DEFAULTS)"))
+        .execute(db)
+        .await
+        .unwrap();
+    sqlx::query(&format!(
+        "INSERT INTO {TABLE} (device_id, sensor_type, value, unit, timestamp)
+         SELECT 'device-' || (i % 100),
+                (ARRAY['temperature', 'humidity', 'motion'])[1 + (i / 100) % 3],
+                random() * 40,
+                '',
+                now() - make_interval(secs => ($1 - i)::DOUBLE PRECISION)
+         FROM generate_series(1, $1::BIGINT) AS i"
+    ))
+    .bind(ROWS)
+    .execute(db)
+    .await
+    .unwrap();
+    sqlx::query(&format!("ANALYZE {TABLE}")).execute(db).await.unwrap();
+}
+
+fn bench_latest_readings(c: &mut Criterion) {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL not set, skipping sensor_readings index benchmark");
+        return;
+    };
+    let rt = Runtime::new().unwrap();
+    let db = rt.block_on(PgPool::connect(&url)).unwrap();
+    rt.block_on(setup(&db));
+
+    let mut group = c.benchmark_group("sensor_readings_latest_100");
+    group.bench_function("without_index", |b| {
+        b.to_async(&rt).iter(|| async {
+            sqlx::query(LATEST_SQL).fetch_all(&db).await.unwrap()
+        })
+    });
+
+    rt.block_on(async {
+        sqlx::query(&format!(
+            "CREATE INDEX {TABLE}_device_type_ts ON {TABLE} (device_id, sensor_type, timestamp DESC)"
+        ))
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query(&format!("ANALYZE {TABLE}")).execute(&db).await.unwrap();
+    });
+
+    group.bench_function("with_index", |b| {
+        b.to_async(&rt).iter(|| async {
+            sqlx::query(LATEST_SQL).fetch_all(&db).await.unwrap()
+        })
+    });
+    group.finish();
+
+    rt.block_on(async {
+        sqlx::query(&format!("DROP TABLE {TABLE}")).execute(&db).await.unwrap();
+    });
+}
+
+criterion_group!(benches, bench_latest_readings);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+//! Redis Pub/Sub Okuma Aboneliği Örneği
+//!
+//! API server `PUBSUB_ENABLED=true` ile çalışırken kabul edilen okumaları
+//! Redis kanalından dinleyip yazdırır.
+//!
+//! ```sh
+//! # Tüm cihazlar (rustyflow:readings)
+//! cargo run -p api-server --example readings_subscriber
+//!
+//! # Tek cihaz (rustyflow:readings:{device_id})
+//! cargo run -p api-server --example readings_subscriber -- edge-agent-001
+//! ```
+//!
+//! Redis adresi `REDIS_URL`'den okunur (varsayılan: `redis://localhost:6379`).
+
+use futures_util::StreamExt;
+use serde::Deserialize;
+
+/// Kanala publish edilen okuma (`POST /api/sensors` formatı)
+#[derive(Debug, Deserialize)]
+struct Reading {
+    device_id: String,
+    sensor_type: String,
+    value: f64,
+    unit: String,
+    timestamp: String,
+}
+
+#[tokio::main]
+async fn main() -> redis::RedisResult<()> {
+    let redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".into());
+    let channel = match std::env::args().nth(1) {
+        Some(device_id) => format!("rustyflow:readings:{device_id}"),
+        None => "rustyflow:readings".to_string(),
+    };
+
+    let client = redis::Client::open(redis_url)?;
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(&channel).await?;
+    println!("📡 Listening on {channel} (Ctrl+C to stop)");
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let payload: String = message.get_payload()?;
+        match serde_json::from_str::<Reading>(&payload) {
+            Ok(r) => println!("{} {} {}: {} {}", r.timestamp, r.device_id, r.sensor_type, r.value, r.unit),
+            Err(e) => eprintln!("⚠️  Invalid reading on {}: {e}", message.get_channel_name()),
+        }
+    }
+    Ok(())
+}
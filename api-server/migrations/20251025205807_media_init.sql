@@ -1,4 +1,3 @@
--- migrate:up
 CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 
 CREATE TABLE IF NOT EXISTS media_datas (
@@ -10,7 +9,3 @@ CREATE TABLE IF NOT EXISTS media_datas (
     created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
     updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
 );
-
--- migrate:down
-DROP TABLE IF EXISTS media_datas;
-DROP EXTENSION IF EXISTS "uuid-ossp";
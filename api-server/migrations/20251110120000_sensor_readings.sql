@@ -0,0 +1,11 @@
+-- Sensör okuma geçmişi (MQTT gateway → POST /api/sensors)
+-- Redis sadece son değeri tutar; history sorguları bu tabloyu kullanır.
+CREATE TABLE IF NOT EXISTS sensor_readings (
+    id BIGSERIAL PRIMARY KEY,
+    device_id TEXT NOT NULL,
+    sensor_type TEXT NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    unit TEXT NOT NULL DEFAULT '',
+    timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    metadata JSONB
+);
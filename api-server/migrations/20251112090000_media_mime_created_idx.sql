@@ -0,0 +1,2 @@
+-- GET /v1/media filtreleri için: mime_type LIKE 'image/%' + created_at aralığı
+CREATE INDEX IF NOT EXISTS idx_media_datas_mime_created ON media_datas (mime_type, created_at);
@@ -0,0 +1,9 @@
+-- Cihaz gölgeleri (device twin): istenen / raporlanan durum ve aralarındaki fark
+CREATE TABLE IF NOT EXISTS device_shadows (
+    device_id UUID PRIMARY KEY,
+    desired JSONB NOT NULL DEFAULT '{}'::jsonb,
+    reported JSONB NOT NULL DEFAULT '{}'::jsonb,
+    delta JSONB NOT NULL DEFAULT '{}'::jsonb,
+    version BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+-- Media full-text arama (GET /v1/media?q=...)
+-- Dosya adlarındaki ayraçlar ('.', '_', '-', '/') boşluğa çevrilir; aksi halde
+-- 'vacation.jpg' tek bir dosya token'ı olarak indekslenir ve 'vacation' ile bulunamaz.
+ALTER TABLE media_datas
+    ADD COLUMN search_vector tsvector
+    GENERATED ALWAYS AS (to_tsvector('english', translate(name, '._-/', '    '))) STORED;
+
+CREATE INDEX media_search_idx ON media_datas USING GIN (search_vector);
@@ -0,0 +1,15 @@
+-- Saatlik sensör özetleri (background::aggregate_sensor_readings doldurur)
+-- GET /v1/sensors/{device_id}/{sensor_type}/summaries bu tabloyu okur.
+CREATE TABLE IF NOT EXISTS sensor_summaries (
+    device_id TEXT NOT NULL,
+    sensor_type TEXT NOT NULL,
+    bucket_start TIMESTAMP WITH TIME ZONE NOT NULL,
+    min_value DOUBLE PRECISION NOT NULL,
+    max_value DOUBLE PRECISION NOT NULL,
+    avg_value DOUBLE PRECISION NOT NULL,
+    count BIGINT NOT NULL,
+    PRIMARY KEY (device_id, sensor_type, bucket_start)
+);
+
+-- Aggregation sorgusu son iki saatlik okumaları tarar
+CREATE INDEX IF NOT EXISTS idx_sensor_readings_timestamp ON sensor_readings (timestamp);
@@ -0,0 +1,31 @@
+-- sensor_readings index stratejisi
+--
+-- 1) sensor_readings_device_type_ts (B-tree, device_id + sensor_type + timestamp DESC)
+--    Dashboard ve history sorgularının ana şekli:
+--      WHERE device_id = $1 AND sensor_type = $2 ORDER BY timestamp DESC LIMIT N
+--    Eşitlik kolonları önde, sıralama kolonu sonda olduğundan Postgres index'i
+--    sırayla okuyup LIMIT'e ulaşınca durur (sort adımı yok). Aynı index
+--    ASC sıralı aralık sorgularında (HISTORY_SQL) geriye doğru taranır.
+--
+-- 2) sensor_readings_ts (B-tree, timestamp DESC)
+--    Cihazdan bağımsız zaman filtreleri: retention (DELETE ... WHERE timestamp < $1),
+--    saatlik aggregation penceresi, dashboard özeti (son bir saat, max(timestamp)).
+--    20251120080000'deki artan sıralı idx_sensor_readings_timestamp'in yerini alır;
+--    B-tree her iki yönde de taranabildiği için ikisini birden tutmak gereksiz.
+--
+-- 3) sensor_readings_ts_brin (BRIN, timestamp)
+--    Okumalar zaman sırasına yakın eklenir (append-only), bu yüzden fiziksel
+--    sayfa sırası timestamp ile korelasyonludur. BRIN her sayfa bloğu için sadece
+--    min/max tutar: B-tree'nin binde biri boyutunda, geniş zaman aralığı taramaları
+--    için yeterli. Tablo çok büyüdüğünde sensor_readings_ts kaldırılıp sadece
+--    BRIN'e geçilebilir; planner şimdilik küçük aralıklarda B-tree'yi seçer.
+
+CREATE INDEX IF NOT EXISTS sensor_readings_device_type_ts
+    ON sensor_readings (device_id, sensor_type, timestamp DESC);
+
+DROP INDEX IF EXISTS idx_sensor_readings_timestamp;
+CREATE INDEX IF NOT EXISTS sensor_readings_ts
+    ON sensor_readings (timestamp DESC);
+
+CREATE INDEX IF NOT EXISTS sensor_readings_ts_brin
+    ON sensor_readings USING BRIN (timestamp);
@@ -0,0 +1,26 @@
+-- Webhook kayıtları (POST/GET /v1/webhooks, DELETE /v1/webhooks/{id})
+-- secret HMAC-SHA256 imzası için saklanır, API response'larında dönmez.
+CREATE TABLE IF NOT EXISTS webhooks (
+    id UUID PRIMARY KEY,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+-- Teslimat sonuçları (background::deliver_webhooks yazar)
+-- Her satır bir olayın bir webhook'a teslimatının nihai sonucudur.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id UUID PRIMARY KEY,
+    webhook_id UUID NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL,
+    delivered BOOLEAN NOT NULL,
+    response_status INTEGER,
+    error TEXT,
+    completed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook ON webhook_deliveries (webhook_id, completed_at DESC);
@@ -0,0 +1,4 @@
+-- Media optimistic concurrency (PUT /v1/media/{id})
+-- Her güncellemede version bir artar; `expected_version` / `If-Match`
+-- ile gönderilen sürüm eşleşmezse güncelleme reddedilir.
+ALTER TABLE media_datas ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,13 @@
+-- İkili (motion) sensör olayları (POST /api/sensors → GET /api/sensors/events)
+-- Her satır 0→1 ile başlayıp 1→0 ile biten bir aralıktır; ended_at NULL ise sürüyor.
+-- Olaylar sensor_readings'ten türetilir: geç gelen okumada etkilenen olaylar yeniden eşlenir.
+CREATE TABLE IF NOT EXISTS motion_events (
+    id BIGSERIAL PRIMARY KEY,
+    device_id TEXT NOT NULL,
+    sensor_type TEXT NOT NULL,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ended_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_motion_events_device_started
+    ON motion_events (device_id, sensor_type, started_at);
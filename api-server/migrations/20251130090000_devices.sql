@@ -0,0 +1,11 @@
+-- Kayıtlı cihazlar (POST /v1/provision)
+-- Aynı seri numarasıyla tekrar kayıt aynı id'yi döner; model/firmware güncellenir.
+CREATE TABLE IF NOT EXISTS devices (
+    id UUID PRIMARY KEY,
+    serial_number TEXT NOT NULL UNIQUE,
+    model TEXT NOT NULL,
+    firmware_version TEXT NOT NULL,
+    public_key_pem TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
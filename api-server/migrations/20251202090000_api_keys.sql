@@ -0,0 +1,8 @@
+-- API anahtarları (api-server create-api-key)
+-- Anahtarın kendisi saklanmaz, sadece SHA-256 hash'i (hex).
+CREATE TABLE IF NOT EXISTS api_keys (
+    id UUID PRIMARY KEY,
+    name TEXT,
+    key_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,5 @@
+-- Sunucunun okumayı aldığı zaman
+-- `timestamp` cihazın bildirdiği zamandır; saati bozuk cihazlarda ileri
+-- tarihli timestamp'ler received_at'e çekilir (MAX_FUTURE_SKEW_SECS).
+-- Bu migration'dan önceki satırlarda NULL kalır.
+ALTER TABLE sensor_readings ADD COLUMN IF NOT EXISTS received_at TIMESTAMP WITH TIME ZONE;
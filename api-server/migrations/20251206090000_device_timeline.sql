@@ -0,0 +1,36 @@
+-- Cihaz zaman çizelgesi kaynakları (GET /v1/devices/{id}/timeline)
+-- Okumalar sensor_readings'ten gelir; bu tablolar diğer olay türlerini tutar.
+
+-- Eşik alarmları (ALERT_THRESHOLDS aşıldığında ingest yolu yazar)
+-- device_id sensor_readings ile aynı şekilde TEXT'tir.
+CREATE TABLE IF NOT EXISTS sensor_alerts (
+    id UUID PRIMARY KEY,
+    device_id TEXT NOT NULL,
+    sensor_type TEXT NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    threshold_min DOUBLE PRECISION,
+    threshold_max DOUBLE PRECISION,
+    message TEXT NOT NULL,
+    triggered_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sensor_alerts_device_time ON sensor_alerts (device_id, triggered_at DESC);
+
+-- Heartbeat'ler (gateway shadow/reported'a {"last_heartbeat": ...} gönderir)
+CREATE TABLE IF NOT EXISTS device_heartbeats (
+    id BIGSERIAL PRIMARY KEY,
+    device_id UUID NOT NULL,
+    received_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_heartbeats_device_time ON device_heartbeats (device_id, received_at DESC);
+
+-- Raporlanan durumlar (gateway shadow/reported'a {"status": ...} gönderir)
+CREATE TABLE IF NOT EXISTS device_status_changes (
+    id BIGSERIAL PRIMARY KEY,
+    device_id UUID NOT NULL,
+    status JSONB NOT NULL,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_status_changes_device_time ON device_status_changes (device_id, changed_at DESC);
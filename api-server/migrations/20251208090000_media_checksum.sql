@@ -0,0 +1,8 @@
+-- İçeriğin SHA-256 özeti (POST /v1/media/upload)
+-- Metadata ile oluşturulan kayıtlarda NULL kalır; aynı içerik iki kez
+-- kaydedilemez (partial unique index NULL'ları kapsamaz).
+ALTER TABLE media_datas ADD COLUMN IF NOT EXISTS checksum_sha256 TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_media_checksum_sha256
+    ON media_datas (checksum_sha256)
+    WHERE checksum_sha256 IS NOT NULL;
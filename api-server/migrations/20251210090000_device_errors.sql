@@ -0,0 +1,15 @@
+-- Cihazların raporladığı hatalar (POST /api/devices/{id}/errors)
+-- Gateway edge agent'ların error_report mesajlarını iletir. Tablo cihaz
+-- başına sınırlıdır: her eklemede son eklenen MAX_DEVICE_ERRORS_PER_DEVICE
+-- satır dışındakiler silinir.
+CREATE TABLE IF NOT EXISTS device_errors (
+    id BIGSERIAL PRIMARY KEY,
+    device_id UUID NOT NULL,
+    level TEXT NOT NULL,
+    message TEXT NOT NULL,
+    context JSONB,
+    reported_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    received_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_errors_device_time ON device_errors (device_id, reported_at DESC);
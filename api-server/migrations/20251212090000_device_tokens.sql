@@ -0,0 +1,14 @@
+-- Cihaza özel API token'ları (POST /v1/devices/{id}/tokens)
+-- Token'ın kendisi saklanmaz, sadece SHA-256 hash'i (hex). Ingest
+-- endpoint'leri bu token'la gelen isteği token'ın cihazına bağlar.
+CREATE TABLE IF NOT EXISTS device_tokens (
+    id UUID PRIMARY KEY,
+    device_id UUID NOT NULL,
+    label TEXT,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE,
+    last_used_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_tokens_device ON device_tokens (device_id);
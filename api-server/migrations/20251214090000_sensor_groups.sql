@@ -0,0 +1,16 @@
+-- Sensör grupları (POST /v1/sensor-groups)
+-- Farklı cihazlardaki sensörleri mantıksal olarak gruplar. Okumalar
+-- device_id + sensor_type ile saklandığından sensor_id bu çiftten türetilen
+-- UUID v5'tir (shared_types::Sensor::id_for).
+CREATE TABLE IF NOT EXISTS sensor_groups (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS sensor_group_members (
+    group_id UUID NOT NULL REFERENCES sensor_groups (id) ON DELETE CASCADE,
+    sensor_id UUID NOT NULL,
+    PRIMARY KEY (group_id, sensor_id)
+);
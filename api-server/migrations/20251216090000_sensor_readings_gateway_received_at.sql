@@ -0,0 +1,5 @@
+-- Gateway'in MQTT mesajını aldığı zaman
+-- `timestamp` cihazın bildirdiği, `received_at` API server'ın aldığı zamandır;
+-- üçü birlikte cihaz saat kaymasını ve gateway → API gecikmesini ayırır.
+-- Doğrudan API'ye gönderilen okumalarda ve bu migration'dan önceki satırlarda NULL kalır.
+ALTER TABLE sensor_readings ADD COLUMN IF NOT EXISTS gateway_received_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,35 @@
+-- Yeni okumaları `rustyflow_readings` kanalına NOTIFY et (DB_NOTIFY=true, bkz. db_notify.rs)
+-- HTTP API'yi atlayıp doğrudan tabloya yazan servislerin okumaları da canlı akışa düşer.
+-- API server'ın kendi yazdıkları (application_name = 'rustyflow-api-server') zaten
+-- yayınlandığı için atlanır. NOTIFY payload'ı 8000 byte'tan kısa olmalıdır; daha
+-- büyük satırlarda sadece `id` gönderilir ve dinleyici satırı kendisi okur.
+CREATE OR REPLACE FUNCTION rustyflow_notify_reading() RETURNS trigger AS $$
+DECLARE
+    payload TEXT;
+BEGIN
+    IF current_setting('application_name', true) = 'rustyflow-api-server' THEN
+        RETURN NEW;
+    END IF;
+    payload := json_build_object(
+        'id', NEW.id,
+        'device_id', NEW.device_id,
+        'sensor_type', NEW.sensor_type,
+        'value', NEW.value,
+        'unit', NEW.unit,
+        'timestamp', NEW.timestamp,
+        'metadata', NEW.metadata,
+        'received_at', NEW.received_at,
+        'gateway_received_at', NEW.gateway_received_at
+    )::text;
+    IF octet_length(payload) >= 8000 THEN
+        payload := json_build_object('id', NEW.id)::text;
+    END IF;
+    PERFORM pg_notify('rustyflow_readings', payload);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS sensor_readings_notify ON sensor_readings;
+CREATE TRIGGER sensor_readings_notify
+    AFTER INSERT ON sensor_readings
+    FOR EACH ROW EXECUTE FUNCTION rustyflow_notify_reading();
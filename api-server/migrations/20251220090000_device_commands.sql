@@ -0,0 +1,18 @@
+-- Cihazlara gönderilen komutlar ve sonuçları (GET /v1/devices/{id}/commands/history)
+-- Satır komut MQTT'ye verilince eklenir; cihazın yanıtı gateway üzerinden
+-- POST /v1/devices/{id}/commands/ack ile gelince ack_* kolonları doldurulur.
+-- ack_status NULL: yanıt bekleniyor.
+CREATE TABLE IF NOT EXISTS device_commands (
+    correlation_id UUID PRIMARY KEY,
+    device_id UUID NOT NULL,
+    command_type TEXT NOT NULL,
+    command_name TEXT NOT NULL,
+    parameters JSONB,
+    dispatched_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ack_status TEXT CHECK (ack_status IN ('executed', 'failed')),
+    ack_message TEXT,
+    acked_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS device_commands_device_dispatched_idx
+    ON device_commands (device_id, dispatched_at DESC);
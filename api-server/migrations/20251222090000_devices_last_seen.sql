@@ -0,0 +1,4 @@
+-- Cihazın son okumasının zamanı (POST /api/sensors ve /v1/sensors/ingest günceller)
+-- GREATEST ile yazılır: sırası karışık gelen eski okumalar geri almaz.
+-- NULL: henüz okuma gelmedi. GET /v1/devices?stale_for= bu sütuna göre filtreler.
+ALTER TABLE devices ADD COLUMN IF NOT EXISTS last_seen TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,24 @@
+-- Komut denetim kaydı (GET /v1/devices/{id}/commands)
+-- Kabul edilen her komut kimin verdiğiyle birlikte eklenir; cihazın yanıtları
+-- (POST /v1/devices/{id}/commands/ack) transitions'a eklenir ve status'u
+-- günceller. Cihaz silinse de korunur; MAX_READING_AGE_DAYS'ten eski satırları
+-- background::prune_command_audit siler.
+CREATE TABLE IF NOT EXISTS command_audit (
+    correlation_id UUID PRIMARY KEY,
+    device_id UUID NOT NULL,
+    command_type TEXT NOT NULL,
+    command_name TEXT NOT NULL,
+    parameters JSONB,
+    issuer TEXT NOT NULL,
+    issued_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('dispatched', 'executed', 'failed')),
+    -- [{"status": "dispatched", "at": "..."}, {"status": "failed", "at": "...", "message": "..."}]
+    transitions JSONB NOT NULL DEFAULT '[]'
+);
+
+CREATE INDEX IF NOT EXISTS command_audit_device_issued_idx
+    ON command_audit (device_id, issued_at DESC);
+
+-- Retention silmesi
+CREATE INDEX IF NOT EXISTS command_audit_issued_idx
+    ON command_audit (issued_at);
@@ -0,0 +1,34 @@
+-- Komut denetim kaydı device_commands'a taşınır: tek tablo hem
+-- GET /v1/devices/{id}/commands/history hem GET /v1/devices/{id}/commands'a
+-- hizmet eder. Satırlar cihaz silinince korunur;
+-- COMMAND_AUDIT_RETENTION_DAYS'ten eskileri background::prune_command_audit siler.
+ALTER TABLE device_commands
+    ADD COLUMN IF NOT EXISTS issuer TEXT NOT NULL DEFAULT 'anonymous',
+    -- [{"status": "dispatched", "at": "..."}, {"status": "failed", "at": "...", "message": "..."}]
+    ADD COLUMN IF NOT EXISTS transitions JSONB NOT NULL DEFAULT '[]';
+
+-- Mevcut komutların geçişleri ack kolonlarından türetilir
+UPDATE device_commands SET transitions =
+    jsonb_build_array(jsonb_build_object('status', 'dispatched', 'at', dispatched_at))
+    || CASE
+        WHEN ack_status IS NULL THEN '[]'::jsonb
+        ELSE jsonb_build_array(jsonb_strip_nulls(jsonb_build_object('status', ack_status, 'at', acked_at, 'message', ack_message)))
+    END
+WHERE transitions = '[]';
+
+-- Denetim kayıtları (cihazı silinmiş olanlar dahil) taşınır; son geçiş ack kolonlarına yazılır
+INSERT INTO device_commands
+    (correlation_id, device_id, command_type, command_name, parameters, dispatched_at, issuer, transitions, ack_status, ack_message, acked_at)
+SELECT
+    correlation_id, device_id, command_type, command_name, parameters, issued_at, issuer, transitions,
+    NULLIF(status, 'dispatched'),
+    CASE WHEN status <> 'dispatched' THEN transitions -> -1 ->> 'message' END,
+    CASE WHEN status <> 'dispatched' THEN (transitions -> -1 ->> 'at')::timestamptz END
+FROM command_audit
+ON CONFLICT (correlation_id) DO UPDATE SET issuer = EXCLUDED.issuer, transitions = EXCLUDED.transitions;
+
+DROP TABLE command_audit;
+
+-- Retention silmesi
+CREATE INDEX IF NOT EXISTS device_commands_dispatched_idx
+    ON device_commands (dispatched_at);
@@ -0,0 +1,5 @@
+-- Alarm onayı (POST /v1/alerts/events/{id}/ack)
+-- NULL: onaylanmamış, yani aktif. GET /v1/alerts/events?active=true bu sütuna göre filtreler.
+ALTER TABLE sensor_alerts ADD COLUMN IF NOT EXISTS acknowledged_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_sensor_alerts_active ON sensor_alerts (triggered_at DESC) WHERE acknowledged_at IS NULL;
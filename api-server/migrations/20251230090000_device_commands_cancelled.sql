@@ -0,0 +1,5 @@
+-- Cihaz kaldırılınca (DELETE /v1/devices/{id}) yanıtı beklenen komutlar
+-- 'cancelled' olarak kapatılır; denetim kaydı korunur.
+ALTER TABLE device_commands DROP CONSTRAINT IF EXISTS device_commands_ack_status_check;
+ALTER TABLE device_commands ADD CONSTRAINT device_commands_ack_status_check
+    CHECK (ack_status IN ('executed', 'failed', 'cancelled'));
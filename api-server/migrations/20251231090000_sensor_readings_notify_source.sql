@@ -0,0 +1,28 @@
+-- API server'ın kendi yazdığı okumalar application_name yerine oturum ayarıyla
+-- (rustyflow.source = 'api', bkz. db::connect_pool) tanınır; operatörün
+-- DATABASE_URL'de verdiği application_name korunur.
+CREATE OR REPLACE FUNCTION rustyflow_notify_reading() RETURNS trigger AS $$
+DECLARE
+    payload TEXT;
+BEGIN
+    IF current_setting('rustyflow.source', true) = 'api' THEN
+        RETURN NEW;
+    END IF;
+    payload := json_build_object(
+        'id', NEW.id,
+        'device_id', NEW.device_id,
+        'sensor_type', NEW.sensor_type,
+        'value', NEW.value,
+        'unit', NEW.unit,
+        'timestamp', NEW.timestamp,
+        'metadata', NEW.metadata,
+        'received_at', NEW.received_at,
+        'gateway_received_at', NEW.gateway_received_at
+    )::text;
+    IF octet_length(payload) >= 8000 THEN
+        payload := json_build_object('id', NEW.id)::text;
+    END IF;
+    PERFORM pg_notify('rustyflow_readings', payload);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
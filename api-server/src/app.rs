@@ -0,0 +1,653 @@
+//! HTTP Router
+//!
+//! `serve` ve testler aynı router'ı kullanır: route'lar, rol yetkilendirmesi,
+//! zaman aşımı, body sınırları, gecikme metrikleri, CORS, sıkıştırma ve X-Request-Id.
+//! Ayarlar `AppState.cfg`'den router kurulurken bir kez okunur;
+//! `/v1/admin/reload-config` bunları değiştirmez (yeniden başlatma gerekir).
+
+use std::time::Duration;
+
+use axum::{Router, routing::{get, post, put, delete, patch}};
+
+use crate::auth::{self, Role};
+use crate::state::AppState;
+use crate::{compression, limits, metrics, msgpack, request_id, routes};
+
+/// Tüm endpoint'leri ve middleware'leri kur
+///
+/// Toplu ingest rate limit'i kaynak IP'yi `ConnectInfo`'dan okur; router
+/// `into_make_service_with_connect_info` ile sunulmalıdır (testlerde
+/// `MockConnectInfo`).
+pub fn build_router(app_state: AppState) -> Router {
+    // Router kurulurken config'e yazan yok; kilit hemen alınır
+    let (compression_mode, compression_min_size, request_timeout_secs, slow_request_ms, max_json_body_bytes, max_upload_bytes) = {
+        let cfg = app_state.cfg.try_read().expect("config is not locked while building the router");
+        (
+            cfg.compression_mode(),
+            cfg.compression_min_size,
+            cfg.request_timeout_secs,
+            cfg.slow_request_ms,
+            cfg.max_json_body_bytes,
+            cfg.max_upload_bytes,
+        )
+    };
+    let http_metrics = app_state.http_metrics.clone();
+
+    // CORS layer ekle (web dashboard için)
+    let cors = tower_http::cors::CorsLayer::new()
+        .allow_origin(tower_http::cors::Any)
+        .allow_methods(tower_http::cors::Any)
+        .allow_headers(tower_http::cors::Any);
+    
+    // Ingestion endpoint'leri: gateway'ler gzip'li body gönderebilir
+    // JSON veya MessagePack (`Content-Type: application/msgpack`)
+    // AUTH_ENABLED: device rolü
+    let ingest = compression::with_request_decompression(
+        Router::new()
+            .route("/api/sensors", post(routes::sensors::add_sensor_data))
+            .route("/v1/sensors/ingest", post(routes::sensors::ingest_sensor_batch))
+            .route("/v1/devices/{id}/shadow/reported", post(routes::devices::update_reported))
+            .route("/api/devices/{id}/errors", post(routes::device_errors::report_device_error))
+            .route("/v1/devices/{id}/commands/ack", post(routes::devices::ack_command)),
+        compression_mode,
+    );
+    let ingest = auth::require_role(ingest, app_state.clone(), Role::Device);
+
+    // Streaming export ve SSE canlı akışı: uzun sürer, zaman aşımı uygulanmaz
+    let export = auth::require_role(
+        Router::new()
+            .route("/v1/sensors/{device_id}/{sensor_type}/export.influx", get(routes::sensors::export_influx_line_protocol))
+            .route("/api/sensors/stream", get(routes::sensors::stream_sensors)),  // ?device_id=
+        app_state.clone(),
+        Role::Viewer,
+    );
+
+    // Media içerik upload'u: kendi body sınırı (MAX_UPLOAD_BYTES), zaman aşımı yok
+    // AUTH_ENABLED: device rolü (kamera fotoğrafları) veya admin
+    let upload = auth::require_role(
+        Router::new()
+            .route("/v1/media/upload", post(routes::media::upload_media)),
+        app_state.clone(),
+        Role::Device,
+    );
+    let upload = limits::with_body_limit(upload.with_state(app_state.clone()), max_upload_bytes);
+
+    // Sensör listesi ve geçmişi: `Accept: application/msgpack` ile MessagePack
+    let negotiated = msgpack::with_msgpack_responses(
+        Router::new()
+            .route("/api/sensors", get(routes::sensors::list_sensors))  // POST: ingest
+            .route("/v1/sensors/{device_id}/{sensor_type}/history", get(routes::sensors::sensor_history)),
+    );
+
+    // Okuma endpoint'leri (AUTH_ENABLED: viewer rolü)
+    let read = Router::new()
+        .merge(negotiated)
+        .route("/v1/config",  get(routes::sys::config))       // Yapılandırma
+        .route("/v1/admin/features", get(routes::admin::get_features))  // Özellik bayrakları
+        .route("/metrics",    get(metrics::metrics_handler))  // Prometheus (FEATURE_METRICS_ENABLED)
+        // Media (v1 API)
+        .route("/v1/media",         get(routes::media::list_media))
+        .route("/v1/media/{id}",    get(routes::media::get_media))
+        // Sensör endpoint'leri (Redis kullanır)
+        .route("/api/sensors/realtime", get(routes::sensors::realtime_sensors))  // Long-poll
+        .route("/api/sensors/events", get(routes::motion::sensor_events))  // Motion olayları
+        .route("/api/summary", get(routes::summary::summary))  // Dashboard başlığı
+        // Sensör geçmişi (PostgreSQL kullanır)
+        .route("/v1/sensors/{device_id}/{sensor_type}/summaries",     get(routes::sensors::sensor_summaries))
+        .route("/v1/sensors/{device_id}/{sensor_type}/stats",         get(routes::sensors::sensor_stats))
+        .route("/v1/sensors/heatmap", get(routes::sensors::get_sensor_heatmap))  // ?sensor_types=a,b
+        // Cihaz gölgesi
+        .route("/v1/devices",                      get(routes::devices::list_devices))  // ?kind=gateway
+        .route("/v1/devices/{id}/timeline",        get(routes::devices::get_device_timeline))
+        .route("/v1/devices/{id}/commands/history", get(routes::devices::command_history))  // ?limit=
+        .route("/v1/devices/{id}/commands",        get(routes::command_audit::list_device_commands))  // ?from=&to=&status=&limit=&offset=
+        .route("/api/devices/{id}/errors",         get(routes::device_errors::list_device_errors))  // ?since=&level=
+        .route("/v1/alerts/events",                get(routes::alerts::list_alert_events))  // ?active=true (onaylanmamış)
+        // Sensör grupları (cihazlar arası)
+        .route("/v1/sensor-groups",                       get(routes::sensor_groups::list_sensor_groups))
+        .route("/v1/sensor-groups/{id}",                  get(routes::sensor_groups::get_sensor_group))
+        .route("/v1/sensor-groups/{id}/readings/latest",  get(routes::sensor_groups::latest_sensor_group_readings));
+    // Tokio görev metrikleri (`tracing` feature'ı, FEATURE_METRICS_ENABLED)
+    #[cfg(feature = "tracing")]
+    let read = read.route("/metrics/runtime", get(crate::runtime_metrics::runtime_metrics_handler));
+    let read = auth::require_role(read, app_state.clone(), Role::Viewer);
+
+    // Yönetim ve değişiklik endpoint'leri (AUTH_ENABLED: admin rolü)
+    let manage = Router::new()
+        .route("/v1/admin/reload-config", post(routes::admin::reload_config))  // ADMIN_TOKEN
+        .route("/v1/admin/features",      patch(routes::admin::update_features))  // ADMIN_TOKEN
+        // Media CRUD
+        .route("/v1/media",         post(routes::media::create_media))
+        .route("/v1/media/bulk",        post(routes::media::create_media_bulk))  // en fazla 1000, hepsi veya hiçbiri
+        .route("/v1/media/bulk-delete", post(routes::media::delete_media_bulk))  // ID başına deleted / not_found
+        .route("/v1/media/{id}",    put(routes::media::update_media).delete(routes::media::delete_media))
+        // Cihaz komut endpoint'leri (MQTT kullanır)
+        .route("/v1/devices/{id}/commands",     post(routes::devices::send_command))  // Denetim kaydına yazılır
+        .route("/v1/devices/{id}/commands/led", post(routes::devices::send_led_command))
+        .route("/v1/devices/{id}/shadow/desired",  post(routes::devices::update_desired))
+        .route("/v1/devices/{id}/data",            delete(routes::devices::delete_device_data))  // GDPR silme
+        .route("/v1/devices/{id}",                 delete(routes::devices::delete_device))  // ?purge_history=
+        // Cihaza özel ingest token'ları (düz token sadece oluştururken döner)
+        .route("/v1/devices/{id}/tokens",            post(routes::device_tokens::create_device_token).get(routes::device_tokens::list_device_tokens))
+        .route("/v1/devices/{id}/tokens/{token_id}", delete(routes::device_tokens::revoke_device_token))
+        // Sensör grubu yönetimi (üye listesi PUT ile tamamen değişir)
+        .route("/v1/sensor-groups",              post(routes::sensor_groups::create_sensor_group))
+        .route("/v1/sensor-groups/{id}",         delete(routes::sensor_groups::delete_sensor_group))
+        .route("/v1/sensor-groups/{id}/members", put(routes::sensor_groups::set_sensor_group_members))
+        // Geçmiş okumalarla alarm eşiği back-test'i
+        .route("/v1/sensors/replay", post(routes::sensors::replay_sensor_readings))
+        // Sentetik test okumaları (FEATURE_SIMULATION_ENABLED)
+        .route("/v1/sensors/{device_id}/simulate", post(routes::simulate::simulate_sensor_readings))
+        // Webhook endpoint'leri (alarm bildirimleri)
+        .route("/v1/webhooks",      post(routes::webhooks::create_webhook).get(routes::webhooks::list_webhooks))
+        .route("/v1/webhooks/{id}", delete(routes::webhooks::delete_webhook))
+        // Alarm onayı (dashboard alarm paneli)
+        .route("/v1/alerts/events/{id}/ack", post(routes::alerts::acknowledge_alert_event));
+    let manage = auth::require_role(manage, app_state.clone(), Role::Admin);
+
+    // Cihaz kaydı (edge agent ilk açılışta çağırır; AUTH_ENABLED: PROVISIONING_TOKEN veya admin)
+    let provision = auth::require_role(
+        Router::new().route("/v1/provision", post(routes::provision::provision_device)),
+        app_state.clone(),
+        Role::Provisioner,
+    );
+
+    // Cihazın kendi gölgesi: dashboard (viewer) ve cihazın kendisi (device, başlangıç senkronizasyonu)
+    let shadow = auth::require_any_role(
+        Router::new().route("/v1/devices/{id}/shadow", get(routes::devices::get_shadow)),
+        app_state.clone(),
+        &[Role::Viewer, Role::Device],
+    );
+
+    // Axum router ile tüm endpoint'leri tanımla
+    let app = Router::new()
+        // Sistem ve sağlık kontrol endpoint'leri (her zaman açık)
+        .route("/",           get(routes::health::root))      // Status check
+        .route("/health",     get(routes::health::health))    // Sağlık durumu
+        .route("/ready",      get(routes::health::ready))     // Hazır mı?
+        // Database sağlık kontrol
+        .route("/db/health", get(routes::db::health))
+        // Rol token'ı (sadece admin'e)
+        .route("/v1/auth/token", post(routes::auth::issue_token))
+        .merge(read)
+        .merge(manage)
+        .merge(provision)
+        .merge(shadow)
+        // Sıkıştırılmış body kabul eden ingestion route'ları
+        .merge(ingest);
+    // İstek zaman aşımı (REQUEST_TIMEOUT_SECS); export bundan sonra eklenir
+    let app = limits::with_request_timeout(app, Duration::from_secs(request_timeout_secs))
+        .merge(export)
+        // Shared state'i TÜM handler'lara inject et (media + sensors)
+        .with_state(app_state);
+    // Body sınırı (MAX_JSON_BODY_BYTES, upload hariç); 408/413 problem+json olarak döner
+    let app = limits::with_body_limit(app, max_json_body_bytes).merge(upload);
+    // Route şablonu başına gecikme/status metriği, SLOW_REQUEST_MS üstü WARN
+    let app = metrics::with_http_metrics(app, http_metrics, Duration::from_millis(slow_request_ms));
+    let app = limits::with_problem_responses(app)
+        // CORS layer'ı ekle (413'ler de dashboard'a CORS header'larıyla ulaşsın)
+        .layer(cors);
+    // Response sıkıştırma (COMPRESSION, COMPRESSION_MIN_SIZE)
+    let app = compression::with_compression(app, compression_mode, compression_min_size);
+    // Request ID: gelen X-Request-Id'yi koru/üret, span'e ekle, response'a yaz
+    request_id::with_request_id(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::connect_info::MockConnectInfo;
+    use axum::http::{header, Method, Request, StatusCode};
+    use serde_json::{json, Value};
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    /// In-memory (DB, Redis ve MQTT yok) state ile router
+    fn router(state: AppState) -> Router {
+        build_router(state).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+    }
+
+    /// İsteği gönder; JSON olmayan body `Value::Null` döner
+    async fn send(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let request = Request::builder().method(method).uri(uri);
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        };
+        let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_health_and_ready() {
+        let app = router(AppState::for_tests());
+
+        let (status, body) = send(&app, Method::GET, "/health", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(send(&app, Method::GET, "/", None).await.0, StatusCode::OK);
+
+        // READINESS_REQUIRED=db ama DB yok
+        assert_eq!(send(&app, Method::GET, "/ready", None).await.0, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(send(&app, Method::GET, "/db/health", None).await.0, StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[tokio::test]
+    async fn test_media_crud_lifecycle() {
+        let app = router(AppState::for_tests());
+        let new = json!({"name": "cat.png", "path": "/media/cat.png", "mime_type": "image/png", "size_bytes": 1024});
+
+        let (status, created) = send(&app, Method::POST, "/v1/media", Some(new.clone())).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = created["id"].as_str().unwrap().to_string();
+        let uri = format!("/v1/media/{id}");
+
+        let (status, fetched) = send(&app, Method::GET, &uri, None).await;
+        assert_eq!((status, &fetched["name"]), (StatusCode::OK, &json!("cat.png")));
+        let (status, listed) = send(&app, Method::GET, "/v1/media?mime_type=image/png", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(listed.to_string().contains(&id));
+
+        let (status, updated) = send(&app, Method::PUT, &uri, Some(json!({"name": "dog.png"}))).await;
+        assert_eq!((status, &updated["name"]), (StatusCode::OK, &json!("dog.png")));
+        // Geçersiz MIME type'lar 400, kayıt değişmez
+        let bad = json!({"name": "x", "path": "/x", "mime_type": "foobar", "size_bytes": 1});
+        assert_eq!(send(&app, Method::POST, "/v1/media", Some(bad.clone())).await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(send(&app, Method::PUT, &uri, Some(json!({"mime_type": "image/*"}))).await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(send(&app, Method::GET, &uri, None).await.1["mime_type"], "image/png");
+        // Eski sürümle güncelleme reddedilir
+        let stale = json!({"name": "bird.png", "expected_version": 1});
+        assert_eq!(send(&app, Method::PUT, &uri, Some(stale)).await.0, StatusCode::CONFLICT);
+
+        assert_eq!(send(&app, Method::DELETE, &uri, None).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(send(&app, Method::GET, &uri, None).await.0, StatusCode::NOT_FOUND);
+        assert_eq!(send(&app, Method::DELETE, &uri, None).await.0, StatusCode::NOT_FOUND);
+
+        // Toplu oluşturma: geçersiz eleman tüm batch'i problem+json ile reddeder
+        let (status, problem) = send(&app, Method::POST, "/v1/media/bulk", Some(json!([new, bad]))).await;
+        assert_eq!((status, &problem["errors"][0]["index"]), (StatusCode::BAD_REQUEST, &json!(1)));
+        let (status, created) = send(&app, Method::POST, "/v1/media/bulk", Some(json!([new, new]))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let first = created["ids"][0].clone();
+        let (status, outcomes) = send(&app, Method::POST, "/v1/media/bulk-delete", Some(json!([first, Uuid::new_v4()]))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(outcomes[0], json!({"id": first, "outcome": "deleted"}));
+        assert_eq!(outcomes[1]["outcome"], "not_found");
+    }
+
+    #[tokio::test]
+    async fn test_sensor_ingest_and_list_without_backends() {
+        let state = AppState::for_tests();
+        let app = router(state.clone());
+        let reading = json!({"device_id": "dev-1", "sensor_type": "temperature", "value": 21.5, "unit": "°C", "timestamp": chrono::Utc::now()});
+
+        // Ne Redis ne PostgreSQL: okuma saklanamaz
+        assert_eq!(send(&app, Method::POST, "/api/sensors", Some(reading.clone())).await.0, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(send(&app, Method::POST, "/v1/sensors/ingest", Some(json!([reading]))).await.0, StatusCode::SERVICE_UNAVAILABLE);
+        let (status, latest) = send(&app, Method::GET, "/api/sensors", None).await;
+        assert_eq!((status, latest), (StatusCode::OK, json!([])));
+        assert_eq!(send(&app, Method::GET, "/api/sensors?unit=fahrenheit", None).await.0, StatusCode::OK);
+        assert_eq!(send(&app, Method::GET, "/api/sensors?unit=furlongs", None).await.0, StatusCode::BAD_REQUEST);
+
+        // Canlı akış: yayınlanan okuma long-poll'da beklemeden döner
+        state.sensor_feed.publish(serde_json::from_value(json!({"device_id": "dev-1", "sensor_type": "temperature", "value": 21.5, "unit": "°C", "timestamp": "2024-01-20T10:30:00Z"})).unwrap());
+        let (status, batch) = send(&app, Method::GET, "/api/sensors/realtime?since=0", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(batch["events"][0]["device_id"], "dev-1");
+        assert_eq!(batch["next_sequence"], 1);
+        // SSE akışı: body sonsuz, sadece başlıklar kontrol edilir
+        let response = app.clone().oneshot(Request::get("/api/sensors/stream?device_id=dev-1").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+
+        let (status, summary) = send(&app, Method::GET, "/api/summary", None).await;
+        assert_eq!((status, &summary["devices_total"]), (StatusCode::OK, &Value::Null));
+    }
+
+    #[tokio::test]
+    async fn test_every_route_is_mounted() {
+        let app = router(AppState::for_tests());
+        let id = Uuid::new_v4();
+        let history = "/v1/sensors/dev-1/temperature";
+        let replay = json!({"device_id": "dev-1", "sensor_type": "temperature", "from": "2024-01-20T00:00:00Z", "to": "2024-01-21T00:00:00Z", "threshold": {"max": 30.0}});
+        let webhook = json!({"url": "https://example.com/hook", "secret": "s3cret", "event_types": ["sensor.alert"]});
+        let simulate = json!({"sensor_type": "temperature", "count": 10, "start_time": "2024-01-20T00:00:00Z", "interval_secs": 60, "pattern": {"constant": 21.5}});
+        let heatmap = "device_id=dev-1&sensor_types=temperature,humidity&from=2024-01-20T00:00:00Z&to=2024-01-21T00:00:00Z&bucket_minutes=60";
+        let provision = json!({"serial_number": "10000000abcdef01", "firmware_version": "0.1.0", "model": "rpi"});
+
+        let cases: Vec<(Method, String, Option<Value>, StatusCode)> = vec![
+            (Method::GET, "/v1/config".into(), None, StatusCode::OK),
+            (Method::POST, "/v1/auth/token".into(), Some(json!({"requested_role": "viewer"})), StatusCode::UNAUTHORIZED),
+            (Method::POST, "/v1/admin/reload-config".into(), None, StatusCode::FORBIDDEN),
+            (Method::GET, "/v1/admin/features".into(), None, StatusCode::OK),
+            (Method::GET, "/metrics".into(), None, StatusCode::OK),
+            (Method::PATCH, "/v1/admin/features".into(), Some(json!({})), StatusCode::FORBIDDEN),
+            (Method::GET, "/api/sensors/events?device_id=dev-1".into(), None, StatusCode::SERVICE_UNAVAILABLE),
+            // Geçmiş sorguları PostgreSQL ister
+            (Method::GET, format!("{history}/history"), None, StatusCode::NOT_IMPLEMENTED),
+            (Method::GET, format!("{history}/summaries"), None, StatusCode::NOT_IMPLEMENTED),
+            (Method::GET, format!("{history}/stats"), None, StatusCode::NOT_IMPLEMENTED),
+            (Method::GET, format!("{history}/export.influx"), None, StatusCode::NOT_IMPLEMENTED),
+            (Method::GET, format!("/v1/sensors/heatmap?{heatmap}"), None, StatusCode::NOT_IMPLEMENTED),
+            (Method::POST, "/v1/sensors/replay".into(), Some(replay), StatusCode::NOT_IMPLEMENTED),
+            (Method::POST, "/v1/sensors/dev-1/simulate".into(), Some(simulate), StatusCode::NOT_IMPLEMENTED),
+            (Method::GET, format!("/v1/devices/{id}/timeline"), None, StatusCode::NOT_IMPLEMENTED),
+            (Method::GET, format!("/api/devices/{id}/errors?level=warning"), None, StatusCode::NOT_IMPLEMENTED),
+            (Method::POST, format!("/api/devices/{id}/errors"), Some(json!({"level": "error", "message": "i2c timeout"})), StatusCode::NOT_IMPLEMENTED),
+            (Method::GET, "/v1/alerts/events?active=true".into(), None, StatusCode::NOT_IMPLEMENTED),
+            (Method::POST, format!("/v1/alerts/events/{id}/ack"), None, StatusCode::NOT_IMPLEMENTED),
+            (Method::GET, format!("/v1/devices/{id}/commands/history?limit=20"), None, StatusCode::OK),
+            // Komut denetim kaydı in-memory çalışır (bilinmeyen komutun yanıtı 404)
+            (
+                Method::POST,
+                format!("/v1/devices/{id}/commands/ack"),
+                Some(json!({"device_id": id, "correlation_id": id, "success": true, "timestamp": "2024-01-20T10:30:01Z"})),
+                StatusCode::NOT_FOUND,
+            ),
+            (Method::GET, format!("/v1/devices/{id}/commands?status=failed&limit=10"), None, StatusCode::OK),
+            // Cihazlar ve gölgeler
+            (Method::GET, format!("/v1/devices/{id}/shadow"), None, StatusCode::NOT_FOUND),
+            (Method::POST, format!("/v1/devices/{id}/shadow/desired"), Some(json!({"led": "on"})), StatusCode::OK),
+            (Method::POST, format!("/v1/devices/{id}/shadow/reported"), Some(json!({"led": "on"})), StatusCode::OK),
+            (Method::GET, "/v1/devices?kind=edge".into(), None, StatusCode::OK),
+            (Method::POST, format!("/v1/devices/{id}/commands"), Some(json!({"command_type": "control", "command_name": "relay"})), StatusCode::SERVICE_UNAVAILABLE),
+            (Method::POST, format!("/v1/devices/{id}/commands/led"), Some(json!({"led_id": "led_01", "state": "on"})), StatusCode::SERVICE_UNAVAILABLE),
+            (Method::DELETE, format!("/v1/devices/{id}/data"), Some(json!({"confirm": true})), StatusCode::OK),
+            (Method::DELETE, format!("/v1/devices/{id}"), None, StatusCode::OK),
+            (Method::POST, "/v1/provision".into(), Some(provision), StatusCode::OK),
+            (Method::POST, format!("/v1/devices/{id}/tokens"), Some(json!({"label": "pi-01"})), StatusCode::CREATED),
+            (Method::GET, format!("/v1/devices/{id}/tokens"), None, StatusCode::OK),
+            (Method::DELETE, format!("/v1/devices/{id}/tokens/{id}"), None, StatusCode::NOT_FOUND),
+            (Method::POST, "/v1/sensor-groups".into(), Some(json!({"name": "bedroom"})), StatusCode::CREATED),
+            (Method::GET, "/v1/sensor-groups".into(), None, StatusCode::OK),
+            (Method::GET, format!("/v1/sensor-groups/{id}"), None, StatusCode::NOT_FOUND),
+            (Method::DELETE, format!("/v1/sensor-groups/{id}"), None, StatusCode::NOT_FOUND),
+            (Method::PUT, format!("/v1/sensor-groups/{id}/members"), Some(json!([id])), StatusCode::NOT_FOUND),
+            (Method::GET, format!("/v1/sensor-groups/{id}/readings/latest"), None, StatusCode::NOT_FOUND),
+            // Webhook'lar
+            (Method::POST, "/v1/webhooks".into(), Some(webhook), StatusCode::CREATED),
+            (Method::GET, "/v1/webhooks".into(), None, StatusCode::OK),
+            (Method::DELETE, format!("/v1/webhooks/{id}"), None, StatusCode::NOT_FOUND),
+        ];
+        for (method, uri, body, expected) in cases {
+            let (status, response) = send(&app, method.clone(), &uri, body).await;
+            assert_eq!(status, expected, "{method} {uri}: {response}");
+        }
+
+        let upload = Request::post("/v1/media/upload?name=notes.txt").body(Body::from("hello")).unwrap();
+        assert_eq!(app.clone().oneshot(upload).await.unwrap().status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_feature_flag_toggle_takes_effect_on_next_request() {
+        let state = AppState::for_tests();
+        state.cfg.write().await.admin_token = Some("secret".into());
+        let app = router(state);
+        let webhook = json!({"url": "https://example.com/hook", "secret": "s3cret", "event_types": ["sensor.alert"]});
+        let erase = format!("/v1/devices/{}/data", Uuid::new_v4());
+        let toggle = |patch: Value| {
+            Request::patch("/v1/admin/features")
+                .header(header::AUTHORIZATION, "Bearer secret")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(patch.to_string()))
+                .unwrap()
+        };
+
+        assert_eq!(send(&app, Method::POST, "/v1/webhooks", Some(webhook.clone())).await.0, StatusCode::CREATED);
+
+        let response = app.clone().oneshot(toggle(json!({"webhook_delivery_enabled": false, "gdpr_deletion_enabled": false}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let (_, flags) = send(&app, Method::GET, "/v1/admin/features", None).await;
+        assert_eq!(flags, json!({"alerts_enabled": true, "webhook_delivery_enabled": false, "gdpr_deletion_enabled": false, "metrics_enabled": true, "simulation_enabled": false}));
+        assert_eq!(send(&app, Method::POST, "/v1/webhooks", Some(webhook.clone())).await.0, StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(send(&app, Method::DELETE, &erase, Some(json!({"confirm": true}))).await.0, StatusCode::NOT_IMPLEMENTED);
+        // Kayıtlı webhook'lar listelenebilir kalır
+        assert_eq!(send(&app, Method::GET, "/v1/webhooks", None).await.0, StatusCode::OK);
+
+        let response = app.clone().oneshot(toggle(json!({"webhook_delivery_enabled": true}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(send(&app, Method::POST, "/v1/webhooks", Some(webhook)).await.0, StatusCode::CREATED);
+        assert_eq!(send(&app, Method::DELETE, &erase, Some(json!({"confirm": true}))).await.0, StatusCode::NOT_IMPLEMENTED);
+
+        // Bilinmeyen bayrak ve yanlış token
+        let response = app.clone().oneshot(toggle(json!({"dark_mode": true}))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let wrong_token = Request::patch("/v1/admin/features")
+            .header(header::AUTHORIZATION, "Bearer wrong")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(wrong_token).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_use_route_templates() {
+        let state = AppState::for_tests();
+        let app = router(state.clone());
+        let id = Uuid::new_v4();
+
+        assert_eq!(send(&app, Method::GET, &format!("/v1/media/{id}"), None).await.0, StatusCode::NOT_FOUND);
+        // Body sınırı ve rol katmanlarının altındaki route'lar da şablonla etiketlenir
+        assert_eq!(send(&app, Method::GET, &format!("/v1/devices/{id}/timeline"), None).await.0, StatusCode::NOT_IMPLEMENTED);
+
+        let response = app.clone().oneshot(Request::get("/metrics").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(text.contains(r#"http_request_duration_seconds_count{method="GET",route="/v1/media/{id}",status="404"} 1"#));
+        assert!(text.contains(r#"route="/v1/devices/{id}/timeline",status="501""#));
+        assert!(!text.contains(&id.to_string()));
+
+        state.features.write().await.metrics_enabled = false;
+        assert_eq!(send(&app, Method::GET, "/metrics", None).await.0, StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_runtime_metrics_endpoint() {
+        let state = AppState::for_tests();
+        let app = router(state.clone());
+
+        let (status, body) = send(&app, Method::GET, "/metrics/runtime", None).await;
+        assert_eq!(status, StatusCode::OK);
+        for field in ["mean_poll_duration_us", "slow_poll_count", "task_count", "dropped_count"] {
+            assert!(body[field].is_u64(), "{field} missing: {body}");
+        }
+
+        state.features.write().await.metrics_enabled = false;
+        assert_eq!(send(&app, Method::GET, "/metrics/runtime", None).await.0, StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[tokio::test]
+    async fn test_error_paths() {
+        let state = AppState::for_tests();
+        let app = router(state.clone());
+
+        assert_eq!(send(&app, Method::GET, "/v1/unknown", None).await.0, StatusCode::NOT_FOUND);
+        assert_eq!(send(&app, Method::DELETE, "/health", None).await.0, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(send(&app, Method::GET, "/v1/media/not-a-uuid", None).await.0, StatusCode::BAD_REQUEST);
+
+        // Bozuk JSON ve eksik alan
+        let request = Request::post("/v1/media")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("{not json"))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::BAD_REQUEST);
+        assert_eq!(send(&app, Method::POST, "/v1/media", Some(json!({"name": "x"}))).await.0, StatusCode::BAD_REQUEST);
+        // Heatmap parametreleri DB'ye gitmeden doğrulanır
+        let range = "from=2024-01-20T00:00:00Z&to=2024-01-21T00:00:00Z";
+        for query in [
+            format!("device_id=dev-1&sensor_types=temperature&{range}&bucket_minutes=1"),
+            format!("device_id=dev-1&sensor_types=&{range}&bucket_minutes=60"),
+            format!("device_id=dev-1&sensor_types=temperature&{range}&bucket_minutes=0"),
+            format!("device_id=dev-1&sensor_types=temperature&{range}"),
+        ] {
+            assert_eq!(send(&app, Method::GET, &format!("/v1/sensors/heatmap?{query}"), None).await.0, StatusCode::BAD_REQUEST, "{query}");
+        }
+        let unconfirmed = send(&app, Method::DELETE, &format!("/v1/devices/{}/data", Uuid::new_v4()), Some(json!({}))).await;
+        assert_eq!(unconfirmed.0, StatusCode::BAD_REQUEST);
+
+        // MAX_JSON_BODY_BYTES (test state'inde 1 MB) aşılırsa 413 problem+json
+        let oversized = json!({"name": "x".repeat(2 * 1024 * 1024), "path": "/", "mime_type": "text/plain", "size_bytes": 1});
+        let (status, problem) = send(&app, Method::POST, "/v1/media", Some(oversized)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(problem["status"], 413);
+
+        // AUTH_ENABLED: token'sız istekler reddedilir, sağlık kontrolü açık kalır
+        state.cfg.write().await.auth_enabled = true;
+        let app = router(state);
+        assert_eq!(send(&app, Method::GET, "/v1/media", None).await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(send(&app, Method::POST, "/api/sensors", Some(json!({}))).await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(send(&app, Method::GET, "/health", None).await.0, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_device_token_binds_ingest_to_device() {
+        let state = AppState::for_tests();
+        {
+            let mut cfg = state.cfg.write().await;
+            cfg.auth_enabled = true;
+            cfg.admin_token = Some("admin".into());
+        }
+        let app = router(state);
+        let call = |method: Method, uri: String, token: String, body: Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<Value>(&bytes).unwrap_or(Value::Null))
+            }
+        };
+
+        let (device, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let (status, issued) = call(Method::POST, format!("/v1/devices/{device}/tokens"), "admin".into(), json!({"label": "pi-01"})).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let token = issued["token"].as_str().unwrap().to_string();
+        assert!(issued.get("token_hash").is_none());
+
+        let reading = |id: Uuid| json!({"device_id": id, "sensor_type": "temperature", "value": 21.5, "unit": "°C"});
+        // Kendi cihazı: yetki geçer (backend olmadığı için 503/501)
+        let own = [
+            (Method::POST, "/api/sensors".to_string(), reading(device), StatusCode::SERVICE_UNAVAILABLE),
+            (Method::POST, "/v1/sensors/ingest".to_string(), json!([reading(device)]), StatusCode::SERVICE_UNAVAILABLE),
+            (Method::POST, format!("/v1/devices/{device}/shadow/reported"), json!({"led": "on"}), StatusCode::OK),
+            (Method::POST, format!("/api/devices/{device}/errors"), json!({"level": "error", "message": "x"}), StatusCode::NOT_IMPLEMENTED),
+        ];
+        // Başka cihaz adına: 403
+        let foreign = [
+            (Method::POST, "/api/sensors".to_string(), reading(other), StatusCode::FORBIDDEN),
+            (Method::POST, "/v1/sensors/ingest".to_string(), json!([reading(device), reading(other)]), StatusCode::FORBIDDEN),
+            (Method::POST, format!("/v1/devices/{other}/shadow/reported"), json!({"led": "on"}), StatusCode::FORBIDDEN),
+            (Method::POST, format!("/api/devices/{other}/errors"), json!({"level": "error", "message": "x"}), StatusCode::FORBIDDEN),
+            // Cihaz token'ı okuma/yönetim endpoint'lerine erişemez
+            (Method::POST, format!("/v1/devices/{device}/tokens"), json!({}), StatusCode::FORBIDDEN),
+        ];
+        for (method, uri, body, expected) in own.into_iter().chain(foreign) {
+            let (status, response) = call(method.clone(), uri.clone(), token.clone(), body).await;
+            assert_eq!(status, expected, "{method} {uri}: {response}");
+        }
+        let (_, tokens) = call(Method::GET, format!("/v1/devices/{device}/tokens"), "admin".into(), Value::Null).await;
+        assert!(tokens[0]["last_used_at"].is_string());
+
+        // İptal edilen token 401
+        let revoke = format!("/v1/devices/{device}/tokens/{}", issued["id"].as_str().unwrap());
+        assert_eq!(call(Method::DELETE, revoke, "admin".into(), Value::Null).await.0, StatusCode::NO_CONTENT);
+        let (status, _) = call(Method::POST, format!("/v1/devices/{device}/shadow/reported"), token, json!({"led": "on"})).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    async fn send_bytes(app: &Router, request: Request<Body>) -> (StatusCode, Option<String>, Vec<u8>) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let content_type = response.headers().get(header::CONTENT_TYPE).map(|ct| ct.to_str().unwrap().to_string());
+        (response.status(), content_type, axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_ingest_accepts_msgpack_and_rejects_malformed() {
+        use shared_types::MsgPack;
+
+        let app = router(AppState::for_tests());
+        let post = |uri: &str, body: Vec<u8>| {
+            Request::post(uri).header(header::CONTENT_TYPE, "application/msgpack").body(Body::from(body)).unwrap()
+        };
+
+        // Çözülen okuma depolamaya ulaşır (test state'inde depolama yok → 503)
+        let reading = json!({"device_id": "edge-agent-001", "sensor_type": "temperature", "value": 21.5, "unit": "°C", "metadata": null});
+        let (status, _, _) = send_bytes(&app, post("/api/sensors", reading.to_msgpack().unwrap())).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        for uri in ["/api/sensors", "/v1/sensors/ingest"] {
+            let (status, content_type, body) = send_bytes(&app, post(uri, vec![0xc1, 0x00])).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            assert_eq!(content_type.as_deref(), Some(crate::limits::PROBLEM_JSON));
+            let problem: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(problem["status"], 400);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_msgpack_round_trip_through_ingest_and_history() {
+        use crate::routes::sensors::SensorData;
+        use shared_types::MsgPack;
+
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        let db = sqlx::PgPool::connect(&url).await.unwrap();
+        let app = router(AppState::for_tests_with_db(db.clone()));
+        let device = format!("msgpack-{}", Uuid::new_v4());
+        let base = chrono::Utc::now() - chrono::Duration::hours(1);
+        let reading = |minutes: i64, value: f64| SensorData {
+            device_id: device.clone(),
+            sensor_type: "temperature".into(),
+            value,
+            unit: "°C".into(),
+            timestamp: (base + chrono::Duration::minutes(minutes)).to_rfc3339(),
+            metadata: Some(json!({"rssi": -61})),
+            received_at: None,
+            gateway_received_at: None,
+            stale: false,
+        };
+        let post = |uri: &str, body: Vec<u8>| {
+            Request::post(uri).header(header::CONTENT_TYPE, "application/msgpack").body(Body::from(body)).unwrap()
+        };
+
+        // İstek yönü: tekil okuma ve batch MessagePack olarak
+        let (status, _, _) = send_bytes(&app, post("/api/sensors", reading(0, 20.5).to_msgpack().unwrap())).await;
+        assert_eq!(status, StatusCode::OK);
+        let batch: Vec<SensorData> = (1..20).map(|i| reading(i, 20.5 + i as f64 / 10.0)).collect();
+        let (status, _, body) = send_bytes(&app, post("/v1/sensors/ingest", batch.to_msgpack().unwrap())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap()["accepted"], 19);
+
+        // Response yönü: aynı geçmiş JSON ve MessagePack olarak
+        let history = format!("/v1/sensors/{device}/temperature/history");
+        let get = |accept: &'static str| Request::get(&history).header(header::ACCEPT, accept).body(Body::empty()).unwrap();
+        let (_, json_type, json_body) = send_bytes(&app, get("application/json")).await;
+        let (status, msgpack_type, msgpack_body) = send_bytes(&app, get("application/msgpack")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json_type.as_deref(), Some("application/json"));
+        assert_eq!(msgpack_type.as_deref(), Some("application/msgpack"));
+
+        let from_json: Vec<SensorData> = serde_json::from_slice(&json_body).unwrap();
+        let from_msgpack = Vec::<SensorData>::from_msgpack(&msgpack_body).unwrap();
+        assert_eq!(from_json.len(), 20);
+        assert_eq!(from_msgpack, from_json);
+        assert!(msgpack_body.len() < json_body.len(), "msgpack {} >= json {}", msgpack_body.len(), json_body.len());
+
+        sqlx::query("DELETE FROM sensor_readings WHERE device_id = $1").bind(&device).execute(&db).await.unwrap();
+    }
+}
@@ -0,0 +1,530 @@
+//! Kimlik Doğrulama ve Rol Tabanlı Yetkilendirme (RBAC)
+//!
+//! Token'lar `JWT_SECRET` ile imzalanan HS256 JWT'lerdir ve bir `role`
+//! claim'i taşır:
+//! - `admin`: her endpoint (cihaz kaydı, webhook'lar, config reload...)
+//! - `viewer`: sadece okuma (`GET` endpoint'leri)
+//! - `device`: sadece okuma gönderimi (`POST /api/sensors` vb.), kendi
+//!   gölgesini okuma ve media yükleme
+//! - `provisioner`: sadece cihaz kaydı (`POST /v1/provision`)
+//!
+//! Kalıcı cihaz token'larını `POST /v1/provision` ve `POST /v1/devices/{id}/tokens`,
+//! JWT'leri `POST /v1/auth/token` (sadece admin'e) verir. `Authorization: Bearer <ADMIN_TOKEN>` her zaman
+//! `admin`, `Authorization: Bearer <PROVISIONING_TOKEN>` her zaman
+//! `provisioner` sayılır. `device` rolündeki token'lar — JWT'ler `sub` claim'iyle,
+//! kalıcı cihaz token'ları (`rfd_...`, `POST /v1/devices/{id}/tokens`) kendi
+//! kaydıyla — isteği token'ın cihazına bağlar (`DeviceBinding`).
+//! `api-server create-api-key` ile üretilen API anahtarları (`rfk_...`,
+//! hash'i `api_keys` tablosunda) `admin` sayılır.
+//! Çağıranın kimliği handler'lara `Issuer` olarak iletilir (komut denetim kaydı).
+//!
+//! Rol kontrolü `AUTH_ENABLED=true` ile açılır; kapalıyken `require_role`
+//! katmanları istekleri olduğu gibi geçirir (`/v1/admin/*` yine `ADMIN_TOKEN`
+//! ister).
+
+use std::fmt;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::cli::{hash_api_key, API_KEY_PREFIX};
+use crate::limits::Problem;
+use crate::routes::device_tokens::{self, DEVICE_TOKEN_PREFIX};
+use crate::state::AppState;
+
+/// Cihaz token'larının geçerlilik süresi
+pub const DEVICE_TOKEN_TTL: chrono::Duration = chrono::Duration::minutes(5);
+
+/// `admin`/`viewer` token'larının geçerlilik süresi
+pub const ACCESS_TOKEN_TTL: chrono::Duration = chrono::Duration::hours(1);
+
+/// Çağıranın rolü (JWT `role` claim'i)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    Viewer,
+    Device,
+    /// Edge agent'ların ilk açılıştaki kaydı (`PROVISIONING_TOKEN`)
+    Provisioner,
+}
+
+impl Role {
+    /// Bu rol `required` isteyen endpoint'e erişebilir mi?
+    ///
+    /// `admin` her yere erişir; diğer roller sadece kendi sınıflarına.
+    pub fn satisfies(self, required: Role) -> bool {
+        self == Role::Admin || self == required
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Viewer => "viewer",
+            Role::Device => "device",
+            Role::Provisioner => "provisioner",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// İsteğin bağlı olduğu cihaz (`device` rolündeki JWT'ler ve kalıcı cihaz token'ları)
+///
+/// `authorize` request extension'ı olarak ekler; ingest handler'ları body'deki
+/// veya path'teki cihaz ID'sini `check` ile doğrular.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceBinding(pub Uuid);
+
+impl DeviceBinding {
+    /// İstek başka bir cihaz adına veri gönderiyorsa 403
+    pub fn check(binding: Option<&Self>, claimed: &str) -> Result<(), StatusCode> {
+        match binding {
+            Some(DeviceBinding(id)) if claimed.parse::<Uuid>().ok() != Some(*id) => {
+                tracing::warn!("Token of device {id} used to submit data for {claimed:?}");
+                Err(StatusCode::FORBIDDEN)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// İsteği yapan kimlik (`{rol}:{subject}`), komut denetim kaydı için
+///
+/// `authorize` rolle birlikte request extension'ı olarak ekler: JWT'lerde
+/// `sub` claim'i, `ADMIN_TOKEN`'da `admin:admin-token`, kalıcı cihaz
+/// token'larında `device:{id}`. Auth kapalıyken eklenmez (`anonymous`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issuer(pub String);
+
+impl Issuer {
+    /// Auth kapalıyken kaydedilen kimlik
+    pub const ANONYMOUS: &str = "anonymous";
+
+    fn new(role: Role, subject: &str) -> Self {
+        Self(format!("{role}:{subject}"))
+    }
+
+    /// Extension yoksa `anonymous`
+    pub fn name(issuer: Option<&Self>) -> &str {
+        issuer.map_or(Self::ANONYMOUS, |Issuer(name)| name)
+    }
+}
+
+/// Token içeriği
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// Token sahibi (cihaz token'larında cihaz ID'si)
+    pub sub: String,
+    pub role: Role,
+    /// Yetki kapsamı: cihazlar için `device:{id}`, diğerleri için `api`
+    pub scope: String,
+    /// Verilme zamanı (Unix saniye)
+    pub iat: i64,
+    /// Son geçerlilik zamanı (Unix saniye)
+    pub exp: i64,
+}
+
+impl Claims {
+    /// `issued_at`'ten itibaren `DEVICE_TOKEN_TTL` süre geçerli cihaz claim'leri
+    pub fn for_device(device_id: Uuid, issued_at: DateTime<Utc>) -> Self {
+        Self {
+            sub: device_id.to_string(),
+            role: Role::Device,
+            scope: format!("device:{device_id}"),
+            iat: issued_at.timestamp(),
+            exp: (issued_at + DEVICE_TOKEN_TTL).timestamp(),
+        }
+    }
+
+    /// `issued_at`'ten itibaren `ACCESS_TOKEN_TTL` süre geçerli claim'ler
+    pub fn for_role(role: Role, subject: impl Into<String>, issued_at: DateTime<Utc>) -> Self {
+        Self {
+            sub: subject.into(),
+            role,
+            scope: "api".into(),
+            iat: issued_at.timestamp(),
+            exp: (issued_at + ACCESS_TOKEN_TTL).timestamp(),
+        }
+    }
+}
+
+/// Claim'leri imzalayıp `header.payload.signature` formatında token üret
+pub fn issue(secret: &str, claims: &Claims) -> String {
+    let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims).expect("claims serialize"));
+    let signing_input = format!("{header}.{payload}");
+    let signature = URL_SAFE_NO_PAD.encode(sign(secret, &signing_input));
+    format!("{signing_input}.{signature}")
+}
+
+fn sign(secret: &str, input: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(input.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// İmzayı ve süreyi doğrula, claim'leri döndür
+pub fn verify(secret: &str, token: &str, now: DateTime<Utc>) -> Option<Claims> {
+    let (signing_input, signature) = token.rsplit_once('.')?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(signing_input.as_bytes());
+    mac.verify_slice(&URL_SAFE_NO_PAD.decode(signature).ok()?).ok()?;
+
+    let (_, payload) = signing_input.split_once('.')?;
+    let claims: Claims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+    (now.timestamp() < claims.exp).then_some(claims)
+}
+
+/// Paylaşılan token'ı (`ADMIN_TOKEN`) sabit zamanda karşılaştır
+///
+/// İki taraf önce SHA-256'dan geçirilir: karşılaştırma süresi ne ortak
+/// önekin ne de token uzunluğunun bilgisini sızdırır.
+pub fn token_matches(expected: &str, given: &str) -> bool {
+    use sha2::Digest;
+    let (expected, given) = (Sha256::digest(expected.as_bytes()), Sha256::digest(given.as_bytes()));
+    expected.iter().zip(given.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// `Authorization: Bearer ...` header'ından çağıranın rolünü çöz
+///
+/// `ADMIN_TOKEN` ile eşleşen token `admin`'dir; diğerleri JWT olarak
+/// doğrulanır. Token yok veya geçersizse `None`.
+pub fn caller_role(secret: &str, admin_token: Option<&str>, headers: &HeaderMap) -> Option<Role> {
+    caller(secret, admin_token, headers).map(|caller| caller.role)
+}
+
+/// Token'dan çözülen çağıran
+#[derive(Debug, Clone, PartialEq)]
+struct Caller {
+    role: Role,
+    issuer: Issuer,
+    /// `device` rolünde token'ın cihazı
+    binding: Option<DeviceBinding>,
+}
+
+/// `caller_role` + çağıranın kimliği ve cihaz bağı
+///
+/// `device` JWT'lerinin `sub` claim'i cihaz ID'si olmalıdır; değilse token
+/// geçersiz sayılır (bağlanmamış bir cihaz token'ı her cihaz adına yazabilirdi).
+fn caller(secret: &str, admin_token: Option<&str>, headers: &HeaderMap) -> Option<Caller> {
+    let given = bearer_token(headers)?;
+    if admin_token.is_some_and(|t| !t.is_empty() && token_matches(t, given)) {
+        return Some(Caller { role: Role::Admin, issuer: Issuer::new(Role::Admin, "admin-token"), binding: None });
+    }
+    let claims = verify(secret, given, Utc::now())?;
+    let binding = match claims.role {
+        Role::Device => Some(DeviceBinding(claims.sub.parse().ok()?)),
+        _ => None,
+    };
+    Some(Caller { role: claims.role, issuer: Issuer::new(claims.role, &claims.sub), binding })
+}
+
+/// `Authorization: Bearer ...` header'ındaki token
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+/// Route'lara rol kontrolü ekle (sadece bu çağrıdan önce eklenmiş route'lar)
+///
+/// Çözülen rol request extension'ı olarak handler'lara iletilir.
+pub fn require_role(router: Router<AppState>, state: AppState, required: Role) -> Router<AppState> {
+    require_any_role(router, state, &[required])
+}
+
+/// `require_role` gibi, ama rollerden herhangi biri yeterli
+///
+/// Ör. cihaz gölgesi hem dashboard'a (`viewer`) hem cihazın kendisine (`device`) açıktır.
+pub fn require_any_role(router: Router<AppState>, state: AppState, allowed: &[Role]) -> Router<AppState> {
+    let allowed: Arc<[Role]> = allowed.into();
+    router.route_layer(middleware::from_fn_with_state(
+        state,
+        move |st: State<AppState>, request: Request, next: Next| authorize(st, allowed.clone(), request, next),
+    ))
+}
+
+/// Rol yetersizse 401/403 problem+json döner
+async fn authorize(State(st): State<AppState>, allowed: Arc<[Role]>, mut request: Request, next: Next) -> Response {
+    let (enabled, admin_token, provisioning_token) = {
+        let cfg = st.cfg.read().await;
+        (cfg.auth_enabled, cfg.admin_token.clone(), cfg.provisioning_token.clone())
+    };
+    if !enabled {
+        return next.run(request).await;
+    }
+
+    let mut caller = caller(&st.jwt_secret, admin_token.as_deref(), request.headers());
+    if caller.is_none() {
+        // Kayıt token'ı: sadece `provisioner` rolü
+        let given = bearer_token(request.headers()).unwrap_or_default();
+        if provisioning_token.is_some_and(|t| !t.is_empty() && token_matches(&t, given)) {
+            caller = Some(Caller {
+                role: Role::Provisioner,
+                issuer: Issuer::new(Role::Provisioner, "provisioning-token"),
+                binding: None,
+            });
+        }
+    }
+    if caller.is_none() {
+        // Kalıcı cihaz token'ı
+        if let Some(token) = bearer_token(request.headers()).filter(|t| t.starts_with(DEVICE_TOKEN_PREFIX)) {
+            if let Some(device_id) = device_tokens::authenticate(&st, token, Utc::now()).await {
+                caller = Some(Caller {
+                    role: Role::Device,
+                    issuer: Issuer::new(Role::Device, &device_id.to_string()),
+                    binding: Some(DeviceBinding(device_id)),
+                });
+            }
+        }
+    }
+    if caller.is_none() {
+        // CLI ile üretilen API anahtarı (`create-api-key`)
+        if let Some(key) = bearer_token(request.headers()).filter(|t| t.starts_with(API_KEY_PREFIX)) {
+            if let Some(owner) = api_key_owner(&st, key).await {
+                caller = Some(Caller { role: Role::Admin, issuer: Issuer::new(Role::Admin, &owner), binding: None });
+            }
+        }
+    }
+    let Some(Caller { role, issuer, binding }) = caller else {
+        return Problem::new(StatusCode::UNAUTHORIZED, "missing or invalid bearer token").into_response();
+    };
+    if !allowed.iter().any(|&required| role.satisfies(required)) {
+        let required: Vec<String> = allowed.iter().map(|r| format!("`{r}`")).collect();
+        let detail = format!("role `{role}` cannot access this endpoint (requires {})", required.join(" or "));
+        return Problem::new(StatusCode::FORBIDDEN, detail).into_response();
+    }
+    // Cihaz token'ı: istek token'ın cihazına bağlanır
+    if let Some(binding) = binding {
+        request.extensions_mut().insert(binding);
+    }
+    request.extensions_mut().insert(role);
+    request.extensions_mut().insert(issuer);
+    next.run(request).await
+}
+
+/// API anahtarını doğrula, kaydın adını (yoksa ID'sini) döndür
+///
+/// Anahtarlar sadece PostgreSQL'de tutulur; DB yoksa veya sorgu hata
+/// verirse `None` döner.
+async fn api_key_owner(st: &AppState, key: &str) -> Option<String> {
+    let db = st.pool().await?;
+    let (id, name): (Uuid, Option<String>) = sqlx::query_as("SELECT id, name FROM api_keys WHERE key_hash = $1")
+        .bind(hash_api_key(key))
+        .fetch_optional(&db)
+        .await
+        .map_err(|e| tracing::error!("API key lookup failed: {e}"))
+        .ok()??;
+    Some(format!("api-key:{}", name.unwrap_or_else(|| id.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::connect_info::MockConnectInfo};
+    use serde_json::{json, Value};
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_token_signed_and_scoped_to_device() {
+        let device_id = Uuid::new_v4();
+        let now = Utc::now();
+        let claims = Claims::for_device(device_id, now);
+        let token = issue("secret", &claims);
+
+        let verified = verify("secret", &token, now).unwrap();
+        assert_eq!(verified, claims);
+        assert_eq!((verified.role, verified.scope), (Role::Device, format!("device:{device_id}")));
+        assert_eq!(verified.exp - verified.iat, 300);
+
+        assert!(verify("other-secret", &token, now).is_none());
+        assert!(verify("secret", &token, now + DEVICE_TOKEN_TTL).is_none());
+    }
+
+    /// Gerçek router (`build_router`), in-memory state
+    async fn app(auth_enabled: bool) -> Router {
+        app_with(AppState::for_tests(), auth_enabled).await
+    }
+
+    async fn app_with(st: AppState, auth_enabled: bool) -> Router {
+        {
+            let mut cfg = st.cfg.write().await;
+            cfg.auth_enabled = auth_enabled;
+            cfg.admin_token = Some("admin-token".into());
+            cfg.provisioning_token = Some("provisioning-token".into());
+        }
+        crate::app::build_router(st).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))))
+    }
+
+    async fn status(app: &Router, method: &str, path: &str, token: Option<&str>, body: Option<Value>) -> StatusCode {
+        let mut request = Request::builder().method(method).uri(path);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let body = match body {
+            Some(body) => {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+                Body::from(body.to_string())
+            }
+            None => Body::empty(),
+        };
+        app.clone().oneshot(request.body(body).unwrap()).await.unwrap().status()
+    }
+
+    /// Her sınıftan bir gerçek endpoint: public, viewer (GET), device (ingest), admin
+    async fn access(app: &Router, token: Option<&str>, device_id: Uuid) -> [StatusCode; 4] {
+        let reading = json!({"device_id": device_id, "sensor_type": "temperature", "value": 21.5, "unit": "°C"});
+        [
+            status(app, "GET", "/health", token, None).await,
+            status(app, "GET", "/v1/admin/features", token, None).await,
+            status(app, "POST", "/api/sensors", token, Some(reading)).await,
+            status(app, "PATCH", "/v1/admin/features", token, Some(json!({}))).await,
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_each_role_against_each_endpoint_class() {
+        let app = app(true).await;
+        let now = Utc::now();
+        let token = |role| issue("test-secret", &Claims::for_role(role, "test", now));
+        let device_id = Uuid::new_v4();
+        let device = issue("test-secret", &Claims::for_device(device_id, now));
+        let forged = issue("wrong-secret", &Claims::for_role(Role::Admin, "test", now));
+
+        use StatusCode as S;
+        // Okuma yetkiyi geçince backend olmadığı için 503 döner
+        let cases = [
+            // (token, /health, GET /v1/admin/features, POST /api/sensors, PATCH /v1/admin/features)
+            (None, [S::OK, S::UNAUTHORIZED, S::UNAUTHORIZED, S::UNAUTHORIZED]),
+            (Some(forged), [S::OK, S::UNAUTHORIZED, S::UNAUTHORIZED, S::UNAUTHORIZED]),
+            (Some(token(Role::Viewer)), [S::OK, S::OK, S::FORBIDDEN, S::FORBIDDEN]),
+            (Some(device), [S::OK, S::FORBIDDEN, S::SERVICE_UNAVAILABLE, S::FORBIDDEN]),
+            (Some(token(Role::Admin)), [S::OK, S::OK, S::SERVICE_UNAVAILABLE, S::OK]),
+            (Some("admin-token".into()), [S::OK, S::OK, S::SERVICE_UNAVAILABLE, S::OK]),
+        ];
+        for (token, expected) in cases {
+            let actual = access(&app, token.as_deref(), device_id).await;
+            assert_eq!(actual, expected, "token {token:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_device_jwt_is_bound_to_its_subject() {
+        let app = app(true).await;
+        let (device_id, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let token = issue("test-secret", &Claims::for_device(device_id, Utc::now()));
+
+        let reading = json!({"device_id": other, "sensor_type": "temperature", "value": 21.5, "unit": "°C"});
+        assert_eq!(status(&app, "POST", "/api/sensors", Some(&token), Some(reading)).await, StatusCode::FORBIDDEN);
+        let shadow = |id: Uuid| format!("/v1/devices/{id}/shadow/reported");
+        assert_eq!(status(&app, "POST", &shadow(other), Some(&token), Some(json!({"led": "on"}))).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(&app, "POST", &shadow(device_id), Some(&token), Some(json!({"led": "on"}))).await, StatusCode::OK);
+
+        // Cihaz ID'si olmayan `sub`: hiçbir cihaza bağlanamaz, token geçersiz
+        let unbound = Claims { sub: "gateway".into(), ..Claims::for_device(device_id, Utc::now()) };
+        let unbound = issue("test-secret", &unbound);
+        assert_eq!(status(&app, "POST", &shadow(device_id), Some(&unbound), Some(json!({"led": "on"}))).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_provisioning_token_only_provisions_and_device_reaches_agent_endpoints() {
+        let app = app(true).await;
+        let provision = json!({"serial_number": "10000000abcdef01", "firmware_version": "0.1.0", "model": "rpi"});
+
+        use StatusCode as S;
+        assert_eq!(status(&app, "POST", "/v1/provision", None, Some(provision.clone())).await, S::UNAUTHORIZED);
+        assert_eq!(status(&app, "POST", "/v1/provision", Some("provisioning-token"), Some(provision.clone())).await, S::OK);
+        assert_eq!(status(&app, "POST", "/v1/provision", Some("admin-token"), Some(provision)).await, S::OK);
+        // Kayıt token'ı başka hiçbir yere erişemez
+        assert_eq!(access(&app, Some("provisioning-token"), Uuid::new_v4()).await, [S::OK, S::FORBIDDEN, S::FORBIDDEN, S::FORBIDDEN]);
+
+        // Cihaz token'ı: kendi gölgesi ve media yükleme (edge agent'ın HTTP çağrıları)
+        let (device_id, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let device = issue("test-secret", &Claims::for_device(device_id, Utc::now()));
+        let shadow = |id: Uuid| format!("/v1/devices/{id}/shadow");
+        assert_eq!(status(&app, "GET", &shadow(device_id), Some(&device), None).await, S::NOT_FOUND);
+        assert_eq!(status(&app, "GET", &shadow(other), Some(&device), None).await, S::FORBIDDEN);
+        assert_eq!(status(&app, "GET", &shadow(other), None, None).await, S::UNAUTHORIZED);
+        let upload = status(&app, "POST", "/v1/media/upload?name=photo.png&dedup=true", Some(&device), None).await;
+        assert!(matches!(upload, S::CREATED | S::OK), "{upload}");
+        assert_eq!(status(&app, "POST", "/v1/media/upload?name=photo.png", None, None).await, S::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_api_key_from_cli_is_admin() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        let cfg = crate::config::Config { database_url: Some(url.clone()), ..Default::default() };
+        let mut out = Vec::new();
+        crate::cli::create_api_key(&cfg, Some("ci"), &mut out).await.unwrap();
+        let key = String::from_utf8(out).unwrap().trim().to_string();
+
+        let app = app_with(AppState::for_tests_with_db(sqlx::PgPool::connect(&url).await.unwrap()), true).await;
+        use StatusCode as S;
+        assert_eq!(status(&app, "PATCH", "/v1/admin/features", Some(&key), Some(json!({}))).await, S::OK);
+        let unknown = crate::cli::generate_api_key();
+        assert_eq!(status(&app, "GET", "/v1/admin/features", Some(&unknown), None).await, S::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_forbidden_is_problem_json_and_disabled_auth_passes() {
+        let viewer = issue("test-secret", &Claims::for_role(Role::Viewer, "test", Utc::now()));
+        let request = Request::patch("/v1/admin/features")
+            .header(header::AUTHORIZATION, format!("Bearer {viewer}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        let response = app(true).await.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], crate::limits::PROBLEM_JSON);
+
+        // AUTH_ENABLED=false: rol kontrolü yok (yönetim endpoint'i kendi `ADMIN_TOKEN` kontrolünü yapar)
+        let app = app(false).await;
+        let device_id = Uuid::new_v4();
+        use StatusCode as S;
+        assert_eq!(access(&app, None, device_id).await, [S::OK, S::OK, S::SERVICE_UNAVAILABLE, S::UNAUTHORIZED]);
+        assert_eq!(access(&app, Some("admin-token"), device_id).await[3], S::OK);
+    }
+
+    #[test]
+    fn test_issuer_identifies_caller() {
+        let name = |token: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+            caller("test-secret", Some("admin-token"), &headers).map(|caller| caller.issuer.0)
+        };
+        let jwt = issue("test-secret", &Claims::for_role(Role::Admin, "ops@example.com", Utc::now()));
+
+        assert_eq!(name(&jwt).as_deref(), Some("admin:ops@example.com"));
+        assert_eq!(name("admin-token").as_deref(), Some("admin:admin-token"));
+        assert_eq!(name("admin-token-but-longer"), None);
+        assert_eq!(Issuer::name(None), Issuer::ANONYMOUS);
+    }
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("admin-token", "admin-token"));
+        assert!(!token_matches("admin-token", "admin-tokex"));
+        assert!(!token_matches("admin-token", "admin"));
+        assert!(!token_matches("admin-token", ""));
+    }
+}
@@ -0,0 +1,477 @@
+//! Arka Plan Görevleri (Background Tasks)
+//!
+//! HTTP isteklerinden bağımsız, periyodik çalışan işler.
+//! - `aggregate_sensor_readings`: `sensor_readings` → `sensor_summaries` (saatlik özet)
+//! - `prune_command_audit`: `COMMAND_AUDIT_RETENTION_DAYS`'ten eski komut denetim kayıtları
+//! - `deliver_webhooks`: webhook teslimat kuyruğu (retry + `webhook_deliveries` kaydı)
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use shared_types::webhook::{sign_payload, SIGNATURE_HEADER};
+use sqlx::PgPool;
+use tokio::sync::{mpsc, RwLock, Semaphore};
+use tokio::time::{interval, sleep, Duration, MissedTickBehavior};
+use uuid::Uuid;
+
+use crate::config::Config;
+
+/// Aggregation periyodu (1 saat)
+pub const AGGREGATION_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Saatlik özetleri hesapla ve upsert et
+///
+/// Pencere bir önceki saatin başından başlar: böylece önceki saat her zaman
+/// eksiksiz yeniden hesaplanır (`now() - 2 hours` gibi kayan bir pencere
+/// en eski bucket'ı yarım okumalarla ezerdi). Mevcut saat de kısmi olarak
+/// yazılır ve sonraki çalıştırmalarda tamamlanır.
+pub const AGGREGATE_SQL: &str = "INSERT INTO sensor_summaries
+        (device_id, sensor_type, bucket_start, min_value, max_value, avg_value, count)
+     SELECT device_id, sensor_type, date_trunc('hour', timestamp, 'UTC'),
+            min(value), max(value), avg(value), count(*)
+     FROM sensor_readings
+     WHERE timestamp >= date_trunc('hour', now() - interval '1 hour', 'UTC')
+     GROUP BY 1, 2, 3
+     ON CONFLICT (device_id, sensor_type, bucket_start) DO UPDATE SET
+        min_value = EXCLUDED.min_value,
+        max_value = EXCLUDED.max_value,
+        avg_value = EXCLUDED.avg_value,
+        count = EXCLUDED.count";
+
+/// Aggregation'ı bir kez çalıştır
+///
+/// Yazılan (eklenen veya güncellenen) özet satırı sayısını döner.
+pub async fn run_aggregation(db: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(AGGREGATE_SQL).execute(db).await?;
+    Ok(result.rows_affected())
+}
+
+/// Saatlik özetleri üreten sonsuz döngü
+///
+/// `main.rs` tarafından DB bağlıysa `tokio::spawn` ile başlatılır.
+/// İlk çalıştırma hemen yapılır (yeniden başlatmada boşluk kalmasın).
+/// Pool her periyotta `AppState.db`'den okunur (`PoolHealthMonitor` yenilemiş olabilir).
+/// Hatalar loglanır; görev bir sonraki periyotta tekrar dener.
+pub async fn aggregate_sensor_readings(db: Arc<RwLock<Option<PgPool>>>) {
+    let mut ticker = interval(AGGREGATION_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        let Some(db) = db.read().await.clone() else {
+            continue;
+        };
+        match run_aggregation(&db).await {
+            Ok(rows) => tracing::info!("Sensor aggregation wrote {rows} hourly summaries"),
+            Err(e) => tracing::error!("Sensor aggregation failed: {e}"),
+        }
+    }
+}
+
+/// Komut denetim kaydı retention periyodu (1 saat)
+pub const RETENTION_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// `cutoff`'tan önce verilmiş komutların denetim kayıtlarını sil
+///
+/// Silinen satır sayısını döner.
+pub async fn run_command_audit_retention(db: &PgPool, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM device_commands WHERE dispatched_at < $1").bind(cutoff).execute(db).await?;
+    Ok(result.rows_affected())
+}
+
+/// Komut denetim kaydını `COMMAND_AUDIT_RETENTION_DAYS` penceresinde tutan sonsuz döngü
+///
+/// `main.rs` tarafından başlatılır. Pencere her çalıştırmada yapılandırmadan,
+/// pool `AppState.db`'den okunur (pencere yeniden yüklenebilir, pool
+/// yenilenebilir).
+/// Hatalar loglanır; görev bir sonraki periyotta tekrar dener.
+pub async fn prune_command_audit(db: Arc<RwLock<Option<PgPool>>>, cfg: Arc<RwLock<Config>>) {
+    let mut ticker = interval(RETENTION_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        let Some(db) = db.read().await.clone() else {
+            continue;
+        };
+        let max_age_days = cfg.read().await.command_audit_retention_days;
+        // Pencere zaman aralığının dışındaysa silinecek kayıt yok
+        let Some(cutoff) = i64::try_from(max_age_days)
+            .ok()
+            .and_then(TimeDelta::try_days)
+            .and_then(|age| Utc::now().checked_sub_signed(age))
+        else {
+            continue;
+        };
+        match run_command_audit_retention(&db, cutoff).await {
+            Ok(0) => {}
+            Ok(rows) => tracing::info!("Command audit retention removed {rows} entries older than {max_age_days} days"),
+            Err(e) => tracing::error!("Command audit retention failed: {e}"),
+        }
+    }
+}
+
+/// Bir teslimatın en fazla deneme sayısı
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+/// İlk yeniden denemeden önceki bekleme (her denemede iki katına çıkar: 1s, 2s, 4s, 8s)
+pub const WEBHOOK_BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Tek bir HTTP denemesinin zaman aşımı
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Teslimat kuyruğu kapasitesi; dolduğunda yeni teslimatlar düşürülür
+pub const WEBHOOK_QUEUE_CAPACITY: usize = 1024;
+
+/// Aynı anda yapılan en fazla teslimat (her biri 5 denemeye kadar sürebilir)
+pub const MAX_CONCURRENT_DELIVERIES: usize = 16;
+
+/// Sınırlı webhook teslimat kuyruğunun gönderen ucu
+///
+/// Klonlar aynı kanalı ve düşürülen teslimat sayacını paylaşır. Alıcılar
+/// yavaşsa `deliver_webhooks` eşzamanlılık sınırında bekler, kuyruk dolar
+/// ve yeni teslimatlar bekletilmeden düşürülür (okuma kaydı yavaşlamaz).
+#[derive(Debug, Clone)]
+pub struct WebhookQueue {
+    tx: mpsc::Sender<WebhookDelivery>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl WebhookQueue {
+    /// `capacity` teslimatlık kuyruk ve tüketici ucu
+    pub fn bounded(capacity: usize) -> (Self, mpsc::Receiver<WebhookDelivery>) {
+        let (tx, rx) = mpsc::channel(capacity);
+        (Self { tx, dropped: Arc::default() }, rx)
+    }
+
+    /// Teslimatı kuyruğa ekle; kuyruk doluysa düşür ve say
+    ///
+    /// Eklendiyse `true` döner.
+    pub fn try_enqueue(&self, delivery: WebhookDelivery) -> bool {
+        match self.tx.try_send(delivery) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(delivery)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("Webhook queue full, dropping delivery {} for webhook {}", delivery.id, delivery.webhook_id);
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// Kuyruk dolu olduğu için düşürülen teslimat sayısı (kümülatif)
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Prometheus text formatında düşürülen teslimat sayacı
+    pub fn render_prometheus(&self) -> String {
+        format!(
+            "# HELP webhook_deliveries_dropped_total Webhook deliveries dropped because the queue was full.\n\
+             # TYPE webhook_deliveries_dropped_total counter\n\
+             webhook_deliveries_dropped_total {}\n",
+            self.dropped()
+        )
+    }
+}
+
+/// Kuyruktaki bir webhook teslimatı
+///
+/// `routes::webhooks::notify` tarafından olayı dinleyen her webhook için oluşturulur.
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub event_type: String,
+    /// JSON serialize edilmiş `WebhookEvent` (imza bu byte'lar üzerinden)
+    pub body: Vec<u8>,
+}
+
+/// Teslimatın nihai sonucu
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryOutcome {
+    /// Yapılan deneme sayısı (1..=5; hedef SSRF denetiminde reddedildiyse 0)
+    pub attempts: u32,
+    /// Son yanıtın HTTP durumu (bağlantı hatasında `None`)
+    pub response_status: Option<u16>,
+    /// Son hatanın açıklaması (başarılıysa `None`)
+    pub error: Option<String>,
+}
+
+impl DeliveryOutcome {
+    /// Alıcı 2xx döndü mü?
+    pub fn delivered(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl WebhookDelivery {
+    /// Teslimatı yap, başarısızsa exponential backoff ile yeniden dene
+    ///
+    /// 2xx dışındaki yanıtlar ve bağlantı hataları başarısız sayılır.
+    pub async fn deliver(&self, http: &reqwest::Client, base_backoff: Duration) -> DeliveryOutcome {
+        let signature = sign_payload(&self.secret, &self.body);
+        let mut outcome = DeliveryOutcome { attempts: 0, response_status: None, error: None };
+
+        for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+            outcome.attempts = attempt;
+            let result = http
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .body(self.body.clone())
+                .send()
+                .await;
+
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    outcome.response_status = Some(response.status().as_u16());
+                    outcome.error = None;
+                    return outcome;
+                }
+                Ok(response) => {
+                    outcome.response_status = Some(response.status().as_u16());
+                    outcome.error = Some(format!("HTTP {}", response.status()));
+                }
+                Err(e) => {
+                    outcome.response_status = None;
+                    outcome.error = Some(e.to_string());
+                }
+            }
+
+            if attempt < MAX_DELIVERY_ATTEMPTS {
+                sleep(base_backoff * 2u32.pow(attempt - 1)).await;
+            }
+        }
+        outcome
+    }
+}
+
+/// Teslimat sonucunu `webhook_deliveries` tablosuna yaz
+pub async fn record_delivery(db: &PgPool, delivery: &WebhookDelivery, outcome: &DeliveryOutcome) -> Result<(), sqlx::Error> {
+    let payload: serde_json::Value = serde_json::from_slice(&delivery.body).unwrap_or_default();
+    sqlx::query(
+        "INSERT INTO webhook_deliveries
+            (id, webhook_id, event_type, payload, attempts, delivered, response_status, error)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+    )
+    .bind(delivery.id)
+    .bind(delivery.webhook_id)
+    .bind(&delivery.event_type)
+    .bind(payload)
+    .bind(outcome.attempts as i32)
+    .bind(outcome.delivered())
+    .bind(outcome.response_status.map(i32::from))
+    .bind(&outcome.error)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+/// Webhook teslimat kuyruğunu tüketen sonsuz döngü
+///
+/// `main.rs` tarafından `tokio::spawn` ile başlatılır. Her teslimat ayrı
+/// task'ta yapılır; yavaş bir alıcı diğerlerini bekletmez. Aynı anda en fazla
+/// `MAX_CONCURRENT_DELIVERIES` teslimat yapılır; sınırdayken kuyruktan okuma
+/// durur ve taşan teslimatları `WebhookQueue::try_enqueue` düşürür.
+/// DB bağlıysa sonuçlar `webhook_deliveries`'e kaydedilir (pool her teslimatta
+/// `AppState.db`'den okunur).
+pub async fn deliver_webhooks(mut queue: mpsc::Receiver<WebhookDelivery>, db: Arc<RwLock<Option<PgPool>>>) {
+    // Yönlendirmeler izlenmez: kayıtta doğrulanan hedef iç ağa yönlendiremez
+    let http = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("reqwest client");
+    let slots = Arc::new(Semaphore::new(MAX_CONCURRENT_DELIVERIES));
+
+    loop {
+        let Ok(permit) = slots.clone().acquire_owned().await else { return };
+        let Some(delivery) = queue.recv().await else { return };
+        let http = http.clone();
+        let db = db.clone();
+        tokio::spawn(async move {
+            let _permit = permit;
+            // DNS kayıttan sonra değişmiş olabilir: hedef her teslimatta yeniden denetlenir
+            let outcome = match crate::routes::webhooks::validate_target(&delivery.url).await {
+                Ok(()) => delivery.deliver(&http, WEBHOOK_BASE_BACKOFF).await,
+                Err(reason) => DeliveryOutcome { attempts: 0, response_status: None, error: Some(format!("blocked target: {reason}")) },
+            };
+            if outcome.delivered() {
+                tracing::info!("Webhook {} delivered {} after {} attempt(s)", delivery.webhook_id, delivery.event_type, outcome.attempts);
+            } else {
+                tracing::warn!(
+                    "Webhook {} delivery failed after {} attempts: {}",
+                    delivery.webhook_id, outcome.attempts, outcome.error.as_deref().unwrap_or_default()
+                );
+            }
+            let pool = db.read().await.clone();
+            if let Some(db) = pool {
+                if let Err(e) = record_delivery(&db, &delivery, &outcome).await {
+                    tracing::error!("Failed to record webhook delivery: {e}");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration as ChronoDuration, DurationRound, Utc};
+    use shared_types::SensorSummary;
+
+    /// Migration'ları uygulanmış gerçek PostgreSQL gerektirir:
+    /// `TEST_DATABASE_URL=postgres://... cargo test -p api-server -- --ignored`
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    async fn insert(db: &PgPool, device: &str, value: f64, at: DateTime<Utc>) {
+        sqlx::query(
+            "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp)
+             VALUES ($1, 'temperature', $2, '°C', $3)"
+        )
+        .bind(device)
+        .bind(value)
+        .bind(at)
+        .execute(db)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_aggregation_builds_hourly_buckets() {
+        let db = test_db().await;
+        let device = format!("agg-{}", uuid::Uuid::new_v4());
+        let hour = Utc::now().duration_trunc(ChronoDuration::hours(1)).unwrap();
+        let previous = hour - ChronoDuration::hours(1);
+
+        for (value, at) in [(10.0, previous), (20.0, previous + ChronoDuration::minutes(30)), (5.0, hour)] {
+            insert(&db, &device, value, at).await;
+        }
+        run_aggregation(&db).await.unwrap();
+
+        let rows = sqlx::query_as::<_, SensorSummary>(
+            "SELECT device_id, sensor_type, bucket_start, min_value, max_value, avg_value, count
+             FROM sensor_summaries WHERE device_id = $1 ORDER BY bucket_start"
+        )
+        .bind(&device)
+        .fetch_all(&db)
+        .await
+        .unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].bucket_start, previous);
+        assert_eq!((rows[0].min_value, rows[0].max_value, rows[0].avg_value), (10.0, 20.0, 15.0));
+        assert_eq!(rows[0].count, 2);
+        assert_eq!((rows[1].bucket_start, rows[1].count), (hour, 1));
+
+        // Yeni okuma gelince mevcut bucket güncellenir (upsert)
+        insert(&db, &device, 7.0, hour + ChronoDuration::minutes(1)).await;
+        run_aggregation(&db).await.unwrap();
+        let count: i64 = sqlx::query_scalar(
+            "SELECT count FROM sensor_summaries WHERE device_id = $1 AND bucket_start = $2"
+        )
+        .bind(&device)
+        .bind(hour)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    mod webhook_delivery {
+        use super::super::*;
+        use axum::{extract::State, http::{HeaderMap, StatusCode}, routing::post, Router};
+        use std::sync::{atomic::{AtomicU32, Ordering}, Arc};
+
+        /// `delivery()` body'sinin "s3cret" ile HMAC-SHA256'sı, bağımsız hesaplanmış:
+        /// `printf '%s' '{"event_type":"sensor.alert","data":{}}' | openssl dgst -sha256 -hmac s3cret`
+        const EXPECTED_SIGNATURE: &str = "sha256=6bf7b4bfb61db78c73754b3abc7f2aa88cc2d5bff240a89f005e14ee95a6c510";
+
+        /// Mock alıcı: ilk `fail_first` isteğe 500 döner, sonra imzayı bilinen değerle karşılaştırır
+        #[derive(Clone)]
+        struct Receiver {
+            hits: Arc<AtomicU32>,
+            fail_first: u32,
+            verified: Arc<AtomicU32>,
+        }
+
+        async fn receive(State(rx): State<Receiver>, headers: HeaderMap, body: axum::body::Bytes) -> StatusCode {
+            let hit = rx.hits.fetch_add(1, Ordering::SeqCst) + 1;
+            if hit <= rx.fail_first {
+                return StatusCode::INTERNAL_SERVER_ERROR;
+            }
+            let signature = headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok()).unwrap_or_default();
+            if signature != EXPECTED_SIGNATURE || body != br#"{"event_type":"sensor.alert","data":{}}"#[..] {
+                return StatusCode::UNAUTHORIZED;
+            }
+            rx.verified.fetch_add(1, Ordering::SeqCst);
+            StatusCode::NO_CONTENT
+        }
+
+        async fn mock_receiver(fail_first: u32) -> (String, Receiver) {
+            let receiver = Receiver { hits: Arc::default(), fail_first, verified: Arc::default() };
+            let app = Router::new().route("/hook", post(receive)).with_state(receiver.clone());
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            (format!("http://{addr}/hook"), receiver)
+        }
+
+        fn delivery(url: String, secret: &str) -> WebhookDelivery {
+            WebhookDelivery {
+                id: Uuid::new_v4(),
+                webhook_id: Uuid::new_v4(),
+                url,
+                secret: secret.into(),
+                event_type: "sensor.alert".into(),
+                body: br#"{"event_type":"sensor.alert","data":{}}"#.to_vec(),
+            }
+        }
+
+        #[tokio::test]
+        async fn test_delivery_retries_until_success() {
+            let (url, receiver) = mock_receiver(2).await;
+            let outcome = delivery(url, "s3cret").deliver(&reqwest::Client::new(), Duration::from_millis(5)).await;
+
+            assert!(outcome.delivered());
+            assert_eq!(outcome.attempts, 3);
+            assert_eq!(outcome.response_status, Some(204));
+            assert_eq!(receiver.verified.load(Ordering::SeqCst), 1);
+        }
+
+        #[tokio::test]
+        async fn test_delivery_gives_up_after_max_attempts() {
+            // Yanlış secret: alıcı imzayı reddeder (401)
+            let (url, receiver) = mock_receiver(0).await;
+            let outcome = delivery(url, "wrong").deliver(&reqwest::Client::new(), Duration::from_millis(1)).await;
+
+            assert!(!outcome.delivered());
+            assert_eq!(outcome.attempts, MAX_DELIVERY_ATTEMPTS);
+            assert_eq!(outcome.response_status, Some(401));
+            assert_eq!(receiver.hits.load(Ordering::SeqCst), MAX_DELIVERY_ATTEMPTS);
+            assert_eq!(receiver.verified.load(Ordering::SeqCst), 0);
+        }
+
+        #[test]
+        fn test_full_queue_drops_and_counts() {
+            let (queue, mut rx) = WebhookQueue::bounded(2);
+            let accepted: Vec<_> = (0..5).map(|_| queue.try_enqueue(delivery("http://a.example/hook".into(), "s"))).collect();
+
+            assert_eq!(accepted, [true, true, false, false, false]);
+            assert_eq!(queue.dropped(), 3);
+            assert!(queue.render_prometheus().contains("webhook_deliveries_dropped_total 3\n"));
+            // Tüketilen yer tekrar kullanılabilir
+            rx.try_recv().unwrap();
+            assert!(queue.try_enqueue(delivery("http://a.example/hook".into(), "s")));
+            assert_eq!(queue.dropped(), 3);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Komut Satırı (CLI)
+//!
+//! ```sh
+//! api-server                        # = serve
+//! api-server serve --port 8080      # flag'ler env var'ları ezer
+//! api-server migrate                # migration'ları çalıştır ve çık
+//! api-server check-config           # geçerli yapılandırmayı yazdır (geçersizse çıkış kodu 1)
+//! api-server create-api-key --name ci
+//! ```
+//!
+//! Yapılandırma flag'leri (`--port`, `--database-url`...) her alt komutta
+//! geçerlidir. Öncelik: flag'ler > env var'lar > config dosyası > varsayılanlar.
+
+use std::{io::Write, path::PathBuf, time::Duration};
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use uuid::Uuid;
+
+use crate::config::{Config, ConfigOverrides};
+
+/// Alt komut
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subcommand {
+    /// HTTP sunucusunu çalıştır (varsayılan)
+    Serve,
+    /// `migrations/` altındaki migration'ları uygula ve çık
+    Migrate,
+    /// Yapılandırmayı doğrula, maskelenmiş halini yazdır
+    CheckConfig,
+    /// Yeni API anahtarı üret (DB varsa hash'ini kaydet)
+    CreateApiKey { name: Option<String> },
+}
+
+/// Parse edilmiş komut satırı
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub subcommand: Subcommand,
+    pub overrides: ConfigOverrides,
+}
+
+impl Cli {
+    /// `std::env::args()`'ı parse et (`--help` veya hatada process'ten çıkar)
+    pub fn parse() -> Self {
+        Self::try_parse_from(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Verilen argümanları parse et
+    pub fn try_parse_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        command().try_get_matches_from(args).map(|m| Self::from_matches(&m))
+    }
+
+    fn from_matches(matches: &ArgMatches) -> Self {
+        let subcommand = match matches.subcommand() {
+            Some(("migrate", _)) => Subcommand::Migrate,
+            Some(("check-config", _)) => Subcommand::CheckConfig,
+            Some(("create-api-key", sub)) => Subcommand::CreateApiKey { name: sub.get_one::<String>("name").cloned() },
+            _ => Subcommand::Serve,
+        };
+        let overrides = ConfigOverrides {
+            config_file: matches.get_one::<PathBuf>("config").cloned(),
+            app_host: matches.get_one::<String>("host").cloned(),
+            app_port: matches.get_one::<u16>("port").copied(),
+            database_url: matches.get_one::<String>("database-url").cloned(),
+            redis_url: matches.get_one::<String>("redis-url").cloned(),
+            log_level: matches.get_one::<String>("log-level").cloned(),
+        };
+        Self { subcommand, overrides }
+    }
+}
+
+/// clap komut tanımı
+fn command() -> Command {
+    let flag = |name: &'static str, value_name: &'static str, help: &'static str| {
+        Arg::new(name).long(name).value_name(value_name).help(help).global(true)
+    };
+    Command::new("api-server")
+        .about("RustyFlow API server")
+        .version(env!("CARGO_PKG_VERSION"))
+        .arg(flag("config", "PATH", "Config file (instead of RUSTYFLOW_CONFIG_FILE)").value_parser(value_parser!(PathBuf)))
+        .arg(flag("host", "ADDR", "Listen address (APP_HOST)"))
+        .arg(flag("port", "PORT", "Listen port (APP_PORT)").value_parser(value_parser!(u16)))
+        .arg(flag("database-url", "URL", "PostgreSQL URL (DATABASE_URL)"))
+        .arg(flag("redis-url", "URL", "Redis URL (REDIS_URL)"))
+        .arg(flag("log-level", "FILTER", "Log filter (RUST_LOG)"))
+        .subcommand(Command::new("serve").about("Run the HTTP server (default)"))
+        .subcommand(Command::new("migrate").about("Apply database migrations and exit"))
+        .subcommand(Command::new("check-config").about("Print the effective (sanitized) config; exit 1 if invalid"))
+        .subcommand(
+            Command::new("create-api-key")
+                .about("Generate an API key and store its hash if a database is configured")
+                .arg(Arg::new("name").long("name").value_name("NAME").help("Label stored with the key")),
+        )
+}
+
+/// `migrate`: migration'ları uygula
+///
+/// `DATABASE_URL` yoksa veya bağlanılamazsa hata döner.
+pub async fn migrate(cfg: &Config, out: &mut impl Write) -> Result<(), String> {
+    let db = connect(cfg).await?.ok_or("DATABASE_URL is not set")?;
+    let migrator = sqlx::migrate!("./migrations");
+    migrator.run(&db).await.map_err(|e| format!("migration failed: {e}"))?;
+    writeln!(out, "Database is up to date ({} migrations)", migrator.iter().count()).map_err(|e| e.to_string())
+}
+
+/// `check-config`: yapılandırmayı doğrula ve maskelenmiş halini yazdır
+///
+/// Okunamayan config dosyası, parse edilemeyen env var veya `Config::validate`,
+/// `listen_addr`, `tls_paths` hatalarında hata döner.
+pub fn check_config(cfg: Result<Config, String>, out: &mut impl Write) -> Result<(), String> {
+    let cfg = cfg?;
+    let json = serde_json::to_string_pretty(&cfg.sanitized()).map_err(|e| e.to_string())?;
+    writeln!(out, "{json}").map_err(|e| e.to_string())?;
+
+    cfg.validate()?;
+    cfg.listen_addr()?;
+    cfg.tls_paths()?;
+    Ok(())
+}
+
+/// API anahtarlarının öneki (`create-api-key`)
+pub const API_KEY_PREFIX: &str = "rfk_";
+
+/// `create-api-key`: anahtar üret, SHA-256 hash'ini `api_keys`'e yaz
+///
+/// Anahtar sadece bir kez (burada) gösterilir ve `admin` rolüyle kabul edilir
+/// (bkz. `auth`). Anahtarlar sadece PostgreSQL'de tutulur: `DATABASE_URL`
+/// ayarlı değilse veya kayıt başarısızsa hata döner, anahtar yazdırılmaz.
+pub async fn create_api_key(cfg: &Config, name: Option<&str>, out: &mut impl Write) -> Result<(), String> {
+    let db = connect(cfg).await?.ok_or("DATABASE_URL is not set; API keys are stored in the database")?;
+    let key = generate_api_key();
+    sqlx::query("INSERT INTO api_keys (id, name, key_hash) VALUES ($1, $2, $3)")
+        .bind(Uuid::new_v4())
+        .bind(name)
+        .bind(hash_api_key(&key))
+        .execute(&db)
+        .await
+        .map_err(|e| format!("cannot store API key: {e}"))?;
+    writeln!(out, "{key}").map_err(|e| e.to_string())
+}
+
+/// `rfk_` + 64 hex karakter (256 bit rastgele)
+pub fn generate_api_key() -> String {
+    format!("{API_KEY_PREFIX}{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Anahtarın DB'de saklanan hash'i (SHA-256, hex)
+pub fn hash_api_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// `DATABASE_URL` ayarlıysa bağlan
+async fn connect(cfg: &Config) -> Result<Option<PgPool>, String> {
+    let Some(url) = &cfg.database_url else {
+        return Ok(None);
+    };
+    PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_secs(5))
+        .connect(url)
+        .await
+        .map(Some)
+        .map_err(|e| format!("cannot connect to database: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve_is_default_and_flags_are_global() {
+        let cli = Cli::try_parse_from(["api-server"]).unwrap();
+        assert_eq!(cli, Cli { subcommand: Subcommand::Serve, overrides: ConfigOverrides::default() });
+
+        let cli = Cli::try_parse_from(["api-server", "--port", "8080", "serve", "--database-url", "postgres://db"]).unwrap();
+        assert_eq!(cli.subcommand, Subcommand::Serve);
+        assert_eq!(cli.overrides.app_port, Some(8080));
+        assert_eq!(cli.overrides.database_url.as_deref(), Some("postgres://db"));
+
+        let cli = Cli::try_parse_from(["api-server", "create-api-key", "--name", "ci", "--log-level", "warn"]).unwrap();
+        assert_eq!(cli.subcommand, Subcommand::CreateApiKey { name: Some("ci".into()) });
+        assert_eq!(cli.overrides.log_level.as_deref(), Some("warn"));
+
+        assert!(Cli::try_parse_from(["api-server", "--port", "abc"]).is_err());
+        assert!(Cli::try_parse_from(["api-server", "frobnicate"]).is_err());
+    }
+
+    #[test]
+    fn test_check_config_prints_and_fails_on_invalid_values() {
+        let mut out = Vec::new();
+        check_config(Ok(Config::default()), &mut out).unwrap();
+        let printed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(printed["app_port"], 3000);
+
+        let invalid = Config { app_host: "not-an-ip".into(), ..Config::default() };
+        assert!(check_config(Ok(invalid), &mut Vec::new()).unwrap_err().contains("APP_HOST"));
+        let invalid = Config { log_level: "info,[".into(), ..Config::default() };
+        assert!(check_config(Ok(invalid), &mut Vec::new()).is_err());
+        assert!(check_config(Err("bad APP_PORT".into()), &mut Vec::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_requires_database() {
+        let err = migrate(&Config::default(), &mut Vec::new()).await.unwrap_err();
+        assert_eq!(err, "DATABASE_URL is not set");
+    }
+
+    #[tokio::test]
+    async fn test_create_api_key_without_database_fails() {
+        // Saklanamayan anahtar kullanılamaz: yazdırılmaz, çıkış kodu 1
+        let mut out = Vec::new();
+        let err = create_api_key(&Config::default(), None, &mut out).await.unwrap_err();
+        assert!(err.contains("DATABASE_URL"), "{err}");
+        assert!(out.is_empty());
+
+        let key = generate_api_key();
+        assert!(key.starts_with(API_KEY_PREFIX) && key.len() == 68, "{key}");
+        assert_eq!(hash_api_key(&key).len(), 64);
+        assert_ne!(generate_api_key(), generate_api_key());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_create_api_key_stores_hash() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        let cfg = Config { database_url: Some(url), ..Config::default() };
+        let mut out = Vec::new();
+        create_api_key(&cfg, Some("test"), &mut out).await.unwrap();
+        let key = String::from_utf8(out).unwrap().trim().to_string();
+
+        let db = connect(&cfg).await.unwrap().unwrap();
+        let name: Option<String> = sqlx::query_scalar("SELECT name FROM api_keys WHERE key_hash = $1")
+            .bind(hash_api_key(&key))
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("test"));
+    }
+}
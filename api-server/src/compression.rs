@@ -0,0 +1,236 @@
+//! HTTP Sıkıştırma (Compression) Middleware'i
+//!
+//! Sensör geçmişi ve media listeleri büyük ve kolay sıkışan JSON'lardır;
+//! kısıtlı bağlantıdaki gateway'ler de batch'leri sıkıştırarak göndermek ister.
+//! - Response'lar `Accept-Encoding`'e göre gzip/br ile sıkıştırılır (`COMPRESSION`)
+//! - `COMPRESSION_MIN_SIZE`'dan küçük response'lar olduğu gibi gönderilir
+//! - Ingestion endpoint'leri `Content-Encoding: gzip` (ve br) body kabul eder
+//! - Streaming response'lar (line protocol export) chunk chunk sıkıştırılır,
+//!   tamamı bellekte toplanmaz
+
+use axum::Router;
+use tower_http::{
+    compression::{
+        predicate::{NotForContentType, Predicate, SizeAbove},
+        CompressionLayer,
+    },
+    decompression::RequestDecompressionLayer,
+};
+
+/// `COMPRESSION` ayarı
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionMode {
+    /// Sıkıştırma yok
+    Off,
+    /// Sadece gzip
+    Gzip,
+    /// Sadece brotli
+    Br,
+    /// Client'ın desteklediği en iyisi (br > gzip)
+    Auto,
+}
+
+impl CompressionMode {
+    /// `off|gzip|br|auto` (büyük/küçük harf duyarsız)
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "gzip" => Some(Self::Gzip),
+            "br" => Some(Self::Br),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+
+    fn gzip(self) -> bool {
+        matches!(self, Self::Gzip | Self::Auto)
+    }
+
+    fn br(self) -> bool {
+        matches!(self, Self::Br | Self::Auto)
+    }
+}
+
+/// Router'a response sıkıştırma katmanını ekle
+///
+/// Boyutu bilinmeyen (streaming) response'lar her zaman sıkıştırılır.
+/// Görseller ve SSE akışları sıkıştırılmaz.
+pub fn with_compression<S>(router: Router<S>, mode: CompressionMode, min_size: u16) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    if mode == CompressionMode::Off {
+        return router;
+    }
+    let predicate = SizeAbove::new(min_size)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE);
+    router.layer(
+        CompressionLayer::new()
+            .gzip(mode.gzip())
+            .br(mode.br())
+            .compress_when(predicate),
+    )
+}
+
+/// Ingestion route'larına request body açma katmanını ekle
+///
+/// Gzip her modda (kapalı hariç) kabul edilir; br sadece `br` ve `auto`'da.
+/// Desteklenmeyen `Content-Encoding` 415 döner.
+pub fn with_request_decompression<S>(router: Router<S>, mode: CompressionMode) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    if mode == CompressionMode::Off {
+        return router;
+    }
+    router.route_layer(RequestDecompressionLayer::new().gzip(true).br(mode.br()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, Request, StatusCode},
+        routing::{get, post},
+    };
+    use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+    use std::io::{Read, Write};
+    use tower::ServiceExt;
+
+    use crate::{routes, state::AppState};
+    use shared_types::Media;
+
+    const MIN_SIZE: u16 = 1024;
+
+    fn app(mode: CompressionMode, st: AppState) -> Router {
+        let ingest = with_request_decompression(
+            Router::new().route("/v1/devices/{id}/shadow/reported", post(routes::devices::update_reported)),
+            mode,
+        );
+        let router = Router::new()
+            .route("/v1/media", get(routes::media::list_media))
+            .route("/stream", get(stream))
+            .merge(ingest)
+            .with_state(st);
+        with_compression(router, mode, MIN_SIZE)
+    }
+
+    /// Export gibi boyutu bilinmeyen, chunk chunk üretilen body
+    async fn stream() -> Body {
+        let chunks = (0..100).map(|i| Ok::<_, std::io::Error>(format!("temperature value={i}.5 {i}\n")));
+        Body::from_stream(futures_util::stream::iter(chunks))
+    }
+
+    fn state_with_media(count: usize) -> AppState {
+        let st = AppState::for_tests();
+        st.media_store.try_write().unwrap().extend((0..count).map(|i| {
+            let m = Media::new(format!("photo-{i}.jpg"), format!("/uploads/photo-{i}.jpg"), "image/jpeg".into(), 1000);
+            (m.id, m)
+        }));
+        st
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn gunzip(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        GzDecoder::new(bytes).read_to_end(&mut out).unwrap();
+        out
+    }
+
+    async fn get_with(app: Router, uri: &str, accept: &str) -> (Option<String>, Vec<u8>) {
+        let request = Request::get(uri).header(header::ACCEPT_ENCODING, accept).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let encoding = response.headers().get(header::CONTENT_ENCODING).map(|v| v.to_str().unwrap().to_string());
+        (encoding, to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_gzip_batch_is_decompressed() {
+        let id = uuid::Uuid::new_v4();
+        let batch: serde_json::Value = (0..50)
+            .map(|i| (format!("sensor_{i}"), serde_json::json!(20.0 + i as f64)))
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+
+        let request = Request::post(format!("/v1/devices/{id}/shadow/reported"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(gzip(&serde_json::to_vec(&batch).unwrap())))
+            .unwrap();
+        let response = app(CompressionMode::Auto, AppState::for_tests()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let shadow: shared_types::DeviceShadow = serde_json::from_slice(&body).unwrap();
+        assert_eq!(shadow.reported, batch);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_request_encoding_rejected() {
+        let request = Request::post(format!("/v1/devices/{}/shadow/reported", uuid::Uuid::new_v4()))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_ENCODING, "br")
+            .body(Body::from("{}"))
+            .unwrap();
+        let response = app(CompressionMode::Gzip, AppState::for_tests()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_large_list_is_gzipped_and_round_trips() {
+        let st = state_with_media(200);
+        let (encoding, plain) = get_with(app(CompressionMode::Off, st.clone()), "/v1/media", "gzip").await;
+        assert_eq!(encoding, None);
+
+        let (encoding, compressed) = get_with(app(CompressionMode::Auto, st), "/v1/media", "gzip").await;
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        assert!(compressed.len() < plain.len() / 4);
+        assert_eq!(gunzip(&compressed), plain);
+    }
+
+    #[tokio::test]
+    async fn test_encoding_follows_mode_and_min_size() {
+        let st = state_with_media(200);
+        let (encoding, _) = get_with(app(CompressionMode::Auto, st.clone()), "/v1/media", "gzip, br").await;
+        assert_eq!(encoding.as_deref(), Some("br"));
+        let (encoding, _) = get_with(app(CompressionMode::Gzip, st.clone()), "/v1/media", "gzip, br").await;
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        let (encoding, _) = get_with(app(CompressionMode::Br, st), "/v1/media", "gzip").await;
+        assert_eq!(encoding, None);
+
+        // MIN_SIZE altındaki response sıkıştırılmaz
+        let (encoding, _) = get_with(app(CompressionMode::Auto, state_with_media(1)), "/v1/media", "gzip").await;
+        assert_eq!(encoding, None);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_response_still_streams_when_compressed() {
+        let request = Request::get("/stream").header(header::ACCEPT_ENCODING, "gzip").body(Body::empty()).unwrap();
+        let response = app(CompressionMode::Auto, AppState::for_tests()).oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        // Boyut önceden bilinmiyor: chunked aktarım
+        assert!(response.headers().get(header::CONTENT_LENGTH).is_none());
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(gunzip(&body)).unwrap();
+        assert_eq!(text.lines().count(), 100);
+        assert!(text.ends_with("temperature value=99.5 99\n"));
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(CompressionMode::parse("auto"), Some(CompressionMode::Auto));
+        assert_eq!(CompressionMode::parse(" GZIP "), Some(CompressionMode::Gzip));
+        assert_eq!(CompressionMode::parse("br"), Some(CompressionMode::Br));
+        assert_eq!(CompressionMode::parse("off"), Some(CompressionMode::Off));
+        assert_eq!(CompressionMode::parse("zstd"), None);
+    }
+}
@@ -0,0 +1,365 @@
+//! Veritabanı Akışları ve Pool Sağlığı (Database Streams, Pool Health)
+//!
+//! Büyük sorgu sonuçlarını `Vec`'e toplamadan satır satır okur: `fetch`
+//! satırları PostgreSQL bağlantısından geldikçe üretir, bellek kullanımı
+//! satır sayısından bağımsızdır.
+//!
+//! PostgreSQL gidip geri geldiğinde SQLx pool'u her zaman kendiliğinden
+//! toparlanmaz: `PoolHealthMonitor` pool'u periyodik olarak dener, art arda
+//! başarısız olursa aynı URL ile yenisini oluşturup paylaşılan pool'u değiştirir.
+//!
+//! Bu modül crate'in geri kalanına bağımlı değildir (`benches/` tarafından
+//! da derlenir).
+
+use std::future::Future;
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use sqlx::{postgres::{PgConnectOptions, PgPoolOptions}, PgPool};
+use tokio::sync::RwLock;
+use tokio::time::{interval, timeout, Duration, MissedTickBehavior};
+
+/// Pool sağlık kontrolü aralığı
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Bu kadar ardışık başarısız kontrolden sonra pool yeniden oluşturulur
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// Tek bir `SELECT 1` kontrolünün zaman aşımı
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Okuma sorgusu - cihaz + sensör tipi için zaman aralığındaki okumalar
+///
+/// `$3`/`$4` NULL ise aralık sınırı uygulanmaz, `$5` NULL ise limit yoktur.
+const READINGS_SQL: &str = "SELECT device_id, sensor_type, value, unit, timestamp, metadata, received_at, gateway_received_at
+     FROM sensor_readings
+     WHERE device_id = $1 AND sensor_type = $2
+       AND ($3::timestamptz IS NULL OR timestamp >= $3)
+       AND ($4::timestamptz IS NULL OR timestamp <= $4)
+     ORDER BY timestamp ASC
+     LIMIT $5";
+
+/// `sensor_readings` tablosundaki bir satır
+#[derive(Debug, Clone, sqlx::FromRow, serde::Deserialize)]
+pub struct SensorReadingRow {
+    pub device_id: String,
+    pub sensor_type: String,
+    pub value: f64,
+    pub unit: String,
+    pub timestamp: DateTime<Utc>,
+    pub metadata: Option<serde_json::Value>,
+    /// Sunucunun okumayı aldığı zaman (kolondan önceki satırlarda NULL)
+    pub received_at: Option<DateTime<Utc>>,
+    /// Gateway'in MQTT mesajını aldığı zaman (doğrudan gönderilenlerde NULL)
+    pub gateway_received_at: Option<DateTime<Utc>>,
+}
+
+/// Bir cihaz + sensör tipinin okumaları, zamana göre artan sırada
+///
+/// ```ignore
+/// let mut chunks = SensorReadingStream::new(db, "edge-agent-001", "temperature")
+///     .between(from, to)
+///     .fetch()
+///     .chunks(1000);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SensorReadingStream {
+    db: PgPool,
+    device_id: String,
+    sensor_type: String,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+}
+
+impl SensorReadingStream {
+    pub fn new(db: PgPool, device_id: impl Into<String>, sensor_type: impl Into<String>) -> Self {
+        Self { db, device_id: device_id.into(), sensor_type: sensor_type.into(), from: None, to: None, limit: None }
+    }
+
+    /// Zaman aralığı (iki uç da dahil, `None` = sınırsız)
+    pub fn between(mut self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Self {
+        self.from = from;
+        self.to = to;
+        self
+    }
+
+    /// Maksimum satır sayısı (`None` = sınırsız)
+    pub fn limit(mut self, limit: Option<i64>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Satırları bağlantıdan geldikçe üret
+    pub fn fetch(&self) -> impl Stream<Item = Result<SensorReadingRow, sqlx::Error>> + Send + '_ {
+        sqlx::query_as::<_, SensorReadingRow>(READINGS_SQL)
+            .bind(&self.device_id)
+            .bind(&self.sensor_type)
+            .bind(self.from)
+            .bind(self.to)
+            .bind(self.limit)
+            .fetch(&self.db)
+    }
+}
+
+/// API server bağlantılarında ayarlanan oturum değişkeni ve değeri
+///
+/// `sensor_readings` NOTIFY trigger'ı `rustyflow.source = 'api'` ile yazılan
+/// okumaları atlar; API server kendi kabul ettiği okumaları akışa zaten
+/// yayınlar. `application_name`'e dokunulmaz (`DATABASE_URL`'deki kalır).
+pub const SOURCE_SETTING: (&str, &str) = ("rustyflow.source", "api");
+
+/// `DATABASE_URL`'e pool aç
+/// 
+/// Başlangıçta ve `PoolHealthMonitor`'ün yeniden oluşturmasında aynı ayarlar
+/// kullanılır: en fazla 5 bağlantı, bağlantı almak için 2 saniye.
+pub async fn connect_pool(url: &str) -> Result<PgPool, sqlx::Error> {
+    let options = url.parse::<PgConnectOptions>()?.options([SOURCE_SETTING]);
+    PgPoolOptions::new()
+        .max_connections(5)
+        .acquire_timeout(Duration::from_secs(2))
+        .connect_with(options)
+        .await
+}
+
+/// `PoolHealthMonitor`'ün izlediği pool türü
+/// 
+/// Üretimde `PgBackend`; testler gerçek veritabanı olmadan mock kullanır.
+pub trait PoolBackend: Send + Sync + 'static {
+    type Pool: Clone + Send + Sync + 'static;
+
+    /// Pool sorgu çalıştırabiliyor mu?
+    fn ping(&self, pool: &Self::Pool) -> impl Future<Output = bool> + Send;
+
+    /// Yeni bir pool oluştur
+    fn connect(&self) -> impl Future<Output = Result<Self::Pool, String>> + Send;
+
+    /// Yerine yenisi konan pool'u kapat (bağlantıları sızmasın)
+    fn close(&self, pool: Self::Pool);
+}
+
+/// PostgreSQL: `SELECT 1` ile kontrol, orijinal URL ile yeniden bağlanma
+pub struct PgBackend {
+    url: String,
+}
+
+impl PgBackend {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+}
+
+impl PoolBackend for PgBackend {
+    type Pool = PgPool;
+
+    async fn ping(&self, pool: &PgPool) -> bool {
+        let check = sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(pool);
+        matches!(timeout(PING_TIMEOUT, check).await, Ok(Ok(_)))
+    }
+
+    async fn connect(&self) -> Result<PgPool, String> {
+        connect_pool(&self.url).await.map_err(|e| e.to_string())
+    }
+
+    /// Ödünç verilmiş bağlantılar geri dönene kadar bekler; monitörü bekletmemek için ayrı task
+    fn close(&self, pool: PgPool) {
+        tokio::spawn(async move { pool.close().await });
+    }
+}
+
+/// Pool'u periyodik olarak kontrol eden ve gerekirse yenileyen görev
+/// 
+/// Her `HEALTH_CHECK_INTERVAL`'da paylaşılan pool'u dener ve sonucu `healthy`
+/// flag'ine yazar. `MAX_CONSECUTIVE_FAILURES` ardışık hatadan sonra yeni
+/// pool oluşturup eskisinin yerine koyar ve eskisini kapatır; handler'lar ve
+/// arka plan görevleri bir sonraki okumada yenisini görür. Bağlanılamazsa her
+/// başarısız kontrolde tekrar dener.
+/// 
+/// Pool hiç yoksa (`None`, in-memory mod) bir şey yapılmaz.
+/// 
+/// ```ignore
+/// let monitor = PoolHealthMonitor::new(PgBackend::new(url), state.db.clone(), state.db_healthy.clone());
+/// tokio::spawn(monitor.run());
+/// ```
+pub struct PoolHealthMonitor<B: PoolBackend> {
+    backend: B,
+    pool: Arc<RwLock<Option<B::Pool>>>,
+    healthy: Arc<AtomicBool>,
+}
+
+impl<B: PoolBackend> PoolHealthMonitor<B> {
+    pub fn new(backend: B, pool: Arc<RwLock<Option<B::Pool>>>, healthy: Arc<AtomicBool>) -> Self {
+        Self { backend, pool, healthy }
+    }
+
+    /// Kontrol döngüsü (`main.rs` tarafından `tokio::spawn` ile başlatılır)
+    pub async fn run(self) {
+        let mut ticker = interval(HEALTH_CHECK_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut failures = 0u32;
+
+        loop {
+            ticker.tick().await;
+            let Some(pool) = self.pool.read().await.clone() else {
+                continue;
+            };
+
+            let ok = self.backend.ping(&pool).await;
+            self.healthy.store(ok, Ordering::Relaxed);
+            if ok {
+                failures = 0;
+                continue;
+            }
+
+            failures += 1;
+            tracing::warn!("Database health check failed ({failures}/{MAX_CONSECUTIVE_FAILURES})");
+            if failures < MAX_CONSECUTIVE_FAILURES {
+                continue;
+            }
+
+            tracing::error!("Database pool unhealthy, reinitializing");
+            match self.backend.connect().await {
+                Ok(new_pool) => {
+                    let old = self.pool.write().await.replace(new_pool);
+                    if let Some(old) = old {
+                        self.backend.close(old);
+                    }
+                    failures = 0;
+                }
+                Err(e) => tracing::error!("Database pool reinitialization failed: {e}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use std::sync::atomic::AtomicU32;
+
+    /// Pool = sürüm numarası; `broken_until`'dan küçük sürümlerin ping'i başarısız olur
+    #[derive(Clone, Default)]
+    struct MockBackend {
+        broken_until: Arc<AtomicU32>,
+        connects: Arc<AtomicU32>,
+        connect_fails: Arc<AtomicBool>,
+        closed: Arc<std::sync::Mutex<Vec<u32>>>,
+    }
+
+    impl PoolBackend for MockBackend {
+        type Pool = u32;
+
+        async fn ping(&self, pool: &u32) -> bool {
+            *pool >= self.broken_until.load(Ordering::SeqCst)
+        }
+
+        async fn connect(&self) -> Result<u32, String> {
+            if self.connect_fails.load(Ordering::SeqCst) {
+                return Err("connection refused".into());
+            }
+            Ok(self.connects.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn close(&self, pool: u32) {
+            self.closed.lock().unwrap().push(pool);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_monitor_reinitializes_after_consecutive_failures() {
+        let backend = MockBackend::default();
+        let pool = Arc::new(RwLock::new(Some(0)));
+        let healthy = Arc::new(AtomicBool::new(true));
+        tokio::spawn(PoolHealthMonitor::new(backend.clone(), pool.clone(), healthy.clone()).run());
+
+        // t=0: sağlıklı
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(healthy.load(Ordering::SeqCst));
+
+        // Veritabanı gitti ve geri geldi; eski pool (0) toparlanmıyor
+        backend.broken_until.store(1, Ordering::SeqCst);
+        backend.connect_fails.store(true, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(10)).await; // t=11: 1. hata
+        assert!(!healthy.load(Ordering::SeqCst));
+        tokio::time::sleep(Duration::from_secs(20)).await; // t=31: 3. hata, bağlanılamadı
+        assert_eq!(*pool.read().await, Some(0));
+
+        // Bir sonraki başarısız kontrolde yeniden bağlanır
+        backend.connect_fails.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(10)).await; // t=41
+        assert_eq!(*pool.read().await, Some(1));
+        assert_eq!(backend.connects.load(Ordering::SeqCst), 1);
+        // Eski pool kapatıldı
+        assert_eq!(*backend.closed.lock().unwrap(), [0]);
+        assert!(!healthy.load(Ordering::SeqCst));
+
+        tokio::time::sleep(Duration::from_secs(10)).await; // t=51: yeni pool sağlıklı
+        assert!(healthy.load(Ordering::SeqCst));
+        assert_eq!(backend.connects.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_monitor_tolerates_transient_failures() {
+        let backend = MockBackend::default();
+        let pool = Arc::new(RwLock::new(Some(0)));
+        let healthy = Arc::new(AtomicBool::new(true));
+        tokio::spawn(PoolHealthMonitor::new(backend.clone(), pool.clone(), healthy.clone()).run());
+
+        // İki hata, sonra toparlanma: pool değiştirilmez
+        backend.broken_until.store(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(15)).await; // t=0 ve t=10
+        assert!(!healthy.load(Ordering::SeqCst));
+        backend.broken_until.store(0, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(10)).await; // t=20
+        assert!(healthy.load(Ordering::SeqCst));
+
+        backend.broken_until.store(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(20)).await; // t=30, t=40: sayaç sıfırdan
+        assert_eq!(*pool.read().await, Some(0));
+        assert_eq!(backend.connects.load(Ordering::SeqCst), 0);
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_streams_ten_thousand_rows_in_order() {
+        const ROWS: i64 = 10_000;
+        let db = test_db().await;
+        let device_id = format!("stream-{}", uuid::Uuid::new_v4().simple());
+        sqlx::query(
+            "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp)
+             SELECT $1, 'temperature', i, '°C', now() - make_interval(secs => ($2 - i)::DOUBLE PRECISION)
+             FROM generate_series(1, $2::BIGINT) AS i",
+        )
+        .bind(&device_id)
+        .bind(ROWS)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let readings = SensorReadingStream::new(db.clone(), &device_id, "temperature");
+        let (mut count, mut previous) = (0i64, None);
+        let mut chunks = readings.fetch().chunks(1000);
+        while let Some(chunk) = chunks.next().await {
+            assert!(chunk.len() <= 1000);
+            for row in chunk {
+                let row = row.unwrap();
+                assert!(previous.is_none_or(|p| p <= row.timestamp));
+                previous = Some(row.timestamp);
+                count += 1;
+            }
+        }
+        assert_eq!(count, ROWS);
+
+        let limited = readings.clone().limit(Some(10)).fetch().count().await;
+        assert_eq!(limited, 10);
+
+        sqlx::query("DELETE FROM sensor_readings WHERE device_id = $1").bind(&device_id).execute(&db).await.unwrap();
+    }
+}
@@ -0,0 +1,178 @@
+//! PostgreSQL LISTEN/NOTIFY Köprüsü (`DB_NOTIFY`)
+//!
+//! HTTP API'yi atlayıp `sensor_readings`'e doğrudan yazan servislerin
+//! okumaları normalde long-poll/SSE client'larına ulaşmaz. Tablodaki trigger
+//! (`20251218090000_sensor_readings_notify.sql`) her yeni satırı
+//! `rustyflow_readings` kanalına NOTIFY eder; bu modül kanalı dinleyip
+//! okumaları `SensorFeed`'e yayınlar.
+//!
+//! - Payload satırın JSON'udur; PostgreSQL'in 8000 byte sınırını aşacak
+//!   satırlarda sadece `{"id": ...}` gelir ve satır tablodan okunur
+//! - API server'ın kendi yazdıkları (`db::SOURCE_SETTING` oturum ayarı)
+//!   trigger'da atlanır, akışa iki kez düşmez
+//! - Bağlantı koparsa `PgListener` yeniden bağlanır; arada gelen NOTIFY'lar kaybolur
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use sqlx::{postgres::PgListener, PgPool};
+use tokio::sync::RwLock;
+use tokio::time::{sleep, Duration};
+
+use crate::db::SensorReadingRow;
+use crate::realtime::SensorFeed;
+use crate::routes::sensors::SensorData;
+
+/// Trigger'ın NOTIFY ettiği kanal
+pub const READINGS_CHANNEL: &str = "rustyflow_readings";
+
+/// `recv` hatasından sonra tekrar denemeden önce beklenen süre
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Büyük satırlar için id ile okuma
+const READING_BY_ID_SQL: &str = "SELECT device_id, sensor_type, value, unit, timestamp, metadata, received_at, gateway_received_at
+     FROM sensor_readings
+     WHERE id = $1";
+
+/// NOTIFY payload'ı
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Notification {
+    /// Satırın tamamı (`id` dahil, kullanılmaz)
+    Reading(SensorReadingRow),
+    /// Payload sınırını aşan satır
+    Id { id: i64 },
+}
+
+/// Payload'dan okumayı çıkar, sadece id geldiyse satırı oku
+///
+/// Satır bu arada silindiyse `None`.
+async fn resolve(db: &RwLock<Option<PgPool>>, payload: &str) -> Result<Option<SensorData>, String> {
+    match serde_json::from_str(payload).map_err(|e| format!("invalid payload: {e}"))? {
+        Notification::Reading(row) => Ok(Some(row.into())),
+        Notification::Id { id } => sqlx::query_as::<_, SensorReadingRow>(READING_BY_ID_SQL)
+            .bind(id)
+            .fetch_optional(&db.read().await.clone().ok_or("database disconnected")?)
+            .await
+            .map(|row| row.map(SensorData::from))
+            .map_err(|e| format!("reading {id} could not be fetched: {e}")),
+    }
+}
+
+/// Kanalı dinlemeye başla
+///
+/// Ayrı bir bağlantı açılır (pool'un ayarlarıyla). `forward_readings`'ten
+/// önce çağrılır ki bağlantı hatası başlangıçta loglanabilsin.
+pub async fn listen(db: &PgPool) -> Result<PgListener, sqlx::Error> {
+    let mut listener = PgListener::connect_with(db).await?;
+    listener.listen(READINGS_CHANNEL).await?;
+    Ok(listener)
+}
+
+/// NOTIFY'ları okuma olarak akışa aktaran sonsuz döngü
+///
+/// `main.rs` tarafından `DB_NOTIFY=true` ve DB bağlıysa `tokio::spawn` ile
+/// başlatılır. Hatalı payload'lar loglanıp atlanır. Satır okumak gerektiğinde
+/// pool her seferinde `AppState.db`'den okunur (`PoolHealthMonitor` yenilemiş olabilir).
+pub async fn forward_readings(mut listener: PgListener, db: Arc<RwLock<Option<PgPool>>>, feed: SensorFeed) {
+    loop {
+        let notification = match listener.recv().await {
+            Ok(notification) => notification,
+            Err(e) => {
+                tracing::warn!("{READINGS_CHANNEL} listener error: {e}");
+                sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+        match resolve(&db, notification.payload()).await {
+            Ok(Some(data)) => {
+                feed.publish(data);
+            }
+            Ok(None) => tracing::debug!("Notified reading no longer exists"),
+            Err(e) => tracing::warn!("Ignoring {READINGS_CHANNEL} notification: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use std::pin::pin;
+
+    #[test]
+    fn test_payload_variants() {
+        let full = r#"{"id": 7, "device_id": "dev-1", "sensor_type": "temperature", "value": 21.5, "unit": "°C",
+            "timestamp": "2025-12-18T09:00:00.123+03:00", "metadata": null, "received_at": null, "gateway_received_at": null}"#;
+        let Notification::Reading(row) = serde_json::from_str(full).unwrap() else {
+            panic!("expected full reading");
+        };
+        let data = SensorData::from(row);
+        assert_eq!((data.device_id.as_str(), data.value), ("dev-1", 21.5));
+        assert_eq!(data.timestamp, "2025-12-18T06:00:00.123+00:00");
+
+        assert!(matches!(serde_json::from_str(r#"{"id": 7}"#).unwrap(), Notification::Id { id: 7 }));
+        assert!(serde_json::from_str::<Notification>(r#"{"device_id": "dev-1"}"#).is_err());
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_direct_insert_reaches_live_stream() {
+        let db = test_db().await;
+        // Operatörün application_name'i korunur
+        let url = std::env::var("TEST_DATABASE_URL").unwrap();
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let api_db = crate::db::connect_pool(&format!("{url}{separator}application_name=ops-dashboard")).await.unwrap();
+        let application_name: String = sqlx::query_scalar("SHOW application_name").fetch_one(&api_db).await.unwrap();
+        assert_eq!(application_name, "ops-dashboard");
+        let device_id = format!("notify-{}", uuid::Uuid::new_v4().simple());
+        let feed = SensorFeed::new();
+        let mut stream = pin!(feed.stream(None));
+        let listener = listen(&db).await.unwrap();
+        let task = tokio::spawn(forward_readings(listener, Arc::new(RwLock::new(Some(db.clone()))), feed.clone()));
+
+        let insert = |db: &PgPool, device_id: String, value: f64, metadata: serde_json::Value| {
+            let db = db.clone();
+            async move {
+                sqlx::query(
+                    "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp, metadata)
+                     VALUES ($1, 'temperature', $2, '°C', now(), $3)",
+                )
+                .bind(device_id)
+                .bind(value)
+                .bind(metadata)
+                .execute(&db)
+                .await
+                .unwrap();
+            }
+        };
+        // API server'ın bağlantısıyla yazılan okuma zaten yayınlanmış sayılır
+        insert(&api_db, format!("{device_id}-api"), 1.0, serde_json::json!({})).await;
+        insert(&db, device_id.clone(), 21.5, serde_json::json!({"source": "etl"})).await;
+        // 8000 byte'ı aşan satır sadece id ile gelir, tablodan okunur
+        let large = "x".repeat(10_000);
+        insert(&db, device_id.clone(), 22.5, serde_json::json!({"blob": large})).await;
+
+        // Paralel testlerin okumaları atlanır
+        let mut events = Vec::new();
+        while events.len() < 2 {
+            let event = tokio::time::timeout(Duration::from_secs(5), stream.next()).await.unwrap().unwrap();
+            if event.data.device_id.starts_with(&device_id) {
+                events.push(event);
+            }
+        }
+        assert_eq!(events[0].data.device_id, device_id);
+        assert_eq!(events[0].data.value, 21.5);
+        assert_eq!(events[0].data.metadata, Some(serde_json::json!({"source": "etl"})));
+        assert_eq!(events[1].data.value, 22.5);
+        assert_eq!(events[1].data.metadata.as_ref().unwrap()["blob"].as_str().unwrap().len(), 10_000);
+
+        task.abort();
+        sqlx::query("DELETE FROM sensor_readings WHERE device_id LIKE $1 || '%'").bind(&device_id).execute(&db).await.unwrap();
+    }
+}
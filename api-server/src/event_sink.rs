@@ -0,0 +1,322 @@
+//! Dış Sistemlere Olay Yayını (Event Sink)
+//!
+//! Kabul edilen her okuma ve üretilen her alarm, REST API'yi poll etmek
+//! istemeyen tüketiciler (analitik) için bir mesaj sistemine yayınlanır.
+//! `EVENT_SINK` ile seçilir:
+//! - `none`: kapalı (varsayılan)
+//! - `nats`: `NATS_URL`'deki sunucuya, `EVENT_SINK_TOPIC` subject'ine (`nats` feature'ı)
+//! - `kafka`: `KAFKA_BROKERS`'a, `EVENT_SINK_TOPIC` topic'ine, key = device_id (`kafka` feature'ı)
+//!
+//! Yayın HTTP isteğini bekletmez ve hata döndürmez: olaylar sınırlı bir
+//! kuyruğa (`EVENT_SINK_QUEUE_SIZE`) atılır, arka plan görevi sırayla
+//! yayınlar ve başarısızları exponential backoff ile yeniden dener.
+//! Kuyruk doluysa yeni olaylar loglanıp atılır. Yayın görevi (`spawn`,
+//! `run`) sadece `nats`/`kafka` feature'larından biri açıkken derlenir.
+
+#[cfg(any(feature = "nats", feature = "kafka", test))]
+use std::future::Future;
+
+use serde::Serialize;
+use shared_types::SensorAlert;
+use tokio::sync::mpsc::{self, error::TrySendError};
+#[cfg(any(feature = "nats", feature = "kafka", test))]
+use tokio::time::{sleep, Duration};
+
+use crate::config::Config;
+use crate::routes::sensors::SensorData;
+
+/// Bir olayın en fazla yayın denemesi
+#[cfg(any(feature = "nats", feature = "kafka", test))]
+pub const MAX_PUBLISH_ATTEMPTS: u32 = 5;
+
+/// İlk yeniden denemeden önceki bekleme (her denemede iki katına çıkar)
+#[cfg(any(feature = "nats", feature = "kafka", test))]
+pub const PUBLISH_BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Yayınlanan olay
+///
+/// ```json
+/// {"type": "reading", "device_id": "edge-agent-001", "sensor_type": "temperature", "value": 23.5, ...}
+/// {"type": "alert", "id": "...", "device_id": "edge-agent-001", "message": "temperature 41.2 above max 35", ...}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IngestEvent {
+    Reading(SensorData),
+    Alert(SensorAlert),
+}
+
+impl IngestEvent {
+    /// Olayın cihazı (Kafka partition key'i)
+    pub fn device_id(&self) -> &str {
+        match self {
+            Self::Reading(data) => &data.device_id,
+            Self::Alert(alert) => &alert.device_id,
+        }
+    }
+}
+
+/// Olayların yayınlandığı dış sistem
+#[cfg(any(feature = "nats", feature = "kafka", test))]
+pub trait EventSink: Send + Sync + 'static {
+    /// Olayı yayınla; hata dönerse `run` yeniden dener
+    fn publish(&self, event: &IngestEvent) -> impl Future<Output = Result<(), String>> + Send;
+}
+
+/// `EVENT_SINK`'e göre sink'i bağla ve yayın görevini başlat
+///
+/// Sink kapalıysa, bilinmiyorsa veya bağlanamazsa `None` döner
+/// (olaylar yayınlanmaz, HTTP istekleri etkilenmez).
+pub async fn start(cfg: &Config) -> Option<mpsc::Sender<IngestEvent>> {
+    let queue = match cfg.event_sink.trim().to_lowercase().as_str() {
+        "" | "none" => return None,
+        #[cfg(feature = "nats")]
+        "nats" => NatsSink::connect(&cfg.nats_url, &cfg.event_sink_topic)
+            .await
+            .map(|sink| spawn(sink, cfg.event_sink_queue_size)),
+        #[cfg(feature = "kafka")]
+        "kafka" => KafkaSink::connect(&cfg.kafka_brokers, &cfg.event_sink_topic)
+            .map(|sink| spawn(sink, cfg.event_sink_queue_size)),
+        other => Err(format!("'{other}' is unknown or not compiled in (see the nats/kafka features)")),
+    };
+    match queue {
+        Ok(queue) => {
+            tracing::info!("📤 Publishing ingest events to {} ({})", cfg.event_sink, cfg.event_sink_topic);
+            Some(queue)
+        }
+        Err(e) => {
+            tracing::error!("Event sink disabled: {e}");
+            None
+        }
+    }
+}
+
+/// `capacity` olaylık kuyruk oluştur ve `run`'ı başlat
+#[cfg(any(feature = "nats", feature = "kafka", test))]
+pub fn spawn<S: EventSink>(sink: S, capacity: usize) -> mpsc::Sender<IngestEvent> {
+    let (tx, rx) = mpsc::channel(capacity.max(1));
+    tokio::spawn(run(rx, sink, PUBLISH_BASE_BACKOFF));
+    tx
+}
+
+/// Olayı yayın kuyruğuna at (sink kapalıysa hiçbir şey yapmaz)
+///
+/// Beklemez: kuyruk doluysa olay atılır.
+pub fn enqueue(queue: Option<&mpsc::Sender<IngestEvent>>, event: IngestEvent) {
+    let Some(queue) = queue else {
+        return;
+    };
+    match queue.try_send(event) {
+        Ok(()) => {}
+        Err(TrySendError::Full(event)) => {
+            tracing::warn!("Event sink queue full, dropping {} event", event.device_id());
+        }
+        Err(TrySendError::Closed(_)) => tracing::warn!("Event sink stopped, dropping event"),
+    }
+}
+
+/// Kuyruğu tüketip olayları sırayla yayınlayan döngü
+///
+/// `start` tarafından `tokio::spawn` ile başlatılır; kuyruk kapanınca döner.
+/// `MAX_PUBLISH_ATTEMPTS` denemede yayınlanamayan olay loglanıp atılır.
+#[cfg(any(feature = "nats", feature = "kafka", test))]
+pub async fn run<S: EventSink>(mut queue: mpsc::Receiver<IngestEvent>, sink: S, base_backoff: Duration) {
+    while let Some(event) = queue.recv().await {
+        for attempt in 1..=MAX_PUBLISH_ATTEMPTS {
+            match sink.publish(&event).await {
+                Ok(()) => break,
+                Err(e) if attempt < MAX_PUBLISH_ATTEMPTS => {
+                    tracing::warn!("Event publish attempt {attempt} failed: {e}");
+                    sleep(base_backoff * 2u32.pow(attempt - 1)).await;
+                }
+                Err(e) => tracing::error!("Dropping event for {} after {attempt} attempts: {e}", event.device_id()),
+            }
+        }
+    }
+}
+
+/// NATS core publish (`EVENT_SINK=nats`)
+#[cfg(feature = "nats")]
+pub struct NatsSink {
+    client: async_nats::Client,
+    subject: String,
+}
+
+#[cfg(feature = "nats")]
+impl NatsSink {
+    /// Sunucuya bağlan; ilk bağlantı başarısızsa client arka planda yeniden dener
+    pub async fn connect(url: &str, subject: &str) -> Result<Self, String> {
+        let client = async_nats::ConnectOptions::new()
+            .retry_on_initial_connect()
+            .connect(url)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Self { client, subject: subject.to_string() })
+    }
+}
+
+#[cfg(feature = "nats")]
+impl EventSink for NatsSink {
+    async fn publish(&self, event: &IngestEvent) -> Result<(), String> {
+        let payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+        self.client
+            .publish(self.subject.clone(), payload.into())
+            .await
+            .map_err(|e| e.to_string())?;
+        self.client.flush().await.map_err(|e| e.to_string())
+    }
+}
+
+/// Kafka producer (`EVENT_SINK=kafka`)
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+    producer: rdkafka::producer::FutureProducer,
+    topic: String,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+    /// Tek bir mesajın broker onayı için beklenen en uzun süre
+    const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Producer oluştur (bağlantı ilk gönderimde kurulur)
+    pub fn connect(brokers: &str, topic: &str) -> Result<Self, String> {
+        let producer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", Self::DELIVERY_TIMEOUT.as_millis().to_string())
+            .create()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { producer, topic: topic.to_string() })
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl EventSink for KafkaSink {
+    async fn publish(&self, event: &IngestEvent) -> Result<(), String> {
+        let payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+        let record = rdkafka::producer::FutureRecord::to(&self.topic)
+            .key(event.device_id())
+            .payload(&payload);
+        self.producer
+            .send(record, Self::DELIVERY_TIMEOUT)
+            .await
+            .map(|_| ())
+            .map_err(|(e, _)| e.to_string())
+    }
+}
+
+/// Yayınlanan olayları bellekte toplayan test sink'i
+///
+/// İlk `fail_first` yayın hata döner.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct MockSink {
+    pub events: std::sync::Arc<std::sync::Mutex<Vec<IngestEvent>>>,
+    fail_first: std::sync::Arc<std::sync::atomic::AtomicU32>,
+}
+
+#[cfg(test)]
+impl MockSink {
+    pub fn failing(times: u32) -> Self {
+        Self { fail_first: std::sync::Arc::new(times.into()), ..Self::default() }
+    }
+
+    pub fn events(&self) -> Vec<IngestEvent> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl EventSink for MockSink {
+    async fn publish(&self, event: &IngestEvent) -> Result<(), String> {
+        use std::sync::atomic::Ordering;
+        let remaining = self.fail_first.load(Ordering::SeqCst);
+        if remaining > 0 {
+            self.fail_first.store(remaining - 1, Ordering::SeqCst);
+            return Err("broker unavailable".into());
+        }
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(device: &str, value: f64) -> IngestEvent {
+        IngestEvent::Reading(SensorData {
+            device_id: device.into(),
+            sensor_type: "temperature".into(),
+            value,
+            unit: "°C".into(),
+            timestamp: "2024-01-20T10:30:00Z".into(),
+            metadata: None,
+            received_at: None,
+            gateway_received_at: None,
+            stale: false,
+        })
+    }
+
+    #[test]
+    fn test_event_json_is_tagged() {
+        let json = serde_json::to_value(reading("edge-agent-001", 23.5)).unwrap();
+        assert_eq!(json["type"], "reading");
+        assert_eq!(json["device_id"], "edge-agent-001");
+        assert_eq!(json["value"], 23.5);
+    }
+
+    #[tokio::test]
+    async fn test_failed_publishes_are_retried_in_order() {
+        let sink = MockSink::failing(2);
+        let (tx, rx) = mpsc::channel(16);
+        for i in 0..3 {
+            enqueue(Some(&tx), reading("dev-1", i as f64));
+        }
+        drop(tx);
+
+        run(rx, sink.clone(), Duration::from_millis(1)).await;
+        assert_eq!(sink.events(), vec![reading("dev-1", 0.0), reading("dev-1", 1.0), reading("dev-1", 2.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_event_dropped_after_max_attempts() {
+        let sink = MockSink::failing(MAX_PUBLISH_ATTEMPTS);
+        let (tx, rx) = mpsc::channel(16);
+        enqueue(Some(&tx), reading("dev-1", 1.0));
+        enqueue(Some(&tx), reading("dev-1", 2.0));
+        drop(tx);
+
+        run(rx, sink.clone(), Duration::from_millis(1)).await;
+        assert_eq!(sink.events(), vec![reading("dev-1", 2.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_drops_when_queue_full() {
+        let (tx, mut rx) = mpsc::channel(1);
+        enqueue(Some(&tx), reading("dev-1", 1.0));
+        // Kuyruk dolu: beklemeden atılır
+        enqueue(Some(&tx), reading("dev-1", 2.0));
+        enqueue(None, reading("dev-1", 3.0));
+
+        drop(tx);
+        assert_eq!(rx.recv().await, Some(reading("dev-1", 1.0)));
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_spawned_sink_publishes_readings_and_alerts() {
+        let sink = MockSink::default();
+        let queue = spawn(sink.clone(), 16);
+        let threshold = shared_types::AlertThreshold { min: None, max: Some(35.0) };
+        let alert = shared_types::alert::check_threshold("dev-1", "temperature", 41.2, &threshold).unwrap();
+        enqueue(Some(&queue), reading("dev-1", 41.2));
+        enqueue(Some(&queue), IngestEvent::Alert(alert.clone()));
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(1);
+        while sink.events().len() < 2 && tokio::time::Instant::now() < deadline {
+            sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(sink.events(), vec![reading("dev-1", 41.2), IngestEvent::Alert(alert)]);
+    }
+}
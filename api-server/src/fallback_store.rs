@@ -0,0 +1,209 @@
+//! In-Memory Media Store Kalıcılığı (Snapshot)
+//!
+//! PostgreSQL olmadan çalışırken media kayıtları sadece bellekte tutulur;
+//! restart hepsini siler. `FALLBACK_STORE_PATH` ayarlıysa:
+//! - Başlangıçta snapshot dosyası okunur (sunucu istek almadan önce)
+//! - `FALLBACK_SNAPSHOT_INTERVAL_SECS` aralıkla ve graceful shutdown'da yazılır
+//! - Yazma atomiktir: önce `<path>.tmp`, sonra `rename`
+//! - Parse edilemeyen dosya `<path>.corrupt-<zaman>` olarak kenara alınır, boş store ile devam edilir
+//! - Okunamayan (ör. izin hatası) dosyanın üstüne yazılmaz: snapshot kapatılır
+
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc};
+
+use chrono::Utc;
+use shared_types::Media;
+use tokio::{fs, io::AsyncWriteExt, sync::{oneshot, RwLock}};
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use uuid::Uuid;
+
+/// Paylaşılan media store (bkz. `AppState::media_store`)
+pub type MediaStore = Arc<RwLock<HashMap<Uuid, Media>>>;
+
+/// Snapshot dosyasını oku
+///
+/// Dosya yoksa boş store döner. Bozuksa dosya karantinaya alınır
+/// (yeniden adlandırılır) ve boş store döner; başlangıç engellenmez.
+///
+/// Dosya okunamaz veya karantinaya alınamazsa hata döner; çağıran bu
+/// yola snapshot yazmamalıdır (boş store gerçek dosyanın üstüne yazılır).
+pub async fn load(path: &Path) -> std::io::Result<HashMap<Uuid, Media>> {
+    let bytes = match fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+
+    match serde_json::from_slice::<Vec<Media>>(&bytes) {
+        Ok(items) => {
+            tracing::info!("Loaded {} media records from {}", items.len(), path.display());
+            Ok(items.into_iter().map(|m| (m.id, m)).collect())
+        }
+        Err(e) => {
+            let quarantine = quarantine_path(path);
+            tracing::error!("Corrupted media snapshot {} ({e}), moving to {}", path.display(), quarantine.display());
+            fs::rename(path, &quarantine).await?;
+            Ok(HashMap::new())
+        }
+    }
+}
+
+/// Store'un anlık görüntüsünü atomik olarak yaz
+///
+/// Kayıtlar `created_at` sırasıyla JSON array olarak yazılır.
+pub async fn save(store: &MediaStore, path: &Path) -> std::io::Result<()> {
+    let bytes = {
+        let map = store.read().await;
+        let mut items: Vec<&Media> = map.values().collect();
+        items.sort_by_key(|m| (m.created_at, m.id));
+        serde_json::to_vec_pretty(&items)?
+    };
+
+    let tmp = sibling(path, ".tmp");
+    let mut file = fs::File::create(&tmp).await?;
+    file.write_all(&bytes).await?;
+    file.sync_all().await?;
+    drop(file);
+    fs::rename(&tmp, path).await
+}
+
+/// Periyodik snapshot döngüsü
+///
+/// `main.rs` tarafından `tokio::spawn` ile başlatılır. `shutdown` sinyali
+/// geldiğinde (veya gönderici düştüğünde) son bir snapshot yazıp döner.
+pub async fn persist(store: MediaStore, path: PathBuf, period: Duration, mut shutdown: oneshot::Receiver<()>) {
+    let mut ticker = interval(period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker.tick().await; // İlk tick hemen döner; başlangıçta yazmaya gerek yok
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if let Err(e) = save(&store, &path).await {
+                    tracing::error!("Media snapshot failed: {e}");
+                }
+            }
+            _ = &mut shutdown => break,
+        }
+    }
+
+    match save(&store, &path).await {
+        Ok(()) => tracing::info!("Media snapshot flushed to {}", path.display()),
+        Err(e) => tracing::error!("Media snapshot flush failed: {e}"),
+    }
+}
+
+/// Yüklenen kayıtların checksum indeksi (bkz. `AppState::media_checksums`)
+pub fn checksum_index(map: &HashMap<Uuid, Media>) -> HashMap<String, Uuid> {
+    map.values()
+        .filter_map(|m| Some((m.checksum_sha256.clone()?, m.id)))
+        .collect()
+}
+
+/// `<path>.corrupt-<UTC zaman>`
+fn quarantine_path(path: &Path) -> PathBuf {
+    sibling(path, &format!(".corrupt-{}", Utc::now().format("%Y%m%dT%H%M%S%.3fZ")))
+}
+
+/// Aynı dizinde, dosya adına `suffix` eklenmiş yol
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test başına ayrı geçici dizin
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rustyflow-{name}-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn store_with(names: &[&str]) -> MediaStore {
+        let map = names
+            .iter()
+            .map(|n| {
+                let m = Media::new(n.to_string(), format!("/uploads/{n}"), "image/jpeg".into(), 10);
+                (m.id, m)
+            })
+            .collect();
+        Arc::new(RwLock::new(map))
+    }
+
+    fn names(map: &HashMap<Uuid, Media>) -> Vec<String> {
+        let mut names: Vec<_> = map.values().map(|m| m.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore() {
+        let dir = temp_dir("snapshot");
+        let path = dir.join("media.json");
+        assert!(load(&path).await.unwrap().is_empty());
+
+        let store = store_with(&["a.jpg", "b.jpg"]);
+        save(&store, &path).await.unwrap();
+        assert!(!sibling(&path, ".tmp").exists());
+
+        let restored = load(&path).await.unwrap();
+        assert_eq!(names(&restored), vec!["a.jpg", "b.jpg"]);
+        let original = store.read().await;
+        for (id, media) in restored {
+            assert_eq!(original[&id], media);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_snapshot_is_quarantined() {
+        let dir = temp_dir("corrupt");
+        let path = dir.join("media.json");
+        std::fs::write(&path, b"[{\"id\": \"not-a-uuid\"").unwrap();
+
+        assert!(load(&path).await.unwrap().is_empty());
+        assert!(!path.exists());
+        let quarantined: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(quarantined.len(), 1);
+        assert!(quarantined[0].starts_with("media.json.corrupt-"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_snapshot() {
+        let dir = temp_dir("flush");
+        let path = dir.join("media.json");
+        let store = store_with(&[]);
+        let (stop_tx, stop_rx) = oneshot::channel();
+        // Aralık testten uzun: sadece shutdown flush'ı yazabilir
+        let task = tokio::spawn(persist(store.clone(), path.clone(), Duration::from_secs(3600), stop_rx));
+
+        let m = Media::new("late.jpg".into(), "/uploads/late.jpg".into(), "image/jpeg".into(), 1);
+        store.write().await.insert(m.id, m);
+        tokio::task::yield_now().await;
+        assert!(!path.exists());
+
+        stop_tx.send(()).unwrap();
+        task.await.unwrap();
+        assert_eq!(names(&load(&path).await.unwrap()), vec!["late.jpg"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_snapshot_is_an_error() {
+        let dir = temp_dir("unreadable");
+        // Dizin okunamaz (NotFound değil): boş store ile devam edilmez
+        let path = dir.join("media.json");
+        std::fs::create_dir(&path).unwrap();
+
+        assert!(load(&path).await.is_err());
+        assert!(path.is_dir());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,220 @@
+//! Özellik Bayrakları (Feature Flags)
+//!
+//! Bazı özellikler ortam başına, yeniden deploy etmeden açılıp kapatılabilir.
+//! Bayraklar başlangıçta `FEATURE_FLAGS_FILE` JSON dosyasından ve `FEATURE_*`
+//! ortam değişkenlerinden okunur (env var'lar dosyayı ezer); çalışırken
+//! `PATCH /v1/admin/features` ile değiştirilir. Değişiklik bir sonraki
+//! istekte etkili olur ama kalıcı değildir (restart'ta yeniden okunur).
+//!
+//! `simulation_enabled` dışındaki tüm bayraklar varsayılan olarak açıktır;
+//! test verisi üreten simülasyon endpoint'i production'da kapalı kalır.
+
+use std::path::Path;
+
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// Çalışırken değiştirilebilen özellik bayrakları
+///
+/// Örnek .env:
+/// ```ignore
+/// FEATURE_FLAGS_FILE=./features.json
+/// FEATURE_ALERTS_ENABLED=true
+/// FEATURE_WEBHOOK_DELIVERY_ENABLED=false
+/// FEATURE_GDPR_DELETION_ENABLED=true
+/// FEATURE_METRICS_ENABLED=true
+/// FEATURE_SIMULATION_ENABLED=false
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureFlags {
+    /// Ingest'te eşik kontrolü ve `POST /v1/sensors/replay`
+    pub alerts_enabled: bool,
+    /// Webhook kayıt endpoint'leri ve alarm teslimatları
+    pub webhook_delivery_enabled: bool,
+    /// `DELETE /v1/devices/{id}/data`
+    pub gdpr_deletion_enabled: bool,
+    /// `GET /metrics` (histogramlar bayrak kapalıyken de tutulur)
+    pub metrics_enabled: bool,
+    /// `POST /v1/sensors/{device_id}/simulate` (varsayılan kapalı; geliştirme/test ortamları)
+    pub simulation_enabled: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        Self {
+            alerts_enabled: true,
+            webhook_delivery_enabled: true,
+            gdpr_deletion_enabled: true,
+            metrics_enabled: true,
+            simulation_enabled: false,
+        }
+    }
+}
+
+/// Kısmi bayrak güncellemesi (`PATCH /v1/admin/features`, JSON dosyası, env var'lar)
+///
+/// Verilmeyen alanlar değişmez.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeatureFlagsPatch {
+    pub alerts_enabled: Option<bool>,
+    pub webhook_delivery_enabled: Option<bool>,
+    pub gdpr_deletion_enabled: Option<bool>,
+    pub metrics_enabled: Option<bool>,
+    pub simulation_enabled: Option<bool>,
+}
+
+impl FeatureFlags {
+    /// `FEATURE_FLAGS_FILE` ve `FEATURE_*` env var'larından yükle
+    ///
+    /// Okunamayan dosya veya geçersiz env var uyarıyla atlanır; o kaynaktaki
+    /// bayraklar varsayılan değerini korur.
+    pub fn load() -> Self {
+        let env: Vec<(String, String)> = std::env::vars().collect();
+        let file = env.iter().find(|(key, _)| key == "FEATURE_FLAGS_FILE").map(|(_, path)| path.clone());
+        let (flags, errors) = Self::from_sources(file.as_deref().map(Path::new), env);
+        for e in errors {
+            tracing::warn!("⚠️  Ignoring feature flags source: {e}");
+        }
+        flags
+    }
+
+    /// Varsayılanlara önce dosyayı, sonra env var'ları uygula
+    ///
+    /// Atlanan kaynakların hataları da döner.
+    fn from_sources(file: Option<&Path>, env: Vec<(String, String)>) -> (Self, Vec<String>) {
+        let mut flags = Self::default();
+        let mut errors = Vec::new();
+
+        if let Some(path) = file {
+            let patch = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|raw| serde_json::from_str::<FeatureFlagsPatch>(&raw).map_err(|e| e.to_string()));
+            match patch {
+                Ok(patch) => flags.apply(patch),
+                Err(e) => errors.push(format!("{}: {e}", path.display())),
+            }
+        }
+
+        // Sadece bilinen bayraklar (FEATURE_FLAGS_FILE ve diğer FEATURE_* değişkenleri atlanır)
+        let env = env.into_iter().filter(|(key, _)| {
+            key.strip_prefix("FEATURE_").is_some_and(|name| flags.get(&name.to_lowercase()).is_some())
+        });
+        match envy::prefixed("FEATURE_").from_iter::<_, FeatureFlagsPatch>(env) {
+            Ok(patch) => flags.apply(patch),
+            Err(e) => errors.push(e.to_string()),
+        }
+        (flags, errors)
+    }
+
+    /// Güncellemedeki verilen alanları uygula
+    pub fn apply(&mut self, patch: FeatureFlagsPatch) {
+        let FeatureFlagsPatch {
+            alerts_enabled,
+            webhook_delivery_enabled,
+            gdpr_deletion_enabled,
+            metrics_enabled,
+            simulation_enabled,
+        } = patch;
+        self.alerts_enabled = alerts_enabled.unwrap_or(self.alerts_enabled);
+        self.webhook_delivery_enabled = webhook_delivery_enabled.unwrap_or(self.webhook_delivery_enabled);
+        self.gdpr_deletion_enabled = gdpr_deletion_enabled.unwrap_or(self.gdpr_deletion_enabled);
+        self.metrics_enabled = metrics_enabled.unwrap_or(self.metrics_enabled);
+        self.simulation_enabled = simulation_enabled.unwrap_or(self.simulation_enabled);
+    }
+
+    /// Bayrağın değeri (bilinmeyen adlarda `None`)
+    pub fn get(&self, name: &str) -> Option<bool> {
+        match name {
+            "alerts_enabled" => Some(self.alerts_enabled),
+            "webhook_delivery_enabled" => Some(self.webhook_delivery_enabled),
+            "gdpr_deletion_enabled" => Some(self.gdpr_deletion_enabled),
+            "metrics_enabled" => Some(self.metrics_enabled),
+            "simulation_enabled" => Some(self.simulation_enabled),
+            _ => None,
+        }
+    }
+}
+
+/// Bayrak kapalıysa (veya bilinmiyorsa) 501 Not Implemented
+///
+/// Handler'lar istek başında çağırır:
+/// ```ignore
+/// check_feature(&*st.features.read().await, "alerts_enabled")?;
+/// ```
+pub fn check_feature(flags: &FeatureFlags, name: &str) -> Result<(), StatusCode> {
+    match flags.get(name) {
+        Some(true) => Ok(()),
+        Some(false) => Err(StatusCode::NOT_IMPLEMENTED),
+        None => {
+            tracing::error!("Unknown feature flag: {name}");
+            Err(StatusCode::NOT_IMPLEMENTED)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_defaults_are_enabled() {
+        let (flags, errors) = FeatureFlags::from_sources(None, env(&[("APP_PORT", "3000")]));
+        assert_eq!(flags, FeatureFlags::default());
+        assert!(errors.is_empty());
+        assert!(flags.alerts_enabled && flags.webhook_delivery_enabled && flags.gdpr_deletion_enabled && flags.metrics_enabled);
+        assert!(!flags.simulation_enabled);
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let path = std::env::temp_dir().join(format!("rustyflow-features-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{"alerts_enabled": false, "gdpr_deletion_enabled": false}"#).unwrap();
+
+        let (flags, errors) = FeatureFlags::from_sources(
+            Some(&path),
+            env(&[
+                ("FEATURE_FLAGS_FILE", path.to_str().unwrap()),
+                ("FEATURE_GDPR_DELETION_ENABLED", "true"),
+                ("FEATURE_METRICS_ENABLED", "false"),
+                ("FEATURE_SIMULATION_ENABLED", "true"),
+            ]),
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(
+            flags,
+            FeatureFlags {
+                alerts_enabled: false,
+                webhook_delivery_enabled: true,
+                gdpr_deletion_enabled: true,
+                metrics_enabled: false,
+                simulation_enabled: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_sources_are_skipped() {
+        let missing = std::env::temp_dir().join("rustyflow-features-missing.json");
+        let (flags, errors) = FeatureFlags::from_sources(Some(&missing), env(&[("FEATURE_ALERTS_ENABLED", "maybe")]));
+        assert_eq!(flags, FeatureFlags::default());
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_check_feature() {
+        let mut flags = FeatureFlags::default();
+        assert_eq!(check_feature(&flags, "alerts_enabled"), Ok(()));
+
+        flags.apply(FeatureFlagsPatch { alerts_enabled: Some(false), ..Default::default() });
+        assert_eq!(check_feature(&flags, "alerts_enabled"), Err(StatusCode::NOT_IMPLEMENTED));
+        assert_eq!(check_feature(&flags, "webhook_delivery_enabled"), Ok(()));
+        assert_eq!(check_feature(&flags, "no_such_flag"), Err(StatusCode::NOT_IMPLEMENTED));
+    }
+}
@@ -0,0 +1,211 @@
+//! İstek Zaman Aşımı ve Body Boyutu Sınırları
+//!
+//! Küçük worker havuzunu tek bir client'ın tüketmemesi için:
+//! - `REQUEST_TIMEOUT_SECS` içinde response üretmeyen istekler 408 döner
+//! - `MAX_JSON_BODY_BYTES`'ı aşan body'ler 413 döner (bağlantı koparılmaz)
+//! - Her iki hata da `application/problem+json` (RFC 9457) body'si taşır
+//!
+//! Streaming export route'u zaman aşımı katmanının dışında kalır; media
+//! upload route'u (`POST /v1/media/upload`) da zaman aşımına tabi değildir ve
+//! kendi (daha büyük) `MAX_UPLOAD_BYTES` sınırıyla `with_body_limit` kullanır.
+
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use serde::Serialize;
+use tokio::time::Duration;
+use tower_http::{limit::RequestBodyLimitLayer, timeout::TimeoutLayer};
+
+/// Problem detayı content type'ı
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// RFC 9457 problem detayı
+#[derive(Debug, Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    /// Toplu isteklerde reddedilen elemanlar (RFC 9457 extension member)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ItemError>,
+}
+
+/// Toplu istekte reddedilen tek eleman
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemError {
+    /// Request'teki sırası (0'dan başlar)
+    pub index: usize,
+    pub detail: String,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Self {
+            kind: "about:blank",
+            title: status.canonical_reason().unwrap_or("Error"),
+            status: status.as_u16(),
+            detail: detail.into(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Reddedilen elemanları ekle
+    pub fn with_errors(mut self, errors: Vec<ItemError>) -> Self {
+        self.errors = errors;
+        self
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = (status, Json(self)).into_response();
+        response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
+
+/// Route'lara zaman aşımı ekle (sadece bu çağrıdan önce eklenmiş route'lar)
+pub fn with_request_timeout<S>(router: Router<S>, timeout: Duration) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, timeout))
+}
+
+/// Request body'sini `max_bytes` ile sınırla
+///
+/// `Content-Length` sınırı aşıyorsa body hiç okunmadan, chunked body'lerde
+/// sınıra ulaşıldığında 413 döner.
+pub fn with_body_limit<S>(router: Router<S>, max_bytes: usize) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(RequestBodyLimitLayer::new(max_bytes))
+}
+
+/// 408/413 response'larını problem+json'a çevir
+///
+/// Timeout ve body limit katmanları (ve axum'un `Json` extractor'ı) düz
+/// metin veya boş body üretir; client her durumda aynı formatı görür.
+pub fn with_problem_responses<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(axum::middleware::map_response(problem_response))
+}
+
+async fn problem_response(response: Response) -> Response {
+    let detail = match response.status() {
+        StatusCode::REQUEST_TIMEOUT => "request did not complete in time",
+        StatusCode::PAYLOAD_TOO_LARGE => "request body exceeds the size limit",
+        _ => return response,
+    };
+    let is_problem = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|ct| ct.as_bytes().starts_with(PROBLEM_JSON.as_bytes()));
+    if is_problem {
+        return response;
+    }
+    Problem::new(response.status(), detail).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::{to_bytes, Body},
+        http::Request,
+        routing::{get, post},
+    };
+    use tower::ServiceExt;
+
+    use crate::{routes, state::AppState};
+
+    const TIMEOUT: Duration = Duration::from_millis(100);
+    const LIMIT: usize = 1024;
+
+    /// main'deki katman sırası: timeout (export hariç) → state → body limit → problem
+    fn app() -> Router {
+        let timed = Router::new()
+            .route("/v1/media", post(routes::media::create_media))
+            .route("/slow", get(slow));
+        let router = with_request_timeout(timed, TIMEOUT)
+            .route("/stream", get(slow))
+            .with_state(AppState::for_tests());
+        with_problem_responses(with_body_limit(router, LIMIT))
+    }
+
+    /// Zaman aşımından uzun süren handler
+    async fn slow() -> &'static str {
+        tokio::time::sleep(TIMEOUT * 5).await;
+        "done"
+    }
+
+    async fn problem(response: Response) -> serde_json::Value {
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    fn media_body(padding: usize) -> String {
+        serde_json::json!({
+            "name": "photo.jpg",
+            "path": format!("/uploads/{}", "x".repeat(padding)),
+            "mime_type": "image/jpeg",
+            "size_bytes": 1,
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_oversized_json_body_is_rejected_with_problem() {
+        let request = Request::post("/v1/media")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(media_body(LIMIT * 4)))
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = problem(response).await;
+        assert_eq!((body["status"].as_u64(), body["title"].as_str()), (Some(413), Some("Payload Too Large")));
+
+        // Content-Length'siz (chunked) body de sınırda kesilir
+        let chunks = media_body(LIMIT * 4).into_bytes().chunks(256).map(|c| Ok::<_, std::io::Error>(c.to_vec())).collect::<Vec<_>>();
+        let request = Request::post("/v1/media")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from_stream(futures_util::stream::iter(chunks)))
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(problem(response).await["status"], 413);
+
+        // Sınırın altındaki body normal işlenir
+        let request = Request::post("/v1/media")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(media_body(10)))
+            .unwrap();
+        assert_eq!(app().oneshot(request).await.unwrap().status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_times_out_with_problem() {
+        let request = Request::get("/slow").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+        let body = problem(response).await;
+        assert_eq!(body["status"], 408);
+        assert_eq!(body["type"], "about:blank");
+    }
+
+    #[tokio::test]
+    async fn test_route_added_after_timeout_is_exempt() {
+        let request = Request::get("/stream").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(to_bytes(response.into_body(), usize::MAX).await.unwrap(), "done");
+    }
+}
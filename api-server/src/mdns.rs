@@ -0,0 +1,62 @@
+//! mDNS Servis Yayını
+//!
+//! `MDNS_ADVERTISE=true` ise API server yerel ağda `_rustyflow._tcp.local.`
+//! servisi olarak yayınlanır. `API_SERVER_URL` ayarlanmamış edge agent'lar
+//! sunucuyu bu kayıt üzerinden bulur.
+
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+
+/// RustyFlow API server servis tipi (edge-agent `discovery` modülüyle aynı)
+pub const SERVICE_TYPE: &str = "_rustyflow._tcp.local.";
+
+/// Servisi yayınla
+///
+/// Adresler makinenin arayüzlerinden otomatik alınır. Dönen daemon
+/// düşürülene (veya `shutdown` çağrılana) kadar yayın sürer.
+pub fn advertise(port: u16) -> Result<ServiceDaemon, mdns_sd::Error> {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "rustyflow-api".into());
+    let daemon = ServiceDaemon::new()?;
+    let info = ServiceInfo::new(
+        SERVICE_TYPE,
+        &format!("rustyflow-api-{host}"),
+        &format!("{host}.local."),
+        "",
+        port,
+        &[("path", "/")][..],
+    )?
+    .enable_addr_auto();
+    daemon.register(info)?;
+    Ok(daemon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mdns_sd::ServiceEvent;
+    use tokio::time::{timeout, Duration};
+
+    #[tokio::test]
+    #[ignore = "requires multicast"]
+    async fn test_advertised_service_is_discoverable() {
+        let publisher = advertise(18302).unwrap();
+
+        let browser = ServiceDaemon::new().unwrap();
+        let events = browser.browse(SERVICE_TYPE).unwrap();
+        let port = timeout(Duration::from_secs(5), async {
+            loop {
+                match events.recv_async().await.unwrap() {
+                    ServiceEvent::ServiceResolved(service) if service.get_port() == 18302 => {
+                        return service.get_port();
+                    }
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .expect("service not discovered");
+
+        assert_eq!(port, 18302);
+        let _ = browser.shutdown();
+        let _ = publisher.shutdown();
+    }
+}
@@ -0,0 +1,273 @@
+//! Handler Gecikmesi ve Hata Oranı Metrikleri
+//!
+//! Her istek için method, route şablonu (`MatchedPath`, ör. `/v1/media/{id}`),
+//! status ve süre:
+//! - `http` tracing span'ine yazılır (request span'inin altında)
+//! - `SLOW_REQUEST_MS`'ten uzun sürdüyse WARN olarak loglanır
+//! - `http_request_duration_seconds` histogramına eklenir
+//!
+//! Long-poll ve streaming route'ları (`UNTIMED_ROUTES`) sadece span'e yazılır:
+//! süreleri bekleme süresidir, yavaşlık değil.
+//!
+//! Histogram `GET /metrics`'ten Prometheus text formatında okunur
+//! (`FEATURE_METRICS_ENABLED`; `tracing` feature'ı ile tokio runtime
+//! gauge'ları da eklenir, bkz. `runtime_metrics`). Hata oranı `status` etiketinden hesaplanır:
+//! `rate(http_request_duration_seconds_count{status=~"5.."}[5m])`.
+//! Etiket olarak gerçek path değil şablon kullanılır (ID başına seri oluşmaz);
+//! hiçbir route'a uymayan istekler `unmatched` altında toplanır.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use tokio::time::{Duration, Instant};
+use tracing::{field, Instrument};
+
+use crate::feature_flags::check_feature;
+use crate::state::AppState;
+
+/// Histogram kova üst sınırları (saniye, Prometheus varsayılanları)
+pub const LATENCY_BUCKETS_SECS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Hiçbir route'a uymayan isteklerin route etiketi
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Yavaş istek logu ve histograma yazılmayan route şablonları
+///
+/// Long-poll (yeni okuma gelene kadar bekler), SSE canlı akışı ve streaming export.
+pub const UNTIMED_ROUTES: [&str; 3] = [
+    "/api/sensors/realtime",
+    "/api/sensors/stream",
+    "/v1/sensors/{device_id}/{sensor_type}/export.influx",
+];
+
+/// Tek bir (method, route, status) serisinin histogramı
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Histogram {
+    /// `LATENCY_BUCKETS_SECS` sırasıyla, kümülatif olmayan sayaçlar
+    buckets: [u64; LATENCY_BUCKETS_SECS.len()],
+    count: u64,
+    sum_secs: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, secs: f64) {
+        if let Some(i) = LATENCY_BUCKETS_SECS.iter().position(|&bound| secs <= bound) {
+            self.buckets[i] += 1;
+        }
+        self.count += 1;
+        self.sum_secs += secs;
+    }
+}
+
+/// (method, route şablonu, status)
+type SeriesKey = (String, String, u16);
+
+/// İstek süresi histogramları
+///
+/// Klonlar aynı sayaçları paylaşır (middleware yazar, `/metrics` okur).
+#[derive(Debug, Clone, Default)]
+pub struct HttpMetrics {
+    series: Arc<Mutex<BTreeMap<SeriesKey, Histogram>>>,
+}
+
+impl HttpMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tamamlanan bir isteği kaydet
+    pub fn record(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let mut series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        series
+            .entry((method.to_string(), route.to_string(), status))
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Prometheus text exposition formatı (0.0.4)
+    pub fn render(&self) -> String {
+        let series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::from(
+            "# HELP http_request_duration_seconds HTTP request latency by method, route template and status.\n\
+             # TYPE http_request_duration_seconds histogram\n",
+        );
+        for ((method, route, status), histogram) in series.iter() {
+            let labels = format!(
+                "method=\"{}\",route=\"{}\",status=\"{status}\"",
+                escape_label(method),
+                escape_label(route)
+            );
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS_SECS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(out, "http_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {cumulative}");
+            }
+            let _ = writeln!(out, "http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}", histogram.count);
+            let _ = writeln!(out, "http_request_duration_seconds_sum{{{labels}}} {}", histogram.sum_secs);
+            let _ = writeln!(out, "http_request_duration_seconds_count{{{labels}}} {}", histogram.count);
+        }
+        out
+    }
+}
+
+/// Prometheus etiket değerindeki `\`, `"` ve satır sonlarını kaçır
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Route'lara gecikme/hata metriği ve yavaş istek logu ekle
+///
+/// Route'lar eklendikten sonra çağrılmalı: `Router::layer` katmanı her
+/// route'a ayrı uygular, `MatchedPath` ancak böyle okunabilir.
+pub fn with_http_metrics<S>(router: Router<S>, metrics: HttpMetrics, slow_request: Duration) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(middleware::from_fn(move |req: Request, next: Next| {
+        track(metrics.clone(), slow_request, req, next)
+    }))
+}
+
+async fn track(metrics: HttpMetrics, slow_request: Duration, req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_owned());
+    let span = tracing::info_span!(
+        "http",
+        method = %method,
+        route = %route,
+        status = field::Empty,
+        elapsed_ms = field::Empty,
+    );
+
+    let started = Instant::now();
+    let response = next.run(req).instrument(span.clone()).await;
+    let elapsed = started.elapsed();
+    let status = response.status().as_u16();
+
+    span.record("status", status);
+    span.record("elapsed_ms", elapsed.as_millis() as u64);
+    if UNTIMED_ROUTES.contains(&route.as_str()) {
+        return response;
+    }
+    if elapsed >= slow_request {
+        tracing::warn!(parent: &span, "🐢 Slow request: {method} {route} → {status} in {} ms", elapsed.as_millis());
+    }
+    metrics.record(&method, &route, status, elapsed);
+    response
+}
+
+/// Prometheus metrikleri
+///
+/// # HTTP
+/// `GET /metrics` (FEATURE_METRICS_ENABLED; kapalıysa 501)
+pub async fn metrics_handler(State(st): State<AppState>) -> Result<impl IntoResponse, StatusCode> {
+    check_feature(&*st.features.read().await, "metrics_enabled")?;
+    let body = st.http_metrics.render() + &st.webhook_queue.render_prometheus();
+    #[cfg(feature = "tracing")]
+    let body = body + &crate::runtime_metrics::render_prometheus();
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get};
+    use tower::ServiceExt;
+
+    fn app(metrics: HttpMetrics, slow_request: Duration) -> Router {
+        let router = Router::new()
+            .route("/v1/media/{id}", get(|| async { "ok" }))
+            .route(
+                "/api/sensors/realtime",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(30)).await;
+                    "[]"
+                }),
+            )
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(30)).await;
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }),
+            );
+        with_http_metrics(router, metrics, slow_request)
+    }
+
+    async fn get_status(app: &Router, uri: &str) -> StatusCode {
+        app.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_route_template_is_the_label() {
+        let metrics = HttpMetrics::new();
+        let app = app(metrics.clone(), Duration::from_secs(60));
+
+        assert_eq!(get_status(&app, "/v1/media/1").await, StatusCode::OK);
+        assert_eq!(get_status(&app, "/v1/media/2").await, StatusCode::OK);
+        assert_eq!(get_status(&app, "/nope").await, StatusCode::NOT_FOUND);
+
+        let text = metrics.render();
+        assert!(text.contains(
+            r#"http_request_duration_seconds_count{method="GET",route="/v1/media/{id}",status="200"} 2"#
+        ));
+        assert!(text.contains(r#"http_request_duration_seconds_count{method="GET",route="unmatched",status="404"} 1"#));
+        assert!(!text.contains("/v1/media/1"));
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_slow_request_is_logged() {
+        let metrics = HttpMetrics::new();
+        let app = app(metrics.clone(), Duration::from_millis(20));
+
+        assert_eq!(get_status(&app, "/v1/media/1").await, StatusCode::OK);
+        assert!(!logs_contain("Slow request"));
+
+        assert_eq!(get_status(&app, "/slow").await, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(logs_contain("Slow request: GET /slow → 500"));
+        assert!(logs_contain("route=/slow"));
+        // Hata oranı status etiketinden okunur
+        assert!(metrics.render().contains(r#"route="/slow",status="500""#));
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_long_poll_is_not_timed() {
+        let metrics = HttpMetrics::new();
+        let app = app(metrics.clone(), Duration::from_millis(20));
+
+        assert_eq!(get_status(&app, "/api/sensors/realtime").await, StatusCode::OK);
+        assert!(!logs_contain("Slow request"));
+        assert!(!metrics.render().contains("/api/sensors/realtime"));
+    }
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let metrics = HttpMetrics::new();
+        for ms in [3, 40, 40, 20_000] {
+            metrics.record("POST", "/v1/sensors/ingest", 202, Duration::from_millis(ms));
+        }
+        let text = metrics.render();
+        let labels = r#"method="POST",route="/v1/sensors/ingest",status="202""#;
+
+        assert!(text.contains(&format!("http_request_duration_seconds_bucket{{{labels},le=\"0.005\"}} 1\n")));
+        assert!(text.contains(&format!("http_request_duration_seconds_bucket{{{labels},le=\"0.05\"}} 3\n")));
+        assert!(text.contains(&format!("http_request_duration_seconds_bucket{{{labels},le=\"10\"}} 3\n")));
+        assert!(text.contains(&format!("http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 4\n")));
+        assert!(text.contains(&format!("http_request_duration_seconds_count{{{labels}}} 4\n")));
+        assert_eq!(escape_label("a\"b\\"), "a\\\"b\\\\");
+    }
+}
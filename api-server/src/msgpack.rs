@@ -0,0 +1,205 @@
+//! MessagePack İçerik Müzakeresi
+//!
+//! Gömülü HTTP client'ları bant genişliğinden tasarruf için JSON yerine
+//! MessagePack kullanabilir (`shared_types::msgpack`, alanlar adlarıyla):
+//! - Ingestion endpoint'leri `Content-Type: application/msgpack` body kabul
+//!   eder (`JsonOrMsgPack` extractor'ı); bozuk MessagePack 400 problem+json
+//! - Sensör listesi ve geçmişi `Accept: application/msgpack` ile MessagePack
+//!   döner (`with_msgpack_responses`); çevrilecek JSON `MAX_TRANSCODE_BYTES`'ı
+//!   aşarsa 406 problem+json döner (client JSON isteyip stream alabilir)
+//!
+//! Diğer durumlarda davranış JSON ile aynıdır.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use shared_types::msgpack::{MsgPack, CONTENT_TYPE as MSGPACK};
+
+use crate::limits::Problem;
+
+/// Eski client'ların kullandığı (kayıtsız) content type
+const X_MSGPACK: &str = "application/x-msgpack";
+
+/// MessagePack'e çevrilmek üzere belleğe alınan JSON response'un üst sınırı
+const MAX_TRANSCODE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Media type listesinde (`Content-Type`, `Accept`) MessagePack var mı?
+///
+/// Büyük/küçük harf ve `q` dışındaki parametreler yok sayılır; `q=0`
+/// "kabul edilmez" anlamına gelir (RFC 9110 §12.4.2).
+fn lists_msgpack(headers: &HeaderMap, name: header::HeaderName) -> bool {
+    headers.get_all(name).iter().filter_map(|value| value.to_str().ok()).any(|value| {
+        value.split(',').any(|range| {
+            let mut params = range.split(';');
+            let essence = params.next().unwrap_or_default().trim();
+            let acceptable = params
+                .filter_map(|param| param.split_once('='))
+                .filter(|(key, _)| key.trim().eq_ignore_ascii_case("q"))
+                .all(|(_, q)| q.trim().parse::<f32>().map_or(true, |q| q > 0.0));
+            acceptable && (essence.eq_ignore_ascii_case(MSGPACK) || essence.eq_ignore_ascii_case(X_MSGPACK))
+        })
+    })
+}
+
+/// JSON veya MessagePack request body'si (`Content-Type`'a göre)
+///
+/// MessagePack dışındaki content type'lar `Json` extractor'ına bırakılır
+/// (aynı 400/415/422 hataları). Body sınırı iki formatta da aynıdır.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonOrMsgPack<T>(pub T);
+
+impl<T, S> FromRequest<S> for JsonOrMsgPack<T>
+where
+    T: MsgPack,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !lists_msgpack(req.headers(), header::CONTENT_TYPE) {
+            let Json(value) = Json::<T>::from_request(req, state).await.map_err(IntoResponse::into_response)?;
+            return Ok(Self(value));
+        }
+        let bytes = Bytes::from_request(req, state).await.map_err(IntoResponse::into_response)?;
+        T::from_msgpack(&bytes).map(Self).map_err(|e| {
+            tracing::debug!("Rejected MessagePack body: {e}");
+            Problem::new(StatusCode::BAD_REQUEST, format!("invalid MessagePack body: {e}")).into_response()
+        })
+    }
+}
+
+/// Route'ların JSON response'larını `Accept: application/msgpack` ile MessagePack'e çevir
+///
+/// Sadece başarılı `application/json` response'lar çevrilir (hatalar
+/// problem+json kalır). Çevrilen body belleğe alınır; streaming history
+/// response'u bu durumda tek parça gönderilir. Body `MAX_TRANSCODE_BYTES`'ı
+/// aşarsa okuma kesilir ve 406 döner.
+pub fn with_msgpack_responses<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(middleware::from_fn(negotiate))
+}
+
+async fn negotiate(req: Request, next: Next) -> Response {
+    let wants_msgpack = lists_msgpack(req.headers(), header::ACCEPT);
+    let mut response = next.run(req).await;
+    response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|ct| ct.as_bytes().starts_with(b"application/json"));
+    if !wants_msgpack || !is_json || !response.status().is_success() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let encoded = match Limited::new(body, MAX_TRANSCODE_BYTES).collect().await {
+        Ok(collected) => serde_json::from_slice::<serde_json::Value>(&collected.to_bytes())
+            .map_err(|e| e.to_string())
+            .and_then(|value| value.to_msgpack().map_err(|e| e.to_string())),
+        Err(e) if e.is::<LengthLimitError>() => {
+            return Problem::new(
+                StatusCode::NOT_ACCEPTABLE,
+                format!("response exceeds {MAX_TRANSCODE_BYTES} bytes and cannot be sent as MessagePack; request application/json or a smaller limit"),
+            )
+            .into_response();
+        }
+        Err(e) => Err(e.to_string()),
+    };
+    match encoded {
+        Ok(bytes) => {
+            parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(MSGPACK));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(bytes))
+        }
+        Err(e) => {
+            tracing::error!("MessagePack response encoding failed: {e}");
+            Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "response could not be encoded as MessagePack").into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::{get, post};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Reading {
+        device_id: String,
+        value: f64,
+    }
+
+    fn app() -> Router {
+        let echo = Router::new().route("/echo", post(|JsonOrMsgPack(r): JsonOrMsgPack<Vec<Reading>>| async move { Json(r) }));
+        let negotiated = with_msgpack_responses(
+            Router::new()
+                .route("/list", get(|| async { Json(json!([{"device_id": "a", "value": 1.5}])) }))
+                .route("/missing", get(|| async { Problem::new(StatusCode::NOT_FOUND, "no such sensor") })),
+        );
+        echo.merge(negotiated)
+    }
+
+    #[test]
+    fn test_media_type_matching() {
+        let headers = |value: &'static str| HeaderMap::from_iter([(header::ACCEPT, HeaderValue::from_static(value))]);
+        assert!(lists_msgpack(&headers("application/msgpack"), header::ACCEPT));
+        assert!(lists_msgpack(&headers("application/json;q=0.5, Application/X-MsgPack"), header::ACCEPT));
+        assert!(!lists_msgpack(&headers("application/json, */*"), header::ACCEPT));
+        assert!(!lists_msgpack(&HeaderMap::new(), header::ACCEPT));
+        // q=0: kabul edilmez
+        assert!(!lists_msgpack(&headers("application/json, application/msgpack;q=0"), header::ACCEPT));
+        assert!(!lists_msgpack(&headers("application/msgpack; Q=0.000"), header::ACCEPT));
+        assert!(lists_msgpack(&headers("application/msgpack;q=0.1"), header::ACCEPT));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_response_is_not_acceptable() {
+        let oversized = with_msgpack_responses(Router::new().route(
+            "/big",
+            get(|| async { Json(vec!["x".repeat(1024); MAX_TRANSCODE_BYTES / 1024 + 1]) }),
+        ));
+        let request = Request::get("/big").header(header::ACCEPT, MSGPACK).body(Body::empty()).unwrap();
+        let response = oversized.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], crate::limits::PROBLEM_JSON);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_msgpack_is_problem_json() {
+        let request = Request::post("/echo")
+            .header(header::CONTENT_TYPE, MSGPACK)
+            .body(Body::from(vec![0x91, 0xc1]))
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], crate::limits::PROBLEM_JSON);
+        let body: serde_json::Value = serde_json::from_slice(&axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert!(body["detail"].as_str().unwrap().starts_with("invalid MessagePack body"));
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_json_clients_are_not_transcoded() {
+        let get = |uri: &str, accept: &'static str| Request::get(uri).header(header::ACCEPT, accept).body(Body::empty()).unwrap();
+
+        let response = app().oneshot(get("/list", "application/json")).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()[header::VARY], "accept");
+
+        let response = app().oneshot(get("/missing", MSGPACK)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], crate::limits::PROBLEM_JSON);
+    }
+}
@@ -0,0 +1,182 @@
+//! Redis Pub/Sub Okuma Akışı (`PUBSUB_ENABLED`)
+//!
+//! MQTT konuşmayan ve REST API'yi poll etmek istemeyen servisler (ör. ML
+//! servisi) için kabul edilen her okuma Redis kanallarına publish edilir:
+//! - `rustyflow:readings`: tüm okumalar
+//! - `rustyflow:readings:{device_id}`: tek cihazın okumaları
+//!
+//! Mesaj, `POST /api/sensors` formatındaki `SensorData` JSON'udur. Publish
+//! hataları loglanır ama okumanın kabulünü etkilemez. Örnek abone:
+//! `cargo run -p api-server --example readings_subscriber`.
+
+use redis::aio::ConnectionManager;
+
+use crate::routes::sensors::SensorData;
+
+/// Tüm okumaların yayınlandığı kanal
+pub const READINGS_CHANNEL: &str = "rustyflow:readings";
+
+/// Tek cihazın okumalarının yayınlandığı kanal
+pub fn device_channel(device_id: &str) -> String {
+    format!("{READINGS_CHANNEL}:{device_id}")
+}
+
+/// Okumayı genel ve cihaz kanalına publish et (tek round-trip)
+pub async fn publish_reading(conn: &mut ConnectionManager, data: &SensorData) {
+    let json = match serde_json::to_string(data) {
+        Ok(json) => json,
+        Err(e) => {
+            tracing::error!("JSON serialization error: {e}");
+            return;
+        }
+    };
+    let result = redis::pipe()
+        .publish(READINGS_CHANNEL, &json)
+        .ignore()
+        .publish(device_channel(&data.device_id), &json)
+        .ignore()
+        .query_async::<()>(conn)
+        .await;
+    match result {
+        Ok(()) => tracing::debug!("Reading published to {READINGS_CHANNEL}"),
+        Err(e) => tracing::warn!("Redis publish failed for {}: {e}", data.device_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{Request, StatusCode}, routing::post, Router};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tower::ServiceExt;
+
+    use crate::{routes, state::AppState};
+
+    type Commands = Arc<Mutex<Vec<Vec<String>>>>;
+
+    /// RESP konuşan sahte Redis: komutları kaydeder, PUBLISH'e `:1`
+    /// (veya `fail_publish` ise hata), diğer her şeye `+OK` döner
+    async fn fake_redis(fail_publish: bool) -> (String, Commands) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("redis://{}", listener.local_addr().unwrap());
+        let commands = Commands::default();
+        let recorded = commands.clone();
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let (read, mut write) = socket.into_split();
+                    let mut reader = BufReader::new(read);
+                    while let Some(command) = read_command(&mut reader).await {
+                        let reply: &[u8] = match command[0].to_uppercase().as_str() {
+                            "PUBLISH" if fail_publish => b"-ERR publish disabled\r\n",
+                            "PUBLISH" => b":1\r\n",
+                            _ => b"+OK\r\n",
+                        };
+                        recorded.lock().unwrap().push(command);
+                        write.write_all(reply).await.unwrap();
+                    }
+                });
+            }
+        });
+        (url, commands)
+    }
+
+    /// Tek bir RESP array komutunu oku (bağlantı kapanınca `None`)
+    async fn read_command<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> Option<Vec<String>> {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok()?;
+        let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            line.clear();
+            reader.read_line(&mut line).await.ok()?;
+            let len: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+            let mut buf = vec![0; len + 2];
+            reader.read_exact(&mut buf).await.ok()?;
+            buf.truncate(len);
+            args.push(String::from_utf8(buf).ok()?);
+        }
+        Some(args)
+    }
+
+    async fn sensors_app(redis_url: &str, pubsub_enabled: bool) -> Router {
+        let mut state = AppState::for_tests();
+        state.cfg.write().await.pubsub_enabled = pubsub_enabled;
+        let client = redis::Client::open(redis_url).unwrap();
+        state.redis = Some(ConnectionManager::new(client).await.unwrap());
+        Router::new()
+            .route("/api/sensors", post(routes::sensors::add_sensor_data))
+            .with_state(state)
+    }
+
+    /// Bir saat önce (retention penceresinin içinde, testin süresince sabit)
+    static RECENT: std::sync::LazyLock<String> =
+        std::sync::LazyLock::new(|| (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339());
+
+    fn reading(device_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "device_id": device_id,
+            "sensor_type": "temperature",
+            "value": 23.5,
+            "unit": "°C",
+            "timestamp": *RECENT,
+            "metadata": null,
+        })
+    }
+
+    async fn post_reading(app: &Router, device_id: &str) -> StatusCode {
+        let request = Request::post("/api/sensors")
+            .header("content-type", "application/json")
+            .body(Body::from(reading(device_id).to_string()))
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    fn publishes(commands: &Commands) -> Vec<(String, serde_json::Value)> {
+        commands
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c[0].eq_ignore_ascii_case("PUBLISH"))
+            .map(|c| (c[1].clone(), serde_json::from_str(&c[2]).unwrap()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_each_reading_published_to_global_and_device_channel() {
+        let (url, commands) = fake_redis(false).await;
+        let app = sensors_app(&url, true).await;
+        for device_id in ["edge-agent-001", "edge-agent-002"] {
+            assert_eq!(post_reading(&app, device_id).await, StatusCode::OK);
+        }
+
+        let published = publishes(&commands);
+        let channels: Vec<_> = published.iter().map(|(channel, _)| channel.as_str()).collect();
+        assert_eq!(
+            channels,
+            [READINGS_CHANNEL, "rustyflow:readings:edge-agent-001", READINGS_CHANNEL, "rustyflow:readings:edge-agent-002"]
+        );
+        assert_eq!(published[0].1, published[1].1);
+        // Sunucu alış zamanını ekler
+        let mut payload = published[0].1.clone();
+        assert!(payload.as_object_mut().unwrap().remove("received_at").is_some());
+        assert_eq!(payload, reading("edge-agent-001"));
+
+        // Kapalıyken publish edilmez
+        let (url, commands) = fake_redis(false).await;
+        assert_eq!(post_reading(&sensors_app(&url, false).await, "edge-agent-001").await, StatusCode::OK);
+        assert!(publishes(&commands).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publish_failure_does_not_fail_ingestion() {
+        let (url, commands) = fake_redis(true).await;
+        let app = sensors_app(&url, true).await;
+        assert_eq!(post_reading(&app, "edge-agent-001").await, StatusCode::OK);
+        assert_eq!(publishes(&commands).len(), 2);
+    }
+}
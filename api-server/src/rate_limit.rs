@@ -0,0 +1,88 @@
+//! IP Başına İstek Sınırlama (Rate Limiting)
+//!
+//! Sabit pencereli (fixed window) sayaç: her kaynak IP için pencere başına
+//! en fazla `limit` istek kabul edilir. Şu an sadece toplu okuma endpoint'i
+//! (`POST /v1/sensors/ingest`) kullanır.
+//!
+//! Kaynak IP TCP bağlantısından alınır; reverse proxy arkasında tüm istekler
+//! proxy'nin IP'sinden geliyormuş gibi sayılır.
+
+use std::{collections::HashMap, net::IpAddr, sync::Mutex};
+use tokio::time::{Duration, Instant};
+
+/// Bu kadar IP birikince süresi dolmuş pencereler temizlenir
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// IP başına sabit pencereli istek sayacı
+#[derive(Debug)]
+pub struct IpRateLimiter {
+    limit: u32,
+    window: Duration,
+    /// IP -> (pencere başlangıcı, penceredeki istek sayısı)
+    hits: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl IpRateLimiter {
+    /// `window` başına IP başına en fazla `limit` istek
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self { limit, window, hits: Mutex::new(HashMap::new()) }
+    }
+
+    /// İsteği say; limit aşıldıysa `false` döner
+    pub fn check(&self, ip: IpAddr) -> bool {
+        self.check_at(ip, Instant::now())
+    }
+
+    fn check_at(&self, ip: IpAddr, now: Instant) -> bool {
+        let mut hits = self.hits.lock().unwrap();
+        if hits.len() >= PRUNE_THRESHOLD {
+            hits.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        }
+
+        let (start, count) = hits.entry(ip).or_insert((now, 0));
+        if now.duration_since(*start) >= self.window {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.limit {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::from([192, 168, 1, last])
+    }
+
+    #[test]
+    fn test_limit_per_ip_and_window_reset() {
+        let limiter = IpRateLimiter::new(3, Duration::from_secs(1));
+        let now = Instant::now();
+
+        assert!((0..3).all(|_| limiter.check_at(ip(1), now)));
+        assert!(!limiter.check_at(ip(1), now + Duration::from_millis(500)));
+        // Diğer IP'ler etkilenmez
+        assert!(limiter.check_at(ip(2), now));
+
+        // Yeni pencere
+        assert!(limiter.check_at(ip(1), now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_expired_windows_are_pruned() {
+        let limiter = IpRateLimiter::new(1, Duration::from_secs(1));
+        let now = Instant::now();
+        for i in 0..PRUNE_THRESHOLD {
+            limiter.check_at(IpAddr::from((i as u32).to_be_bytes()), now);
+        }
+
+        assert!(limiter.check_at(ip(1), now + Duration::from_secs(2)));
+        assert_eq!(limiter.hits.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,247 @@
+//! Gerçek Zamanlı Sensör Akışı (Realtime Feed)
+//!
+//! `POST /api/sensors` ile gelen her okuma artan bir sıra numarası alır ve
+//! `tokio::sync::broadcast` kanalına gönderilir. Long-poll endpoint'i
+//! (`GET /api/sensors/realtime?since=N`) kanala abone olup yeni okumaları bekler.
+//!
+//! Kanal sadece "yeni okuma var" sinyalidir; okumalar ayrıca sınırlı bir
+//! backlog'da tutulur. Böylece iki poll arasında gelen okumalar kaybolmaz ve
+//! yavaş kalan (lagged) abonelik sıralamayı bozmaz.
+//!
+//! Aynı kanal `GET /api/sensors/stream` (Server-Sent Events) ile de sunulur;
+//! SSE event ID'si okumanın sıra numarasıdır, yeniden bağlanan client
+//! `Last-Event-ID` ile backlog'daki kaçırdığı okumaları alır.
+
+use std::{collections::VecDeque, sync::{Arc, Mutex}};
+
+use futures_util::{stream, Stream};
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::{timeout_at, Duration, Instant};
+
+use crate::routes::sensors::SensorData;
+
+/// Broadcast kanalı ve backlog kapasitesi
+pub const FEED_CAPACITY: usize = 256;
+
+/// Sıra numaralı sensör okuması
+#[derive(Debug, Clone, Serialize)]
+pub struct SensorEvent {
+    /// Sunucu başlatıldığından beri artan sıra numarası (1'den başlar)
+    pub sequence: u64,
+    #[serde(flatten)]
+    pub data: SensorData,
+}
+
+/// Paylaşılan sensör akışı
+///
+/// `Clone` ucuzdur; tüm kopyalar aynı kanalı ve backlog'u paylaşır.
+#[derive(Clone)]
+pub struct SensorFeed {
+    tx: broadcast::Sender<SensorEvent>,
+    /// Son `FEED_CAPACITY` okuma (sıra numarasına göre artan) + son sıra numarası
+    backlog: Arc<Mutex<(VecDeque<SensorEvent>, u64)>>,
+}
+
+impl Default for SensorFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SensorFeed {
+    /// Boş akış oluştur
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(FEED_CAPACITY);
+        Self { tx, backlog: Arc::new(Mutex::new((VecDeque::with_capacity(FEED_CAPACITY), 0))) }
+    }
+
+    /// Okumayı yayınla ve sıra numarasını döndür
+    ///
+    /// Numara backlog kilidi altında verilir; kanala da aynı sırayla gönderilir.
+    pub fn publish(&self, data: SensorData) -> u64 {
+        let mut guard = self.backlog.lock().unwrap();
+        let (events, latest) = &mut *guard;
+        *latest += 1;
+        let event = SensorEvent { sequence: *latest, data };
+
+        if events.len() == FEED_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(event.clone());
+        // Abone yoksa hata döner; okuma yine de backlog'da kalır
+        let _ = self.tx.send(event);
+        *latest
+    }
+
+    /// Kanala abone ol
+    pub fn subscribe(&self) -> broadcast::Receiver<SensorEvent> {
+        self.tx.subscribe()
+    }
+
+    /// En son verilen sıra numarası (henüz okuma yoksa 0)
+    pub fn latest(&self) -> u64 {
+        self.backlog.lock().unwrap().1
+    }
+
+    /// `since`'ten büyük en fazla `limit` okuma (eskiden yeniye)
+    pub fn since(&self, since: u64, limit: usize) -> Vec<SensorEvent> {
+        let guard = self.backlog.lock().unwrap();
+        guard.0.iter().filter(|e| e.sequence > since).take(limit).cloned().collect()
+    }
+
+    /// `since`'ten sonraki okumaları bekle (long-poll)
+    ///
+    /// Backlog'da yeni okuma varsa hemen döner. Yoksa `wait` süresince
+    /// kanalı dinler ve ilk okuma geldiğinde birikenleri (en fazla `limit`) döner.
+    /// Süre dolarsa boş liste döner.
+    pub async fn poll(&self, since: u64, limit: usize, wait: Duration) -> Vec<SensorEvent> {
+        // Backlog'dan önce abone ol: arada gelen okuma kaçırılmasın
+        let mut rx = self.subscribe();
+        let mut events = self.since(since, limit);
+        let deadline = Instant::now() + wait;
+
+        while events.is_empty() {
+            match timeout_at(deadline, rx.recv()).await {
+                Err(_) | Ok(Err(RecvError::Closed)) => break,
+                Ok(Ok(_)) | Ok(Err(RecvError::Lagged(_))) => events = self.since(since, limit),
+            }
+        }
+        events
+    }
+
+    /// Okumaların kesintisiz akışı (SSE)
+    ///
+    /// `since` verilirse önce backlog'daki daha yeni okumalar, ardından kanala
+    /// gelenler sırayla verilir; `None` ise sadece yeni okumalar. Yavaş kalan
+    /// (lagged) abonelik backlog'dan tamamlanır, backlog'dan da düşmüş
+    /// okumalar atlanır. Her okuma bir kez ve artan sırayla gelir.
+    pub fn stream(&self, since: Option<u64>) -> impl Stream<Item = SensorEvent> + Send + 'static {
+        // Backlog'dan önce abone ol: arada gelen okuma kaçırılmasın
+        let rx = self.subscribe();
+        let (pending, last) = match since {
+            Some(since) => (VecDeque::from(self.since(since, FEED_CAPACITY)), since),
+            None => (VecDeque::new(), self.latest()),
+        };
+
+        stream::unfold((self.clone(), rx, pending, last), |(feed, mut rx, mut pending, mut last)| async move {
+            loop {
+                if let Some(event) = pending.pop_front() {
+                    last = event.sequence;
+                    return Some((event, (feed, rx, pending, last)));
+                }
+                match rx.recv().await {
+                    Ok(event) if event.sequence > last => pending.push_back(event),
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(_)) => pending.extend(feed.since(last, FEED_CAPACITY)),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(value: f64) -> SensorData {
+        SensorData {
+            device_id: "dev-1".into(),
+            sensor_type: "temperature".into(),
+            value,
+            unit: "°C".into(),
+            timestamp: "2024-01-20T10:30:00Z".into(),
+            metadata: None,
+            received_at: None,
+            gateway_received_at: None,
+            stale: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_sequence_ordering() {
+        let feed = SensorFeed::new();
+        let mut rx = feed.subscribe();
+
+        let sequences: Vec<u64> = (0..5).map(|i| feed.publish(reading(i as f64))).collect();
+        assert_eq!(sequences, vec![1, 2, 3, 4, 5]);
+
+        for expected in 1..=5 {
+            let event = rx.recv().await.unwrap();
+            assert_eq!(event.sequence, expected);
+            assert_eq!(event.data.value, (expected - 1) as f64);
+        }
+        assert_eq!(feed.latest(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_since_filters_limits_and_evicts() {
+        let feed = SensorFeed::new();
+        for i in 0..(FEED_CAPACITY + 10) {
+            feed.publish(reading(i as f64));
+        }
+
+        let batch = feed.since(100, 3);
+        assert_eq!(batch.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![101, 102, 103]);
+
+        // En eski okumalar backlog'dan düşer
+        let all = feed.since(0, usize::MAX);
+        assert_eq!(all.len(), FEED_CAPACITY);
+        assert_eq!(all[0].sequence, 11);
+    }
+
+    #[tokio::test]
+    async fn test_stream_replays_backlog_then_follows_channel() {
+        use futures_util::StreamExt;
+
+        let feed = SensorFeed::new();
+        for i in 0..3 {
+            feed.publish(reading(i as f64));
+        }
+
+        // Sadece yeni okumalar
+        let mut live = Box::pin(feed.stream(None));
+        // Son görülen 1: 2 ve 3 backlog'dan, 4 kanaldan
+        let mut resumed = Box::pin(feed.stream(Some(1)));
+        feed.publish(reading(3.0));
+
+        assert_eq!(live.next().await.unwrap().sequence, 4);
+        let sequences: Vec<u64> = resumed.by_ref().take(3).map(|e| e.sequence).collect().await;
+        assert_eq!(sequences, vec![2, 3, 4]);
+
+        // Kanal kapasitesini aşan okumalar: lagged abonelik backlog'dan tamamlanır
+        for i in 0..(FEED_CAPACITY + 5) {
+            feed.publish(reading(i as f64));
+        }
+        let next = resumed.next().await.unwrap().sequence;
+        assert!(next > 4, "{next}");
+        let rest: Vec<u64> = resumed.take(FEED_CAPACITY - 1).map(|e| e.sequence).collect().await;
+        assert!(rest.windows(2).all(|w| w[0] + 1 == w[1]));
+        assert_eq!(rest.last(), Some(&feed.latest()));
+    }
+
+    #[tokio::test]
+    async fn test_poll_wakes_on_publish_and_times_out() {
+        let feed = SensorFeed::new();
+        feed.publish(reading(1.0));
+
+        // Backlog'da yeni okuma var: beklemeden döner
+        let events = feed.poll(0, 50, Duration::from_secs(5)).await;
+        assert_eq!(events.len(), 1);
+
+        // Yeni okuma yok: süre dolunca boş döner
+        assert!(feed.poll(1, 50, Duration::from_millis(20)).await.is_empty());
+
+        // Bekleme sırasında gelen okuma
+        let publisher = feed.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            publisher.publish(reading(2.0));
+            publisher.publish(reading(3.0));
+        });
+        let events = feed.poll(1, 50, Duration::from_secs(5)).await;
+        assert_eq!(events.first().map(|e| e.sequence), Some(2));
+        assert!(events.windows(2).all(|w| w[0].sequence < w[1].sequence));
+    }
+}
@@ -0,0 +1,81 @@
+//! İstek ID'si (Request ID) Middleware'i
+//!
+//! Bir sensör okumasının edge agent → gateway → api-server yolculuğunu
+//! loglarda eşleştirmek için kullanılır.
+//! - Gelen `X-Request-Id` header'ı korunur (gateway okumanın `trace_id`'sini taşır)
+//! - Yoksa yeni bir UUID üretilir
+//! - ID, request'in tracing span'ine eklenir (span içindeki tüm loglarda görünür)
+//! - Response'a aynı header ile geri yazılır
+
+use axum::{http::Request, Router};
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::TraceLayer,
+};
+
+/// Request ID header'ı
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Router'a request ID katmanlarını ekle
+///
+/// Katman sırası (dıştan içe): ID ata → span aç → response'a ID yaz.
+pub fn with_request_id<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // `.layer()` en son ekleneni en dışa koyar; bu yüzden içten dışa ekleniyor
+    router
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(TraceLayer::new_for_http().make_span_with(|req: &Request<_>| {
+            let request_id = req
+                .headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("-");
+            tracing::info_span!(
+                "request",
+                request_id = %request_id,
+                method = %req.method(),
+                uri = %req.uri(),
+            )
+        }))
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get};
+    use tower::ServiceExt;
+
+    use crate::{routes, state::AppState};
+
+    fn app() -> Router {
+        with_request_id(
+            Router::new()
+                .route("/health", get(routes::health::health))
+                .with_state(AppState::for_tests()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_incoming_request_id_is_echoed() {
+        let id = "3f2b8c1e-0000-4000-8000-000000000001";
+        let request = Request::get("/health")
+            .header(REQUEST_ID_HEADER, id)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], id);
+    }
+
+    #[tokio::test]
+    async fn test_missing_request_id_is_generated() {
+        let request = Request::get("/health").body(Body::empty()).unwrap();
+
+        let response = app().oneshot(request).await.unwrap();
+        let id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(id).is_ok());
+    }
+}
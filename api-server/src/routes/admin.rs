@@ -0,0 +1,239 @@
+//! Yönetim Endpoint'leri (Admin Routes)
+//!
+//! Çalışan sunucuyu restart etmeden yöneten endpoint'ler. Hepsi
+//! `Authorization: Bearer <ADMIN_TOKEN>` ister; `ADMIN_TOKEN` ayarlı değilse
+//! kapalıdır. `AUTH_ENABLED=true` iken `admin` rolündeki JWT'ler de kabul
+//! edilir (bkz. `crate::auth`).
+
+use axum::{
+    extract::{Extension, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
+use serde::Serialize;
+
+use crate::auth::{token_matches, Role};
+use crate::config::{Config, SanitizedConfig};
+use crate::feature_flags::{FeatureFlags, FeatureFlagsPatch};
+use crate::state::AppState;
+
+/// Yeniden yükleme sonucu
+#[derive(Debug, Serialize)]
+pub struct ConfigReload {
+    /// Artık geçerli olan yapılandırma
+    pub config: SanitizedConfig,
+    /// Değişmiş ama restart gerektirdiği için uygulanmamış alanlar
+    pub restart_required: Vec<&'static str>,
+}
+
+/// Yapılandırmayı yeniden yükle
+///
+/// # HTTP
+/// `POST /v1/admin/reload-config` (`Authorization: Bearer <ADMIN_TOKEN>`)
+///
+/// Config dosyası, `.env`, ortam değişkenleri ve komut satırı flag'leri
+/// başlangıçtaki gibi okunur (`Config::reload`; flag'ler env var'ları, env
+/// var'lar dosyayı ezer). `.env`'deki değerler süreçteki eskilerini ezer.
+/// Sadece yeniden yüklenebilen alanlar uygulanır (bkz. `Config`); log
+/// seviyesi anında değişir. Dosya okunamaz veya bir env var parse
+/// edilemezse eski yapılandırma olduğu gibi kalır.
+///
+/// Rol katmanı çağıranı `admin` olarak doğruladıysa `ADMIN_TOKEN` ayrıca
+/// kontrol edilmez.
+///
+/// # Response
+/// ```json
+/// {
+///   "config": {"app_port": 3000, "has_database_url": true, "log_level": "debug", ...},
+///   "restart_required": ["app_port"]
+/// }
+/// ```
+///
+/// # Status Kodları
+/// - 200 OK: Yeni yapılandırma uygulandı
+/// - 401 Unauthorized: Token yok veya yanlış
+/// - 403 Forbidden: `ADMIN_TOKEN` ayarlı değil
+/// - 422 Unprocessable Entity: Yeni yapılandırma okunamadı veya geçersiz (hiçbir şey değişmez)
+#[tracing::instrument(skip_all)]
+pub async fn reload_config(
+    State(st): State<AppState>,
+    caller: Option<Extension<Role>>,
+    headers: HeaderMap,
+) -> Result<Json<ConfigReload>, StatusCode> {
+    if caller.map(|Extension(role)| role) != Some(Role::Admin) {
+        let admin_token = st.cfg.read().await.admin_token.clone();
+        authorize(admin_token.as_deref(), &headers)?;
+    }
+    let new = Config::reload(&st.config_overrides).map_err(|e| {
+        tracing::warn!("Config reload rejected: {e}");
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+    apply(&st, new).await.map(Json)
+}
+
+/// Özellik bayraklarını göster
+///
+/// # HTTP
+/// `GET /v1/admin/features`
+///
+/// # Response
+/// ```json
+/// {"alerts_enabled": true, "webhook_delivery_enabled": false, "gdpr_deletion_enabled": true, "metrics_enabled": true, "simulation_enabled": false}
+/// ```
+pub async fn get_features(State(st): State<AppState>) -> Json<FeatureFlags> {
+    Json(*st.features.read().await)
+}
+
+/// Özellik bayraklarını değiştir
+///
+/// # HTTP
+/// `PATCH /v1/admin/features` (`Authorization: Bearer <ADMIN_TOKEN>`)
+///
+/// Sadece verilen bayraklar değişir ve bir sonraki istekte etkili olur.
+/// Değişiklik kalıcı değildir; restart'ta `FEATURE_*` yeniden okunur.
+///
+/// # Request Body
+/// ```json
+/// {"webhook_delivery_enabled": false}
+/// ```
+///
+/// # Response
+/// Güncel bayraklar (`GET /v1/admin/features` gibi)
+///
+/// # Status Kodları
+/// - 200 OK: Bayraklar güncellendi
+/// - 401 Unauthorized: Token yok veya yanlış
+/// - 403 Forbidden: `ADMIN_TOKEN` ayarlı değil
+/// - 422 Unprocessable Entity: Bilinmeyen bayrak veya bool olmayan değer
+#[tracing::instrument(skip_all)]
+pub async fn update_features(
+    State(st): State<AppState>,
+    caller: Option<Extension<Role>>,
+    headers: HeaderMap,
+    Json(patch): Json<FeatureFlagsPatch>,
+) -> Result<Json<FeatureFlags>, StatusCode> {
+    if caller.map(|Extension(role)| role) != Some(Role::Admin) {
+        let admin_token = st.cfg.read().await.admin_token.clone();
+        authorize(admin_token.as_deref(), &headers)?;
+    }
+    let flags = {
+        let mut flags = st.features.write().await;
+        flags.apply(patch);
+        *flags
+    };
+    tracing::info!("🚩 Feature flags updated: {:?}", flags);
+    Ok(Json(flags))
+}
+
+/// Bearer token'ı `ADMIN_TOKEN` ile sabit zamanda karşılaştır
+fn authorize(admin_token: Option<&str>, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let Some(expected) = admin_token.filter(|t| !t.is_empty()) else {
+        return Err(StatusCode::FORBIDDEN);
+    };
+    let given = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if !given.is_some_and(|given| token_matches(expected, given)) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(())
+}
+
+/// Yeni yapılandırmayı doğrula ve uygula
+///
+/// Write lock sadece atama süresince tutulur; doğrulama ve log filtresi
+/// değişimi lock dışında yapılır, böylece okuyan handler'lar beklemez.
+async fn apply(st: &AppState, new: Config) -> Result<ConfigReload, StatusCode> {
+    let current = st.cfg.read().await.clone();
+    let (cfg, restart_required) = current.reloaded(new).map_err(|e| {
+        tracing::warn!("Config reload rejected: {e}");
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+
+    if let Some(handle) = &st.log_filter {
+        handle
+            .reload(tracing_subscriber::EnvFilter::new(&cfg.log_level))
+            .map_err(|e| {
+                tracing::error!("Log filter reload failed: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    let report = ConfigReload { config: cfg.sanitized(), restart_required };
+    *st.cfg.write().await = cfg;
+    tracing::info!("🔄 Config reloaded (restart required for: {:?})", report.restart_required);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+    use tracing_subscriber::{layer::SubscriberExt, reload, EnvFilter};
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_authorize_requires_configured_token() {
+        assert_eq!(authorize(None, &bearer("secret")), Err(StatusCode::FORBIDDEN));
+        assert_eq!(authorize(Some(""), &bearer("")), Err(StatusCode::FORBIDDEN));
+        assert_eq!(authorize(Some("secret"), &HeaderMap::new()), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(authorize(Some("secret"), &bearer("wrong")), Err(StatusCode::UNAUTHORIZED));
+        assert_eq!(authorize(Some("secret"), &bearer("secret")), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_config_is_not_applied() {
+        let st = AppState::for_tests();
+        let before = st.cfg.read().await.clone();
+        let bad = Config { log_level: "info,[".into(), ..before.clone() };
+
+        assert_eq!(apply(&st, bad).await.unwrap_err(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(*st.cfg.read().await, before);
+    }
+
+    #[tokio::test]
+    async fn test_reload_does_not_block_readers_and_applies_log_level() {
+        // Global subscriber kurmadan: filtre sadece bu dispatcher'da
+        let (filter, handle) = reload::Layer::new(EnvFilter::new("info"));
+        let dispatch = tracing::Dispatch::new(tracing_subscriber::registry().with(filter));
+        let debug_enabled = || tracing::dispatcher::with_default(&dispatch, || tracing::enabled!(tracing::Level::DEBUG));
+        assert!(!debug_enabled());
+
+        let st = AppState { log_filter: Some(handle), ..AppState::for_tests() };
+        let stop = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let (cfg, stop) = (st.cfg.clone(), stop.clone());
+                tokio::spawn(async move {
+                    let mut reads = 0u64;
+                    while !stop.load(Ordering::Relaxed) {
+                        drop(cfg.read().await.log_level.clone());
+                        reads += 1;
+                        tokio::task::yield_now().await;
+                    }
+                    reads
+                })
+            })
+            .collect();
+
+        for level in ["debug", "warn", "debug"] {
+            let new = Config { log_level: level.into(), ..st.cfg.read().await.clone() };
+            apply(&st, new).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+        stop.store(true, Ordering::Relaxed);
+
+        // Okuyucular reload sırasında da ilerler (süre ölçülmez: CI'da zamanlama dalgalanır)
+        for reader in readers {
+            assert!(reader.await.unwrap() > 0);
+        }
+        assert_eq!(st.cfg.read().await.log_level, "debug");
+        assert!(debug_enabled());
+    }
+}
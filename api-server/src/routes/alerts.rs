@@ -0,0 +1,206 @@
+//! Alarm Olayı Endpoint'leri
+//!
+//! Ingest yolu eşik aşımlarını `sensor_alerts` tablosuna yazar; dashboard'un
+//! alarm paneli aktif (onaylanmamış) alarmları buradan çeker ve onaylar.
+//!
+//! # Endpoint'ler
+//! - GET /v1/alerts/events - Alarmları listele (`?active=true&limit=`)
+//! - POST /v1/alerts/events/{id}/ack - Alarmı onayla (admin rolü)
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::state::AppState;
+use shared_types::SensorAlert;
+
+/// `GET` varsayılan satır sayısı
+const DEFAULT_ALERTS_LIMIT: i64 = 100;
+
+/// `GET` en fazla satır sayısı
+const MAX_ALERTS_LIMIT: i64 = 1000;
+
+/// `GET /v1/alerts/events` parametreleri
+///
+/// Örnek: `?active=true&limit=50`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AlertEventParams {
+    /// Sadece onaylanmamış alarmlar
+    #[serde(default)]
+    pub active: bool,
+    /// Maksimum satır (varsayılan 100, en fazla 1000)
+    pub limit: Option<i64>,
+}
+
+/// Alarm ve onay durumu
+///
+/// ```json
+/// {"id": "…", "device_id": "dev-1", "sensor_type": "temperature", "value": 41.2,
+///  "threshold": {"min": null, "max": 35.0}, "message": "temperature 41.2 above max 35",
+///  "triggered_at": "2024-01-20T10:30:00Z", "acknowledged": false}
+/// ```
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AlertEvent {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub alert: SensorAlert,
+    pub acknowledged: bool,
+}
+
+/// Alarmları listele (yeniden eskiye)
+///
+/// # HTTP
+/// `GET /v1/alerts/events?active=true&limit=`
+///
+/// # Error Responses
+/// - 400 Bad Request: geçersiz `limit`
+/// - 500 Internal Server Error: Database hatası
+/// - 501 Not Implemented: PostgreSQL bağlı değil
+#[tracing::instrument(skip(st), err)]
+pub async fn list_alert_events(
+    State(st): State<AppState>,
+    Query(params): Query<AlertEventParams>,
+) -> Result<Json<Vec<AlertEvent>>, StatusCode> {
+    let limit = params.limit.unwrap_or(DEFAULT_ALERTS_LIMIT);
+    if !(1..=MAX_ALERTS_LIMIT).contains(&limit) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let db = &st.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+
+    let events = query_alert_events(db, params.active, limit).await.map_err(|e| {
+        tracing::error!("Alert events query failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(events))
+}
+
+async fn query_alert_events(db: &PgPool, active: bool, limit: i64) -> sqlx::Result<Vec<AlertEvent>> {
+    sqlx::query_as::<_, AlertEvent>(
+        "SELECT id, device_id, sensor_type, value, threshold_min, threshold_max, message, triggered_at,
+                acknowledged_at IS NOT NULL AS acknowledged
+         FROM sensor_alerts
+         WHERE NOT $1 OR acknowledged_at IS NULL
+         ORDER BY triggered_at DESC, id
+         LIMIT $2",
+    )
+    .bind(active)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+}
+
+/// Alarmı onayla; onaylanmış alarm tekrar onaylanabilir (ilk zaman korunur)
+///
+/// # HTTP
+/// `POST /v1/alerts/events/{id}/ack`
+///
+/// # Response
+/// 204 No Content
+///
+/// # Error Responses
+/// - 404 Not Found: Alarm yok
+/// - 500 Internal Server Error: Database hatası
+/// - 501 Not Implemented: PostgreSQL bağlı değil
+#[tracing::instrument(skip(st), err)]
+pub async fn acknowledge_alert_event(State(st): State<AppState>, Path(id): Path<Uuid>) -> Result<StatusCode, StatusCode> {
+    let db = &st.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+
+    let result = sqlx::query("UPDATE sensor_alerts SET acknowledged_at = COALESCE(acknowledged_at, now()) WHERE id = $1")
+        .bind(id)
+        .execute(db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Acknowledging alert {id} failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if result.rows_affected() == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::alert::{check_threshold, AlertThreshold};
+
+    #[tokio::test]
+    async fn test_without_database() {
+        let st = AppState::for_tests();
+        let bad_limit = AlertEventParams { active: true, limit: Some(0) };
+        let result = list_alert_events(State(st.clone()), Query(bad_limit)).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+        let result = list_alert_events(State(st.clone()), Query(AlertEventParams::default())).await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+        let result = acknowledge_alert_event(State(st), Path(Uuid::new_v4())).await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[test]
+    fn test_event_serializes_flat() {
+        let threshold = AlertThreshold { min: None, max: Some(35.0) };
+        let alert = check_threshold("dev-1", "temperature", 41.2, &threshold).unwrap();
+        let json = serde_json::to_value(AlertEvent { alert, acknowledged: false }).unwrap();
+        assert_eq!(json["device_id"], "dev-1");
+        assert_eq!(json["threshold"]["max"], 35.0);
+        assert_eq!(json["acknowledged"], false);
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_acknowledged_alert_leaves_active_list() {
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        let device = format!("alerts-{}", Uuid::new_v4());
+        let threshold = AlertThreshold { min: Some(10.0), max: None };
+        let alert = check_threshold(&device, "temperature", 4.5, &threshold).unwrap();
+        sqlx::query(
+            "INSERT INTO sensor_alerts (id, device_id, sensor_type, value, threshold_min, threshold_max, message, triggered_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(alert.id)
+        .bind(&alert.device_id)
+        .bind(&alert.sensor_type)
+        .bind(alert.value)
+        .bind(alert.threshold.min)
+        .bind(alert.threshold.max)
+        .bind(&alert.message)
+        .bind(alert.triggered_at)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let list = |active: bool| {
+            let st = st.clone();
+            let device = device.clone();
+            async move {
+                let params = AlertEventParams { active, limit: Some(MAX_ALERTS_LIMIT) };
+                let Json(events) = list_alert_events(State(st), Query(params)).await.unwrap();
+                events.into_iter().filter(|e| e.alert.device_id == device).collect::<Vec<_>>()
+            }
+        };
+        let active = list(true).await;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].alert.threshold, threshold);
+        assert!(!active[0].acknowledged);
+
+        assert_eq!(acknowledge_alert_event(State(st.clone()), Path(alert.id)).await, Ok(StatusCode::NO_CONTENT));
+        assert_eq!(acknowledge_alert_event(State(st.clone()), Path(alert.id)).await, Ok(StatusCode::NO_CONTENT));
+        assert!(list(true).await.is_empty());
+        let all = list(false).await;
+        assert!(all.len() == 1 && all[0].acknowledged);
+        assert_eq!(acknowledge_alert_event(State(st), Path(Uuid::new_v4())).await, Err(StatusCode::NOT_FOUND));
+
+        sqlx::query("DELETE FROM sensor_alerts WHERE device_id = $1").bind(&device).execute(&db).await.unwrap();
+    }
+}
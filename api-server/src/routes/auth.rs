@@ -0,0 +1,120 @@
+//! Token Endpoint'i (Auth Routes)
+//!
+//! `AUTH_ENABLED=true` iken API'yi kullanacak client'lara rol token'ı verir
+//! (bkz. `crate::auth`). Token'ları sadece admin verebilir.
+//!
+//! # Endpoint'ler
+//! - POST /v1/auth/token - `viewer`, `device` veya `admin` token'ı al
+
+use axum::{extract::State, http::{HeaderMap, StatusCode}, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::{self, Claims, Role};
+use crate::state::AppState;
+
+/// Token isteği
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    pub requested_role: Role,
+    /// `device` rolü için zorunlu: token'ın ait olduğu cihaz
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
+}
+
+/// Verilen token
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub token: String,
+    pub role: Role,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Rol token'ı al
+///
+/// # HTTP
+/// `POST /v1/auth/token`
+///
+/// # Request Body
+/// ```json
+/// {"requested_role": "viewer"}
+/// ```
+///
+/// Her rol için çağıran admin olmalıdır (`Authorization: Bearer <ADMIN_TOKEN>`
+/// veya admin token'ı); aksi halde endpoint açık olduğu için herkes kendine
+/// `viewer` token'ı alıp okuma endpoint'lerine erişebilirdi. `device` token'ı
+/// `device_id` ister ve 5 dakika, diğerleri 1 saat geçerlidir.
+///
+/// # Response (200 OK)
+/// ```json
+/// {"token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...", "role": "viewer", "expires_at": "2024-11-13T22:30:00Z"}
+/// ```
+///
+/// # Error Responses
+/// - 400 Bad Request: `device` rolü için `device_id` yok
+/// - 401 Unauthorized: Token yok veya geçersiz
+/// - 403 Forbidden: Çağıran admin değil
+#[tracing::instrument(skip(st, headers), err)]
+pub async fn issue_token(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<TokenRequest>,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    let admin_token = st.cfg.read().await.admin_token.clone();
+    match auth::caller_role(&st.jwt_secret, admin_token.as_deref(), &headers) {
+        Some(Role::Admin) => {}
+        Some(_) => return Err(StatusCode::FORBIDDEN),
+        None => return Err(StatusCode::UNAUTHORIZED),
+    }
+
+    let issued_at = Utc::now();
+    let claims = match request.requested_role {
+        Role::Device => Claims::for_device(request.device_id.ok_or(StatusCode::BAD_REQUEST)?, issued_at),
+        role => Claims::for_role(role, role.as_str(), issued_at),
+    };
+    let expires_at = DateTime::from_timestamp(claims.exp, 0).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(TokenResponse { token: auth::issue(&st.jwt_secret, &claims), role: claims.role, expires_at }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header;
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+        headers
+    }
+
+    async fn request(st: &AppState, headers: HeaderMap, role: Role, device_id: Option<Uuid>) -> Result<TokenResponse, StatusCode> {
+        let request = TokenRequest { requested_role: role, device_id };
+        issue_token(State(st.clone()), headers, Json(request)).await.map(|Json(r)| r)
+    }
+
+    #[tokio::test]
+    async fn test_every_role_requires_admin() {
+        let st = AppState::for_tests();
+        st.cfg.write().await.admin_token = Some("admin-token".into());
+
+        let viewer = request(&st, bearer("admin-token"), Role::Viewer, None).await.unwrap();
+        let claims = auth::verify("test-secret", &viewer.token, Utc::now()).unwrap();
+        assert_eq!(claims.role, Role::Viewer);
+
+        for role in [Role::Viewer, Role::Admin, Role::Device] {
+            let device_id = Some(Uuid::new_v4());
+            assert_eq!(request(&st, HeaderMap::new(), role, device_id).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+            assert_eq!(request(&st, bearer(&viewer.token), role, device_id).await.unwrap_err(), StatusCode::FORBIDDEN);
+            assert_eq!(request(&st, bearer("admin-token"), role, device_id).await.unwrap().role, role);
+        }
+
+        // Admin token'ı ile alınan admin token'ı da yetkilidir
+        let admin = request(&st, bearer("admin-token"), Role::Admin, None).await.unwrap();
+        let device_id = Uuid::new_v4();
+        let device = request(&st, bearer(&admin.token), Role::Device, Some(device_id)).await.unwrap();
+        let claims = auth::verify("test-secret", &device.token, Utc::now()).unwrap();
+        assert_eq!(claims.scope, format!("device:{device_id}"));
+        assert_eq!(request(&st, bearer(&admin.token), Role::Device, None).await.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,443 @@
+//! Komut Denetim Kaydı (Command Audit Log)
+//!
+//! "Cihaz X'e röleyi açmasını kim, ne zaman söyledi?" sorusunun cevabı.
+//! `devices::dispatch_command` kabul edilen her komutu veren kimlikle
+//! (`auth::Issuer`) kaydeder; gateway'in ilettiği yanıtlar
+//! (`POST /v1/devices/{id}/commands/ack`) `correlation_id` ile eşleştirilip
+//! durum geçişi olarak eklenir.
+//! - PostgreSQL: `device_commands` tablosu (`issuer`, `transitions` ve son
+//!   yanıtın `ack_*` kolonları); `COMMAND_AUDIT_RETENTION_DAYS`'ten eski
+//!   satırları `background::prune_command_audit` siler
+//! - PostgreSQL yoksa: son `MAX_IN_MEMORY_COMMANDS` komut (`AppState::command_audit`)
+//!
+//! Aynı kayıt iki görünümle sunulur: tarihçesiyle denetim kaydı
+//! (`/commands`) ve son sonuç (`/commands/history`). Kayıt cihaz
+//! kaldırılınca korunur, yanıtı beklenen komutlar `cancelled` olur; GDPR
+//! silmesi (`DELETE /v1/devices/{id}/data`) kaydı da siler.
+//!
+//! # Endpoint'ler
+//! - GET /v1/devices/{id}/commands?from=&to=&status=&limit=&offset=
+
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::{types::Json as SqlJson, PgConnection, PgPool};
+use uuid::Uuid;
+
+use shared_types::messages::DeviceCommandResponse;
+use shared_types::{AckStatus, CommandAuditEntry, CommandStatus, CommandTransition};
+
+use crate::routes::devices::MAX_COMMAND_HISTORY_LIMIT;
+use crate::state::AppState;
+
+/// In-memory halkada tutulan en fazla komut (en eskisi düşer)
+pub const MAX_IN_MEMORY_COMMANDS: usize = 1000;
+
+/// `GET /v1/devices/{id}/commands` varsayılan sayfa boyutu
+const DEFAULT_AUDIT_LIMIT: i64 = 50;
+
+/// Kabul edilen komutu kaydet (`dispatched`, yanıt bekleniyor)
+///
+/// PostgreSQL yoksa halkaya eklenir; hata sadece PostgreSQL'den gelir.
+pub async fn record(st: &AppState, db: Option<&PgPool>, entry: CommandAuditEntry) -> sqlx::Result<()> {
+    let Some(db) = db else {
+        let mut ring = st.command_audit.write().await;
+        if ring.len() >= MAX_IN_MEMORY_COMMANDS {
+            ring.pop_front();
+        }
+        ring.push_back(entry);
+        return Ok(());
+    };
+    sqlx::query(
+        "INSERT INTO device_commands
+            (correlation_id, device_id, command_type, command_name, parameters, issuer, dispatched_at, transitions)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         ON CONFLICT (correlation_id) DO NOTHING",
+    )
+    .bind(entry.correlation_id)
+    .bind(entry.device_id)
+    .bind(&entry.command_type)
+    .bind(&entry.command_name)
+    .bind(&entry.parameters)
+    .bind(&entry.issuer)
+    .bind(entry.issued_at)
+    .bind(SqlJson(&entry.transitions))
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Publish edilemeyen (kabul edilmeyen) komutun kaydını geri al
+pub async fn discard(st: &AppState, db: Option<&PgPool>, correlation_id: Uuid) {
+    let Some(db) = db else {
+        st.command_audit.write().await.retain(|entry| entry.correlation_id != correlation_id);
+        return;
+    };
+    if let Err(e) = sqlx::query("DELETE FROM device_commands WHERE correlation_id = $1").bind(correlation_id).execute(db).await {
+        tracing::warn!("Discarding audit of command {correlation_id} failed: {e}");
+    }
+}
+
+/// Cihazın yanıtını komutun durum geçişi olarak ekle
+///
+/// Son yanıt `ack_*` kolonlarına da yazılır. Aynı yanıtın tekrarı (QoS 1)
+/// hiçbir şeyi değiştirmez. Bu cihaza bu `correlation_id` ile kaydedilmiş
+/// komut yoksa `false` döner.
+pub async fn record_response(st: &AppState, db: Option<&PgPool>, response: &DeviceCommandResponse) -> sqlx::Result<bool> {
+    let transition = CommandTransition {
+        status: AckStatus::from_success(response.success).into(),
+        at: response.timestamp,
+        message: response.message.clone(),
+    };
+    let Some(db) = db else {
+        let mut ring = st.command_audit.write().await;
+        let entry = ring
+            .iter_mut()
+            .find(|entry| entry.correlation_id == response.correlation_id && entry.device_id == response.device_id);
+        return Ok(entry.map(|entry| entry.transition(transition)).is_some());
+    };
+    let status = transition.status.as_str();
+    let updated = sqlx::query(
+        "UPDATE device_commands SET
+            ack_status = CASE WHEN transitions @> $6 THEN ack_status ELSE $3 END,
+            ack_message = CASE WHEN transitions @> $6 THEN ack_message ELSE $4 END,
+            acked_at = CASE WHEN transitions @> $6 THEN acked_at ELSE $5 END,
+            transitions = CASE WHEN transitions @> $6 THEN transitions ELSE transitions || $6 END
+         WHERE correlation_id = $1 AND device_id = $2",
+    )
+    .bind(response.correlation_id)
+    .bind(response.device_id)
+    .bind(status)
+    .bind(&response.message)
+    .bind(response.timestamp)
+    .bind(SqlJson([transition]))
+    .execute(db)
+    .await?
+    .rows_affected();
+    Ok(updated > 0)
+}
+
+/// Cihaz kaldırılınca yanıtı beklenen komutların geçişi
+fn cancelled(at: DateTime<Utc>) -> CommandTransition {
+    CommandTransition { status: CommandStatus::Cancelled, at, message: Some("device removed".into()) }
+}
+
+/// Cihazın yanıtı beklenen komutlarını `cancelled` olarak kapat; sayısını döner
+///
+/// `devices::device_teardown` transaction'ı içinde çağrılır.
+pub async fn cancel_pending(conn: &mut PgConnection, device_id: Uuid, at: DateTime<Utc>) -> sqlx::Result<u64> {
+    let transition = cancelled(at);
+    let cancelled = sqlx::query(
+        "UPDATE device_commands SET
+            ack_status = $2, ack_message = $3, acked_at = $4, transitions = transitions || $5
+         WHERE device_id = $1 AND ack_status IS NULL",
+    )
+    .bind(device_id)
+    .bind(transition.status.as_str())
+    .bind(&transition.message)
+    .bind(at)
+    .bind(SqlJson([&transition]))
+    .execute(conn)
+    .await?
+    .rows_affected();
+    Ok(cancelled)
+}
+
+/// `cancel_pending`'in in-memory karşılığı
+pub async fn cancel_pending_in_memory(st: &AppState, device_id: Uuid, at: DateTime<Utc>) -> u64 {
+    let mut ring = st.command_audit.write().await;
+    ring.iter_mut()
+        .filter(|entry| entry.device_id == device_id && entry.status == CommandStatus::Dispatched)
+        .filter_map(|entry| entry.transition(cancelled(at)).then_some(()))
+        .count() as u64
+}
+
+/// `GET /v1/devices/{id}/commands` parametreleri
+#[derive(Debug, Default, Deserialize)]
+pub struct CommandAuditParams {
+    /// `issued_at` başlangıcı (dahil)
+    pub from: Option<DateTime<Utc>>,
+    /// `issued_at` bitişi (dahil)
+    pub to: Option<DateTime<Utc>>,
+    /// Sadece bu güncel durumdaki komutlar
+    pub status: Option<CommandStatus>,
+    /// Sayfa boyutu (varsayılan 50, en fazla `MAX_COMMAND_HISTORY_LIMIT`)
+    pub limit: Option<i64>,
+    /// Atlanacak kayıt sayısı
+    pub offset: Option<i64>,
+}
+
+impl CommandAuditParams {
+    /// Filtre kaydı kapsıyor mu? (in-memory yol)
+    fn matches(&self, entry: &CommandAuditEntry) -> bool {
+        self.from.is_none_or(|from| entry.issued_at >= from)
+            && self.to.is_none_or(|to| entry.issued_at <= to)
+            && self.status.is_none_or(|status| entry.status == status)
+    }
+}
+
+/// `device_commands` satırı (`status` yanıt yoksa `dispatched`, aksi halde `ack_status`)
+#[derive(Debug, sqlx::FromRow)]
+struct CommandAuditRow {
+    correlation_id: Uuid,
+    device_id: Uuid,
+    command_type: String,
+    command_name: String,
+    parameters: Option<Value>,
+    issuer: String,
+    issued_at: DateTime<Utc>,
+    status: String,
+    transitions: SqlJson<Vec<CommandTransition>>,
+}
+
+impl From<CommandAuditRow> for CommandAuditEntry {
+    fn from(row: CommandAuditRow) -> Self {
+        Self {
+            correlation_id: row.correlation_id,
+            device_id: row.device_id,
+            command_type: row.command_type,
+            command_name: row.command_name,
+            parameters: row.parameters,
+            issuer: row.issuer,
+            issued_at: row.issued_at,
+            // Kolonun CHECK kısıtı sadece geçerli değerlere izin verir
+            status: row.status.parse().unwrap_or(CommandStatus::Dispatched),
+            transitions: row.transitions.0,
+        }
+    }
+}
+
+/// Cihaza verilen komutların denetim kaydı (yeniden eskiye)
+///
+/// # HTTP
+/// `GET /v1/devices/{id}/commands?from=2024-01-20T00:00:00Z&status=failed&limit=50&offset=50`
+///
+/// # Response (200 OK)
+/// `CommandAuditEntry` listesi (komutu veren, durum ve geçişleri)
+///
+/// # Error Responses
+/// - 400 Bad Request: `limit` 1..=500 dışında, negatif `offset`, `from` > `to`
+///   veya bilinmeyen `status`
+/// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st), err)]
+pub async fn list_device_commands(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<CommandAuditParams>,
+) -> Result<Json<Vec<CommandAuditEntry>>, StatusCode> {
+    let limit = params.limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+    let offset = params.offset.unwrap_or(0);
+    if !(1..=MAX_COMMAND_HISTORY_LIMIT).contains(&limit) || offset < 0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from > to {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let Some(db) = st.pool().await else {
+        // ===== In-Memory Yolu =====
+        let ring = st.command_audit.read().await;
+        let mut entries: Vec<_> = ring.iter().filter(|entry| entry.device_id == id && params.matches(entry)).cloned().collect();
+        entries.sort_by(|a, b| b.issued_at.cmp(&a.issued_at).then(a.correlation_id.cmp(&b.correlation_id)));
+        return Ok(Json(entries.into_iter().skip(offset as usize).take(limit as usize).collect()));
+    };
+
+    // ===== PostgreSQL Yolu =====
+    let rows = sqlx::query_as::<_, CommandAuditRow>(
+        "SELECT correlation_id, device_id, command_type, command_name, parameters, issuer,
+                dispatched_at AS issued_at, COALESCE(ack_status, 'dispatched') AS status, transitions
+         FROM device_commands
+         WHERE device_id = $1
+           AND ($2::timestamptz IS NULL OR dispatched_at >= $2)
+           AND ($3::timestamptz IS NULL OR dispatched_at <= $3)
+           AND ($4::text IS NULL OR COALESCE(ack_status, 'dispatched') = $4)
+         ORDER BY dispatched_at DESC, correlation_id
+         LIMIT $5 OFFSET $6",
+    )
+    .bind(id)
+    .bind(params.from)
+    .bind(params.to)
+    .bind(params.status.map(CommandStatus::as_str))
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Command audit query failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(rows.into_iter().map(CommandAuditEntry::from).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Extension;
+    use chrono::SubsecRound;
+    use rumqttc::AsyncClient;
+    use shared_types::messages::{DeviceCommand, LedCommand, LedState};
+
+    use crate::auth::Issuer;
+    use crate::routes::devices::{ack_command, send_command, send_led_command, NewDeviceCommand};
+
+    fn relay() -> NewDeviceCommand {
+        NewDeviceCommand {
+            command_type: "control".into(),
+            command_name: "relay".into(),
+            parameters: Some(serde_json::json!({"relay_id": "relay_01", "state": "open"})),
+        }
+    }
+
+    fn led() -> LedCommand {
+        LedCommand { led_id: "led_01".into(), state: LedState::On, brightness: None, duration_ms: None, blink: None }
+    }
+
+    fn ops() -> Option<Extension<Issuer>> {
+        Some(Extension(Issuer("admin:ops@example.com".into())))
+    }
+
+    async fn issue(st: &AppState, id: Uuid) -> (DeviceCommand, DeviceCommand) {
+        let (_, Json(relay)) = send_command(State(st.clone()), ops(), Path(id), Json(relay())).await.unwrap();
+        // PostgreSQL mikrosaniyeye yuvarlar: sıralama eşit zamana kalmasın
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        let (_, Json(led)) = send_led_command(State(st.clone()), None, Path(id), Json(led())).await.unwrap();
+        (relay, led)
+    }
+
+    async fn list(st: &AppState, id: Uuid, params: CommandAuditParams) -> Result<Vec<CommandAuditEntry>, StatusCode> {
+        list_device_commands(State(st.clone()), Path(id), Query(params)).await.map(|Json(entries)| entries)
+    }
+
+    /// Issuance, yanıt eşleştirme ve filtreler (in-memory ve PostgreSQL'de aynı)
+    ///
+    /// Komut verilen cihazların ID'lerini döner (test temizliği için).
+    async fn check_audit_flow(st: &AppState) -> [Uuid; 2] {
+        let id = Uuid::new_v4();
+        let (relay, led) = issue(st, id).await;
+        // Başka cihazın komutu listelenmez
+        let other = Uuid::new_v4();
+        issue(st, other).await;
+
+        let entries = list(st, id, CommandAuditParams::default()).await.unwrap();
+        assert_eq!(entries.iter().map(|e| e.correlation_id).collect::<Vec<_>>(), [led.correlation_id, relay.correlation_id]);
+        assert_eq!((entries[1].issuer.as_str(), entries[1].command_name.as_str()), ("admin:ops@example.com", "relay"));
+        assert_eq!(entries[1].parameters.as_ref().unwrap()["state"], "open");
+        // PostgreSQL mikrosaniye tutar
+        assert_eq!(entries[1].issued_at.trunc_subsecs(6), relay.timestamp.trunc_subsecs(6));
+        assert_eq!(entries[0].issuer, Issuer::ANONYMOUS);
+        assert!(entries.iter().all(|e| e.status == CommandStatus::Dispatched && e.transitions.len() == 1));
+
+        // Yanıtlar correlation_id ile komuta bağlanır; tekrarı geçiş eklemez
+        let ack = |response: DeviceCommandResponse| ack_command(State(st.clone()), None, Path(id), Json(response));
+        let failed = DeviceCommandResponse::error(&relay, "relay stuck");
+        assert_eq!(ack(failed.clone()).await.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(ack(failed).await.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(ack(DeviceCommandResponse::ok(&led)).await.unwrap(), StatusCode::NO_CONTENT);
+        let unknown = DeviceCommand::new(id, "control".into(), "relay".into());
+        assert_eq!(ack(DeviceCommandResponse::ok(&unknown)).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        let status = |status| CommandAuditParams { status: Some(status), ..Default::default() };
+        let entries = list(st, id, status(CommandStatus::Failed)).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].correlation_id, relay.correlation_id);
+        let statuses: Vec<_> = entries[0].transitions.iter().map(|t| (t.status, t.message.as_deref())).collect();
+        assert_eq!(statuses, [(CommandStatus::Dispatched, None), (CommandStatus::Failed, Some("relay stuck"))]);
+        assert_eq!(list(st, id, status(CommandStatus::Executed)).await.unwrap()[0].correlation_id, led.correlation_id);
+        assert!(list(st, id, status(CommandStatus::Dispatched)).await.unwrap().is_empty());
+
+        // Zaman aralığı ve sayfalama
+        let range = CommandAuditParams { from: Some(led.timestamp.trunc_subsecs(6)), ..Default::default() };
+        assert_eq!(list(st, id, range).await.unwrap()[0].correlation_id, led.correlation_id);
+        let range = CommandAuditParams { to: Some(relay.timestamp), ..Default::default() };
+        assert_eq!(list(st, id, range).await.unwrap()[0].correlation_id, relay.correlation_id);
+        let page = CommandAuditParams { limit: Some(1), offset: Some(1), ..Default::default() };
+        assert_eq!(list(st, id, page).await.unwrap()[0].correlation_id, relay.correlation_id);
+        let past = CommandAuditParams { offset: Some(2), ..Default::default() };
+        assert!(list(st, id, past).await.unwrap().is_empty());
+        [id, other]
+    }
+
+    fn with_mqtt(st: AppState) -> (AppState, flume::Receiver<rumqttc::Request>) {
+        let (tx, rx) = flume::bounded(10);
+        (AppState { mqtt: Some(AsyncClient::from_senders(tx)), ..st }, rx)
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_audit_flow() {
+        let (st, _rx) = with_mqtt(AppState::for_tests());
+        check_audit_flow(&st).await;
+    }
+
+    #[tokio::test]
+    async fn test_rejected_commands_and_params() {
+        let (st, rx) = with_mqtt(AppState::for_tests());
+        let id = Uuid::new_v4();
+
+        let blank = NewDeviceCommand { command_name: " ".into(), ..relay() };
+        assert_eq!(send_command(State(st.clone()), ops(), Path(id), Json(blank)).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        // Publish edilemeyen komut kabul edilmemiştir
+        drop(rx);
+        let result = send_command(State(st.clone()), ops(), Path(id), Json(relay())).await;
+        assert_eq!(result.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(st.command_audit.read().await.is_empty());
+
+        let now = Utc::now();
+        for params in [
+            CommandAuditParams { limit: Some(0), ..Default::default() },
+            CommandAuditParams { limit: Some(MAX_COMMAND_HISTORY_LIMIT + 1), ..Default::default() },
+            CommandAuditParams { offset: Some(-1), ..Default::default() },
+            CommandAuditParams { from: Some(now), to: Some(now - chrono::TimeDelta::seconds(1)), ..Default::default() },
+        ] {
+            assert_eq!(list(&st, id, params).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_ring_drops_oldest() {
+        let st = AppState::for_tests();
+        let id = Uuid::new_v4();
+        let commands: Vec<_> = (0..MAX_IN_MEMORY_COMMANDS + 3).map(|_| DeviceCommand::new(id, "control".into(), "relay".into())).collect();
+        for command in &commands {
+            record(&st, None, CommandAuditEntry::issued(command, "admin:ops")).await.unwrap();
+        }
+
+        let ring = st.command_audit.read().await;
+        assert_eq!(ring.len(), MAX_IN_MEMORY_COMMANDS);
+        assert_eq!(ring.front().unwrap().correlation_id, commands[3].correlation_id);
+    }
+
+    /// Migration'ları uygulanmış gerçek PostgreSQL gerektirir:
+    /// `TEST_DATABASE_URL=postgres://... cargo test -p api-server -- --ignored`
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_audit_flow_and_retention_in_postgres() {
+        let db = test_db().await;
+        let (st, _rx) = with_mqtt(AppState::for_tests_with_db(db.clone()));
+        let devices = check_audit_flow(&st).await;
+        assert!(st.command_audit.read().await.is_empty());
+
+        // Retention penceresinden eski kayıt silinir, yenisi kalır
+        let id = Uuid::new_v4();
+        let mut old = DeviceCommand::new(id, "control".into(), "relay".into());
+        old.timestamp = Utc::now() - chrono::TimeDelta::days(31);
+        record(&st, Some(&db), CommandAuditEntry::issued(&old, "admin:ops")).await.unwrap();
+        let (recent, _) = issue(&st, id).await;
+        let cutoff = Utc::now() - chrono::TimeDelta::days(30);
+        assert!(crate::background::run_command_audit_retention(&db, cutoff).await.unwrap() >= 1);
+        let entries = list(&st, id, CommandAuditParams::default()).await.unwrap();
+        assert!(entries.iter().all(|e| e.correlation_id != old.correlation_id));
+        assert!(entries.iter().any(|e| e.correlation_id == recent.correlation_id));
+
+        let devices = [devices.as_slice(), &[id]].concat();
+        sqlx::query("DELETE FROM device_commands WHERE device_id = ANY($1)").bind(&devices).execute(&db).await.unwrap();
+    }
+}
@@ -3,12 +3,12 @@ use serde::Serialize;
 use crate::state::AppState;
 
 #[derive(Serialize)]
-struct DbHealth { db: &'static str }
+pub struct DbHealth { db: &'static str }
 
 pub async fn health(State(st): State<AppState>) -> (StatusCode, Json<DbHealth>) {
-    if let Some(pool) = &st.db {
+    if let Some(pool) = &st.pool().await {
         // Basit check: SELECT 1
-        let res = sqlx::query_scalar::<_, i64>("SELECT 1").fetch_one(pool).await;
+        let res = sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(pool).await;
         match res {
             Ok(_)  => (StatusCode::OK, Json(DbHealth { db: "up" })),
             Err(_) => (StatusCode::SERVICE_UNAVAILABLE, Json(DbHealth { db: "down" })),
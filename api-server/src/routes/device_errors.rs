@@ -0,0 +1,283 @@
+//! Cihaz Hata Raporu Endpoint'leri
+//!
+//! Edge agent'lar komut ve sürücü hatalarını `error_report` `DeviceMessage`'ı
+//! olarak publish eder; gateway bunları buraya iletir. Hatalar
+//! `device_errors` tablosunda cihaz başına sınırlı sayıda saklanır.
+//!
+//! # Endpoint'ler
+//! - POST /api/devices/{id}/errors - Hata raporu kaydet (gateway, device rolü)
+//! - GET /api/devices/{id}/errors - Hataları listele (`?since=&level=&limit=`)
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::DeviceBinding;
+use crate::state::AppState;
+use shared_types::{DeviceError, ErrorLevel, ErrorReport};
+
+/// Cihaz başına saklanan en fazla hata (eskileri her eklemede silinir)
+pub const MAX_DEVICE_ERRORS_PER_DEVICE: i64 = 500;
+
+/// `GET` varsayılan satır sayısı
+const DEFAULT_ERRORS_LIMIT: i64 = 100;
+
+/// `GET /api/devices/{id}/errors` parametreleri
+///
+/// Örnek: `?since=2024-01-20T00:00:00Z&level=warning&limit=50`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeviceErrorParams {
+    /// Bu zamandan sonra oluşan hatalar (dahil, `reported_at`)
+    pub since: Option<DateTime<Utc>>,
+    /// En düşük önem derecesi (`warning`: warning, error, critical)
+    pub level: Option<String>,
+    /// Maksimum satır (varsayılan 100, en fazla `MAX_DEVICE_ERRORS_PER_DEVICE`)
+    pub limit: Option<i64>,
+}
+
+impl DeviceErrorParams {
+    /// Dahil edilecek seviyeler (bilinmeyen seviye 400)
+    fn levels(&self) -> Result<Vec<&'static str>, StatusCode> {
+        let min = match &self.level {
+            Some(level) => level.parse::<ErrorLevel>().map_err(|_| StatusCode::BAD_REQUEST)?,
+            None => ErrorLevel::Info,
+        };
+        Ok([ErrorLevel::Info, ErrorLevel::Warning, ErrorLevel::Error, ErrorLevel::Critical]
+            .into_iter()
+            .filter(|level| *level >= min)
+            .map(ErrorLevel::as_str)
+            .collect())
+    }
+}
+
+/// Cihazın hata raporunu kaydet
+///
+/// # HTTP
+/// `POST /api/devices/{id}/errors`
+///
+/// # Request Body
+/// ```json
+/// {"level": "error", "message": "libcamera capture failed: timeout", "context": {"source": "camera"}, "timestamp": "2024-01-20T10:30:00Z"}
+/// ```
+///
+/// Büyük mesaj ve bağlam kırpılarak saklanır (`ErrorReport::truncate`).
+/// `timestamp` yoksa alındığı zaman kullanılır.
+///
+/// # Response (201 Created)
+/// Saklanan `DeviceError`
+///
+/// # Error Responses
+/// - 403 Forbidden: Cihaz token'ı başka bir cihaza ait
+/// - 500 Internal Server Error: Database hatası
+/// - 501 Not Implemented: PostgreSQL bağlı değil
+#[tracing::instrument(skip(st, report), err)]
+pub async fn report_device_error(
+    State(st): State<AppState>,
+    binding: Option<Extension<DeviceBinding>>,
+    Path(id): Path<Uuid>,
+    Json(mut report): Json<ErrorReport>,
+) -> Result<(StatusCode, Json<DeviceError>), StatusCode> {
+    DeviceBinding::check(binding.as_deref(), &id.to_string())?;
+    let db = &st.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    if report.truncate() {
+        tracing::debug!("Truncated oversized error report from {id}");
+    }
+
+    let stored = insert_device_error(db, id, &report).await.map_err(|e| {
+        tracing::error!("Storing error report of {id} failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    tracing::info!("Device {id} reported {}: {}", stored.level, stored.message);
+    Ok((StatusCode::CREATED, Json(stored)))
+}
+
+/// Raporu ekle ve cihazın eski hatalarını sınıra indir (tek transaction)
+async fn insert_device_error(db: &PgPool, id: Uuid, report: &ErrorReport) -> sqlx::Result<DeviceError> {
+    let mut tx = db.begin().await?;
+    let stored = sqlx::query_as::<_, DeviceError>(
+        "INSERT INTO device_errors (device_id, level, message, context, reported_at)
+         VALUES ($1, $2, $3, $4, COALESCE($5, now()))
+         RETURNING id, device_id, level, message, context, reported_at, received_at",
+    )
+    .bind(id)
+    .bind(report.level.as_str())
+    .bind(&report.message)
+    .bind(&report.context)
+    .bind(report.timestamp)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "DELETE FROM device_errors
+         WHERE device_id = $1
+           AND id < (SELECT min(id) FROM (
+                SELECT id FROM device_errors WHERE device_id = $1 ORDER BY id DESC LIMIT $2
+           ) newest)",
+    )
+    .bind(id)
+    .bind(MAX_DEVICE_ERRORS_PER_DEVICE)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(stored)
+}
+
+/// Cihazın hatalarını listele (yeniden eskiye)
+///
+/// # HTTP
+/// `GET /api/devices/{id}/errors?since=&level=&limit=`
+///
+/// # Error Responses
+/// - 400 Bad Request: bilinmeyen `level` veya geçersiz `limit`
+/// - 500 Internal Server Error: Database hatası
+/// - 501 Not Implemented: PostgreSQL bağlı değil
+#[tracing::instrument(skip(st), err)]
+pub async fn list_device_errors(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DeviceErrorParams>,
+) -> Result<Json<Vec<DeviceError>>, StatusCode> {
+    let levels = params.levels()?;
+    let limit = params.limit.unwrap_or(DEFAULT_ERRORS_LIMIT);
+    if !(1..=MAX_DEVICE_ERRORS_PER_DEVICE).contains(&limit) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let db = &st.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+
+    let errors = sqlx::query_as::<_, DeviceError>(
+        "SELECT id, device_id, level, message, context, reported_at, received_at
+         FROM device_errors
+         WHERE device_id = $1
+           AND level = ANY($2)
+           AND ($3::timestamptz IS NULL OR reported_at >= $3)
+         ORDER BY reported_at DESC, id DESC
+         LIMIT $4",
+    )
+    .bind(id)
+    .bind(&levels)
+    .bind(params.since)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Device errors query failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::device_error::MAX_ERROR_MESSAGE_BYTES;
+    use shared_types::messages::DeviceMessage;
+
+    #[test]
+    fn test_level_filter_includes_higher_severities() {
+        let params = |level: Option<&str>| DeviceErrorParams { level: level.map(String::from), ..Default::default() };
+        assert_eq!(params(None).levels().unwrap(), vec!["info", "warning", "error", "critical"]);
+        assert_eq!(params(Some("warning")).levels().unwrap(), vec!["warning", "error", "critical"]);
+        assert_eq!(params(Some("critical")).levels().unwrap(), vec!["critical"]);
+        assert_eq!(params(Some("fatal")).levels().unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_without_database() {
+        let st = AppState::for_tests();
+        let report = ErrorReport::new(ErrorLevel::Error, "i2c timeout");
+        let result = report_device_error(State(st.clone()), None, Path(Uuid::new_v4()), Json(report)).await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+
+        let bad_limit = DeviceErrorParams { limit: Some(0), ..Default::default() };
+        let result = list_device_errors(State(st.clone()), Path(Uuid::new_v4()), Query(bad_limit)).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+        let result = list_device_errors(State(st), Path(Uuid::new_v4()), Query(DeviceErrorParams::default())).await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_reports_stored_filtered_and_capped() {
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        let id = Uuid::new_v4();
+
+        // Gateway'in ilettiği body: edge agent mesajının `data`'sı + zamanı
+        let message = DeviceMessage::error_report(
+            id,
+            &ErrorReport::new(ErrorLevel::Critical, "x".repeat(MAX_ERROR_MESSAGE_BYTES * 2))
+                .with_context(serde_json::json!({"source": "camera"})),
+        );
+        let mut forwarded: ErrorReport = serde_json::from_value(message.data).unwrap();
+        forwarded.timestamp = Some(message.timestamp);
+        let (status, Json(stored)) = report_device_error(State(st.clone()), None, Path(id), Json(forwarded)).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!((stored.device_id, stored.level), (id, ErrorLevel::Critical));
+        assert!(stored.message.len() <= MAX_ERROR_MESSAGE_BYTES && stored.message.ends_with('…'));
+        assert_eq!(stored.context, Some(serde_json::json!({"source": "camera"})));
+        assert_eq!(stored.reported_at.timestamp_micros(), message.timestamp.timestamp_micros());
+
+        for (level, minutes_ago) in [(ErrorLevel::Info, 30), (ErrorLevel::Warning, 20), (ErrorLevel::Error, 10)] {
+            let report = ErrorReport {
+                timestamp: Some(Utc::now() - chrono::Duration::minutes(minutes_ago)),
+                ..ErrorReport::new(level, format!("{level} report"))
+            };
+            let (status, _) = report_device_error(State(st.clone()), None, Path(id), Json(report)).await.unwrap();
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        let list = |level: Option<&str>, since: Option<DateTime<Utc>>| {
+            let params = DeviceErrorParams { level: level.map(String::from), since, limit: None };
+            let st = st.clone();
+            async move {
+                let Json(errors) = list_device_errors(State(st), Path(id), Query(params)).await.unwrap();
+                errors.into_iter().map(|e| e.level).collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            list(None, None).await,
+            vec![ErrorLevel::Critical, ErrorLevel::Error, ErrorLevel::Warning, ErrorLevel::Info]
+        );
+        assert_eq!(list(Some("warning"), None).await, vec![ErrorLevel::Critical, ErrorLevel::Error, ErrorLevel::Warning]);
+        let since = Some(Utc::now() - chrono::Duration::minutes(15));
+        assert_eq!(list(Some("info"), since).await, vec![ErrorLevel::Critical, ErrorLevel::Error]);
+
+        // Sınırı aşan eski hatalar silinir
+        sqlx::query(
+            "INSERT INTO device_errors (device_id, level, message, reported_at)
+             SELECT $1, 'info', 'bulk', now() - interval '1 day' FROM generate_series(1, $2)",
+        )
+        .bind(id)
+        .bind(MAX_DEVICE_ERRORS_PER_DEVICE as i32)
+        .execute(&db)
+        .await
+        .unwrap();
+        let (_, Json(latest)) = report_device_error(State(st.clone()), None, Path(id), Json(ErrorReport::new(ErrorLevel::Error, "latest")))
+            .await
+            .unwrap();
+        assert_eq!(latest.message, "latest");
+        let count: i64 = sqlx::query_scalar("SELECT count(*) FROM device_errors WHERE device_id = $1")
+            .bind(id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(count, MAX_DEVICE_ERRORS_PER_DEVICE);
+        let levels = list(Some("critical"), None).await;
+        assert!(levels.is_empty(), "oldest rows (incl. the critical one) are dropped first");
+
+        sqlx::query("DELETE FROM device_errors WHERE device_id = $1").bind(id).execute(&db).await.unwrap();
+    }
+}
@@ -0,0 +1,306 @@
+//! Cihaz Token Endpoint'leri
+//!
+//! Tüm filoda tek bir API anahtarı yerine her cihaza kendi kalıcı token'ı
+//! verilir. Token `rfd_` önekli rastgele bir dizedir; sadece SHA-256 hash'i
+//! saklanır ve düz hali yalnızca oluşturma yanıtında döner.
+//!
+//! `AUTH_ENABLED=true` iken ingest endpoint'leri `Authorization: Bearer rfd_...`
+//! kabul eder: istek `device` rolüyle token'ın cihazına bağlanır
+//! (`auth::DeviceBinding`) ve başka bir cihaz adına gelen body'ler 403 alır.
+//!
+//! # Endpoint'ler
+//! - POST /v1/devices/{id}/tokens - Yeni token oluştur (admin)
+//! - GET /v1/devices/{id}/tokens - Cihazın token'larını listele (admin)
+//! - DELETE /v1/devices/{id}/tokens/{token_id} - Token'ı iptal et (admin)
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::cli::hash_api_key;
+use crate::state::AppState;
+use shared_types::{DeviceToken, IssuedDeviceToken, NewDeviceToken};
+
+/// Cihaz token'larının öneki (JWT'lerden ve `rfk_` API anahtarlarından ayırt edilir)
+pub const DEVICE_TOKEN_PREFIX: &str = "rfd_";
+
+/// `last_used_at` en fazla bu sıklıkla yazılır (her ingest isteği UPDATE yapmasın)
+pub const LAST_USED_RESOLUTION: chrono::Duration = chrono::Duration::seconds(60);
+
+/// `rfd_` + 64 hex karakter (256 bit rastgele)
+pub fn generate_device_token() -> String {
+    format!("{DEVICE_TOKEN_PREFIX}{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Cihaz için yeni token oluştur
+///
+/// # HTTP
+/// `POST /v1/devices/{id}/tokens`
+///
+/// # Request Body
+/// ```json
+/// {"label": "raspberry-pi-01", "expires_at": "2025-11-13T21:30:00Z"}
+/// ```
+///
+/// Her iki alan da opsiyoneldir; `expires_at` verilmezse token iptal
+/// edilene kadar geçerlidir.
+///
+/// # Response (201 Created)
+/// `IssuedDeviceToken`: düz `token` sadece bu yanıtta döner.
+///
+/// # Error Responses
+/// - 400 Bad Request: `expires_at` geçmişte
+/// - 500 Internal Server Error: Database hatası
+pub async fn create_device_token(
+    State(st): State<AppState>,
+    Path(device_id): Path<Uuid>,
+    Json(body): Json<NewDeviceToken>,
+) -> Result<(StatusCode, Json<IssuedDeviceToken>), StatusCode> {
+    let now = Utc::now();
+    if body.expires_at.is_some_and(|exp| exp <= now) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let label = body.label.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+    let issued = issue_device_token(&st, device_id, label, body.expires_at, now).await?;
+    Ok((StatusCode::CREATED, Json(issued)))
+}
+
+/// Yeni token üret ve hash'ini sakla (`create_device_token`, `POST /v1/provision`)
+pub async fn issue_device_token(
+    st: &AppState,
+    device_id: Uuid,
+    label: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<IssuedDeviceToken, StatusCode> {
+    let token = generate_device_token();
+
+    let info = if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        sqlx::query_as::<_, DeviceToken>(
+            "INSERT INTO device_tokens (id, device_id, label, token_hash, created_at, expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING id, device_id, label, token_hash, created_at, expires_at, last_used_at",
+        )
+        .bind(Uuid::new_v4())
+        .bind(device_id)
+        .bind(&label)
+        .bind(hash_api_key(&token))
+        .bind(now)
+        .bind(expires_at)
+        .fetch_one(db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Storing token for device {device_id} failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+    } else {
+        // ===== In-Memory Fallback =====
+        let info = DeviceToken {
+            id: Uuid::new_v4(),
+            device_id,
+            label,
+            token_hash: hash_api_key(&token),
+            created_at: now,
+            expires_at,
+            last_used_at: None,
+        };
+        st.device_tokens.write().await.insert(info.token_hash.clone(), info.clone());
+        info
+    };
+
+    tracing::info!("Issued token {} for device {device_id}", info.id);
+    Ok(IssuedDeviceToken { token, info })
+}
+
+/// Cihazın token'larını listele (`created_at` sırasıyla, süresi dolanlar dahil)
+///
+/// # HTTP
+/// `GET /v1/devices/{id}/tokens`
+pub async fn list_device_tokens(
+    State(st): State<AppState>,
+    Path(device_id): Path<Uuid>,
+) -> Result<Json<Vec<DeviceToken>>, StatusCode> {
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        sqlx::query_as::<_, DeviceToken>(
+            "SELECT id, device_id, label, token_hash, created_at, expires_at, last_used_at
+             FROM device_tokens WHERE device_id = $1 ORDER BY created_at",
+        )
+        .bind(device_id)
+        .fetch_all(db)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut tokens: Vec<DeviceToken> =
+            st.device_tokens.read().await.values().filter(|t| t.device_id == device_id).cloned().collect();
+        tokens.sort_by_key(|t| t.created_at);
+        Ok(Json(tokens))
+    }
+}
+
+/// Token'ı iptal et (sonraki isteklerde 401)
+///
+/// # HTTP
+/// `DELETE /v1/devices/{id}/tokens/{token_id}`
+///
+/// # Error Responses
+/// - 404 Not Found: Token yok veya başka bir cihaza ait
+/// - 500 Internal Server Error: Database hatası
+pub async fn revoke_device_token(
+    State(st): State<AppState>,
+    Path((device_id, token_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    let revoked = if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        sqlx::query("DELETE FROM device_tokens WHERE id = $1 AND device_id = $2")
+            .bind(token_id)
+            .bind(device_id)
+            .execute(db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .rows_affected()
+            > 0
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut tokens = st.device_tokens.write().await;
+        let before = tokens.len();
+        tokens.retain(|_, t| !(t.id == token_id && t.device_id == device_id));
+        tokens.len() < before
+    };
+
+    if !revoked {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    tracing::info!("Revoked token {token_id} of device {device_id}");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Token'ı doğrula, sahibi olan cihazın ID'sini döndür
+///
+/// Bilinmeyen, iptal edilmiş veya süresi dolmuş token'lar (ve DB hataları)
+/// `None` döner. `last_used_at` en fazla `LAST_USED_RESOLUTION`'da bir yazılır.
+pub async fn authenticate(st: &AppState, token: &str, now: DateTime<Utc>) -> Option<Uuid> {
+    if !token.starts_with(DEVICE_TOKEN_PREFIX) {
+        return None;
+    }
+    let hash = hash_api_key(token);
+    let stale = |t: &DeviceToken| t.last_used_at.is_none_or(|used| now - used >= LAST_USED_RESOLUTION);
+
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        let found = sqlx::query_as::<_, DeviceToken>(
+            "SELECT id, device_id, label, token_hash, created_at, expires_at, last_used_at
+             FROM device_tokens WHERE token_hash = $1",
+        )
+        .bind(&hash)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| tracing::error!("Device token lookup failed: {e}"))
+        .ok()??;
+        if found.is_expired(now) {
+            return None;
+        }
+        if stale(&found) {
+            let updated = sqlx::query("UPDATE device_tokens SET last_used_at = $2 WHERE id = $1")
+                .bind(found.id)
+                .bind(now)
+                .execute(db)
+                .await;
+            if let Err(e) = updated {
+                tracing::warn!("Updating last_used_at of token {} failed: {e}", found.id);
+            }
+        }
+        Some(found.device_id)
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut tokens = st.device_tokens.write().await;
+        let found = tokens.get_mut(&hash).filter(|t| !t.is_expired(now))?;
+        if stale(found) {
+            found.last_used_at = Some(now);
+        }
+        Some(found.device_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    async fn issue(st: &AppState, device_id: Uuid, expires_at: Option<DateTime<Utc>>) -> IssuedDeviceToken {
+        let body = NewDeviceToken { label: Some(" pi-01 ".into()), expires_at };
+        let (status, Json(issued)) = create_device_token(State(st.clone()), Path(device_id), Json(body)).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        issued
+    }
+
+    /// Oluştur → doğrula → last_used_at → listele → iptal et → 401
+    async fn check_lifecycle(st: &AppState) {
+        let device_id = Uuid::new_v4();
+        let issued = issue(st, device_id, None).await;
+        assert!(issued.token.starts_with(DEVICE_TOKEN_PREFIX) && issued.token.len() == 68);
+        assert_eq!((issued.info.device_id, issued.info.label.as_deref()), (device_id, Some("pi-01")));
+        assert_eq!(issued.info.token_hash, hash_api_key(&issued.token));
+
+        let now = Utc::now();
+        assert_eq!(authenticate(st, &issued.token, now).await, Some(device_id));
+        assert_eq!(authenticate(st, &generate_device_token(), now).await, None);
+        assert_eq!(authenticate(st, "not-a-device-token", now).await, None);
+
+        // İkinci kullanım `LAST_USED_RESOLUTION` dolmadan last_used_at'i değiştirmez
+        assert_eq!(authenticate(st, &issued.token, now + chrono::Duration::seconds(5)).await, Some(device_id));
+        let Json(tokens) = list_device_tokens(State(st.clone()), Path(device_id)).await.unwrap();
+        assert_eq!(tokens.len(), 1);
+        let last_used = tokens[0].last_used_at.unwrap();
+        assert_eq!(last_used.timestamp_millis(), now.timestamp_millis());
+
+        // Başka cihazın yolundan iptal edilemez
+        let wrong = revoke_device_token(State(st.clone()), Path((Uuid::new_v4(), issued.info.id))).await;
+        assert_eq!(wrong.unwrap_err(), StatusCode::NOT_FOUND);
+        let revoked = revoke_device_token(State(st.clone()), Path((device_id, issued.info.id))).await;
+        assert_eq!(revoked.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(authenticate(st, &issued.token, Utc::now()).await, None);
+        let again = revoke_device_token(State(st.clone()), Path((device_id, issued.info.id))).await;
+        assert_eq!(again.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    /// Süresi dolan token reddedilir, geçmiş `expires_at` ile oluşturulamaz
+    async fn check_expiry(st: &AppState) {
+        let device_id = Uuid::new_v4();
+        let expires_at = Utc::now() + chrono::Duration::minutes(10);
+        let issued = issue(st, device_id, Some(expires_at)).await;
+        assert_eq!(authenticate(st, &issued.token, expires_at - chrono::Duration::seconds(1)).await, Some(device_id));
+        assert_eq!(authenticate(st, &issued.token, expires_at).await, None);
+
+        let past = NewDeviceToken { label: None, expires_at: Some(Utc::now() - chrono::Duration::seconds(1)) };
+        let result = create_device_token(State(st.clone()), Path(device_id), Json(past)).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_token_lifecycle_in_memory() {
+        let st = AppState::for_tests();
+        check_lifecycle(&st).await;
+        check_expiry(&st).await;
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_token_lifecycle_with_database() {
+        let st = AppState::for_tests_with_db(test_db().await);
+        check_lifecycle(&st).await;
+        check_expiry(&st).await;
+    }
+}
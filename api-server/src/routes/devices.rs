@@ -0,0 +1,1683 @@
+//! Cihaz Endpoint'leri (Device Routes)
+//!
+//! Edge cihazlara MQTT üzerinden komut gönderen endpoint'ler.
+//! Komutlar `devices/{device_id}/commands` topic'ine `DeviceCommand` JSON'u olarak publish edilir.
+//!
+//! Ayrıca cihaz gölgelerini (device shadow) yönetir: PostgreSQL'e veya
+//! in-memory store'a kayıt yapılır (fallback).
+//!
+//! # Endpoint'ler
+//! - GET  /v1/devices?kind=&stale_for= - Cihaz gölgelerini listele (gateway'ler `kind=gateway`, son görülme ile)
+//! - POST /v1/devices/{id}/commands - Komut gönder (`command_type`, `command_name`, `parameters`)
+//! - POST /v1/devices/{id}/commands/led - LED kontrol komutu gönder
+//! - POST /v1/devices/{id}/commands/ack - Komut yanıtını kaydet (MQTT gateway)
+//! - GET  /v1/devices/{id}/commands/history?limit= - Gönderilen komutlar ve sonuçları (PostgreSQL)
+//! - GET  /v1/devices/{id}/shadow - Cihaz gölgesini al
+//! - POST /v1/devices/{id}/shadow/desired - İstenen durumu güncelle
+//! - POST /v1/devices/{id}/shadow/reported - Raporlanan durumu güncelle (MQTT gateway)
+//! - GET  /v1/devices/{id}/timeline - Okuma, alarm, heartbeat ve durum olayları (PostgreSQL)
+//! - DELETE /v1/devices/{id}/data - Cihazın tüm verisini sil (GDPR, `confirm: true` gerekir)
+//! - DELETE /v1/devices/{id}?purge_history= - Cihazı kaldır (kayıt, gölge, cache; istenirse geçmiş)
+
+use std::collections::BTreeMap;
+
+use axum::{extract::{Path, Query, State}, http::StatusCode, Extension, Json};
+use chrono::{DateTime, TimeDelta, Utc};
+use rumqttc::QoS;
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use shared_types::{
+    AckStatus, CommandAuditEntry, CommandHistoryEntry, DeleteDataRequest, DeleteDataResult, DeviceKind, DeviceShadow, DeviceTeardownParams,
+    DeviceTeardownResult, DeviceTimeline, TimelineEvent,
+};
+use shared_types::conventions::{command_topic, redis_last_seen_key, status_topic};
+use shared_types::messages::{DeviceCommand, DeviceCommandResponse, DeviceMessage, LedCommand};
+
+use crate::auth::{DeviceBinding, Issuer};
+use crate::feature_flags::check_feature;
+use crate::routes::command_audit;
+use crate::routes::sensors::{delete_device_sensors_from_redis, TimeRangeParams};
+use crate::state::AppState;
+
+/// `GET /v1/devices/{id}/timeline` varsayılan olay sayısı
+const DEFAULT_TIMELINE_LIMIT: i64 = 500;
+
+/// Dört kaynaktan olaylar; her dal kendi index'iyle `limit`'e kadar okunur,
+/// birleşim yeniden sıralanıp tekrar kesilir
+const TIMELINE_SQL: &str = "
+    SELECT timestamp, event_type, sensor_type, value, message FROM (
+        (SELECT timestamp, 'reading' AS event_type, sensor_type, value, NULL::text AS message
+         FROM sensor_readings
+         WHERE device_id = $1::text AND ($2::timestamptz IS NULL OR timestamp >= $2) AND ($3::timestamptz IS NULL OR timestamp <= $3)
+         ORDER BY timestamp DESC LIMIT $4)
+        UNION ALL
+        (SELECT triggered_at, 'alert', sensor_type, value, message
+         FROM sensor_alerts
+         WHERE device_id = $1::text AND ($2::timestamptz IS NULL OR triggered_at >= $2) AND ($3::timestamptz IS NULL OR triggered_at <= $3)
+         ORDER BY triggered_at DESC LIMIT $4)
+        UNION ALL
+        (SELECT received_at, 'heartbeat', NULL, NULL, NULL
+         FROM device_heartbeats
+         WHERE device_id = $1 AND ($2::timestamptz IS NULL OR received_at >= $2) AND ($3::timestamptz IS NULL OR received_at <= $3)
+         ORDER BY received_at DESC LIMIT $4)
+        UNION ALL
+        (SELECT changed_at, 'status_change', NULL, NULL, status::text
+         FROM device_status_changes
+         WHERE device_id = $1 AND ($2::timestamptz IS NULL OR changed_at >= $2) AND ($3::timestamptz IS NULL OR changed_at <= $3)
+         ORDER BY changed_at DESC LIMIT $4)
+    ) events
+    ORDER BY timestamp DESC
+    LIMIT $4";
+
+/// `GET /v1/devices/{id}/commands/history` varsayılan satır sayısı
+const DEFAULT_COMMAND_HISTORY_LIMIT: i64 = 50;
+
+/// `GET /v1/devices/{id}/commands/history` en fazla satır sayısı
+pub const MAX_COMMAND_HISTORY_LIMIT: i64 = 500;
+
+/// `GET /v1/devices` filtresi
+#[derive(Debug, Default, Deserialize)]
+pub struct DeviceListParams {
+    /// Sadece bu türdeki cihazlar (`reported.kind`, yoksa `edge`)
+    pub kind: Option<DeviceKind>,
+    /// Sadece son bu kadar saniyedir okuma göndermeyenler (hiç görülmeyenler dahil)
+    pub stale_for: Option<u64>,
+}
+
+/// `devices.last_seen`'i okumaların zamanıyla ilerlet (`GREATEST`: geri almaz)
+const TOUCH_LAST_SEEN_SQL: &str = "UPDATE devices d SET last_seen = GREATEST(d.last_seen, seen.at)
+     FROM UNNEST($1::uuid[], $2::timestamptz[]) AS seen(id, at)
+     WHERE d.id = seen.id";
+
+/// `AUTO_REGISTER_DEVICES`: bilinmeyen cihazlar taslak satırla eklenir
+const REGISTER_LAST_SEEN_SQL: &str = "INSERT INTO devices (id, serial_number, model, firmware_version, last_seen)
+     SELECT id, id::text, 'unknown', 'unknown', at FROM UNNEST($1::uuid[], $2::timestamptz[]) AS seen(id, at)
+     ON CONFLICT (id) DO UPDATE SET last_seen = GREATEST(devices.last_seen, EXCLUDED.last_seen)";
+
+/// Redis'teki son görülme zamanını sadece ileri al (epoch ms)
+const LAST_SEEN_SCRIPT: &str = "local current = tonumber(redis.call('GET', KEYS[1]))
+if current == nil or current < tonumber(ARGV[1]) then redis.call('SET', KEYS[1], ARGV[1]) end";
+
+/// Komutu cihaza MQTT üzerinden gönder
+///
+/// `try_publish` kullanılır: broker'a ulaşılamıyor ve kuyruk doluysa
+/// request bloklanmaz, 503 döner. Komut publish'ten önce `issuer` ile denetim
+/// kaydına (`command_audit`) yazılır (yanıt kayıttan önce gelmesin); publish
+/// başarısız olursa kayıt geri alınır. Kaydı yazılamayan komut gönderilmez.
+///
+/// # Error Responses
+/// - 503 Service Unavailable: MQTT client yok veya kuyruk dolu
+/// - 500 Internal Server Error: Serializasyon veya denetim kaydı hatası
+pub async fn dispatch_command(st: &AppState, cmd: &DeviceCommand, issuer: &str) -> Result<(), StatusCode> {
+    let client = st.mqtt.as_ref().ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    let payload = serde_json::to_vec(cmd).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let db = st.pool().await;
+    if let Err(e) = command_audit::record(st, db.as_ref(), CommandAuditEntry::issued(cmd, issuer)).await {
+        tracing::error!("Auditing command {} failed: {e}", cmd.correlation_id);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    if let Err(e) = client.try_publish(command_topic(cmd.device_id), QoS::AtLeastOnce, false, payload) {
+        tracing::warn!("Command dispatch failed: {e}");
+        command_audit::discard(st, db.as_ref(), cmd.correlation_id).await;
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    tracing::info!(
+        "Dispatched {}/{} to device {} ({}) for {issuer}",
+        cmd.command_type, cmd.command_name, cmd.device_id, cmd.correlation_id
+    );
+    Ok(())
+}
+
+/// Cihazın komut yanıtını kaydet
+///
+/// # HTTP
+/// `POST /v1/devices/{id}/commands/ack`
+///
+/// # Request Body
+/// Edge agent'ın `devices/{id}/responses`'a publish ettiği `DeviceCommandResponse`
+/// (gateway iletir):
+/// ```json
+/// {"device_id": "550e8400-...", "correlation_id": "550e8400-...", "success": false, "message": "unknown LED `led_09`", "timestamp": "2024-01-20T10:30:01Z"}
+/// ```
+///
+/// Yanıt komutun denetim kaydına durum geçişi olarak eklenir (PostgreSQL
+/// yoksa in-memory kayda); aynı yanıtın tekrar gelmesi (QoS 1) sonucu
+/// değiştirmez.
+///
+/// # Response
+/// 204 No Content
+///
+/// # Error Responses
+/// - 400 Bad Request: body'deki `device_id` path'tekinden farklı
+/// - 403 Forbidden: Cihaz token'ı başka bir cihaza ait
+/// - 404 Not Found: Bu cihaza bu `correlation_id` ile gönderilmiş komut yok
+/// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st, response), err)]
+pub async fn ack_command(
+    State(st): State<AppState>,
+    binding: Option<Extension<DeviceBinding>>,
+    Path(id): Path<Uuid>,
+    Json(response): Json<DeviceCommandResponse>,
+) -> Result<StatusCode, StatusCode> {
+    DeviceBinding::check(binding.as_deref(), &id.to_string())?;
+    if response.device_id != id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let db = st.pool().await;
+    let found = command_audit::record_response(&st, db.as_ref(), &response).await.map_err(|e| {
+        tracing::error!("Storing ack of command {} failed: {e}", response.correlation_id);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !found {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let status = AckStatus::from_success(response.success);
+    tracing::info!("Device {id} {status} command {}", response.correlation_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /v1/devices/{id}/commands/history` parametreleri
+#[derive(Debug, Default, Deserialize)]
+pub struct CommandHistoryParams {
+    /// Maksimum satır (varsayılan 50, en fazla `MAX_COMMAND_HISTORY_LIMIT`)
+    pub limit: Option<i64>,
+}
+
+/// `device_commands` satırı (`ack_status` TEXT kolonundan okunur)
+#[derive(Debug, sqlx::FromRow)]
+struct CommandHistoryRow {
+    correlation_id: Uuid,
+    command_name: String,
+    parameters: Option<Value>,
+    dispatched_at: DateTime<Utc>,
+    ack_status: Option<String>,
+    ack_message: Option<String>,
+    acked_at: Option<DateTime<Utc>>,
+}
+
+impl From<CommandHistoryRow> for CommandHistoryEntry {
+    fn from(row: CommandHistoryRow) -> Self {
+        Self {
+            correlation_id: row.correlation_id,
+            command_name: row.command_name,
+            parameters: row.parameters,
+            dispatched_at: row.dispatched_at,
+            // Kolonun CHECK kısıtı sadece geçerli değerlere izin verir
+            ack_status: row.ack_status.and_then(|s| s.parse().ok()),
+            ack_message: row.ack_message,
+            acked_at: row.acked_at,
+        }
+    }
+}
+
+/// Cihaza gönderilen komutları listele (yeniden eskiye)
+///
+/// # HTTP
+/// `GET /v1/devices/{id}/commands/history?limit=50`
+///
+/// Denetim kaydının (`command_audit`) son sonuç görünümü; PostgreSQL yoksa
+/// in-memory kayıttan okunur.
+///
+/// # Response (200 OK)
+/// `CommandHistoryEntry` listesi; yanıtı gelmemiş komutlarda `ack_status: null`
+///
+/// # Error Responses
+/// - 400 Bad Request: `limit` 1..=500 dışında
+/// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st), err)]
+pub async fn command_history(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<CommandHistoryParams>,
+) -> Result<Json<Vec<CommandHistoryEntry>>, StatusCode> {
+    let limit = params.limit.unwrap_or(DEFAULT_COMMAND_HISTORY_LIMIT);
+    if !(1..=MAX_COMMAND_HISTORY_LIMIT).contains(&limit) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let Some(db) = &st.pool().await else {
+        // ===== In-Memory Yolu =====
+        let ring = st.command_audit.read().await;
+        let mut entries: Vec<_> = ring.iter().filter(|entry| entry.device_id == id).collect();
+        entries.sort_by(|a, b| b.issued_at.cmp(&a.issued_at).then(a.correlation_id.cmp(&b.correlation_id)));
+        return Ok(Json(entries.into_iter().take(limit as usize).map(CommandHistoryEntry::from).collect()));
+    };
+
+    let rows = sqlx::query_as::<_, CommandHistoryRow>(
+        "SELECT correlation_id, command_name, parameters, dispatched_at, ack_status, ack_message, acked_at
+         FROM device_commands
+         WHERE device_id = $1
+         ORDER BY dispatched_at DESC, correlation_id
+         LIMIT $2",
+    )
+    .bind(id)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Command history query failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(rows.into_iter().map(CommandHistoryEntry::from).collect()))
+}
+
+/// `POST /v1/devices/{id}/commands` body'si
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewDeviceCommand {
+    /// Komut kategorisi: "control", "config", "maintenance"
+    pub command_type: String,
+    /// Komut adı (örn: "relay", "take_photo", "calibrate")
+    pub command_name: String,
+    #[serde(default)]
+    pub parameters: Option<Value>,
+}
+
+/// Cihaza komut gönder
+///
+/// Komut olduğu gibi `DeviceCommand`'a sarılır; cihazın tanımadığı komutlar
+/// `failed` yanıtıyla döner. Her komut veren kimlikle denetim kaydına yazılır.
+///
+/// # HTTP
+/// `POST /v1/devices/{id}/commands`
+///
+/// # Request Body
+/// ```json
+/// {"command_type": "control", "command_name": "relay", "parameters": {"relay_id": "relay_01", "state": "open"}}
+/// ```
+///
+/// # Response (202 Accepted)
+/// Gönderilen `DeviceCommand` (yanıt eşleştirmesi için `correlation_id` içerir).
+///
+/// # Error Responses
+/// - 400 Bad Request: Boş `command_type` veya `command_name`
+/// - 503 Service Unavailable: MQTT broker'a ulaşılamıyor
+#[tracing::instrument(skip(st, issuer), err)]
+pub async fn send_command(
+    State(st): State<AppState>,
+    issuer: Option<Extension<Issuer>>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<NewDeviceCommand>,
+) -> Result<(StatusCode, Json<DeviceCommand>), StatusCode> {
+    if body.command_type.trim().is_empty() || body.command_name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let mut cmd = DeviceCommand::new(id, body.command_type, body.command_name);
+    cmd.parameters = body.parameters;
+    dispatch_command(&st, &cmd, Issuer::name(issuer.as_deref())).await?;
+    Ok((StatusCode::ACCEPTED, Json(cmd)))
+}
+
+/// LED kontrol komutu gönder
+///
+/// # HTTP
+/// `POST /v1/devices/{id}/commands/led`
+///
+/// # Request Body
+/// ```json
+/// {
+///   "led_id": "led_01",
+///   "state": "toggle",
+///   "brightness": 200
+/// }
+/// ```
+///
+/// # Response (202 Accepted)
+/// Gönderilen `DeviceCommand` (yanıt eşleştirmesi için `correlation_id` içerir).
+///
+/// # Error Responses
+/// - 503 Service Unavailable: MQTT broker'a ulaşılamıyor
+#[tracing::instrument(skip(st, issuer), err)]
+pub async fn send_led_command(
+    State(st): State<AppState>,
+    issuer: Option<Extension<Issuer>>,
+    Path(id): Path<Uuid>,
+    Json(led): Json<LedCommand>,
+) -> Result<(StatusCode, Json<DeviceCommand>), StatusCode> {
+    let cmd = DeviceCommand::led(id, &led);
+    dispatch_command(&st, &cmd, Issuer::name(issuer.as_deref())).await?;
+    Ok((StatusCode::ACCEPTED, Json(cmd)))
+}
+
+/// Shadow'un güncellenecek bölümü
+#[derive(Debug, Clone, Copy)]
+enum ShadowSection {
+    Desired,
+    Reported,
+}
+
+/// Cihaz gölgesini al
+///
+/// # HTTP
+/// `GET /v1/devices/{id}/shadow`
+///
+/// # Response (200 OK)
+/// ```json
+/// {
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "desired": {"sensor_interval_secs": 10},
+///   "reported": {"sensors": {"temperature": 22.5}},
+///   "delta": {"sensor_interval_secs": 10},
+///   "version": 3,
+///   "updated_at": "2024-11-13T21:30:00Z"
+/// }
+/// ```
+///
+/// `device` rolündeki token'lar sadece kendi cihazlarının gölgesini okur.
+///
+/// # Error Responses
+/// - 403 Forbidden: Token başka bir cihaza ait
+/// - 404 Not Found: Cihaz için henüz gölge yok
+/// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st, binding), err)]
+pub async fn get_shadow(
+    State(st): State<AppState>,
+    binding: Option<Extension<DeviceBinding>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DeviceShadow>, StatusCode> {
+    DeviceBinding::check(binding.as_deref(), &id.to_string())?;
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        let shadow = sqlx::query_as::<_, DeviceShadow>(
+            "SELECT device_id, desired, reported, delta, version, updated_at FROM device_shadows WHERE device_id = $1"
+        )
+        .bind(id)
+        .fetch_optional(db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+        Ok(Json(shadow))
+    } else {
+        // ===== In-Memory Fallback =====
+        let map = st.shadow_store.read().await;
+        map.get(&id).cloned().map(Json).ok_or(StatusCode::NOT_FOUND)
+    }
+}
+
+/// Cihaz gölgelerini listele
+///
+/// # HTTP
+/// `GET /v1/devices?kind=gateway&stale_for=600`
+///
+/// Gölgeler `device_id`'ye göre sıralı döner. Tür gölgenin `reported.kind`
+/// alanından okunur; alan yoksa cihaz `edge` sayılır. Gateway'ler kendi
+/// durumlarını `"kind": "gateway"` ile raporlar (`reported.status.connected`
+/// bağlantı durumudur).
+///
+/// Her gölgeye cihazın son okumasının zamanı (`last_seen`) eklenir:
+/// `devices` tablosundan, PostgreSQL yoksa Redis'ten (bkz. `record_last_seen`).
+/// `stale_for` verilirse sadece o kadar saniyedir okuma göndermeyen veya
+/// hiç görülmeyen cihazlar döner.
+///
+/// # Error Responses
+/// - 400 Bad Request: Bilinmeyen `kind` veya çok büyük `stale_for`
+/// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st), err)]
+pub async fn list_devices(
+    State(st): State<AppState>,
+    Query(params): Query<DeviceListParams>,
+) -> Result<Json<Vec<DeviceShadow>>, StatusCode> {
+    let cutoff = params.stale_for.map(|secs| stale_cutoff(secs, Utc::now())).transpose()?;
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        let shadows = sqlx::query_as::<_, DeviceShadow>(
+            "SELECT s.device_id, s.desired, s.reported, s.delta, s.version, s.updated_at, d.last_seen
+             FROM device_shadows s
+             LEFT JOIN devices d ON d.id = s.device_id
+             WHERE ($1::text IS NULL OR COALESCE(s.reported->>'kind', 'edge') = $1)
+               AND ($2::timestamptz IS NULL OR d.last_seen IS NULL OR d.last_seen < $2)
+             ORDER BY s.device_id"
+        )
+        .bind(params.kind.map(|kind| kind.as_str()))
+        .bind(cutoff)
+        .fetch_all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Device list query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        Ok(Json(shadows))
+    } else {
+        // ===== In-Memory Fallback =====
+        let map = st.shadow_store.read().await;
+        let mut shadows: Vec<_> = map
+            .values()
+            .filter(|shadow| params.kind.is_none_or(|kind| shadow.kind() == kind))
+            .cloned()
+            .collect();
+        shadows.sort_by_key(|shadow| shadow.device_id);
+
+        if let Some(mut conn) = st.redis.clone() {
+            let ids: Vec<_> = shadows.iter().map(|shadow| shadow.device_id).collect();
+            match last_seen_from_redis(&mut conn, &ids).await {
+                Ok(seen) => shadows.iter_mut().zip(seen).for_each(|(shadow, seen)| shadow.last_seen = seen),
+                Err(e) => tracing::warn!("Redis last_seen read failed: {e}"),
+            }
+        }
+        if let Some(cutoff) = cutoff {
+            shadows.retain(|shadow| is_stale(shadow.last_seen, cutoff));
+        }
+        Ok(Json(shadows))
+    }
+}
+
+/// `stale_for` saniye önce (çok büyükse 400)
+fn stale_cutoff(stale_for: u64, now: DateTime<Utc>) -> Result<DateTime<Utc>, StatusCode> {
+    i64::try_from(stale_for)
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .and_then(|age| now.checked_sub_signed(age))
+        .ok_or(StatusCode::BAD_REQUEST)
+}
+
+/// `cutoff`'tan beri okuma gelmedi mi? (hiç görülmeyenler dahil)
+fn is_stale(last_seen: Option<DateTime<Utc>>, cutoff: DateTime<Utc>) -> bool {
+    last_seen.is_none_or(|seen| seen < cutoff)
+}
+
+/// Cihazların Redis'teki son görülme zamanları (`ids` sırasıyla)
+async fn last_seen_from_redis(
+    conn: &mut redis::aio::ConnectionManager,
+    ids: &[Uuid],
+) -> redis::RedisResult<Vec<Option<DateTime<Utc>>>> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let keys: Vec<_> = ids.iter().map(|id| redis_last_seen_key(&id.to_string())).collect();
+    let millis: Vec<Option<i64>> = redis::cmd("MGET").arg(keys).query_async(conn).await?;
+    Ok(millis.into_iter().map(|ms| ms.and_then(DateTime::from_timestamp_millis)).collect())
+}
+
+/// Okumaların kanıtladığı son görülme zamanlarını kaydet
+///
+/// `POST /api/sensors` ve `POST /v1/sensors/ingest` kabul ettiği okumalarla
+/// çağırır (simülasyon okumaları cihazın canlı olduğunu göstermez). Cihaz
+/// başına en yeni zaman yazılır; sırası karışık gelen eski okumalar
+/// `last_seen`'i geri almaz.
+/// - PostgreSQL: `devices.last_seen` (sadece UUID `device_id`'ler);
+///   `AUTO_REGISTER_DEVICES` ile bilinmeyen cihazlar taslak olarak eklenir
+/// - PostgreSQL yoksa Redis: `device:last_seen:{device_id}` (epoch ms, TTL yok)
+///
+/// Hatalar loglanır; okumanın kaydını engellemez.
+pub async fn record_last_seen(st: &AppState, readings: impl IntoIterator<Item = (String, DateTime<Utc>)>) {
+    let latest = latest_per_device(readings);
+    if latest.is_empty() {
+        return;
+    }
+    if let Some(db) = &st.pool().await {
+        let auto_register = st.cfg.read().await.auto_register_devices;
+        if let Err(e) = touch_last_seen(db, &latest, auto_register).await {
+            tracing::warn!("last_seen update failed: {e}");
+        }
+    } else if let Some(mut conn) = st.redis.clone() {
+        for (device_id, seen) in &latest {
+            let result: redis::RedisResult<()> = redis::Script::new(LAST_SEEN_SCRIPT)
+                .key(redis_last_seen_key(device_id))
+                .arg(seen.timestamp_millis())
+                .invoke_async(&mut conn)
+                .await;
+            if let Err(e) = result {
+                tracing::warn!("Redis last_seen update failed for {device_id}: {e}");
+            }
+        }
+    }
+}
+
+/// Cihaz başına en yeni okuma zamanı
+fn latest_per_device(readings: impl IntoIterator<Item = (String, DateTime<Utc>)>) -> BTreeMap<String, DateTime<Utc>> {
+    let mut latest = BTreeMap::new();
+    for (device_id, seen) in readings {
+        latest.entry(device_id).and_modify(|at: &mut DateTime<Utc>| *at = (*at).max(seen)).or_insert(seen);
+    }
+    latest
+}
+
+/// `devices.last_seen`'i tek sorguda güncelle; etkilenen satır sayısını döner
+async fn touch_last_seen(db: &PgPool, latest: &BTreeMap<String, DateTime<Utc>>, auto_register: bool) -> sqlx::Result<u64> {
+    let (ids, seen): (Vec<Uuid>, Vec<DateTime<Utc>>) =
+        latest.iter().filter_map(|(device_id, at)| Some((device_id.parse::<Uuid>().ok()?, *at))).unzip();
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let sql = if auto_register { REGISTER_LAST_SEEN_SQL } else { TOUCH_LAST_SEEN_SQL };
+    let result = sqlx::query(sql).bind(ids).bind(seen).execute(db).await?;
+    Ok(result.rows_affected())
+}
+
+/// İstenen durumu güncelle
+///
+/// # HTTP
+/// `POST /v1/devices/{id}/shadow/desired`
+///
+/// # Request Body (JSON merge patch)
+/// ```json
+/// {"sensor_interval_secs": 10, "led": null}
+/// ```
+/// `null` değerler alanı siler. Gölge yoksa oluşturulur.
+///
+/// # Response (200 OK)
+/// Güncellenmiş `DeviceShadow` (yeni `delta` ile).
+#[tracing::instrument(skip(st), err)]
+pub async fn update_desired(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(patch): Json<Value>,
+) -> Result<Json<DeviceShadow>, StatusCode> {
+    update_shadow(&st, id, ShadowSection::Desired, &patch).await.map(Json)
+}
+
+/// Raporlanan durumu güncelle
+///
+/// # HTTP
+/// `POST /v1/devices/{id}/shadow/reported`
+///
+/// MQTT gateway tarafından cihazdan gelen sensör okumaları ve durum
+/// mesajları ile çağrılır. Body formatı `update_desired` ile aynıdır.
+/// `kind` verilirse cihaz türü olmalıdır (`edge` veya `gateway`).
+///
+/// # Error Responses
+/// - 400 Bad Request: Patch bir JSON object değil veya `kind` geçersiz
+/// - 403 Forbidden: Cihaz token'ı başka bir cihaza ait
+#[tracing::instrument(skip(st), err)]
+pub async fn update_reported(
+    State(st): State<AppState>,
+    binding: Option<Extension<DeviceBinding>>,
+    Path(id): Path<Uuid>,
+    Json(patch): Json<Value>,
+) -> Result<Json<DeviceShadow>, StatusCode> {
+    DeviceBinding::check(binding.as_deref(), &id.to_string())?;
+    if let Some(kind) = patch.get("kind").filter(|kind| !kind.is_null()) {
+        if serde_json::from_value::<DeviceKind>(kind.clone()).is_err() {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    let shadow = update_shadow(&st, id, ShadowSection::Reported, &patch).await?;
+    if let Some(db) = &st.pool().await {
+        record_timeline_events(db, id, &patch).await;
+    }
+    Ok(Json(shadow))
+}
+
+/// Raporlanan heartbeat ve durumları zaman çizelgesi tablolarına yaz
+/// 
+/// Gateway heartbeat'i `{"last_heartbeat": <zaman>}`, durum mesajını
+/// `{"status": ...}` olarak gönderir. Hatalar loglanır; shadow güncellemesi
+/// zaten yapılmıştır.
+async fn record_timeline_events(db: &PgPool, id: Uuid, patch: &Value) {
+    if let Some(heartbeat) = patch.get("last_heartbeat") {
+        let received_at = heartbeat
+            .as_str()
+            .and_then(|raw| DateTime::parse_from_rfc3339(raw).ok())
+            .map_or_else(Utc::now, |t| t.with_timezone(&Utc));
+        let result = sqlx::query("INSERT INTO device_heartbeats (device_id, received_at) VALUES ($1, $2)")
+            .bind(id)
+            .bind(received_at)
+            .execute(db)
+            .await;
+        if let Err(e) = result {
+            tracing::error!("Heartbeat insert failed: {e}");
+        }
+    }
+    if let Some(status) = patch.get("status") {
+        let result = sqlx::query("INSERT INTO device_status_changes (device_id, status) VALUES ($1, $2)")
+            .bind(id)
+            .bind(status)
+            .execute(db)
+            .await;
+        if let Err(e) = result {
+            tracing::error!("Status change insert failed: {e}");
+        }
+    }
+}
+
+/// Cihazın zaman çizelgesi
+///
+/// # HTTP
+/// `GET /v1/devices/{id}/timeline?from=&to=&limit=`
+///
+/// Okumalar (tüm sensör tipleri), eşik alarmları, heartbeat'ler ve durum
+/// değişiklikleri `timestamp`'e göre azalan sırada döner (varsayılan 500 olay).
+///
+/// # Response (200 OK)
+/// ```json
+/// {
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "events": [
+///     {"timestamp": "2024-01-20T10:30:05Z", "event_type": "heartbeat"},
+///     {"timestamp": "2024-01-20T10:30:00Z", "event_type": "alert", "sensor_type": "temperature", "value": 41.2, "message": "temperature 41.2 above max 35"},
+///     {"timestamp": "2024-01-20T10:30:00Z", "event_type": "reading", "sensor_type": "temperature", "value": 41.2}
+///   ]
+/// }
+/// ```
+///
+/// # Error Responses
+/// - 400 Bad Request: `limit` pozitif değil
+/// - 501 Not Implemented: PostgreSQL bağlı değil
+#[tracing::instrument(skip(st), err)]
+pub async fn get_device_timeline(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(range): Query<TimeRangeParams>,
+) -> Result<Json<DeviceTimeline>, StatusCode> {
+    let db = &st.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let limit = range.limit.unwrap_or(DEFAULT_TIMELINE_LIMIT);
+    if limit <= 0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let events = sqlx::query_as::<_, TimelineEvent>(TIMELINE_SQL)
+        .bind(id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(limit)
+        .fetch_all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Device timeline query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(DeviceTimeline { device_id: id, events }))
+}
+
+/// Cihazın tüm verisini sil (GDPR)
+///
+/// # HTTP
+/// `DELETE /v1/devices/{id}/data`
+///
+/// # Request Body
+/// ```json
+/// {"confirm": true, "include_metadata": true}
+/// ```
+///
+/// Okumalar (`sensor_readings`, `sensor_summaries`, `motion_events`),
+/// alarmlar, heartbeat'ler, durum değişiklikleri, hata raporları ve komut
+/// kayıtları (`device_commands`) tek transaction'da silinir; Redis'teki son
+/// değerler (`sensor:<id>:*`) ardından silinir. `include_metadata` ile cihaz
+/// kaydı (`devices`), gölgesi ve token'ları (`device_tokens`) da silinir.
+///
+/// Cihaz kaydı kalıyorsa `devices/{id}/status`'a çevrimdışı durum
+/// publish edilir. Kayıt silindiyse publish edilmez: gateway mesajı
+/// shadow'a yazıp cihazı yeniden oluştururdu.
+///
+/// İşlem idempotenttir; Redis hatasında tekrar denenebilir.
+///
+/// # Response (200 OK)
+/// ```json
+/// {"readings_deleted": 2160, "alerts_deleted": 3, "device_deleted": true, "commands_deleted": 12, "tokens_revoked": 1}
+/// ```
+///
+/// # Error Responses
+/// - 400 Bad Request: `confirm: true` verilmedi
+/// - 500 Internal Server Error: Database veya Redis hatası
+/// - 501 Not Implemented: `gdpr_deletion_enabled` kapalı
+#[tracing::instrument(skip(st), err)]
+pub async fn delete_device_data(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<DeleteDataRequest>,
+) -> Result<Json<DeleteDataResult>, StatusCode> {
+    check_feature(&*st.features.read().await, "gdpr_deletion_enabled")?;
+    if !request.confirm {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut result = if let Some(db) = &st.pool().await {
+        delete_device_rows(db, id, request.include_metadata).await.map_err(|e| {
+            tracing::error!("Device data deletion failed for {id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+    } else {
+        DeleteDataResult::default()
+    };
+
+    // In-memory kayıtlar (PostgreSQL yokken oluşturulmuş olabilir)
+    {
+        let mut audit = st.command_audit.write().await;
+        let before = audit.len();
+        audit.retain(|entry| entry.device_id != id);
+        result.commands_deleted += (before - audit.len()) as u64;
+    }
+    if request.include_metadata {
+        let removed_device = st.device_store.write().await.remove(&id).is_some();
+        let removed_shadow = st.shadow_store.write().await.remove(&id).is_some();
+        result.device_deleted |= removed_device || removed_shadow;
+
+        let mut tokens = st.device_tokens.write().await;
+        let before = tokens.len();
+        tokens.retain(|_, token| token.device_id != id);
+        result.tokens_revoked += (before - tokens.len()) as u64;
+    }
+
+    if let Some(mut conn) = st.redis.clone() {
+        delete_device_sensors_from_redis(&mut conn, &id.to_string()).await.map_err(|e| {
+            tracing::error!("Redis cleanup failed for {id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    if !request.include_metadata {
+        publish_offline(&st, id);
+    }
+
+    tracing::info!(
+        "Deleted data of device {id}: {} readings, {} alerts, {} commands, {} tokens revoked, device deleted: {}",
+        result.readings_deleted, result.alerts_deleted, result.commands_deleted, result.tokens_revoked, result.device_deleted
+    );
+    Ok(Json(result))
+}
+
+/// Cihazın PostgreSQL satırlarını tek transaction'da sil
+async fn delete_device_rows(db: &PgPool, id: Uuid, include_metadata: bool) -> sqlx::Result<DeleteDataResult> {
+    let mut tx = db.begin().await?;
+    let (readings_deleted, alerts_deleted) = delete_history_rows(&mut tx, id).await?;
+    let commands_deleted = delete_rows(&mut tx, "DELETE FROM device_commands WHERE device_id = $1", id).await?;
+
+    let (device_deleted, tokens_revoked) = if include_metadata {
+        let shadows = delete_rows(&mut tx, "DELETE FROM device_shadows WHERE device_id = $1", id).await?;
+        let tokens = delete_rows(&mut tx, "DELETE FROM device_tokens WHERE device_id = $1", id).await?;
+        (delete_rows(&mut tx, "DELETE FROM devices WHERE id = $1", id).await? + shadows > 0, tokens)
+    } else {
+        (false, 0)
+    };
+
+    tx.commit().await?;
+    Ok(DeleteDataResult { readings_deleted, alerts_deleted, device_deleted, commands_deleted, tokens_revoked })
+}
+
+/// Cihazın geçmiş satırlarını sil; silinen (okuma, alarm) sayısını döner
+///
+/// Okuma tabloları `device_id`'yi TEXT, olay tabloları UUID olarak tutar.
+async fn delete_history_rows(conn: &mut PgConnection, id: Uuid) -> sqlx::Result<(u64, u64)> {
+    let readings_deleted = delete_rows(conn, "DELETE FROM sensor_readings WHERE device_id = $1::text", id).await?;
+    delete_rows(conn, "DELETE FROM sensor_summaries WHERE device_id = $1::text", id).await?;
+    delete_rows(conn, "DELETE FROM motion_events WHERE device_id = $1::text", id).await?;
+    let alerts_deleted = delete_rows(conn, "DELETE FROM sensor_alerts WHERE device_id = $1::text", id).await?;
+    delete_rows(conn, "DELETE FROM device_heartbeats WHERE device_id = $1", id).await?;
+    delete_rows(conn, "DELETE FROM device_status_changes WHERE device_id = $1", id).await?;
+    delete_rows(conn, "DELETE FROM device_errors WHERE device_id = $1", id).await?;
+    Ok((readings_deleted, alerts_deleted))
+}
+
+/// `$1`'e cihaz ID'si bağlanan DELETE'i çalıştır
+async fn delete_rows(conn: &mut PgConnection, sql: &str, id: Uuid) -> sqlx::Result<u64> {
+    Ok(sqlx::query(sql).bind(id).execute(conn).await?.rows_affected())
+}
+
+/// Cihazı kaldır
+///
+/// # HTTP
+/// `DELETE /v1/devices/{id}?purge_history=true`
+///
+/// Cihaz kaydı ve gölgesi silinir; gölgede cihazın henüz uygulamadığı
+/// `desired` değişiklikleri ve yanıtı beklenen komutlar iptal edilir. Sensörlerinin
+/// `sensor:{id}:*` cache key'leri silinir, `/api/sensors`'ta görünmez.
+/// Cihaz token'ları iptal edilir. `purge_history=true` ile okuma, alarm ve
+/// olay geçmişi de silinir; aksi halde geçmiş sorgulanabilir kalır. Komut
+/// denetim kaydı her durumda korunur (tamamen silmek için
+/// `DELETE /v1/devices/{id}/data`). Ayrıntılar: `device_teardown`.
+///
+/// # Response (200 OK)
+/// `DeviceTeardownResult`
+///
+/// # Error Responses
+/// - 404 Not Found: Cihaz kaydı veya gölgesi yok
+/// - 500 Internal Server Error: Database hatası (hiçbir şey silinmez)
+#[tracing::instrument(skip(st), err)]
+pub async fn delete_device(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DeviceTeardownParams>,
+) -> Result<Json<DeviceTeardownResult>, StatusCode> {
+    device_teardown(&st, id, params.purge_history).await.map(Json)
+}
+
+/// Cihazı ve ona bağlı durumu kaldır
+///
+/// 1. PostgreSQL satırları tek transaction'da silinir (kayıt, gölge, cihaz
+///    token'ları ve `purge_history` ile geçmiş); yanıtı beklenen komutlar
+///    `cancelled` olur. Hata olursa transaction geri alınır ve sonraki
+///    adımlara geçilmez.
+/// 2. In-memory kayıt, gölge ve token'lar silinir, bekleyen komutlar iptal
+///    edilir (PostgreSQL yokken oluşturulmuş olabilir).
+/// 3. Redis cache'i en iyi çabayla temizlenir: hata loglanır, özet
+///    `cache_keys_deleted: null` döner; istek tekrarlanarak temizlenebilir.
+///
+/// Çevrimdışı durum publish edilmez: gateway mesajı shadow'a yazıp
+/// cihazı yeniden oluştururdu.
+pub async fn device_teardown(st: &AppState, id: Uuid, purge_history: bool) -> Result<DeviceTeardownResult, StatusCode> {
+    let in_memory = st.device_store.read().await.contains_key(&id) || st.shadow_store.read().await.contains_key(&id);
+
+    let mut result = match &st.pool().await {
+        Some(db) => teardown_rows(db, id, purge_history, in_memory).await.map_err(|e| {
+            tracing::error!("Device teardown failed for {id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+        None => Some(DeviceTeardownResult::default()),
+    }
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    result.device_deleted |= st.device_store.write().await.remove(&id).is_some();
+    if let Some(shadow) = st.shadow_store.write().await.remove(&id) {
+        result.shadow_deleted = true;
+        result.pending_changes_cancelled += pending_changes(&shadow.delta);
+    }
+    if !result.device_deleted && !result.shadow_deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    {
+        let mut tokens = st.device_tokens.write().await;
+        let before = tokens.len();
+        tokens.retain(|_, token| token.device_id != id);
+        result.tokens_revoked += (before - tokens.len()) as u64;
+    }
+    result.commands_cancelled += command_audit::cancel_pending_in_memory(st, id, Utc::now()).await;
+
+    if let Some(mut conn) = st.redis.clone() {
+        match delete_device_sensors_from_redis(&mut conn, &id.to_string()).await {
+            Ok(deleted) => result.cache_keys_deleted = Some(deleted),
+            Err(e) => tracing::warn!("Redis cleanup failed for removed device {id}: {e}"),
+        }
+    }
+
+    tracing::info!(
+        "Removed device {id}: {} pending changes and {} commands cancelled, {} readings and {} alerts deleted, {:?} cache keys",
+        result.pending_changes_cancelled, result.commands_cancelled, result.readings_deleted, result.alerts_deleted, result.cache_keys_deleted
+    );
+    Ok(result)
+}
+
+/// Teardown'un PostgreSQL adımı
+///
+/// Cihazın kaydı da gölgesi de yoksa (ve in-memory kaydı yoksa) transaction
+/// geri alınır ve `None` döner: geçmiş bilinmeyen bir ID için silinmez.
+async fn teardown_rows(db: &PgPool, id: Uuid, purge_history: bool, in_memory: bool) -> sqlx::Result<Option<DeviceTeardownResult>> {
+    let mut tx = db.begin().await?;
+    let device_deleted = delete_rows(&mut tx, "DELETE FROM devices WHERE id = $1", id).await? > 0;
+    let delta: Option<Value> = sqlx::query_scalar("DELETE FROM device_shadows WHERE device_id = $1 RETURNING delta")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if !device_deleted && delta.is_none() && !in_memory {
+        return Ok(None);
+    }
+
+    let tokens_revoked = delete_rows(&mut tx, "DELETE FROM device_tokens WHERE device_id = $1", id).await?;
+    let commands_cancelled = command_audit::cancel_pending(&mut tx, id, Utc::now()).await?;
+    let (readings_deleted, alerts_deleted) = if purge_history {
+        delete_history_rows(&mut tx, id).await?
+    } else {
+        (0, 0)
+    };
+    tx.commit().await?;
+
+    Ok(Some(DeviceTeardownResult {
+        device_deleted,
+        shadow_deleted: delta.is_some(),
+        pending_changes_cancelled: delta.as_ref().map_or(0, pending_changes),
+        readings_deleted,
+        alerts_deleted,
+        cache_keys_deleted: None,
+        tokens_revoked,
+        commands_cancelled,
+    }))
+}
+
+/// Gölge `delta`'sındaki (cihazın henüz uygulamadığı) üst seviye alan sayısı
+fn pending_changes(delta: &Value) -> u64 {
+    delta.as_object().map_or(0, |fields| fields.len() as u64)
+}
+
+/// Çevrimdışı durumu publish et (MQTT yoksa veya kuyruk doluysa loglanır)
+fn publish_offline(st: &AppState, id: Uuid) {
+    let Some(client) = &st.mqtt else {
+        return;
+    };
+    let payload = match serde_json::to_vec(&DeviceMessage::offline(id)) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::warn!("Offline status encoding failed: {e}");
+            return;
+        }
+    };
+    if let Err(e) = client.try_publish(status_topic(id), QoS::AtLeastOnce, false, payload) {
+        tracing::warn!("Offline status publish failed for {id}: {e}");
+    }
+}
+
+/// Gölgenin bir bölümüne merge patch uygula
+///
+/// PostgreSQL yolunda satır transaction içinde `FOR UPDATE` ile kilitlenir;
+/// eş zamanlı raporlar birbirinin üzerine yazmaz.
+///
+/// # Error Responses
+/// - 400 Bad Request: Patch bir JSON object değil
+async fn update_shadow(
+    st: &AppState,
+    id: Uuid,
+    section: ShadowSection,
+    patch: &Value,
+) -> Result<DeviceShadow, StatusCode> {
+    if !patch.is_object() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let apply = |shadow: &mut DeviceShadow| match section {
+        ShadowSection::Desired => shadow.update_desired(patch),
+        ShadowSection::Reported => shadow.update_reported(patch),
+    };
+
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        let mut tx = db.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        // Step 1: Satırın var olduğundan emin ol, sonra kilitle
+        sqlx::query("INSERT INTO device_shadows (device_id) VALUES ($1) ON CONFLICT (device_id) DO NOTHING")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        let mut shadow = sqlx::query_as::<_, DeviceShadow>(
+            "SELECT device_id, desired, reported, delta, version, updated_at FROM device_shadows WHERE device_id = $1 FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        // Step 2: Patch'i uygula (delta + version yalnızca değişiklikte yeniden hesaplanır)
+        if !apply(&mut shadow) {
+            tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            return Ok(shadow);
+        }
+
+        // Step 3: Kaydet
+        sqlx::query(
+            "UPDATE device_shadows SET desired = $1, reported = $2, delta = $3, version = $4, updated_at = $5
+             WHERE device_id = $6"
+        )
+        .bind(&shadow.desired)
+        .bind(&shadow.reported)
+        .bind(&shadow.delta)
+        .bind(shadow.version as i64)
+        .bind(shadow.updated_at)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        Ok(shadow)
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut map = st.shadow_store.write().await;
+        let shadow = map.entry(id).or_insert_with(|| DeviceShadow::new(id));
+        apply(shadow);
+        Ok(shadow.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rumqttc::{AsyncClient, Request};
+    use shared_types::messages::LedState;
+
+    fn led() -> LedCommand {
+        LedCommand { led_id: "led_01".into(), state: LedState::On, brightness: None, duration_ms: None, blink: None }
+    }
+
+    #[tokio::test]
+    async fn test_led_command_published_to_device_topic() {
+        let (tx, rx) = flume::bounded(10);
+        let st = AppState { mqtt: Some(AsyncClient::from_senders(tx)), ..AppState::for_tests() };
+        let device_id = Uuid::new_v4();
+
+        let (status, Json(cmd)) = send_led_command(State(st), None, Path(device_id), Json(led()))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(cmd.command_name, "led");
+
+        // Publish isteği event loop kuyruğuna düşmüş olmalı
+        let Ok(Request::Publish(publish)) = rx.try_recv() else {
+            panic!("expected a publish request");
+        };
+        assert_eq!(publish.topic, format!("devices/{device_id}/commands"));
+        let sent: DeviceCommand = serde_json::from_slice(&publish.payload).unwrap();
+        assert_eq!(sent.correlation_id, cmd.correlation_id);
+        assert_eq!(LedCommand::from_command(&sent).unwrap(), led());
+    }
+
+    #[tokio::test]
+    async fn test_led_command_without_mqtt_is_unavailable() {
+        let result = send_led_command(State(AppState::for_tests()), None, Path(Uuid::new_v4()), Json(led())).await;
+        assert_eq!(result.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_shadow_desired_and_reported_flow() {
+        let st = AppState::for_tests();
+        let id = Uuid::new_v4();
+
+        assert_eq!(get_shadow(State(st.clone()), None, Path(id)).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        let desired = serde_json::json!({"sensor_interval_secs": 10, "led": {"led_01": "on"}});
+        let Json(shadow) = update_desired(State(st.clone()), Path(id), Json(desired.clone())).await.unwrap();
+        assert_eq!(shadow.delta, desired);
+
+        let reported = serde_json::json!({"sensor_interval_secs": 10, "sensors": {"temperature": 22.5}});
+        let _ = update_reported(State(st.clone()), None, Path(id), Json(reported.clone())).await.unwrap();
+        // Aynı okumanın tekrarı versiyonu artırmaz
+        let Json(shadow) = update_reported(State(st.clone()), None, Path(id), Json(reported)).await.unwrap();
+        assert_eq!(shadow.version, 2);
+
+        let Json(shadow) = get_shadow(State(st), None, Path(id)).await.unwrap();
+        assert_eq!(shadow.version, 2);
+        assert_eq!(shadow.delta, serde_json::json!({"led": {"led_01": "on"}}));
+        assert_eq!(shadow.reported["sensors"]["temperature"], 22.5);
+    }
+
+    #[tokio::test]
+    async fn test_shadow_rejects_non_object_patch() {
+        let result = update_desired(State(AppState::for_tests()), Path(Uuid::new_v4()), Json(serde_json::json!([1]))).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_gateway_kind_reported_and_listed() {
+        let st = AppState::for_tests();
+        let (edge, gateway) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let _ = update_reported(State(st.clone()), None, Path(edge), Json(serde_json::json!({"status": {"uptime": 10}}))).await.unwrap();
+        let status = serde_json::json!({"kind": "gateway", "status": {"kind": "gateway", "connected": true, "version": "0.1.0"}});
+        let Json(shadow) = update_reported(State(st.clone()), None, Path(gateway), Json(status)).await.unwrap();
+        assert_eq!(shadow.kind(), DeviceKind::Gateway);
+
+        let invalid = update_reported(State(st.clone()), None, Path(Uuid::new_v4()), Json(serde_json::json!({"kind": "camera"}))).await;
+        assert_eq!(invalid.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        let list = |kind| {
+            let st = st.clone();
+            async move {
+                let Json(shadows) = list_devices(State(st), Query(DeviceListParams { kind, stale_for: None })).await.unwrap();
+                shadows.into_iter().map(|s| s.device_id).collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(list(Some(DeviceKind::Gateway)).await, [gateway]);
+        assert_eq!(list(Some(DeviceKind::Edge)).await, [edge]);
+        assert_eq!(list(None).await.len(), 2);
+    }
+
+    #[test]
+    fn test_latest_reading_wins_and_never_seen_is_stale() {
+        let now = Utc::now();
+        let (old, new) = (now - TimeDelta::minutes(20), now - TimeDelta::minutes(1));
+        let latest = latest_per_device([("a".to_string(), new), ("a".to_string(), old), ("b".to_string(), old)]);
+        assert_eq!(latest["a"], new);
+        assert_eq!(latest["b"], old);
+
+        let cutoff = stale_cutoff(600, now).unwrap();
+        assert!(is_stale(Some(old), cutoff));
+        assert!(!is_stale(Some(new), cutoff));
+        assert!(is_stale(None, cutoff));
+        assert_eq!(stale_cutoff(u64::MAX, now).unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_stale_filter_without_db_or_redis_lists_all_as_never_seen() {
+        let st = AppState::for_tests();
+        let device = Uuid::new_v4();
+        let _ = update_reported(State(st.clone()), None, Path(device), Json(serde_json::json!({"status": {"uptime": 1}}))).await.unwrap();
+
+        let params = DeviceListParams { kind: None, stale_for: Some(600) };
+        let Json(shadows) = list_devices(State(st.clone()), Query(params)).await.unwrap();
+        assert_eq!(shadows.len(), 1);
+        assert_eq!(shadows[0].last_seen, None);
+
+        let params = DeviceListParams { kind: None, stale_for: Some(u64::MAX) };
+        assert_eq!(list_devices(State(st), Query(params)).await.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_delete_device_data_requires_confirm_and_clears_memory() {
+        let st = AppState::for_tests();
+        let device = shared_types::Device::new(&shared_types::DeviceProvisioningRequest {
+            serial_number: "10000000abcdef01".into(),
+            firmware_version: "0.1.0".into(),
+            model: "rpi".into(),
+            public_key_pem: None,
+        });
+        let id = device.id;
+        st.device_store.write().await.insert(id, device);
+        let _ = update_reported(State(st.clone()), None, Path(id), Json(serde_json::json!({"status": {"uptime": 1}}))).await.unwrap();
+
+        let unconfirmed = DeleteDataRequest { confirm: false, include_metadata: true };
+        let result = delete_device_data(State(st.clone()), Path(id), Json(unconfirmed)).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+        assert!(st.device_store.read().await.contains_key(&id));
+
+        let (tx, _rx) = flume::bounded(10);
+        let with_mqtt = AppState { mqtt: Some(AsyncClient::from_senders(tx)), ..st.clone() };
+        let _ = send_led_command(State(with_mqtt), None, Path(id), Json(led())).await.unwrap();
+        let _ = crate::routes::device_tokens::issue_device_token(&st, id, None, None, Utc::now()).await.unwrap();
+
+        let request = DeleteDataRequest { confirm: true, include_metadata: true };
+        let Json(result) = delete_device_data(State(st.clone()), Path(id), Json(request)).await.unwrap();
+        assert_eq!(
+            result,
+            DeleteDataResult { readings_deleted: 0, alerts_deleted: 0, device_deleted: true, commands_deleted: 1, tokens_revoked: 1 }
+        );
+        assert!(st.device_store.read().await.is_empty());
+        assert!(st.shadow_store.read().await.is_empty());
+        assert!(st.command_audit.read().await.is_empty());
+        assert!(st.device_tokens.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_device_data_publishes_offline_status() {
+        let (tx, rx) = flume::bounded(10);
+        let st = AppState { mqtt: Some(AsyncClient::from_senders(tx)), ..AppState::for_tests() };
+        let id = Uuid::new_v4();
+
+        let request = DeleteDataRequest { confirm: true, include_metadata: false };
+        let Json(result) = delete_device_data(State(st.clone()), Path(id), Json(request)).await.unwrap();
+        assert!(!result.device_deleted);
+        let Ok(Request::Publish(publish)) = rx.try_recv() else {
+            panic!("expected a publish request");
+        };
+        assert_eq!(publish.topic, format!("devices/{id}/status"));
+        let sent: DeviceMessage = serde_json::from_slice(&publish.payload).unwrap();
+        assert_eq!((sent.device_id, sent.data), (id, serde_json::json!({"connected": false})));
+
+        // Kayıt da silinirse publish edilmez (gateway gölgeyi yeniden oluştururdu)
+        let request = DeleteDataRequest { confirm: true, include_metadata: true };
+        let _ = delete_device_data(State(st), Path(id), Json(request)).await.unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    fn sample_device() -> shared_types::Device {
+        shared_types::Device::new(&shared_types::DeviceProvisioningRequest {
+            serial_number: "10000000abcdef02".into(),
+            firmware_version: "0.1.0".into(),
+            model: "rpi".into(),
+            public_key_pem: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_delete_device_cancels_pending_changes() {
+        let st = AppState::for_tests();
+        let device = sample_device();
+        let id = device.id;
+        st.device_store.write().await.insert(id, device);
+        let desired = serde_json::json!({"sensor_interval_secs": 10, "led": {"led_01": "on"}});
+        let _ = update_desired(State(st.clone()), Path(id), Json(desired)).await.unwrap();
+        let (tx, _rx) = flume::bounded(10);
+        let with_mqtt = AppState { mqtt: Some(AsyncClient::from_senders(tx)), ..st.clone() };
+        let (_, Json(acked)) = send_led_command(State(with_mqtt.clone()), None, Path(id), Json(led())).await.unwrap();
+        let (_, Json(pending)) = send_led_command(State(with_mqtt), None, Path(id), Json(led())).await.unwrap();
+        let _ = ack_command(State(st.clone()), None, Path(id), Json(DeviceCommandResponse::ok(&acked))).await.unwrap();
+
+        let Json(result) = delete_device(State(st.clone()), Path(id), Query(DeviceTeardownParams::default())).await.unwrap();
+        assert_eq!(
+            result,
+            DeviceTeardownResult {
+                device_deleted: true,
+                shadow_deleted: true,
+                pending_changes_cancelled: 2,
+                commands_cancelled: 1,
+                ..Default::default()
+            }
+        );
+        assert!(st.device_store.read().await.is_empty());
+        assert!(st.shadow_store.read().await.is_empty());
+
+        // Denetim kaydı kalır; yanıtı beklenen komut iptal edilmiştir
+        let Json(history) = command_history(State(st.clone()), Path(id), Query(CommandHistoryParams::default())).await.unwrap();
+        let status = |correlation_id| history.iter().find(|e| e.correlation_id == correlation_id).unwrap().ack_status;
+        assert_eq!(status(pending.correlation_id), Some(AckStatus::Cancelled));
+        assert_eq!(status(acked.correlation_id), Some(AckStatus::Executed));
+
+        let again = delete_device(State(st), Path(id), Query(DeviceTeardownParams::default())).await;
+        assert_eq!(again.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_delete_device_db_failure_keeps_memory() {
+        let db = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(200))
+            .connect_lazy("postgres://postgres@127.0.0.1:1/unreachable")
+            .unwrap();
+        let st = AppState::for_tests_with_db(db);
+        let device = sample_device();
+        let id = device.id;
+        st.device_store.write().await.insert(id, device);
+
+        let result = delete_device(State(st.clone()), Path(id), Query(DeviceTeardownParams { purge_history: true })).await;
+        assert_eq!(result.unwrap_err(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(st.device_store.read().await.contains_key(&id));
+    }
+
+    #[tokio::test]
+    async fn test_timeline_without_db_is_not_implemented() {
+        let result = get_device_timeline(State(AppState::for_tests()), Path(Uuid::new_v4()), Query(TimeRangeParams::default())).await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[tokio::test]
+    async fn test_command_history_validates_before_db() {
+        let history = |limit| command_history(State(AppState::for_tests()), Path(Uuid::new_v4()), Query(CommandHistoryParams { limit }));
+        assert_eq!(history(Some(0)).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        assert_eq!(history(Some(MAX_COMMAND_HISTORY_LIMIT + 1)).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        // PostgreSQL yoksa in-memory denetim kaydından okunur
+        assert_eq!(history(None).await.unwrap().0, []);
+
+        // Body başka bir cihaza ait
+        let command = DeviceCommand::new(Uuid::new_v4(), "control".into(), "led".into());
+        let ack = ack_command(State(AppState::for_tests()), None, Path(Uuid::new_v4()), Json(DeviceCommandResponse::ok(&command))).await;
+        assert_eq!(ack.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_timeline_merges_event_types_newest_first() {
+        use crate::routes::sensors::{add_sensor_data, SensorData};
+        use chrono::SubsecRound;
+        use shared_types::TimelineEventType;
+
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        st.cfg.write().await.alert_thresholds = "temperature=:35".into();
+        let (id, other) = (Uuid::new_v4(), Uuid::new_v4());
+        // PostgreSQL mikrosaniye saklar
+        let base = (Utc::now() - chrono::Duration::hours(1)).trunc_subsecs(0);
+        let at = |minutes: i64| base + chrono::Duration::minutes(minutes);
+
+        let ingest = |device: Uuid, sensor_type: &str, value: f64, minutes: i64| {
+            let data = SensorData {
+                device_id: device.to_string(),
+                sensor_type: sensor_type.into(),
+                value,
+                unit: String::new(),
+                timestamp: at(minutes).to_rfc3339(),
+                metadata: None,
+                received_at: None,
+                gateway_received_at: None,
+                stale: false,
+            };
+            let st = st.clone();
+            async move {
+                let Json(result) = add_sensor_data(State(st), None, crate::msgpack::JsonOrMsgPack(data)).await.unwrap();
+                assert_eq!(result.accepted, 1);
+            }
+        };
+        ingest(id, "temperature", 22.5, 0).await;
+        ingest(id, "humidity", 55.0, 1).await;
+        // Eşiği aşar: okuma + alarm
+        ingest(id, "temperature", 41.2, 2).await;
+        ingest(other, "temperature", 20.0, 3).await;
+
+        let _ = update_reported(State(st.clone()), None, Path(id), Json(serde_json::json!({"last_heartbeat": at(4)}))).await.unwrap();
+        let _ = update_reported(State(st.clone()), None, Path(id), Json(serde_json::json!({"status": {"uptime": "1h"}}))).await.unwrap();
+        // Sensör raporları zaman çizelgesine ayrıca yazılmaz (okuma zaten var)
+        let _ = update_reported(State(st.clone()), None, Path(id), Json(serde_json::json!({"sensors": {"temperature": 41.2}}))).await.unwrap();
+
+        let Json(timeline) = get_device_timeline(State(st.clone()), Path(id), Query(TimeRangeParams::default())).await.unwrap();
+        assert_eq!(timeline.device_id, id);
+        let kinds: Vec<_> = timeline.events.iter().map(|e| (e.event_type, e.sensor_type.as_deref(), e.value)).collect();
+        // Alarm ve durum kaydı işlendikleri anın zamanını taşır (okumalardan yeni)
+        assert_eq!(
+            kinds,
+            [
+                (TimelineEventType::StatusChange, None, None),
+                (TimelineEventType::Alert, Some("temperature"), Some(41.2)),
+                (TimelineEventType::Heartbeat, None, None),
+                (TimelineEventType::Reading, Some("temperature"), Some(41.2)),
+                (TimelineEventType::Reading, Some("humidity"), Some(55.0)),
+                (TimelineEventType::Reading, Some("temperature"), Some(22.5)),
+            ]
+        );
+        assert!(timeline.events.windows(2).all(|w| w[0].timestamp >= w[1].timestamp));
+        assert_eq!(timeline.events[0].message.as_deref(), Some(r#"{"uptime": "1h"}"#));
+        assert_eq!(timeline.events[1].message.as_deref(), Some("temperature 41.2 above max 35"));
+        assert_eq!(timeline.events[2].timestamp, at(4));
+
+        // Aralık ve limit
+        let range = TimeRangeParams { from: Some(at(1)), to: Some(at(2)), limit: None };
+        let Json(window) = get_device_timeline(State(st.clone()), Path(id), Query(range)).await.unwrap();
+        assert_eq!(window.events.iter().filter(|e| e.event_type == TimelineEventType::Reading).count(), 2);
+        let range = TimeRangeParams { limit: Some(2), ..Default::default() };
+        let Json(limited) = get_device_timeline(State(st.clone()), Path(id), Query(range)).await.unwrap();
+        assert_eq!(limited.events, timeline.events[..2]);
+        let range = TimeRangeParams { limit: Some(0), ..Default::default() };
+        assert_eq!(get_device_timeline(State(st), Path(id), Query(range)).await.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        for device in [id, other] {
+            sqlx::query("DELETE FROM sensor_readings WHERE device_id = $1").bind(device.to_string()).execute(&db).await.unwrap();
+            sqlx::query("DELETE FROM sensor_alerts WHERE device_id = $1").bind(device.to_string()).execute(&db).await.unwrap();
+        }
+        sqlx::query("DELETE FROM device_heartbeats WHERE device_id = $1").bind(id).execute(&db).await.unwrap();
+        sqlx::query("DELETE FROM device_status_changes WHERE device_id = $1").bind(id).execute(&db).await.unwrap();
+        sqlx::query("DELETE FROM device_shadows WHERE device_id = $1").bind(id).execute(&db).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_list_devices_filters_kind_in_db() {
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        let (edge, gateway) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let _ = update_reported(State(st.clone()), None, Path(edge), Json(serde_json::json!({"sensors": {"temperature": 21.0}}))).await.unwrap();
+        let _ = update_reported(State(st.clone()), None, Path(gateway), Json(serde_json::json!({"kind": "gateway"}))).await.unwrap();
+
+        let Json(gateways) = list_devices(State(st.clone()), Query(DeviceListParams { kind: Some(DeviceKind::Gateway), stale_for: None })).await.unwrap();
+        assert!(gateways.iter().any(|s| s.device_id == gateway));
+        assert!(gateways.iter().all(|s| s.kind() == DeviceKind::Gateway));
+        let Json(edges) = list_devices(State(st.clone()), Query(DeviceListParams { kind: Some(DeviceKind::Edge), stale_for: None })).await.unwrap();
+        assert!(edges.iter().any(|s| s.device_id == edge) && !edges.iter().any(|s| s.device_id == gateway));
+        let Json(all) = list_devices(State(st), Query(DeviceListParams::default())).await.unwrap();
+        assert!(all.windows(2).all(|w| w[0].device_id < w[1].device_id));
+
+        for device in [edge, gateway] {
+            sqlx::query("DELETE FROM device_shadows WHERE device_id = $1").bind(device).execute(&db).await.unwrap();
+        }
+    }
+
+    async fn last_seen_in_db(db: &PgPool, device: Uuid) -> Option<Option<DateTime<Utc>>> {
+        sqlx::query_scalar("SELECT last_seen FROM devices WHERE id = $1").bind(device).fetch_optional(db).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_ingest_auto_registers_and_never_moves_last_seen_back() {
+        use crate::routes::sensors::{add_sensor_data, ingest_sensor_batch, SensorData};
+        use axum::extract::ConnectInfo;
+        use chrono::SubsecRound;
+
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        let device = Uuid::new_v4();
+        let base = Utc::now().trunc_subsecs(0) - TimeDelta::hours(1);
+        let reading = |at: DateTime<Utc>| SensorData {
+            device_id: device.to_string(),
+            sensor_type: "temperature".into(),
+            value: 21.0,
+            unit: "°C".into(),
+            timestamp: at.to_rfc3339(),
+            metadata: None,
+            received_at: None,
+            gateway_received_at: None,
+            stale: false,
+        };
+
+        // Bayrak kapalıyken bilinmeyen cihaz eklenmez
+        let _ = add_sensor_data(State(st.clone()), None, crate::msgpack::JsonOrMsgPack(reading(base))).await.unwrap();
+        assert_eq!(last_seen_in_db(&db, device).await, None);
+
+        st.cfg.write().await.auto_register_devices = true;
+        let _ = add_sensor_data(State(st.clone()), None, crate::msgpack::JsonOrMsgPack(reading(base))).await.unwrap();
+        assert_eq!(last_seen_in_db(&db, device).await, Some(Some(base)));
+        let (serial, model): (String, String) = sqlx::query_as("SELECT serial_number, model FROM devices WHERE id = $1")
+            .bind(device)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!((serial.as_str(), model.as_str()), (device.to_string().as_str(), "unknown"));
+
+        // Batch içinde sırası karışık: en yenisi kazanır
+        let newest = base + TimeDelta::minutes(5);
+        let addr = ConnectInfo(std::net::SocketAddr::from(([10, 0, 0, 96], 40000)));
+        let batch = vec![reading(newest), reading(base - TimeDelta::minutes(20))];
+        let Json(result) = ingest_sensor_batch(State(st.clone()), None, addr, crate::msgpack::JsonOrMsgPack(batch)).await.unwrap();
+        assert_eq!(result.accepted, 2);
+        assert_eq!(last_seen_in_db(&db, device).await, Some(Some(newest)));
+
+        // Geç gelen eski okuma last_seen'i geri almaz
+        let _ = add_sensor_data(State(st.clone()), None, crate::msgpack::JsonOrMsgPack(reading(base - TimeDelta::minutes(30)))).await.unwrap();
+        assert_eq!(last_seen_in_db(&db, device).await, Some(Some(newest)));
+
+        sqlx::query("DELETE FROM sensor_readings WHERE device_id = $1").bind(device.to_string()).execute(&db).await.unwrap();
+        sqlx::query("DELETE FROM devices WHERE id = $1").bind(device).execute(&db).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_list_devices_stale_filter_in_db() {
+        use chrono::SubsecRound;
+
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        st.cfg.write().await.auto_register_devices = true;
+        let (fresh, stale, never) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now().trunc_subsecs(0);
+
+        record_last_seen(&st, [(fresh.to_string(), now - TimeDelta::minutes(1)), (stale.to_string(), now - TimeDelta::hours(2))]).await;
+        for device in [fresh, stale, never] {
+            let _ = update_reported(State(st.clone()), None, Path(device), Json(serde_json::json!({"status": {"uptime": 1}}))).await.unwrap();
+        }
+
+        let Json(all) = list_devices(State(st.clone()), Query(DeviceListParams::default())).await.unwrap();
+        let fresh_shadow = all.iter().find(|s| s.device_id == fresh).unwrap();
+        assert_eq!(fresh_shadow.last_seen, Some(now - TimeDelta::minutes(1)));
+
+        let params = DeviceListParams { kind: None, stale_for: Some(600) };
+        let Json(listed) = list_devices(State(st), Query(params)).await.unwrap();
+        let ids: Vec<_> = listed.iter().map(|s| s.device_id).filter(|id| [fresh, stale, never].contains(id)).collect();
+        let mut expected = vec![stale, never];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        for device in [fresh, stale, never] {
+            sqlx::query("DELETE FROM device_shadows WHERE device_id = $1").bind(device).execute(&db).await.unwrap();
+            sqlx::query("DELETE FROM devices WHERE id = $1").bind(device).execute(&db).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_delete_device_data_clears_all_tables() {
+        use crate::routes::sensors::{add_sensor_data, SensorData};
+
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        st.cfg.write().await.alert_thresholds = "temperature=:35".into();
+        let (id, other) = (Uuid::new_v4(), Uuid::new_v4());
+
+        for (device, value) in [(id, 22.5), (id, 41.2), (other, 20.0)] {
+            let data = SensorData {
+                device_id: device.to_string(),
+                sensor_type: "temperature".into(),
+                value,
+                unit: "°C".into(),
+                timestamp: Utc::now().to_rfc3339(),
+                metadata: None,
+                received_at: None,
+                gateway_received_at: None,
+                stale: false,
+            };
+            let _ = add_sensor_data(State(st.clone()), None, crate::msgpack::JsonOrMsgPack(data)).await.unwrap();
+        }
+        let _ = update_reported(State(st.clone()), None, Path(id), Json(serde_json::json!({"last_heartbeat": Utc::now()}))).await.unwrap();
+        let _ = update_reported(State(st.clone()), None, Path(id), Json(serde_json::json!({"status": {"uptime": 1}}))).await.unwrap();
+        sqlx::query("INSERT INTO sensor_summaries (device_id, sensor_type, bucket_start, min_value, max_value, avg_value, count) VALUES ($1, 'temperature', now(), 1, 2, 1.5, 2)")
+            .bind(id.to_string()).execute(&db).await.unwrap();
+        sqlx::query("INSERT INTO motion_events (device_id, sensor_type, started_at) VALUES ($1, 'motion', now())")
+            .bind(id.to_string()).execute(&db).await.unwrap();
+        sqlx::query("INSERT INTO devices (id, serial_number, model, firmware_version) VALUES ($1, $2, 'rpi', '0.1.0')")
+            .bind(id).bind(format!("serial-{id}")).execute(&db).await.unwrap();
+        let (tx, _rx) = flume::bounded(10);
+        let with_mqtt = AppState { mqtt: Some(AsyncClient::from_senders(tx)), ..st.clone() };
+        let _ = send_led_command(State(with_mqtt), None, Path(id), Json(led())).await.unwrap();
+        let token = crate::routes::device_tokens::issue_device_token(&st, id, None, None, Utc::now()).await.unwrap();
+
+        let request = DeleteDataRequest { confirm: true, include_metadata: true };
+        let Json(result) = delete_device_data(State(st.clone()), Path(id), Json(request)).await.unwrap();
+        assert_eq!(
+            result,
+            DeleteDataResult { readings_deleted: 2, alerts_deleted: 1, device_deleted: true, commands_deleted: 1, tokens_revoked: 1 }
+        );
+        assert_eq!(crate::routes::device_tokens::authenticate(&st, &token.token, Utc::now()).await, None);
+
+        for (table, column) in [
+            ("sensor_readings", "device_id::text"),
+            ("sensor_summaries", "device_id::text"),
+            ("motion_events", "device_id::text"),
+            ("sensor_alerts", "device_id::text"),
+            ("device_heartbeats", "device_id::text"),
+            ("device_status_changes", "device_id::text"),
+            ("device_shadows", "device_id::text"),
+            ("device_commands", "device_id::text"),
+            ("device_tokens", "device_id::text"),
+            ("devices", "id::text"),
+        ] {
+            let count: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM {table} WHERE {column} = $1"))
+                .bind(id.to_string())
+                .fetch_one(&db)
+                .await
+                .unwrap();
+            assert_eq!(count, 0, "{table}");
+        }
+
+        // Diğer cihazın verisi kalır; tekrar silmek hata vermez
+        let remaining: i64 = sqlx::query_scalar("SELECT count(*) FROM sensor_readings WHERE device_id = $1")
+            .bind(other.to_string()).fetch_one(&db).await.unwrap();
+        assert_eq!(remaining, 1);
+        let request = DeleteDataRequest { confirm: true, include_metadata: true };
+        let Json(again) = delete_device_data(State(st.clone()), Path(id), Json(request)).await.unwrap();
+        assert_eq!(again, DeleteDataResult::default());
+
+        let request = DeleteDataRequest { confirm: true, include_metadata: false };
+        let _ = delete_device_data(State(st), Path(other), Json(request)).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_delete_device_purges_history_only_on_request() {
+        use crate::routes::sensors::{add_sensor_data, SensorData};
+
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        let (kept, purged) = (Uuid::new_v4(), Uuid::new_v4());
+        let readings = |id: Uuid| {
+            let db = db.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>("SELECT count(*) FROM sensor_readings WHERE device_id = $1")
+                    .bind(id.to_string())
+                    .fetch_one(&db)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        for id in [kept, purged] {
+            let data = SensorData {
+                device_id: id.to_string(),
+                sensor_type: "temperature".into(),
+                value: 22.5,
+                unit: "°C".into(),
+                timestamp: Utc::now().to_rfc3339(),
+                metadata: None,
+                received_at: None,
+                gateway_received_at: None,
+                stale: false,
+            };
+            let _ = add_sensor_data(State(st.clone()), None, crate::msgpack::JsonOrMsgPack(data)).await.unwrap();
+            let _ = update_desired(State(st.clone()), Path(id), Json(serde_json::json!({"led": {"led_01": "on"}}))).await.unwrap();
+            sqlx::query("INSERT INTO devices (id, serial_number, model, firmware_version) VALUES ($1, $2, 'rpi', '0.1.0')")
+                .bind(id).bind(format!("serial-{id}")).execute(&db).await.unwrap();
+        }
+
+        let (tx, _rx) = flume::bounded(10);
+        let with_mqtt = AppState { mqtt: Some(AsyncClient::from_senders(tx)), ..st.clone() };
+        let (_, Json(pending)) = send_led_command(State(with_mqtt), None, Path(kept), Json(led())).await.unwrap();
+
+        let Json(result) = delete_device(State(st.clone()), Path(kept), Query(DeviceTeardownParams { purge_history: false })).await.unwrap();
+        assert_eq!(
+            result,
+            DeviceTeardownResult {
+                device_deleted: true,
+                shadow_deleted: true,
+                pending_changes_cancelled: 1,
+                commands_cancelled: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(readings(kept).await, 1);
+        let Json(history) = command_history(State(st.clone()), Path(kept), Query(CommandHistoryParams::default())).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!((history[0].correlation_id, history[0].ack_status), (pending.correlation_id, Some(AckStatus::Cancelled)));
+        assert_eq!(history[0].ack_message.as_deref(), Some("device removed"));
+        assert_eq!(get_shadow(State(st.clone()), None, Path(kept)).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        let Json(result) = delete_device(State(st.clone()), Path(purged), Query(DeviceTeardownParams { purge_history: true })).await.unwrap();
+        assert_eq!((result.device_deleted, result.readings_deleted), (true, 1));
+        assert_eq!(readings(purged).await, 0);
+
+        // Bilinmeyen ID'nin geçmişi silinmez
+        let unknown = delete_device(State(st), Path(kept), Query(DeviceTeardownParams { purge_history: true })).await;
+        assert_eq!(unknown.unwrap_err(), StatusCode::NOT_FOUND);
+        assert_eq!(readings(kept).await, 1);
+
+        sqlx::query("DELETE FROM device_commands WHERE device_id = $1").bind(kept).execute(&db).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_command_history_records_dispatch_and_ack() {
+        let db = test_db().await;
+        let (tx, rx) = flume::bounded(10);
+        let st = AppState { mqtt: Some(AsyncClient::from_senders(tx)), ..AppState::for_tests_with_db(db.clone()) };
+        let id = Uuid::new_v4();
+        let history = |limit| command_history(State(st.clone()), Path(id), Query(CommandHistoryParams { limit }));
+
+        let (_, Json(first)) = send_led_command(State(st.clone()), None, Path(id), Json(led())).await.unwrap();
+        let (_, Json(second)) = send_led_command(State(st.clone()), None, Path(id), Json(led())).await.unwrap();
+        assert_eq!(rx.len(), 2);
+
+        // Yanıt gelmeyenler beklemede, en yeni önce
+        let Json(entries) = history(None).await.unwrap();
+        assert_eq!(entries.iter().map(|e| e.correlation_id).collect::<Vec<_>>(), [second.correlation_id, first.correlation_id]);
+        assert!(entries.iter().all(|e| e.ack_status.is_none() && e.acked_at.is_none()));
+        assert_eq!(entries[0].command_name, "led");
+        assert_eq!(entries[0].parameters.as_ref().unwrap()["led_id"], "led_01");
+
+        let ack = |response: DeviceCommandResponse| ack_command(State(st.clone()), None, Path(id), Json(response));
+        assert_eq!(ack(DeviceCommandResponse::ok(&first)).await.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(ack(DeviceCommandResponse::error(&second, "unknown LED")).await.unwrap(), StatusCode::NO_CONTENT);
+        // Bilinmeyen komut
+        let unknown = DeviceCommand::new(id, "control".into(), "led".into());
+        assert_eq!(ack(DeviceCommandResponse::ok(&unknown)).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        let Json(entries) = history(Some(1)).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].ack_status, Some(AckStatus::Failed));
+        assert_eq!(entries[0].ack_message.as_deref(), Some("unknown LED"));
+        let Json(entries) = history(None).await.unwrap();
+        assert_eq!(entries[1].ack_status, Some(AckStatus::Executed));
+        assert!(entries[1].acked_at.is_some());
+
+        // Publish başarısızsa kayıt geri alınır
+        drop(rx);
+        assert_eq!(send_led_command(State(st.clone()), None, Path(id), Json(led())).await.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(history(None).await.unwrap().0.len(), 2);
+
+        // GDPR silmesi komut kayıtlarını da siler
+        delete_device_rows(&db, id, true).await.unwrap();
+        assert!(history(None).await.unwrap().0.is_empty());
+    }
+}
@@ -2,27 +2,123 @@
 //!
 //! Sunucunun ve servislerinin sağlık durumunu kontrol etmek için kullanılan endpoint'ler.
 //! Kubernetes ve diğer orchestration araçları tarafından kullanılır.
+//!
+//! - `/health` (liveness): Sadece process'in cevap verip vermediğine bakar.
+//!   Hiçbir dış bağımlılığa dokunmaz; DB kesintisi sağlıklı pod'ları yeniden başlatmamalı.
+//! - `/ready` (readiness): DB, Redis ve MQTT bağlantılarını ölçer.
+//!   `READINESS_REQUIRED` ile hangilerinin zorunlu olduğu seçilir.
+
+use std::{future::Future, time::{Duration, Instant}};
+use std::sync::atomic::Ordering;
 
-use axum::{response::IntoResponse, Json};
+use axum::{extract::State, http::StatusCode, Json};
 use serde::Serialize;
 
-/// Basit sağlık durumu response'ı
-#[derive(Serialize)]
-struct Health { 
-    /// Durum: "ok" veya "ready"
-    status: &'static str 
+use crate::state::AppState;
+
+/// Tek bir bağımlılık kontrolünün zaman aşımı
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Liveness response'ı
+#[derive(Debug, Serialize)]
+pub struct Liveness {
+    /// Her zaman "ok"
+    pub status: &'static str,
+    /// Sunucunun başlatılmasından bu yana geçen süre (saniye)
+    pub uptime_secs: u64,
+    /// Son arka plan pool kontrolü başarılı mı? (DB yoksa alan yok)
+    ///
+    /// Sadece bilgi amaçlıdır; `false` olsa da status 200 kalır.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db_healthy: Option<bool>,
+}
+
+/// Readiness genel durumu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessStatus {
+    /// Tüm bağımlılıklar ayakta
+    Ready,
+    /// Zorunlu bağımlılıklar ayakta, bilgi amaçlı olanlardan biri çökük
+    Degraded,
+    /// Zorunlu bir bağımlılık çökük (503)
+    NotReady,
+}
+
+/// Tek bir bağımlılığın kontrol sonucu
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyCheck {
+    /// Bağımlılık erişilebilir mi?
+    pub ok: bool,
+    /// `READINESS_REQUIRED` içinde mi? (false = bilgi amaçlı)
+    pub required: bool,
+    /// Kontrolün süresi (milisaniye); ölçülemiyorsa `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    /// Hata açıklaması
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Readiness response'ı
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "db": {"ok": true, "required": true, "latency_ms": 1.8},
+///   "redis": {"ok": false, "required": false, "latency_ms": 2000.4, "error": "timeout"},
+///   "mqtt_bridge": {"ok": true, "required": false},
+///   "status": "degraded"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub db: DependencyCheck,
+    pub redis: DependencyCheck,
+    pub mqtt_bridge: DependencyCheck,
+    pub status: ReadinessStatus,
+}
+
+impl DependencyCheck {
+    /// Bağımlılık hiç yapılandırılmamış / bağlanamamış
+    fn unavailable(required: bool, error: &str) -> Self {
+        Self { ok: false, required, latency_ms: None, error: Some(error.to_string()) }
+    }
+}
+
+impl ReadinessReport {
+    /// Kontrol sonuçlarından genel durumu hesapla
+    pub fn new(db: DependencyCheck, redis: DependencyCheck, mqtt_bridge: DependencyCheck) -> Self {
+        let checks = [&db, &redis, &mqtt_bridge];
+        let status = if checks.iter().any(|c| c.required && !c.ok) {
+            ReadinessStatus::NotReady
+        } else if checks.iter().any(|c| !c.ok) {
+            ReadinessStatus::Degraded
+        } else {
+            ReadinessStatus::Ready
+        };
+        Self { db, redis, mqtt_bridge, status }
+    }
+
+    /// Durumun HTTP karşılığı: sadece `NotReady` 503 döner
+    pub fn status_code(&self) -> StatusCode {
+        match self.status {
+            ReadinessStatus::NotReady => StatusCode::SERVICE_UNAVAILABLE,
+            ReadinessStatus::Ready | ReadinessStatus::Degraded => StatusCode::OK,
+        }
+    }
 }
 
 /// Root endpoint - Basit status check
-/// 
+///
 /// # HTTP
 /// `GET /`
-/// 
+///
 /// # Response
 /// ```text
 /// "RustyFlow API — OK"
 /// ```
-/// 
+///
 /// # Amaç
 /// Basit liveness check için. Sunucunun çalışıp çalışmadığını hızlı biçimde kontrol et.
 pub async fn root() -> &'static str {
@@ -30,37 +126,178 @@ pub async fn root() -> &'static str {
 }
 
 /// Health endpoint - Sunucunun sağlığını kontrol et
-/// 
+///
 /// # HTTP
 /// `GET /health`
-/// 
+///
 /// # Response
 /// ```json
-/// {"status":"ok"}
+/// {"status":"ok","uptime_secs":3600,"db_healthy":true}
 /// ```
-/// 
+///
 /// # Amaç
-/// Liveness probe için. Konteyner orchestration araçları (Kubernetes, Docker Compose)
-/// sunucunun canlı olup olmadığını kontrol eder.
-pub async fn health() -> impl IntoResponse {
-    Json(Health { status: "ok" })
+/// Liveness probe için. Handler'ın çalışması event loop'un canlı olduğunu gösterir;
+/// bu yüzden DB, Redis gibi bağımlılıklar burada kontrol edilmez. `db_healthy`
+/// arka plandaki `PoolHealthMonitor`'ün son sonucudur (sorgu yapılmaz).
+pub async fn health(State(st): State<AppState>) -> Json<Liveness> {
+    let db_healthy = st.db.read().await.is_some().then(|| st.db_healthy.load(Ordering::Relaxed));
+    Json(Liveness { status: "ok", uptime_secs: st.started_at.elapsed().as_secs(), db_healthy })
 }
 
 /// Ready endpoint - Sunucu hazır mı?
-/// 
+///
 /// # HTTP
 /// `GET /ready`
-/// 
+///
 /// # Response
-/// ```json
-/// {"status":"ready"}
-/// ```
-/// 
-/// # Amaç
-/// Readiness probe için. Sunucunun istek kabul etmeye hazır olup olmadığını kontrol et.
-/// 
-/// **Not**: İleride database bağlantısı, message queue, cache vb. kontroller eklenebilir.
-pub async fn ready() -> impl IntoResponse {
-    // ileride: DB bağlantısı, mqtt bağlı mı gibi kontroller.
-    Json(Health { status: "ready" })
-}
\ No newline at end of file
+/// `ReadinessReport` (her bağımlılık için `ok`, `required`, `latency_ms`).
+///
+/// # Status Kodları
+/// - 200 OK: Zorunlu bağımlılıklar ayakta (`ready` veya `degraded`)
+/// - 503 Service Unavailable: Zorunlu bir bağımlılık çökük (`not_ready`)
+///
+/// Zorunlu bağımlılıklar `READINESS_REQUIRED` ile seçilir (varsayılan: `db`).
+pub async fn ready(State(st): State<AppState>) -> (StatusCode, Json<ReadinessReport>) {
+    let required = st.cfg.read().await.readiness_required_list();
+    let is_required = |name: &str| required.iter().any(|r| r == name);
+
+    // DB ve Redis paralel kontrol edilir (toplam süre en yavaş kontrol kadar)
+    let db_check = async {
+        match &st.pool().await {
+            Some(pool) => timed(is_required("db"), async {
+                sqlx::query("SELECT 1").execute(pool).await.map(|_| ())
+            }).await,
+            None => DependencyCheck::unavailable(is_required("db"), "not connected"),
+        }
+    };
+    let redis_check = async {
+        match st.redis.clone() {
+            Some(mut conn) => timed(is_required("redis"), async move {
+                redis::cmd("PING").query_async::<String>(&mut conn).await.map(|_| ())
+            }).await,
+            None => DependencyCheck::unavailable(is_required("redis"), "not connected"),
+        }
+    };
+    let (db, redis) = tokio::join!(db_check, redis_check);
+
+    // MQTT bağlantı durumu event loop tarafından tutulur (ölçülecek round-trip yok)
+    let mqtt_required = is_required("mqtt_bridge");
+    let mqtt_bridge = if st.mqtt.is_some() && st.mqtt_connected.load(Ordering::Relaxed) {
+        DependencyCheck { ok: true, required: mqtt_required, latency_ms: None, error: None }
+    } else {
+        DependencyCheck::unavailable(mqtt_required, "broker not connected")
+    };
+
+    let report = ReadinessReport::new(db, redis, mqtt_bridge);
+    (report.status_code(), Json(report))
+}
+
+/// Kontrolü zaman aşımı ile çalıştır ve süresini ölç
+async fn timed<F, E>(required: bool, check: F) -> DependencyCheck
+where
+    F: Future<Output = Result<(), E>>,
+    E: std::fmt::Display,
+{
+    let start = Instant::now();
+    let result = tokio::time::timeout(CHECK_TIMEOUT, check).await;
+    let latency_ms = Some(start.elapsed().as_secs_f64() * 1000.0);
+
+    let error = match result {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some("timeout".to_string()),
+    };
+    DependencyCheck { ok: error.is_none(), required, latency_ms, error }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{atomic::AtomicBool, Arc};
+
+    fn check(ok: bool, required: bool) -> DependencyCheck {
+        DependencyCheck { ok, required, latency_ms: Some(1.0), error: None }
+    }
+
+    fn state_with(required: &str, mqtt_connected: bool) -> AppState {
+        let mut st = AppState::for_tests();
+        st.cfg.try_write().unwrap().readiness_required = required.to_string();
+        st.mqtt_connected = Arc::new(AtomicBool::new(mqtt_connected));
+        let (tx, _rx) = flume::bounded(1);
+        st.mqtt = Some(rumqttc::AsyncClient::from_senders(tx));
+        st
+    }
+
+    #[test]
+    fn test_all_dependencies_up_is_ready() {
+        let report = ReadinessReport::new(check(true, true), check(true, false), check(true, false));
+        assert_eq!(report.status, ReadinessStatus::Ready);
+        assert_eq!(report.status_code(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_required_dependency_down_is_not_ready() {
+        let report = ReadinessReport::new(check(false, true), check(true, false), check(true, false));
+        assert_eq!(report.status, ReadinessStatus::NotReady);
+        assert_eq!(report.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_informational_dependency_down_is_degraded() {
+        let report = ReadinessReport::new(check(true, true), check(false, false), check(true, false));
+        assert_eq!(report.status, ReadinessStatus::Degraded);
+        assert_eq!(report.status_code(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_required_down_wins_over_informational_down() {
+        let report = ReadinessReport::new(check(true, false), check(false, false), check(false, true));
+        assert_eq!(report.status, ReadinessStatus::NotReady);
+    }
+
+    #[tokio::test]
+    async fn test_health_never_touches_dependencies() {
+        // DB/Redis/MQTT yok, yine de liveness ok
+        let Json(live) = health(State(AppState::for_tests())).await;
+        assert_eq!(live.status, "ok");
+        assert_eq!(live.db_healthy, None);
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_monitor_flag() {
+        // Lazy pool: bağlantı açılmaz, flag olduğu gibi okunur
+        let pool = sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://localhost/unused").unwrap();
+        let st = AppState::for_tests_with_db(pool);
+        assert_eq!(health(State(st.clone())).await.0.db_healthy, Some(true));
+
+        st.db_healthy.store(false, Ordering::Relaxed);
+        let Json(live) = health(State(st)).await;
+        assert_eq!((live.status, live.db_healthy), ("ok", Some(false)));
+    }
+
+    #[tokio::test]
+    async fn test_ready_missing_required_db_returns_503() {
+        let (status, Json(report)) = ready(State(state_with("db", true))).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(report.db.required && !report.db.ok);
+        assert!(report.mqtt_bridge.ok);
+    }
+
+    #[tokio::test]
+    async fn test_ready_missing_informational_deps_is_degraded() {
+        let (status, Json(report)) = ready(State(state_with("mqtt_bridge", true))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report.status, ReadinessStatus::Degraded);
+        assert!(!report.db.required && !report.redis.required);
+    }
+
+    #[tokio::test]
+    async fn test_ready_disconnected_mqtt_bridge() {
+        let (status, Json(report)) = ready(State(state_with("mqtt_bridge", false))).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!report.mqtt_bridge.ok);
+
+        let (status, _) = ready(State(state_with("", false))).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -12,19 +12,42 @@
 //!
 //! # Endpoint'ler
 //! - POST /v1/media - Yeni media oluştur
-//! - GET /v1/media - Tüm medya listele
+//! - POST /v1/media/bulk - En fazla 1000 media'yı tek seferde oluştur (hepsi veya hiçbiri)
+//! - POST /v1/media/bulk-delete - ID listesini sil (ID başına sonuç)
+//! - POST /v1/media/upload - İçerik yükle (aynı içerik checksum ile tespit edilir)
+//! - GET /v1/media - Medyaları listele (filtre + sayfalama)
 //! - GET /v1/media/{id} - Belirli bir medyayı al
 //! - PUT /v1/media/{id} - Medyayı güncelle (partial)
 //! - DELETE /v1/media/{id} - Medyayı sil
 
-use axum::{extract::{Path, State}, http::StatusCode, Json};
+use std::collections::HashSet;
+use std::path::{Path as FsPath, PathBuf};
+
+use axum::{
+    body::Body,
+    extract::{rejection::JsonRejection, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+use sqlx::{Postgres, QueryBuilder};
+use tokio::{fs, io::AsyncWriteExt};
 use uuid::Uuid;
 
+use crate::limits::{ItemError, Problem};
 use crate::state::AppState;
 
 // shared-types'tan Media tiplerini import et
 // Artık kendi Media struct'ımız yok, merkezi shared-types'ı kullanıyoruz
-use shared_types::{Media, NewMedia, UpdateMedia};
+use shared_types::{
+    BulkCreatedMedia, Media, MediaDeleteOutcome, MediaDeleteResult, MediaQuery, NewMedia, SortField, SortOrder, SortParams,
+    UpdateMedia, UploadMediaParams,
+};
+
+/// Toplu oluşturma ve silme isteklerinde en fazla eleman sayısı
+pub const MEDIA_BULK_MAX_ITEMS: usize = 1000;
 
 // ============================================================================
 // HTTP HANDLER FONKSİYONLARI (HTTP HANDLERS)
@@ -57,16 +80,23 @@ use shared_types::{Media, NewMedia, UpdateMedia};
 /// 2. Eğer PostgreSQL bağlıysa: INSERT query'si çalıştır
 /// 3. Yoksa: In-memory HashMap'e ekle
 /// 4. 201 (CREATED) status ile response dön
+/// 
+/// # Error Responses
+/// - 400 Bad Request: Body okunamadı, alan eksik veya `mime_type` geçersiz
+///   (body: hata mesajı, bkz. `MimeType::parse`)
+/// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st, payload), err)]
 pub async fn create_media(
     State(st): State<AppState>,
-    Json(body): Json<NewMedia>,
-) -> Result<(StatusCode, Json<Media>), StatusCode> {
-    if let Some(db) = &st.db {
+    payload: Result<Json<NewMedia>, JsonRejection>,
+) -> Result<(StatusCode, Json<Media>), MediaError> {
+    let body = json_body(payload)?;
+    if let Some(db) = &st.pool().await {
         // ===== PostgreSQL Yolu =====
         let item = sqlx::query_as::<_, Media>(
             "INSERT INTO media_datas (id, name, path, mime_type, size_bytes, created_at, updated_at) 
              VALUES ($1, $2, $3, $4, $5, NOW(), NOW()) 
-             RETURNING id, name, path, mime_type, size_bytes, created_at, updated_at"
+             RETURNING id, name, path, mime_type, size_bytes, created_at, updated_at, version, checksum_sha256"
         )
         .bind(Uuid::new_v4())
         .bind(&body.name)
@@ -87,10 +117,359 @@ pub async fn create_media(
     }
 }
 
-/// Tüm media nesnelerini listele
+/// Çok sayıda medya nesnesini tek istekte oluştur
+/// 
+/// # HTTP
+/// `POST /v1/media/bulk`
+/// 
+/// # Request Body
+/// `NewMedia` dizisi (en fazla `MEDIA_BULK_MAX_ITEMS`):
+/// ```json
+/// [
+///   {"name": "a.jpg", "path": "/uploads/a.jpg", "mime_type": "image/jpeg", "size_bytes": 1024},
+///   {"name": "b.png", "path": "/uploads/b.png", "mime_type": "image/png", "size_bytes": 2048}
+/// ]
+/// ```
+/// 
+/// # Response (201 Created)
+/// Oluşturulan ID'ler, request'teki sırayla (bkz. `BulkCreatedMedia`):
+/// ```json
+/// {"ids": ["550e8400-e29b-41d4-a716-446655440000", "..."]}
+/// ```
+/// 
+/// # Detay
+/// Önce tüm elemanlar doğrulanır; biri bile geçersizse hiçbiri kaydedilmez.
+/// PostgreSQL'de kayıtlar `UNNEST` ile tek bir çok satırlı INSERT'te eklenir
+/// (tek ifade, tek transaction).
+/// 
+/// # Error Responses
+/// - 400 Bad Request: Body dizi değil, veya eleman(lar) geçersiz. İkincisinde
+///   body problem+json'dur ve `errors` her geçersiz elemanın `index`'ini ve
+///   hata mesajını listeler
+/// - 413 Payload Too Large: `MEDIA_BULK_MAX_ITEMS`'tan fazla eleman
+/// - 500 Internal Server Error: Database hatası (hiçbir kayıt eklenmez)
+#[tracing::instrument(skip(st, payload), err)]
+pub async fn create_media_bulk(
+    State(st): State<AppState>,
+    payload: Result<Json<Vec<serde_json::Value>>, JsonRejection>,
+) -> Result<(StatusCode, Json<BulkCreatedMedia>), MediaError> {
+    let raw = json_body(payload)?;
+    check_bulk_size(raw.len())?;
+    let items = parse_bulk_items(raw)?;
+    let ids: Vec<Uuid> = items.iter().map(|_| Uuid::new_v4()).collect();
+
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        if !items.is_empty() {
+            sqlx::query(
+                "INSERT INTO media_datas (id, name, path, mime_type, size_bytes, created_at, updated_at)
+                 SELECT id, name, path, mime_type, size_bytes, NOW(), NOW()
+                 FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::text[], $5::bigint[])
+                     AS t(id, name, path, mime_type, size_bytes)"
+            )
+            .bind(&ids)
+            .bind(items.iter().map(|m| m.name.as_str()).collect::<Vec<_>>())
+            .bind(items.iter().map(|m| m.path.as_str()).collect::<Vec<_>>())
+            .bind(items.iter().map(|m| m.mime_type.as_str()).collect::<Vec<_>>())
+            .bind(items.iter().map(|m| m.size_bytes).collect::<Vec<_>>())
+            .execute(db)
+            .await
+            .map_err(|e| {
+                tracing::error!("Bulk media insert failed: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        }
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut map = st.media_store.write().await;
+        for (id, new) in ids.iter().zip(items) {
+            let mut item = Media::new(new.name, new.path, new.mime_type, new.size_bytes);
+            item.id = *id;
+            map.insert(item.id, item);
+        }
+    }
+    Ok((StatusCode::CREATED, Json(BulkCreatedMedia { ids })))
+}
+
+/// ID listesindeki medya nesnelerini sil
+/// 
+/// # HTTP
+/// `POST /v1/media/bulk-delete`
+/// 
+/// # Request Body
+/// UUID dizisi (en fazla `MEDIA_BULK_MAX_ITEMS`):
+/// ```json
+/// ["550e8400-e29b-41d4-a716-446655440000", "6ba7b810-9dad-11d1-80b4-00c04fd430c8"]
+/// ```
+/// 
+/// # Response (200 OK)
+/// ID başına sonuç, request'teki sırayla (bkz. `MediaDeleteResult`). Aynı ID
+/// tekrar ederse sadece ilki `deleted` olur:
+/// ```json
+/// [
+///   {"id": "550e8400-e29b-41d4-a716-446655440000", "outcome": "deleted"},
+///   {"id": "6ba7b810-9dad-11d1-80b4-00c04fd430c8", "outcome": "not_found"}
+/// ]
+/// ```
+/// 
+/// # Error Responses
+/// - 400 Bad Request: Body UUID dizisi değil
+/// - 413 Payload Too Large: `MEDIA_BULK_MAX_ITEMS`'tan fazla ID
+/// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st, payload), err)]
+pub async fn delete_media_bulk(
+    State(st): State<AppState>,
+    payload: Result<Json<Vec<Uuid>>, JsonRejection>,
+) -> Result<Json<Vec<MediaDeleteResult>>, MediaError> {
+    let ids = json_body(payload)?;
+    check_bulk_size(ids.len())?;
+
+    let mut deleted: HashSet<Uuid> = if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        sqlx::query_scalar("DELETE FROM media_datas WHERE id = ANY($1) RETURNING id")
+            .bind(&ids)
+            .fetch_all(db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .into_iter()
+            .collect()
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut map = st.media_store.write().await;
+        let mut checksums = st.media_checksums.write().await;
+        ids.iter()
+            .filter_map(|id| map.remove(id))
+            .map(|removed| {
+                if let Some(checksum) = &removed.checksum_sha256 {
+                    checksums.remove(checksum);
+                }
+                removed.id
+            })
+            .collect()
+    };
+
+    let results = ids
+        .into_iter()
+        .map(|id| MediaDeleteResult {
+            id,
+            outcome: if deleted.remove(&id) { MediaDeleteOutcome::Deleted } else { MediaDeleteOutcome::NotFound },
+        })
+        .collect();
+    Ok(Json(results))
+}
+
+/// Toplu istek `MEDIA_BULK_MAX_ITEMS`'ı aşıyorsa 413
+fn check_bulk_size(len: usize) -> Result<(), Problem> {
+    if len > MEDIA_BULK_MAX_ITEMS {
+        let detail = format!("at most {MEDIA_BULK_MAX_ITEMS} items per request, got {len}");
+        return Err(Problem::new(StatusCode::PAYLOAD_TOO_LARGE, detail));
+    }
+    Ok(())
+}
+
+/// Elemanları `NewMedia` olarak çöz; geçersizlerin hepsi tek 400'de listelenir
+fn parse_bulk_items(raw: Vec<serde_json::Value>) -> Result<Vec<NewMedia>, Problem> {
+    let mut items = Vec::with_capacity(raw.len());
+    let mut errors = Vec::new();
+    for (index, value) in raw.into_iter().enumerate() {
+        match serde_json::from_value::<NewMedia>(value) {
+            Ok(item) => items.push(item),
+            Err(e) => errors.push(ItemError { index, detail: e.to_string() }),
+        }
+    }
+    if errors.is_empty() {
+        return Ok(items);
+    }
+    let detail = format!("{} of {} items are invalid, nothing was created", errors.len(), errors.len() + items.len());
+    Err(Problem::new(StatusCode::BAD_REQUEST, detail).with_errors(errors))
+}
+
+/// Media içeriğini yükle (aynı içerik tekrar kaydedilmez)
+/// 
+/// # HTTP
+/// `POST /v1/media/upload?name=photo.jpg&dedup=true`
+/// 
+/// Body dosyanın kendisidir; MIME type `Content-Type` header'ından alınır
+/// (yoksa `application/octet-stream`). Bkz. `UploadMediaParams`.
+/// 
+/// # Response (201 Created)
+/// ```json
+/// {
+///   "id": "550e8400-e29b-41d4-a716-446655440000",
+///   "name": "photo.jpg",
+///   "path": "./data/uploads/550e8400-e29b-41d4-a716-446655440000",
+///   "mime_type": "image/jpeg",
+///   "size_bytes": 24576,
+///   "checksum_sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+/// }
+/// ```
+/// 
+/// # Detay
+/// 1. Body parça parça `MEDIA_UPLOAD_DIR/<id>.part` dosyasına yazılır; SHA-256
+///    aynı anda artımlı hesaplanır (içerik bellekte biriktirilmez)
+/// 2. Dosya `MEDIA_UPLOAD_DIR/<id>` olarak taşınır
+/// 3. Aynı checksum'lı kayıt yoksa yeni kayıt eklenir; varsa dosya silinir
+///    ve mevcut kayıt döner (PostgreSQL'de unique index yarışları da yakalar)
+/// 
+/// # Tekrar Eden İçerik
+/// Body her iki durumda da mevcut kayıttır, `Location` header'ı onu gösterir:
+/// - 409 Conflict: Varsayılan
+/// - 200 OK: `?dedup=true`; yükleme mevcut kayda bağlanır, yeni kayıt oluşmaz
+/// 
+/// # Error Responses
+/// - 400 Bad Request: `name` eksik veya body okunamadı
+/// - 413 Payload Too Large: `MAX_UPLOAD_BYTES` aşıldı
+/// - 500 Internal Server Error: Dosya yazılamadı veya database hatası
+#[tracing::instrument(skip(st, headers, body), err)]
+pub async fn upload_media(
+    State(st): State<AppState>,
+    Query(params): Query<UploadMediaParams>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, StatusCode> {
+    let mime_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let dir = PathBuf::from(&st.cfg.read().await.media_upload_dir);
+    let id = Uuid::new_v4();
+
+    let part = dir.join(format!("{id}.part"));
+    let (checksum, size_bytes) = match write_upload(body, &dir, &part).await {
+        Ok(written) => written,
+        Err(status) => {
+            let _ = fs::remove_file(&part).await;
+            return Err(status);
+        }
+    };
+    let path = dir.join(id.to_string());
+    fs::rename(&part, &path).await.map_err(|e| {
+        tracing::error!("Failed to store upload {}: {e}", path.display());
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut item = Media::new(params.name, path.to_string_lossy().into_owned(), mime_type, size_bytes);
+    item.id = id;
+    item.checksum_sha256 = Some(checksum);
+
+    let outcome = insert_upload(&st, item).await;
+    if !matches!(outcome, Ok(UploadOutcome::Created(_))) {
+        let _ = fs::remove_file(&path).await;
+    }
+    let (status, item) = match outcome? {
+        UploadOutcome::Created(item) => (StatusCode::CREATED, item),
+        UploadOutcome::Duplicate(existing) if params.dedup => (StatusCode::OK, existing),
+        UploadOutcome::Duplicate(existing) => (StatusCode::CONFLICT, existing),
+    };
+    Ok((status, [(header::LOCATION, format!("/v1/media/{}", item.id))], Json(item)).into_response())
+}
+
+/// `insert_upload` sonucu
+#[derive(Debug)]
+enum UploadOutcome {
+    /// Yeni kayıt eklendi
+    Created(Media),
+    /// Aynı checksum'lı mevcut kayıt (yeni kayıt eklenmedi)
+    Duplicate(Media),
+}
+
+/// Body'yi `part` dosyasına yaz, SHA-256'yı (hex) ve boyutu döndür
+async fn write_upload(body: Body, dir: &FsPath, part: &FsPath) -> Result<(String, i64), StatusCode> {
+    let io_error = |e: std::io::Error| {
+        tracing::error!("Failed to write upload {}: {e}", part.display());
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    fs::create_dir_all(dir).await.map_err(io_error)?;
+    let mut file = fs::File::create(part).await.map_err(io_error)?;
+
+    let mut hasher = Sha256::new();
+    let mut size_bytes = 0i64;
+    let mut chunks = body.into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(body_error_status)?;
+        hasher.update(&chunk);
+        size_bytes += chunk.len() as i64;
+        file.write_all(&chunk).await.map_err(io_error)?;
+    }
+    file.sync_all().await.map_err(io_error)?;
+    Ok((format!("{:x}", hasher.finalize()), size_bytes))
+}
+
+/// Body okuma hatası: sınır aşıldıysa 413, değilse 400
+/// 
+/// `Content-Length`'siz (chunked) body'lerde sınır okuma sırasında aşılır.
+fn body_error_status(e: axum::Error) -> StatusCode {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(&e);
+    while let Some(err) = source {
+        if err.is::<http_body_util::LengthLimitError>() {
+            return StatusCode::PAYLOAD_TOO_LARGE;
+        }
+        source = err.source();
+    }
+    StatusCode::BAD_REQUEST
+}
+
+/// Yüklenen kaydı ekle (aynı checksum'lı kayıt yoksa)
+async fn insert_upload(st: &AppState, item: Media) -> Result<UploadOutcome, StatusCode> {
+    let checksum = item.checksum_sha256.clone().unwrap_or_default();
+
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        // Partial unique index çakışması = aynı içerik (eş zamanlı upload'lar dahil)
+        let created = sqlx::query_as::<_, Media>(
+            "INSERT INTO media_datas (id, name, path, mime_type, size_bytes, checksum_sha256, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
+             ON CONFLICT (checksum_sha256) WHERE checksum_sha256 IS NOT NULL DO NOTHING
+             RETURNING id, name, path, mime_type, size_bytes, created_at, updated_at, version, checksum_sha256"
+        )
+        .bind(item.id)
+        .bind(&item.name)
+        .bind(&item.path)
+        .bind(&item.mime_type)
+        .bind(item.size_bytes)
+        .bind(&checksum)
+        .fetch_optional(db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if let Some(created) = created {
+            return Ok(UploadOutcome::Created(created));
+        }
+
+        let existing = sqlx::query_as::<_, Media>(
+            "SELECT id, name, path, mime_type, size_bytes, created_at, updated_at, version, checksum_sha256
+             FROM media_datas WHERE checksum_sha256 = $1"
+        )
+        .bind(&checksum)
+        .fetch_optional(db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        // Çakışan kayıt bu arada silindi
+        .ok_or(StatusCode::CONFLICT)?;
+        Ok(UploadOutcome::Duplicate(existing))
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut map = st.media_store.write().await;
+        let mut index = st.media_checksums.write().await;
+        if let Some(existing) = index.get(&checksum).and_then(|id| map.get(id)) {
+            return Ok(UploadOutcome::Duplicate(existing.clone()));
+        }
+        index.insert(checksum, item.id);
+        map.insert(item.id, item.clone());
+        Ok(UploadOutcome::Created(item))
+    }
+}
+
+/// Media nesnelerini listele (filtre + sayfalama)
 /// 
 /// # HTTP
-/// `GET /v1/media`
+/// `GET /v1/media?q=&mime_type=image/%&min_size=&max_size=&name_contains=&created_after=&created_before=&checksum=&sort_by=&order=&limit=&offset=`
+/// 
+/// Tüm query parametreleri opsiyoneldir (bkz. `MediaQuery`, `SortParams`).
+/// Sonuçlar `created_at` sırasıyla döner; sayfalama filtrelerden sonra uygulanır.
+/// `q` verildiyse sonuçlar `rank` (azalan) ile sıralanır ve her media `rank` içerir.
+/// `sort_by` (`name`, `size_bytes`, `created_at`, `updated_at`) ve `order`
+/// (`asc`, `desc`) verilirse varsayılan sıra yerine o kullanılır.
 /// 
 /// # Response (200 OK)
 /// ```json
@@ -109,25 +488,146 @@ pub async fn create_media(
 /// ```
 /// 
 /// # Detay
-/// 1. Eğer PostgreSQL bağlıysa: SELECT * FROM media_datas
-/// 2. Yoksa: In-memory HashMap'teki tüm değerleri dön
-pub async fn list_media(State(st): State<AppState>) -> Result<Json<Vec<Media>>, StatusCode> {
-    if let Some(db) = &st.db {
+/// 1. Eğer PostgreSQL bağlıysa: Filtrelerden dinamik WHERE clause oluştur (bind parametreleri ile)
+///    - `q`: `search_vector @@ plainto_tsquery(...)`, skor `ts_rank` ile
+/// 2. Yoksa: In-memory HashMap'i `MediaQuery::matches` ile filtrele (`q` için substring arama)
+///    - `checksum`: Store taranmaz, aday `media_checksums` indeksinden alınır
+/// 
+/// # Error Responses
+/// - 400 Bad Request: Negatif `limit` veya `offset`, bilinmeyen `sort_by` / `order`
+#[tracing::instrument(skip(st), err)]
+pub async fn list_media(
+    State(st): State<AppState>,
+    Query(query): Query<MediaQuery>,
+    Query(sort): Query<SortParams>,
+) -> Result<Json<Vec<Media>>, StatusCode> {
+    if query.limit.is_some_and(|l| l < 0) || query.offset.is_some_and(|o| o < 0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Sadece noktalama içeren veya boş `q` arama sayılmaz
+    let terms = query.search_terms();
+    let search = (!terms.is_empty()).then(|| terms.join(" "));
+
+    if let Some(db) = &st.pool().await {
         // ===== PostgreSQL Yolu =====
-        let items = sqlx::query_as::<_, Media>(
-            "SELECT id, name, path, mime_type, size_bytes, created_at, updated_at FROM media_datas"
-        )
+        let mut qb = QueryBuilder::<Postgres>::new(
+            "SELECT id, name, path, mime_type, size_bytes, created_at, updated_at, version, checksum_sha256"
+        );
+        if let Some(q) = &search {
+            qb.push(", ts_rank(search_vector, plainto_tsquery('english', ")
+                .push_bind(q.clone())
+                .push(")) AS rank");
+        }
+        qb.push(" FROM media_datas WHERE TRUE");
+        push_media_filters(&mut qb, &query);
+        if let Some(q) = &search {
+            qb.push(" AND search_vector @@ plainto_tsquery('english', ").push_bind(q.clone()).push(")");
+        }
+        match sort.sort_by {
+            Some(field) => {
+                qb.push(" ORDER BY ")
+                    .push(sort_column(field))
+                    .push(sort_direction(sort.order))
+                    .push(", id ASC");
+            }
+            None if search.is_some() => {
+                qb.push(" ORDER BY rank DESC, created_at ASC, id ASC");
+            }
+            None => {
+                qb.push(" ORDER BY created_at ASC, id ASC");
+            }
+        }
+        if let Some(limit) = query.limit {
+            qb.push(" LIMIT ").push_bind(limit);
+        }
+        if let Some(offset) = query.offset {
+            qb.push(" OFFSET ").push_bind(offset);
+        }
+
+        let items = qb.build_query_as::<Media>()
             .fetch_all(db)
             .await
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
         Ok(Json(items))
     } else {
         // ===== In-Memory Fallback =====
-        let items = {
+        let mut items = {
             let map = st.media_store.read().await;
-            map.values().cloned().collect::<Vec<_>>()
+            match query.checksum_filter() {
+                Some(checksum) => {
+                    let index = st.media_checksums.read().await;
+                    index.get(&checksum).and_then(|id| map.get(id)).filter(|m| query.matches(m)).cloned().into_iter().collect()
+                }
+                None => map.values().filter(|m| query.matches(m)).cloned().collect::<Vec<_>>(),
+            }
         };
-        Ok(Json(items))
+        if search.is_some() {
+            for item in &mut items {
+                item.rank = MediaQuery::search_rank(&terms, item);
+            }
+        }
+        items.sort_by(|a, b| {
+            let primary = match sort.sort_by {
+                Some(_) => sort.compare(a, b),
+                None => b.rank.unwrap_or_default().total_cmp(&a.rank.unwrap_or_default())
+                    .then(a.created_at.cmp(&b.created_at)),
+            };
+            primary.then(a.id.cmp(&b.id))
+        });
+
+        let offset = query.offset.unwrap_or(0) as usize;
+        let limit = query.limit.map_or(usize::MAX, |l| l as usize);
+        Ok(Json(items.into_iter().skip(offset).take(limit).collect()))
+    }
+}
+
+/// `MediaQuery` filtrelerini WHERE clause'a ekle
+/// 
+/// Tüm değerler `push_bind` ile parametre olarak gönderilir (string interpolation yok).
+fn push_media_filters(qb: &mut QueryBuilder<'_, Postgres>, query: &MediaQuery) {
+    if let Some(pattern) = &query.mime_type {
+        qb.push(" AND mime_type LIKE ").push_bind(pattern.clone());
+    }
+    if let Some(min) = query.min_size {
+        qb.push(" AND size_bytes >= ").push_bind(min);
+    }
+    if let Some(max) = query.max_size {
+        qb.push(" AND size_bytes <= ").push_bind(max);
+    }
+    if let Some(needle) = &query.name_contains {
+        // Kullanıcı girdisindeki LIKE wildcard'larını literal yap
+        let escaped = needle.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        qb.push(" AND name ILIKE ").push_bind(format!("%{}%", escaped));
+    }
+    if let Some(after) = query.created_after {
+        qb.push(" AND created_at > ").push_bind(after);
+    }
+    if let Some(before) = query.created_before {
+        qb.push(" AND created_at < ").push_bind(before);
+    }
+    if let Some(checksum) = query.checksum_filter() {
+        qb.push(" AND checksum_sha256 = ").push_bind(checksum);
+    }
+}
+
+/// `ORDER BY` kolonu
+/// 
+/// Kolon adı kullanıcı girdisinden değil, bu sabit eşleşmeden gelir.
+fn sort_column(field: SortField) -> &'static str {
+    match field {
+        SortField::Name => "name",
+        SortField::SizeBytes => "size_bytes",
+        SortField::CreatedAt => "created_at",
+        SortField::UpdatedAt => "updated_at",
+    }
+}
+
+/// `ORDER BY` yönü
+fn sort_direction(order: SortOrder) -> &'static str {
+    match order {
+        SortOrder::Asc => " ASC",
+        SortOrder::Desc => " DESC",
     }
 }
 
@@ -151,14 +651,15 @@ pub async fn list_media(State(st): State<AppState>) -> Result<Json<Vec<Media>>,
 /// # Error Responses
 /// - 404 Not Found: ID bulunamadı
 /// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st), err)]
 pub async fn get_media(
     State(st): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<Media>, StatusCode> {
-    if let Some(db) = &st.db {
+    if let Some(db) = &st.pool().await {
         // ===== PostgreSQL Yolu =====
         let item = sqlx::query_as::<_, Media>(
-            "SELECT id, name, path, mime_type, size_bytes, created_at, updated_at FROM media_datas WHERE id = $1"
+            "SELECT id, name, path, mime_type, size_bytes, created_at, updated_at, version, checksum_sha256 FROM media_datas WHERE id = $1"
         )
         .bind(id)
         .fetch_optional(db)  // Sonuç: Option<Media>
@@ -201,55 +702,191 @@ pub async fn get_media(
 /// 
 /// # Update Mantığı
 /// 1. Mevcut kaydı al
-/// 2. Gönderilen alanları (null olmayan) yeni değerlerle değiştir
-/// 3. Eski değerleri koru (null ise)
-/// 4. Güncellenmiş kaydı döndür
+/// 2. Beklenen sürüm verildiyse mevcut `version` ile karşılaştır
+/// 3. Gönderilen alanları (null olmayan) yeni değerlerle değiştir, `version`'ı artır
+/// 4. Eski değerleri koru (null ise)
+/// 5. Güncellenmiş kaydı döndür
+/// 
+/// # Optimistic Concurrency
+/// Beklenen sürüm body'de `expected_version` veya `If-Match: "3"` header'ı
+/// ile gönderilir (ikisi de varsa header geçerlidir). Hiçbiri yoksa kayıt
+/// koşulsuz güncellenir: okuma ile yazma arasında kaydı başka bir istek
+/// değiştirdiyse patch güncel kayda yeniden uygulanır. Sürüm verildiyse
+/// araya giren güncelleme de çakışma sayılır.
+/// 
+/// # Error Responses
+/// - 400 Bad Request: `If-Match` sayı değil veya `mime_type` geçersiz
+/// - 404 Not Found: ID bulunamadı
+/// - 409 Conflict: `expected_version` eşleşmedi (body: güncel kayıt)
+/// - 412 Precondition Failed: `If-Match` eşleşmedi (body: güncel kayıt)
+/// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st, payload), err)]
 pub async fn update_media(
     State(st): State<AppState>,
     Path(id): Path<Uuid>,
-    Json(patch): Json<UpdateMedia>,
-) -> Result<Json<Media>, StatusCode> {
-    if let Some(db) = &st.db {
+    headers: HeaderMap,
+    payload: Result<Json<UpdateMedia>, JsonRejection>,
+) -> Result<Json<Media>, MediaError> {
+    let patch = json_body(payload)?;
+    let (expected, mismatch_status) = match if_match_version(&headers)? {
+        Some(version) => (Some(version), StatusCode::PRECONDITION_FAILED),
+        None => (patch.expected_version, StatusCode::CONFLICT),
+    };
+
+    if let Some(db) = &st.pool().await {
         // ===== PostgreSQL Yolu =====
         
-        // Step 1: Mevcut kaydı al
-        let mut current = sqlx::query_as::<_, Media>(
-            "SELECT id, name, path, mime_type, size_bytes, created_at, updated_at FROM media_datas WHERE id = $1"
-        )
-        .bind(id)
-        .fetch_optional(db)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
-        
-        // Step 2: Patch'i uygula
-        current.apply_update(&patch);
-        
-        // Step 3: Database'i güncelle
-        let updated = sqlx::query_as::<_, Media>(
-            "UPDATE media_datas SET name = $1, path = $2, mime_type = $3, size_bytes = $4, updated_at = NOW() 
-             WHERE id = $5 
-             RETURNING id, name, path, mime_type, size_bytes, created_at, updated_at"
-        )
-        .bind(&current.name)
-        .bind(&current.path)
-        .bind(&current.mime_type)
-        .bind(current.size_bytes)
-        .bind(id)
-        .fetch_one(db)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        
-        Ok(Json(updated))
+        // Sürüm verilmediyse araya giren güncellemeden sonra patch güncel kayda yeniden uygulanır
+        loop {
+            // Step 1: Mevcut kaydı al
+            let mut current = fetch_media(db, id).await?.ok_or(StatusCode::NOT_FOUND)?;
+            if expected.is_some_and(|v| v != current.version) {
+                return Err(MediaError::VersionMismatch(mismatch_status, current));
+            }
+            let read_version = current.version;
+
+            // Step 2: Patch'i uygula
+            current.apply_update(&patch);
+
+            // Step 3: Database'i güncelle (okunan sürüm hâlâ geçerliyse)
+            let updated = sqlx::query_as::<_, Media>(
+                "UPDATE media_datas SET name = $1, path = $2, mime_type = $3, size_bytes = $4, updated_at = NOW(),
+                        version = version + 1
+                 WHERE id = $5 AND version = $6
+                 RETURNING id, name, path, mime_type, size_bytes, created_at, updated_at, version, checksum_sha256"
+            )
+            .bind(&current.name)
+            .bind(&current.path)
+            .bind(&current.mime_type)
+            .bind(current.size_bytes)
+            .bind(id)
+            .bind(read_version)
+            .fetch_optional(db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            match updated {
+                Some(updated) => return Ok(Json(updated)),
+                // Araya başka bir güncelleme girdi: client bir sürüm beklediyse çakışma
+                None if expected.is_some() => {
+                    let latest = fetch_media(db, id).await?.ok_or(StatusCode::NOT_FOUND)?;
+                    return Err(MediaError::VersionMismatch(mismatch_status, latest));
+                }
+                None => continue,
+            }
+        }
     } else {
         // ===== In-Memory Fallback =====
         let mut map = st.media_store.write().await;
         let item = map.get_mut(&id).ok_or(StatusCode::NOT_FOUND)?;
+        if expected.is_some_and(|v| v != item.version) {
+            return Err(MediaError::VersionMismatch(mismatch_status, item.clone()));
+        }
         item.apply_update(&patch);
         Ok(Json(item.clone()))
     }
 }   
 
+/// `create_media` / `update_media` / toplu işlem hatası
+/// 
+/// Sürüm çakışmasında client'ın yeniden deneyebilmesi için güncel kayıt
+/// response body'sinde döner.
+#[derive(Debug)]
+pub enum MediaError {
+    Status(StatusCode),
+    /// 409 veya 412 + güncel kayıt
+    VersionMismatch(StatusCode, Media),
+    /// Request body reddedildi (status + hata mesajı)
+    InvalidBody(StatusCode, String),
+    /// problem+json (toplu isteklerde geçersiz elemanların listesiyle)
+    Problem(Problem),
+}
+
+impl From<StatusCode> for MediaError {
+    fn from(status: StatusCode) -> Self {
+        Self::Status(status)
+    }
+}
+
+impl From<Problem> for MediaError {
+    fn from(problem: Problem) -> Self {
+        Self::Problem(problem)
+    }
+}
+
+impl From<(StatusCode, String)> for MediaError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        Self::InvalidBody(status, message)
+    }
+}
+
+impl std::fmt::Display for MediaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status(status) => write!(f, "{status}"),
+            Self::VersionMismatch(status, current) => write!(f, "{status} (current version {})", current.version),
+            Self::InvalidBody(status, message) => write!(f, "{status}: {message}"),
+            Self::Problem(problem) => write!(f, "{}: {}", problem.status, problem.detail),
+        }
+    }
+}
+
+impl IntoResponse for MediaError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Status(status) => status.into_response(),
+            Self::VersionMismatch(status, current) => (status, Json(current)).into_response(),
+            Self::InvalidBody(status, message) => (status, message).into_response(),
+            Self::Problem(problem) => problem.into_response(),
+        }
+    }
+}
+
+/// JSON body'yi çöz
+/// 
+/// Eksik/yanlış tipli alanlar ve doğrulanan alanlar (`mime_type`) axum'un
+/// 422'si yerine 400 döner; mesaj hatalı alanı söyler. Diğer red nedenleri
+/// (bozuk JSON, `Content-Type`) axum'un status'unu korur.
+fn json_body<T>(payload: Result<Json<T>, JsonRejection>) -> Result<T, (StatusCode, String)> {
+    match payload {
+        Ok(Json(body)) => Ok(body),
+        Err(rejection) => {
+            let status = match rejection {
+                JsonRejection::JsonDataError(_) => StatusCode::BAD_REQUEST,
+                _ => rejection.status(),
+            };
+            tracing::debug!("Rejected media body: {}", rejection.body_text());
+            Err((status, rejection.body_text()))
+        }
+    }
+}
+
+/// `If-Match` header'ındaki sürüm (`"3"`, `3` veya `W/"3"`)
+/// 
+/// Header yoksa veya `*` ise `None`; sayı değilse 400.
+fn if_match_version(headers: &HeaderMap) -> Result<Option<i32>, StatusCode> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let raw = value.to_str().map_err(|_| StatusCode::BAD_REQUEST)?.trim();
+    if raw == "*" {
+        return Ok(None);
+    }
+    let tag = raw.strip_prefix("W/").unwrap_or(raw).trim_matches('"');
+    tag.parse().map(Some).map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+/// ID ile media kaydını oku
+async fn fetch_media(db: &sqlx::PgPool, id: Uuid) -> Result<Option<Media>, StatusCode> {
+    sqlx::query_as::<_, Media>(
+        "SELECT id, name, path, mime_type, size_bytes, created_at, updated_at, version, checksum_sha256 FROM media_datas WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
 /// Bir media nesnesini sil
 /// 
 /// # HTTP
@@ -264,11 +901,12 @@ pub async fn update_media(
 /// # Error Responses
 /// - 404 Not Found: ID bulunamadı
 /// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st), err)]
 pub async fn delete_media(
     State(st): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, StatusCode> {
-    if let Some(db) = &st.db {
+    if let Some(db) = &st.pool().await {
         // ===== PostgreSQL Yolu =====
         let result = sqlx::query("DELETE FROM media_datas WHERE id = $1")
             .bind(id)
@@ -285,6 +923,620 @@ pub async fn delete_media(
     } else {
         // ===== In-Memory Fallback =====
         let mut map = st.media_store.write().await;
-        map.remove(&id).map(|_| StatusCode::NO_CONTENT).ok_or(StatusCode::NOT_FOUND)
+        let removed = map.remove(&id).ok_or(StatusCode::NOT_FOUND)?;
+        if let Some(checksum) = removed.checksum_sha256 {
+            st.media_checksums.write().await.remove(&checksum);
+        }
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_state(items: Vec<Media>) -> AppState {
+        let st = AppState::for_tests();
+        st.media_store.try_write().unwrap().extend(items.into_iter().map(|m| (m.id, m)));
+        st
+    }
+
+    fn media(name: &str, mime: &str, size: i64) -> Media {
+        Media::new(name.into(), format!("/uploads/{name}"), mime.into(), size)
+    }
+
+    async fn list(st: &AppState, query: MediaQuery) -> Vec<String> {
+        let Json(items) = list_media(State(st.clone()), Query(query), Query(SortParams::default())).await.unwrap();
+        let mut names: Vec<_> = items.into_iter().map(|m| m.name).collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_list_media_filter_combinations() {
+        let st = test_state(vec![
+            media("beach.jpg", "image/jpeg", 500),
+            media("vacation.png", "image/png", 5_000),
+            media("vacation.mp4", "video/mp4", 50_000),
+        ]);
+
+        assert_eq!(list(&st, MediaQuery::default()).await.len(), 3);
+
+        let images = MediaQuery { mime_type: Some("image/%".into()), ..Default::default() };
+        assert_eq!(list(&st, images).await, ["beach.jpg", "vacation.png"]);
+
+        let vacation_images = MediaQuery {
+            mime_type: Some("image/%".into()),
+            name_contains: Some("VACATION".into()),
+            ..Default::default()
+        };
+        assert_eq!(list(&st, vacation_images).await, ["vacation.png"]);
+
+        let sized = MediaQuery { min_size: Some(1_000), max_size: Some(50_000), ..Default::default() };
+        assert_eq!(list(&st, sized).await, ["vacation.mp4", "vacation.png"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_media_filters_matching_nothing() {
+        let st = test_state(vec![media("beach.jpg", "image/jpeg", 500)]);
+
+        let no_match = MediaQuery {
+            mime_type: Some("image/%".into()),
+            min_size: Some(1_000),
+            ..Default::default()
+        };
+        assert!(list(&st, no_match).await.is_empty());
+
+        let inverted = MediaQuery { min_size: Some(600), max_size: Some(100), ..Default::default() };
+        assert!(list(&st, inverted).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_media_filters_compose_with_pagination() {
+        let mut items = Vec::new();
+        for i in 0..5 {
+            let mut m = media(&format!("img-{i}.jpg"), "image/jpeg", 100);
+            m.created_at += chrono::Duration::seconds(i);
+            items.push(m);
+        }
+        items.push(media("clip.mp4", "video/mp4", 100));
+        let st = test_state(items);
+
+        let page = MediaQuery {
+            mime_type: Some("image/%".into()),
+            limit: Some(2),
+            offset: Some(1),
+            ..Default::default()
+        };
+        let Json(items) = list_media(State(st.clone()), Query(page), Query(SortParams::default())).await.unwrap();
+        let names: Vec<_> = items.into_iter().map(|m| m.name).collect();
+        assert_eq!(names, ["img-1.jpg", "img-2.jpg"]);
+
+        let negative = MediaQuery { limit: Some(-1), ..Default::default() };
+        let result = list_media(State(st), Query(negative), Query(SortParams::default())).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_list_media_search_multi_word() {
+        let st = test_state(vec![
+            media("vacation.jpg", "image/jpeg", 100),
+            media("summer-vacation-beach.jpg", "image/jpeg", 100),
+            media("summer-party.jpg", "image/jpeg", 100),
+        ]);
+
+        let query = MediaQuery { q: Some("Summer vacation".into()), ..Default::default() };
+        assert_eq!(list(&st, query).await, ["summer-vacation-beach.jpg"]);
+
+        // Tek kelime: kısa ve tam eşleşen isim önce gelir
+        let query = MediaQuery { q: Some("vacation".into()), ..Default::default() };
+        let Json(items) = list_media(State(st.clone()), Query(query), Query(SortParams::default())).await.unwrap();
+        let names: Vec<_> = items.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["vacation.jpg", "summer-vacation-beach.jpg"]);
+        assert!(items.iter().all(|m| m.rank.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_list_media_search_special_characters() {
+        let st = test_state(vec![
+            media("beach_sun.png", "image/png", 100),
+            media("100%.png", "image/png", 100),
+        ]);
+
+        let query = MediaQuery { q: Some("sun & beach!".into()), ..Default::default() };
+        assert_eq!(list(&st, query).await, ["beach_sun.png"]);
+
+        let injection = MediaQuery { q: Some("'; DROP TABLE media_datas; --".into()), ..Default::default() };
+        assert!(list(&st, injection).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_media_empty_search_is_ignored() {
+        let st = test_state(vec![
+            media("a.jpg", "image/jpeg", 100),
+            media("b.jpg", "image/jpeg", 100),
+        ]);
+
+        for q in ["", "   ", "%!?"] {
+            let query = MediaQuery { q: Some(q.into()), ..Default::default() };
+            let Json(items) = list_media(State(st.clone()), Query(query), Query(SortParams::default())).await.unwrap();
+            assert_eq!(items.len(), 2);
+            assert!(items.iter().all(|m| m.rank.is_none()));
+        }
+    }
+
+    /// `GET {uri}` isteğini router üzerinden gönder, isimleri sırasıyla döndür
+    async fn get_names(st: AppState, uri: &str) -> Result<Vec<String>, StatusCode> {
+        use axum::{body::Body, http::Request, routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new().route("/v1/media", get(list_media)).with_state(st);
+        let response = app.oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+        if !response.status().is_success() {
+            return Err(response.status());
+        }
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let items: Vec<Media> = serde_json::from_slice(&body).unwrap();
+        Ok(items.into_iter().map(|m| m.name).collect())
+    }
+
+    #[tokio::test]
+    async fn test_list_media_sort_by_size_desc() {
+        let st = test_state(vec![
+            media("medium.jpg", "image/jpeg", 5_000),
+            media("small.jpg", "image/jpeg", 500),
+            media("large.jpg", "image/jpeg", 50_000),
+        ]);
+
+        let names = get_names(st.clone(), "/v1/media?sort_by=size_bytes&order=desc").await.unwrap();
+        assert_eq!(names, ["large.jpg", "medium.jpg", "small.jpg"]);
+
+        // order verilmezse artan
+        let names = get_names(st.clone(), "/v1/media?sort_by=size_bytes").await.unwrap();
+        assert_eq!(names, ["small.jpg", "medium.jpg", "large.jpg"]);
+
+        let names = get_names(st.clone(), "/v1/media?sort_by=name&order=asc&limit=2").await.unwrap();
+        assert_eq!(names, ["large.jpg", "medium.jpg"]);
+
+        for uri in ["/v1/media?sort_by=path", "/v1/media?sort_by=name&order=up"] {
+            assert_eq!(get_names(st.clone(), uri).await.unwrap_err(), StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_media_sort_overrides_search_rank() {
+        let st = test_state(vec![
+            media("vacation.jpg", "image/jpeg", 100),
+            media("summer-vacation-beach.jpg", "image/jpeg", 200),
+        ]);
+
+        // Varsayılan: skor sırası (kısa isim önce)
+        let names = get_names(st.clone(), "/v1/media?q=vacation").await.unwrap();
+        assert_eq!(names, ["vacation.jpg", "summer-vacation-beach.jpg"]);
+
+        let names = get_names(st, "/v1/media?q=vacation&sort_by=size_bytes&order=desc").await.unwrap();
+        assert_eq!(names, ["summer-vacation-beach.jpg", "vacation.jpg"]);
+    }
+
+    #[test]
+    fn test_sort_direction_and_ties() {
+        let (mut a, mut b) = (media("a.jpg", "image/jpeg", 1), media("b.jpg", "image/jpeg", 1));
+        b.updated_at = a.updated_at + chrono::Duration::seconds(1);
+        let desc = SortParams { sort_by: Some(SortField::UpdatedAt), order: SortOrder::Desc };
+        assert_eq!(desc.compare(&a, &b), std::cmp::Ordering::Greater);
+
+        // Eşit değerler id ile sıralanır (handler'da)
+        a.size_bytes = b.size_bytes;
+        let by_size = SortParams { sort_by: Some(SortField::SizeBytes), order: SortOrder::Asc };
+        assert_eq!(by_size.compare(&a, &b), std::cmp::Ordering::Equal);
+        assert_eq!((sort_column(SortField::SizeBytes), sort_direction(SortOrder::Desc)), ("size_bytes", " DESC"));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_list_media_sort_by_size_desc_in_postgres() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        let db = sqlx::PgPool::connect(&url).await.unwrap();
+        let st = AppState::for_tests_with_db(db.clone());
+
+        // Tabloda başka kayıtlar olabilir: isimler bu teste özgü
+        let tag = Uuid::new_v4().simple().to_string();
+        let mut ids = Vec::new();
+        for (name, size) in [("medium", 5_000), ("small", 500), ("large", 50_000)] {
+            let new = NewMedia {
+                name: format!("{tag}-{name}.jpg"),
+                path: format!("/uploads/{tag}-{name}.jpg"),
+                mime_type: "image/jpeg".into(),
+                size_bytes: size,
+            };
+            let (_, Json(created)) = create_media(State(st.clone()), Ok(Json(new))).await.unwrap();
+            ids.push(created.id);
+        }
+
+        let uri = format!("/v1/media?name_contains={tag}&sort_by=size_bytes&order=desc");
+        let names = get_names(st.clone(), &uri).await.unwrap();
+        let expected: Vec<_> = ["large", "medium", "small"].iter().map(|n| format!("{tag}-{n}.jpg")).collect();
+        assert_eq!(names, expected);
+
+        for id in ids {
+            delete_media(State(st.clone()), Path(id)).await.unwrap();
+        }
+    }
+
+    /// `POST {uri}` ile içerik yükle; status, `Location` ve body'yi döndür
+    async fn upload(st: &AppState, uri: &str, content: &'static [u8]) -> (StatusCode, Option<String>, Media) {
+        use axum::{http::Request, routing::post, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new().route("/v1/media/upload", post(upload_media)).with_state(st.clone());
+        let request = Request::post(uri).header(header::CONTENT_TYPE, "image/jpeg").body(Body::from(content)).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let location = response.headers().get(header::LOCATION).map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, location, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_identical_uploads_conflict() {
+        let st = test_state(vec![]);
+
+        let (status, location, first) = upload(&st, "/v1/media/upload?name=a.jpg", b"same photo").await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(location, Some(format!("/v1/media/{}", first.id)));
+        assert_eq!((first.mime_type.as_str(), first.size_bytes), ("image/jpeg", 10));
+        assert_eq!(first.checksum_sha256, Some(format!("{:x}", Sha256::digest(b"same photo"))));
+        assert_eq!(tokio::fs::read(&first.path).await.unwrap(), b"same photo");
+
+        // İkinci kopya kaydedilmez; mevcut kayda yönlendirilir
+        let (status, location, existing) = upload(&st, "/v1/media/upload?name=b.jpg", b"same photo").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(location, Some(format!("/v1/media/{}", first.id)));
+        assert_eq!(existing, first);
+        assert_eq!(st.media_store.read().await.len(), 1);
+
+        tokio::fs::remove_file(&first.path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_differing_uploads_both_created() {
+        let st = test_state(vec![media("legacy.jpg", "image/jpeg", 5)]);
+
+        let (status, _, a) = upload(&st, "/v1/media/upload?name=a.jpg", b"photo one").await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, _, b) = upload(&st, "/v1/media/upload?name=b.jpg", b"photo two").await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_ne!(a.checksum_sha256, b.checksum_sha256);
+
+        // Checksum ile arama (büyük harf hex de kabul edilir)
+        let checksum = b.checksum_sha256.clone().unwrap().to_uppercase();
+        let names = get_names(st.clone(), &format!("/v1/media?checksum={checksum}")).await.unwrap();
+        assert_eq!(names, ["b.jpg"]);
+        let names = get_names(st.clone(), &format!("/v1/media?checksum={}", "0".repeat(64))).await.unwrap();
+        assert!(names.is_empty());
+        assert_eq!(get_names(st.clone(), "/v1/media").await.unwrap().len(), 3);
+
+        for item in [a, b] {
+            tokio::fs::remove_file(&item.path).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dedup_flag_links_to_existing() {
+        let st = test_state(vec![]);
+
+        let (_, _, first) = upload(&st, "/v1/media/upload?name=a.jpg", b"dedup photo").await;
+        let (status, location, linked) = upload(&st, "/v1/media/upload?name=b.jpg&dedup=true", b"dedup photo").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(location, Some(format!("/v1/media/{}", first.id)));
+        assert_eq!(linked.id, first.id);
+        assert_eq!(st.media_store.read().await.len(), 1);
+
+        // Silinen kayıt indeksten de çıkar; aynı içerik tekrar yüklenebilir
+        delete_media(State(st.clone()), Path(first.id)).await.unwrap();
+        assert!(st.media_checksums.read().await.is_empty());
+        let (status, _, again) = upload(&st, "/v1/media/upload?name=b.jpg&dedup=true", b"dedup photo").await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_ne!(again.id, first.id);
+
+        for path in [first.path, again.path] {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunked_upload_over_limit() {
+        use axum::{body::Bytes, http::Request, routing::post, Router};
+        use tower::ServiceExt;
+
+        let st = test_state(vec![]);
+        let app = Router::new().route("/v1/media/upload", post(upload_media)).with_state(st.clone());
+        let app = crate::limits::with_body_limit(app, 8);
+
+        // Content-Length yok: sınır okuma sırasında aşılır
+        let chunks = futures_util::stream::iter([b"12345".as_slice(), b"67890".as_slice()].map(|c| Ok::<_, std::io::Error>(Bytes::from_static(c))));
+        let request = Request::post("/v1/media/upload?name=big.bin").body(Body::from_stream(chunks)).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(st.media_store.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_identical_uploads_in_postgres() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        let db = sqlx::PgPool::connect(&url).await.unwrap();
+        let st = AppState::for_tests_with_db(db.clone());
+
+        // Tabloda önceki çalıştırmalardan kayıt kalmasın diye içerik teste özgü
+        let content: &'static [u8] = Box::leak(Uuid::new_v4().to_string().into_bytes().into_boxed_slice());
+        let (status, _, first) = upload(&st, "/v1/media/upload?name=a.jpg", content).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, location, existing) = upload(&st, "/v1/media/upload?name=b.jpg", content).await;
+        assert_eq!((status, location), (StatusCode::CONFLICT, Some(format!("/v1/media/{}", first.id))));
+        assert_eq!(existing.id, first.id);
+        let (status, _, linked) = upload(&st, "/v1/media/upload?name=b.jpg&dedup=true", content).await;
+        assert_eq!((status, linked.id), (StatusCode::OK, first.id));
+
+        let checksum = first.checksum_sha256.clone().unwrap();
+        let names = get_names(st.clone(), &format!("/v1/media?checksum={checksum}")).await.unwrap();
+        assert_eq!(names, ["a.jpg"]);
+
+        delete_media(State(st), Path(first.id)).await.unwrap();
+        tokio::fs::remove_file(&first.path).await.unwrap();
+    }
+
+    fn rename(name: &str, expected_version: Option<i32>) -> UpdateMedia {
+        UpdateMedia { name: Some(name.into()), expected_version, ..Default::default() }
+    }
+
+    async fn update(st: &AppState, id: Uuid, headers: HeaderMap, patch: UpdateMedia) -> Result<Media, MediaError> {
+        update_media(State(st.clone()), Path(id), headers, Ok(Json(patch))).await.map(|Json(m)| m)
+    }
+
+    #[tokio::test]
+    async fn test_interleaved_updates_second_is_rejected() {
+        let item = media("draft.jpg", "image/jpeg", 100);
+        let id = item.id;
+        let st = test_state(vec![item]);
+
+        // İki dashboard aynı kaydı (version 1) okudu
+        let first = update(&st, id, HeaderMap::new(), rename("first.jpg", Some(1))).await.unwrap();
+        assert_eq!(first.version, 2);
+
+        let Err(MediaError::VersionMismatch(status, current)) =
+            update(&st, id, HeaderMap::new(), rename("second.jpg", Some(1))).await
+        else {
+            panic!("stale update accepted");
+        };
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!((current.name.as_str(), current.version), ("first.jpg", 2));
+
+        // Aynı çakışma If-Match ile 412 döner
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, "\"1\"".parse().unwrap());
+        let err = update(&st, id, headers, rename("second.jpg", None)).await.unwrap_err();
+        assert!(matches!(err, MediaError::VersionMismatch(StatusCode::PRECONDITION_FAILED, _)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, "\"2\"".parse().unwrap());
+        let retried = update(&st, id, headers, rename("second.jpg", None)).await.unwrap();
+        assert_eq!((retried.name.as_str(), retried.version), ("second.jpg", 3));
+    }
+
+    #[tokio::test]
+    async fn test_update_without_version_always_applies() {
+        let item = media("a.jpg", "image/jpeg", 100);
+        let id = item.id;
+        let st = test_state(vec![item]);
+
+        update(&st, id, HeaderMap::new(), rename("b.jpg", None)).await.unwrap();
+        let latest = update(&st, id, HeaderMap::new(), rename("c.jpg", None)).await.unwrap();
+        assert_eq!((latest.name.as_str(), latest.version), ("c.jpg", 3));
+    }
+
+    #[test]
+    fn test_if_match_version_parsing() {
+        let parse = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::IF_MATCH, value.parse().unwrap());
+            if_match_version(&headers)
+        };
+        assert_eq!(if_match_version(&HeaderMap::new()), Ok(None));
+        assert_eq!(parse("\"3\""), Ok(Some(3)));
+        assert_eq!(parse("W/\"3\""), Ok(Some(3)));
+        assert_eq!(parse("*"), Ok(None));
+        assert_eq!(parse("\"abc\""), Err(StatusCode::BAD_REQUEST));
+    }
+
+    /// Body'yi router'daki gibi `Json` extractor'ıyla çöz
+    async fn extract<T: serde::de::DeserializeOwned>(body: &str) -> Result<Json<T>, JsonRejection> {
+        use axum::extract::FromRequest;
+        let request = axum::http::Request::post("/v1/media")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        Json::<T>::from_request(request, &()).await
+    }
+
+    fn new_media_json(name: &str) -> serde_json::Value {
+        serde_json::json!({"name": name, "path": format!("/uploads/{name}"), "mime_type": "image/jpeg", "size_bytes": 10})
+    }
+
+    #[tokio::test]
+    async fn test_bulk_create_is_atomic() {
+        let st = test_state(Vec::new());
+        let batch = vec![
+            new_media_json("a.jpg"),
+            serde_json::json!({"name": "b.jpg", "path": "/b", "mime_type": "foobar", "size_bytes": 1}),
+            new_media_json("c.jpg"),
+            serde_json::json!({"name": "d.jpg"}),
+        ];
+        let Err(MediaError::Problem(problem)) = create_media_bulk(State(st.clone()), Ok(Json(batch))).await else {
+            panic!("invalid batch accepted");
+        };
+        assert_eq!(problem.status, 400);
+        assert_eq!(problem.errors.iter().map(|e| e.index).collect::<Vec<_>>(), [1, 3]);
+        assert!(problem.errors[0].detail.contains("invalid mime_type \"foobar\""), "{:?}", problem.errors);
+        assert!(problem.errors[1].detail.contains("missing field `path`"), "{:?}", problem.errors);
+        assert!(st.media_store.read().await.is_empty());
+
+        // Geçerli batch: ID'ler request sırasıyla
+        let batch = vec![new_media_json("a.jpg"), new_media_json("b.jpg"), new_media_json("c.jpg")];
+        let (status, Json(created)) = create_media_bulk(State(st.clone()), Ok(Json(batch))).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        let store = st.media_store.read().await;
+        let names: Vec<_> = created.ids.iter().map(|id| store[id].name.as_str()).collect();
+        assert_eq!(names, ["a.jpg", "b.jpg", "c.jpg"]);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_requests_capped_at_max_items() {
+        let st = test_state(Vec::new());
+        let batch = vec![new_media_json("a.jpg"); MEDIA_BULK_MAX_ITEMS + 1];
+        let Err(MediaError::Problem(problem)) = create_media_bulk(State(st.clone()), Ok(Json(batch))).await else {
+            panic!("oversized batch accepted");
+        };
+        assert_eq!(problem.status, 413);
+        assert!(st.media_store.read().await.is_empty());
+
+        let ids = vec![Uuid::new_v4(); MEDIA_BULK_MAX_ITEMS + 1];
+        let err = delete_media_bulk(State(st.clone()), Ok(Json(ids))).await.unwrap_err();
+        assert_eq!(err.into_response().status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let batch = vec![new_media_json("a.jpg"); MEDIA_BULK_MAX_ITEMS];
+        let (_, Json(created)) = create_media_bulk(State(st.clone()), Ok(Json(batch))).await.unwrap();
+        assert_eq!(created.ids.len(), MEDIA_BULK_MAX_ITEMS);
+        assert_eq!(st.media_store.read().await.len(), MEDIA_BULK_MAX_ITEMS);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_delete_mixed_outcomes() {
+        let mut uploaded = media("uploaded.jpg", "image/jpeg", 1);
+        uploaded.checksum_sha256 = Some("abc".into());
+        let (kept, plain) = (media("kept.jpg", "image/jpeg", 1), media("plain.jpg", "image/jpeg", 1));
+        let st = test_state(vec![uploaded.clone(), kept.clone(), plain.clone()]);
+        st.media_checksums.write().await.insert("abc".into(), uploaded.id);
+
+        let missing = Uuid::new_v4();
+        let ids = vec![uploaded.id, missing, plain.id, uploaded.id];
+        let Json(results) = delete_media_bulk(State(st.clone()), Ok(Json(ids))).await.unwrap();
+        let outcomes: Vec<_> = results.iter().map(|r| (r.id, r.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            [
+                (uploaded.id, MediaDeleteOutcome::Deleted),
+                (missing, MediaDeleteOutcome::NotFound),
+                (plain.id, MediaDeleteOutcome::Deleted),
+                // Aynı istekte ikinci kez
+                (uploaded.id, MediaDeleteOutcome::NotFound),
+            ]
+        );
+        assert_eq!(st.media_store.read().await.keys().collect::<Vec<_>>(), [&kept.id]);
+        assert!(st.media_checksums.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_bulk_create_and_delete_in_postgres() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        let db = sqlx::PgPool::connect(&url).await.unwrap();
+        let st = AppState::for_tests_with_db(db.clone());
+        let tag = Uuid::new_v4().simple().to_string();
+        let count = || async {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM media_datas WHERE name LIKE $1")
+                .bind(format!("{tag}-%"))
+                .fetch_one(&db)
+                .await
+                .unwrap()
+        };
+
+        let mut batch: Vec<_> = (0..3).map(|i| new_media_json(&format!("{tag}-{i}.jpg"))).collect();
+        batch.push(serde_json::json!({"name": format!("{tag}-bad.jpg"), "path": "/x", "mime_type": "image/*", "size_bytes": 1}));
+        let err = create_media_bulk(State(st.clone()), Ok(Json(batch.clone()))).await.unwrap_err();
+        assert!(matches!(&err, MediaError::Problem(p) if p.errors.len() == 1 && p.errors[0].index == 3), "{err}");
+        assert_eq!(count().await, 0);
+
+        batch.pop();
+        let (_, Json(created)) = create_media_bulk(State(st.clone()), Ok(Json(batch))).await.unwrap();
+        for (i, id) in created.ids.iter().enumerate() {
+            let item = fetch_media(&db, *id).await.unwrap().unwrap();
+            assert_eq!((item.name, item.version), (format!("{tag}-{i}.jpg"), 1));
+        }
+
+        let missing = Uuid::new_v4();
+        let ids = vec![created.ids[0], missing, created.ids[2], created.ids[0]];
+        let Json(results) = delete_media_bulk(State(st.clone()), Ok(Json(ids))).await.unwrap();
+        let outcomes: Vec<_> = results.iter().map(|r| r.outcome).collect();
+        use MediaDeleteOutcome::{Deleted, NotFound};
+        assert_eq!(outcomes, [Deleted, NotFound, Deleted, NotFound]);
+        assert_eq!(count().await, 1);
+        delete_media(State(st), Path(created.ids[1])).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_mime_type_rejected_with_message() {
+        let st = test_state(Vec::new());
+        let payload = extract(r#"{"name": "a", "path": "/a", "mime_type": "foobar", "size_bytes": 1}"#).await;
+        let Err(MediaError::InvalidBody(status, message)) = create_media(State(st.clone()), payload).await else {
+            panic!("invalid mime type accepted");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains("invalid mime_type \"foobar\""), "{message}");
+        assert!(st.media_store.read().await.is_empty());
+
+        // Geçerli tip normalize edilerek kaydedilir
+        let payload = extract(r#"{"name": "a", "path": "/a", "mime_type": "Image/JPEG", "size_bytes": 1}"#).await;
+        let (_, Json(created)) = create_media(State(st.clone()), payload).await.unwrap();
+        assert_eq!(created.mime_type, "image/jpeg");
+
+        let payload = extract(r#"{"mime_type": "image/*"}"#).await;
+        let err = update_media(State(st.clone()), Path(created.id), HeaderMap::new(), payload).await.unwrap_err();
+        assert!(matches!(&err, MediaError::InvalidBody(StatusCode::BAD_REQUEST, m) if m.contains("wildcards")), "{err}");
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Bozuk JSON axum'un status'unu korur
+        let payload = extract::<NewMedia>("{not json").await;
+        assert_eq!(json_body(payload).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_versioned_update_in_postgres() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        let db = sqlx::PgPool::connect(&url).await.unwrap();
+        let st = AppState::for_tests_with_db(db.clone());
+
+        let new = NewMedia { name: "draft.jpg".into(), path: "/uploads/draft.jpg".into(), mime_type: "image/jpeg".into(), size_bytes: 1 };
+        let (_, Json(created)) = create_media(State(st.clone()), Ok(Json(new))).await.unwrap();
+        assert_eq!(created.version, 1);
+
+        let first = update(&st, created.id, HeaderMap::new(), rename("first.jpg", Some(1))).await.unwrap();
+        assert_eq!(first.version, 2);
+        let Err(MediaError::VersionMismatch(status, current)) =
+            update(&st, created.id, HeaderMap::new(), rename("second.jpg", Some(1))).await
+        else {
+            panic!("stale update accepted");
+        };
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(current.name, "first.jpg");
+
+        let latest = update(&st, created.id, HeaderMap::new(), rename("third.jpg", None)).await.unwrap();
+        assert_eq!(latest.version, 3);
+
+        // Eş zamanlı sürümsüz güncellemelerin hepsi uygulanır
+        let updates: Vec<_> = (0..10)
+            .map(|i| {
+                let st = st.clone();
+                tokio::spawn(async move { update(&st, created.id, HeaderMap::new(), rename(&format!("{i}.jpg"), None)).await })
+            })
+            .collect();
+        for task in updates {
+            task.await.unwrap().unwrap();
+        }
+        let current = fetch_media(&db, created.id).await.unwrap().unwrap();
+        assert_eq!(current.version, 13);
+        delete_media(State(st), Path(created.id)).await.unwrap();
+    }
+}
@@ -5,6 +5,19 @@
 
 pub mod health;   // Sağlık kontrol endpoint'leri (/, /health, /ready)
 pub mod sys;      // Sistem endpoint'leri (/v1/config)
+pub mod admin;    // Yönetim endpoint'leri (/v1/admin/*, ADMIN_TOKEN)
+pub mod auth;     // Rol token'ları (/v1/auth/token)
 pub mod media;    // Media CRUD endpoint'leri (/v1/media/*)
 pub mod db;       // Database endpoint'leri (/db/*)
-pub mod sensors;  // Sensör endpoint'leri (/api/sensors) 
\ No newline at end of file
+pub mod devices;  // Cihaz endpoint'leri (/v1/devices/*)
+pub mod command_audit; // Komut denetim kaydı (/v1/devices/{id}/commands)
+pub mod device_errors; // Cihaz hata raporları (/api/devices/{id}/errors)
+pub mod device_tokens; // Cihaza özel token'lar (/v1/devices/{id}/tokens)
+pub mod provision; // Cihaz kaydı (/v1/provision)
+pub mod sensors;  // Sensör endpoint'leri (/api/sensors, /v1/sensors/*)
+pub mod sensor_groups; // Cihazlar arası sensör grupları (/v1/sensor-groups)
+pub mod motion;   // Motion olay geçmişi (/api/sensors/events)
+pub mod simulate; // Test okuması üretme (/v1/sensors/{device_id}/simulate)
+pub mod alerts;   // Alarm olayları ve onay (/v1/alerts/events)
+pub mod summary;  // Dashboard özeti (/api/summary)
+pub mod webhooks; // Webhook kayıtları ve alarm bildirimleri (/v1/webhooks)
@@ -0,0 +1,402 @@
+//! Hareket olayı endpoint'leri
+//!
+//! İkili sensörlerin (motion) tek Redis key'i sadece son durumu tutar.
+//! Bu modül her okumada durum geçişlerini olay olarak da saklar:
+//! - PostgreSQL varsa `motion_events` tablosuna (`started_at`/`ended_at`)
+//! - Redis varsa `motion_events:{device_id}:{sensor_type}` listesine (son
+//!   `REDIS_EVENTS_CAP` geçiş); son durum ve geçiş tek Lua script'iyle
+//!   atomik yazılır
+//!
+//! `GET /api/sensors/events` olayları süreleriyle döner.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::Deserialize;
+use shared_types::{motion::{is_active, pair_transitions}, MotionEvent, SensorType};
+use sqlx::{PgConnection, PgPool};
+
+use crate::routes::sensors::SensorData;
+use crate::state::AppState;
+
+/// Redis geçiş listelerinin key prefix'i
+const REDIS_EVENTS_PREFIX: &str = "motion_events:";
+
+/// Redis listesinde tutulan en fazla geçiş sayısı
+pub const REDIS_EVENTS_CAP: isize = 500;
+
+/// Olay sorgusu - `[from, to]` ile kesişen olaylar
+///
+/// Sürmekte olan olaylar (`ended_at IS NULL`) her `from` ile kesişir.
+const EVENTS_SQL: &str = "SELECT device_id, sensor_type, started_at, ended_at,
+            EXTRACT(EPOCH FROM ended_at - started_at)::float8 AS duration_secs
+     FROM motion_events
+     WHERE device_id = $1 AND sensor_type = $2
+       AND ($3::timestamptz IS NULL OR ended_at IS NULL OR ended_at >= $3)
+       AND ($4::timestamptz IS NULL OR started_at <= $4)
+     ORDER BY started_at ASC";
+
+/// `GET /api/sensors/events` parametreleri
+///
+/// Örnek: `?device_id=edge-agent-001&sensor_type=motion&from=2024-01-20T00:00:00Z`
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventsParams {
+    pub device_id: String,
+    /// Varsayılan: "motion"
+    #[serde(default = "default_sensor_type")]
+    pub sensor_type: String,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+fn default_sensor_type() -> String { "motion".into() }
+
+/// Sensör tipi olay olarak saklanıyor mu?
+pub fn is_binary_sensor(sensor_type: &str) -> bool {
+    sensor_type.parse::<SensorType>().is_ok_and(|kind| kind.is_binary())
+}
+
+/// İkili sensör olayları
+///
+/// GET /api/sensors/events?device_id=&sensor_type=motion&from=&to=
+///
+/// `[from, to]` ile kesişen olayları başlangıca göre artan sırada döner.
+/// PostgreSQL varsa `motion_events` tablosu, yoksa Redis'teki son geçişler
+/// kullanılır. İkili olmayan sensör tipi → 400, depolama yoksa 503.
+///
+/// Response:
+/// ```json
+/// [
+///   {"device_id": "edge-agent-001", "sensor_type": "motion",
+///    "started_at": "2024-01-20T10:30:00Z", "ended_at": "2024-01-20T10:30:42Z", "duration_secs": 42.0},
+///   {"device_id": "edge-agent-001", "sensor_type": "motion",
+///    "started_at": "2024-01-20T11:02:10Z", "ended_at": null, "duration_secs": null}
+/// ]
+/// ```
+#[tracing::instrument(skip(state), err)]
+pub async fn sensor_events(
+    State(state): State<AppState>,
+    Query(params): Query<EventsParams>,
+) -> Result<Json<Vec<MotionEvent>>, StatusCode> {
+    if !is_binary_sensor(&params.sensor_type) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if let Some(db) = &state.pool().await {
+        let events = sqlx::query_as::<_, MotionEvent>(EVENTS_SQL)
+            .bind(&params.device_id)
+            .bind(&params.sensor_type)
+            .bind(params.from)
+            .bind(params.to)
+            .fetch_all(db)
+            .await
+            .map_err(|e| {
+                tracing::error!("Motion events query failed: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        return Ok(Json(events));
+    }
+
+    let Some(mut redis_conn) = state.redis.clone() else {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+    let key = redis_events_key(&params.device_id, &params.sensor_type);
+    let raw: Vec<String> = redis_conn.lrange(&key, 0, -1).await.map_err(|e| {
+        tracing::error!("Redis read error: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let transitions = raw
+        .iter()
+        .filter_map(|json| serde_json::from_str::<SensorData>(json).ok())
+        .filter_map(|data| Some((reading_time(&data)?, data.value)));
+
+    let events = pair_transitions(&params.device_id, &params.sensor_type, transitions)
+        .into_iter()
+        .filter(|event| event.overlaps(params.from, params.to))
+        .collect();
+    Ok(Json(events))
+}
+
+/// Okumadan sonra cihazın olaylarını güncelle
+///
+/// Okuma `sensor_readings`'e yazıldıktan sonra çağrılır. Sensörün en yeni
+/// okumasıysa (olağan durum) sadece son olay açılır veya kapanır. Geç gelen
+/// okumada, okumanın zamanında veya öncesinde başlayan son olaydan itibaren
+/// okumalar `sensor_readings`'ten yeniden eşlenir; böylece doğru olay bölünür
+/// veya birleşir. Öncesinde olay yoksa o zamana kadarki okumaların hepsi
+/// 0'dır (her okumada bu fonksiyon çalıştığı için), eşleme okumanın
+/// kendisinden başlar.
+pub async fn record_in_db(db: &PgPool, data: &SensorData) -> Result<(), StatusCode> {
+    let timestamp = reading_time(data).ok_or(StatusCode::BAD_REQUEST)?;
+    let db_error = |e: sqlx::Error| {
+        tracing::error!("Motion events update failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    let mut tx = db.begin().await.map_err(db_error)?;
+    // Aynı sensör için eş zamanlı okumalar sırayla eşlenir
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1 || '/' || $2))")
+        .bind(&data.device_id)
+        .bind(&data.sensor_type)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    let late: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sensor_readings WHERE device_id = $1 AND sensor_type = $2 AND timestamp > $3)",
+    )
+    .bind(&data.device_id)
+    .bind(&data.sensor_type)
+    .bind(timestamp)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if !late {
+        advance_last_event(&mut tx, data, timestamp).await.map_err(db_error)?;
+        return tx.commit().await.map_err(db_error);
+    }
+
+    let anchor: Option<DateTime<Utc>> = sqlx::query_scalar(
+        "SELECT max(started_at) FROM motion_events
+         WHERE device_id = $1 AND sensor_type = $2 AND started_at <= $3",
+    )
+    .bind(&data.device_id)
+    .bind(&data.sensor_type)
+    .bind(timestamp)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let anchor = anchor.unwrap_or(timestamp);
+
+    let readings: Vec<(DateTime<Utc>, f64)> = sqlx::query_as(
+        "SELECT timestamp, value FROM sensor_readings
+         WHERE device_id = $1 AND sensor_type = $2 AND timestamp >= $3
+         ORDER BY timestamp ASC, id ASC",
+    )
+    .bind(&data.device_id)
+    .bind(&data.sensor_type)
+    .bind(anchor)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let events = pair_transitions(&data.device_id, &data.sensor_type, readings);
+
+    sqlx::query("DELETE FROM motion_events WHERE device_id = $1 AND sensor_type = $2 AND started_at >= $3")
+        .bind(&data.device_id)
+        .bind(&data.sensor_type)
+        .bind(anchor)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    let (starts, ends): (Vec<_>, Vec<_>) = events.iter().map(|e| (e.started_at, e.ended_at)).unzip();
+    sqlx::query(
+        "INSERT INTO motion_events (device_id, sensor_type, started_at, ended_at)
+         SELECT $1, $2, * FROM UNNEST($3::timestamptz[], $4::timestamptz[])",
+    )
+    .bind(&data.device_id)
+    .bind(&data.sensor_type)
+    .bind(&starts)
+    .bind(&ends)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)
+}
+
+/// En yeni okumayla son olayı ilerlet: aktifse ve açık olay yoksa başlat,
+/// pasifse açık olayı bitir
+async fn advance_last_event(conn: &mut PgConnection, data: &SensorData, timestamp: DateTime<Utc>) -> sqlx::Result<()> {
+    let open: Option<i64> = sqlx::query_scalar(
+        "SELECT id FROM motion_events
+         WHERE device_id = $1 AND sensor_type = $2 AND ended_at IS NULL
+         ORDER BY started_at DESC LIMIT 1",
+    )
+    .bind(&data.device_id)
+    .bind(&data.sensor_type)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match (open, is_active(data.value)) {
+        (None, true) => {
+            sqlx::query("INSERT INTO motion_events (device_id, sensor_type, started_at) VALUES ($1, $2, $3)")
+                .bind(&data.device_id)
+                .bind(&data.sensor_type)
+                .bind(timestamp)
+                .execute(conn)
+                .await?;
+        }
+        (Some(id), false) => {
+            sqlx::query("UPDATE motion_events SET ended_at = $2 WHERE id = $1").bind(id).bind(timestamp).execute(conn).await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Son değeri Redis'e yaz; ikili durum değiştiyse okumayı geçiş listesine ekle
+///
+/// KEYS: sensör key'i, geçiş listesi. ARGV: okuma JSON'u, TTL, yeni durum
+/// (`0`/`1`), liste kapasitesi. Önceki değerin okunması ve üzerine yazılması
+/// tek adımdadır: eş zamanlı okumalar aynı geçişi iki kez eklemez veya
+/// kaçırmaz. Önceki değer yoksa veya okunamıyorsa geçiş sayılır.
+const RECORD_SCRIPT: &str = "local previous = redis.call('GET', KEYS[1])
+redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+local was = nil
+if previous then
+  local ok, decoded = pcall(cjson.decode, previous)
+  if ok and type(decoded) == 'table' and type(decoded.value) == 'number' then
+    was = decoded.value > 0 and '1' or '0'
+  end
+end
+if was ~= ARGV[3] then
+  redis.call('LPUSH', KEYS[2], ARGV[1])
+  redis.call('LTRIM', KEYS[2], 0, tonumber(ARGV[4]) - 1)
+end
+return 0";
+
+/// İkili sensörün okumasını Redis'e yaz (son değer + geçiş listesi, son `REDIS_EVENTS_CAP`)
+///
+/// `sensor_key`'e `ttl_secs` ile yazılan değer diğer sensörlerinkiyle aynıdır.
+pub async fn record_in_redis(
+    conn: &mut redis::aio::ConnectionManager,
+    sensor_key: &str,
+    data: &SensorData,
+    ttl_secs: u64,
+) -> Result<(), StatusCode> {
+    let json = serde_json::to_string(data).map_err(|_| StatusCode::BAD_REQUEST)?;
+    redis::Script::new(RECORD_SCRIPT)
+        .key(sensor_key)
+        .key(redis_events_key(&data.device_id, &data.sensor_type))
+        .arg(json)
+        .arg(ttl_secs)
+        .arg(if is_active(data.value) { "1" } else { "0" })
+        .arg(REDIS_EVENTS_CAP)
+        .invoke_async::<()>(conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Redis write error: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+fn redis_events_key(device_id: &str, sensor_type: &str) -> String {
+    format!("{REDIS_EVENTS_PREFIX}{device_id}:{sensor_type}")
+}
+
+fn reading_time(data: &SensorData) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&data.timestamp).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn motion(device_id: &str, value: f64, timestamp: &str) -> SensorData {
+        SensorData {
+            device_id: device_id.into(),
+            sensor_type: "motion".into(),
+            value,
+            unit: "bool".into(),
+            timestamp: timestamp.into(),
+            metadata: None,
+            received_at: None,
+            gateway_received_at: None,
+            stale: false,
+        }
+    }
+
+    fn params(device_id: &str, sensor_type: &str) -> EventsParams {
+        EventsParams { device_id: device_id.into(), sensor_type: sensor_type.into(), from: None, to: None }
+    }
+
+    #[test]
+    fn test_only_binary_sensors_are_tracked() {
+        assert!(is_binary_sensor("motion"));
+        assert!(is_binary_sensor(" Motion "));
+        assert!(!is_binary_sensor("temperature"));
+    }
+
+    #[tokio::test]
+    async fn test_events_rejects_non_binary_and_needs_storage() {
+        let state = AppState::for_tests();
+        let result = sensor_events(State(state.clone()), Query(params("dev-1", "temperature"))).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+        let result = sensor_events(State(state), Query(params("dev-1", "motion"))).await;
+        assert_eq!(result.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_events_follow_ingested_transitions() {
+        let db = test_db().await;
+        let device = format!("motion-{}", uuid::Uuid::new_v4());
+        let state = AppState::for_tests_with_db(db.clone());
+        // Dünün tarihi: MAX_READING_AGE_DAYS penceresinin içinde
+        let day = (chrono::Utc::now() - chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+        let ingest = |value: f64, time: &str| {
+            let (state, data) = (state.clone(), motion(&device, value, &format!("{day}T10:{time}Z")));
+            async move {
+                let Json(result) = crate::routes::sensors::add_sensor_data(State(state), None, crate::msgpack::JsonOrMsgPack(data)).await.unwrap();
+                assert_eq!(result.accepted, 1);
+            }
+        };
+        let events = || async {
+            let Json(events) = sensor_events(State(state.clone()), Query(params(&device, "motion"))).await.unwrap();
+            events.into_iter().map(|e| (e.started_at.format("%M:%S").to_string(), e.duration_secs)).collect::<Vec<_>>()
+        };
+
+        // Hızlı açılıp kapanma + tekrar eden değerler
+        ingest(1.0, "00:00").await;
+        ingest(1.0, "00:05").await;
+        ingest(0.0, "00:10").await;
+        ingest(1.0, "00:11").await;
+        ingest(0.0, "00:12").await;
+        // Eşi olmayan başlangıç: sürüyor
+        ingest(1.0, "05:00").await;
+        assert_eq!(
+            events().await,
+            vec![("00:00".into(), Some(10.0)), ("00:11".into(), Some(1.0)), ("05:00".into(), None)]
+        );
+
+        // Geç gelen okumalar: ilk olayı böler, sürmekte olanı bitirir
+        ingest(0.0, "00:04").await;
+        ingest(0.0, "06:00").await;
+        assert_eq!(
+            events().await,
+            vec![
+                ("00:00".into(), Some(4.0)),
+                ("00:05".into(), Some(5.0)),
+                ("00:11".into(), Some(1.0)),
+                ("05:00".into(), Some(60.0)),
+            ]
+        );
+
+        // Aralık filtresi
+        let range = EventsParams {
+            from: Some(format!("{day}T10:00:30Z").parse().unwrap()),
+            to: Some(format!("{day}T10:05:30Z").parse().unwrap()),
+            ..params(&device, "motion")
+        };
+        let Json(in_range) = sensor_events(State(state.clone()), Query(range)).await.unwrap();
+        assert_eq!(in_range.len(), 1);
+
+        for table in ["motion_events", "sensor_readings"] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE device_id = $1"))
+                .bind(&device)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+    }
+}
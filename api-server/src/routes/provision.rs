@@ -0,0 +1,214 @@
+//! Cihaz Kaydı (Device Provisioning)
+//!
+//! Edge agent `DEVICE_ID` verilmeden ilk kez açıldığında seri numarasıyla
+//! kayıt olur; sunucu cihaz ID'sini, MQTT broker adresini, başlangıç
+//! ayarlarını ve kalıcı bir cihaz token'ı (`rfd_...`, bkz. `device_tokens`)
+//! döner. Agent token'ı saklar ve sonraki isteklerinde kullanır.
+//!
+//! Cihazlar PostgreSQL'e veya in-memory store'a kaydedilir (fallback).
+//!
+//! # Endpoint'ler
+//! - POST /v1/provision - Cihazı kaydet (seri numarası başına tek kayıt)
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::Utc;
+use serde_json::Value;
+use uuid::Uuid;
+
+use shared_types::{Device, DeviceProvisioningRequest, DeviceProvisioningResponse, RemoteConfig};
+
+use crate::routes::device_tokens;
+use crate::state::AppState;
+
+/// Kayıtta verilen cihaz token'larının etiketi
+pub const PROVISIONED_TOKEN_LABEL: &str = "provisioned";
+
+/// Cihazı kaydet
+///
+/// # HTTP
+/// `POST /v1/provision` (`AUTH_ENABLED=true` iken `provisioner` rolü, yani
+/// `Authorization: Bearer <PROVISIONING_TOKEN>`, veya `admin`)
+///
+/// # Request Body
+/// ```json
+/// {
+///   "serial_number": "10000000abcdef01",
+///   "firmware_version": "0.1.0",
+///   "model": "Raspberry Pi 4 Model B Rev 1.4"
+/// }
+/// ```
+///
+/// Seri numarası daha önce kaydedildiyse aynı `device_id` döner ve
+/// model/firmware güncellenir. `initial_config` cihaz gölgesinin `desired`
+/// bölümünden gelir (gölge yoksa boş).
+///
+/// # Response (200 OK)
+/// `DeviceProvisioningResponse`; `token` iptal edilene kadar geçerli bir
+/// cihaz token'ıdır (`provisioned` etiketiyle `GET /v1/devices/{id}/tokens`'ta
+/// listelenir). Her kayıt yeni bir token verir.
+///
+/// # Error Responses
+/// - 400 Bad Request: `serial_number` boş
+/// - 500 Internal Server Error: Database hatası
+#[tracing::instrument(skip(st), err)]
+pub async fn provision_device(
+    State(st): State<AppState>,
+    Json(request): Json<DeviceProvisioningRequest>,
+) -> Result<Json<DeviceProvisioningResponse>, StatusCode> {
+    if request.serial_number.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let device = upsert_device(&st, &request).await?;
+    let initial_config = initial_config(&st, device.id).await?;
+
+    let issued = device_tokens::issue_device_token(&st, device.id, Some(PROVISIONED_TOKEN_LABEL.into()), None, Utc::now()).await?;
+
+    let cfg = st.cfg.read().await;
+    tracing::info!("Provisioned device {} (serial {})", device.id, device.serial_number);
+    Ok(Json(DeviceProvisioningResponse {
+        device_id: device.id,
+        mqtt_broker: cfg.mqtt_broker_host.clone(),
+        mqtt_port: cfg.mqtt_broker_port,
+        initial_config,
+        token: issued.token,
+        token_expires_at: issued.info.expires_at,
+    }))
+}
+
+/// Seri numarasına göre cihazı bul veya oluştur
+async fn upsert_device(st: &AppState, request: &DeviceProvisioningRequest) -> Result<Device, StatusCode> {
+    let new = Device::new(request);
+
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        // Çakışmada mevcut id korunur; public key verilmemişse eskisi kalır
+        sqlx::query_as::<_, Device>(
+            "INSERT INTO devices (id, serial_number, model, firmware_version, public_key_pem, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $6)
+             ON CONFLICT (serial_number) DO UPDATE SET
+                model = EXCLUDED.model,
+                firmware_version = EXCLUDED.firmware_version,
+                public_key_pem = COALESCE(EXCLUDED.public_key_pem, devices.public_key_pem),
+                updated_at = NOW()
+             RETURNING id, serial_number, model, firmware_version, public_key_pem, created_at, updated_at"
+        )
+        .bind(new.id)
+        .bind(&new.serial_number)
+        .bind(&new.model)
+        .bind(&new.firmware_version)
+        .bind(&new.public_key_pem)
+        .bind(new.created_at)
+        .fetch_one(db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Device upsert failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut map = st.device_store.write().await;
+        if let Some(device) = map.values_mut().find(|d| d.serial_number == new.serial_number) {
+            device.update(request);
+            return Ok(device.clone());
+        }
+        map.insert(new.id, new.clone());
+        Ok(new)
+    }
+}
+
+/// Cihaz gölgesinin `desired` bölümünden başlangıç ayarları
+async fn initial_config(st: &AppState, device_id: Uuid) -> Result<RemoteConfig, StatusCode> {
+    let desired = if let Some(db) = &st.pool().await {
+        sqlx::query_scalar::<_, Value>("SELECT desired FROM device_shadows WHERE device_id = $1")
+            .bind(device_id)
+            .fetch_optional(db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    } else {
+        st.shadow_store.read().await.get(&device_id).map(|s| s.desired.clone())
+    };
+    Ok(desired.map(|d| RemoteConfig::from_desired(&d)).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    fn request(serial: &str) -> DeviceProvisioningRequest {
+        DeviceProvisioningRequest {
+            serial_number: serial.into(),
+            firmware_version: "0.1.0".into(),
+            model: "Raspberry Pi 4 Model B Rev 1.4".into(),
+            public_key_pem: None,
+        }
+    }
+
+    async fn provision(st: &AppState, request: DeviceProvisioningRequest) -> DeviceProvisioningResponse {
+        provision_device(State(st.clone()), Json(request)).await.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn test_repeat_provisioning_returns_same_device_id() {
+        let st = AppState::for_tests();
+        {
+            let mut cfg = st.cfg.write().await;
+            cfg.mqtt_broker_host = "mqtt.local".into();
+            cfg.mqtt_broker_port = 1883;
+        }
+
+        let first = provision(&st, request("10000000abcdef01")).await;
+        assert_eq!((first.mqtt_broker.as_str(), first.mqtt_port), ("mqtt.local", 1883));
+        assert_eq!(first.initial_config, RemoteConfig::default());
+        assert_eq!(first.token_expires_at, None);
+        let now = Utc::now();
+        assert_eq!(device_tokens::authenticate(&st, &first.token, now).await, Some(first.device_id));
+
+        // Aynı seri numarası (firmware güncellenmiş): aynı cihaz, gölge ayarlarıyla
+        st.shadow_store
+            .write()
+            .await
+            .entry(first.device_id)
+            .or_insert_with(|| shared_types::DeviceShadow::new(first.device_id))
+            .update_desired(&serde_json::json!({"sensor_interval_secs": 10}));
+        let again = provision(&st, DeviceProvisioningRequest { firmware_version: "0.2.0".into(), ..request(" 10000000abcdef01 ") }).await;
+        assert_eq!(again.device_id, first.device_id);
+        assert_eq!(again.initial_config.sensor_interval_secs, Some(10));
+        assert_eq!(st.device_store.read().await[&first.device_id].firmware_version, "0.2.0");
+
+        let other = provision(&st, request("10000000abcdef02")).await;
+        assert_ne!(other.device_id, first.device_id);
+        assert_eq!(st.device_store.read().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_provision_rejects_blank_serial() {
+        let result = provision_device(State(AppState::for_tests()), Json(request("  "))).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_repeat_provisioning_returns_same_device_id_in_db() {
+        let st = AppState::for_tests_with_db(test_db().await);
+        let serial = format!("serial-{}", Uuid::new_v4().simple());
+
+        let first = provision(&st, DeviceProvisioningRequest { public_key_pem: Some("PEM".into()), ..request(&serial) }).await;
+        let again = provision(&st, DeviceProvisioningRequest { firmware_version: "0.2.0".into(), ..request(&serial) }).await;
+        assert_eq!(again.device_id, first.device_id);
+
+        let (firmware, key): (String, Option<String>) =
+            sqlx::query_as("SELECT firmware_version, public_key_pem FROM devices WHERE id = $1")
+                .bind(first.device_id)
+                .fetch_one(&st.pool().await.unwrap())
+                .await
+                .unwrap();
+        assert_eq!((firmware.as_str(), key.as_deref()), ("0.2.0", Some("PEM")));
+    }
+}
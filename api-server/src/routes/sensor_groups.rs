@@ -0,0 +1,457 @@
+//! Sensör Grubu Endpoint'leri
+//!
+//! Hangi cihaza bağlı olduklarından bağımsız olarak sensörleri mantıksal
+//! gruplarda toplar (ör. tüm "bedroom" sensörleri). Okumalar
+//! `device_id` + `sensor_type` ile saklandığından üye ID'leri
+//! `Sensor::id_for` ile bu çiftten türetilir; üyeler ID veya çift olarak
+//! verilebilir.
+//!
+//! # Endpoint'ler
+//! - POST /v1/sensor-groups - Yeni grup oluştur (admin)
+//! - GET /v1/sensor-groups - Grupları listele
+//! - GET /v1/sensor-groups/{id} - Tek grup
+//! - DELETE /v1/sensor-groups/{id} - Grubu sil (admin)
+//! - PUT /v1/sensor-groups/{id}/members - Üye listesini değiştir (admin)
+//! - GET /v1/sensor-groups/{id}/readings/latest - Üyelerin son okumaları + ortalama
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::SensorReadingRow;
+use crate::routes::sensors::get_all_sensors_from_redis;
+use crate::state::AppState;
+use shared_types::{NewSensorGroup, Sensor, SensorGroup, SensorGroupMember, SensorGroupReadings, SensorReading};
+
+/// Bir gruptaki maksimum üye sayısı
+pub const MAX_GROUP_MEMBERS: usize = 500;
+
+/// Grup + üyeleri (sıralı) tek satırda
+const GROUP_SQL: &str = "SELECT g.id, g.name, g.description,
+            COALESCE(array_agg(m.sensor_id ORDER BY m.sensor_id) FILTER (WHERE m.sensor_id IS NOT NULL), '{}') AS sensor_ids
+     FROM sensor_groups g
+     LEFT JOIN sensor_group_members m ON m.group_id = g.id";
+
+/// Yeni sensör grubu oluştur
+///
+/// # HTTP
+/// `POST /v1/sensor-groups`
+///
+/// # Request Body
+/// ```json
+/// {"name": "bedroom", "description": "Yatak odasındaki tüm sensörler"}
+/// ```
+///
+/// # Response (201 Created)
+/// Üyesiz `SensorGroup`; üyeler `PUT /v1/sensor-groups/{id}/members` ile eklenir.
+///
+/// # Error Responses
+/// - 400 Bad Request: `name` boş
+/// - 500 Internal Server Error: Database hatası
+pub async fn create_sensor_group(
+    State(st): State<AppState>,
+    Json(body): Json<NewSensorGroup>,
+) -> Result<(StatusCode, Json<SensorGroup>), StatusCode> {
+    let name = body.name.trim().to_string();
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let group = SensorGroup { id: Uuid::new_v4(), name, description: body.description.trim().to_string(), sensor_ids: vec![] };
+
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        sqlx::query("INSERT INTO sensor_groups (id, name, description) VALUES ($1, $2, $3)")
+            .bind(group.id)
+            .bind(&group.name)
+            .bind(&group.description)
+            .execute(db)
+            .await
+            .map_err(|e| {
+                tracing::error!("Sensor group insert failed: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    } else {
+        // ===== In-Memory Fallback =====
+        st.sensor_groups.write().await.insert(group.id, group.clone());
+    }
+
+    tracing::info!("Created sensor group {} ({})", group.id, group.name);
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/// Grupları isme göre sıralı listele
+///
+/// # HTTP
+/// `GET /v1/sensor-groups`
+pub async fn list_sensor_groups(State(st): State<AppState>) -> Result<Json<Vec<SensorGroup>>, StatusCode> {
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        sqlx::query_as::<_, SensorGroup>(&format!("{GROUP_SQL} GROUP BY g.id ORDER BY g.name, g.id"))
+            .fetch_all(db)
+            .await
+            .map(Json)
+            .map_err(|e| {
+                tracing::error!("Sensor group list failed: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut groups: Vec<SensorGroup> = st.sensor_groups.read().await.values().cloned().collect();
+        groups.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        Ok(Json(groups))
+    }
+}
+
+/// Tek grubu döndür
+///
+/// # HTTP
+/// `GET /v1/sensor-groups/{id}`
+///
+/// # Error Responses
+/// - 404 Not Found: Grup yok
+pub async fn get_sensor_group(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SensorGroup>, StatusCode> {
+    find_group(&st, id).await?.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Grubu (ve üyeliklerini) sil
+///
+/// # HTTP
+/// `DELETE /v1/sensor-groups/{id}`
+///
+/// # Error Responses
+/// - 404 Not Found: Grup yok
+pub async fn delete_sensor_group(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let deleted = if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu (üyeler ON DELETE CASCADE ile silinir) =====
+        sqlx::query("DELETE FROM sensor_groups WHERE id = $1")
+            .bind(id)
+            .execute(db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .rows_affected()
+            > 0
+    } else {
+        // ===== In-Memory Fallback =====
+        st.sensor_groups.write().await.remove(&id).is_some()
+    };
+
+    if !deleted {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    tracing::info!("Deleted sensor group {id}");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Grubun üye listesini değiştir
+///
+/// # HTTP
+/// `PUT /v1/sensor-groups/{id}/members`
+///
+/// # Request Body
+/// Üyelerin JSON array'i; mevcut üyelerin yerini alır (ekleme ve çıkarma
+/// aynı istekle yapılır, boş array tüm üyeleri çıkarır). Her üye sensör
+/// ID'si veya `{device_id, sensor_type}` çifti olabilir (`SensorGroupMember`):
+/// ```json
+/// ["550e8400-e29b-41d4-a716-446655440001", {"device_id": "edge-agent-001", "sensor_type": "humidity"}]
+/// ```
+///
+/// # Response (200 OK)
+/// Güncel `SensorGroup` (üyeler sıralı, tekrarsız)
+///
+/// # Error Responses
+/// - 400 Bad Request: `MAX_GROUP_MEMBERS`'tan fazla üye
+/// - 404 Not Found: Grup yok
+/// - 500 Internal Server Error: Database hatası
+pub async fn set_sensor_group_members(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(members): Json<Vec<SensorGroupMember>>,
+) -> Result<Json<SensorGroup>, StatusCode> {
+    if members.len() > MAX_GROUP_MEMBERS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let sensor_ids: Vec<Uuid> = members.iter().map(SensorGroupMember::sensor_id).collect();
+
+    let group = if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        let mut group = find_group_in_db(db, id).await?.ok_or(StatusCode::NOT_FOUND)?;
+        group.set_members(sensor_ids);
+        replace_members(db, &group).await.map_err(|e| {
+            tracing::error!("Updating members of sensor group {id} failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        group
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut groups = st.sensor_groups.write().await;
+        let group = groups.get_mut(&id).ok_or(StatusCode::NOT_FOUND)?;
+        group.set_members(sensor_ids);
+        group.clone()
+    };
+
+    tracing::info!("Sensor group {id} now has {} members", group.sensor_ids.len());
+    Ok(Json(group))
+}
+
+/// Grubun üye sensörlerinin son okumaları
+///
+/// # HTTP
+/// `GET /v1/sensor-groups/{id}/readings/latest`
+///
+/// Son değerler Redis'ten (`GET /api/sensors` ile aynı kaynak), Redis yoksa
+/// `sensor_readings` tablosundan okunur. Her okumanın `metadata`'sında
+/// sensörün `device_id`, `sensor_type` ve `unit`'i bulunur.
+///
+/// # Response (200 OK)
+/// ```json
+/// {
+///   "group_id": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+///   "readings": [
+///     {
+///       "sensor_id": "550e8400-e29b-41d4-a716-446655440001",
+///       "value": "22.5",
+///       "timestamp": "2024-11-13T21:30:00Z",
+///       "is_valid": true,
+///       "metadata": {"device_id": "edge-agent-001", "sensor_type": "temperature", "unit": "°C"}
+///     }
+///   ],
+///   "missing": [],
+///   "average": 22.5
+/// }
+/// ```
+///
+/// # Error Responses
+/// - 404 Not Found: Grup yok
+/// - 500 Internal Server Error: Database hatası
+pub async fn latest_sensor_group_readings(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SensorGroupReadings>, StatusCode> {
+    let group = find_group(&st, id).await?.ok_or(StatusCode::NOT_FOUND)?;
+    let latest = latest_readings(&st).await?;
+
+    let mut by_id: HashMap<Uuid, SensorReading> =
+        latest.into_iter().filter(|r| group.contains(r.sensor_id)).map(|r| (r.sensor_id, r)).collect();
+    let average = group.average_reading(&by_id);
+    let (mut readings, mut missing) = (Vec::new(), Vec::new());
+    for sensor_id in &group.sensor_ids {
+        match by_id.remove(sensor_id) {
+            Some(reading) => readings.push(reading),
+            None => missing.push(*sensor_id),
+        }
+    }
+
+    Ok(Json(SensorGroupReadings { group_id: group.id, readings, missing, average }))
+}
+
+/// Grubu DB'den veya in-memory store'dan bul
+async fn find_group(st: &AppState, id: Uuid) -> Result<Option<SensorGroup>, StatusCode> {
+    if let Some(db) = &st.pool().await {
+        find_group_in_db(db, id).await
+    } else {
+        Ok(st.sensor_groups.read().await.get(&id).cloned())
+    }
+}
+
+async fn find_group_in_db(db: &PgPool, id: Uuid) -> Result<Option<SensorGroup>, StatusCode> {
+    sqlx::query_as::<_, SensorGroup>(&format!("{GROUP_SQL} WHERE g.id = $1 GROUP BY g.id"))
+        .bind(id)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Sensor group query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Üyeleri tek transaction'da değiştir
+async fn replace_members(db: &PgPool, group: &SensorGroup) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    sqlx::query("DELETE FROM sensor_group_members WHERE group_id = $1")
+        .bind(group.id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT INTO sensor_group_members (group_id, sensor_id) SELECT $1, UNNEST($2::uuid[])")
+        .bind(group.id)
+        .bind(&group.sensor_ids)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+/// Tüm sensörlerin son okumaları (`sensor_id` = `Sensor::id_for`)
+///
+/// Redis varsa oradan, yoksa `sensor_readings`'ten (her çiftin en yeni
+/// satırı) okunur; ikisi de yoksa boş döner.
+async fn latest_readings(st: &AppState) -> Result<Vec<SensorReading>, StatusCode> {
+    if let Some(mut redis_conn) = st.redis.clone() {
+        let sensors = get_all_sensors_from_redis(&mut redis_conn).await.map_err(|e| {
+            tracing::error!("Redis read error: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        return Ok(sensors
+            .into_iter()
+            .filter_map(|s| {
+                let timestamp = DateTime::parse_from_rfc3339(&s.timestamp).ok()?.with_timezone(&Utc);
+                Some(to_reading(&s.device_id, &s.sensor_type, s.value, &s.unit, timestamp))
+            })
+            .collect());
+    }
+
+    let Some(db) = st.pool().await else {
+        return Ok(vec![]);
+    };
+    let rows = sqlx::query_as::<_, SensorReadingRow>(
+        "SELECT DISTINCT ON (device_id, sensor_type) device_id, sensor_type, value, unit, timestamp, metadata, received_at, gateway_received_at
+         FROM sensor_readings
+         ORDER BY device_id, sensor_type, timestamp DESC",
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Latest readings query failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(rows.iter().map(|r| to_reading(&r.device_id, &r.sensor_type, r.value, &r.unit, r.timestamp)).collect())
+}
+
+fn to_reading(device_id: &str, sensor_type: &str, value: f64, unit: &str, timestamp: DateTime<Utc>) -> SensorReading {
+    SensorReading {
+        sensor_id: Sensor::id_for(device_id, sensor_type),
+        value: value.to_string(),
+        timestamp,
+        is_valid: true,
+        metadata: Some(serde_json::json!({"device_id": device_id, "sensor_type": sensor_type, "unit": unit})),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create(st: &AppState, name: &str) -> SensorGroup {
+        let body = NewSensorGroup { name: name.into(), description: "test".into() };
+        let (status, Json(group)) = create_sensor_group(State(st.clone()), Json(body)).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        group
+    }
+
+    async fn check_group_lifecycle(st: &AppState) {
+        let name = format!("bedroom-{}", Uuid::new_v4());
+        let group = create(st, &name).await;
+        assert_eq!(group.name, name);
+        assert!(group.sensor_ids.is_empty());
+
+        let blank = NewSensorGroup { name: "  ".into(), description: String::new() };
+        assert_eq!(create_sensor_group(State(st.clone()), Json(blank)).await.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        // Üye ekleme (tekrarlar atılır) ve çıkarma
+        let (a, b) = (Sensor::id_for("edge-a", "temperature"), Sensor::id_for("edge-b", "temperature"));
+        let members = |ids: Vec<SensorGroupMember>| set_sensor_group_members(State(st.clone()), Path(group.id), Json(ids));
+        let Json(updated) = members(vec![b.into(), a.into(), b.into()]).await.unwrap();
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(updated.sensor_ids, expected);
+        // Okumaların cihaz + sensör tipi çiftiyle de eklenebilir
+        let pair = SensorGroupMember::Sensor { device_id: "edge-a".into(), sensor_type: "temperature".into() };
+        let Json(updated) = members(vec![pair, b.into()]).await.unwrap();
+        assert_eq!(updated.sensor_ids, expected);
+        let Json(updated) = members(vec![b.into()]).await.unwrap();
+        assert_eq!(updated.sensor_ids, vec![b]);
+
+        let Json(found) = get_sensor_group(State(st.clone()), Path(group.id)).await.unwrap();
+        assert_eq!(found.sensor_ids, vec![b]);
+        let Json(all) = list_sensor_groups(State(st.clone())).await.unwrap();
+        assert!(all.iter().any(|g| g.id == group.id && g.sensor_ids == vec![b]));
+
+        let too_many = vec![a.into(); MAX_GROUP_MEMBERS + 1];
+        assert_eq!(members(too_many).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        let unknown = set_sensor_group_members(State(st.clone()), Path(Uuid::new_v4()), Json(vec![a.into()])).await;
+        assert_eq!(unknown.unwrap_err(), StatusCode::NOT_FOUND);
+
+        assert_eq!(delete_sensor_group(State(st.clone()), Path(group.id)).await.unwrap(), StatusCode::NO_CONTENT);
+        assert_eq!(delete_sensor_group(State(st.clone()), Path(group.id)).await.unwrap_err(), StatusCode::NOT_FOUND);
+        assert_eq!(get_sensor_group(State(st.clone()), Path(group.id)).await.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_group_lifecycle_in_memory() {
+        check_group_lifecycle(&AppState::for_tests()).await;
+    }
+
+    #[tokio::test]
+    async fn test_latest_readings_without_storage_reports_missing() {
+        let st = AppState::for_tests();
+        let group = create(&st, "kitchen").await;
+        let sensor = Sensor::id_for("edge-a", "temperature");
+        let _ = set_sensor_group_members(State(st.clone()), Path(group.id), Json(vec![sensor.into()])).await.unwrap();
+
+        let Json(latest) = latest_sensor_group_readings(State(st.clone()), Path(group.id)).await.unwrap();
+        assert!(latest.readings.is_empty());
+        assert_eq!(latest.missing, vec![sensor]);
+        assert_eq!(latest.average, None);
+
+        let unknown = latest_sensor_group_readings(State(st), Path(Uuid::new_v4())).await;
+        assert_eq!(unknown.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_group_lifecycle_with_database() {
+        check_group_lifecycle(&AppState::for_tests_with_db(test_db().await)).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_latest_readings_with_database() {
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        let (device_a, device_b) = (format!("group-{}", Uuid::new_v4()), format!("group-{}", Uuid::new_v4()));
+        let now = Utc::now();
+        for (device, value, age) in [(&device_a, 19.0, 60), (&device_a, 20.0, 0), (&device_b, 23.0, 0)] {
+            sqlx::query("INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp) VALUES ($1, 'temperature', $2, '°C', $3)")
+                .bind(device)
+                .bind(value)
+                .bind(now - chrono::Duration::seconds(age))
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+
+        let group = create(&st, "bedroom").await;
+        let (a, b) = (Sensor::id_for(&device_a, "temperature"), Sensor::id_for(&device_b, "temperature"));
+        let silent = Sensor::id_for(&device_b, "humidity");
+        let _ = set_sensor_group_members(State(st.clone()), Path(group.id), Json(vec![a.into(), b.into(), silent.into()])).await.unwrap();
+
+        let Json(latest) = latest_sensor_group_readings(State(st.clone()), Path(group.id)).await.unwrap();
+        let values: HashMap<Uuid, &str> = latest.readings.iter().map(|r| (r.sensor_id, r.value.as_str())).collect();
+        assert_eq!(values, HashMap::from([(a, "20"), (b, "23")]));
+        assert_eq!(latest.missing, vec![silent]);
+        assert_eq!(latest.average, Some(21.5));
+
+        delete_sensor_group(State(st), Path(group.id)).await.unwrap();
+        sqlx::query("DELETE FROM sensor_readings WHERE device_id = ANY($1)")
+            .bind(vec![device_a, device_b])
+            .execute(&db)
+            .await
+            .unwrap();
+    }
+}
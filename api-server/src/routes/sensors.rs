@@ -1,30 +1,514 @@
-/// Sensör endpoint'leri
-/// 
-/// MQTT gateway'den gelen sensör verilerini Redis'te cache'leyip web dashboard'a sunar.
-/// Redis bağlantısı yoksa in-memory HashMap fallback kullanır.
+//! Sensör endpoint'leri
+//! 
+//! MQTT gateway'den gelen sensör verilerini Redis'te cache'leyip web dashboard'a sunar.
+//! Redis bağlantısı yoksa in-memory HashMap fallback kullanır.
+//!
+//! PostgreSQL bağlıysa her okuma `sensor_readings` tablosuna da yazılır
+//! (history ve export endpoint'leri için).
 
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::Json,
+    body::Body,
+    extract::{ConnectInfo, Path, Query, State},
+    Extension,
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
 };
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use serde::{Deserialize, Serialize};
 use redis::AsyncCommands;
+use sqlx::PgPool;
+use shared_types::conventions::{redis_device_pattern, redis_last_seen_key, redis_sensor_key, REDIS_SENSOR_PREFIX, REDIS_SENSOR_TTL_SECS};
+use shared_types::{
+    alert::check_threshold, sensor::normalize_unit, unit, AlertThreshold, HeatmapParams, HeatmapResponse, IngestResult, ReplayRequest, ReplayResult, SensorAlert, SensorReadingValidator, SensorStats,
+    SensorSummary, SensorType, Unit,
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use tokio::time::Duration;
+use crate::db::{SensorReadingRow, SensorReadingStream};
+use crate::realtime::SensorEvent;
+use crate::routes;
+use crate::timeseries::{self, FillMethod, Filled};
+use crate::event_sink::{self, IngestEvent};
+use crate::feature_flags::check_feature;
+use crate::pubsub;
+use crate::auth::DeviceBinding;
+use crate::config::Config;
+use crate::msgpack::JsonOrMsgPack;
 use crate::state::AppState;
 
 /// Sensör verisi - Dashboard'a gönderilen format
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SensorData {
     pub device_id: String,
     pub sensor_type: String,
     pub value: f64,
     pub unit: String,
+    /// Cihazın bildirdiği ölçüm zamanı (RFC 3339)
+    /// 
+    /// Gönderilmezse veya `MAX_FUTURE_SKEW_SECS`'ten fazla ileriyse
+    /// `received_at` ile değiştirilir (bkz. `TimestampGuard`).
+    #[serde(default)]
     pub timestamp: String,
     pub metadata: Option<serde_json::Value>,
+    /// Sunucunun okumayı aldığı zaman (RFC 3339, istemcinin gönderdiği değer yok sayılır)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<String>,
+    /// MQTT gateway'in mesajı aldığı zaman (RFC 3339, gateway doldurur)
+    /// 
+    /// `timestamp` ile farkı cihazın saat kaymasını, `received_at` ile farkı
+    /// gateway → API gecikmesini gösterir. Gönderilmezse veya parse
+    /// edilemezse `None` saklanır; okuma reddedilmez.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway_received_at: Option<String>,
+    /// Son okuma `stale_threshold_secs`'ten eski mi? (`GET /api/sensors` hesaplar)
+    /// 
+    /// Sadece `true` iken serialize edilir; pub/sub, event sink ve Redis
+    /// payload'ları değişmez. İstemcinin gönderdiği değer yok sayılır.
+    #[serde(default, skip_deserializing, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+}
+
+/// `POST /v1/sensors/ingest` isteğindeki maksimum okuma sayısı
+pub const INGEST_MAX_ITEMS: usize = 100;
+
+/// `POST /v1/sensors/ingest` için IP başına saniyedeki maksimum istek
+pub const INGEST_RATE_LIMIT: u32 = 10;
+
+/// Line protocol export'unda bir chunk'taki satır sayısı
+const EXPORT_CHUNK_ROWS: usize = 1000;
+
+/// Realtime long-poll'un en fazla bekleme süresi
+const REALTIME_WAIT: Duration = Duration::from_secs(2);
+
+/// Realtime long-poll'da tek seferde dönen maksimum okuma
+const REALTIME_BATCH_LIMIT: usize = 50;
+
+/// SSE akışında okuma gelmezse gönderilen keep-alive yorumunun aralığı
+const STREAM_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// History sorgusu varsayılan satır limiti
+const DEFAULT_HISTORY_LIMIT: i64 = 1000;
+
+/// Downsampling'in varsayılan hedef nokta sayısı
+const DEFAULT_DOWNSAMPLE_POINTS: usize = 500;
+
+/// Downsampling için belleğe alınabilecek en fazla okuma (`?limit=`)
+pub const MAX_DOWNSAMPLE_SOURCE: i64 = 100_000;
+
+/// `?fill=` ile bir yanıta eklenebilecek en fazla nokta
+pub const MAX_FILL_POINTS: u64 = 10_000;
+
+/// `POST /v1/sensors/replay` aralığının en fazla uzunluğu
+pub const REPLAY_MAX_RANGE: chrono::TimeDelta = chrono::TimeDelta::days(7);
+
+/// `POST /v1/sensors/replay`'in işleyeceği en fazla okuma
+pub const REPLAY_MAX_READINGS: u64 = 100_000;
+
+/// Aynı (cihaz, sensör, eşik) için iki alarm arasındaki en kısa süre
+pub const ALERT_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Bu kadar kayıttan sonra süresi dolan cooldown'lar temizlenir
+const ALERT_COOLDOWN_PRUNE_AT: usize = 10_000;
+
+/// Cooldown anahtarı: (cihaz, sensör tipi, eşik sınırlarının bitleri)
+type CooldownKey = (String, String, [Option<u64>; 2]);
+
+/// Alarm cooldown'ları: eşiğin üstünde kalan sensör her okumada alarm üretmez
+///
+/// Anahtar (cihaz, sensör tipi, eşik sınırlarının bitleri); eşik yeniden
+/// yüklemeyle değişirse yeni eşik hemen alarm üretebilir.
+#[derive(Debug, Default)]
+pub struct AlertCooldowns {
+    last_fired: std::sync::Mutex<HashMap<CooldownKey, std::time::Instant>>,
+}
+
+impl AlertCooldowns {
+    /// Cooldown dışındaysa `now`'ı kaydet ve `true` döndür
+    pub fn try_fire(&self, device_id: &str, sensor_type: &str, threshold: &AlertThreshold, now: std::time::Instant) -> bool {
+        let bits = [threshold.min.map(f64::to_bits), threshold.max.map(f64::to_bits)];
+        let key = (device_id.to_string(), sensor_type.to_string(), bits);
+        let mut last_fired = self.last_fired.lock().unwrap_or_else(|e| e.into_inner());
+        if last_fired.get(&key).is_some_and(|at| now.duration_since(*at) < ALERT_COOLDOWN) {
+            return false;
+        }
+        if last_fired.len() >= ALERT_COOLDOWN_PRUNE_AT {
+            last_fired.retain(|_, at| now.duration_since(*at) < ALERT_COOLDOWN);
+        }
+        last_fired.insert(key, now);
+        true
+    }
+}
+
+/// Özet sorgusu - saatlik özetleri istenen çözünürlükte birleştirir
+/// 
+/// `$3` date_trunc birimi ('hour' / 'day'). Ortalama, okuma sayısıyla
+/// ağırlıklandırılır; saatlik çözünürlükte satırlar aynen döner.
+const SUMMARIES_SQL: &str = "SELECT device_id, sensor_type,
+            date_trunc($3, bucket_start, 'UTC') AS bucket_start,
+            min(min_value) AS min_value,
+            max(max_value) AS max_value,
+            sum(avg_value * count) / sum(count) AS avg_value,
+            sum(count)::BIGINT AS count
+     FROM sensor_summaries
+     WHERE device_id = $1 AND sensor_type = $2
+       AND ($4::timestamptz IS NULL OR bucket_start >= $4)
+       AND ($5::timestamptz IS NULL OR bucket_start <= $5)
+     GROUP BY 1, 2, 3
+     ORDER BY 3 ASC";
+
+/// Heatmap hücreleri: epoch'a hizalı bucket × sensör tipi başına ortalama
+/// (boş hücreler dönmez, matris `HeatmapResponse::from_cells` ile doldurulur)
+const HEATMAP_SQL: &str = "SELECT to_timestamp(floor(extract(epoch FROM timestamp) / $5) * $5) AS bucket,
+            sensor_type,
+            avg(value) AS value
+     FROM sensor_readings
+     WHERE device_id = $1 AND sensor_type = ANY($2)
+       AND timestamp >= $3 AND timestamp <= $4
+     GROUP BY 1, 2";
+
+impl From<SensorReadingRow> for SensorData {
+    fn from(row: SensorReadingRow) -> Self {
+        Self {
+            device_id: row.device_id,
+            sensor_type: row.sensor_type,
+            value: row.value,
+            unit: row.unit,
+            timestamp: row.timestamp.to_rfc3339(),
+            metadata: row.metadata,
+            received_at: row.received_at.map(|t| t.to_rfc3339()),
+            gateway_received_at: row.gateway_received_at.map(|t| t.to_rfc3339()),
+            stale: false,
+        }
+    }
+}
+
+/// Okuma zaman damgası kontrolü (`MAX_FUTURE_SKEW_SECS`, `MAX_READING_AGE_DAYS`)
+/// 
+/// Saati bozuk cihazlar 1970 veya 2099 tarihli okumalar gönderebilir; bunlar
+/// history sorgularını ve retention'ı bozar.
+/// - `timestamp` yoksa veya `max_future_skew`'den fazla ileriyse sunucunun
+///   alış zamanı kullanılır (clamp)
+/// - `max_age`'den eskiyse okuma reddedilir
+/// - Parse edilemiyorsa okuma reddedilir
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampGuard {
+    pub max_future_skew: chrono::TimeDelta,
+    pub max_age: chrono::TimeDelta,
+}
+
+impl TimestampGuard {
+    /// Yapılandırmadaki sınırlar
+    pub fn from_config(cfg: &Config) -> Self {
+        Self {
+            max_future_skew: i64::try_from(cfg.max_future_skew_secs)
+                .ok()
+                .and_then(chrono::TimeDelta::try_seconds)
+                .unwrap_or(chrono::TimeDelta::MAX),
+            max_age: i64::try_from(cfg.max_reading_age_days)
+                .ok()
+                .and_then(chrono::TimeDelta::try_days)
+                .unwrap_or(chrono::TimeDelta::MAX),
+        }
+    }
+
+    /// `received_at`'i yaz ve `timestamp`'i kontrol et
+    /// 
+    /// `timestamp` alış zamanıyla değiştirildiyse `Ok(true)` döner.
+    pub fn apply(&self, data: &mut SensorData, received_at: DateTime<Utc>) -> Result<bool, String> {
+        data.received_at = Some(received_at.to_rfc3339());
+        if data.timestamp.trim().is_empty() {
+            data.timestamp = received_at.to_rfc3339();
+            return Ok(true);
+        }
+
+        let timestamp = DateTime::parse_from_rfc3339(&data.timestamp)
+            .map_err(|_| format!("invalid timestamp: {}", data.timestamp))?
+            .with_timezone(&Utc);
+        // Sınır tarih aralığının dışına taşıyorsa (ör. `TimeDelta::MAX`) o yön sınırsızdır
+        if received_at.checked_sub_signed(self.max_age).is_some_and(|oldest| timestamp < oldest) {
+            return Err(format!("timestamp older than retention window: {}", data.timestamp));
+        }
+        if received_at.checked_add_signed(self.max_future_skew).is_some_and(|latest| timestamp > latest) {
+            tracing::warn!(
+                device_id = %data.device_id,
+                "Reading timestamp {} is in the future, clamped to receive time", data.timestamp
+            );
+            data.timestamp = received_at.to_rfc3339();
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+/// History/export sorguları için zaman aralığı parametreleri
+/// 
+/// Örnek: `?from=2024-01-20T00:00:00Z&to=2024-01-21T00:00:00Z&limit=500`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TimeRangeParams {
+    /// Başlangıç zamanı (dahil)
+    pub from: Option<DateTime<Utc>>,
+    /// Bitiş zamanı (dahil)
+    pub to: Option<DateTime<Utc>>,
+    /// Maksimum satır sayısı (history varsayılanı: 1000, export: sınırsız)
+    pub limit: Option<i64>,
+}
+
+/// Özet çözünürlüğü (`?resolution=1h|1d`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum SummaryResolution {
+    /// Saatlik (tablonun kendi çözünürlüğü)
+    #[default]
+    #[serde(rename = "1h")]
+    Hour,
+    /// Günlük (UTC gün başı)
+    #[serde(rename = "1d")]
+    Day,
+}
+
+impl SummaryResolution {
+    /// PostgreSQL `date_trunc` birimi
+    fn date_trunc_unit(self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+        }
+    }
+
+    /// Bucket uzunluğu (`?fill=` için beklenen aralık)
+    fn bucket(self) -> chrono::TimeDelta {
+        match self {
+            Self::Hour => chrono::TimeDelta::hours(1),
+            Self::Day => chrono::TimeDelta::days(1),
+        }
+    }
+}
+
+/// Özet sorgusu parametreleri
+/// 
+/// Örnek: `?resolution=1d&from=2024-01-01T00:00:00Z&to=2024-01-31T00:00:00Z`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SummaryParams {
+    #[serde(default)]
+    pub resolution: SummaryResolution,
+    /// İlk bucket başlangıcı (dahil)
+    pub from: Option<DateTime<Utc>>,
+    /// Son bucket başlangıcı (dahil)
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Downsampling yöntemi (`?downsample=lttb`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownsampleMethod {
+    /// Largest-Triangle-Three-Buckets (bkz. `timeseries::lttb`)
+    Lttb,
+}
+
+/// History downsampling parametreleri
+/// 
+/// Örnek: `?downsample=lttb&points=500`
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct DownsampleParams {
+    /// Yoksa okumalar olduğu gibi döner
+    pub downsample: Option<DownsampleMethod>,
+    /// Hedef nokta sayısı (varsayılan 500)
+    pub points: Option<usize>,
 }
 
-/// Redis key prefix - Tüm sensor key'leri bu prefix ile başlar
-const REDIS_KEY_PREFIX: &str = "sensor:";
+/// Downsample edilmiş history yanıtı
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownsampledHistory {
+    /// Seçilen okumalar (zamana göre artan)
+    pub readings: Vec<SensorData>,
+    /// Sorgudan gelen okuma sayısı (atlananlar dahil)
+    pub source_points: usize,
+    /// Sayısal olmayan değerleri (NaN, sonsuz) yüzünden atlanan okumalar
+    pub skipped_points: usize,
+}
+
+/// Boşluk doldurma parametreleri (history ve summaries)
+/// 
+/// Örnek: `?fill=linear&interval=60&max_gap=3600`
+/// 
+/// `interval` ve `max_gap` saniyedir. `interval` verilmezse history'de
+/// ardışık okumalar arasındaki farkların medyanı, summaries'te çözünürlük
+/// kullanılır. `max_gap`'ten uzun boşluklar boş kalır (bkz. `timeseries::fill_gaps`).
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct FillParams {
+    #[serde(default)]
+    pub fill: FillMethod,
+    pub interval: Option<u64>,
+    pub max_gap: Option<u64>,
+}
+
+impl FillParams {
+    /// Açık verilmiş aralık ve en uzun boşluk (ms); 0 aralık 400
+    fn millis(&self) -> Result<(Option<i64>, Option<i64>), StatusCode> {
+        let millis = |secs: u64| i64::try_from(secs.saturating_mul(1000)).unwrap_or(i64::MAX);
+        if self.interval == Some(0) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        Ok((self.interval.map(millis), self.max_gap.map(millis)))
+    }
+}
+
+/// `?fill=` ile dönen history noktası
+/// 
+/// Okumalar `SensorData` olarak aynen döner; eklenen noktalar `filled: true`
+/// taşır ve `fill=null`'da değerleri `null`'dır. Deserialize'da `Gap` önce
+/// denenir: `filled` alanı olmayan JSON okumadır.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum HistoryPoint {
+    Gap(GapReading),
+    Reading(SensorData),
+}
+
+/// Boşluğa eklenen history noktası
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GapReading {
+    pub device_id: String,
+    pub sensor_type: String,
+    pub value: Option<f64>,
+    /// Boşluktan önceki okumanın birimi
+    pub unit: String,
+    pub timestamp: String,
+    pub filled: bool,
+}
+
+/// `?fill=` ile dönen özet bucket'ı (`HistoryPoint` gibi `Gap` önce denenir)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SummaryPoint {
+    Gap(SummaryGap),
+    Summary(SensorSummary),
+}
+
+/// Boşluğa eklenen özet bucket'ı
+/// 
+/// Doldurma ortalamalar üzerinden yapılır; min/max/ortalama aynı değeri
+/// taşır ve `count` 0'dır.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SummaryGap {
+    pub device_id: String,
+    pub sensor_type: String,
+    pub bucket_start: DateTime<Utc>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub avg_value: Option<f64>,
+    pub count: i64,
+    pub filled: bool,
+}
+
+/// Gösterim birimi parametresi (`?unit=fahrenheit`)
+/// 
+/// Değerler sunucuda çevrilir ve `unit` alanı yeni birimle yazılır.
+/// Sembol veya isim kabul edilir (`°F`, `fahrenheit`, `inHg`, `mph`...).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UnitParams {
+    pub unit: Option<String>,
+}
+
+impl UnitParams {
+    /// İstenen birim (yoksa `None`); bilinmeyen birim 400
+    fn target(&self) -> Result<Option<Unit>, StatusCode> {
+        self.unit.as_deref().map(str::parse).transpose().map_err(|_| StatusCode::BAD_REQUEST)
+    }
+}
+
+/// Tek bir sensör serisinin (cihaz + tip) birim dönüşümü
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct UnitConversion {
+    /// Serinin saklanan birimi
+    from: Unit,
+    to: Unit,
+}
+
+impl UnitConversion {
+    /// `?unit=` için serinin dönüşümünü bul
+    /// 
+    /// Saklanan birim serinin son okumasının birimidir (okuma yoksa tipin
+    /// varsayılan birimi). Dönüştürülemeyen birimler (ör. `%` → °F) 400;
+    /// hiç okuma ve varsayılan birim yoksa dönüştürülecek bir şey yoktur.
+    async fn for_series(db: &PgPool, device_id: &str, sensor_type: &str, params: &UnitParams) -> Result<Option<Self>, StatusCode> {
+        let Some(to) = params.target()? else {
+            return Ok(None);
+        };
+        let stored: Option<String> = sqlx::query_scalar(
+            "SELECT unit FROM sensor_readings WHERE device_id = $1 AND sensor_type = $2 ORDER BY timestamp DESC LIMIT 1"
+        )
+        .bind(device_id)
+        .bind(sensor_type)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Sensor unit query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        let stored = stored.unwrap_or_else(|| sensor_type.parse::<SensorType>().map_or("", |t| t.default_unit()).to_string());
+        if stored.is_empty() {
+            return Ok(None);
+        }
+        let from: Unit = normalize_unit(&stored).parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+        unit::convert(0.0, from, to).map_err(|_| StatusCode::BAD_REQUEST)?;
+        Ok(Some(Self { from, to }))
+    }
+
+    /// Okumayı çevir; okumanın kendi birimi tanınmıyorsa serinin birimi varsayılır
+    fn apply(self, data: &mut SensorData) {
+        let from = normalize_unit(&data.unit)
+            .parse::<Unit>()
+            .ok()
+            .filter(|u| u.quantity() == self.to.quantity())
+            .unwrap_or(self.from);
+        if let Ok(value) = unit::convert(data.value, from, self.to) {
+            data.value = value;
+            data.unit = self.to.as_str().to_string();
+        }
+    }
+}
+
+/// Okumayı `to` birimine çevir ve `unit` alanını yeniden yaz
+/// 
+/// Okumanın birimi tanınmıyorsa veya farklı bir büyüklükse hata döner;
+/// okuma değişmez.
+pub fn convert_reading(data: &mut SensorData, to: Unit) -> shared_types::Result<()> {
+    let from: Unit = normalize_unit(&data.unit).parse()?;
+    data.value = unit::convert(data.value, from, to)?;
+    data.unit = to.as_str().to_string();
+    Ok(())
+}
+
+/// Realtime sorgu parametreleri (`?since=42`)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RealtimeParams {
+    /// Client'ın gördüğü son sıra numarası (varsayılan 0: backlog'un tamamı)
+    #[serde(default)]
+    pub since: u64,
+}
+
+/// SSE akışı sorgu parametreleri (`?device_id=edge-agent-001`)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamParams {
+    /// Sadece bu cihazın okumaları (varsayılan: hepsi)
+    pub device_id: Option<String>,
+}
+
+/// Realtime long-poll yanıtı
+#[derive(Debug, Clone, Serialize)]
+pub struct RealtimeBatch {
+    /// `since`'ten sonraki okumalar (sıra numarasına göre artan)
+    pub events: Vec<SensorEvent>,
+    /// Sonraki istekte `since` olarak gönderilecek değer
+    pub next_sequence: u64,
+}
 
 /// Tüm sensör verilerini listele
 /// 
@@ -33,6 +517,16 @@ const REDIS_KEY_PREFIX: &str = "sensor:";
 /// Redis'ten tüm sensor:* key'lerini okur ve JSON array döner.
 /// Redis bağlantısı yoksa boş array döner.
 /// 
+/// `?unit=fahrenheit` ile aynı büyüklükteki okumalar (sıcaklıklar) çevrilir;
+/// liste farklı tipleri karıştırdığından diğerleri (ör. nem) olduğu gibi
+/// döner. Bilinmeyen birim → 400.
+/// 
+/// `timestamp`'i `stale_threshold_secs`'ten eski okumalara `stale: true`
+/// eklenir (sensör veri göndermeyi bırakmış, değer son bilinen değer).
+/// 
+/// `Accept: application/msgpack` ile aynı içerik MessagePack döner
+/// (bkz. `msgpack::with_msgpack_responses`).
+/// 
 /// Response:
 /// ```json
 /// [
@@ -42,17 +536,32 @@ const REDIS_KEY_PREFIX: &str = "sensor:";
 ///     "value": 23.5,
 ///     "unit": "°C",
 ///     "timestamp": "2024-01-20T10:30:00Z",
-///     "metadata": null
+///     "metadata": null,
+///     "stale": true
 ///   }
 /// ]
 /// ```
+#[tracing::instrument(skip(state), err)]
 pub async fn list_sensors(
     State(state): State<AppState>,
+    Query(unit): Query<UnitParams>,
 ) -> Result<Json<Vec<SensorData>>, StatusCode> {
+    let target = unit.target()?;
+    let threshold = state.cfg.read().await.stale_threshold_secs;
+    let stale_after = chrono::TimeDelta::seconds(threshold.min(i64::MAX as u64 / 1000) as i64);
+
     // Redis varsa Redis'ten oku
     if let Some(mut redis_conn) = state.redis.clone() {
         match get_all_sensors_from_redis(&mut redis_conn).await {
-            Ok(sensors) => return Ok(Json(sensors)),
+            Ok(mut sensors) => {
+                if let Some(to) = target {
+                    for sensor in &mut sensors {
+                        let _ = convert_reading(sensor, to);
+                    }
+                }
+                mark_stale(&mut sensors, stale_after, Utc::now());
+                return Ok(Json(sensors));
+            }
             Err(e) => {
                 tracing::warn!("Redis read error: {e}, returning empty list");
                 return Ok(Json(vec![]));
@@ -65,12 +574,21 @@ pub async fn list_sensors(
     Ok(Json(vec![]))
 }
 
+/// Son okumaları yaşlarına göre `stale` işaretle
+/// 
+/// `timestamp`'i okunamayan okumalar bayat sayılmaz (yaşları bilinmiyor).
+fn mark_stale(sensors: &mut [SensorData], max_age: chrono::TimeDelta, now: DateTime<Utc>) {
+    for sensor in sensors {
+        sensor.stale = DateTime::parse_from_rfc3339(&sensor.timestamp).is_ok_and(|at| now - at.with_timezone(&Utc) > max_age);
+    }
+}
+
 /// Redis'ten tüm sensör verilerini oku
-async fn get_all_sensors_from_redis(
+pub(crate) async fn get_all_sensors_from_redis(
     conn: &mut redis::aio::ConnectionManager,
 ) -> Result<Vec<SensorData>, Box<dyn std::error::Error>> {
     // sensor:* pattern'ine uyan tüm key'leri bul
-    let keys: Vec<String> = conn.keys(format!("{}*", REDIS_KEY_PREFIX)).await?;
+    let keys: Vec<String> = conn.keys(format!("{REDIS_SENSOR_PREFIX}*")).await?;
     
     let mut sensors = Vec::new();
     
@@ -85,6 +603,19 @@ async fn get_all_sensors_from_redis(
     Ok(sensors)
 }
 
+/// Cihazın Redis'teki son değerlerini ve son görülme zamanını sil
+/// (`sensor:<device_id>:*`, `device:last_seen:<device_id>`)
+/// 
+/// Silinen key sayısını döner.
+pub(crate) async fn delete_device_sensors_from_redis(
+    conn: &mut redis::aio::ConnectionManager,
+    device_id: &str,
+) -> redis::RedisResult<u64> {
+    let mut keys: Vec<String> = conn.keys(redis_device_pattern(device_id)).await?;
+    keys.push(redis_last_seen_key(device_id));
+    conn.del(keys).await
+}
+
 /// Yeni sensör verisi ekle (MQTT gateway tarafından kullanılır)
 /// 
 /// POST /api/sensors
@@ -93,6 +624,16 @@ async fn get_all_sensors_from_redis(
 /// Key format: "sensor:device_id:sensor_type"
 /// Value: JSON serialized SensorData
 /// 
+/// Kayıttan sonra değer `ALERT_THRESHOLDS` eşiğini aşıyorsa `sensor.alert`
+/// webhook'ları tetiklenir.
+/// 
+/// `timestamp` eksikse veya `MAX_FUTURE_SKEW_SECS`'ten fazla ileriyse
+/// sunucunun alış zamanı kullanılır ve yanıtta `clamped: [0]` döner
+/// (bkz. `TimestampGuard`).
+/// 
+/// Body JSON veya `Content-Type: application/msgpack` ile MessagePack olabilir
+/// (aynı alan adları; bozuk MessagePack 400 problem+json).
+/// 
 /// Body:
 /// ```json
 /// {
@@ -103,37 +644,2039 @@ async fn get_all_sensors_from_redis(
 ///   "timestamp": "2024-01-20T10:30:00Z"
 /// }
 /// ```
+/// 
+/// Response:
+/// ```json
+/// {"accepted": 1, "rejected": [], "clamped": [0]}
+/// ```
+/// 
+/// # Error Responses
+/// - 400 Bad Request: `timestamp` geçersiz veya `MAX_READING_AGE_DAYS`'ten eski;
+///   `STRICT_SENSOR_VALIDATION` açıkken değer veya birim sensör tipine uymuyor
+///   (sebep loglanır)
+/// - 403 Forbidden: Cihaz token'ı başka bir `device_id`'ye ait
+/// - 413 Payload Too Large: `metadata` `MAX_METADATA_BYTES`'tan büyük
+/// - 503 Service Unavailable: Ne Redis ne PostgreSQL bağlı
+#[tracing::instrument(skip(state), err)]
 pub async fn add_sensor_data(
     State(state): State<AppState>,
-    Json(data): Json<SensorData>,
-) -> Result<StatusCode, StatusCode> {
-    // Redis varsa Redis'e yaz
-    if let Some(mut redis_conn) = state.redis.clone() {
-        let key = format!("{}{}:{}", REDIS_KEY_PREFIX, data.device_id, data.sensor_type);
-        
-        match serde_json::to_string(&data) {
-            Ok(json) => {
-                // Redis'e JSON string olarak kaydet
-                // TTL 1 saat (3600 saniye) - eski veriler otomatik silinir
-                match redis_conn.set_ex::<_, _, ()>(&key, json, 3600).await {
-                    Ok(_) => {
-                        tracing::debug!("Sensor data saved to Redis: {key}");
-                        return Ok(StatusCode::OK);
-                    }
-                    Err(e) => {
-                        tracing::error!("Redis write error: {e}");
-                        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-                    }
+    binding: Option<Extension<DeviceBinding>>,
+    JsonOrMsgPack(mut data): JsonOrMsgPack<SensorData>,
+) -> Result<Json<IngestResult>, StatusCode> {
+    DeviceBinding::check(binding.as_deref(), &data.device_id)?;
+    check_metadata_size(&data, state.cfg.read().await.max_metadata_bytes).map_err(|reason| {
+        tracing::warn!(device_id = %data.device_id, "Rejected reading: {reason}");
+        StatusCode::PAYLOAD_TOO_LARGE
+    })?;
+    let guard = TimestampGuard::from_config(&*state.cfg.read().await);
+    let clamped = guard.apply(&mut data, Utc::now()).map_err(|reason| {
+        tracing::warn!(device_id = %data.device_id, "Rejected reading: {reason}");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    // Deprecated birim yazımları (celsius, percent, boolean) standart birime çevrilir
+    data.unit = normalize_unit(&data.unit).to_string();
+
+    if state.cfg.read().await.strict_sensor_validation {
+        check_sensor_type(&data).map_err(|reason| {
+            tracing::warn!(device_id = %data.device_id, "Rejected reading: {reason}");
+            StatusCode::BAD_REQUEST
+        })?;
+    }
+
+    let seen = last_seen_of(&data);
+    store_reading(&state, data).await?;
+    routes::devices::record_last_seen(&state, seen).await;
+    Ok(Json(IngestResult { accepted: 1, clamped: if clamped { vec![0] } else { vec![] }, ..Default::default() }))
+}
+
+/// Toplu sensör verisi ekle (MQTT çalıştıramayan cihazlar ve gateway batch modu)
+/// 
+/// POST /v1/sensors/ingest
+/// 
+/// Body: `POST /api/sensors` formatındaki okumaların JSON array'i
+/// (en fazla `INGEST_MAX_ITEMS`; `Content-Type: application/msgpack` ile
+/// MessagePack array'i). Her okuma ayrı doğrulanır; geçerli olanlar
+/// PostgreSQL ve Redis'e (hangileri bağlıysa) yazılır, geçersizler
+/// batch'teki index'leriyle `rejected` listesinde döner. `timestamp`'i
+/// alış zamanıyla değiştirilen okumaların index'leri `clamped`'dedir.
+/// `STRICT_SENSOR_VALIDATION` açıkken tipine uymayan okumalar da reddedilir;
+/// `metadata`'sı `MAX_METADATA_BYTES`'ı aşan okumalar her zaman reddedilir.
+/// 
+/// Response:
+/// ```json
+/// {"accepted": 2, "rejected": [[1, "invalid timestamp: yesterday"]], "clamped": [2]}
+/// ```
+/// 
+/// # Error Responses
+/// - 403 Forbidden: Cihaz token'ıyla gelen batch'te başka cihazın okuması var
+/// - 413 Payload Too Large: `INGEST_MAX_ITEMS`'tan fazla okuma
+/// - 429 Too Many Requests: Kaynak IP saniyede `INGEST_RATE_LIMIT`'i aştı
+/// - 503 Service Unavailable: Ne Redis ne PostgreSQL bağlı
+#[tracing::instrument(skip(state, items), fields(items = items.len()), err)]
+pub async fn ingest_sensor_batch(
+    State(state): State<AppState>,
+    binding: Option<Extension<DeviceBinding>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    JsonOrMsgPack(items): JsonOrMsgPack<Vec<SensorData>>,
+) -> Result<Json<IngestResult>, StatusCode> {
+    if !state.ingest_limiter.check(addr.ip()) {
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    }
+    if items.len() > INGEST_MAX_ITEMS {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    for data in &items {
+        DeviceBinding::check(binding.as_deref(), &data.device_id)?;
+    }
+    if state.pool().await.is_none() && state.redis.is_none() {
+        tracing::warn!("Redis not available, sensor batch not saved");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    let (guard, strict, max_metadata) = {
+        let cfg = state.cfg.read().await;
+        (TimestampGuard::from_config(&cfg), cfg.strict_sensor_validation, cfg.max_metadata_bytes)
+    };
+    let received_at = Utc::now();
+    let mut result = IngestResult::default();
+    let mut seen = Vec::new();
+    for (index, mut data) in items.into_iter().enumerate() {
+        let checked = validate_reading(&data)
+            .and_then(|()| check_metadata_size(&data, max_metadata))
+            .and_then(|()| if strict { check_sensor_type(&data) } else { Ok(()) })
+            .and_then(|()| guard.apply(&mut data, received_at));
+        let clamped = match checked {
+            Ok(clamped) => clamped,
+            Err(reason) => {
+                result.rejected.push((index, reason));
+                continue;
+            }
+        };
+        data.unit = normalize_unit(&data.unit).to_string();
+        let reading_seen = last_seen_of(&data);
+        match store_reading(&state, data).await {
+            Ok(()) => {
+                seen.extend(reading_seen);
+                result.accepted += 1;
+                if clamped {
+                    result.clamped.push(index);
                 }
             }
-            Err(e) => {
+            Err(status) => result.rejected.push((index, format!("storage failed: {status}"))),
+        }
+    }
+    routes::devices::record_last_seen(&state, seen).await;
+    Ok(Json(result))
+}
+
+/// Okumanın cihazı ve zamanı (`last_seen` için; `TimestampGuard`'dan sonra)
+fn last_seen_of(data: &SensorData) -> Option<(String, DateTime<Utc>)> {
+    let timestamp = DateTime::parse_from_rfc3339(&data.timestamp).ok()?;
+    Some((data.device_id.clone(), timestamp.with_timezone(&Utc)))
+}
+
+/// Toplu gönderimdeki bir okumayı doğrula (`timestamp` `TimestampGuard`'da kontrol edilir)
+fn validate_reading(data: &SensorData) -> Result<(), String> {
+    if data.device_id.trim().is_empty() {
+        return Err("device_id is empty".into());
+    }
+    if data.sensor_type.trim().is_empty() {
+        return Err("sensor_type is empty".into());
+    }
+    if !data.value.is_finite() {
+        return Err("value is not a finite number".into());
+    }
+    Ok(())
+}
+
+/// `metadata`'nın JSON boyutu `max_bytes`'ı aşıyor mu? (`MAX_METADATA_BYTES`)
+fn check_metadata_size(data: &SensorData, max_bytes: usize) -> Result<(), String> {
+    let Some(metadata) = &data.metadata else {
+        return Ok(());
+    };
+    let size = serde_json::to_vec(metadata).map_or(0, |json| json.len());
+    if size > max_bytes {
+        return Err(format!("metadata is {size} bytes (max {max_bytes})"));
+    }
+    Ok(())
+}
+
+/// Değeri ve birimi sensör tipine göre doğrula (`STRICT_SENSOR_VALIDATION`)
+fn check_sensor_type(data: &SensorData) -> Result<(), String> {
+    let Ok(sensor_type) = data.sensor_type.parse::<SensorType>();
+    SensorReadingValidator::default()
+        .validate_value(&sensor_type, data.value, &data.unit)
+        .map_err(|e| e.to_string())
+}
+
+/// Okumayı kaydet, alarmları kontrol et ve realtime akışa yayınla
+/// 
+/// PostgreSQL varsa history'ye, Redis varsa cache'e yazılır.
+/// İkisi de yoksa 503 döner. İkili sensörlerin (motion) geçişleri ayrıca
+/// olay olarak saklanır (bkz. `routes::motion`).
+pub async fn store_reading(state: &AppState, data: SensorData) -> Result<(), StatusCode> {
+    let binary = routes::motion::is_binary_sensor(&data.sensor_type);
+
+    // PostgreSQL varsa history'ye kaydet
+    if let Some(db) = &state.pool().await {
+        insert_reading(db, &data).await?;
+        if binary {
+            routes::motion::record_in_db(db, &data).await?;
+        }
+    }
+
+    // Redis varsa Redis'e yaz
+    if let Some(mut redis_conn) = state.redis.clone() {
+        let key = redis_sensor_key(&data.device_id, &data.sensor_type);
+        if binary {
+            // Son değer ve durum geçişi tek script'te (önceki değer atomik okunur)
+            routes::motion::record_in_redis(&mut redis_conn, &key, &data, REDIS_SENSOR_TTL_SECS).await?;
+        } else {
+            let json = serde_json::to_string(&data).map_err(|e| {
                 tracing::error!("JSON serialization error: {e}");
-                return Err(StatusCode::BAD_REQUEST);
+                StatusCode::BAD_REQUEST
+            })?;
+
+            // Redis'e JSON string olarak kaydet
+            // TTL dolunca eski veriler otomatik silinir
+            redis_conn.set_ex::<_, _, ()>(&key, json, REDIS_SENSOR_TTL_SECS).await.map_err(|e| {
+                tracing::error!("Redis write error: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        }
+        tracing::debug!("Sensor data saved to Redis: {key}");
+
+        if state.cfg.read().await.pubsub_enabled {
+            pubsub::publish_reading(&mut redis_conn, &data).await;
+        }
+    } else if state.pool().await.is_none() {
+        // Ne Redis ne DB varsa hata dön
+        tracing::warn!("Redis not available, sensor data not saved");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    check_alerts(state, &data).await;
+    event_sink::enqueue(state.event_queue.as_ref(), IngestEvent::Reading(data.clone()));
+    state.sensor_feed.publish(data);
+    Ok(())
+}
+
+/// Yeni sensör okumalarını bekle (long-poll)
+/// 
+/// GET /api/sensors/realtime?since=<sequence>
+/// 
+/// `since`'ten sonraki okumalar varsa hemen döner; yoksa en fazla 2 saniye
+/// bekler. Tek seferde en fazla 50 okuma döner. Client bir sonraki istekte
+/// `next_sequence`'i `since` olarak gönderir. Sunucu yeniden başladıysa
+/// (`since` son sıra numarasından büyük) akış baştan okunur.
+/// 
+/// Response:
+/// ```json
+/// {
+///   "events": [
+///     {"sequence": 43, "device_id": "edge-agent-001", "sensor_type": "temperature",
+///      "value": 23.5, "unit": "°C", "timestamp": "2024-01-20T10:30:00Z", "metadata": null}
+///   ],
+///   "next_sequence": 43
+/// }
+/// ```
+#[tracing::instrument(skip(state))]
+pub async fn realtime_sensors(
+    State(state): State<AppState>,
+    Query(params): Query<RealtimeParams>,
+) -> Json<RealtimeBatch> {
+    let feed = &state.sensor_feed;
+    let since = if params.since > feed.latest() { 0 } else { params.since };
+
+    let events = feed.poll(since, REALTIME_BATCH_LIMIT, REALTIME_WAIT).await;
+    let next_sequence = events.last().map_or(since, |e| e.sequence);
+    Json(RealtimeBatch { events, next_sequence })
+}
+
+/// Yeni sensör okumalarının canlı akışı (Server-Sent Events)
+/// 
+/// GET /api/sensors/stream?device_id=<id>
+/// 
+/// WebSocket geçiremeyen proxy'ler için long-poll ile aynı akışı
+/// (`SensorFeed`) tek bağlantıda sunar. Kabul edilen her okuma bir
+/// `reading` event'i olarak gönderilir; event ID'si okumanın sıra numarası,
+/// data'sı `GET /api/sensors/realtime`'daki gibi JSON'dur:
+/// 
+/// ```text
+/// event: reading
+/// id: 43
+/// data: {"sequence":43,"device_id":"edge-agent-001","sensor_type":"temperature","value":23.5,"unit":"°C","timestamp":"2024-01-20T10:30:00Z","metadata":null}
+/// ```
+/// 
+/// Okuma gelmezse `STREAM_KEEP_ALIVE` aralığıyla `: keep-alive` yorumu
+/// gönderilir. Yeniden bağlanan client'ın `Last-Event-ID` header'ı varsa
+/// backlog'daki (son `FEED_CAPACITY` okuma) daha yeni okumalar önce
+/// gönderilir; sunucu yeniden başladıysa (ID son sıra numarasından büyük)
+/// backlog baştan gönderilir. Akışa istek zaman aşımı uygulanmaz.
+#[tracing::instrument(skip(state, headers))]
+pub async fn stream_sensors(
+    State(state): State<AppState>,
+    Query(params): Query<StreamParams>,
+    headers: HeaderMap,
+) -> Sse<impl futures_util::Stream<Item = Result<Event, axum::Error>>> {
+    let feed = &state.sensor_feed;
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(|id| if id > feed.latest() { 0 } else { id });
+
+    let device_id = params.device_id;
+    let events = feed
+        .stream(last_event_id)
+        .filter(move |event| std::future::ready(device_id.as_ref().is_none_or(|id| event.data.device_id == *id)))
+        .map(|event| Event::default().event("reading").id(event.sequence.to_string()).json_data(&event));
+    Sse::new(events).keep_alive(KeepAlive::new().interval(STREAM_KEEP_ALIVE).text("keep-alive"))
+}
+
+/// Okumayı `ALERT_THRESHOLDS` eşiğiyle karşılaştır, aşıldıysa webhook'lara bildir
+/// 
+/// PostgreSQL bağlıysa alarm cihaz zaman çizelgesi için `sensor_alerts`'e de yazılır.
+/// `alerts_enabled` bayrağı kapalıysa eşik kontrolü yapılmaz. Eşiğin dışında
+/// kalan sensör `ALERT_COOLDOWN` boyunca tekrar alarm üretmez.
+async fn check_alerts(state: &AppState, data: &SensorData) {
+    if !state.features.read().await.alerts_enabled {
+        return;
+    }
+    let Some(threshold) = state.cfg.read().await.alert_thresholds.get(&data.sensor_type).copied() else {
+        return;
+    };
+    if let Some(alert) = check_threshold(&data.device_id, &data.sensor_type, data.value, &threshold) {
+        if !state.alert_cooldowns.try_fire(&alert.device_id, &alert.sensor_type, &threshold, std::time::Instant::now()) {
+            tracing::debug!("Alert from {} suppressed (cooldown): {}", alert.device_id, alert.message);
+            return;
+        }
+        tracing::warn!("🚨 Alert from {}: {}", alert.device_id, alert.message);
+        if let Some(db) = &state.pool().await {
+            insert_alert(db, &alert).await;
+        }
+        routes::webhooks::notify_alert(state, &alert).await;
+        event_sink::enqueue(state.event_queue.as_ref(), IngestEvent::Alert(alert));
+    }
+}
+
+/// Alarmı `sensor_alerts` tablosuna ekle (hata loglanır; ingest'i bozmaz)
+async fn insert_alert(db: &PgPool, alert: &SensorAlert) {
+    let result = sqlx::query(
+        "INSERT INTO sensor_alerts (id, device_id, sensor_type, value, threshold_min, threshold_max, message, triggered_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+    )
+    .bind(alert.id)
+    .bind(&alert.device_id)
+    .bind(&alert.sensor_type)
+    .bind(alert.value)
+    .bind(alert.threshold.min)
+    .bind(alert.threshold.max)
+    .bind(&alert.message)
+    .bind(alert.triggered_at)
+    .execute(db)
+    .await;
+    if let Err(e) = result {
+        tracing::error!("Sensor alert insert failed: {e}");
+    }
+}
+
+/// Okumayı `sensor_readings` tablosuna ekle
+async fn insert_reading(db: &PgPool, data: &SensorData) -> Result<(), StatusCode> {
+    let parse = |raw: &str| DateTime::parse_from_rfc3339(raw).map(|t| t.with_timezone(&Utc));
+    let timestamp = parse(&data.timestamp).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let received_at = data.received_at.as_deref().and_then(|raw| parse(raw).ok());
+    let gateway_received_at = data.gateway_received_at.as_deref().and_then(|raw| parse(raw).ok());
+
+    sqlx::query(
+        "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp, metadata, received_at, gateway_received_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+    )
+    .bind(&data.device_id)
+    .bind(&data.sensor_type)
+    .bind(data.value)
+    .bind(&data.unit)
+    .bind(timestamp)
+    .bind(&data.metadata)
+    .bind(received_at)
+    .bind(gateway_received_at)
+    .execute(db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Sensor reading insert failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(())
+}
+
+impl TimeRangeParams {
+    /// Bu aralıktaki okumalar (`limit` ayrıca uygulanır)
+    fn readings(&self, db: PgPool, device_id: String, sensor_type: String) -> SensorReadingStream {
+        SensorReadingStream::new(db, device_id, sensor_type).between(self.from, self.to)
+    }
+}
+
+/// Sensör okuma geçmişi
+/// 
+/// GET /v1/sensors/{device_id}/{sensor_type}/history?from=&to=&limit=
+/// 
+/// Okumaları zamana göre artan sırada JSON array olarak döner. Satırlar
+/// 1000'lik chunk'lar halinde stream edilir; büyük `limit`'ler belleğe
+/// alınmaz. PostgreSQL bağlı değilse 501 döner. `Accept: application/msgpack`
+/// ile (tüm biçimlerde) MessagePack döner; bu durumda body belleğe alınır.
+/// 
+/// `?downsample=lttb&points=500` ile `limit` kadar okuma LTTB ile `points`
+/// noktaya indirilir (grafikler için) ve `DownsampledHistory` döner:
+/// ```json
+/// {"readings": [...], "source_points": 100000, "skipped_points": 0}
+/// ```
+/// Okumalar belleğe alındığı için `limit` `MAX_DOWNSAMPLE_SOURCE`'u aşarsa 400.
+/// 
+/// `?unit=fahrenheit` ile değerler çevrilir ve `unit` yeniden yazılır;
+/// serinin birimi dönüştürülemiyorsa (ör. nem) veya birim bilinmiyorsa 400.
+/// 
+/// `?fill=null|previous|linear` ile okumalar arasındaki boşluklara
+/// `filled: true` noktalar eklenir (bkz. `FillParams`, `HistoryPoint`):
+/// ```json
+/// [{"value": 21.5, ...}, {"value": null, "timestamp": "...", "filled": true, ...}, ...]
+/// ```
+/// Okumalar belleğe alınır; `downsample` ile birlikte verilirse, `limit`
+/// veya eklenecek nokta sayısı `MAX_FILL_POINTS`'u aşarsa 400.
+#[tracing::instrument(skip(state), err)]
+pub async fn sensor_history(
+    State(state): State<AppState>,
+    Path((device_id, sensor_type)): Path<(String, String)>,
+    Query(range): Query<TimeRangeParams>,
+    Query(downsample): Query<DownsampleParams>,
+    Query(unit): Query<UnitParams>,
+    Query(fill): Query<FillParams>,
+) -> Result<Response, StatusCode> {
+    unit.target()?;
+    let (interval, max_gap) = fill.millis()?;
+    if fill.fill != FillMethod::None && downsample.downsample.is_some() {
+        tracing::debug!("Rejected history query: fill and downsample are exclusive");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let limit = range.limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    if downsample.downsample.is_some() && limit > MAX_DOWNSAMPLE_SOURCE {
+        tracing::debug!("Rejected history downsample: limit {limit} exceeds {MAX_DOWNSAMPLE_SOURCE}");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if fill.fill != FillMethod::None && limit > MAX_FILL_POINTS as i64 {
+        tracing::debug!("Rejected history fill: limit {limit} exceeds {MAX_FILL_POINTS}");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let db = state.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let conversion = UnitConversion::for_series(&db, &device_id, &sensor_type, &unit).await?;
+    let readings = range.readings(db, device_id, sensor_type).limit(Some(limit));
+
+    if fill.fill != FillMethod::None {
+        let history = fill_history(readings, conversion, fill.fill, interval, max_gap).await?;
+        return Ok(Json(history).into_response());
+    }
+
+    if let Some(DownsampleMethod::Lttb) = downsample.downsample {
+        let points = downsample.points.unwrap_or(DEFAULT_DOWNSAMPLE_POINTS);
+        let mut history = downsample_lttb(readings, points).await?;
+        if let Some(conversion) = conversion {
+            history.readings.iter_mut().for_each(|reading| conversion.apply(reading));
+        }
+        return Ok(Json(history).into_response());
+    }
+
+    // Satırlar virgülle ayrılır; ilk satırdan önce virgül yok
+    let mut first = true;
+    let body = chunked_body(readings, ("[", "]"), move |rows| {
+        let mut out = String::new();
+        for row in rows {
+            if !std::mem::take(&mut first) {
+                out.push(',');
             }
+            let mut reading = SensorData::from(row);
+            if let Some(conversion) = conversion {
+                conversion.apply(&mut reading);
+            }
+            out.push_str(&serde_json::to_string(&reading).unwrap_or_default());
+        }
+        out
+    })
+    .await?;
+
+    Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
+}
+
+/// Okumaları LTTB ile `points` noktaya indir
+/// 
+/// LTTB bucket'ları toplam sayıya bağlı olduğundan okumalar (en fazla
+/// `limit` kadar) belleğe alınır. Sayısal olmayan değerler atlanır ve
+/// sayılır.
+async fn downsample_lttb(readings: SensorReadingStream, points: usize) -> Result<DownsampledHistory, StatusCode> {
+    let (mut rows, mut source_points) = (Vec::new(), 0);
+    let mut stream = readings.fetch();
+    while let Some(row) = stream.next().await {
+        let row = row.map_err(|e| {
+            tracing::error!("Sensor history query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        source_points += 1;
+        if row.value.is_finite() {
+            rows.push(row);
         }
     }
-    
-    // Redis yoksa hata dön
-    tracing::warn!("Redis not available, sensor data not saved");
-    Err(StatusCode::SERVICE_UNAVAILABLE)
+
+    let skipped_points = source_points - rows.len();
+    if skipped_points > 0 {
+        tracing::warn!("Skipped {skipped_points} non-numeric readings while downsampling");
+    }
+    let rows = timeseries::lttb(rows, points, |row| (row.timestamp.timestamp_millis() as f64, row.value));
+    Ok(DownsampledHistory { readings: rows.into_iter().map(SensorData::from).collect(), source_points, skipped_points })
+}
+
+/// Okumaları belleğe alıp aralarındaki boşlukları doldur
+/// 
+/// `interval` verilmezse okumaların medyan aralığı kullanılır; farklı
+/// zamanlı iki okuma yoksa seri aynen döner. Eklenen noktalar birim
+/// dönüşümünden sonra hesaplanır ve önceki okumanın birimini taşır.
+async fn fill_history(
+    readings: SensorReadingStream,
+    conversion: Option<UnitConversion>,
+    method: FillMethod,
+    interval: Option<i64>,
+    max_gap: Option<i64>,
+) -> Result<Vec<HistoryPoint>, StatusCode> {
+    let mut rows = Vec::new();
+    let mut stream = readings.fetch();
+    while let Some(row) = stream.next().await {
+        let row = row.map_err(|e| {
+            tracing::error!("Sensor history query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let millis = row.timestamp.timestamp_millis();
+        let mut reading = SensorData::from(row);
+        if let Some(conversion) = conversion {
+            conversion.apply(&mut reading);
+        }
+        rows.push((millis, reading));
+    }
+
+    let interval = interval.or_else(|| timeseries::median_interval(rows.iter().map(|(x, _)| *x))).unwrap_or(0);
+    let added = timeseries::missing_points(&rows, interval, max_gap, |(x, _)| *x);
+    if added > MAX_FILL_POINTS {
+        tracing::debug!("Rejected history fill: {added} points exceed {MAX_FILL_POINTS}");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut previous: Option<(String, String, String)> = None;
+    let points = timeseries::fill_gaps(rows, method, interval, max_gap, |(x, reading)| (*x, reading.value))
+        .into_iter()
+        .map(|point| match point {
+            Filled::Source((_, reading)) => {
+                previous = Some((reading.device_id.clone(), reading.sensor_type.clone(), reading.unit.clone()));
+                HistoryPoint::Reading(reading)
+            }
+            Filled::Gap { x, y } => {
+                // Boşluklar her zaman bir okumadan sonra gelir
+                let (device_id, sensor_type, unit) = previous.clone().unwrap_or_default();
+                HistoryPoint::Gap(GapReading {
+                    device_id,
+                    sensor_type,
+                    value: y,
+                    unit,
+                    timestamp: DateTime::<Utc>::from_timestamp_millis(x).unwrap_or_default().to_rfc3339(),
+                    filled: true,
+                })
+            }
+        })
+        .collect();
+    Ok(points)
+}
+
+/// Sensör istatistikleri (sayı/min/max/ortalama/standart sapma)
+/// 
+/// GET /v1/sensors/{device_id}/{sensor_type}/stats?from=&to=
+/// 
+/// Aralıktaki ham okumalardan tek geçişte hesaplanır (`SensorStats`); okumalar
+/// belleğe alınmaz. `limit` yok sayılır. PostgreSQL bağlı değilse 501 döner.
+/// `?unit=` history'deki gibi çevirir (`std_dev`'e sadece ölçek uygulanır).
+#[tracing::instrument(skip(state), err)]
+pub async fn sensor_stats(
+    State(state): State<AppState>,
+    Path((device_id, sensor_type)): Path<(String, String)>,
+    Query(range): Query<TimeRangeParams>,
+    Query(unit): Query<UnitParams>,
+) -> Result<Json<SensorStats>, StatusCode> {
+    unit.target()?;
+    let db = state.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let conversion = UnitConversion::for_series(&db, &device_id, &sensor_type, &unit).await?;
+    let readings = range.readings(db, device_id, sensor_type);
+
+    let mut stats = SensorStats::default();
+    let mut rows = readings.fetch();
+    while let Some(row) = rows.next().await {
+        let row = row.map_err(|e| {
+            tracing::error!("Sensor stats query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        stats.push(row.value);
+    }
+    if let Some(UnitConversion { from, to }) = conversion {
+        stats.convert_unit(from, to).map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+    Ok(Json(stats))
+}
+
+/// Geçmiş okumaları bir eşikle yeniden oynat (alarm back-test)
+/// 
+/// POST /v1/sensors/replay
+/// 
+/// Aralıktaki okumalar stream edilir ve her birine `check_threshold`
+/// uygulanır. Üretilen alarmlar sadece response'ta döner: kaydedilmez,
+/// webhook'lar tetiklenmez.
+/// 
+/// Request:
+/// ```json
+/// {
+///   "device_id": "edge-agent-001",
+///   "sensor_type": "temperature",
+///   "from": "2024-01-20T00:00:00Z",
+///   "to": "2024-01-21T00:00:00Z",
+///   "threshold": {"max": 30.0}
+/// }
+/// ```
+/// 
+/// # Error Responses
+/// - 400 Bad Request: `to` `from`'dan önce veya aralık `REPLAY_MAX_RANGE`'ten uzun
+/// - 413 Payload Too Large: Aralıkta `REPLAY_MAX_READINGS`'ten fazla okuma var
+/// - 501 Not Implemented: PostgreSQL bağlı değil veya `alerts_enabled` kapalı
+#[tracing::instrument(skip(state, request), fields(device_id = %request.device_id, sensor_type = %request.sensor_type), err)]
+pub async fn replay_sensor_readings(
+    State(state): State<AppState>,
+    Json(request): Json<ReplayRequest>,
+) -> Result<Json<ReplayResult>, StatusCode> {
+    check_feature(&*state.features.read().await, "alerts_enabled")?;
+    if request.to < request.from || request.to - request.from > REPLAY_MAX_RANGE {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let db = state.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+
+    // Sınırı aşan aralığı tespit etmek için bir satır fazla okunur
+    let readings = SensorReadingStream::new(db, &request.device_id, &request.sensor_type)
+        .between(Some(request.from), Some(request.to))
+        .limit(Some(REPLAY_MAX_READINGS as i64 + 1));
+
+    let mut result = ReplayResult::default();
+    let mut rows = readings.fetch();
+    while let Some(row) = rows.next().await {
+        let row = row.map_err(|e| {
+            tracing::error!("Sensor replay query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        if result.total_readings == REPLAY_MAX_READINGS {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        result.push(&request, row.value, row.timestamp);
+    }
+    Ok(Json(result))
+}
+
+/// Sensör özetleri (min/max/ortalama/sayı)
+/// 
+/// GET /v1/sensors/{device_id}/{sensor_type}/summaries?resolution=1h&from=&to=
+/// 
+/// `sensor_summaries` tablosunu okur (arka plan görevi saatlik doldurur).
+/// Geçersiz `resolution` → 400. PostgreSQL bağlı değilse 501 döner.
+/// `?unit=` history'deki gibi min/max/ortalamayı çevirir.
+/// 
+/// `?fill=null|previous|linear` ile eksik bucket'lar `count: 0`,
+/// `filled: true` satırlar olarak eklenir (bkz. `SummaryGap`); `interval`
+/// verilmezse beklenen aralık çözünürlüktür. `MAX_FILL_POINTS`'ten fazla
+/// bucket eklenecekse 400.
+/// 
+/// Response:
+/// ```json
+/// [
+///   {
+///     "device_id": "edge-agent-001",
+///     "sensor_type": "temperature",
+///     "bucket_start": "2024-01-20T10:00:00Z",
+///     "min_value": 21.2,
+///     "max_value": 23.9,
+///     "avg_value": 22.4,
+///     "count": 60
+///   }
+/// ]
+/// ```
+#[tracing::instrument(skip(state), err)]
+pub async fn sensor_summaries(
+    State(state): State<AppState>,
+    Path((device_id, sensor_type)): Path<(String, String)>,
+    Query(params): Query<SummaryParams>,
+    Query(unit): Query<UnitParams>,
+    Query(fill): Query<FillParams>,
+) -> Result<Json<Vec<SummaryPoint>>, StatusCode> {
+    unit.target()?;
+    let (interval, max_gap) = fill.millis()?;
+    let db = &state.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let conversion = UnitConversion::for_series(db, &device_id, &sensor_type, &unit).await?;
+
+    let mut rows = sqlx::query_as::<_, SensorSummary>(SUMMARIES_SQL)
+        .bind(&device_id)
+        .bind(&sensor_type)
+        .bind(params.resolution.date_trunc_unit())
+        .bind(params.from)
+        .bind(params.to)
+        .fetch_all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Sensor summaries query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if let Some(UnitConversion { from, to }) = conversion {
+        for row in &mut rows {
+            row.convert_unit(from, to).map_err(|_| StatusCode::BAD_REQUEST)?;
+        }
+    }
+
+    let interval = interval.unwrap_or(params.resolution.bucket().num_milliseconds());
+    let bucket_millis = |row: &SensorSummary| row.bucket_start.timestamp_millis();
+    if fill.fill != FillMethod::None {
+        let added = timeseries::missing_points(&rows, interval, max_gap, bucket_millis);
+        if added > MAX_FILL_POINTS {
+            tracing::debug!("Rejected summaries fill: {added} buckets exceed {MAX_FILL_POINTS}");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    let points = timeseries::fill_gaps(rows, fill.fill, interval, max_gap, |row| (bucket_millis(row), row.avg_value))
+        .into_iter()
+        .map(|point| match point {
+            Filled::Source(row) => SummaryPoint::Summary(row),
+            Filled::Gap { x, y } => SummaryPoint::Gap(SummaryGap {
+                device_id: device_id.clone(),
+                sensor_type: sensor_type.clone(),
+                bucket_start: DateTime::<Utc>::from_timestamp_millis(x).unwrap_or_default(),
+                min_value: y,
+                max_value: y,
+                avg_value: y,
+                count: 0,
+                filled: true,
+            }),
+        })
+        .collect();
+    Ok(Json(points))
+}
+
+/// Cihazın sensör tiplerini zaman bucket'ı × sensör tipi matrisi olarak getir
+/// 
+/// GET /v1/sensors/heatmap?device_id=&sensor_types=temperature,humidity&from=&to=&bucket_minutes=60
+/// 
+/// Her hücre bucket'taki okumaların ortalamasıdır; okuma olmayan hücreler
+/// `null` döner. En fazla 1000 bucket × 20 sensör tipi; aşılırsa veya
+/// aralık geçersizse 400, PostgreSQL bağlı değilse 501 döner.
+#[tracing::instrument(skip(state), err)]
+pub async fn get_sensor_heatmap(
+    State(state): State<AppState>,
+    Query(mut params): Query<HeatmapParams>,
+) -> Result<Json<HeatmapResponse>, StatusCode> {
+    params.validate().map_err(|e| {
+        tracing::debug!("Invalid heatmap query: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+    let db = &state.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+
+    let cells = sqlx::query_as::<_, (DateTime<Utc>, String, f64)>(HEATMAP_SQL)
+        .bind(&params.device_id)
+        .bind(&params.sensor_types)
+        .bind(params.from)
+        .bind(params.to)
+        .bind(params.bucket_secs() as f64)
+        .fetch_all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!("Sensor heatmap query failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let buckets = params.buckets();
+    Ok(Json(HeatmapResponse::from_cells(buckets, params.sensor_types, cells)))
+}
+
+/// Sensör geçmişini InfluxDB line protocol olarak export et
+/// 
+/// GET /v1/sensors/{device_id}/{sensor_type}/export.influx?from=&to=
+/// 
+/// Her satır:
+/// ```text
+/// sensor_readings,device_id=<id>,sensor_type=<type> value=<f64> <unix_nanoseconds>
+/// ```
+/// 
+/// Satırlar 1000'lik chunk'lar halinde stream edilir; büyük aralıklar
+/// belleğe alınmaz. PostgreSQL bağlı değilse 501 döner.
+#[tracing::instrument(skip(state), err)]
+pub async fn export_influx_line_protocol(
+    State(state): State<AppState>,
+    Path((device_id, sensor_type)): Path<(String, String)>,
+    Query(range): Query<TimeRangeParams>,
+) -> Result<Response, StatusCode> {
+    let db = state.pool().await.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let disposition = attachment_disposition(&format!("{}-{}.lp", device_id, sensor_type));
+    let readings = range.readings(db, device_id, sensor_type).limit(range.limit);
+
+    let body = chunked_body(readings, ("", ""), |rows| to_line_protocol(&rows)).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// İndirme için `Content-Disposition` (RFC 6266)
+///
+/// Path segmentleri kullanıcı girdisidir: `filename` sadece güvenli ASCII
+/// karakterler taşır (diğerleri `_`), asıl ad `filename*` ile UTF-8
+/// percent-encode edilerek verilir.
+fn attachment_disposition(filename: &str) -> String {
+    const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_');
+    let fallback: String = filename
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_') { c } else { '_' })
+        .collect();
+    format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", fallback, utf8_percent_encode(filename, ATTR_CHAR))
+}
+
+/// Okumaları `EXPORT_CHUNK_ROWS`'luk chunk'lar halinde response body'sine aktar
+/// 
+/// Stream, handler'ın ömründen bağımsız olmalı: satırlar ayrı bir task'ta
+/// okunup kanal üzerinden body'ye aktarılır. Her chunk `render` ile metne
+/// çevrilir; body `prefix` ile başlar, tüm satırlar okunduysa `suffix` ile
+/// biter.
+/// 
+/// İlk chunk beklenir: sorgu hemen başarısız olursa (bağlantı, SQL) 500
+/// döner. Sonraki hatalar body'yi yarıda keser.
+async fn chunked_body(
+    readings: SensorReadingStream,
+    (prefix, suffix): (&'static str, &'static str),
+    mut render: impl FnMut(Vec<SensorReadingRow>) -> String + Send + 'static,
+) -> Result<Body, StatusCode> {
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Result<String, sqlx::Error>>(4);
+    tokio::spawn(async move {
+        let mut chunks = readings.fetch().chunks(EXPORT_CHUNK_ROWS);
+        while let Some(chunk) = chunks.next().await {
+            let chunk: Result<Vec<_>, _> = chunk.into_iter().collect();
+            let msg = chunk.map(&mut render);
+            let failed = msg.is_err();
+            if tx.send(msg).await.is_err() || failed {
+                return;
+            }
+        }
+        let _ = tx.send(Ok(suffix.to_string())).await;
+    });
+
+    let first = rx.recv().await.transpose().map_err(|e| {
+        tracing::error!("Sensor readings query failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let head = futures_util::stream::iter([Ok(prefix.to_string())].into_iter().chain(first.map(Ok)));
+    let rest = futures_util::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|chunk| (chunk, rx)) });
+    Ok(Body::from_stream(head.chain(rest)))
+}
+
+/// Satırları line protocol formatına çevir (her satır `\n` ile biter)
+fn to_line_protocol(rows: &[SensorReadingRow]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        out.push_str(&format!(
+            "sensor_readings,device_id={},sensor_type={} value={} {}\n",
+            escape_tag(&row.device_id),
+            escape_tag(&row.sensor_type),
+            row.value,
+            row.timestamp.timestamp_nanos_opt().unwrap_or_default(),
+        ));
+    }
+    out
+}
+
+/// Line protocol tag değerlerindeki özel karakterleri escape et (virgül, boşluk, eşittir)
+fn escape_tag(value: &str) -> String {
+    value.replace(',', "\\,").replace(' ', "\\ ").replace('=', "\\=")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_disposition_is_sanitized() {
+        assert_eq!(
+            attachment_disposition("edge-1-temperature.lp"),
+            "attachment; filename=\"edge-1-temperature.lp\"; filename*=UTF-8''edge-1-temperature.lp"
+        );
+        let disposition = attachment_disposition("a\"b\r\nX: y;ı-t.lp");
+        assert_eq!(disposition, "attachment; filename=\"a_b__X__y__-t.lp\"; filename*=UTF-8''a%22b%0D%0AX%3A%20y%3B%C4%B1-t.lp");
+        assert!(header::HeaderValue::from_str(&disposition).is_ok());
+    }
+
+    #[test]
+    fn test_line_protocol_format() {
+        let start = DateTime::parse_from_rfc3339("2024-01-20T10:30:00Z").unwrap().with_timezone(&Utc);
+        let rows: Vec<_> = (0..100)
+            .map(|i| SensorReadingRow {
+                device_id: "edge-agent-001".to_string(),
+                sensor_type: "temperature".to_string(),
+                value: 20.0 + i as f64 * 0.1,
+                unit: "°C".to_string(),
+                timestamp: start + chrono::Duration::seconds(i),
+                metadata: None,
+                received_at: None,
+                gateway_received_at: None,
+            })
+            .collect();
+
+        let output = to_line_protocol(&rows);
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 100);
+
+        for (i, line) in lines.iter().enumerate() {
+            // measurement+tags, field set, timestamp
+            let parts: Vec<_> = line.split(' ').collect();
+            assert_eq!(parts.len(), 3, "line: {line}");
+            assert_eq!(parts[0], "sensor_readings,device_id=edge-agent-001,sensor_type=temperature");
+            assert!(parts[1].starts_with("value="));
+            parts[1]["value=".len()..].parse::<f64>().unwrap();
+
+            let nanos: i64 = parts[2].parse().unwrap();
+            assert_eq!(nanos, rows[i].timestamp.timestamp_nanos_opt().unwrap());
+            assert_eq!(parts[2].len(), 19);
+        }
+    }
+
+    #[test]
+    fn test_escape_tag() {
+        assert_eq!(escape_tag("living room,1"), "living\\ room\\,1");
+    }
+
+    #[test]
+    fn test_alert_cooldown_per_device_sensor_threshold() {
+        let cooldowns = AlertCooldowns::default();
+        let high = AlertThreshold { min: None, max: Some(35.0) };
+        let low = AlertThreshold { min: Some(10.0), max: None };
+        let t0 = std::time::Instant::now();
+
+        assert!(cooldowns.try_fire("dev-1", "temperature", &high, t0));
+        // Aynı anahtar bekleme süresi içinde bastırılır
+        assert!(!cooldowns.try_fire("dev-1", "temperature", &high, t0 + Duration::from_secs(1)));
+        // Farklı cihaz, sensör ya da eşik bağımsızdır
+        assert!(cooldowns.try_fire("dev-2", "temperature", &high, t0));
+        assert!(cooldowns.try_fire("dev-1", "humidity", &high, t0));
+        assert!(cooldowns.try_fire("dev-1", "temperature", &low, t0));
+        // Süre dolunca tekrar tetiklenir
+        assert!(cooldowns.try_fire("dev-1", "temperature", &high, t0 + ALERT_COOLDOWN));
+    }
+
+    fn summary_params(query: &str) -> Option<SummaryParams> {
+        let uri: axum::http::Uri = format!("http://localhost/summaries{query}").parse().unwrap();
+        Query::<SummaryParams>::try_from_uri(&uri).ok().map(|q| q.0)
+    }
+
+    #[test]
+    fn test_summary_resolution_params() {
+        assert_eq!(summary_params("").unwrap().resolution, SummaryResolution::Hour);
+        assert_eq!(summary_params("?resolution=1h").unwrap().resolution.date_trunc_unit(), "hour");
+        assert_eq!(summary_params("?resolution=1d").unwrap().resolution.date_trunc_unit(), "day");
+        assert!(summary_params("?resolution=5m").is_none());
+    }
+
+    #[test]
+    fn test_convert_reading_rewrites_unit() {
+        let mut reading = SensorData { unit: "celsius".into(), ..reading("dev-1", 25.0) };
+        convert_reading(&mut reading, Unit::Fahrenheit).unwrap();
+        assert_eq!((reading.value, reading.unit.as_str()), (77.0, "°F"));
+
+        // Farklı büyüklük: okuma değişmez
+        let mut humidity = SensorData { sensor_type: "humidity".into(), unit: "%".into(), value: 40.0, ..reading };
+        assert!(convert_reading(&mut humidity, Unit::Fahrenheit).is_err());
+        assert_eq!((humidity.value, humidity.unit.as_str()), (40.0, "%"));
+
+        assert_eq!(UnitParams { unit: Some("F".into()) }.target(), Ok(Some(Unit::Fahrenheit)));
+        assert_eq!(UnitParams::default().target(), Ok(None));
+        assert_eq!(UnitParams { unit: Some("furlongs".into()) }.target(), Err(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_summaries_without_db_not_implemented() {
+        let result = sensor_summaries(
+            State(AppState::for_tests()),
+            Path(("edge-agent-001".to_string(), "temperature".to_string())),
+            Query(SummaryParams::default()),
+            Query(UnitParams::default()),
+            Query(FillParams::default()),
+        )
+        .await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+
+        let path = Path(("edge-agent-001".to_string(), "temperature".to_string()));
+        let result = sensor_stats(State(AppState::for_tests()), path, Query(TimeRangeParams::default()), Query(UnitParams::default())).await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[test]
+    fn test_downsample_params() {
+        let uri: axum::http::Uri = "/history?downsample=lttb&points=200&limit=5000".parse().unwrap();
+        let Query(params) = Query::<DownsampleParams>::try_from_uri(&uri).unwrap();
+        assert_eq!((params.downsample, params.points), (Some(DownsampleMethod::Lttb), Some(200)));
+
+        let uri: axum::http::Uri = "/history?downsample=minmax".parse().unwrap();
+        assert!(Query::<DownsampleParams>::try_from_uri(&uri).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_downsample_rejects_oversized_limit() {
+        let history = |limit| {
+            let path = Path(("edge-agent-001".to_string(), "temperature".to_string()));
+            let range = TimeRangeParams { limit: Some(limit), ..Default::default() };
+            let downsample = DownsampleParams { downsample: Some(DownsampleMethod::Lttb), points: None };
+            sensor_history(State(AppState::for_tests()), path, Query(range), Query(downsample), Query(UnitParams::default()), Query(FillParams::default()))
+        };
+        assert_eq!(history(MAX_DOWNSAMPLE_SOURCE + 1).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        // Sınırda istek kabul edilir (burada DB yok)
+        assert_eq!(history(MAX_DOWNSAMPLE_SOURCE).await.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[test]
+    fn test_fill_params() {
+        let fill = |query: &str| {
+            let uri: axum::http::Uri = format!("/history{query}").parse().unwrap();
+            Query::<FillParams>::try_from_uri(&uri).ok().map(|q| q.0)
+        };
+        let params = fill("?fill=linear&interval=60&max_gap=3600&limit=10").unwrap();
+        assert_eq!(params.fill, FillMethod::Linear);
+        assert_eq!(params.millis(), Ok((Some(60_000), Some(3_600_000))));
+
+        let params = fill("").unwrap();
+        assert_eq!((params.fill, params.millis()), (FillMethod::None, Ok((None, None))));
+        assert_eq!(fill("?fill=null").unwrap().fill, FillMethod::Null);
+        assert_eq!(fill("?fill=previous").unwrap().fill, FillMethod::Previous);
+        assert!(fill("?fill=spline").is_none());
+        assert_eq!(fill("?fill=null&interval=0").unwrap().millis(), Err(StatusCode::BAD_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_fill_rejected_with_downsample_large_limit_or_zero_interval() {
+        let path = || Path(("edge-agent-001".to_string(), "temperature".to_string()));
+        let fill = FillParams { fill: FillMethod::Linear, ..Default::default() };
+        let downsample = DownsampleParams { downsample: Some(DownsampleMethod::Lttb), points: None };
+        let result = sensor_history(
+            State(AppState::for_tests()),
+            path(),
+            Query(TimeRangeParams::default()),
+            Query(downsample),
+            Query(UnitParams::default()),
+            Query(fill),
+        )
+        .await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        let range = TimeRangeParams { limit: Some(MAX_FILL_POINTS as i64 + 1), ..Default::default() };
+        let fill = FillParams { fill: FillMethod::Previous, ..Default::default() };
+        let result = sensor_history(
+            State(AppState::for_tests()),
+            path(),
+            Query(range),
+            Query(DownsampleParams::default()),
+            Query(UnitParams::default()),
+            Query(fill),
+        )
+        .await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        let fill = FillParams { fill: FillMethod::Null, interval: Some(0), max_gap: None };
+        let result = sensor_summaries(State(AppState::for_tests()), path(), Query(SummaryParams::default()), Query(UnitParams::default()), Query(fill)).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    fn replay_request(from: DateTime<Utc>, to: DateTime<Utc>) -> ReplayRequest {
+        ReplayRequest {
+            device_id: "edge-agent-001".into(),
+            sensor_type: "temperature".into(),
+            from,
+            to,
+            threshold: shared_types::AlertThreshold { min: None, max: Some(30.0) },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_rejects_invalid_ranges() {
+        let now = Utc::now();
+        let replay = |request| replay_sensor_readings(State(AppState::for_tests()), Json(request));
+
+        let too_long = replay_request(now - REPLAY_MAX_RANGE - chrono::TimeDelta::seconds(1), now);
+        assert_eq!(replay(too_long).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        let reversed = replay_request(now, now - chrono::TimeDelta::hours(1));
+        assert_eq!(replay(reversed).await.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        // Geçerli aralık, ama PostgreSQL yok
+        let week = replay_request(now - REPLAY_MAX_RANGE, now);
+        assert_eq!(replay(week).await.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[tokio::test]
+    async fn test_realtime_returns_ordered_batch_and_next_sequence() {
+        let state = AppState::for_tests();
+        for i in 0..(REALTIME_BATCH_LIMIT + 5) {
+            state.sensor_feed.publish(SensorData {
+                device_id: "edge-agent-001".into(),
+                sensor_type: "temperature".into(),
+                value: i as f64,
+                unit: "°C".into(),
+                timestamp: "2024-01-20T10:30:00Z".into(),
+                metadata: None,
+                received_at: None,
+                gateway_received_at: None,
+                stale: false,
+            });
+        }
+
+        let Json(batch) = realtime_sensors(State(state.clone()), Query(RealtimeParams { since: 2 })).await;
+        assert_eq!(batch.events.len(), REALTIME_BATCH_LIMIT);
+        assert_eq!(batch.events[0].sequence, 3);
+        assert!(batch.events.windows(2).all(|w| w[1].sequence == w[0].sequence + 1));
+        assert_eq!(batch.next_sequence, 52);
+
+        let Json(rest) = realtime_sensors(State(state.clone()), Query(RealtimeParams { since: batch.next_sequence })).await;
+        assert_eq!(rest.events.len(), 3);
+        assert_eq!(rest.next_sequence, 55);
+
+        // Sunucu yeniden başlamış gibi: client'ın since'i ileride → baştan oku
+        let Json(reset) = realtime_sensors(State(state), Query(RealtimeParams { since: 1000 })).await;
+        assert_eq!(reset.events[0].sequence, 1);
+    }
+
+    /// SSE akışından sonraki event'i oku (`event:`, `id:`, `data:` satırları)
+    async fn next_sse_event(body: &mut axum::body::BodyDataStream) -> String {
+        let mut event = String::new();
+        while !event.ends_with("\n\n") {
+            let chunk = tokio::time::timeout(Duration::from_secs(5), body.next()).await.unwrap().unwrap().unwrap();
+            event.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+        event
+    }
+
+    #[tokio::test]
+    async fn test_stream_filters_by_device_and_replays_after_last_event_id() {
+        let state = AppState::for_tests();
+        let publish = |device_id: &str, value: f64| state.sensor_feed.publish(SensorData { device_id: device_id.into(), ..reading("", value) });
+        publish("edge-agent-001", 20.0);
+        publish("edge-agent-002", 21.0);
+
+        let stream = |device_id: Option<&str>, last_event_id: Option<&str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(id) = last_event_id {
+                headers.insert("last-event-id", id.parse().unwrap());
+            }
+            let params = StreamParams { device_id: device_id.map(str::to_string) };
+            let state = state.clone();
+            async move {
+                let response = stream_sensors(State(state), Query(params), headers).await.into_response();
+                assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+                response.into_body().into_data_stream()
+            }
+        };
+        let mut live = stream(Some("edge-agent-001"), None).await;
+        let mut resumed = stream(None, Some("1")).await;
+        let mut restarted = stream(None, Some("1000")).await;
+
+        publish("edge-agent-002", 22.0);
+        publish("edge-agent-001", 23.0);
+
+        // Filtreli canlı akış: sadece bağlandıktan sonraki edge-agent-001 okuması
+        let event = next_sse_event(&mut live).await;
+        assert!(event.starts_with("event: reading\nid: 4\ndata: "), "{event}");
+        let data: serde_json::Value = serde_json::from_str(event.lines().nth(2).unwrap().strip_prefix("data: ").unwrap()).unwrap();
+        assert_eq!((data["sequence"].as_u64(), data["device_id"].as_str(), data["value"].as_f64()), (Some(4), Some("edge-agent-001"), Some(23.0)));
+
+        // Last-Event-ID: 1'den sonrakiler backlog'dan, sonra canlı
+        for id in 2..=4 {
+            assert!(next_sse_event(&mut resumed).await.contains(&format!("id: {id}\n")));
+        }
+        // Sunucu yeniden başlamış gibi: backlog baştan
+        assert!(next_sse_event(&mut restarted).await.contains("id: 1\n"));
+    }
+
+    /// Bir saat önce (retention penceresinin içinde, testin süresince sabit)
+    static RECENT: std::sync::LazyLock<String> =
+        std::sync::LazyLock::new(|| (Utc::now() - chrono::Duration::hours(1)).to_rfc3339());
+
+    fn reading(device_id: &str, value: f64) -> SensorData {
+        SensorData {
+            device_id: device_id.into(),
+            sensor_type: "temperature".into(),
+            value,
+            unit: "°C".into(),
+            timestamp: RECENT.clone(),
+            metadata: None,
+            received_at: None,
+            gateway_received_at: None,
+            stale: false,
+        }
+    }
+
+    #[test]
+    fn test_mark_stale_uses_reading_timestamp() {
+        let now = Utc::now();
+        let mut sensors = vec![reading("fresh", 1.0), reading("old", 2.0), reading("unknown", 3.0)];
+        sensors[0].timestamp = (now - chrono::Duration::seconds(30)).to_rfc3339();
+        sensors[1].timestamp = (now - chrono::Duration::hours(3)).to_rfc3339();
+        sensors[2].timestamp = "not a timestamp".into();
+
+        mark_stale(&mut sensors, chrono::TimeDelta::seconds(300), now);
+        let stale: Vec<_> = sensors.iter().map(|s| (s.device_id.as_str(), s.stale)).collect();
+        assert_eq!(stale, [("fresh", false), ("old", true), ("unknown", false)]);
+
+        // İşaret her listede yeniden hesaplanır (eşik yükseltilirse kalkar)
+        mark_stale(&mut sensors, chrono::TimeDelta::hours(4), now);
+        assert!(sensors.iter().all(|s| !s.stale));
+
+        // İstemci `stale` gönderemez
+        let ingested: SensorData = serde_json::from_value(serde_json::json!({
+            "device_id": "edge", "sensor_type": "temperature", "value": 1.0, "unit": "°C", "stale": true
+        }))
+        .unwrap();
+        assert!(!ingested.stale);
+    }
+
+    fn peer(last: u8) -> ConnectInfo<SocketAddr> {
+        ConnectInfo(SocketAddr::from(([10, 0, 0, last], 40000)))
+    }
+
+    #[tokio::test]
+    async fn test_ingest_enforces_batch_size_and_rate_limit() {
+        let state = AppState::for_tests();
+
+        let oversized = vec![reading("edge-agent-001", 1.0); INGEST_MAX_ITEMS + 1];
+        let result = ingest_sensor_batch(State(state.clone()), None, peer(1), JsonOrMsgPack(oversized)).await;
+        assert_eq!(result.unwrap_err(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Depolama yok: limit içindeki istekler 503, sonrası 429
+        for _ in 1..INGEST_RATE_LIMIT {
+            let result = ingest_sensor_batch(State(state.clone()), None, peer(1), JsonOrMsgPack(vec![])).await;
+            assert_eq!(result.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+        }
+        let result = ingest_sensor_batch(State(state.clone()), None, peer(1), JsonOrMsgPack(vec![])).await;
+        assert_eq!(result.unwrap_err(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Limit IP başına
+        let result = ingest_sensor_batch(State(state), None, peer(2), JsonOrMsgPack(vec![])).await;
+        assert_eq!(result.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_validate_reading() {
+        assert_eq!(validate_reading(&reading("edge-agent-001", 21.5)), Ok(()));
+        assert_eq!(validate_reading(&reading(" ", 21.5)).unwrap_err(), "device_id is empty");
+        assert_eq!(validate_reading(&reading("edge-agent-001", f64::NAN)).unwrap_err(), "value is not a finite number");
+    }
+
+    #[test]
+    fn test_timestamp_guard() {
+        let guard = TimestampGuard::from_config(&Config::default());
+        let now = "2025-06-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let check = |timestamp: &str| {
+            let mut data = SensorData { timestamp: timestamp.into(), ..reading("edge-agent-001", 21.5) };
+            guard.apply(&mut data, now).map(|clamped| (clamped, data.timestamp, data.received_at))
+        };
+        let received_at = Some(now.to_rfc3339());
+
+        // Geçerli: değişmez (5 dakikaya kadar ileri tarih tolere edilir)
+        for ok in ["2025-06-01T11:59:00Z", "2025-06-01T12:04:59+00:00", "2025-05-02T12:00:01Z"] {
+            assert_eq!(check(ok), Ok((false, ok.to_string(), received_at.clone())));
+        }
+        // İleri tarihli ve eksik: alış zamanına çekilir
+        for clamped in ["2099-01-01T00:00:00Z", "2025-06-01T12:05:01Z", "", "  "] {
+            assert_eq!(check(clamped), Ok((true, now.to_rfc3339(), received_at.clone())), "{clamped:?}");
+        }
+        // Retention penceresinden eski veya geçersiz: reddedilir
+        assert_eq!(
+            check("1970-01-01T00:00:00Z").unwrap_err(),
+            "timestamp older than retention window: 1970-01-01T00:00:00Z"
+        );
+        assert_eq!(check("yesterday").unwrap_err(), "invalid timestamp: yesterday");
+
+        // Sınırlar yapılandırmadan gelir
+        let strict = TimestampGuard::from_config(&Config { max_future_skew_secs: 0, max_reading_age_days: 1, ..Config::default() });
+        assert_eq!(strict, TimestampGuard { max_future_skew: chrono::TimeDelta::zero(), max_age: chrono::TimeDelta::days(1) });
+
+        // Çok büyük sınırlar panik yerine sınırsız sayılır
+        let unbounded = TimestampGuard::from_config(&Config { max_future_skew_secs: u64::MAX, max_reading_age_days: u64::MAX, ..Config::default() });
+        assert_eq!(unbounded, TimestampGuard { max_future_skew: chrono::TimeDelta::MAX, max_age: chrono::TimeDelta::MAX });
+        for timestamp in ["1970-01-01T00:00:00Z", "2099-01-01T00:00:00Z"] {
+            let mut data = SensorData { timestamp: timestamp.into(), ..reading("edge-agent-001", 21.5) };
+            assert_eq!(unbounded.apply(&mut data, now), Ok(false), "{timestamp}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_sensor_data_rejects_ancient_readings() {
+        let ancient = SensorData { timestamp: "1970-01-01T00:00:00Z".into(), ..reading("edge-agent-001", 21.5) };
+        let result = add_sensor_data(State(AppState::for_tests()), None, JsonOrMsgPack(ancient)).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        // Geçerli okuma depolamaya ulaşır (test state'inde depolama yok)
+        let result = add_sensor_data(State(AppState::for_tests()), None, JsonOrMsgPack(reading("edge-agent-001", 21.5))).await;
+        assert_eq!(result.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_strict_validation_rejects_mismatched_readings() {
+        let state = AppState::for_tests();
+        let humidity_in_celsius = SensorData { sensor_type: "humidity".into(), ..reading("edge-agent-001", 21.5) };
+        let add = |data: SensorData| add_sensor_data(State(state.clone()), None, JsonOrMsgPack(data));
+
+        // Kapalıyken (varsayılan) depolamaya ulaşır
+        assert_eq!(add(humidity_in_celsius.clone()).await.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+
+        state.cfg.write().await.strict_sensor_validation = true;
+        assert_eq!(add(humidity_in_celsius.clone()).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        assert_eq!(add(reading("edge-agent-001", 400.0)).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        // Uyumlu okuma ve tipi bilinmeyen sensör geçer
+        assert_eq!(add(reading("edge-agent-001", 21.5)).await.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+        let unknown = SensorData { sensor_type: "pressure".into(), unit: "hPa".into(), ..reading("edge-agent-001", 1013.0) };
+        assert_eq!(add(unknown).await.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let reason = check_sensor_type(&humidity_in_celsius).unwrap_err();
+        assert!(reason.contains("does not match humidity"), "{reason}");
+    }
+
+    #[tokio::test]
+    async fn test_metadata_size_limit_boundary() {
+        let state = AppState::for_tests();
+        state.cfg.write().await.max_metadata_bytes = 64;
+        // JSON string'i tırnaklarla birlikte `len + 2` byte
+        let with_metadata = |len: usize| SensorData {
+            metadata: Some(serde_json::Value::String("x".repeat(len - 2))),
+            ..reading("edge-agent-001", 21.5)
+        };
+        let add = |data: SensorData| add_sensor_data(State(state.clone()), None, JsonOrMsgPack(data));
+
+        // Tam sınırda kabul edilir (depolama olmadığı için 503)
+        assert_eq!(add(with_metadata(64)).await.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(add(with_metadata(65)).await.unwrap_err(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(add(reading("edge-agent-001", 21.5)).await.unwrap_err(), StatusCode::SERVICE_UNAVAILABLE);
+
+        assert_eq!(check_metadata_size(&with_metadata(65), 64).unwrap_err(), "metadata is 65 bytes (max 64)");
+        assert!(check_metadata_size(&with_metadata(64), 64).is_ok());
+    }
+
+    #[test]
+    fn test_missing_timestamp_deserializes_empty() {
+        let data: SensorData = serde_json::from_value(serde_json::json!({
+            "device_id": "edge-agent-001", "sensor_type": "temperature", "value": 21.5, "unit": "°C",
+            "metadata": null, "received_at": "2000-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert_eq!(data.timestamp, "");
+        assert_eq!(data.gateway_received_at, None);
+
+        // İstemcinin gönderdiği received_at ezilir
+        let mut data = data;
+        let now = Utc::now();
+        assert_eq!(TimestampGuard::from_config(&Config::default()).apply(&mut data, now), Ok(true));
+        assert_eq!(data.received_at, Some(now.to_rfc3339()));
+    }
+
+    /// Migration'ları uygulanmış gerçek PostgreSQL gerektirir:
+    /// `TEST_DATABASE_URL=postgres://... cargo test -p api-server -- --ignored`
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    /// Sorgunun `EXPLAIN ANALYZE` çıktısı (satırlar birleştirilmiş)
+    ///
+    /// Test tablosu küçük olabileceğinden seq scan kapatılır; böylece
+    /// planner'ın index'i bu sorgu şekli için kullanabildiği doğrulanır.
+    async fn explain(db: &PgPool, sql: &str) -> String {
+        let mut tx = db.begin().await.unwrap();
+        sqlx::query("SET LOCAL enable_seqscan = off").execute(&mut *tx).await.unwrap();
+        let plan: Vec<String> = sqlx::query_scalar(&format!("EXPLAIN ANALYZE {sql}"))
+            .fetch_all(&mut *tx)
+            .await
+            .unwrap();
+        plan.join("\n")
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_latest_readings_query_uses_compound_index() {
+        let db = test_db().await;
+        let plan = explain(
+            &db,
+            "SELECT * FROM sensor_readings
+             WHERE device_id = 'edge-agent-001' AND sensor_type = 'temperature'
+             ORDER BY timestamp DESC LIMIT 100",
+        )
+        .await;
+
+        assert!(plan.contains("Index Scan using sensor_readings_device_type_ts"), "plan:\n{plan}");
+        // Index sırası ORDER BY'ı karşılar, ayrı sort adımı olmamalı
+        assert!(!plan.contains("Sort"), "plan:\n{plan}");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_retention_query_uses_timestamp_index() {
+        let db = test_db().await;
+        let plan = explain(
+            &db,
+            "SELECT id FROM sensor_readings
+             WHERE timestamp < now() - interval '30 days'
+             ORDER BY timestamp DESC LIMIT 1000",
+        )
+        .await;
+        assert!(plan.contains("Index Scan using sensor_readings_ts"), "plan:\n{plan}");
+
+        let brin: Option<String> = sqlx::query_scalar(
+            "SELECT am.amname::TEXT FROM pg_class c JOIN pg_am am ON am.oid = c.relam
+             WHERE c.relname = 'sensor_readings_ts_brin'"
+        )
+        .fetch_optional(&db)
+        .await
+        .unwrap();
+        assert_eq!(brin.as_deref(), Some("brin"));
+    }
+
+    /// Cihaz ID'sine göre `sensor_readings` satır sayısı
+    async fn count_readings(db: &PgPool, device_prefix: &str) -> i64 {
+        sqlx::query_scalar("SELECT count(*) FROM sensor_readings WHERE device_id LIKE $1 || '%'")
+            .bind(device_prefix)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    async fn delete_readings(db: &PgPool, device_prefix: &str) {
+        sqlx::query("DELETE FROM sensor_readings WHERE device_id LIKE $1 || '%'")
+            .bind(device_prefix)
+            .execute(db)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_ingest_batch_stores_valid_items() {
+        let db = test_db().await;
+        let device = format!("ingest-{}", uuid::Uuid::new_v4());
+        let state = AppState::for_tests_with_db(db.clone());
+
+        let batch = vec![
+            reading(&device, 21.5),
+            SensorData { timestamp: "yesterday".into(), ..reading(&device, 22.0) },
+            reading("", 22.5),
+            SensorData { unit: "celsius".into(), ..reading(&device, 23.0) },
+            SensorData { timestamp: "2099-01-01T00:00:00Z".into(), ..reading(&device, 24.0) },
+            SensorData { timestamp: "1970-01-01T00:00:00Z".into(), ..reading(&device, 25.0) },
+            SensorData { timestamp: String::new(), ..reading(&device, 26.0) },
+        ];
+        let before = Utc::now();
+        let Json(result) = ingest_sensor_batch(State(state), None, peer(1), JsonOrMsgPack(batch)).await.unwrap();
+        assert_eq!(result.accepted, 4);
+        assert_eq!(
+            result.rejected,
+            vec![
+                (1, "invalid timestamp: yesterday".to_string()),
+                (2, "device_id is empty".to_string()),
+                (5, "timestamp older than retention window: 1970-01-01T00:00:00Z".to_string()),
+            ]
+        );
+        assert_eq!(result.clamped, vec![4, 6]);
+
+        // Düzeltilen okumalarda timestamp = received_at; hepsinde received_at dolu
+        let rows: Vec<(f64, DateTime<Utc>, Option<DateTime<Utc>>)> = sqlx::query_as(
+            "SELECT value, timestamp, received_at FROM sensor_readings WHERE device_id = $1 ORDER BY value",
+        )
+        .bind(&device)
+        .fetch_all(&db)
+        .await
+        .unwrap();
+        assert_eq!(rows.iter().map(|r| r.0).collect::<Vec<_>>(), vec![21.5, 23.0, 24.0, 26.0]);
+        for (value, timestamp, received_at) in &rows {
+            let received_at = received_at.expect("received_at stored");
+            assert!(received_at >= before - chrono::Duration::milliseconds(1));
+            assert_eq!(*timestamp == received_at, *value >= 24.0, "value {value}");
+        }
+
+        let units: Vec<String> = sqlx::query_scalar("SELECT DISTINCT unit FROM sensor_readings WHERE device_id = $1")
+            .bind(&device)
+            .fetch_all(&db)
+            .await
+            .unwrap();
+        assert_eq!(units, vec!["°C"]);
+        delete_readings(&db, &device).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_posted_reading_arrives_on_stream() {
+        let db = test_db().await;
+        let device = format!("stream-{}", uuid::Uuid::new_v4());
+        let state = AppState::for_tests_with_db(db.clone());
+
+        let response = stream_sensors(State(state.clone()), Query(StreamParams { device_id: Some(device.clone()) }), HeaderMap::new()).await;
+        let mut body = response.into_response().into_body().into_data_stream();
+        let _ = add_sensor_data(State(state.clone()), None, JsonOrMsgPack(reading(&device, 21.5))).await.unwrap();
+
+        let event = next_sse_event(&mut body).await;
+        let id = state.sensor_feed.latest();
+        assert!(event.starts_with(&format!("event: reading\nid: {id}\n")), "{event}");
+        assert!(event.contains(&format!("\"device_id\":\"{device}\"")), "{event}");
+        delete_readings(&db, &device).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_gateway_received_at_stored_and_returned_by_history() {
+        let db = test_db().await;
+        let device = format!("gateway-ts-{}", uuid::Uuid::new_v4());
+        let state = AppState::for_tests_with_db(db.clone());
+
+        let gateway_received_at = Utc::now() - chrono::Duration::seconds(2);
+        let batch = vec![
+            SensorData { gateway_received_at: Some(gateway_received_at.to_rfc3339()), ..reading(&device, 21.5) },
+            // Parse edilemeyen değer okumayı reddettirmez, NULL saklanır
+            SensorData { gateway_received_at: Some("soon".into()), ..reading(&device, 22.0) },
+            reading(&device, 22.5),
+        ];
+        let Json(result) = ingest_sensor_batch(State(state.clone()), None, peer(1), JsonOrMsgPack(batch)).await.unwrap();
+        assert_eq!(result.accepted, 3);
+
+        let path = Path((device.clone(), "temperature".to_string()));
+        let response = sensor_history(State(state), path, Query(TimeRangeParams::default()), Query(DownsampleParams::default()), Query(UnitParams::default()), Query(FillParams::default()))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let mut readings: Vec<SensorData> = serde_json::from_slice(&body).unwrap();
+        readings.sort_by(|a, b| a.value.total_cmp(&b.value));
+        let stored: Vec<_> = readings
+            .iter()
+            .map(|r| r.gateway_received_at.as_deref().map(|t| DateTime::parse_from_rfc3339(t).unwrap().with_timezone(&Utc)))
+            .collect();
+        // TIMESTAMPTZ mikrosaniye hassasiyetinde saklar
+        let expected = DateTime::from_timestamp_micros(gateway_received_at.timestamp_micros()).unwrap();
+        assert_eq!(stored, vec![Some(expected), None, None]);
+        delete_readings(&db, &device).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_ingest_batch_strict_validation_reports_reason() {
+        let db = test_db().await;
+        let device = format!("strict-{}", uuid::Uuid::new_v4());
+        let state = AppState::for_tests_with_db(db.clone());
+        state.cfg.write().await.strict_sensor_validation = true;
+
+        let batch = vec![
+            reading(&device, 21.5),
+            SensorData { sensor_type: "humidity".into(), ..reading(&device, 40.0) },
+            SensorData { sensor_type: "motion".into(), unit: "bool".into(), ..reading(&device, 2.0) },
+            SensorData { unit: "°F".into(), ..reading(&device, 70.0) },
+        ];
+        let Json(result) = ingest_sensor_batch(State(state), None, peer(1), JsonOrMsgPack(batch)).await.unwrap();
+        assert_eq!(result.accepted, 2);
+        let rejected: Vec<_> = result.rejected.iter().map(|(index, _)| *index).collect();
+        assert_eq!(rejected, [1, 2]);
+        assert!(result.rejected[0].1.contains("unit '°C' does not match humidity"), "{:?}", result.rejected);
+        assert!(result.rejected[1].1.contains("must be 0 or 1"), "{:?}", result.rejected);
+        delete_readings(&db, &device).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_ingest_batch_rejects_oversized_metadata() {
+        let db = test_db().await;
+        let device = format!("metadata-{}", uuid::Uuid::new_v4());
+        let state = AppState::for_tests_with_db(db.clone());
+        let max = state.cfg.read().await.max_metadata_bytes;
+        let with_metadata = |len: usize| SensorData {
+            metadata: Some(serde_json::Value::String("x".repeat(len - 2))),
+            ..reading(&device, 21.5)
+        };
+
+        let batch = vec![with_metadata(max), with_metadata(max + 1)];
+        let Json(result) = ingest_sensor_batch(State(state), None, peer(1), JsonOrMsgPack(batch)).await.unwrap();
+        assert_eq!(result.accepted, 1);
+        assert_eq!(result.rejected, vec![(1, format!("metadata is {} bytes (max {max})", max + 1))]);
+        delete_readings(&db, &device).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_ingest_publishes_accepted_readings_to_event_sink() {
+        let db = test_db().await;
+        let device = format!("sink-{}", uuid::Uuid::new_v4());
+        let sink = crate::event_sink::MockSink::default();
+        let state = AppState { event_queue: Some(crate::event_sink::spawn(sink.clone(), 16)), ..AppState::for_tests_with_db(db.clone()) };
+
+        let batch = vec![
+            reading(&device, 21.5),
+            SensorData { timestamp: "yesterday".into(), ..reading(&device, 22.0) },
+            reading(&device, 23.0),
+        ];
+        let Json(result) = ingest_sensor_batch(State(state), None, peer(1), JsonOrMsgPack(batch)).await.unwrap();
+        assert_eq!(result.accepted, 2);
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        let events: Vec<_> = sink
+            .events()
+            .into_iter()
+            .map(|event| match event {
+                IngestEvent::Reading(data) => {
+                    assert!(data.received_at.is_some());
+                    IngestEvent::Reading(SensorData { received_at: None, ..data })
+                }
+                other => other,
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![IngestEvent::Reading(reading(&device, 21.5)), IngestEvent::Reading(reading(&device, 23.0))]
+        );
+        delete_readings(&db, &device).await;
+    }
+
+    /// Yük testi: gerçek HTTP sunucusuna çok sayıda kaynak IP'den eş zamanlı batch'ler
+    ///
+    /// Her client farklı bir loopback adresinden (127.0.0.x) bağlanır; böylece
+    /// IP başına limit client'ları birbirinden ayırır.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_ingest_load() {
+        const CLIENTS: u8 = 20;
+        const REQUESTS_PER_CLIENT: usize = INGEST_RATE_LIMIT as usize;
+
+        let db = test_db().await;
+        let prefix = format!("load-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let state = AppState::for_tests_with_db(db.clone());
+        let app = axum::Router::new()
+            .route("/v1/sensors/ingest", axum::routing::post(ingest_sensor_batch))
+            .with_state(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1/sensors/ingest", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
+        });
+
+        let clients = (0..CLIENTS).map(|c| {
+            let url = url.clone();
+            let device = format!("{prefix}-{c}");
+            tokio::spawn(async move {
+                let http = reqwest::Client::builder()
+                    .local_address(std::net::IpAddr::from([127, 0, 0, 10 + c]))
+                    .build()
+                    .unwrap();
+                let mut accepted = 0;
+                for _ in 0..REQUESTS_PER_CLIENT {
+                    let batch = vec![reading(&device, 21.5); INGEST_MAX_ITEMS];
+                    let response = http.post(&url).json(&batch).send().await.unwrap();
+                    assert_eq!(response.status(), reqwest::StatusCode::OK);
+                    let result: IngestResult = response.json().await.unwrap();
+                    assert!(result.rejected.is_empty());
+                    accepted += result.accepted;
+                }
+                accepted
+            })
+        });
+        let mut accepted = 0;
+        for client in clients.collect::<Vec<_>>() {
+            accepted += client.await.unwrap();
+        }
+
+        let expected = CLIENTS as usize * REQUESTS_PER_CLIENT * INGEST_MAX_ITEMS;
+        assert_eq!(accepted, expected);
+        assert_eq!(count_readings(&db, &prefix).await, expected as i64);
+
+        // Tek IP'den ani yük: pencere başına sadece INGEST_RATE_LIMIT istek geçer
+        let http = reqwest::Client::builder()
+            .local_address(std::net::IpAddr::from([127, 0, 0, 200]))
+            .build()
+            .unwrap();
+        let burst = (0..INGEST_RATE_LIMIT + 5).map(|_| http.post(&url).json(&Vec::<SensorData>::new()).send());
+        let statuses: Vec<_> = futures_util::future::join_all(burst)
+            .await
+            .into_iter()
+            .map(|r| r.unwrap().status())
+            .collect();
+        let limited = statuses.iter().filter(|s| **s == reqwest::StatusCode::TOO_MANY_REQUESTS).count();
+        assert_eq!(limited, 5, "statuses: {statuses:?}");
+
+        delete_readings(&db, &prefix).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_history_and_stats_stream_across_chunks() {
+        let db = test_db().await;
+        let device_id = format!("history-{}", uuid::Uuid::new_v4().simple());
+        sqlx::query(
+            "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp)
+             SELECT $1, 'temperature', i, '°C', now() - make_interval(secs => (2500 - i)::DOUBLE PRECISION)
+             FROM generate_series(1, 2500) AS i",
+        )
+        .bind(&device_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        let state = AppState::for_tests_with_db(db.clone());
+        let path = || Path((device_id.clone(), "temperature".to_string()));
+
+        // 3 chunk (1000 + 1000 + 500) tek bir geçerli JSON array oluşturur
+        let range = TimeRangeParams { limit: Some(2500), ..Default::default() };
+        let response = sensor_history(State(state.clone()), path(), Query(range), Query(DownsampleParams::default()), Query(UnitParams::default()), Query(FillParams::default())).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let readings: Vec<SensorData> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(readings.len(), 2500);
+        assert_eq!((readings[0].value, readings[2499].value), (1.0, 2500.0));
+
+        // Varsayılan limit ve boş sonuç
+        let response = sensor_history(State(state.clone()), path(), Query(TimeRangeParams::default()), Query(DownsampleParams::default()), Query(UnitParams::default()), Query(FillParams::default()))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<Vec<SensorData>>(&body).unwrap().len(), DEFAULT_HISTORY_LIMIT as usize);
+        let path_none = Path(("no-such-device".to_string(), "temperature".to_string()));
+        let response = sensor_history(State(state.clone()), path_none, Query(TimeRangeParams::default()), Query(DownsampleParams::default()), Query(UnitParams::default()), Query(FillParams::default()))
+            .await
+            .unwrap();
+        assert_eq!(&axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap()[..], b"[]");
+
+        let Json(stats) = sensor_stats(State(state), path(), Query(TimeRangeParams::default()), Query(UnitParams::default())).await.unwrap();
+        assert_eq!(stats.count, 2500);
+        assert_eq!((stats.min, stats.max, stats.mean), (Some(1.0), Some(2500.0), Some(1250.5)));
+
+        delete_readings(&db, &device_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_export_influx_line_protocol_from_db() {
+        let db = test_db().await;
+        let device_id = format!("export {}", uuid::Uuid::new_v4().simple());
+        sqlx::query(
+            "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp)
+             SELECT $1, 'temperature', i * 0.5, '°C', now() - make_interval(secs => (100 - i)::DOUBLE PRECISION)
+             FROM generate_series(1, 100) AS i",
+        )
+        .bind(&device_id)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let path = Path((device_id.clone(), "temperature".to_string()));
+        let response = export_influx_line_protocol(State(AppState::for_tests_with_db(db.clone())), path, Query(TimeRangeParams::default()))
+            .await
+            .unwrap();
+        let disposition = response.headers()[header::CONTENT_DISPOSITION].to_str().unwrap().to_string();
+        assert!(disposition.starts_with(&format!("attachment; filename=\"{}-temperature.lp\"", device_id.replace(' ', "_"))), "{disposition}");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        let lines: Vec<_> = body.lines().collect();
+        assert_eq!(lines.len(), 100);
+        let tags = format!("sensor_readings,device_id={},sensor_type=temperature", device_id.replace(' ', "\\ "));
+        assert!(lines.iter().all(|line| line.starts_with(&format!("{tags} value="))), "{}", lines[0]);
+        assert!(lines[0].contains(" value=0.5 ") && lines[99].contains(" value=50 "), "{} / {}", lines[0], lines[99]);
+
+        delete_readings(&db, &device_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_unit_conversion_for_history_stats_and_summaries() {
+        let db = test_db().await;
+        let device_id = format!("units-{}", uuid::Uuid::new_v4().simple());
+        sqlx::query(
+            "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp)
+             VALUES ($1, 'temperature', 0, '°C', now() - interval '2 minutes'),
+                    ($1, 'temperature', 100, '°C', now() - interval '1 minute'),
+                    ($1, 'humidity', 40, '%', now())",
+        )
+        .bind(&device_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        let bucket_start = Utc::now() - chrono::Duration::hours(1);
+        sqlx::query(
+            "INSERT INTO sensor_summaries (device_id, sensor_type, bucket_start, min_value, max_value, avg_value, count)
+             VALUES ($1, 'temperature', date_trunc('hour', $2::timestamptz), 0, 100, 50, 2)",
+        )
+        .bind(&device_id)
+        .bind(bucket_start)
+        .execute(&db)
+        .await
+        .unwrap();
+        let state = AppState::for_tests_with_db(db.clone());
+        let path = |sensor_type: &str| Path((device_id.clone(), sensor_type.to_string()));
+        let unit = |unit: &str| Query(UnitParams { unit: Some(unit.into()) });
+        let history = |sensor_type: &'static str, params: Query<UnitParams>| {
+            let state = state.clone();
+            let path = path(sensor_type);
+            async move {
+                sensor_history(State(state), path, Query(TimeRangeParams::default()), Query(DownsampleParams::default()), params, Query(FillParams::default())).await
+            }
+        };
+
+        let response = history("temperature", unit("fahrenheit")).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let readings: Vec<SensorData> = serde_json::from_slice(&body).unwrap();
+        let converted: Vec<_> = readings.iter().map(|r| (r.value, r.unit.as_str())).collect();
+        assert_eq!(converted, vec![(32.0, "°F"), (212.0, "°F")]);
+
+        let Json(stats) = sensor_stats(State(state.clone()), path("temperature"), Query(TimeRangeParams::default()), unit("°F"))
+            .await
+            .unwrap();
+        assert_eq!((stats.min, stats.max, stats.mean), (Some(32.0), Some(212.0), Some(122.0)));
+        assert!((stats.std_dev.unwrap() - 90.0).abs() < 1e-9);
+
+        let Json(summaries) = sensor_summaries(State(state.clone()), path("temperature"), Query(SummaryParams::default()), unit("fahrenheit"), Query(FillParams::default()))
+            .await
+            .unwrap();
+        assert_eq!(summaries.len(), 1);
+        let SummaryPoint::Summary(summary) = &summaries[0] else { panic!("unexpected gap: {summaries:?}") };
+        assert_eq!((summary.min_value, summary.max_value, summary.avg_value), (32.0, 212.0, 122.0));
+
+        // Nem °F'a çevrilemez, bilinmeyen birim reddedilir
+        assert_eq!(history("humidity", unit("fahrenheit")).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        assert_eq!(history("temperature", unit("rankine")).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        let result = sensor_stats(State(state.clone()), path("humidity"), Query(TimeRangeParams::default()), unit("°F")).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        sqlx::query("DELETE FROM sensor_summaries WHERE device_id = $1").bind(&device_id).execute(&db).await.unwrap();
+        delete_readings(&db, &device_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_sensor_heatmap_fills_missing_buckets() {
+        let db = test_db().await;
+        let device_id = format!("heatmap-{}", uuid::Uuid::new_v4().simple());
+        sqlx::query(
+            "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp)
+             VALUES ($1, 'temperature', 20, '°C', '2024-01-20T10:05:00Z'),
+                    ($1, 'temperature', 22, '°C', '2024-01-20T10:55:00Z'),
+                    ($1, 'temperature', 30, '°C', '2024-01-20T12:00:00Z'),
+                    ($1, 'humidity', 55, '%', '2024-01-20T11:30:00Z'),
+                    ($1, 'pressure', 1013, 'hPa', '2024-01-20T10:30:00Z'),
+                    ($1, 'temperature', 99, '°C', '2024-01-20T13:00:00Z')",
+        )
+        .bind(&device_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        let state = AppState::for_tests_with_db(db.clone());
+        let params = HeatmapParams {
+            device_id: device_id.clone(),
+            sensor_types: vec!["temperature".into(), "humidity".into(), "motion".into()],
+            from: "2024-01-20T10:00:00Z".parse().unwrap(),
+            to: "2024-01-20T12:59:59Z".parse().unwrap(),
+            bucket_minutes: 60,
+        };
+
+        let Json(heatmap) = get_sensor_heatmap(State(state), Query(params)).await.unwrap();
+        let buckets: Vec<String> = heatmap.buckets.iter().map(|b| b.to_rfc3339()).collect();
+        assert_eq!(buckets, vec!["2024-01-20T10:00:00+00:00", "2024-01-20T11:00:00+00:00", "2024-01-20T12:00:00+00:00"]);
+        assert_eq!(heatmap.sensor_types, vec!["temperature", "humidity", "motion"]);
+        assert_eq!(
+            heatmap.values,
+            vec![vec![Some(21.0), None, None], vec![None, Some(55.0), None], vec![Some(30.0), None, None]]
+        );
+
+        delete_readings(&db, &device_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_history_downsampled_with_lttb() {
+        let db = test_db().await;
+        let device_id = format!("lttb-{}", uuid::Uuid::new_v4().simple());
+        // 10.000 okumalık sinüs + tek bir tepe; 2 NaN okuma
+        sqlx::query(
+            "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp)
+             SELECT $1, 'temperature',
+                    CASE WHEN i = 7777 THEN 100 WHEN i IN (10, 20) THEN 'NaN'::float8 ELSE 20 + sin(i / 100.0) END,
+                    '°C', now() - make_interval(secs => (10000 - i)::DOUBLE PRECISION)
+             FROM generate_series(1, 10000) AS i",
+        )
+        .bind(&device_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        let state = AppState::for_tests_with_db(db.clone());
+        let path = Path((device_id.clone(), "temperature".to_string()));
+
+        let range = TimeRangeParams { limit: Some(10_000), ..Default::default() };
+        let downsample = DownsampleParams { downsample: Some(DownsampleMethod::Lttb), points: Some(500) };
+        let response = sensor_history(State(state), path, Query(range), Query(downsample), Query(UnitParams::default()), Query(FillParams::default())).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let history: DownsampledHistory = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!((history.source_points, history.skipped_points, history.readings.len()), (10_000, 2, 500));
+        assert!(history.readings.iter().any(|r| r.value == 100.0));
+        assert_eq!(history.readings[0].value, 20.0 + (1.0f64 / 100.0).sin());
+        assert!(history.readings.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+        delete_readings(&db, &device_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_history_and_summaries_fill_gaps() {
+        let db = test_db().await;
+        let device_id = format!("fill-{}", uuid::Uuid::new_v4().simple());
+        // Dakikalık okumalar; 10:03-10:05 arası ve 10:06'dan sonra 4 saat kopukluk
+        sqlx::query(
+            "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp)
+             VALUES ($1, 'temperature', 20, '°C', '2024-01-20T10:00:00Z'),
+                    ($1, 'temperature', 21, '°C', '2024-01-20T10:01:00Z'),
+                    ($1, 'temperature', 22, '°C', '2024-01-20T10:02:00Z'),
+                    ($1, 'temperature', 26, '°C', '2024-01-20T10:06:00Z'),
+                    ($1, 'temperature', 27, '°C', '2024-01-20T14:06:00Z')",
+        )
+        .bind(&device_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO sensor_summaries (device_id, sensor_type, bucket_start, min_value, max_value, avg_value, count)
+             VALUES ($1, 'temperature', '2024-01-20T10:00:00Z', 20, 26, 22, 4),
+                    ($1, 'temperature', '2024-01-20T13:00:00Z', 27, 27, 27, 1)",
+        )
+        .bind(&device_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        let state = AppState::for_tests_with_db(db.clone());
+        let path = || Path((device_id.clone(), "temperature".to_string()));
+        let history = |fill: FillParams| {
+            let (state, path) = (state.clone(), path());
+            async move {
+                let response = sensor_history(
+                    State(state),
+                    path,
+                    Query(TimeRangeParams::default()),
+                    Query(DownsampleParams::default()),
+                    Query(UnitParams::default()),
+                    Query(fill),
+                )
+                .await?;
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                Ok::<_, StatusCode>(serde_json::from_slice::<Vec<HistoryPoint>>(&body).unwrap())
+            }
+        };
+        let gaps = |points: &[HistoryPoint]| -> Vec<(String, Option<f64>)> {
+            points
+                .iter()
+                .filter_map(|p| match p {
+                    HistoryPoint::Gap(gap) => Some((gap.timestamp.clone(), gap.value)),
+                    HistoryPoint::Reading(_) => None,
+                })
+                .collect()
+        };
+
+        // Medyan aralık 1 dk; 4 saatlik boşluk max_gap'ten uzun → boş kalır
+        let fill = FillParams { fill: FillMethod::Linear, interval: None, max_gap: Some(600) };
+        let points = history(fill).await.unwrap();
+        assert_eq!(points.len(), 8);
+        assert_eq!(
+            gaps(&points),
+            vec![
+                ("2024-01-20T10:03:00+00:00".to_string(), Some(23.0)),
+                ("2024-01-20T10:04:00+00:00".to_string(), Some(24.0)),
+                ("2024-01-20T10:05:00+00:00".to_string(), Some(25.0)),
+            ]
+        );
+        let HistoryPoint::Gap(gap) = &points[3] else { panic!("expected gap: {points:?}") };
+        assert_eq!((gap.device_id.as_str(), gap.unit.as_str(), gap.filled), (device_id.as_str(), "°C", true));
+
+        // null: değerler null serialize edilir
+        let fill = FillParams { fill: FillMethod::Null, interval: Some(120), max_gap: Some(600) };
+        let points = history(fill).await.unwrap();
+        assert_eq!(gaps(&points), vec![("2024-01-20T10:04:00+00:00".to_string(), None)]);
+        let json = serde_json::to_value(&points[3]).unwrap();
+        assert_eq!((json["value"].clone(), json["filled"].clone()), (serde_json::Value::Null, serde_json::Value::Bool(true)));
+
+        // Sınırsız boşluk saniyelik aralıkla MAX_FILL_POINTS'i aşar
+        let fill = FillParams { fill: FillMethod::Previous, interval: Some(1), max_gap: None };
+        assert_eq!(history(fill).await.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        // Özetler: aralık çözünürlükten (1 saat), eksik bucket'lar count 0
+        let fill = FillParams { fill: FillMethod::Previous, interval: None, max_gap: None };
+        let Json(summaries) = sensor_summaries(State(state.clone()), path(), Query(SummaryParams::default()), Query(UnitParams::default()), Query(fill))
+            .await
+            .unwrap();
+        let filled: Vec<_> = summaries
+            .iter()
+            .map(|p| match p {
+                SummaryPoint::Summary(s) => (s.bucket_start.to_rfc3339(), s.avg_value, s.count),
+                SummaryPoint::Gap(g) => (g.bucket_start.to_rfc3339(), g.avg_value.unwrap(), g.count),
+            })
+            .collect();
+        assert_eq!(
+            filled,
+            vec![
+                ("2024-01-20T10:00:00+00:00".to_string(), 22.0, 4),
+                ("2024-01-20T11:00:00+00:00".to_string(), 22.0, 0),
+                ("2024-01-20T12:00:00+00:00".to_string(), 22.0, 0),
+                ("2024-01-20T13:00:00+00:00".to_string(), 27.0, 1),
+            ]
+        );
+
+        sqlx::query("DELETE FROM sensor_summaries WHERE device_id = $1").bind(&device_id).execute(&db).await.unwrap();
+        delete_readings(&db, &device_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_replay_triggers_on_known_breaches() {
+        let db = test_db().await;
+        let device_id = format!("replay-{}", uuid::Uuid::new_v4().simple());
+        let start = "2024-01-20T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        // Dakikalık 60 okuma; sadece 3'ü 30'un üstünde
+        let values: Vec<f64> = (0..60).map(|i| if [10, 25, 47].contains(&i) { 31.0 + i as f64 } else { 22.0 }).collect();
+        for (minute, value) in values.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO sensor_readings (device_id, sensor_type, value, unit, timestamp) VALUES ($1, 'temperature', $2, '°C', $3)",
+            )
+            .bind(&device_id)
+            .bind(value)
+            .bind(start + chrono::TimeDelta::minutes(minute as i64))
+            .execute(&db)
+            .await
+            .unwrap();
+        }
+        let state = AppState::for_tests_with_db(db.clone());
+
+        let request = ReplayRequest { device_id: device_id.clone(), ..replay_request(start, start + chrono::TimeDelta::hours(1)) };
+        let Json(result) = replay_sensor_readings(State(state.clone()), Json(request)).await.unwrap();
+        assert_eq!((result.total_readings, result.breach_count), (60, 3));
+        let triggered: Vec<_> = result.alerts_triggered.iter().map(|a| (a.value, a.triggered_at)).collect();
+        assert_eq!(
+            triggered,
+            [10, 25, 47].map(|i| (31.0 + i as f64, start + chrono::TimeDelta::minutes(i))),
+        );
+
+        // Aralık sınırları dahil: ilk 11 okuma
+        let request = ReplayRequest {
+            device_id: device_id.clone(),
+            ..replay_request(start, start + chrono::TimeDelta::minutes(10))
+        };
+        let Json(result) = replay_sensor_readings(State(state), Json(request)).await.unwrap();
+        assert_eq!((result.total_readings, result.breach_count), (11, 1));
+
+        delete_readings(&db, &device_id).await;
+    }
 }
@@ -0,0 +1,329 @@
+//! Sensör Simülasyonu
+//!
+//! Gerçek edge agent çalıştırmadan dashboard ve alarm testleri için sentetik
+//! okuma üretir. Okumalar normal ingest yolundan (`sensors::store_reading`)
+//! geçer: PostgreSQL'e yazılır, Redis cache'i ve realtime akış güncellenir,
+//! eşik alarmları çalışır. Üretilen okumaların `metadata`'sında
+//! `{"simulated": true}` bulunur.
+//!
+//! `simulation_enabled` bayrağı varsayılan olarak kapalıdır; geliştirme
+//! ortamında `FEATURE_SIMULATION_ENABLED=true` ile açılır.
+//!
+//! # Endpoint'ler
+//! - POST /v1/sensors/{device_id}/simulate - Sentetik okuma üret (admin)
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use shared_types::SensorType;
+
+use crate::feature_flags::check_feature;
+use crate::routes::sensors::{self, SensorData};
+use crate::state::AppState;
+
+/// Tek istekte üretilebilecek en fazla okuma
+pub const SIMULATE_MAX_COUNT: u32 = 10_000;
+
+/// Değer deseni
+///
+/// ```json
+/// {"sine": {"amplitude": 3.0, "offset": 22.0, "period_secs": 3600}}
+/// {"random": {"min": 40.0, "max": 60.0}}
+/// {"constant": 21.5}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulatePattern {
+    /// `offset + amplitude * sin(2π * t / period_secs)` (t: `start_time`'dan beri saniye)
+    Sine { amplitude: f64, offset: f64, period_secs: u64 },
+    /// `[min, max]` aralığında düzgün dağılımlı
+    Random { min: f64, max: f64 },
+    /// Her okumada aynı değer
+    Constant(f64),
+}
+
+impl SimulatePattern {
+    /// Geçersiz desen parametresinin açıklaması
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            Self::Sine { amplitude, offset, period_secs } => {
+                if !amplitude.is_finite() || !offset.is_finite() {
+                    return Err("sine amplitude/offset must be finite".into());
+                }
+                if period_secs == 0 {
+                    return Err("sine period_secs must be positive".into());
+                }
+            }
+            Self::Random { min, max } => {
+                if !min.is_finite() || !max.is_finite() || min > max {
+                    return Err("random range must be finite with min <= max".into());
+                }
+            }
+            Self::Constant(value) => {
+                if !value.is_finite() {
+                    return Err("constant value must be finite".into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `start_time`'dan `elapsed_secs` saniye sonraki değer
+    fn value_at(&self, elapsed_secs: u64, rng: &mut impl Rng) -> f64 {
+        match *self {
+            Self::Sine { amplitude, offset, period_secs } => {
+                let phase = (elapsed_secs % period_secs) as f64 / period_secs as f64;
+                offset + amplitude * (std::f64::consts::TAU * phase).sin()
+            }
+            Self::Random { min, max } => rng.gen_range(min..=max),
+            Self::Constant(value) => value,
+        }
+    }
+}
+
+/// `POST /v1/sensors/{device_id}/simulate` body'si
+///
+/// ```json
+/// {
+///   "sensor_type": "temperature",
+///   "count": 60,
+///   "start_time": "2024-01-20T10:00:00Z",
+///   "interval_secs": 60,
+///   "pattern": {"sine": {"amplitude": 3.0, "offset": 22.0, "period_secs": 3600}}
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulateRequest {
+    pub sensor_type: String,
+    /// Üretilecek okuma sayısı (1..=`SIMULATE_MAX_COUNT`)
+    pub count: u32,
+    /// İlk okumanın zamanı
+    pub start_time: DateTime<Utc>,
+    /// Ardışık okumalar arası süre
+    pub interval_secs: u64,
+    pub pattern: SimulatePattern,
+}
+
+/// Simülasyon sonucu
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulateResult {
+    pub device_id: String,
+    pub sensor_type: String,
+    /// Kaydedilen okuma sayısı
+    pub inserted: u32,
+    /// İlk ve son okumanın zamanı
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl SimulateRequest {
+    /// İsteği doğrula (geçersizse açıklama döner)
+    fn validate(&self) -> Result<(), String> {
+        if self.sensor_type.trim().is_empty() {
+            return Err("sensor_type is empty".into());
+        }
+        if !(1..=SIMULATE_MAX_COUNT).contains(&self.count) {
+            return Err(format!("count must be between 1 and {SIMULATE_MAX_COUNT}"));
+        }
+        self.timestamp(self.count - 1).ok_or("time range overflows")?;
+        self.pattern.validate()
+    }
+
+    /// `index`'inci okumanın zamanı (taşarsa `None`)
+    fn timestamp(&self, index: u32) -> Option<DateTime<Utc>> {
+        let offset = self.interval_secs.checked_mul(u64::from(index))?;
+        let offset = TimeDelta::try_seconds(i64::try_from(offset).ok()?)?;
+        self.start_time.checked_add_signed(offset)
+    }
+
+    /// Doğrulanmış istekten okumaları üret
+    fn generate(&self, device_id: &str, received_at: DateTime<Utc>, rng: &mut impl Rng) -> Vec<SensorData> {
+        let unit = self.sensor_type.parse::<SensorType>().map_or("", |t| t.default_unit());
+        (0..self.count)
+            .filter_map(|index| {
+                let timestamp = self.timestamp(index)?;
+                let elapsed_secs = self.interval_secs * u64::from(index);
+                Some(SensorData {
+                    device_id: device_id.to_string(),
+                    sensor_type: self.sensor_type.clone(),
+                    value: self.pattern.value_at(elapsed_secs, rng),
+                    unit: unit.to_string(),
+                    timestamp: timestamp.to_rfc3339(),
+                    metadata: Some(serde_json::json!({ "simulated": true })),
+                    received_at: Some(received_at.to_rfc3339()),
+                    gateway_received_at: None,
+                    stale: false,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Cihaz için sentetik sensör okumaları üret ve kaydet
+///
+/// POST /v1/sensors/{device_id}/simulate
+///
+/// Response:
+/// ```json
+/// {"device_id": "sim-001", "sensor_type": "temperature", "inserted": 60,
+///  "from": "2024-01-20T10:00:00Z", "to": "2024-01-20T10:59:00Z"}
+/// ```
+///
+/// # Error Responses
+/// - 400 Bad Request: `count` 1..=10000 dışında, desen parametreleri geçersiz veya zaman aralığı taşıyor
+/// - 501 Not Implemented: `simulation_enabled` kapalı veya PostgreSQL bağlı değil
+#[tracing::instrument(skip(state, request), fields(sensor_type = %request.sensor_type, count = request.count), err)]
+pub async fn simulate_sensor_readings(
+    State(state): State<AppState>,
+    Path(device_id): Path<String>,
+    Json(request): Json<SimulateRequest>,
+) -> Result<Json<SimulateResult>, StatusCode> {
+    check_feature(&*state.features.read().await, "simulation_enabled")?;
+    request.validate().map_err(|reason| {
+        tracing::debug!("Invalid simulate request: {reason}");
+        StatusCode::BAD_REQUEST
+    })?;
+    if state.pool().await.is_none() {
+        return Err(StatusCode::NOT_IMPLEMENTED);
+    }
+
+    let readings = request.generate(&device_id, Utc::now(), &mut rand::thread_rng());
+    let inserted = readings.len() as u32;
+    for data in readings {
+        sensors::store_reading(&state, data).await?;
+    }
+    tracing::info!("🧪 Simulated {inserted} {} readings for {device_id}", request.sensor_type);
+
+    Ok(Json(SimulateResult {
+        device_id,
+        sensor_type: request.sensor_type.clone(),
+        inserted,
+        from: request.start_time,
+        to: request.timestamp(request.count - 1).unwrap_or(request.start_time),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature_flags::FeatureFlagsPatch;
+    use sqlx::PgPool;
+
+    fn request(count: u32, interval_secs: u64, pattern: SimulatePattern) -> SimulateRequest {
+        SimulateRequest {
+            sensor_type: "temperature".into(),
+            count,
+            start_time: "2024-01-20T10:00:00Z".parse().unwrap(),
+            interval_secs,
+            pattern,
+        }
+    }
+
+    async fn enable_simulation(st: &AppState) {
+        st.features.write().await.apply(FeatureFlagsPatch { simulation_enabled: Some(true), ..Default::default() });
+    }
+
+    #[test]
+    fn test_sine_pattern_stays_in_range() {
+        let pattern = SimulatePattern::Sine { amplitude: 3.0, offset: 22.0, period_secs: 3600 };
+        let req = request(120, 60, pattern);
+        req.validate().unwrap();
+        let readings = req.generate("sim-001", Utc::now(), &mut rand::thread_rng());
+
+        assert_eq!(readings.len(), 120);
+        assert!(readings.iter().all(|r| (19.0 - 1e-9..=25.0 + 1e-9).contains(&r.value)));
+        // Çeyrek periyotta tepe, yarım periyotta offset
+        assert!((readings[15].value - 25.0).abs() < 1e-9);
+        assert!((readings[30].value - 22.0).abs() < 1e-9);
+        assert_eq!(readings[0].timestamp, "2024-01-20T10:00:00+00:00");
+        assert_eq!(readings[119].timestamp, "2024-01-20T11:59:00+00:00");
+        assert_eq!((readings[0].unit.as_str(), readings[0].device_id.as_str()), ("°C", "sim-001"));
+        assert_eq!(readings[0].metadata, Some(serde_json::json!({"simulated": true})));
+    }
+
+    #[test]
+    fn test_random_pattern_stays_in_range() {
+        let req = request(500, 5, SimulatePattern::Random { min: 40.0, max: 60.0 });
+        req.validate().unwrap();
+        let readings = req.generate("sim-001", Utc::now(), &mut rand::thread_rng());
+
+        assert_eq!(readings.len(), 500);
+        assert!(readings.iter().all(|r| (40.0..=60.0).contains(&r.value)));
+        assert!(readings.iter().any(|r| r.value != readings[0].value), "values vary");
+
+        let constant = request(3, 5, SimulatePattern::Constant(21.5)).generate("sim-001", Utc::now(), &mut rand::thread_rng());
+        assert!(constant.iter().all(|r| r.value == 21.5));
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let constant = SimulatePattern::Constant(1.0);
+        assert!(request(0, 60, constant.clone()).validate().is_err());
+        assert!(request(SIMULATE_MAX_COUNT + 1, 60, constant.clone()).validate().is_err());
+        assert!(request(10, u64::MAX, constant.clone()).validate().is_err());
+        assert!(request(10, 60, SimulatePattern::Random { min: 2.0, max: 1.0 }).validate().is_err());
+        assert!(request(10, 60, SimulatePattern::Sine { amplitude: 1.0, offset: 0.0, period_secs: 0 }).validate().is_err());
+        assert!(request(10, 60, SimulatePattern::Constant(f64::NAN)).validate().is_err());
+        assert!(SimulateRequest { sensor_type: " ".into(), ..request(10, 60, constant) }.validate().is_err());
+
+        let pattern: SimulatePattern = serde_json::from_str(r#"{"constant": 21.5}"#).unwrap();
+        assert_eq!(pattern, SimulatePattern::Constant(21.5));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_by_default_and_requires_database() {
+        let st = AppState::for_tests();
+        let req = || Json(request(10, 60, SimulatePattern::Constant(1.0)));
+        let result = simulate_sensor_readings(State(st.clone()), Path("sim-001".into()), req()).await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+
+        enable_simulation(&st).await;
+        let invalid = Json(request(0, 60, SimulatePattern::Constant(1.0)));
+        let result = simulate_sensor_readings(State(st.clone()), Path("sim-001".into()), invalid).await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+        let result = simulate_sensor_readings(State(st), Path("sim-001".into()), req()).await;
+        assert_eq!(result.unwrap_err(), StatusCode::NOT_IMPLEMENTED);
+    }
+
+    async fn test_db() -> PgPool {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        PgPool::connect(&url).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PostgreSQL (TEST_DATABASE_URL)"]
+    async fn test_simulated_readings_are_stored_and_published() {
+        let db = test_db().await;
+        let st = AppState::for_tests_with_db(db.clone());
+        enable_simulation(&st).await;
+        let device_id = format!("sim-{}", uuid::Uuid::new_v4());
+        let since = st.sensor_feed.latest();
+
+        let pattern = SimulatePattern::Sine { amplitude: 3.0, offset: 22.0, period_secs: 600 };
+        let Json(result) = simulate_sensor_readings(State(st.clone()), Path(device_id.clone()), Json(request(20, 60, pattern)))
+            .await
+            .unwrap();
+        assert_eq!(result.inserted, 20);
+        assert_eq!(result.to - result.from, TimeDelta::minutes(19));
+
+        let (count, min, max): (i64, f64, f64) = sqlx::query_as(
+            "SELECT count(*), min(value), max(value) FROM sensor_readings WHERE device_id = $1 AND sensor_type = 'temperature'",
+        )
+        .bind(&device_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(count, 20);
+        assert!(min >= 19.0 - 1e-9 && max <= 25.0 + 1e-9);
+
+        let events = st.sensor_feed.since(since, 100);
+        assert_eq!(events.iter().filter(|e| e.data.device_id == device_id).count(), 20);
+
+        sqlx::query("DELETE FROM sensor_readings WHERE device_id = $1").bind(&device_id).execute(&db).await.unwrap();
+    }
+}
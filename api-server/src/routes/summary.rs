@@ -0,0 +1,259 @@
+//! Dashboard Özet Endpoint'i (Summary Route)
+//!
+//! Dashboard başlık çubuğu için cihaz/sensör/okuma sayılarını tek seferde hesaplar.
+//! - Redis: son değerler (çevrimiçi cihazlar, raporlayan sensörler, son okuma)
+//! - PostgreSQL: `device_shadows` (kayıtlı cihazlar, bağlı gateway'ler), `sensor_readings` (son bir saat)
+//!   ve `sensor_alerts` (onaylanmamış alarmlar)
+//!
+//! Her backend yoksa ilgili alanlar diğerinden doldurulur, o da yoksa `null` döner.
+//! Dashboard sık poll ettiği için sonuç `SUMMARY_CACHE_TTL` boyunca `AppState`'te tutulur.
+
+use std::{collections::HashSet, time::Instant};
+
+use axum::{extract::State, Json};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use shared_types::DashboardSummary;
+use sqlx::PgPool;
+use tokio::time::Duration;
+
+use crate::routes::sensors::{get_all_sensors_from_redis, SensorData};
+use crate::state::AppState;
+
+/// Hesaplanan özetin cache'te kalma süresi
+pub const SUMMARY_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Bu süre içinde okuma gönderen cihaz çevrimiçi sayılır
+const ONLINE_WINDOW_MINUTES: i64 = 5;
+
+/// PostgreSQL istatistikleri - tek sorguda
+const DB_STATS_SQL: &str = "SELECT
+        (SELECT count(*) FROM device_shadows) AS devices_total,
+        (SELECT count(*) FROM device_shadows
+         WHERE reported->>'kind' = 'gateway' AND reported->'status'->>'connected' = 'true') AS gateways_online,
+        count(DISTINCT device_id) FILTER (WHERE timestamp >= now() - make_interval(mins => $1)) AS devices_online,
+        count(DISTINCT (device_id, sensor_type)) AS sensors_reporting,
+        count(*) AS readings_last_hour,
+        (SELECT max(timestamp) FROM sensor_readings) AS last_ingest_at,
+        (SELECT count(*) FROM sensor_alerts WHERE acknowledged_at IS NULL) AS alerts_active
+     FROM sensor_readings
+     WHERE timestamp >= now() - interval '1 hour'";
+
+/// Redis'teki son değerlerden çıkarılan canlı istatistikler
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LiveStats {
+    pub devices_online: u64,
+    pub sensors_reporting: u64,
+    pub last_ingest_at: Option<DateTime<Utc>>,
+}
+
+/// `sensor_readings` / `device_shadows` / `sensor_alerts` istatistikleri
+#[derive(Debug, Clone, Default, PartialEq, sqlx::FromRow)]
+pub struct DbStats {
+    pub devices_total: i64,
+    pub gateways_online: i64,
+    pub devices_online: i64,
+    pub sensors_reporting: i64,
+    pub readings_last_hour: i64,
+    pub last_ingest_at: Option<DateTime<Utc>>,
+    pub alerts_active: i64,
+}
+
+impl LiveStats {
+    /// Son değerlerden istatistik çıkar
+    ///
+    /// Redis key'leri 1 saat TTL'li olduğundan her key raporlayan bir sensördür.
+    pub fn from_latest(sensors: &[SensorData], now: DateTime<Utc>) -> Self {
+        let online_since = now - ChronoDuration::minutes(ONLINE_WINDOW_MINUTES);
+        let timestamps: Vec<_> = sensors
+            .iter()
+            .filter_map(|s| DateTime::parse_from_rfc3339(&s.timestamp).ok().map(|t| (s, t.with_timezone(&Utc))))
+            .collect();
+
+        let online: HashSet<_> = timestamps
+            .iter()
+            .filter(|(_, at)| *at >= online_since)
+            .map(|(s, _)| s.device_id.as_str())
+            .collect();
+        let reporting: HashSet<_> = sensors.iter().map(|s| (&s.device_id, &s.sensor_type)).collect();
+
+        Self {
+            devices_online: online.len() as u64,
+            sensors_reporting: reporting.len() as u64,
+            last_ingest_at: timestamps.iter().map(|(_, at)| *at).max(),
+        }
+    }
+}
+
+/// Backend sonuçlarını birleştir
+///
+/// Canlı değerlerde Redis, sayımlarda PostgreSQL önceliklidir.
+/// Bir backend yoksa (`None`) alan diğerinden doldurulur.
+pub fn combine(live: Option<LiveStats>, db: Option<DbStats>) -> DashboardSummary {
+    let db_count = |f: fn(&DbStats) -> i64| db.as_ref().map(|d| f(d).max(0) as u64);
+
+    DashboardSummary {
+        devices_online: live.as_ref().map(|l| l.devices_online).or(db_count(|d| d.devices_online)),
+        devices_total: db_count(|d| d.devices_total),
+        gateways_online: db_count(|d| d.gateways_online),
+        sensors_reporting: live.as_ref().map(|l| l.sensors_reporting).or(db_count(|d| d.sensors_reporting)),
+        readings_last_hour: db_count(|d| d.readings_last_hour),
+        alerts_active: db_count(|d| d.alerts_active),
+        last_ingest_at: live
+            .as_ref()
+            .and_then(|l| l.last_ingest_at)
+            .max(db.as_ref().and_then(|d| d.last_ingest_at)),
+    }
+}
+
+/// Dashboard özeti
+///
+/// GET /api/summary
+///
+/// Response:
+/// ```json
+/// {
+///   "devices_online": 3,
+///   "devices_total": 4,
+///   "gateways_online": 1,
+///   "sensors_reporting": 9,
+///   "readings_last_hour": 2160,
+///   "alerts_active": 2,
+///   "last_ingest_at": "2024-01-20T10:30:00Z"
+/// }
+/// ```
+///
+/// Backend hataları loglanır ve o backend yokmuş gibi davranılır (hata dönmez).
+pub async fn summary(State(state): State<AppState>) -> Json<DashboardSummary> {
+    if let Some((computed_at, cached)) = state.summary_cache.read().await.as_ref() {
+        if computed_at.elapsed() < SUMMARY_CACHE_TTL {
+            return Json(cached.clone());
+        }
+    }
+
+    let summary = compute_summary(&state).await;
+    *state.summary_cache.write().await = Some((Instant::now(), summary.clone()));
+    Json(summary)
+}
+
+/// Özeti backend'lerden hesapla (cache'siz)
+async fn compute_summary(state: &AppState) -> DashboardSummary {
+    let live = async {
+        let mut conn = state.redis.clone()?;
+        match get_all_sensors_from_redis(&mut conn).await {
+            Ok(sensors) => Some(LiveStats::from_latest(&sensors, Utc::now())),
+            Err(e) => {
+                tracing::warn!("Summary: Redis read failed: {e}");
+                None
+            }
+        }
+    };
+    let db = async {
+        let db = &state.pool().await?;
+        db_stats(db).await
+    };
+
+    let (live, db) = tokio::join!(live, db);
+    combine(live, db)
+}
+
+/// PostgreSQL istatistiklerini oku
+async fn db_stats(db: &PgPool) -> Option<DbStats> {
+    sqlx::query_as::<_, DbStats>(DB_STATS_SQL)
+        .bind(ONLINE_WINDOW_MINUTES as i32)
+        .fetch_one(db)
+        .await
+        .map_err(|e| tracing::warn!("Summary: database query failed: {e}"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sensor(device_id: &str, sensor_type: &str, timestamp: DateTime<Utc>) -> SensorData {
+        SensorData {
+            device_id: device_id.into(),
+            sensor_type: sensor_type.into(),
+            value: 1.0,
+            unit: String::new(),
+            timestamp: timestamp.to_rfc3339(),
+            metadata: None,
+            received_at: None,
+            gateway_received_at: None,
+            stale: false,
+        }
+    }
+
+    #[test]
+    fn test_live_stats_from_latest_values() {
+        let now = Utc::now();
+        let stale = now - ChronoDuration::minutes(30);
+        let sensors = vec![
+            sensor("dev-1", "temperature", now),
+            sensor("dev-1", "humidity", now - ChronoDuration::minutes(1)),
+            sensor("dev-2", "temperature", stale),
+        ];
+
+        let live = LiveStats::from_latest(&sensors, now);
+        assert_eq!(live.devices_online, 1);
+        assert_eq!(live.sensors_reporting, 3);
+        assert_eq!(live.last_ingest_at.map(|t| t.timestamp()), Some(now.timestamp()));
+    }
+
+    #[test]
+    fn test_combine_partial_backends() {
+        let live = LiveStats { devices_online: 2, sensors_reporting: 5, last_ingest_at: Some(Utc::now()) };
+        let db = DbStats {
+            devices_total: 4,
+            gateways_online: 1,
+            devices_online: 1,
+            sensors_reporting: 3,
+            readings_last_hour: 120,
+            last_ingest_at: None,
+            alerts_active: 2,
+        };
+
+        // Hiç backend yok: her şey null
+        assert_eq!(combine(None, None), DashboardSummary::default());
+
+        // Sadece Redis: sayımlar null, canlı değerler dolu
+        let only_live = combine(Some(live.clone()), None);
+        assert_eq!((only_live.devices_online, only_live.sensors_reporting), (Some(2), Some(5)));
+        assert_eq!((only_live.devices_total, only_live.gateways_online, only_live.readings_last_hour), (None, None, None));
+        assert_eq!(only_live.alerts_active, None);
+
+        // Sadece DB: canlı değerler DB'den
+        let only_db = combine(None, Some(db.clone()));
+        assert_eq!(only_db.devices_online, Some(1));
+        assert_eq!(only_db.devices_total, Some(4));
+        assert_eq!(only_db.gateways_online, Some(1));
+        assert_eq!(only_db.readings_last_hour, Some(120));
+
+        // İkisi de var: canlı değerlerde Redis önceliklidir
+        let both = combine(Some(live.clone()), Some(db));
+        assert_eq!((both.devices_online, both.devices_total), (Some(2), Some(4)));
+        assert_eq!(both.last_ingest_at, live.last_ingest_at);
+        assert_eq!(both.alerts_active, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_summary_is_cached_until_ttl() {
+        let state = AppState::for_tests();
+        let Json(first) = summary(State(state.clone())).await;
+        assert_eq!(first, DashboardSummary::default());
+
+        // Cache'i taze bir değerle değiştir: TTL dolmadan aynen dönmeli
+        let cached = DashboardSummary { devices_total: Some(7), ..Default::default() };
+        *state.summary_cache.write().await = Some((Instant::now(), cached.clone()));
+        let Json(second) = summary(State(state.clone())).await;
+        assert_eq!(second, cached);
+
+        // TTL dolmuşsa yeniden hesaplanır
+        let expired = Instant::now() - SUMMARY_CACHE_TTL - Duration::from_millis(1);
+        *state.summary_cache.write().await = Some((expired, cached));
+        let Json(third) = summary(State(state.clone())).await;
+        assert_eq!(third, DashboardSummary::default());
+        let (computed_at, _) = state.summary_cache.read().await.clone().unwrap();
+        assert!(computed_at.elapsed() < SUMMARY_CACHE_TTL);
+    }
+}
@@ -25,7 +25,7 @@ use crate::state::AppState;
 /// **Güvenlik Notu**: Hassas bilgiler (database şifresi vb.) maskelenmiştir.
 /// Sadece "var mı yok mu" bilgisi döndürülür.
 pub async fn config(State(st): State<AppState>) -> impl IntoResponse {
-    // st.cfg.sanitized() = Güvenli yapılandırma
+    // st.cfg.read().await.sanitized() = Güvenli yapılandırma
     // Veritabanı URL'sinin full değeri yerine has_database_url: true/false döndür
-    Json(st.cfg.sanitized())
+    Json(st.cfg.read().await.sanitized())
 }
\ No newline at end of file
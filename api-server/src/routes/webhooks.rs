@@ -0,0 +1,385 @@
+//! Webhook Endpoint'leri
+//!
+//! Harici sistemler (Slack köprüsü, PagerDuty, kendi servisleri) olay
+//! bildirimleri için webhook kaydeder. Sensör eşiği aşıldığında
+//! (`sensor.alert`) ilgili webhook'lara imzalı bir POST gönderilir.
+//!
+//! Teslimat asenkrondur: handler sadece `webhook_queue`'ya ekler,
+//! `background::deliver_webhooks` gönderir ve gerekirse yeniden dener.
+//!
+//! SSRF: hedef sadece http(s) olabilir; loopback, link-local (bulut metadata
+//! servisleri dahil), özel ve paylaşılan ağlara çözümlenen adresler hem
+//! kayıtta hem her teslimattan önce reddedilir (`validate_target`).
+//!
+//! # Endpoint'ler
+//! - POST /v1/webhooks - Yeni webhook kaydet
+//! - GET /v1/webhooks - Webhook'ları listele
+//! - DELETE /v1/webhooks/{id} - Webhook'u sil
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use axum::{extract::{Path, State}, http::StatusCode, Json};
+use chrono::Utc;
+use reqwest::Url;
+use uuid::Uuid;
+
+use crate::background::WebhookDelivery;
+use crate::feature_flags::check_feature;
+use crate::state::AppState;
+use shared_types::webhook::EVENT_SENSOR_ALERT;
+use shared_types::{NewWebhook, SensorAlert, Webhook, WebhookEvent};
+
+/// Desteklenen olay tipleri
+pub const EVENT_TYPES: &[&str] = &[EVENT_SENSOR_ALERT];
+
+/// Yeni webhook kaydet
+///
+/// # HTTP
+/// `POST /v1/webhooks`
+///
+/// # Request Body
+/// ```json
+/// {
+///   "url": "https://example.com/hooks/rustyflow",
+///   "secret": "s3cret",
+///   "event_types": ["sensor.alert"]
+/// }
+/// ```
+///
+/// # Response (201 Created)
+/// Kaydedilen webhook (`secret` hariç)
+///
+/// # Error Responses
+/// - 400 Bad Request: URL http(s) değil veya iç ağı hedefliyor (`validate_target`),
+///   `secret` boş ya da bilinmeyen/boş `event_types`
+/// - 500 Internal Server Error: Database hatası
+/// - 501 Not Implemented: `webhook_delivery_enabled` kapalı
+pub async fn create_webhook(
+    State(st): State<AppState>,
+    Json(body): Json<NewWebhook>,
+) -> Result<(StatusCode, Json<Webhook>), StatusCode> {
+    check_feature(&*st.features.read().await, "webhook_delivery_enabled")?;
+    let valid_events = !body.event_types.is_empty()
+        && body.event_types.iter().all(|e| EVENT_TYPES.contains(&e.as_str()));
+    if body.secret.is_empty() || !valid_events {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if let Err(reason) = validate_target(&body.url).await {
+        tracing::warn!("Rejected webhook target {}: {reason}", body.url);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        let hook = sqlx::query_as::<_, Webhook>(
+            "INSERT INTO webhooks (id, url, secret, event_types, active, created_at)
+             VALUES ($1, $2, $3, $4, $5, NOW())
+             RETURNING id, url, secret, event_types, active, created_at"
+        )
+        .bind(Uuid::new_v4())
+        .bind(&body.url)
+        .bind(&body.secret)
+        .bind(&body.event_types)
+        .bind(body.active)
+        .fetch_one(db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok((StatusCode::CREATED, Json(hook)))
+    } else {
+        // ===== In-Memory Fallback =====
+        let hook = Webhook {
+            id: Uuid::new_v4(),
+            url: body.url,
+            secret: body.secret,
+            event_types: body.event_types,
+            active: body.active,
+            created_at: Utc::now(),
+        };
+        st.webhook_store.write().await.insert(hook.id, hook.clone());
+        Ok((StatusCode::CREATED, Json(hook)))
+    }
+}
+
+/// Kayıtlı webhook'ları listele (`created_at` sırasıyla)
+///
+/// # HTTP
+/// `GET /v1/webhooks`
+pub async fn list_webhooks(State(st): State<AppState>) -> Result<Json<Vec<Webhook>>, StatusCode> {
+    load_webhooks(&st).await.map(Json)
+}
+
+/// Bir webhook'u sil (teslimat geçmişi de silinir)
+///
+/// # HTTP
+/// `DELETE /v1/webhooks/{id}`
+///
+/// # Error Responses
+/// - 404 Not Found: ID bulunamadı
+/// - 500 Internal Server Error: Database hatası
+pub async fn delete_webhook(
+    State(st): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = $1")
+            .bind(id)
+            .execute(db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        if result.rows_affected() > 0 {
+            Ok(StatusCode::NO_CONTENT)
+        } else {
+            Err(StatusCode::NOT_FOUND)
+        }
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut map = st.webhook_store.write().await;
+        map.remove(&id).map(|_| StatusCode::NO_CONTENT).ok_or(StatusCode::NOT_FOUND)
+    }
+}
+
+/// Webhook hedefini SSRF'e karşı doğrula
+///
+/// Şema http(s) olmalı ve host iç ağa işaret etmemeli: IP literal'leri
+/// doğrudan, alan adları çözümlenen tüm adresleriyle kontrol edilir.
+/// Çözümlenemeyen alan adı reddedilmez (teslimat zaten başarısız olur);
+/// DNS sonradan değişebileceği için kontrol her teslimattan önce tekrarlanır.
+pub async fn validate_target(url: &str) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|e| format!("invalid url: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("unsupported scheme '{}'", url.scheme()));
+    }
+    let port = url.port_or_known_default().unwrap_or(443);
+    let host = url.host_str().ok_or("missing host")?;
+    // IPv6 literal'leri köşeli parantezli gelir; IPv4 yazımları (`2130706433`) parse'ta normalize edilir
+    if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        return check_ip(ip);
+    }
+    let domain = host.trim_end_matches('.').to_ascii_lowercase();
+    if domain == "localhost" || domain.ends_with(".localhost") {
+        return Err(format!("host '{domain}' is loopback"));
+    }
+    let resolved = tokio::net::lookup_host(format!("{domain}:{port}")).await;
+    match resolved {
+        Ok(mut addrs) => addrs.try_for_each(|addr| check_ip(addr.ip())),
+        Err(_) => Ok(()),
+    }
+}
+
+/// İç ağ adreslerini reddet
+fn check_ip(ip: IpAddr) -> Result<(), String> {
+    if is_internal(ip) {
+        Err(format!("address {ip} is not publicly routable"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Webhook'un ulaşmaması gereken adres mi?
+///
+/// Loopback, belirsiz, özel (RFC 1918 / ULA), link-local (169.254.169.254
+/// metadata servisi dahil), paylaşılan (100.64/10), IETF (192.0.0/24),
+/// broadcast ve multicast. IPv4'e eşlenmiş IPv6 adresleri IPv4 olarak denetlenir.
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_internal_v4(v4),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_internal_v4(v4),
+            None => is_internal_v6(v6),
+        },
+    }
+}
+
+fn is_internal_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64)
+        || (a == 192 && b == 0 && c == 0)
+}
+
+fn is_internal_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+}
+
+/// Tüm webhook'ları (secret dahil) oku
+async fn load_webhooks(st: &AppState) -> Result<Vec<Webhook>, StatusCode> {
+    if let Some(db) = &st.pool().await {
+        // ===== PostgreSQL Yolu =====
+        sqlx::query_as::<_, Webhook>(
+            "SELECT id, url, secret, event_types, active, created_at FROM webhooks ORDER BY created_at"
+        )
+        .fetch_all(db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    } else {
+        // ===== In-Memory Fallback =====
+        let mut hooks: Vec<Webhook> = st.webhook_store.read().await.values().cloned().collect();
+        hooks.sort_by_key(|h| h.created_at);
+        Ok(hooks)
+    }
+}
+
+/// Alarmı dinleyen webhook'lara teslimat kuyruğuna ekle
+///
+/// Sadece aktif ve `sensor.alert`'e abone webhook'lar seçilir.
+/// Eklenen teslimat sayısını döndürür; hata sadece loglanır (okuma kaydını engellemez),
+/// kuyruk doluysa teslimat düşürülür (`WebhookQueue::try_enqueue`).
+/// `webhook_delivery_enabled` kapalıysa hiçbir teslimat eklenmez.
+pub async fn notify_alert(st: &AppState, alert: &SensorAlert) -> usize {
+    if !st.features.read().await.webhook_delivery_enabled {
+        return 0;
+    }
+    let hooks = match load_webhooks(st).await {
+        Ok(hooks) => hooks,
+        Err(_) => {
+            tracing::error!("Failed to load webhooks for alert {}", alert.id);
+            return 0;
+        }
+    };
+
+    let event = WebhookEvent {
+        event_type: EVENT_SENSOR_ALERT.to_string(),
+        occurred_at: alert.triggered_at,
+        data: serde_json::to_value(alert).unwrap_or_default(),
+    };
+    let body = serde_json::to_vec(&event).unwrap_or_default();
+
+    let mut queued = 0;
+    for hook in hooks.into_iter().filter(|h| h.subscribes_to(EVENT_SENSOR_ALERT)) {
+        let delivery = WebhookDelivery {
+            id: Uuid::new_v4(),
+            webhook_id: hook.id,
+            url: hook.url,
+            secret: hook.secret,
+            event_type: EVENT_SENSOR_ALERT.to_string(),
+            body: body.clone(),
+        };
+        if st.webhook_queue.try_enqueue(delivery) {
+            queued += 1;
+        }
+    }
+    queued
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::alert::{check_threshold, AlertThreshold};
+
+    fn new_webhook(url: &str, event_types: &[&str], active: bool) -> NewWebhook {
+        NewWebhook {
+            url: url.into(),
+            secret: "s3cret".into(),
+            event_types: event_types.iter().map(|e| e.to_string()).collect(),
+            active,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_crud_in_memory() {
+        let st = AppState::for_tests();
+
+        let (status, Json(hook)) = create_webhook(State(st.clone()), Json(new_webhook("https://example.com/hook", &["sensor.alert"], true)))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        // Secret response'ta görünmez
+        assert!(serde_json::to_value(&hook).unwrap().get("secret").is_none());
+
+        let Json(hooks) = list_webhooks(State(st.clone())).await.unwrap();
+        assert_eq!(hooks.iter().map(|h| h.id).collect::<Vec<_>>(), vec![hook.id]);
+
+        assert_eq!(delete_webhook(State(st.clone()), Path(hook.id)).await, Ok(StatusCode::NO_CONTENT));
+        assert_eq!(delete_webhook(State(st.clone()), Path(hook.id)).await, Err(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_create_webhook_validation() {
+        let st = AppState::for_tests();
+        for body in [
+            new_webhook("ftp://example.com/hook", &["sensor.alert"], true),
+            new_webhook("http://127.0.0.1:8080/hook", &["sensor.alert"], true),
+            new_webhook("http://localhost/hook", &["sensor.alert"], true),
+            new_webhook("http://169.254.169.254/latest/meta-data/", &["sensor.alert"], true),
+            new_webhook("https://example.com/hook", &[], true),
+            new_webhook("https://example.com/hook", &["device.deleted"], true),
+            NewWebhook { secret: String::new(), ..new_webhook("https://example.com/hook", &["sensor.alert"], true) },
+        ] {
+            let result = create_webhook(State(st.clone()), Json(body)).await;
+            assert_eq!(result.err(), Some(StatusCode::BAD_REQUEST));
+        }
+        assert!(st.webhook_store.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_target_rejects_internal_addresses() {
+        for url in [
+            "http://10.0.0.5/hook",
+            "http://172.16.3.4/hook",
+            "http://192.168.1.10/hook",
+            "http://100.64.0.1/hook",
+            "http://192.0.0.192/hook",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[fd00:ec2::254]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+            "http://api.localhost./hook",
+            "http://2130706433/hook",
+            "file:///etc/passwd",
+            "not a url",
+        ] {
+            assert!(validate_target(url).await.is_err(), "{url}");
+        }
+        for url in ["https://8.8.8.8/hook", "http://[2606:4700::1111]:8443/hook", "https://172.32.0.1/hook"] {
+            assert_eq!(validate_target(url).await, Ok(()), "{url}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notify_alert_enqueues_signed_deliveries() {
+        let (queue, mut rx) = crate::background::WebhookQueue::bounded(8);
+        let st = AppState { webhook_queue: queue, ..AppState::for_tests() };
+
+        let (_, Json(subscribed)) = create_webhook(State(st.clone()), Json(new_webhook("https://a.example/hook", &["sensor.alert"], true)))
+            .await
+            .unwrap();
+        let (_, Json(inactive)) = create_webhook(State(st.clone()), Json(new_webhook("https://b.example/hook", &["sensor.alert"], false)))
+            .await
+            .unwrap();
+        assert!(!inactive.subscribes_to("sensor.alert"));
+
+        let threshold = AlertThreshold { min: None, max: Some(35.0) };
+        let alert = check_threshold("dev-1", "temperature", 41.2, &threshold).unwrap();
+        assert_eq!(notify_alert(&st, &alert).await, 1);
+
+        let delivery = rx.try_recv().unwrap();
+        assert_eq!(delivery.webhook_id, subscribed.id);
+        assert_eq!(delivery.url, "https://a.example/hook");
+        let event: WebhookEvent = serde_json::from_slice(&delivery.body).unwrap();
+        assert_eq!(event.event_type, "sensor.alert");
+        assert_eq!(event.data["message"], "temperature 41.2 above max 35");
+        // İmza teslimat anında bu secret ile atılır (bilinen vektör: background testleri)
+        assert_eq!(delivery.secret, "s3cret");
+        assert!(rx.try_recv().is_err());
+
+        // Teslimat kapatılınca kuyruğa bir şey eklenmez
+        st.features.write().await.webhook_delivery_enabled = false;
+        assert_eq!(notify_alert(&st, &alert).await, 0);
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Tokio Runtime Sağlığı (`tracing` feature'ı)
+//!
+//! `main.rs`'in başlattığı uzun ömürlü görevler (MQTT event loop, webhook
+//! teslimatı, pool monitörü, ...) `spawn` ile başlatılır. Feature açıkken
+//! görevler ortak bir `TaskMonitor`'dan geçer ve şunlar ölçülür:
+//! - ortalama poll süresi ve yavaş poll sayısı (poll > 50 µs)
+//! - izlenen/biten görev sayısı
+//!
+//! `GET /metrics/runtime` anlık değerleri JSON olarak döner; `watch` 5 saniyede
+//! bir aynı değerleri ve `RuntimeMonitor`'ün kararlı metriklerini (worker,
+//! canlı görev, global kuyruk) `GET /metrics`'e gauge olarak yazar. Yavaş poll
+//! sayısı saniyede 100'den fazla artarsa executor bloklanıyor demektir
+//! (async kodda senkron I/O veya `std::thread::sleep`) ve WARN loglanır.
+//!
+//! Feature kapalıyken `spawn` doğrudan `tokio::spawn`'dır.
+//!
+//! ```bash
+//! cargo run -p api-server --features tracing
+//! ```
+
+use std::future::Future;
+
+use tokio::task::JoinHandle;
+
+/// Arka plan görevini başlat (`tracing` feature'ı ile `TASKS` üzerinden ölçülür)
+pub fn spawn<F>(task: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(feature = "tracing")]
+    let task = TASKS.instrument(task);
+    tokio::spawn(task)
+}
+
+#[cfg(feature = "tracing")]
+pub use enabled::*;
+
+#[cfg(feature = "tracing")]
+mod enabled {
+    use std::fmt::Write;
+    use std::sync::{LazyLock, Mutex};
+
+    use axum::{extract::State, http::StatusCode, Json};
+    use serde::Serialize;
+    use tokio::time::{Duration, Instant};
+    use tokio_metrics::{RuntimeMonitor, TaskMetrics, TaskMonitor};
+
+    use crate::feature_flags::check_feature;
+    use crate::state::AppState;
+
+    /// `spawn` ile başlatılan tüm görevlerin monitörü
+    pub static TASKS: LazyLock<TaskMonitor> = LazyLock::new(TaskMonitor::new);
+
+    /// `watch`'ın son ölçümü (`GET /metrics` gauge'ları; ilk 5 saniyede boş)
+    static LATEST: Mutex<Option<RuntimeGauges>> = Mutex::new(None);
+
+    /// `watch`'ın ölçüm aralığı
+    pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+    /// Bu hızın üstündeki yavaş poll artışı bloklanmış executor sayılır (saniyede)
+    pub const BLOCKED_SLOW_POLLS_PER_SEC: f64 = 100.0;
+
+    /// İzlenen görevlerin metrikleri (`GET /metrics/runtime`)
+    ///
+    /// ```json
+    /// {"mean_poll_duration_us": 12, "slow_poll_count": 3, "task_count": 7, "dropped_count": 0}
+    /// ```
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+    pub struct RuntimeMetrics {
+        /// Ortalama poll süresi (mikrosaniye)
+        pub mean_poll_duration_us: u64,
+        /// 50 µs'den uzun süren poll'ler (toplam)
+        pub slow_poll_count: u64,
+        /// İzlenmeye başlanan görevler
+        pub task_count: u64,
+        /// Biten (drop edilen) görevler
+        pub dropped_count: u64,
+    }
+
+    impl From<&TaskMetrics> for RuntimeMetrics {
+        fn from(tasks: &TaskMetrics) -> Self {
+            Self {
+                mean_poll_duration_us: tasks.mean_poll_duration().as_micros() as u64,
+                slow_poll_count: tasks.total_slow_poll_count,
+                task_count: tasks.instrumented_count,
+                dropped_count: tasks.dropped_count,
+            }
+        }
+    }
+
+    impl RuntimeMetrics {
+        /// Monitörün başlangıçtan beri toplam metrikleri
+        pub fn collect(tasks: &TaskMonitor) -> Self {
+            Self::from(&tasks.cumulative())
+        }
+    }
+
+    /// `GET /metrics`'e yazılan ölçümler
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    struct RuntimeGauges {
+        tasks: RuntimeMetrics,
+        workers_count: usize,
+        live_tasks_count: usize,
+        global_queue_depth: usize,
+    }
+
+    impl RuntimeGauges {
+        /// Prometheus text formatı (kümülatif sayaçlar `counter`, `_total` ekiyle)
+        fn render(&self, out: &mut String) {
+            let metrics = [
+                ("tokio_task_mean_poll_duration_microseconds", "gauge", "Mean poll duration of monitored tasks.", self.tasks.mean_poll_duration_us),
+                ("tokio_task_slow_polls_total", "counter", "Polls of monitored tasks that took longer than 50us.", self.tasks.slow_poll_count),
+                ("tokio_tasks_started_total", "counter", "Monitored tasks started.", self.tasks.task_count),
+                ("tokio_tasks_dropped_total", "counter", "Monitored tasks finished.", self.tasks.dropped_count),
+                ("tokio_runtime_workers", "gauge", "Runtime worker threads.", self.workers_count as u64),
+                ("tokio_runtime_live_tasks", "gauge", "Tasks alive in the runtime.", self.live_tasks_count as u64),
+                ("tokio_runtime_global_queue_depth", "gauge", "Tasks waiting in the global queue.", self.global_queue_depth as u64),
+            ];
+            for (name, kind, help, value) in metrics {
+                let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}");
+            }
+        }
+    }
+
+    /// `watch`'ın son ölçümü Prometheus formatında (henüz ölçüm yoksa boş)
+    pub fn render_prometheus() -> String {
+        let mut out = String::new();
+        if let Some(gauges) = *LATEST.lock().unwrap_or_else(|e| e.into_inner()) {
+            gauges.render(&mut out);
+        }
+        out
+    }
+
+    /// Yavaş poll artışı saniyede `BLOCKED_SLOW_POLLS_PER_SEC`'i geçtiyse hızı döner
+    pub fn blocked_executor_rate(previous: u64, current: u64, elapsed: Duration) -> Option<f64> {
+        let secs = elapsed.as_secs_f64();
+        if secs <= 0.0 {
+            return None;
+        }
+        let rate = current.saturating_sub(previous) as f64 / secs;
+        (rate > BLOCKED_SLOW_POLLS_PER_SEC).then_some(rate)
+    }
+
+    /// Runtime'ı `POLL_INTERVAL`'de bir ölç (sonsuza kadar çalışır)
+    pub async fn watch(runtime: tokio::runtime::Handle) {
+        let mut intervals = RuntimeMonitor::new(&runtime).intervals();
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        let mut previous = (RuntimeMetrics::collect(&TASKS), Instant::now());
+        loop {
+            ticker.tick().await;
+            let tasks = RuntimeMetrics::collect(&TASKS);
+            let now = Instant::now();
+            if let Some(rate) = blocked_executor_rate(previous.0.slow_poll_count, tasks.slow_poll_count, now - previous.1) {
+                tracing::warn!(
+                    slow_poll_count = tasks.slow_poll_count,
+                    "🧱 Executor looks blocked: {rate:.0} slow polls/s (blocking call in async code?)"
+                );
+            }
+            previous = (tasks, now);
+
+            let Some(runtime) = intervals.next() else { continue };
+            *LATEST.lock().unwrap_or_else(|e| e.into_inner()) = Some(RuntimeGauges {
+                tasks,
+                workers_count: runtime.workers_count,
+                live_tasks_count: runtime.live_tasks_count,
+                global_queue_depth: runtime.global_queue_depth,
+            });
+        }
+    }
+
+    /// İzlenen görevlerin anlık metrikleri
+    ///
+    /// # HTTP
+    /// `GET /metrics/runtime` (FEATURE_METRICS_ENABLED; kapalıysa 501)
+    pub async fn runtime_metrics_handler(State(st): State<AppState>) -> Result<Json<RuntimeMetrics>, StatusCode> {
+        check_feature(&*st.features.read().await, "metrics_enabled")?;
+        Ok(Json(RuntimeMetrics::collect(&TASKS)))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_blocking_poll_is_counted_as_slow() {
+            let tasks = TaskMonitor::new();
+            tokio::spawn(tasks.instrument(async {
+                // Executor'ı bloklayan senkron bekleme
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }))
+            .await
+            .unwrap();
+
+            let metrics = RuntimeMetrics::collect(&tasks);
+            assert!(metrics.slow_poll_count > 0);
+            assert!(metrics.mean_poll_duration_us >= 20_000);
+            assert_eq!((metrics.task_count, metrics.dropped_count), (1, 1));
+        }
+
+        #[test]
+        fn test_blocked_executor_rate_threshold() {
+            let second = Duration::from_secs(1);
+            assert_eq!(blocked_executor_rate(10, 110, second), None);
+            assert_eq!(blocked_executor_rate(0, 600, Duration::from_secs(5)), Some(120.0));
+            assert_eq!(blocked_executor_rate(500, 10, second), None);
+            assert_eq!(blocked_executor_rate(0, 1_000, Duration::ZERO), None);
+        }
+
+        #[test]
+        fn test_gauges_render_as_prometheus() {
+            let gauges = RuntimeGauges {
+                tasks: RuntimeMetrics { mean_poll_duration_us: 12, slow_poll_count: 3, task_count: 7, dropped_count: 1 },
+                workers_count: 4,
+                live_tasks_count: 9,
+                global_queue_depth: 0,
+            };
+            let mut out = String::new();
+            gauges.render(&mut out);
+            assert!(out.contains("# TYPE tokio_task_slow_polls_total counter\ntokio_task_slow_polls_total 3\n"));
+            assert!(out.contains("# TYPE tokio_tasks_started_total counter\ntokio_tasks_started_total 7\n"));
+            assert!(out.contains("# TYPE tokio_tasks_dropped_total counter\ntokio_tasks_dropped_total 1\n"));
+            assert!(out.contains("# TYPE tokio_runtime_workers gauge\n"));
+            assert!(out.contains("tokio_task_mean_poll_duration_microseconds 12\n"));
+            assert!(out.contains("tokio_runtime_workers 4\n"));
+        }
+    }
+}
@@ -3,14 +3,26 @@
 //! Tüm HTTP handler'larına inject edilen shared state.
 //! Thread-safe ve async-compatible veri yapıları içerir.
 
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::RwLock;
+use std::{collections::{HashMap, VecDeque}, sync::{atomic::AtomicBool, Arc}, time::Instant};
+use tokio::sync::{mpsc, RwLock};
 use uuid::Uuid;
 use sqlx::PgPool;
 use redis::aio::ConnectionManager;
-use shared_types::Media;
+use rumqttc::AsyncClient;
+use tracing_subscriber::{EnvFilter, Registry};
+use shared_types::{CommandAuditEntry, DashboardSummary, Device, DeviceShadow, DeviceToken, Media, SensorGroup, Webhook};
 
-use crate::config::Config;
+use crate::background::WebhookQueue;
+use crate::config::{Config, ConfigOverrides};
+use crate::event_sink::IngestEvent;
+use crate::feature_flags::FeatureFlags;
+use crate::rate_limit::IpRateLimiter;
+use crate::metrics::HttpMetrics;
+use crate::realtime::SensorFeed;
+use crate::routes::sensors::AlertCooldowns;
+
+/// Çalışan `EnvFilter`'ı değiştiren handle (`tracing_subscriber::reload`)
+pub type LogFilterHandle = tracing_subscriber::reload::Handle<EnvFilter, Registry>;
 
 /// Uygulama global durumu
 /// 
@@ -21,9 +33,32 @@ use crate::config::Config;
 /// 
 /// # Bileşenler
 /// 
-/// - **cfg**: Sunucu konfigürasyonu (port, database URL, log level)
+/// - **cfg**: Sunucu konfigürasyonu (port, database URL, log level), yeniden yüklenebilir
+/// - **config_overrides**: Komut satırı flag'leri (yeniden yüklemede de uygulanır)
+/// - **log_filter**: Log seviyesini çalışırken değiştirmek için reload handle
 /// - **media_store**: In-memory fallback storage (PostgreSQL yoksa kullan)
-/// - **db**: PostgreSQL connection pool (optional)
+/// - **media_checksums**: In-memory media'ların checksum → ID indeksi
+/// - **shadow_store**: Device shadow'lar için in-memory fallback
+/// - **device_store**: Kayıtlı cihazlar için in-memory fallback
+/// - **device_tokens**: Cihaz token'ları için in-memory fallback (hash → token)
+/// - **sensor_groups**: Sensör grupları için in-memory fallback
+/// - **command_audit**: Komut denetim kaydı için in-memory fallback (sınırlı halka)
+/// - **jwt_secret**: API token'larını imzalayan anahtar (`auth`)
+/// - **db**: PostgreSQL connection pool (optional, `PoolHealthMonitor` yenileyebilir)
+/// - **db_healthy**: Son pool sağlık kontrolü başarılı mı?
+/// - **redis**: Redis connection manager (optional)
+/// - **mqtt**: Cihazlara komut göndermek için MQTT client (optional)
+/// - **mqtt_connected**: MQTT bağlantı durumu (readiness için)
+/// - **started_at**: Sunucunun başlatıldığı an (uptime için)
+/// - **sensor_feed**: Yeni okumaların yayınlandığı broadcast kanalı (realtime)
+/// - **summary_cache**: Son hesaplanan dashboard özeti (kısa süreli cache)
+/// - **webhook_store**: Webhook'lar için in-memory fallback
+/// - **webhook_queue**: Webhook teslimat kuyruğu (arka plan görevi tüketir)
+/// - **alert_cooldowns**: (cihaz, sensör, eşik) başına son alarm zamanı
+/// - **ingest_limiter**: Toplu okuma endpoint'i için IP başına istek sınırı
+/// - **event_queue**: NATS/Kafka'ya yayınlanacak olayların kuyruğu (optional)
+/// - **features**: Çalışırken değiştirilebilen özellik bayrakları
+/// - **http_metrics**: Handler gecikme histogramları (`GET /metrics`)
 /// 
 /// # Örnek Kullanım
 /// 
@@ -33,15 +68,27 @@ use crate::config::Config;
 ///     State(state): State<AppState>
 /// ) -> String {
 ///     // state.cfg, state.db, state.media_store'a erişebilirsin
-///     state.cfg.app_port.to_string()
+///     state.cfg.read().await.app_port.to_string()
 /// }
 /// ```
 #[derive(Clone)]
 pub struct AppState {
     /// Sunucu yapılandırması
     /// 
-    /// Port, database URL, log level gibi ayarları içerir
-    pub cfg: Config,
+    /// Port, database URL, log level gibi ayarları içerir.
+    /// `POST /v1/admin/reload-config` yeniden yüklenebilen alanları günceller
+    /// (bkz. `Config::reloaded`); handler'lar her istekte kısa süreli okur.
+    pub cfg: Arc<RwLock<Config>>,
+
+    /// Başlangıçta verilen komut satırı flag'leri (`--port` vb.)
+    /// 
+    /// Yeniden yüklenen yapılandırmaya da uygulanır; flag'ler env var'ları ezer.
+    pub config_overrides: Arc<ConfigOverrides>,
+
+    /// Çalışan tracing filtresini değiştirmek için handle
+    /// 
+    /// `None` ise (testler) log seviyesi yeniden yüklenmez.
+    pub log_filter: Option<LogFilterHandle>,
     
     /// In-memory media storage (fallback amaçlı)
     /// 
@@ -55,6 +102,43 @@ pub struct AppState {
     /// - `HashMap<Uuid, Media>` = ID -> Media eşlemesi
     pub media_store: Arc<RwLock<HashMap<Uuid, Media>>>,
 
+    /// In-memory media'ların içerik özeti indeksi (`checksum_sha256` → ID)
+    /// 
+    /// Upload'da aynı içeriği ve `?checksum=` sorgusunu store'u taramadan
+    /// bulmak için. Sadece `checksum_sha256`'sı olan kayıtları içerir;
+    /// `media_store` ile birlikte güncellenir (önce store kilitlenir).
+    pub media_checksums: Arc<RwLock<HashMap<String, Uuid>>>,
+
+    /// In-memory device shadow storage (fallback amaçlı)
+    /// 
+    /// PostgreSQL bağlanmazsa, cihaz gölgeleri burada saklanır.
+    pub shadow_store: Arc<RwLock<HashMap<Uuid, DeviceShadow>>>,
+
+    /// In-memory cihaz kayıtları (fallback amaçlı)
+    /// 
+    /// PostgreSQL bağlanmazsa, `POST /v1/provision` kayıtları burada tutar.
+    pub device_store: Arc<RwLock<HashMap<Uuid, Device>>>,
+
+    /// In-memory cihaz token'ları (fallback amaçlı)
+    ///
+    /// Anahtar token'ın SHA-256 hash'idir; ingest isteklerinde doğrudan aranır.
+    pub device_tokens: Arc<RwLock<HashMap<String, DeviceToken>>>,
+
+    /// In-memory sensör grupları (fallback amaçlı)
+    pub sensor_groups: Arc<RwLock<HashMap<Uuid, SensorGroup>>>,
+
+    /// In-memory komut denetim kaydı (fallback amaçlı)
+    ///
+    /// PostgreSQL bağlanmazsa son `MAX_IN_MEMORY_COMMANDS` komut burada
+    /// tutulur (eskiden yeniye; dolunca en eskisi düşer).
+    pub command_audit: Arc<RwLock<VecDeque<CommandAuditEntry>>>,
+
+    /// API token'larının HS256 anahtarı
+    /// 
+    /// `JWT_SECRET` veya (ayarlı değilse) başlangıçta rastgele üretilen
+    /// değer. Restart gerektirir.
+    pub jwt_secret: Arc<String>,
+
     /// PostgreSQL connection pool
     /// 
     /// `Option<PgPool>` çünkü veritabanı bağlanması başarısız olabilir.
@@ -63,7 +147,17 @@ pub struct AppState {
     /// - Connection'ları reuse eder (performans)
     /// - Timeout'ları yönetir
     /// - Health check'ler yapar
-    pub db: Option<PgPool>,
+    /// 
+    /// Veritabanı gidip geldikten sonra pool toparlanmazsa
+    /// `db::PoolHealthMonitor` yenisini (aynı `DATABASE_URL` ile) buraya koyar;
+    /// handler'lar pool'u her istekte `pool()` ile alır.
+    pub db: Arc<RwLock<Option<PgPool>>>,
+
+    /// Son pool sağlık kontrolü (`SELECT 1`) başarılı mı?
+    /// 
+    /// `db::PoolHealthMonitor` her 10 saniyede günceller; `/health`
+    /// bilgi amaçlı gösterir.
+    pub db_healthy: Arc<AtomicBool>,
 
     /// Redis connection manager
     /// 
@@ -77,4 +171,164 @@ pub struct AppState {
     /// - Timeout'ları yönetir
     /// - Async-compatible (tokio ile çalışır)
     pub redis: Option<ConnectionManager>,
-}
\ No newline at end of file
+
+    /// MQTT client (cihaz komutları için)
+    /// 
+    /// `DeviceCommand`'lar `devices/{device_id}/commands` topic'ine publish edilir.
+    /// Event loop ayrı bir task'ta çalışır ve bağlantı koparsa yeniden dener.
+    /// 
+    /// `None` ise komut endpoint'leri 503 döner.
+    pub mqtt: Option<AsyncClient>,
+
+    /// MQTT broker'a bağlı mı?
+    /// 
+    /// Event loop ConnAck aldığında `true`, bağlantı hatasında `false` yapar.
+    pub mqtt_connected: Arc<AtomicBool>,
+
+    /// Sunucunun başlatıldığı an (`/health` uptime'ı)
+    pub started_at: Instant,
+
+    /// Gerçek zamanlı sensör akışı
+    /// 
+    /// `POST /api/sensors` her okumayı buraya yayınlar,
+    /// `GET /api/sensors/realtime` abone olup bekler.
+    pub sensor_feed: SensorFeed,
+
+    /// Son hesaplanan dashboard özeti ve hesaplanma anı
+    /// 
+    /// `GET /api/summary` TTL dolmadan tekrar hesaplamaz.
+    pub summary_cache: Arc<RwLock<Option<(Instant, DashboardSummary)>>>,
+
+    /// In-memory webhook storage (fallback amaçlı)
+    /// 
+    /// PostgreSQL bağlanmazsa, webhook kayıtları burada saklanır.
+    pub webhook_store: Arc<RwLock<HashMap<Uuid, Webhook>>>,
+
+    /// Webhook teslimat kuyruğu
+    /// 
+    /// `background::deliver_webhooks` kuyruğu tüketir, başarısız teslimatları
+    /// exponential backoff ile yeniden dener. Sınırlıdır: dolduğunda yeni
+    /// teslimatlar düşürülür ve `/metrics`'te sayılır.
+    pub webhook_queue: WebhookQueue,
+
+    /// Alarm cooldown'ları (`ALERT_COOLDOWN`)
+    /// 
+    /// Eşiğin dışında kalan sensör her okumada webhook tetiklemesin.
+    pub alert_cooldowns: Arc<AlertCooldowns>,
+
+    /// `POST /v1/sensors/ingest` için IP başına istek sınırlayıcı
+    /// 
+    /// Diğer endpoint'lerden bağımsızdır (saniyede `INGEST_RATE_LIMIT` istek).
+    pub ingest_limiter: Arc<IpRateLimiter>,
+
+    /// Dış sisteme (NATS/Kafka) yayın kuyruğu
+    /// 
+    /// Kabul edilen okumalar ve alarmlar buraya atılır, `event_sink::run`
+    /// yayınlar. `None` ise `EVENT_SINK` kapalıdır.
+    pub event_queue: Option<mpsc::Sender<IngestEvent>>,
+
+    /// Özellik bayrakları (`FEATURE_*`, `FEATURE_FLAGS_FILE`)
+    /// 
+    /// `PATCH /v1/admin/features` değiştirir; handler'lar her istekte
+    /// `feature_flags::check_feature` ile kontrol eder.
+    pub features: Arc<RwLock<FeatureFlags>>,
+
+    /// Route şablonu başına istek süresi histogramları
+    /// 
+    /// `metrics::with_http_metrics` middleware'i yazar, `GET /metrics` okur.
+    pub http_metrics: HttpMetrics,
+}
+impl AppState {
+    /// Güncel PostgreSQL pool'u (yoksa in-memory fallback)
+    pub async fn pool(&self) -> Option<PgPool> {
+        self.db.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+impl AppState {
+    /// DB, Redis ve MQTT olmadan (in-memory fallback) test state'i
+    pub fn for_tests() -> Self {
+        Self {
+            cfg: Arc::new(RwLock::new(Config {
+                app_port: 0,
+                app_host: "127.0.0.1".into(),
+                tls_cert_path: None,
+                tls_key_path: None,
+                database_url: None,
+                redis_url: String::new(),
+                mqtt_broker_host: String::new(),
+                mqtt_broker_port: 0,
+                readiness_required: "db".into(),
+                alert_thresholds: Default::default(),
+                compression: "auto".into(),
+                compression_min_size: 1024,
+                request_timeout_secs: 30,
+                slow_request_ms: 1000,
+                max_json_body_bytes: 1024 * 1024,
+                max_upload_bytes: 1024 * 1024,
+                media_upload_dir: std::env::temp_dir().join("rustyflow-test-uploads").to_string_lossy().into_owned(),
+                fallback_store_path: None,
+                fallback_snapshot_interval_secs: 30,
+                mdns_advertise: false,
+                event_sink: "none".into(),
+                event_sink_topic: "rustyflow.ingest".into(),
+                event_sink_queue_size: 16,
+                nats_url: String::new(),
+                kafka_brokers: String::new(),
+                pubsub_enabled: false,
+                db_notify: false,
+                max_future_skew_secs: 300,
+                max_reading_age_days: 30,
+                command_audit_retention_days: 90,
+                stale_threshold_secs: 300,
+                strict_sensor_validation: false,
+                auto_register_devices: false,
+                max_metadata_bytes: 64 * 1024,
+                jwt_secret: None,
+                auth_enabled: false,
+                admin_token: None,
+                provisioning_token: None,
+                log_level: "info".into(),
+            })),
+            config_overrides: Arc::new(ConfigOverrides::default()),
+            log_filter: None,
+            media_store: Arc::new(RwLock::new(HashMap::new())),
+            media_checksums: Arc::new(RwLock::new(HashMap::new())),
+            shadow_store: Arc::new(RwLock::new(HashMap::new())),
+            device_store: Arc::new(RwLock::new(HashMap::new())),
+            device_tokens: Arc::new(RwLock::new(HashMap::new())),
+            sensor_groups: Arc::new(RwLock::new(HashMap::new())),
+            command_audit: Arc::new(RwLock::new(VecDeque::new())),
+            jwt_secret: Arc::new("test-secret".into()),
+            db: Arc::new(RwLock::new(None)),
+            db_healthy: Arc::new(AtomicBool::new(false)),
+            redis: None,
+            mqtt: None,
+            mqtt_connected: Arc::new(AtomicBool::new(false)),
+            started_at: Instant::now(),
+            sensor_feed: SensorFeed::new(),
+            summary_cache: Arc::new(RwLock::new(None)),
+            webhook_store: Arc::new(RwLock::new(HashMap::new())),
+            // Alıcı yok: testler kuyruğu gerektiğinde kendi kanalıyla değiştirir
+            webhook_queue: WebhookQueue::bounded(crate::background::WEBHOOK_QUEUE_CAPACITY).0,
+            alert_cooldowns: Arc::new(AlertCooldowns::default()),
+            ingest_limiter: Arc::new(IpRateLimiter::new(
+                crate::routes::sensors::INGEST_RATE_LIMIT,
+                tokio::time::Duration::from_secs(1),
+            )),
+            event_queue: None,
+            features: Arc::new(RwLock::new(FeatureFlags::default())),
+            http_metrics: HttpMetrics::new(),
+        }
+    }
+
+    /// `for_tests` + PostgreSQL pool
+    pub fn for_tests_with_db(db: PgPool) -> Self {
+        Self {
+            db: Arc::new(RwLock::new(Some(db))),
+            db_healthy: Arc::new(AtomicBool::new(true)),
+            ..Self::for_tests()
+        }
+    }
+}
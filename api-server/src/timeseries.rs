@@ -0,0 +1,393 @@
+//! Zaman Serisi Yardımcıları
+//!
+//! Grafik sorguları için downsampling ve boşluk doldurma. Dashboard'un WASM
+//! grafiği on binlerce noktayı çizemez; `lttb` seriyi görsel şeklini koruyarak
+//! istenen nokta sayısına indirir. Cihaz çevrimdışıyken oluşan boşluklarda
+//! çizgi veri varmış gibi birleşir; `fill_gaps` boşluklara beklenen aralıkta
+//! nokta ekler.
+
+use serde::Deserialize;
+
+/// Largest-Triangle-Three-Buckets ile `threshold` noktaya indir
+///
+/// `point` her elemanın `(x, y)` koordinatıdır; elemanlar `x`'e göre sıralı
+/// olmalıdır. Dönen elemanlar girdinin alt kümesidir ve girdi sırasını korur;
+/// ilk ve son eleman her zaman seçilir. Ara noktalar eşit büyüklükte
+/// bucket'lara bölünür ve her bucket'tan, önceki seçilen nokta ile sonraki
+/// bucket'ın ortalamasıyla en büyük üçgeni oluşturan nokta seçilir.
+///
+/// Eleman sayısı `threshold`'dan fazla değilse girdi aynen döner;
+/// `threshold` 3'ten küçükse sadece ilk ve son eleman döner.
+///
+/// ```ignore
+/// let points: Vec<(f64, f64)> = (0..1000).map(|i| (i as f64, (i as f64).sin())).collect();
+/// assert_eq!(lttb(points, 100, |p| *p).len(), 100);
+/// ```
+pub fn lttb<T>(items: Vec<T>, threshold: usize, point: impl Fn(&T) -> (f64, f64)) -> Vec<T> {
+    let len = items.len();
+    if len <= threshold || len <= 2 {
+        return items;
+    }
+
+    let mut selected = Vec::with_capacity(threshold.max(2));
+    selected.push(0);
+    if threshold >= 3 {
+        // İlk ve son nokta hariç noktalar `threshold - 2` bucket'a bölünür
+        // (tam sayı bölmesi: her bucket en az bir nokta içerir)
+        let bucket_start = |bucket: usize| 1 + bucket * (len - 2) / (threshold - 2);
+
+        let mut previous = 0;
+        for bucket in 0..threshold - 2 {
+            let (start, end) = (bucket_start(bucket), bucket_start(bucket + 1));
+
+            // Sonraki bucket'ın ortalaması (son bucket için son nokta)
+            let next = bucket_start(bucket + 1)..bucket_start(bucket + 2).min(len - 1);
+            let (avg_x, avg_y) = if next.is_empty() {
+                point(&items[len - 1])
+            } else {
+                let count = next.len() as f64;
+                let (sum_x, sum_y) = next
+                    .map(|i| point(&items[i]))
+                    .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+                (sum_x / count, sum_y / count)
+            };
+
+            let (prev_x, prev_y) = point(&items[previous]);
+            let area = |i: usize| {
+                let (x, y) = point(&items[i]);
+                ((prev_x - avg_x) * (y - prev_y) - (prev_x - x) * (avg_y - prev_y)).abs()
+            };
+            // Eşit alanlarda ilk nokta seçilir
+            previous = (start..end).fold(start, |best, i| if area(i) > area(best) { i } else { best });
+            selected.push(previous);
+        }
+    }
+    selected.push(len - 1);
+
+    let mut selected = selected.into_iter().peekable();
+    items
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, item)| selected.next_if_eq(&i).map(|_| item))
+        .collect()
+}
+
+/// Boşluk doldurma yöntemi (`?fill=none|null|previous|linear`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FillMethod {
+    /// Boşluklar olduğu gibi kalır
+    #[default]
+    None,
+    /// Boşluğa değeri `null` noktalar eklenir (grafik çizgiyi keser)
+    Null,
+    /// Boşluktan önceki değer taşınır
+    Previous,
+    /// Boşluğun iki ucundaki değerler arasında doğrusal interpolasyon
+    Linear,
+}
+
+/// `fill_gaps` çıktısının bir elemanı
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filled<T> {
+    /// Girdideki eleman
+    Source(T),
+    /// Boşluğa eklenen nokta (`Null` yönteminde `y` yok)
+    Gap { x: i64, y: Option<f64> },
+}
+
+/// Ardışık x'ler arasındaki farkların medyanı (beklenen örnekleme aralığı)
+///
+/// Aynı x'li elemanlar (fark 0) ve sırasız çiftler yok sayılır; çift sayıda
+/// farkta küçük olan medyan döner. Pozitif fark yoksa `None`.
+pub fn median_interval(xs: impl IntoIterator<Item = i64>) -> Option<i64> {
+    let mut xs = xs.into_iter();
+    let mut previous = xs.next()?;
+    let mut deltas: Vec<i64> = xs
+        .filter_map(|x| {
+            let delta = x - std::mem::replace(&mut previous, x);
+            (delta > 0).then_some(delta)
+        })
+        .collect();
+    if deltas.is_empty() {
+        return None;
+    }
+    let middle = (deltas.len() - 1) / 2;
+    Some(*deltas.select_nth_unstable(middle).1)
+}
+
+/// `from` ile `to` arasındaki boşluğa eklenecek nokta sayısı
+///
+/// Noktalar `from + k * interval` anlarına konur; `to`'ya yarım aralıktan
+/// yakın olan anlar atlanır (1.5 aralıktan kısa titremeler boşluk sayılmaz).
+/// Boşluk `max_gap`'ten uzunsa doldurulmaz.
+fn missing_slots(from: i64, to: i64, interval: i64, max_gap: Option<i64>) -> i64 {
+    let delta = i128::from(to) - i128::from(from);
+    let interval_wide = i128::from(interval);
+    if interval <= 0 || max_gap.is_some_and(|max| delta > i128::from(max)) || 2 * delta <= interval_wide {
+        return 0;
+    }
+    // En büyük k: 2·k·interval < 2·delta − interval
+    i64::try_from((2 * delta - interval_wide - 1) / (2 * interval_wide)).unwrap_or(i64::MAX)
+}
+
+/// `fill_gaps`'in ekleyeceği toplam nokta sayısı
+///
+/// Doldurmadan önce yanıt boyutunu sınırlamak için; argümanlar `fill_gaps`
+/// ile aynıdır.
+pub fn missing_points<T>(items: &[T], interval: i64, max_gap: Option<i64>, x: impl Fn(&T) -> i64) -> u64 {
+    items
+        .windows(2)
+        .map(|pair| missing_slots(x(&pair[0]), x(&pair[1]), interval, max_gap) as u64)
+        .fold(0, u64::saturating_add)
+}
+
+/// Seriyi `interval` aralığında eksik noktalarla doldur
+///
+/// `point` her elemanın `(x, y)` koordinatıdır; elemanlar `x`'e göre sıralı
+/// olmalıdır (sırasız çiftler arasına nokta eklenmez). Girdi elemanları
+/// sırasıyla `Filled::Source` olarak döner; aralarındaki boşluğa
+/// `Filled::Gap` noktaları eklenir (bkz. `missing_slots`). İlk elemandan önce
+/// ve son elemandan sonra nokta eklenmez; `max_gap`'ten uzun boşluklar boş
+/// kalır.
+///
+/// ```ignore
+/// let points = vec![(0, 1.0), (3, 4.0)];
+/// let filled = fill_gaps(points, FillMethod::Linear, 1, None, |p| *p);
+/// // Source(0, 1.0), Gap(1, 2.0), Gap(2, 3.0), Source(3, 4.0)
+/// ```
+pub fn fill_gaps<T>(
+    items: Vec<T>,
+    method: FillMethod,
+    interval: i64,
+    max_gap: Option<i64>,
+    point: impl Fn(&T) -> (i64, f64),
+) -> Vec<Filled<T>> {
+    if method == FillMethod::None {
+        return items.into_iter().map(Filled::Source).collect();
+    }
+
+    let mut filled = Vec::with_capacity(items.len());
+    let mut previous: Option<(i64, f64)> = None;
+    for item in items {
+        let (x, y) = point(&item);
+        if let Some((prev_x, prev_y)) = previous {
+            for k in 1..=missing_slots(prev_x, x, interval, max_gap) {
+                let slot = prev_x + k * interval;
+                let value = match method {
+                    FillMethod::None | FillMethod::Null => None,
+                    FillMethod::Previous => Some(prev_y),
+                    FillMethod::Linear => Some(prev_y + (y - prev_y) * (slot - prev_x) as f64 / (x - prev_x) as f64),
+                };
+                filled.push(Filled::Gap { x: slot, y: value });
+            }
+        }
+        previous = Some((x, y));
+        filled.push(Filled::Source(item));
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_lttb_keeps_peaks() {
+        // Düz bir çizgi üzerinde tek bir tepe ve tek bir çukur
+        let points: Vec<(f64, f64)> =
+            (0..100).map(|i| (i as f64, if i == 37 { 50.0 } else if i == 71 { -50.0 } else { 0.0 })).collect();
+        let sampled = lttb(points, 10, |p| *p);
+        assert_eq!(sampled.len(), 10);
+        assert!(sampled.contains(&(37.0, 50.0)) && sampled.contains(&(71.0, -50.0)));
+        assert_eq!((sampled[0], sampled[9]), ((0.0, 0.0), (99.0, 0.0)));
+    }
+
+    #[test]
+    fn test_lttb_small_inputs_and_thresholds() {
+        let points: Vec<(f64, f64)> = (0..5).map(|i| (i as f64, i as f64)).collect();
+        assert_eq!(lttb(points.clone(), 5, |p| *p), points);
+        assert_eq!(lttb(points.clone(), 500, |p| *p), points);
+        assert_eq!(lttb(points.clone(), 0, |p| *p), [(0.0, 0.0), (4.0, 4.0)]);
+        assert_eq!(lttb(points[..1].to_vec(), 0, |p| *p), [(0.0, 0.0)]);
+        assert!(lttb(Vec::<(f64, f64)>::new(), 10, |p| *p).is_empty());
+    }
+
+    /// Doldurulmuş seriyi `(x, y, eklenen mi)` olarak düzleştir
+    fn flatten(filled: Vec<Filled<(i64, f64)>>) -> Vec<(i64, Option<f64>, bool)> {
+        filled
+            .into_iter()
+            .map(|p| match p {
+                Filled::Source((x, y)) => (x, Some(y), false),
+                Filled::Gap { x, y } => (x, y, true),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_median_interval_irregular_series() {
+        // 10 sn örnekleme, bir titreme, bir kopukluk ve bir tekrar
+        let xs = [0, 10, 20, 31, 40, 50, 50, 600, 610];
+        assert_eq!(median_interval(xs), Some(10));
+        assert_eq!(median_interval([0, 10, 30, 60]), Some(20));
+        assert_eq!(median_interval([5, 5, 5]), None);
+        assert_eq!(median_interval([5]), None);
+        assert_eq!(median_interval(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_fill_none_returns_input() {
+        let points = vec![(0, 1.0), (100, 2.0)];
+        assert_eq!(flatten(fill_gaps(points, FillMethod::None, 10, None, |p| *p)), [(0, Some(1.0), false), (100, Some(2.0), false)]);
+    }
+
+    #[test]
+    fn test_fill_null_marks_gap() {
+        let points = vec![(0, 1.0), (10, 2.0), (50, 3.0), (60, 4.0)];
+        assert_eq!(
+            flatten(fill_gaps(points, FillMethod::Null, 10, None, |p| *p)),
+            [
+                (0, Some(1.0), false),
+                (10, Some(2.0), false),
+                (20, None, true),
+                (30, None, true),
+                (40, None, true),
+                (50, Some(3.0), false),
+                (60, Some(4.0), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fill_previous_carries_last_value() {
+        let points = vec![(0, 5.0), (30, 8.0)];
+        assert_eq!(
+            flatten(fill_gaps(points, FillMethod::Previous, 10, None, |p| *p)),
+            [(0, Some(5.0), false), (10, Some(5.0), true), (20, Some(5.0), true), (30, Some(8.0), false)]
+        );
+    }
+
+    #[test]
+    fn test_fill_linear_interpolates_irregular_gap() {
+        // Boşluk aralığın katı değil: 30 ile 35 arası yarım aralık → 30 atlanır
+        let points = vec![(0, 0.0), (35, 7.0)];
+        assert_eq!(
+            flatten(fill_gaps(points, FillMethod::Linear, 10, None, |p| *p)),
+            [(0, Some(0.0), false), (10, Some(2.0), true), (20, Some(4.0), true), (35, Some(7.0), false)]
+        );
+
+        let points = vec![(0, 0.0), (36, 36.0)];
+        let filled = flatten(fill_gaps(points, FillMethod::Linear, 10, None, |p| *p));
+        assert_eq!(filled.iter().filter(|p| p.2).map(|p| p.0).collect::<Vec<_>>(), [10, 20, 30]);
+        assert_eq!(filled[3].1, Some(30.0));
+    }
+
+    #[test]
+    fn test_fill_ignores_jitter() {
+        // 1.5 aralığa kadar gecikmeler boşluk sayılmaz
+        let points = vec![(0, 1.0), (12, 1.0), (27, 1.0), (34, 1.0), (44, 1.0)];
+        assert_eq!(missing_points(&points, 10, None, |p| p.0), 0);
+        assert_eq!(fill_gaps(points, FillMethod::Null, 10, None, |p| *p).len(), 5);
+
+        // Tam iki aralık → tek nokta
+        assert_eq!(missing_points(&[(0, 1.0), (20, 1.0)], 10, None, |p| p.0), 1);
+    }
+
+    #[test]
+    fn test_fill_respects_max_gap() {
+        // İlk boşluk (40) doldurulur, ikincisi (1000) max_gap'ten uzun → boş kalır
+        let points = vec![(0, 1.0), (40, 5.0), (1040, 9.0), (1050, 9.0)];
+        assert_eq!(missing_points(&points, 10, Some(100), |p| p.0), 3);
+        let filled = flatten(fill_gaps(points, FillMethod::Previous, 10, Some(100), |p| *p));
+        assert_eq!(
+            filled,
+            [
+                (0, Some(1.0), false),
+                (10, Some(1.0), true),
+                (20, Some(1.0), true),
+                (30, Some(1.0), true),
+                (40, Some(5.0), false),
+                (1040, Some(9.0), false),
+                (1050, Some(9.0), false),
+            ]
+        );
+
+        // max_gap tam boşluk uzunluğunda → doldurulur
+        assert_eq!(missing_points(&[(0, 1.0), (40, 1.0)], 10, Some(40), |p| p.0), 3);
+    }
+
+    #[test]
+    fn test_fill_edge_cases() {
+        let empty: Vec<(i64, f64)> = Vec::new();
+        assert!(fill_gaps(empty, FillMethod::Linear, 10, None, |p| *p).is_empty());
+        assert_eq!(fill_gaps(vec![(0, 1.0)], FillMethod::Linear, 10, None, |p| *p), [Filled::Source((0, 1.0))]);
+
+        // Geçersiz aralık veya sırasız/aynı x'ler arasına nokta eklenmez
+        let points = vec![(0, 1.0), (100, 2.0), (50, 3.0), (50, 4.0)];
+        assert_eq!(missing_points(&points, 0, None, |p| p.0), 0);
+        assert_eq!(missing_points(&points, -10, None, |p| p.0), 0);
+        assert_eq!(missing_points(&points, 10, None, |p| p.0), 9);
+
+        // Çok büyük boşluklar taşmaz
+        assert_eq!(missing_points(&[(i64::MIN, 0.0), (i64::MAX, 0.0)], 1, None, |p| p.0), i64::MAX as u64);
+    }
+
+    /// Artan x değerleri ve rastgele y değerleri
+    fn series() -> impl Strategy<Value = Vec<(f64, f64)>> {
+        prop::collection::vec((1u32..1000, -1e6f64..1e6), 0..2000).prop_map(|steps| {
+            let mut x = 0.0;
+            steps.into_iter().map(|(step, y)| { x += f64::from(step); (x, y) }).collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_lttb_output_is_ordered_subset(points in series(), threshold in 0usize..600) {
+            let sampled = lttb(points.clone(), threshold, |p| *p);
+
+            // Girdinin alt kümesi, girdi sırasında (x'ler kesin artan)
+            let mut rest = points.iter();
+            for p in &sampled {
+                prop_assert!(rest.any(|q| q == p), "{:?} not in input order", p);
+            }
+            prop_assert!(sampled.windows(2).all(|w| w[0].0 < w[1].0));
+
+            // Uzunluk ve uç noktalar
+            let expected = if points.len() <= threshold { points.len() } else { threshold.max(2).min(points.len()) };
+            prop_assert_eq!(sampled.len(), expected);
+            prop_assert_eq!(sampled.first(), points.first());
+            prop_assert_eq!(sampled.last(), points.last());
+        }
+
+        #[test]
+        fn prop_fill_keeps_source_and_stays_in_range(
+            steps in prop::collection::vec((1i64..200, -1e3f64..1e3), 0..300),
+            interval in 1i64..50,
+            max_gap in prop::option::of(1i64..400),
+        ) {
+            let mut x = 0;
+            let points: Vec<(i64, f64)> = steps.into_iter().map(|(step, y)| { x += step; (x, y) }).collect();
+            let expected = missing_points(&points, interval, max_gap, |p| p.0);
+            let filled = fill_gaps(points.clone(), FillMethod::Linear, interval, max_gap, |p| *p);
+            prop_assert_eq!(filled.len() as u64, points.len() as u64 + expected);
+
+            // Kaynak noktalar sırayla korunur, x'ler kesin artar
+            let sources: Vec<_> = filled.iter().filter_map(|p| match p { Filled::Source(p) => Some(*p), _ => None }).collect();
+            prop_assert_eq!(&sources, &points);
+            let xs: Vec<i64> = filled.iter().map(|p| match p { Filled::Source(p) => p.0, Filled::Gap { x, .. } => *x }).collect();
+            prop_assert!(xs.windows(2).all(|w| w[0] < w[1]));
+
+            // Interpolasyon komşuların arasında kalır; eklenen noktalar arası aralık kadar
+            for (i, p) in filled.iter().enumerate() {
+                if let Filled::Gap { x, y: Some(y) } = p {
+                    let prev = filled[..i].iter().rev().find_map(|p| match p { Filled::Source(p) => Some(*p), _ => None }).unwrap();
+                    let next = filled[i..].iter().find_map(|p| match p { Filled::Source(p) => Some(*p), _ => None }).unwrap();
+                    prop_assert!(*y >= prev.1.min(next.1) - 1e-9 && *y <= prev.1.max(next.1) + 1e-9);
+                    prop_assert_eq!((x - prev.0) % interval, 0);
+                    prop_assert!(2 * (next.0 - x) > interval);
+                }
+            }
+        }
+    }
+}
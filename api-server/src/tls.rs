@@ -0,0 +1,124 @@
+//! HTTPS Desteği (rustls)
+//!
+//! `TLS_CERT_PATH` ve `TLS_KEY_PATH` ayarlıysa API server `axum-server`'ın
+//! rustls acceptor'ı ile HTTPS dinler; küçük kurulumlarda ayrı bir reverse
+//! proxy gerekmez.
+//!
+//! Sertifika yenilendiğinde (ör. Let's Encrypt) sunucuya SIGHUP gönderilir;
+//! dosyalar yeniden okunur ve yeni bağlantılar yeni sertifikayı kullanır.
+//! Yeniden okuma başarısız olursa eski sertifika kullanılmaya devam eder.
+
+use std::{path::Path, sync::Arc};
+
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+
+/// Sertifika zincirini ve key'i oku, eşleştiklerini doğrula
+///
+/// Okunamayan dosya, boş zincir, geçersiz key veya sertifikayla eşleşmeyen
+/// key için hangi dosyanın sorunlu olduğunu söyleyen bir hata döner.
+pub fn load_server_config(cert_path: &Path, key_path: &Path) -> Result<ServerConfig, String> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("cannot read TLS certificate {}: {e}", cert_path.display()))?;
+    if certs.is_empty() {
+        return Err(format!("no certificates found in {}", cert_path.display()));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| format!("cannot read TLS key {}: {e}", key_path.display()))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| {
+            format!("TLS key {} does not fit certificate {}: {e}", key_path.display(), cert_path.display())
+        })?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+/// Her SIGHUP'ta sertifikayı yeniden oku ve `config`'e uygula
+///
+/// `main` tarafından `tokio::spawn` ile başlatılır. Mevcut bağlantılar
+/// etkilenmez; hata olursa loglanır ve eski sertifika kalır.
+#[cfg(unix)]
+pub async fn reload_on_sighup(config: RustlsConfig, cert_path: std::path::PathBuf, key_path: std::path::PathBuf) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            tracing::warn!("Cannot listen for SIGHUP, TLS certificate reload disabled: {e}");
+            return;
+        }
+    };
+    while hangups.recv().await.is_some() {
+        match load_server_config(&cert_path, &key_path) {
+            Ok(server_config) => {
+                config.reload_from_config(Arc::new(server_config));
+                tracing::info!("🔐 TLS certificate reloaded from {}", cert_path.display());
+            }
+            Err(e) => tracing::error!("TLS certificate reload failed, keeping the current one: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// `tests/fixtures/tls`: self-signed localhost sertifikası (P-256, 100 yıl)
+    /// ve ona ait olmayan ikinci bir key
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls").join(name)
+    }
+
+    #[test]
+    fn test_loads_matching_pair() {
+        let config = load_server_config(&fixture("cert.pem"), &fixture("key.pem")).unwrap();
+        assert_eq!(config.alpn_protocols, vec![b"h2".to_vec(), b"http/1.1".to_vec()]);
+    }
+
+    #[test]
+    fn test_rejects_mismatched_key() {
+        let err = load_server_config(&fixture("cert.pem"), &fixture("other_key.pem")).unwrap_err();
+        assert!(err.contains("does not fit certificate"), "{err}");
+    }
+
+    #[test]
+    fn test_rejects_unreadable_files() {
+        let err = load_server_config(&fixture("missing.pem"), &fixture("key.pem")).unwrap_err();
+        assert!(err.contains("cannot read TLS certificate") && err.contains("missing.pem"), "{err}");
+
+        let err = load_server_config(&fixture("cert.pem"), &fixture("missing.pem")).unwrap_err();
+        assert!(err.contains("cannot read TLS key"), "{err}");
+
+        // Key dosyası sertifika yerine verilirse zincir boştur
+        let err = load_server_config(&fixture("key.pem"), &fixture("key.pem")).unwrap_err();
+        assert!(err.contains("no certificates found"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_serves_https_with_loaded_certificate() {
+        let server_config = load_server_config(&fixture("cert.pem"), &fixture("key.pem")).unwrap();
+        let config = RustlsConfig::from_config(Arc::new(server_config));
+        let handle = axum_server::Handle::new();
+        let app = axum::Router::new().route("/", axum::routing::get(|| async { "ok" }));
+        let server = axum_server::bind_rustls("127.0.0.1:0".parse().unwrap(), config)
+            .handle(handle.clone())
+            .serve(app.into_make_service());
+        tokio::spawn(server);
+        let addr = handle.listening().await.unwrap();
+
+        let cert = reqwest::Certificate::from_pem(&std::fs::read(fixture("cert.pem")).unwrap()).unwrap();
+        let client = reqwest::Client::builder().add_root_certificate(cert).build().unwrap();
+        let url = format!("https://localhost:{}/", addr.port());
+        assert_eq!(client.get(&url).send().await.unwrap().text().await.unwrap(), "ok");
+
+        handle.shutdown();
+    }
+}
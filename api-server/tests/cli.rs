@@ -0,0 +1,62 @@
+//! Komut satırı entegrasyon testi
+//!
+//! `api-server` binary'si alt komutlarla çalıştırılır ve çıkış kodları
+//! kontrol edilir. Ortam temizlenir ve çalışma dizini boş bir geçici dizindir
+//! (`.env` / `rustyflow.toml` okunmasın).
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use uuid::Uuid;
+
+/// Test bitince (panic dahil) çalışma dizinini sil
+struct WorkDir(PathBuf);
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn run(args: &[&str], env: &[(&str, &str)]) -> Output {
+    let work_dir = WorkDir(std::env::temp_dir().join(format!("api-server-cli-test-{}", Uuid::new_v4())));
+    std::fs::create_dir_all(&work_dir.0).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_api-server"))
+        .args(args)
+        .current_dir(&work_dir.0)
+        .env_clear()
+        .envs(env.iter().copied())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_check_config_exit_codes_and_flag_precedence() {
+    let output = run(&["check-config", "--port", "9000"], &[("APP_PORT", "8000")]);
+    assert_eq!(output.status.code(), Some(0), "{output:?}");
+    let printed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(printed["app_port"], 9000);
+
+    // Geçersiz değer, parse edilemeyen env var, parse edilemeyen flag
+    assert_eq!(run(&["check-config"], &[("APP_HOST", "not-an-ip")]).status.code(), Some(1));
+    assert_eq!(run(&["check-config"], &[("APP_PORT", "abc")]).status.code(), Some(1));
+    assert_eq!(run(&["check-config", "--port", "abc"], &[]).status.code(), Some(2));
+    assert_eq!(run(&["frobnicate"], &[]).status.code(), Some(2));
+}
+
+#[test]
+fn test_migrate_without_database_fails() {
+    let output = run(&["migrate"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("DATABASE_URL is not set"));
+}
+
+#[test]
+fn test_create_api_key_without_database_fails() {
+    // Saklanamayan anahtar kabul edilmezdi: hiçbir şey yazdırılmaz
+    let output = run(&["create-api-key", "--name", "ci"], &[]);
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("DATABASE_URL is not set"));
+}
@@ -0,0 +1,189 @@
+//! MQTT Teslim Onayı Takibi
+//!
+//! QoS 1/2 publish'leri broker onaylayana kadar "bekleyen" sayılır
+//! (QoS 1: `PubAck`, QoS 2: `PubComp`). Bekleyen sayı `MAX_PENDING_ACKS`'i
+//! aşarsa sensör döngüsü bir aralık okuma yapmadan bekler; broker yavaş
+//! veya erişilemezken rumqttc'nin kuyruğu sınırsız büyümez.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use rumqttc::{Event, Packet, QoS};
+use tokio::time::{Duration, Instant};
+
+/// Bekleyen onaylar bu süreden uzun eşiğin üstünde kalırsa uyarı loglanır
+pub const ACK_STALL_WARN_AFTER: Duration = Duration::from_secs(30);
+
+/// Onay bekleyen publish sayacı
+///
+/// Sensör döngüsü publish'ten önce artırır, event loop task'ı onay gelince azaltır.
+/// Artırma `publish().await`'ten sonra yapılsaydı onay sayaçtan önce gelebilir,
+/// sıfırda yutulur ve sayaç bir fazla kalırdı.
+#[derive(Debug, Clone, Default)]
+pub struct PendingAcks {
+    pending: Arc<AtomicU64>,
+}
+
+impl PendingAcks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Kuyruğa alınacak publish'i say (QoS 0 onay beklemez)
+    ///
+    /// `publish()`'ten önce çağrılır; publish hata dönerse `on_publish_failed` geri alır.
+    pub fn on_publish(&self, qos: QoS) {
+        if qos != QoS::AtMostOnce {
+            self.pending.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Kuyruğa alınamayan publish'in sayımını geri al
+    pub fn on_publish_failed(&self, qos: QoS) {
+        if qos != QoS::AtMostOnce {
+            let _ = self.pending.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+        }
+    }
+
+    /// Event loop'tan gelen olayı işle; onaysa sayacı azalt
+    ///
+    /// Sayaç sıfırın altına inmez (ör. yeniden bağlanma sonrası gelen fazla onay).
+    pub fn on_event(&self, event: &Event) {
+        if let Event::Incoming(Packet::PubAck(_) | Packet::PubComp(_)) = event {
+            let _ = self.pending.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+        }
+    }
+
+    /// Şu an onay bekleyen publish sayısı
+    pub fn count(&self) -> u64 {
+        self.pending.load(Ordering::Relaxed)
+    }
+}
+
+/// Backpressure kararı
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Eşiğin altında, normal devam
+    Clear,
+    /// Eşiğin üstünde, bu aralıkta okuma yapma
+    Throttle,
+    /// Eşiğin üstünde ve `ACK_STALL_WARN_AFTER`'dan uzun süredir öyle
+    /// (her tıkanma döneminde bir kez döner, sonra `Throttle`)
+    Stalled(Duration),
+}
+
+/// Bekleyen onay sayısından backpressure kararı veren durum makinesi
+#[derive(Debug)]
+pub struct AckBackpressure {
+    max_pending: u64,
+    congested_since: Option<Instant>,
+    warned: bool,
+}
+
+impl AckBackpressure {
+    pub fn new(max_pending: u64) -> Self {
+        Self { max_pending, congested_since: None, warned: false }
+    }
+
+    /// `pending` sayısına göre bu tick'te ne yapılacağını belirle
+    pub fn observe(&mut self, pending: u64, now: Instant) -> Backpressure {
+        if pending <= self.max_pending {
+            self.congested_since = None;
+            self.warned = false;
+            return Backpressure::Clear;
+        }
+        let since = *self.congested_since.get_or_insert(now);
+        let stalled_for = now.duration_since(since);
+        if stalled_for > ACK_STALL_WARN_AFTER && !self.warned {
+            self.warned = true;
+            return Backpressure::Stalled(stalled_for);
+        }
+        Backpressure::Throttle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rumqttc::{Outgoing, PubAck, PubComp, PubRec};
+
+    fn incoming(packet: Packet) -> Event {
+        Event::Incoming(packet)
+    }
+
+    #[test]
+    fn test_counts_only_acknowledged_qos() {
+        let acks = PendingAcks::new();
+        acks.on_publish(QoS::AtMostOnce);
+        acks.on_publish(QoS::AtLeastOnce);
+        acks.on_publish(QoS::ExactlyOnce);
+        assert_eq!(acks.count(), 2);
+    }
+
+    #[test]
+    fn test_failed_publish_is_rolled_back() {
+        let acks = PendingAcks::new();
+        acks.on_publish(QoS::AtLeastOnce);
+        // Onay, publish çağrısı dönmeden gelebilir: sayaç zaten artırılmış olmalı
+        acks.on_event(&incoming(Packet::PubAck(PubAck::new(1))));
+        assert_eq!(acks.count(), 0);
+
+        acks.on_publish(QoS::ExactlyOnce);
+        acks.on_publish_failed(QoS::ExactlyOnce);
+        acks.on_publish(QoS::AtMostOnce);
+        acks.on_publish_failed(QoS::AtMostOnce);
+        assert_eq!(acks.count(), 0);
+    }
+
+    #[test]
+    fn test_event_loop_acks_decrement_pending() {
+        let acks = PendingAcks::new();
+        let event_loop_side = acks.clone();
+        for _ in 0..3 {
+            acks.on_publish(QoS::AtLeastOnce);
+        }
+        acks.on_publish(QoS::ExactlyOnce);
+
+        // Event loop'un gördüğü sıra: giden publish'ler, ardından onaylar
+        let events = [
+            Event::Outgoing(Outgoing::Publish(1)),
+            incoming(Packet::PubAck(PubAck::new(1))),
+            incoming(Packet::PubAck(PubAck::new(2))),
+            // QoS 2: PubRec henüz teslim değil, PubComp bitirir
+            incoming(Packet::PubRec(PubRec::new(4))),
+            incoming(Packet::PubComp(PubComp::new(4))),
+        ];
+        for event in &events {
+            event_loop_side.on_event(event);
+        }
+        assert_eq!(acks.count(), 1);
+
+        // Fazladan gelen onaylar sayacı sıfırın altına indirmez
+        for pkid in [3, 5] {
+            event_loop_side.on_event(&incoming(Packet::PubAck(PubAck::new(pkid))));
+        }
+        assert_eq!(acks.count(), 0);
+    }
+
+    #[test]
+    fn test_backpressure_throttles_above_threshold_and_warns_once() {
+        let start = Instant::now();
+        let mut backpressure = AckBackpressure::new(100);
+
+        assert_eq!(backpressure.observe(100, start), Backpressure::Clear);
+        assert_eq!(backpressure.observe(101, start), Backpressure::Throttle);
+        assert_eq!(backpressure.observe(150, start + Duration::from_secs(30)), Backpressure::Throttle);
+        assert_eq!(
+            backpressure.observe(150, start + Duration::from_secs(31)),
+            Backpressure::Stalled(Duration::from_secs(31))
+        );
+        assert_eq!(backpressure.observe(150, start + Duration::from_secs(40)), Backpressure::Throttle);
+
+        // Eşiğin altına inince süre ve uyarı sıfırlanır
+        assert_eq!(backpressure.observe(10, start + Duration::from_secs(41)), Backpressure::Clear);
+        assert_eq!(backpressure.observe(101, start + Duration::from_secs(60)), Backpressure::Throttle);
+        assert_eq!(backpressure.observe(101, start + Duration::from_secs(80)), Backpressure::Throttle);
+    }
+}
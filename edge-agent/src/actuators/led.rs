@@ -0,0 +1,141 @@
+//! Tek bir GPIO pinine bağlı LED
+//!
+//! `gpio` feature'ı açıksa pin rppal ile çıkış olarak açılır. Feature
+//! kapalıysa veya pin açılamazsa (Raspberry Pi değil, yetki yok) pin yoktur:
+//! durum sadece bellekte tutulur ve pin işlemleri no-op olur.
+
+use shared_types::messages::LedState;
+#[cfg(feature = "gpio")]
+use tracing::warn;
+
+/// GPIO pinine bağlı LED
+pub struct LedController {
+    /// Açılabildiyse çıkış pini
+    #[cfg(feature = "gpio")]
+    pin: Option<rppal::gpio::OutputPin>,
+    /// BCM GPIO pin numarası
+    gpio_pin_number: u8,
+    /// Açık mı?
+    on: bool,
+    /// Son `set`'in parlaklığı (`restore` için)
+    brightness: Option<u8>,
+}
+
+impl LedController {
+    /// `pin_number`'ı çıkış olarak açmayı dene (başarısızsa uyarı loglanır)
+    pub fn new(pin_number: u8) -> Self {
+        Self {
+            #[cfg(feature = "gpio")]
+            pin: open_pin(pin_number),
+            gpio_pin_number: pin_number,
+            on: false,
+            brightness: None,
+        }
+    }
+
+    /// BCM GPIO pin numarası
+    pub fn pin_number(&self) -> u8 {
+        self.gpio_pin_number
+    }
+
+    /// LED açık mı?
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    /// Durumu uygula ve yeni durumu döndür (`true` = açık)
+    pub fn set_state(&mut self, state: &LedState, brightness: Option<u8>) -> bool {
+        let on = match state {
+            LedState::On => true,
+            LedState::Off => false,
+            LedState::Toggle => !self.on,
+        };
+        self.set(on, brightness);
+        on
+    }
+
+    /// LED'i açık/kapalı duruma getir
+    pub fn set(&mut self, on: bool, brightness: Option<u8>) {
+        self.on = on;
+        self.brightness = brightness;
+        self.restore();
+    }
+
+    /// Pini durumu değiştirmeden sür (yanıp sönme adımları için)
+    pub fn flash(&mut self, on: bool) {
+        #[cfg(feature = "gpio")]
+        if let Some(out) = &mut self.pin {
+            drive_pin(out, on, None);
+        }
+        #[cfg(not(feature = "gpio"))]
+        let _ = on;
+    }
+
+    /// Pini kayıtlı duruma (açık/kapalı ve parlaklık) geri getir
+    pub fn restore(&mut self) {
+        #[cfg(feature = "gpio")]
+        if let Some(out) = &mut self.pin {
+            drive_pin(out, self.on, self.brightness);
+        }
+    }
+}
+
+/// Pini çıkış olarak aç; GPIO erişilemezse `None`
+#[cfg(feature = "gpio")]
+fn open_pin(pin_number: u8) -> Option<rppal::gpio::OutputPin> {
+    let pin = rppal::gpio::Gpio::new().and_then(|gpio| gpio.get(pin_number));
+    match pin {
+        Ok(pin) => Some(pin.into_output()),
+        Err(e) => {
+            warn!("GPIO{} not available, LED will only be logged: {}", pin_number, e);
+            None
+        }
+    }
+}
+
+/// Pini sür: parlaklık verildiyse software PWM, yoksa düz high/low
+#[cfg(feature = "gpio")]
+fn drive_pin(out: &mut rppal::gpio::OutputPin, on: bool, brightness: Option<u8>) {
+    match (on, brightness) {
+        (true, Some(b)) => {
+            if let Err(e) = out.set_pwm_frequency(100.0, b as f64 / 255.0) {
+                warn!("PWM failed on GPIO{}: {}", out.pin(), e);
+            }
+        }
+        (true, None) => {
+            let _ = out.clear_pwm();
+            out.set_high();
+        }
+        (false, _) => {
+            let _ = out.clear_pwm();
+            out.set_low();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_state_without_pin() {
+        let mut led = LedController::new(17);
+        assert_eq!((led.pin_number(), led.is_on()), (17, false));
+
+        assert!(led.set_state(&LedState::On, Some(128)));
+        assert!(!led.set_state(&LedState::Toggle, None));
+        assert!(led.set_state(&LedState::Toggle, None));
+        assert!(!led.set_state(&LedState::Off, None));
+    }
+
+    #[test]
+    fn test_flash_keeps_state() {
+        let mut led = LedController::new(17);
+        led.set(true, Some(64));
+
+        led.flash(false);
+        assert!(led.is_on());
+        led.restore();
+        assert_eq!((led.is_on(), led.brightness), (true, Some(64)));
+    }
+}
@@ -0,0 +1,193 @@
+//! Aktüatörler
+//!
+//! Cihaz komutlarıyla kontrol edilen çıkışlar (LED vb.).
+//! `gpio` feature'ı açıksa ve Raspberry Pi GPIO erişilebilirse gerçek pinler
+//! rppal ile sürülür (bkz. `led`); aksi halde durum sadece loglanır ve
+//! bellekte tutulur.
+
+mod led;
+
+use std::collections::HashMap;
+
+use shared_types::messages::{LedCommand, LedState};
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+pub use led::LedController;
+
+/// `LedCommand.blink` ile yanıp sönmenin periyodu (ms)
+pub const BLINK_PERIOD_MS: u64 = 500;
+
+/// `LedCommand.blink`'in üst sınırı (20 × 500 ms = 10 sn)
+pub const MAX_BLINK_TIMES: u32 = 20;
+
+/// LED durumlarını yöneten aktüatör
+pub struct LedActuator {
+    /// Pin eşlemesi olan LED'ler (led_id → controller)
+    leds: HashMap<String, LedController>,
+    /// Pin eşlemesi olmayan LED'lerin durumu (sadece loglanır)
+    unmapped: HashMap<String, bool>,
+}
+
+impl LedActuator {
+    /// Yeni LED aktüatörü oluştur
+    ///
+    /// `pin_map` formatı: `led_01:17,led_02:27` (led_id:BCM pin).
+    /// Geçersiz girdiler uyarı ile atlanır.
+    pub fn new(pin_map: &str) -> Self {
+        let leds = pin_map
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .filter_map(|entry| {
+                let parsed = entry
+                    .split_once(':')
+                    .and_then(|(id, pin)| Some((id.trim().to_string(), pin.trim().parse().ok()?)));
+                if parsed.is_none() {
+                    warn!("Ignoring invalid LED pin mapping: {}", entry);
+                }
+                parsed
+            })
+            .map(|(id, pin)| (id, LedController::new(pin)))
+            .collect();
+
+        Self { leds, unmapped: HashMap::new() }
+    }
+
+    /// LED'in mevcut durumu (bilinmiyorsa kapalı)
+    pub fn is_on(&self, led_id: &str) -> bool {
+        match self.leds.get(led_id) {
+            Some(led) => led.is_on(),
+            None => self.unmapped.get(led_id).copied().unwrap_or(false),
+        }
+    }
+
+    /// LED komutunu uygula ve yeni durumu döndür (`true` = açık)
+    ///
+    /// `blink` burada yok sayılır; yanıp sönme `blink` ile ayrı sürülür.
+    pub fn apply(&mut self, cmd: &LedCommand) -> bool {
+        let on = match self.leds.get_mut(&cmd.led_id) {
+            Some(led) => led.set_state(&cmd.state, cmd.brightness),
+            None => {
+                let on = match cmd.state {
+                    LedState::On => true,
+                    LedState::Off => false,
+                    LedState::Toggle => !self.is_on(&cmd.led_id),
+                };
+                self.unmapped.insert(cmd.led_id.clone(), on);
+                on
+            }
+        };
+        self.log(&cmd.led_id, on, cmd.brightness);
+        on
+    }
+
+    /// LED'i açık/kapalı duruma getir
+    pub fn set(&mut self, led_id: &str, on: bool, brightness: Option<u8>) {
+        match self.leds.get_mut(led_id) {
+            Some(led) => led.set(on, brightness),
+            None => {
+                self.unmapped.insert(led_id.to_string(), on);
+            }
+        }
+        self.log(led_id, on, brightness);
+    }
+
+    /// Pin eşlemesi olan LED'in pinini durumu değiştirmeden sür
+    fn flash(&mut self, led_id: &str, on: bool) {
+        if let Some(led) = self.leds.get_mut(led_id) {
+            led.flash(on);
+        }
+    }
+
+    /// Pini kayıtlı duruma geri getir
+    fn restore(&mut self, led_id: &str) {
+        if let Some(led) = self.leds.get_mut(led_id) {
+            led.restore();
+        }
+    }
+
+    fn log(&self, led_id: &str, on: bool, brightness: Option<u8>) {
+        info!(
+            "💡 LED {} ({}) → {}{}",
+            led_id,
+            self.pin_label(led_id),
+            if on { "ON" } else { "OFF" },
+            brightness.map(|b| format!(" @ {}", b)).unwrap_or_default()
+        );
+    }
+
+    fn pin_label(&self, led_id: &str) -> String {
+        self.leds
+            .get(led_id)
+            .map(|led| format!("GPIO{}", led.pin_number()))
+            .unwrap_or_else(|| "no pin".to_string())
+    }
+}
+
+/// LED'i `times` kez yanıp söndür (her biri `BLINK_PERIOD_MS`), sonra pini
+/// kayıtlı duruma geri getir
+///
+/// Kilit sadece pin sürülürken tutulur; bekleme sırasında diğer komutlar
+/// işlenebilir. Kayıtlı durum değişmediği için task iptal edilirse bir
+/// sonraki `set`/`apply` pini düzeltir.
+pub async fn blink(leds: &Mutex<LedActuator>, led_id: &str, times: u32) {
+    {
+        let leds = leds.lock().await;
+        info!("💡 LED {} ({}) → BLINK x{}", led_id, leds.pin_label(led_id), times);
+    }
+    let half = Duration::from_millis(BLINK_PERIOD_MS / 2);
+    for _ in 0..times.min(MAX_BLINK_TIMES) {
+        leds.lock().await.flash(led_id, true);
+        sleep(half).await;
+        leds.lock().await.flash(led_id, false);
+        sleep(half).await;
+    }
+    leds.lock().await.restore(led_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(state: LedState) -> LedCommand {
+        LedCommand { led_id: "led_01".into(), state, brightness: None, duration_ms: None, blink: None }
+    }
+
+    #[test]
+    fn test_led_state_transitions() {
+        for pin_map in ["led_01:17", ""] {
+            let mut leds = LedActuator::new(pin_map);
+            assert!(!leds.is_on("led_01"));
+
+            assert!(leds.apply(&cmd(LedState::On)));
+            assert!(!leds.apply(&cmd(LedState::Toggle)));
+            assert!(leds.apply(&cmd(LedState::Toggle)));
+            assert!(!leds.apply(&cmd(LedState::Off)));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_blink_releases_lock_and_keeps_state() {
+        let leds = std::sync::Arc::new(Mutex::new(LedActuator::new("led_01:17")));
+        leds.lock().await.apply(&cmd(LedState::On));
+
+        let task = tokio::spawn({
+            let leds = leds.clone();
+            async move { blink(&leds, "led_01", 2).await }
+        });
+        sleep(Duration::from_millis(BLINK_PERIOD_MS / 4)).await;
+        assert!(leds.try_lock().is_ok());
+        assert!(!task.is_finished());
+
+        task.await.unwrap();
+        assert!(leds.lock().await.is_on("led_01"));
+    }
+
+    #[test]
+    fn test_pin_map_parsing() {
+        let leds = LedActuator::new("led_01:17, led_02:27,broken,led_03:x");
+        assert_eq!(leds.leds.len(), 2);
+        assert_eq!(leds.leds.get("led_02").map(LedController::pin_number), Some(27));
+    }
+}
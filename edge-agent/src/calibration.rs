@@ -0,0 +1,175 @@
+//! Sensör Kalibrasyonu
+//!
+//! Ucuz sensörler sürekli yüksek/düşük okuyabilir. Ham değer okunduktan
+//! sonra, yayınlanmadan önce `kalibre = ham * scale + offset` uygulanır.
+//! Ham değer `metadata.raw_value` içinde korunur.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use shared_types::{Error, Result};
+
+use crate::sensors::SensorData;
+
+/// Kalibre edilebilen (sayısal) sensör tipleri
+pub const CALIBRATABLE_SENSORS: &[&str] = &["temperature", "humidity"];
+
+/// Tek bir sensörün kalibrasyonu
+///
+/// Eksik alanlar varsayılan değerini alır (offset 0, scale 1):
+/// ```json
+/// {"offset": -1.5, "scale": 1.02}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Calibration {
+    /// Ölçeklemeden sonra eklenen sabit düzeltme
+    pub offset: f64,
+    /// Ham değerin çarpanı
+    pub scale: f64,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self { offset: 0.0, scale: 1.0 }
+    }
+}
+
+impl Calibration {
+    /// Env değişkenlerinden kalibrasyon oluştur (ikisi de yoksa `None`)
+    pub fn from_parts(offset: Option<f64>, scale: Option<f64>) -> Option<Self> {
+        if offset.is_none() && scale.is_none() {
+            return None;
+        }
+        let default = Self::default();
+        Some(Self {
+            offset: offset.unwrap_or(default.offset),
+            scale: scale.unwrap_or(default.scale),
+        })
+    }
+
+    /// Değerlerin kullanılabilir olduğunu kontrol et
+    pub fn validate(&self, sensor_type: &str) -> Result<()> {
+        if !CALIBRATABLE_SENSORS.contains(&sensor_type) {
+            return Err(Error::InvalidParameter(format!("sensor `{sensor_type}` cannot be calibrated")));
+        }
+        if !self.offset.is_finite() || !self.scale.is_finite() || self.scale == 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "invalid calibration for `{sensor_type}`: offset and scale must be finite, scale non-zero"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Ham değere kalibrasyonu uygula
+    pub fn apply(&self, raw: f64) -> f64 {
+        raw * self.scale + self.offset
+    }
+
+    /// Okumayı yerinde kalibre et
+    ///
+    /// - Ham değer `metadata.raw_value`'ya yazılır (mevcut metadata korunur)
+    /// - Değer ham okumayla aynı ondalık hassasiyette formatlanır
+    /// - Sonuç sensörün fiziksel aralığı dışındaysa sınıra sıkıştırılır ve
+    ///   okuma geçersiz (`is_valid = false`) işaretlenir
+    /// - Sayısal olmayan değerlere dokunulmaz
+    pub fn apply_to(&self, data: &mut SensorData) {
+        let Ok(raw) = data.reading.value.parse::<f64>() else {
+            return;
+        };
+        let decimals = data.reading.value.split_once('.').map_or(0, |(_, frac)| frac.len());
+
+        let mut value = self.apply(raw);
+        if let Some((min, max)) = valid_range(&data.sensor_type) {
+            if !(min..=max).contains(&value) {
+                value = value.clamp(min, max);
+                data.reading.is_valid = false;
+            }
+        }
+
+        data.reading.value = format!("{:.*}", decimals, value);
+        let metadata = data.reading.metadata.get_or_insert_with(|| Value::Object(Map::new()));
+        if !metadata.is_object() {
+            *metadata = Value::Object(Map::new());
+        }
+        if let Value::Object(map) = metadata {
+            map.insert("raw_value".into(), Value::from(raw));
+        }
+    }
+}
+
+/// Sensörün fiziksel ölçüm aralığı (DHT22 veri sayfası)
+pub fn valid_range(sensor_type: &str) -> Option<(f64, f64)> {
+    match sensor_type {
+        "temperature" => Some((-40.0, 80.0)),
+        "humidity" => Some((0.0, 100.0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::sensor::SensorReading;
+
+    fn data(sensor_type: &str, value: &str) -> SensorData {
+        SensorData {
+            reading: SensorReading::new(uuid::Uuid::new_v4(), value.to_string()),
+            sensor_type: sensor_type.to_string(),
+            unit: String::new(),
+            statistics: None,
+        }
+    }
+
+    #[test]
+    fn test_offset_and_scale() {
+        let offset = Calibration { offset: -1.5, ..Default::default() };
+        let mut temp = data("temperature", "22.00");
+        offset.apply_to(&mut temp);
+        assert_eq!(temp.reading.value, "20.50");
+
+        let scale = Calibration { scale: 1.02, ..Default::default() };
+        let mut hum = data("humidity", "50.0");
+        scale.apply_to(&mut hum);
+        assert_eq!(hum.reading.value, "51.0");
+
+        // Önce ölçekleme, sonra offset
+        assert_eq!(Calibration { offset: -1.0, scale: 2.0 }.apply(10.0), 19.0);
+        assert!(temp.reading.is_valid && hum.reading.is_valid);
+    }
+
+    #[test]
+    fn test_raw_value_preserved_in_metadata() {
+        let cal = Calibration { offset: 1.0, scale: 1.0 };
+        let mut temp = data("temperature", "22.00");
+        temp.reading.metadata = Some(serde_json::json!({"source": "dht22"}));
+        cal.apply_to(&mut temp);
+
+        assert_eq!(temp.reading.value, "23.00");
+        assert_eq!(temp.reading.metadata, Some(serde_json::json!({"source": "dht22", "raw_value": 22.0})));
+
+        // Sayısal olmayan değerler değişmez
+        let mut odd = data("humidity", "n/a");
+        cal.apply_to(&mut odd);
+        assert_eq!(odd.reading.value, "n/a");
+        assert!(odd.reading.metadata.is_none());
+    }
+
+    #[test]
+    fn test_out_of_range_clamped_and_invalid() {
+        let mut hum = data("humidity", "95.0");
+        Calibration { offset: 10.0, scale: 1.0 }.apply_to(&mut hum);
+        assert_eq!(hum.reading.value, "100.0");
+        assert!(!hum.reading.is_valid);
+        assert_eq!(hum.reading.metadata.unwrap()["raw_value"], 95.0);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Calibration::default().validate("temperature").is_ok());
+        assert!(Calibration::default().validate("motion").is_err());
+        assert!(Calibration { scale: 0.0, ..Default::default() }.validate("humidity").is_err());
+        assert!(Calibration { offset: f64::NAN, ..Default::default() }.validate("humidity").is_err());
+        assert_eq!(Calibration::from_parts(None, None), None);
+        assert_eq!(Calibration::from_parts(Some(-1.5), None), Some(Calibration { offset: -1.5, scale: 1.0 }));
+    }
+}
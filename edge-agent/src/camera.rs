@@ -0,0 +1,366 @@
+//! Kamera
+//!
+//! `take_photo` komutu veya hareket algılandığında (`MOTION_CAPTURE`) fotoğraf
+//! çeker, `CAMERA_CAPTURE_DIR`'e kaydeder ve görüntüyü API server'a yükler
+//! (`POST /v1/media/upload`, cihaz token'ıyla); sunucu `Media` kaydını döner.
+//! - `camera` feature'ı açıksa libcamera ile (`rpicam-still`) gerçek fotoğraf
+//!   çekilir; komut başarısız olursa placeholder'a düşülür
+//! - Aksi halde (mock mod) küçük bir gri tonlamalı placeholder PNG üretilir
+//!
+//! Yükleme başarısız olsa da görüntü cihazda kalır. Token reddi (401/403)
+//! tekrar denenmez ve hata olarak döner.
+
+use std::path::PathBuf;
+
+use chrono::Utc;
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+use shared_types::media::Media;
+use tokio::time::{Duration, Instant};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Yükleme deneme sayısı
+const UPLOAD_ATTEMPTS: u32 = 3;
+
+/// İlk tekrar denemeden önceki bekleme (her denemede iki katına çıkar)
+const UPLOAD_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Placeholder görüntünün boyutu (piksel)
+const PLACEHOLDER_SIZE: (u32, u32) = (64, 48);
+
+/// Çekilmiş fotoğraf
+#[derive(Debug, Clone, PartialEq)]
+pub struct Photo {
+    pub bytes: Vec<u8>,
+    pub mime_type: &'static str,
+    pub extension: &'static str,
+}
+
+/// Fotoğraf çek (feature'a göre libcamera veya placeholder)
+pub async fn capture() -> Photo {
+    #[cfg(feature = "camera")]
+    match capture_libcamera().await {
+        Ok(photo) => return photo,
+        Err(e) => warn!("libcamera capture failed, using placeholder: {}", e),
+    }
+    placeholder_png()
+}
+
+/// `rpicam-still` ile JPEG çek (stdout'a yazdırılır)
+#[cfg(feature = "camera")]
+async fn capture_libcamera() -> Result<Photo, String> {
+    let output = tokio::process::Command::new("rpicam-still")
+        .args(["--nopreview", "--immediate", "--encoding", "jpg", "--output", "-"])
+        .output()
+        .await
+        .map_err(|e| format!("rpicam-still: {e}"))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(format!("rpicam-still exited with {}", output.status));
+    }
+    Ok(Photo { bytes: output.stdout, mime_type: "image/jpeg", extension: "jpg" })
+}
+
+/// Mock kamera görüntüsü: yatay gri tonlama geçişi
+pub fn placeholder_png() -> Photo {
+    let (width, height) = PLACEHOLDER_SIZE;
+    let pixels: Vec<u8> = (0..height).flat_map(|_| (0..width).map(move |x| (x * 255 / (width - 1)) as u8)).collect();
+    Photo { bytes: encode_gray_png(width, height, &pixels), mime_type: "image/png", extension: "png" }
+}
+
+/// 8-bit gri tonlamalı PNG (sıkıştırmasız deflate bloklarıyla)
+fn encode_gray_png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    // Her satır filtre tipi 0 (None) ile başlar
+    let mut raw = Vec::with_capacity(pixels.len() + height as usize);
+    for row in pixels.chunks(width as usize) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    // zlib: başlık + stored bloklar (en fazla 65535 byte) + Adler-32
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if raw.is_empty() { vec![&[]] } else { raw.chunks(u16::MAX as usize).collect() };
+    for (i, block) in blocks.iter().enumerate() {
+        let len = block.len() as u16;
+        zlib.push(u8::from(i == blocks.len() - 1));
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 0, 0, 0, 0]); // 8 bit, gri tonlama, deflate, filtre 0, interlace yok
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", ihdr.as_slice()), (b"IDAT", zlib.as_slice()), (b"IEND", &[][..])] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[start..]);
+        png.extend_from_slice(&crc.to_be_bytes());
+    }
+    png
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |c, _| if c & 1 == 1 { (c >> 1) ^ 0xEDB8_8320 } else { c >> 1 })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+/// Fotoğraf çekip API server'a yükleyen kamera
+pub struct Camera {
+    client: reqwest::Client,
+    api_url: String,
+    capture_dir: PathBuf,
+    device_id: Uuid,
+    retry_delay: Duration,
+}
+
+impl Camera {
+    /// Yeni kamera (`capture_dir` ilk çekimde oluşturulur)
+    pub fn new(api_url: &str, capture_dir: impl Into<PathBuf>, device_id: Uuid) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            capture_dir: capture_dir.into(),
+            device_id,
+            retry_delay: UPLOAD_RETRY_DELAY,
+        }
+    }
+
+    /// API server istekleri için client (cihaz token'ını gönderen `http_transport::api_client`)
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// İlk tekrar denemeden önceki bekleme (testlerde kısaltılır)
+    #[cfg(test)]
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Fotoğraf çek, diske kaydet ve API server'a yükle
+    pub async fn take_photo(&self) -> Result<Media, String> {
+        let photo = capture().await;
+        let name = format!("photo-{}-{}.{}", self.device_id, Utc::now().format("%Y%m%dT%H%M%S%.3fZ"), photo.extension);
+        let path = self.save(&name, &photo.bytes).await?;
+
+        let media = self.upload(&name, &photo).await?;
+        info!("📷 Photo {} saved to {} and uploaded (media {})", name, path.display(), media.id);
+        Ok(media)
+    }
+
+    async fn save(&self, name: &str, bytes: &[u8]) -> Result<PathBuf, String> {
+        tokio::fs::create_dir_all(&self.capture_dir)
+            .await
+            .map_err(|e| format!("{}: {e}", self.capture_dir.display()))?;
+        let path = std::path::absolute(self.capture_dir.join(name)).map_err(|e| e.to_string())?;
+        tokio::fs::write(&path, bytes).await.map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(path)
+    }
+
+    /// `POST /v1/media/upload?name=...&dedup=true` (bağlantı hatası ve 5xx'te üstel bekleme ile tekrar dener)
+    async fn upload(&self, name: &str, photo: &Photo) -> Result<Media, String> {
+        let url = format!("{}/v1/media/upload", self.api_url);
+        let mut delay = self.retry_delay;
+        for attempt in 1..=UPLOAD_ATTEMPTS {
+            let request = self
+                .client
+                .post(&url)
+                .query(&[("name", name), ("dedup", "true")])
+                .header(CONTENT_TYPE, photo.mime_type)
+                .body(photo.bytes.clone());
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    return response.json().await.map_err(|e| format!("invalid media response: {e}"));
+                }
+                Ok(response) if matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                    let message = format!("API server refused media upload: {} (check DEVICE_TOKEN)", response.status());
+                    error!("{}", message);
+                    return Err(message);
+                }
+                Ok(response) if response.status().is_client_error() => {
+                    return Err(format!("API server rejected media: {}", response.status()));
+                }
+                Ok(response) => response.status().to_string(),
+                Err(e) => e.to_string(),
+            };
+            if attempt == UPLOAD_ATTEMPTS {
+                return Err(format!("media upload failed after {attempt} attempts: {error}"));
+            }
+            warn!("Media upload attempt {} failed: {} (retry in {:?})", attempt, error, delay);
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+        unreachable!("UPLOAD_ATTEMPTS is at least 1")
+    }
+}
+
+/// Hareket algılanınca (0 → 1 geçişi) çekim tetikleyici
+///
+/// Son çekimden `cooldown` geçmeden gelen geçişler yok sayılır.
+#[derive(Debug)]
+pub struct MotionTrigger {
+    cooldown: Duration,
+    motion: bool,
+    last_capture: Option<Instant>,
+}
+
+impl MotionTrigger {
+    pub fn new(cooldown: Duration) -> Self {
+        Self { cooldown, motion: false, last_capture: None }
+    }
+
+    /// Hareket okumasını işle; çekim yapılmalıysa `true`
+    pub fn observe(&mut self, motion: bool, now: Instant) -> bool {
+        let rising = motion && !self.motion;
+        self.motion = motion;
+        let cooled_down = self.last_capture.is_none_or(|last| now.duration_since(last) >= self.cooldown);
+        if rising && cooled_down {
+            self.last_capture = Some(now);
+        }
+        rising && cooled_down
+    }
+}
+
+/// Hareket okuması mı? (`motion` sensörü, değer `1`)
+pub fn is_motion(sensor_type: &str, value: &str) -> Option<bool> {
+    (sensor_type == "motion").then(|| value == "1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Bytes,
+        extract::Query,
+        http::{header::AUTHORIZATION, HeaderMap},
+        routing::post,
+        Json, Router,
+    };
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_placeholder_png_is_well_formed() {
+        let photo = placeholder_png();
+        assert_eq!((photo.mime_type, photo.extension), ("image/png", "png"));
+
+        let png = &photo.bytes;
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!((&png[16..20], &png[20..24]), (&64u32.to_be_bytes()[..], &48u32.to_be_bytes()[..]));
+        // IEND chunk'ı ve bilinen CRC'si
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+        // Referans değerler
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn test_motion_trigger_cooldown() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut trigger = MotionTrigger::new(Duration::from_secs(60));
+
+        assert!(trigger.observe(true, at(0)));
+        // Hareket sürüyor: geçiş yok
+        assert!(!trigger.observe(true, at(5)));
+        // Yeni geçiş ama cooldown dolmadı
+        assert!(!trigger.observe(false, at(10)));
+        assert!(!trigger.observe(true, at(20)));
+        assert!(!trigger.observe(false, at(61)));
+        assert!(trigger.observe(true, at(62)));
+
+        assert_eq!(is_motion("motion", "1"), Some(true));
+        assert_eq!(is_motion("temperature", "1"), None);
+    }
+
+    #[tokio::test]
+    async fn test_upload_gives_up_after_attempts() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let app = Router::new().route(
+            "/v1/media/upload",
+            post(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = std::env::temp_dir().join(format!("captures-{}", Uuid::new_v4()));
+        let camera = Camera::new(&api_url, &dir, Uuid::new_v4()).with_retry_delay(Duration::from_millis(1));
+        let err = camera.take_photo().await.unwrap_err();
+        assert!(err.contains("after 3 attempts"), "{err}");
+        assert_eq!(attempts.load(Ordering::SeqCst), UPLOAD_ATTEMPTS);
+
+        // Görüntü yükleme başarısız olsa da cihazda kalır
+        let saved: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(std::fs::read(&saved[0]).unwrap(), placeholder_png().bytes);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_upload_sends_image_with_device_token_and_fails_hard_on_401() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let app = Router::new().route(
+            "/v1/media/upload",
+            post(move |Query(query): Query<HashMap<String, String>>, headers: HeaderMap, body: Bytes| async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                if headers.get(AUTHORIZATION).is_none_or(|v| v != "Bearer rfd_device") {
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
+                assert_eq!(headers[CONTENT_TYPE], "image/png");
+                assert_eq!(query["dedup"], "true");
+                assert_eq!(body, placeholder_png().bytes);
+                let now = Utc::now();
+                Ok((StatusCode::CREATED, Json(serde_json::json!({
+                    "id": Uuid::new_v4(),
+                    "name": query["name"],
+                    "path": "/srv/uploads/photo",
+                    "mime_type": "image/png",
+                    "size_bytes": body.len(),
+                    "created_at": now,
+                    "updated_at": now,
+                }))))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let dir = std::env::temp_dir().join(format!("captures-{}", Uuid::new_v4()));
+
+        let authorized = crate::http_transport::api_client(Some("rfd_device")).unwrap();
+        let camera = Camera::new(&api_url, &dir, Uuid::new_v4()).with_client(authorized);
+        let media = camera.take_photo().await.unwrap();
+        assert!(media.name.starts_with("photo-") && media.name.ends_with(".png"), "{}", media.name);
+        assert_eq!(media.size_bytes, placeholder_png().bytes.len() as i64);
+
+        // Token yok: tek deneme, hata döner
+        let camera = Camera::new(&api_url, &dir, Uuid::new_v4()).with_retry_delay(Duration::from_millis(1));
+        let err = camera.take_photo().await.unwrap_err();
+        assert!(err.contains("401") && err.contains("DEVICE_TOKEN"), "{err}");
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
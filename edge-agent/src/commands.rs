@@ -0,0 +1,369 @@
+//! Cihaz Komutları
+//!
+//! API server'dan MQTT üzerinden gelen `DeviceCommand`'ları işler.
+//! - Komut topic'i: `devices/{device_id}/commands`
+//! - Yanıt topic'i: `devices/{device_id}/responses`
+//! - Durum topic'i: `devices/{device_id}/status` (her bağlantıda heartbeat,
+//!   bağlantı koparsa broker'ın yayınladığı LWT, bkz. `keepalive`)
+//!
+//! Topic formatları `shared_types::conventions`'ta tanımlıdır.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use serde::Deserialize;
+
+use shared_types::messages::{DeviceCommand, DeviceCommandResponse, LedCommand};
+use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+use tokio::time::Duration;
+use tracing::{info, warn};
+use crate::actuators::{self, LedActuator, MAX_BLINK_TIMES};
+use crate::calibration::Calibration;
+use crate::camera::Camera;
+use crate::runtime_config::{RuntimeConfigPatch, SharedRuntimeConfig};
+
+/// Komutları işleyen handler
+///
+/// Komutların değiştirdiği paylaşılan durumu tutar.
+pub struct CommandHandler {
+    runtime: SharedRuntimeConfig,
+    overrides_path: PathBuf,
+    leds: Arc<Mutex<LedActuator>>,
+    /// Süren yanıp sönme task'ları (led_id → task); yeni LED komutu iptal eder
+    blinks: std::sync::Mutex<HashMap<String, AbortHandle>>,
+    /// `duration_ms` sonunda dönülecek durumlar (led_id → (task, durum)); yeni LED komutu iptal eder
+    reverts: std::sync::Mutex<HashMap<String, (AbortHandle, bool)>>,
+    camera: Option<Arc<Camera>>,
+}
+
+impl CommandHandler {
+    /// Yeni handler oluştur
+    pub fn new(runtime: SharedRuntimeConfig, overrides_path: PathBuf, leds: LedActuator) -> Self {
+        Self {
+            runtime,
+            overrides_path,
+            leds: Arc::new(Mutex::new(leds)),
+            blinks: Default::default(),
+            reverts: Default::default(),
+            camera: None,
+        }
+    }
+
+    /// `take_photo` komutu için kamera
+    pub fn with_camera(mut self, camera: Arc<Camera>) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Komutu işle ve yanıt üret
+    pub async fn handle(&self, cmd: &DeviceCommand) -> DeviceCommandResponse {
+        match cmd.command_name.as_str() {
+            "config_update" => self.config_update(cmd).await,
+            "calibrate" => self.calibrate(cmd).await,
+            "led" => self.led(cmd).await,
+            "take_photo" => self.take_photo(cmd).await,
+            other => {
+                warn!("Unknown command: {}", other);
+                DeviceCommandResponse::error(cmd, format!("unknown command `{other}`"))
+            }
+        }
+    }
+
+    /// `config_update`: runtime config'i patch'le ve override'ı diske yaz
+    async fn config_update(&self, cmd: &DeviceCommand) -> DeviceCommandResponse {
+        let params = cmd.parameters.clone().unwrap_or_else(|| serde_json::json!({}));
+        match RuntimeConfigPatch::from_parameters(&params) {
+            Ok(patch) => self.apply_patch(cmd, patch).await,
+            Err(e) => DeviceCommandResponse::error(cmd, e.to_string()),
+        }
+    }
+
+    /// `calibrate`: tek bir sensörün kalibrasyonunu ayarla
+    ///
+    /// ```json
+    /// {"sensor_type": "temperature", "offset": -1.5, "scale": 1.02}
+    /// ```
+    /// `config_update` ile `{"calibration": {"temperature": {...}}}` göndermenin kısayoludur.
+    async fn calibrate(&self, cmd: &DeviceCommand) -> DeviceCommandResponse {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct CalibrateParams {
+            sensor_type: String,
+            offset: Option<f64>,
+            scale: Option<f64>,
+        }
+
+        let params = cmd.parameters.clone().unwrap_or_else(|| serde_json::json!({}));
+        let params: CalibrateParams = match serde_json::from_value(params) {
+            Ok(params) => params,
+            Err(e) => return DeviceCommandResponse::error(cmd, e.to_string()),
+        };
+        let calibration = Calibration::from_parts(params.offset, params.scale).unwrap_or_default();
+        let patch = RuntimeConfigPatch {
+            calibration: Some(BTreeMap::from([(params.sensor_type, calibration)])),
+            ..Default::default()
+        };
+        self.apply_patch(cmd, patch).await
+    }
+
+    /// Patch'i runtime config'e uygula ve override'ı diske yaz
+    async fn apply_patch(&self, cmd: &DeviceCommand, patch: RuntimeConfigPatch) -> DeviceCommandResponse {
+        let mut rt = self.runtime.write().await;
+        if let Err(e) = rt.apply(&patch) {
+            return DeviceCommandResponse::error(cmd, e.to_string());
+        }
+
+        // Diskteki override'lar tek doğruluk kaynağı (SIGHUP'ta da tekrar uygulanır)
+        let mut overrides = RuntimeConfigPatch::load(&self.overrides_path).unwrap_or_default();
+        overrides.merge(&patch);
+        if let Err(e) = overrides.persist(&self.overrides_path) {
+            warn!("Failed to persist runtime config overrides: {}", e);
+        }
+
+        info!("⚙️  Runtime config updated: {:?}", *rt);
+        DeviceCommandResponse::ok(cmd)
+    }
+
+    /// `led`: LED durumunu değiştir
+    ///
+    /// `blink` verildiyse (en fazla `MAX_BLINK_TIMES`) durum hemen uygulanır,
+    /// LED arka planda yanıp söner ve sonunda bu duruma döner; aynı LED'e
+    /// gelen sonraki komut yanıp sönmeyi iptal eder.
+    /// `duration_ms` verildiyse süre sonunda önceki duruma dönülür; süresi
+    /// dolmamış bir önceki `duration_ms`'in dönüşü iptal edilir ve onun
+    /// döneceği durum önceki durum sayılır.
+    async fn led(&self, cmd: &DeviceCommand) -> DeviceCommandResponse {
+        let led = match LedCommand::from_command(cmd) {
+            Ok(led) => led,
+            Err(e) => return DeviceCommandResponse::error(cmd, e.to_string()),
+        };
+        if led.blink.is_some_and(|times| times > MAX_BLINK_TIMES) {
+            return DeviceCommandResponse::error(cmd, format!("blink must be at most {MAX_BLINK_TIMES}"));
+        }
+
+        let running = self.blinks.lock().unwrap_or_else(|e| e.into_inner()).remove(&led.led_id);
+        if let Some(running) = running {
+            running.abort();
+        }
+        let pending_revert = self.reverts.lock().unwrap_or_else(|e| e.into_inner()).remove(&led.led_id);
+        if let Some((pending, _)) = &pending_revert {
+            pending.abort();
+        }
+
+        let mut leds = self.leds.lock().await;
+        let previous = pending_revert.map_or_else(|| leds.is_on(&led.led_id), |(_, state)| state);
+        let on = leds.apply(&led);
+        drop(leds);
+
+        if let Some(times) = led.blink {
+            let leds = self.leds.clone();
+            let led_id = led.led_id.clone();
+            let task = tokio::spawn(async move { actuators::blink(&leds, &led_id, times).await });
+            self.blinks.lock().unwrap_or_else(|e| e.into_inner()).insert(led.led_id.clone(), task.abort_handle());
+        }
+
+        if let Some(ms) = led.duration_ms {
+            let leds = self.leds.clone();
+            let led_id = led.led_id.clone();
+            let task = tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(ms as u64)).await;
+                leds.lock().await.set(&led_id, previous, None);
+            });
+            self.reverts.lock().unwrap_or_else(|e| e.into_inner()).insert(led.led_id.clone(), (task.abort_handle(), previous));
+        }
+
+        DeviceCommandResponse {
+            message: Some(if on { "on" } else { "off" }.to_string()),
+            ..DeviceCommandResponse::ok(cmd)
+        }
+    }
+
+    /// `take_photo`: fotoğraf çek ve API server'a yükle
+    ///
+    /// Yanıtın `data`'sı oluşturulan kaydın ID'sini taşır: `{"media_id": "..."}`;
+    /// `message` sunucudaki dosya yoludur.
+    async fn take_photo(&self, cmd: &DeviceCommand) -> DeviceCommandResponse {
+        let Some(camera) = &self.camera else {
+            return DeviceCommandResponse::error(cmd, "no camera configured");
+        };
+        match camera.take_photo().await {
+            Ok(media) => DeviceCommandResponse {
+                message: Some(media.path),
+                data: Some(serde_json::json!({ "media_id": media.id })),
+                ..DeviceCommandResponse::ok(cmd)
+            },
+            Err(e) => DeviceCommandResponse::error(cmd, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime_config::RuntimeConfig;
+    use shared_types::messages::LedState;
+    use tokio::sync::RwLock;
+    use uuid::Uuid;
+
+    fn handler() -> CommandHandler {
+        let runtime = Arc::new(RwLock::new(RuntimeConfig {
+            sensor_interval_secs: 5,
+            enabled_sensors: vec!["temperature".into()],
+            qos: 0,
+            calibration: Default::default(),
+        }));
+        let path = std::env::temp_dir().join(format!("rt-{}.json", Uuid::new_v4()));
+        CommandHandler::new(runtime, path, LedActuator::new(""))
+    }
+
+    #[tokio::test]
+    async fn test_led_command_dispatch() {
+        let handler = handler();
+        let led = LedCommand { led_id: "led_01".into(), state: LedState::Toggle, brightness: None, duration_ms: None, blink: None };
+        let cmd = DeviceCommand::led(Uuid::new_v4(), &led);
+
+        let resp = handler.handle(&cmd).await;
+        assert!(resp.success);
+        assert_eq!(resp.correlation_id, cmd.correlation_id);
+        assert_eq!(resp.message.as_deref(), Some("on"));
+        assert!(handler.leds.lock().await.is_on("led_01"));
+    }
+
+    #[tokio::test]
+    async fn test_led_command_duration_reverts() {
+        let handler = handler();
+        let led = LedCommand { led_id: "led_01".into(), state: LedState::On, brightness: None, duration_ms: Some(10), blink: None };
+        handler.handle(&DeviceCommand::led(Uuid::new_v4(), &led)).await;
+        assert!(handler.leds.lock().await.is_on("led_01"));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!handler.leds.lock().await.is_on("led_01"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_led_command_duration_replaces_pending_revert() {
+        let handler = handler();
+        let led = LedCommand { led_id: "led_01".into(), state: LedState::On, brightness: None, duration_ms: Some(100), blink: None };
+        handler.handle(&DeviceCommand::led(Uuid::new_v4(), &led)).await;
+        let longer = LedCommand { duration_ms: Some(300), ..led };
+        handler.handle(&DeviceCommand::led(Uuid::new_v4(), &longer)).await;
+
+        // İlk komutun dönüşü iptal edildi: LED ikinci sürenin sonuna kadar açık
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(handler.leds.lock().await.is_on("led_01"));
+
+        // İkinci komut ilk komuttan önceki duruma (kapalı) döner
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(!handler.leds.lock().await.is_on("led_01"));
+    }
+
+    #[tokio::test]
+    async fn test_led_blink_runs_in_background() {
+        let handler = handler();
+        let led = LedCommand { led_id: "led_01".into(), state: LedState::On, brightness: None, duration_ms: None, blink: Some(MAX_BLINK_TIMES) };
+        let resp = handler.handle(&DeviceCommand::led(Uuid::new_v4(), &led)).await;
+        assert_eq!(resp.message.as_deref(), Some("on"));
+        assert!(handler.leds.try_lock().is_ok());
+
+        // Yeni komut süren yanıp sönmeyi iptal eder
+        let off = LedCommand { state: LedState::Off, blink: None, ..led.clone() };
+        assert!(handler.handle(&DeviceCommand::led(Uuid::new_v4(), &off)).await.success);
+        assert!(handler.blinks.lock().unwrap().is_empty());
+        assert!(!handler.leds.lock().await.is_on("led_01"));
+
+        let excessive = LedCommand { blink: Some(MAX_BLINK_TIMES + 1), ..led };
+        assert!(!handler.handle(&DeviceCommand::led(Uuid::new_v4(), &excessive)).await.success);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_and_malformed_commands_rejected() {
+        let handler = handler();
+        let unknown = DeviceCommand::new(Uuid::new_v4(), "control".into(), "self_destruct".into());
+        assert!(!handler.handle(&unknown).await.success);
+
+        let malformed = DeviceCommand::new(Uuid::new_v4(), "control".into(), "led".into())
+            .with_parameters(serde_json::json!({"state": "on"}));
+        assert!(!handler.handle(&malformed).await.success);
+    }
+
+    #[tokio::test]
+    async fn test_calibrate_command_applies_and_persists() {
+        let handler = handler();
+        let cmd = DeviceCommand::new(Uuid::new_v4(), "config".into(), "calibrate".into())
+            .with_parameters(serde_json::json!({"sensor_type": "temperature", "offset": -1.5}));
+
+        assert!(handler.handle(&cmd).await.success);
+        let expected = Calibration { offset: -1.5, scale: 1.0 };
+        assert_eq!(handler.runtime.read().await.calibration.get("temperature"), Some(&expected));
+
+        let persisted = RuntimeConfigPatch::load(&handler.overrides_path).unwrap();
+        assert_eq!(persisted.calibration.unwrap().get("temperature"), Some(&expected));
+        std::fs::remove_file(&handler.overrides_path).unwrap();
+
+        let invalid = DeviceCommand::new(Uuid::new_v4(), "config".into(), "calibrate".into())
+            .with_parameters(serde_json::json!({"sensor_type": "motion", "scale": 2.0}));
+        assert!(!handler.handle(&invalid).await.success);
+    }
+
+    #[tokio::test]
+    async fn test_take_photo_retries_and_returns_media_id() {
+        use axum::{body::Bytes, http::StatusCode, routing::post, Json, Router};
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        // İlk istek 503, ikincisi kaydı oluşturur
+        let attempts = Arc::new(AtomicU32::new(0));
+        let media_id = Uuid::new_v4();
+        let counter = attempts.clone();
+        let app = Router::new().route(
+            "/v1/media/upload",
+            post(move |body: Bytes| async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+                let now = chrono::Utc::now();
+                let media = serde_json::json!({
+                    "id": media_id,
+                    "name": "photo.png",
+                    "path": format!("/srv/uploads/{media_id}"),
+                    "mime_type": "image/png",
+                    "size_bytes": body.len(),
+                    "created_at": now,
+                    "updated_at": now,
+                });
+                Ok((StatusCode::CREATED, Json(media)))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = std::env::temp_dir().join(format!("captures-{}", Uuid::new_v4()));
+        let camera = Camera::new(&api_url, &dir, Uuid::new_v4()).with_retry_delay(Duration::from_millis(1));
+        let handler = handler().with_camera(Arc::new(camera));
+        let cmd = DeviceCommand::new(Uuid::new_v4(), "control".into(), "take_photo".into());
+
+        let resp = handler.handle(&cmd).await;
+        assert!(resp.success, "{:?}", resp.message);
+        assert_eq!(resp.data, Some(serde_json::json!({ "media_id": media_id })));
+        assert_eq!(resp.message, Some(format!("/srv/uploads/{media_id}")));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        // Mock kamera placeholder PNG'yi cihazda da saklar
+        let saved: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(&std::fs::read(&saved[0]).unwrap()[..8], b"\x89PNG\r\n\x1a\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_take_photo_without_camera_rejected() {
+        let cmd = DeviceCommand::new(Uuid::new_v4(), "control".into(), "take_photo".into());
+        let resp = handler().handle(&cmd).await;
+        assert!(!resp.success);
+        assert_eq!(resp.data, None);
+    }
+}
@@ -1,10 +1,23 @@
 //! Edge Agent Yapılandırması
 //!
 //! Device ID, MQTT broker bilgileri ve sensör ayarları.
+//! Env var'lardan ve (varsa) TOML config dosyasından okunur; env var'lar önceliklidir.
+
+use std::collections::BTreeMap;
+use std::path::Path;
 
 use serde::Deserialize;
+use tokio::time::Duration;
+use tracing::{debug, info, warn};
+use shared_types::config::FileConfig;
+use shared_types::mqtt::{MqttError, MqttProtocol, SessionOptions};
+use shared_types::{lz4, merge_config, Cbor, KalmanFilter1D, MqttMessage};
 use uuid::Uuid;
 
+use crate::calibration::Calibration;
+use crate::smoothing::Smoother;
+use crate::discovery::{discover, API_SERVICE_TYPE, MQTT_SERVICE_TYPE};
+
 /// Edge Agent yapılandırması
 /// 
 /// .env dosyasından veya ortam değişkenlerinden okunacak ayarlar.
@@ -13,17 +26,64 @@ use uuid::Uuid;
 /// ```ignore
 /// DEVICE_ID=550e8400-e29b-41d4-a716-446655440000
 /// DEVICE_NAME=raspberry-pi-01
+/// DEVICE_SERIAL=10000000abcdef01
+/// DEVICE_ID_FILE=device_id
+/// DEVICE_TOKEN_FILE=device_token
 /// MQTT_BROKER_HOST=localhost
 /// MQTT_BROKER_PORT=1883
+/// MQTT_DISCOVERY_TIMEOUT_SECS=5
+/// MQTT_KEEP_ALIVE_SECS=5
+/// MQTT_CLEAN_SESSION=true
+/// MQTT_MAX_INFLIGHT=100
+/// MQTT_REQUEST_CHANNEL_CAPACITY=10
 /// SENSOR_INTERVAL_SECS=5
+/// MIN_SENSOR_INTERVAL_SECS=1
+/// MAX_SENSOR_INTERVAL_SECS=60
+/// CHANGE_RATE_THRESHOLD=0.5
+/// ENABLED_SENSORS=temperature,humidity,motion
+/// SENSOR_CHANGE_THRESHOLD=0.1
+/// TEMP_DEADBAND=0.2
+/// HUMIDITY_DEADBAND=1.0
+/// TEMP_KALMAN_PROCESS_VAR=0.01
+/// TEMP_KALMAN_MEASUREMENT_VAR=0.25
+/// TEMP_SMOOTHING=ema:0.3
+/// HUMIDITY_SMOOTHING=sma:5
+/// MAX_SILENCE_SECS=60
+/// WILL_INTERVAL_SECS=30
+/// TEMP_OFFSET=-1.5
+/// TEMP_SCALE=1.02
+/// HUMIDITY_OFFSET=0
+/// HUMIDITY_SCALE=1.0
+/// MQTT_QOS=0
+/// MAX_PENDING_ACKS=100
+/// RUNTIME_CONFIG_PATH=runtime_config.json
+/// LED_PINS=led_01:17,led_02:27
+/// CAMERA_CAPTURE_DIR=captures
+/// MOTION_CAPTURE=false
+/// MOTION_CAPTURE_COOLDOWN_SECS=60
+/// API_SERVER_URL=http://localhost:3000
+/// PROVISIONING_TOKEN=change-me
+/// DEVICE_TOKEN=rfd_...
+/// PAYLOAD_FORMAT=json
+/// MQTT_COMPRESS=false
+/// MQTT_PROTOCOL=v4
+/// SENSOR_TRANSPORT=mqtt
+/// SIMULATE_DEVICES=0
+/// SIMULATE_RATE_MULTIPLIER=1.0
+/// SIMULATE_SEED=0
 /// RUST_LOG=info
 /// ```
-#[derive(Debug, Clone, Deserialize)]
+/// 
+/// Aynı ayarlar TOML dosyasında küçük harfli alan adlarıyla verilebilir
+/// (bkz. `rustyflow.example.toml`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct Config {
     /// Bu cihazın benzersiz ID'si (UUID)
     /// 
     /// Her edge agent'ın farklı bir ID'si olmalı.
-    /// Eğer ayarlanmazsa, her başlangıçta yeni UUID üretilir.
+    /// Eğer ayarlanmazsa, cihaz API server'a kayıt olur (`POST /v1/provision`)
+    /// ve dönen ID'yi `device_id_file`'a yazar; kayıt başarısızsa her
+    /// başlangıçta yeni UUID üretilir.
     #[serde(default = "generate_device_id")]
     pub device_id: Uuid,
 
@@ -35,10 +95,32 @@ pub struct Config {
     #[serde(default = "default_device_name")]
     pub device_name: String,
 
+    /// Kayıtta kullanılan donanım seri numarası
+    /// 
+    /// Ayarlanmazsa `/proc/cpuinfo`'daki `Serial` satırı okunur (Raspberry Pi).
+    /// 
+    /// Örnek: `DEVICE_SERIAL=10000000abcdef01`
+    pub device_serial: Option<String>,
+
+    /// Kayıtla alınan device ID'nin saklandığı dosya
+    /// 
+    /// Varsayılan: "device_id"
+    #[serde(default = "default_device_id_file")]
+    pub device_id_file: String,
+
+    /// Kayıtla alınan cihaz token'ının saklandığı dosya (0600 izinle yazılır)
+    /// 
+    /// `DEVICE_TOKEN` verilmemişse API istekleri bu token'la yapılır.
+    /// 
+    /// Varsayılan: "device_token"
+    #[serde(default = "default_device_token_file")]
+    pub device_token_file: String,
+
     /// MQTT broker adresi
     /// 
-    /// Varsayılan: "localhost"
-    #[serde(default = "default_broker_host")]
+    /// Ayarlanmazsa `discover_services` mDNS ile `_mqtt._tcp.local.` arar;
+    /// bulunamazsa "localhost" kullanılır.
+    #[serde(default)]
     pub mqtt_broker_host: String,
 
     /// MQTT broker portu
@@ -47,6 +129,51 @@ pub struct Config {
     #[serde(default = "default_broker_port")]
     pub mqtt_broker_port: u16,
 
+    /// mDNS servis keşfi zaman aşımı (saniye)
+    /// 
+    /// Varsayılan: 5. Sadece `MQTT_BROKER_HOST` veya `API_SERVER_URL`
+    /// ayarlanmamışsa kullanılır.
+    #[serde(default = "default_discovery_timeout")]
+    pub mqtt_discovery_timeout_secs: u64,
+
+    /// Broker'ın bağlantıyı canlı sayması için ping aralığı (saniye, en az 5)
+    /// 
+    /// Hücresel gibi gecikmeli bağlantılarda kısa keep-alive sürekli
+    /// yeniden bağlanmaya yol açar; 30-60 önerilir. Bağlantı koparsa LWT
+    /// (çevrimdışı durumu) en geç keep-alive'ın 1.5 katı sonra yayınlanır.
+    /// 
+    /// Varsayılan: 5
+    #[serde(default = "default_keep_alive_secs")]
+    pub mqtt_keep_alive_secs: u64,
+
+    /// Her bağlantıda temiz oturum açılsın mı?
+    /// 
+    /// `false` ile broker aboneliği ve cihaz çevrimdışıyken gönderilen QoS 1
+    /// komutları saklar, yeniden bağlanınca teslim eder (eskimiş komutlar
+    /// da uygulanır). Client ID `edge-{DEVICE_ID}` olduğundan cihaz ID'si
+    /// sabit kalmalıdır; simülasyon modunun client ID'si her process'te
+    /// değiştiği için orada etkisizdir. Onay alınmamış okumalar sadece
+    /// process ayaktayken yeniden gönderilir, restart'ta kaybolur.
+    /// 
+    /// Varsayılan: true
+    #[serde(default = "default_clean_session")]
+    pub mqtt_clean_session: bool,
+
+    /// Onay bekleyebilecek en fazla QoS 1/2 publish (1..=65535)
+    /// 
+    /// Sınıra ulaşılınca yeni publish'ler istek kanalında bekler; sensör
+    /// döngüsünün backpressure'ı için bkz. `MAX_PENDING_ACKS`.
+    /// 
+    /// Varsayılan: 100
+    #[serde(default = "default_max_inflight")]
+    pub mqtt_max_inflight: u32,
+
+    /// Client'tan event loop'a giden istek kanalının kapasitesi (en az 1)
+    /// 
+    /// Varsayılan: 10
+    #[serde(default = "default_request_channel_capacity")]
+    pub mqtt_request_channel_capacity: usize,
+
     /// Sensör okuma aralığı (saniye)
     /// 
     /// Varsayılan: 5 saniye
@@ -55,6 +182,253 @@ pub struct Config {
     #[serde(default = "default_sensor_interval")]
     pub sensor_interval_secs: u64,
 
+    /// Adaptif örneklemede en kısa okuma aralığı (saniye)
+    /// 
+    /// Varsayılan: 1
+    #[serde(default = "default_min_sensor_interval")]
+    pub min_sensor_interval_secs: u64,
+
+    /// Adaptif örneklemede en uzun okuma aralığı (saniye)
+    /// 
+    /// Varsayılan: 60
+    #[serde(default = "default_max_sensor_interval")]
+    pub max_sensor_interval_secs: u64,
+
+    /// Aralığı kısaltan değişim hızı (birim/saniye)
+    /// 
+    /// Varsayılan: 0.5
+    /// 
+    /// Okumalar bundan hızlı değişirse aralık yarıya iner, yavaş değişirse iki katına çıkar.
+    /// 
+    /// Örnek: `CHANGE_RATE_THRESHOLD=0.2`
+    #[serde(default = "default_change_rate_threshold")]
+    pub change_rate_threshold: f64,
+
+    /// Yayın için gereken minimum değer değişimi
+    /// 
+    /// Varsayılan: 0.1 (0.1°C / 0.1%)
+    /// 
+    /// Daha küçük değişimler publish edilmez. Hareket sensörü her zaman yayınlanır.
+    #[serde(default = "default_change_threshold")]
+    pub sensor_change_threshold: f64,
+
+    /// Sıcaklık sensörü deadband'i (°C)
+    /// 
+    /// Ayarlanmazsa `SENSOR_CHANGE_THRESHOLD` kullanılır.
+    /// 
+    /// Örnek: `TEMP_DEADBAND=0.2`
+    pub temp_deadband: Option<f64>,
+
+    /// Nem sensörü deadband'i (%)
+    /// 
+    /// Ayarlanmazsa `SENSOR_CHANGE_THRESHOLD` kullanılır.
+    /// 
+    /// Örnek: `HUMIDITY_DEADBAND=1.0`
+    pub humidity_deadband: Option<f64>,
+
+    /// Sıcaklık Kalman filtresinin süreç varyansı (Q)
+    /// 
+    /// `TEMP_KALMAN_MEASUREMENT_VAR` ile birlikte verilirse sıcaklık okumaları
+    /// yayınlanmadan önce `KalmanFilter1D` ile yumuşatılır. Küçük değerler
+    /// daha çok yumuşatır, büyük değerler değişimlere daha hızlı uyar. Sadece
+    /// birinin verilmesi veya pozitif olmayan değer agent'ın başlamasını engeller.
+    /// 
+    /// Örnek: `TEMP_KALMAN_PROCESS_VAR=0.01`
+    pub temp_kalman_process_var: Option<f64>,
+
+    /// Sıcaklık sensörü gürültüsünün varyansı (R, °C²)
+    /// 
+    /// Örnek: `TEMP_KALMAN_MEASUREMENT_VAR=0.25` (±0.5°C gürültü)
+    pub temp_kalman_measurement_var: Option<f64>,
+
+    /// Sıcaklık okumalarının yumuşatılması (`sma:<pencere>`, `ema:<alpha>`, `none`)
+    /// 
+    /// Kalibrasyondan sonra, deadband'den önce uygulanır. Geçersiz değer
+    /// agent'ın başlamasını engeller.
+    /// 
+    /// Örnek: `TEMP_SMOOTHING=ema:0.3`
+    pub temp_smoothing: Option<String>,
+
+    /// Nem okumalarının yumuşatılması
+    /// 
+    /// Örnek: `HUMIDITY_SMOOTHING=sma:5`
+    pub humidity_smoothing: Option<String>,
+
+    /// Sıcaklık kalibrasyon offset'i (°C, ölçeklemeden sonra eklenir)
+    /// 
+    /// Örnek: `TEMP_OFFSET=-1.5`
+    pub temp_offset: Option<f64>,
+
+    /// Sıcaklık kalibrasyon çarpanı
+    /// 
+    /// Örnek: `TEMP_SCALE=1.02`
+    pub temp_scale: Option<f64>,
+
+    /// Nem kalibrasyon offset'i (%)
+    pub humidity_offset: Option<f64>,
+
+    /// Nem kalibrasyon çarpanı
+    pub humidity_scale: Option<f64>,
+
+    /// Değer değişmese bile iki yayın arasındaki maksimum süre (saniye)
+    /// 
+    /// Varsayılan: 60
+    /// 
+    /// Downstream staleness tespitinin çalışması için heartbeat görevi görür.
+    #[serde(default = "default_max_silence")]
+    pub max_silence_secs: u64,
+
+    /// Çevrimiçi durumunun (`DeviceStatusMessage`) yeniden yayın aralığı (saniye)
+    ///
+    /// Varsayılan: 30 (0: sadece bağlanınca yayınlanır)
+    ///
+    /// Sensör döngüsünde kontrol edilir; okuma aralığından sık yayın olmaz.
+    #[serde(default = "default_will_interval")]
+    pub will_interval_secs: u64,
+
+    /// Aktif sensörler (virgülle ayrılmış)
+    /// 
+    /// Varsayılan: "temperature,humidity,motion"
+    /// 
+    /// Örnek: `ENABLED_SENSORS=temperature,motion`
+    #[serde(default = "default_enabled_sensors")]
+    pub enabled_sensors: String,
+
+    /// Sensör mesajları için MQTT QoS seviyesi (0, 1, 2)
+    /// 
+    /// Varsayılan: 0
+    #[serde(default)]
+    pub mqtt_qos: u8,
+
+    /// QoS 1/2'de broker onayı bekleyen maksimum publish sayısı
+    /// 
+    /// Varsayılan: 100
+    /// 
+    /// Aşılırsa sensör döngüsü bir aralık okuma yapmadan bekler (backpressure).
+    #[serde(default = "default_max_pending_acks")]
+    pub max_pending_acks: u64,
+
+    /// Uzaktan uygulanan runtime config override'larının saklandığı dosya
+    /// 
+    /// Varsayılan: "runtime_config.json"
+    #[serde(default = "default_runtime_config_path")]
+    pub runtime_config_path: String,
+
+    /// API server adresi (device shadow senkronizasyonu ve `SENSOR_TRANSPORT=http` için)
+    /// 
+    /// Ayarlanmazsa mDNS ile `_rustyflow._tcp.local.` aranır;
+    /// bulunamazsa "http://localhost:3000" kullanılır.
+    #[serde(default)]
+    pub api_server_url: String,
+
+    /// Cihaz kaydında gönderilen bearer token (API server'ın `PROVISIONING_TOKEN`'ı)
+    /// 
+    /// API server'da `AUTH_ENABLED=true` iken `POST /v1/provision` bu token'ı
+    /// (veya admin token'ı) ister. Sadece `DEVICE_ID` verilmemişse kullanılır.
+    /// 
+    /// Örnek: `PROVISIONING_TOKEN=change-me`
+    pub provisioning_token: Option<String>,
+
+    /// API server'a yapılan her HTTP isteğinde gönderilen cihaz token'ı
+    /// 
+    /// `POST /v1/devices/{id}/tokens` ile alınan `rfd_...` token'ı veya bir
+    /// `device` JWT'si. Ayarlanmazsa kayıtta alınan token kullanılır
+    /// (`DEVICE_TOKEN_FILE`). Okuma gönderimi (`SENSOR_TRANSPORT=http`), gölge
+    /// senkronizasyonu ve fotoğraf yükleme bu token'la yapılır.
+    /// 
+    /// Örnek: `DEVICE_TOKEN=rfd_...`
+    pub device_token: Option<String>,
+
+    /// Sensör mesajlarının MQTT payload formatı
+    /// 
+    /// Varsayılan: json
+    /// 
+    /// Örnek: `PAYLOAD_FORMAT=cbor` (daha küçük payload, topic'e `/cbor` eklenir)
+    #[serde(default)]
+    pub payload_format: PayloadFormat,
+
+    /// Sensör mesajlarını LZ4 ile sıkıştır
+    /// 
+    /// Sıkıştırılmış payload'lar topic'e `/lz4` eklenerek publish edilir
+    /// (`sensors/dev/temp/cbor/lz4`). Sıkıştırma payload'u küçültmüyorsa
+    /// mesaj sıkıştırılmadan gönderilir.
+    /// 
+    /// Varsayılan: false
+    /// 
+    /// Örnek: `MQTT_COMPRESS=true`
+    #[serde(default)]
+    pub mqtt_compress: bool,
+
+    /// MQTT protokol sürümü
+    /// 
+    /// `v5`'te okumalar `device_id`, `sensor_type` ve `schema_version` user
+    /// property'leriyle ve `MAX_SILENCE_SECS` kadar message expiry ile
+    /// publish edilir. Broker v5'i reddederse v4 ile yeniden bağlanılır.
+    /// 
+    /// Varsayılan: v4
+    /// 
+    /// Örnek: `MQTT_PROTOCOL=v5`
+    #[serde(default)]
+    pub mqtt_protocol: MqttProtocol,
+
+    /// Sensör okumalarının gönderim yolu
+    /// 
+    /// Varsayılan: mqtt
+    /// 
+    /// Örnek: `SENSOR_TRANSPORT=http` (okumalar `{API_SERVER_URL}/api/sensors`'a POST edilir;
+    /// komutlar yine MQTT'den gelir)
+    #[serde(default)]
+    pub sensor_transport: SensorTransport,
+
+    /// Simüle edilecek sanal cihaz sayısı (yük testi)
+    /// 
+    /// Varsayılan: 0 (kapalı, tek gerçek cihaz)
+    /// 
+    /// Örnek: `SIMULATE_DEVICES=50`
+    #[serde(default)]
+    pub simulate_devices: usize,
+
+    /// Simülasyonda okuma aralığı hızlandırma çarpanı
+    /// 
+    /// Varsayılan: 1.0
+    /// 
+    /// Örnek: `SIMULATE_RATE_MULTIPLIER=10` (5 saniyelik aralık → 0.5 saniye)
+    #[serde(default = "default_rate_multiplier")]
+    pub simulate_rate_multiplier: f64,
+
+    /// Sanal cihaz ID'lerinin türetildiği seed
+    /// 
+    /// Varsayılan: 0. Aynı seed her çalıştırmada aynı cihaz ID'lerini üretir.
+    #[serde(default)]
+    pub simulate_seed: u64,
+
+    /// LED → GPIO (BCM) pin eşlemesi
+    /// 
+    /// Varsayılan: "" (pin yok, LED komutları sadece loglanır)
+    /// 
+    /// Örnek: `LED_PINS=led_01:17,led_02:27`
+    #[serde(default)]
+    pub led_pins: String,
+
+    /// `take_photo` ile çekilen fotoğrafların kaydedildiği dizin
+    /// 
+    /// Varsayılan: "captures". Media kayıtlarının `path`'i bu dizindeki
+    /// dosyayı gösterir.
+    #[serde(default = "default_camera_capture_dir")]
+    pub camera_capture_dir: String,
+
+    /// Hareket algılanınca (`motion` 0 → 1) otomatik fotoğraf çek
+    /// 
+    /// Varsayılan: false
+    #[serde(default)]
+    pub motion_capture: bool,
+
+    /// Otomatik çekimler arasındaki en kısa süre (saniye)
+    /// 
+    /// Varsayılan: 60
+    #[serde(default = "default_motion_capture_cooldown")]
+    pub motion_capture_cooldown_secs: u64,
+
     /// Logging seviyesi
     /// 
     /// Varsayılan: "info"
@@ -62,35 +436,531 @@ pub struct Config {
     pub log_level: String,
 }
 
+/// MQTT payload formatı
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFormat {
+    #[default]
+    Json,
+    /// Binary CBOR; gateway `/cbor` topic suffix'inden tanır
+    Cbor,
+}
+
+impl PayloadFormat {
+    /// Formata göre publish topic'i (`sensors/dev/temp` → `sensors/dev/temp/cbor`)
+    pub fn topic(self, base: &str) -> String {
+        match self {
+            Self::Json => base.to_string(),
+            Self::Cbor => format!("{}/cbor", base),
+        }
+    }
+
+    /// Mesajı formata göre serialize et
+    pub fn encode(self, message: &MqttMessage) -> shared_types::Result<Vec<u8>> {
+        match self {
+            Self::Json => serde_json::to_vec(message)
+                .map_err(|e| shared_types::Error::SerializationError(e.to_string())),
+            Self::Cbor => message.to_cbor(),
+        }
+    }
+}
+
+/// Serialize edilmiş payload'u LZ4 ile sıkıştır (`MQTT_COMPRESS`)
+/// 
+/// Sıkıştırılmış payload ve `/lz4` eklenmiş topic döner; sıkıştırma
+/// payload'u küçültmüyorsa (çok küçük mesajlar) ikisi de aynen döner.
+pub fn compress_payload(topic: String, bytes: Vec<u8>) -> (String, Vec<u8>) {
+    let compressed = lz4::compress(&bytes);
+    let compression_ratio = lz4::ratio(bytes.len(), compressed.len());
+    debug!(compression_ratio, "🗜️  {} → {} bytes for '{}'", bytes.len(), compressed.len(), topic);
+    if compressed.len() < bytes.len() {
+        (format!("{}{}", topic, lz4::TOPIC_SUFFIX), compressed)
+    } else {
+        (topic, bytes)
+    }
+}
+
+/// Sensör okumalarının gönderim yolu
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorTransport {
+    /// `sensors/{device_name}/{sensor_type}` topic'ine publish (gateway forward eder)
+    #[default]
+    Mqtt,
+    /// Gateway'siz: doğrudan API server'a `POST /api/sensors`
+    Http,
+}
+
 // Varsayılan değer fonksiyonları
 fn generate_device_id() -> Uuid { Uuid::new_v4() }
 fn default_device_name() -> String { "edge-agent".into() }
+fn default_device_id_file() -> String { "device_id".into() }
+fn default_device_token_file() -> String { "device_token".into() }
 fn default_broker_host() -> String { "localhost".into() }
 fn default_broker_port() -> u16 { 1883 }
+fn default_discovery_timeout() -> u64 { 5 }
+fn default_keep_alive_secs() -> u64 { 5 }
+fn default_clean_session() -> bool { true }
+fn default_max_inflight() -> u32 { 100 }
+fn default_request_channel_capacity() -> usize { 10 }
 fn default_sensor_interval() -> u64 { 5 }
+fn default_min_sensor_interval() -> u64 { 1 }
+fn default_max_sensor_interval() -> u64 { 60 }
+fn default_change_rate_threshold() -> f64 { 0.5 }
+fn default_change_threshold() -> f64 { 0.1 }
+fn default_max_silence() -> u64 { 60 }
+fn default_will_interval() -> u64 { 30 }
+fn default_enabled_sensors() -> String { "temperature,humidity,motion".into() }
+fn default_max_pending_acks() -> u64 { 100 }
+fn default_runtime_config_path() -> String { "runtime_config.json".into() }
+fn default_api_server_url() -> String { "http://localhost:3000".into() }
+fn default_rate_multiplier() -> f64 { 1.0 }
+fn default_camera_capture_dir() -> String { "captures".into() }
+fn default_motion_capture_cooldown() -> u64 { 60 }
 fn default_log() -> String { "info".into() }
 
-impl Config {
-    /// Yapılandırmayı yükle
-    pub fn load() -> Self {
-        // .env dosyasını yükle
-        let _ = dotenvy::dotenv();
-
-        // Ortam değişkenlerini parse et
-        let mut cfg: Config = envy::from_env().unwrap_or_else(|_| Config {
+impl Default for Config {
+    fn default() -> Self {
+        Config {
             device_id: generate_device_id(),
             device_name: default_device_name(),
-            mqtt_broker_host: default_broker_host(),
+            device_serial: None,
+            device_id_file: default_device_id_file(),
+            device_token_file: default_device_token_file(),
+            mqtt_broker_host: String::new(),
             mqtt_broker_port: default_broker_port(),
+            mqtt_discovery_timeout_secs: default_discovery_timeout(),
+            mqtt_keep_alive_secs: default_keep_alive_secs(),
+            mqtt_clean_session: default_clean_session(),
+            mqtt_max_inflight: default_max_inflight(),
+            mqtt_request_channel_capacity: default_request_channel_capacity(),
             sensor_interval_secs: default_sensor_interval(),
+            min_sensor_interval_secs: default_min_sensor_interval(),
+            max_sensor_interval_secs: default_max_sensor_interval(),
+            change_rate_threshold: default_change_rate_threshold(),
+            sensor_change_threshold: default_change_threshold(),
+            temp_deadband: None,
+            humidity_deadband: None,
+            temp_kalman_process_var: None,
+            temp_kalman_measurement_var: None,
+            temp_smoothing: None,
+            humidity_smoothing: None,
+            temp_offset: None,
+            temp_scale: None,
+            humidity_offset: None,
+            humidity_scale: None,
+            max_silence_secs: default_max_silence(),
+            will_interval_secs: default_will_interval(),
+            enabled_sensors: default_enabled_sensors(),
+            mqtt_qos: 0,
+            max_pending_acks: default_max_pending_acks(),
+            runtime_config_path: default_runtime_config_path(),
+            api_server_url: String::new(),
+            provisioning_token: None,
+            device_token: None,
+            payload_format: PayloadFormat::default(),
+            mqtt_compress: false,
+            mqtt_protocol: MqttProtocol::default(),
+            sensor_transport: SensorTransport::default(),
+            simulate_devices: 0,
+            simulate_rate_multiplier: default_rate_multiplier(),
+            simulate_seed: 0,
+            led_pins: String::new(),
+            camera_capture_dir: default_camera_capture_dir(),
+            motion_capture: false,
+            motion_capture_cooldown_secs: default_motion_capture_cooldown(),
             log_level: default_log(),
-        });
+        }
+    }
+}
+
+impl FileConfig for Config {
+    /// `device_id` varsayılanı rastgele olduğundan her zaman `override_`'dakidir
+    fn merge(base: Self, override_: Self) -> Self {
+        merge_config!(
+            Config {
+                device_id,
+                device_name,
+                device_serial,
+                device_id_file,
+                device_token_file,
+                mqtt_broker_host,
+                mqtt_broker_port,
+                mqtt_discovery_timeout_secs,
+                mqtt_keep_alive_secs,
+                mqtt_clean_session,
+                mqtt_max_inflight,
+                mqtt_request_channel_capacity,
+                sensor_interval_secs,
+                min_sensor_interval_secs,
+                max_sensor_interval_secs,
+                change_rate_threshold,
+                sensor_change_threshold,
+                temp_deadband,
+                humidity_deadband,
+                temp_kalman_process_var,
+                temp_kalman_measurement_var,
+                temp_smoothing,
+                humidity_smoothing,
+                temp_offset,
+                temp_scale,
+                humidity_offset,
+                humidity_scale,
+                max_silence_secs,
+                will_interval_secs,
+                enabled_sensors,
+                mqtt_qos,
+                max_pending_acks,
+                runtime_config_path,
+                api_server_url,
+                provisioning_token,
+                device_token,
+                payload_format,
+                mqtt_compress,
+                mqtt_protocol,
+                sensor_transport,
+                simulate_devices,
+                simulate_rate_multiplier,
+                simulate_seed,
+                led_pins,
+                camera_capture_dir,
+                motion_capture,
+                motion_capture_cooldown_secs,
+                log_level,
+            },
+            base,
+            override_,
+        )
+    }
+
+    fn from_file_with_env(path: &Path, env: impl IntoIterator<Item = (String, String)>) -> shared_types::Result<Self> {
+        let file: Config = shared_types::config::read_file(path)?;
+        let env: Vec<(String, String)> = env.into_iter().collect();
+        let has_device_id = env.iter().any(|(key, _)| key == "DEVICE_ID");
+        let mut env_cfg: Config = shared_types::config::from_env(env)?;
+        // Varsayılan device_id her seferinde rastgele: env'de yoksa dosyadaki geçerli
+        if !has_device_id {
+            env_cfg.device_id = file.device_id;
+        }
+        Ok(Self::merge(file, env_cfg))
+    }
+}
+
+impl Config {
+    /// Yapılandırmayı yükle
+    /// 
+    /// Config dosyası varsa (`RUSTYFLOW_CONFIG_FILE` / `./rustyflow.toml`)
+    /// env var'larla birleştirilir, yoksa sadece env var'lar okunur.
+    /// Okunamayan veya parse edilemeyen config dosyası hata döner.
+    pub fn load() -> shared_types::Result<Self> {
+        // .env dosyasını yükle
+        let _ = dotenvy::dotenv();
+
+        // Config dosyası + ortam değişkenleri
+        // Okunamayan config dosyası hata döner (servis başlamaz)
+        let mut cfg = match shared_types::config::config_file_path() {
+            Some(path) => Self::from_file(&path)?,
+            None => envy::from_env().unwrap_or_default(),
+        };
 
         // RUST_LOG özel işlemi
         if let Ok(level) = std::env::var("RUST_LOG") {
             cfg.log_level = level;
         }
 
-        cfg
+        Ok(cfg)
+    }
+
+    /// `DEVICE_ID` env var'da veya config dosyasında verilmiş mi?
+    /// 
+    /// Verilmemişse `device_id` rastgeledir ve cihaz kayıt olmalıdır
+    /// (bkz. `provisioning`). `load`'dan sonra çağrılmalı (.env okunmuş olur).
+    pub fn device_id_configured() -> bool {
+        std::env::var_os("DEVICE_ID").is_some()
+            || shared_types::config::config_file_path()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .and_then(|raw| raw.parse::<toml::Table>().ok())
+                .is_some_and(|table| table.contains_key("device_id"))
+    }
+
+    /// Ayarlanmamış MQTT broker ve API server adreslerini mDNS ile bul
+    /// 
+    /// İki servis paralel aranır; her biri en fazla
+    /// `mqtt_discovery_timeout_secs` bekler. Bulunamayanlar için localhost
+    /// varsayılanları kullanılır ve uyarı loglanır.
+    pub async fn discover_services(&mut self) {
+        self.discover_services_with(MQTT_SERVICE_TYPE, API_SERVICE_TYPE).await
+    }
+
+    async fn discover_services_with(&mut self, mqtt_service: &str, api_service: &str) {
+        let timeout = Duration::from_secs(self.mqtt_discovery_timeout_secs);
+        let need_broker = self.mqtt_broker_host.is_empty();
+        let need_api = self.api_server_url.is_empty();
+
+        let (broker, api) = tokio::join!(
+            async { if need_broker { discover(mqtt_service, timeout).await } else { None } },
+            async { if need_api { discover(api_service, timeout).await } else { None } },
+        );
+
+        if need_broker {
+            match broker {
+                Some(found) => {
+                    info!("🔎 Discovered MQTT broker via mDNS: {}:{}", found.host, found.port);
+                    self.mqtt_broker_host = found.host;
+                    self.mqtt_broker_port = found.port;
+                }
+                None => {
+                    warn!("No MQTT broker found via mDNS within {:?}, falling back to localhost", timeout);
+                    self.mqtt_broker_host = default_broker_host();
+                }
+            }
+        }
+        if need_api {
+            match api {
+                Some(found) => {
+                    info!("🔎 Discovered API server via mDNS: {}:{}", found.host, found.port);
+                    self.api_server_url = format!("http://{}:{}", found.host, found.port);
+                }
+                None => {
+                    warn!("No API server found via mDNS within {:?}, falling back to localhost", timeout);
+                    self.api_server_url = default_api_server_url();
+                }
+            }
+        }
+    }
+
+    /// MQTT oturum ayarları (keep-alive, clean session, inflight, istek kanalı)
+    /// 
+    /// Sınır dışı değerler hata döner (bkz. `SessionOptions::from_config`).
+    pub fn mqtt_session(&self) -> Result<SessionOptions, MqttError> {
+        SessionOptions::from_config(
+            self.mqtt_keep_alive_secs,
+            self.mqtt_clean_session,
+            self.mqtt_max_inflight,
+            self.mqtt_request_channel_capacity,
+        )
+    }
+
+    /// Aktif sensör listesini parse et (virgülle ayrılmış → Vec<String>)
+    pub fn parse_enabled_sensors(&self) -> Vec<String> {
+        parse_list(&self.enabled_sensors)
+    }
+
+    /// Sıcaklık sensörünün Kalman filtresi
+    /// 
+    /// İki varyans da verilmemişse `None`. Sadece biri verilmişse veya
+    /// değerlerden biri sonlu ve pozitif değilse hata döner.
+    pub fn temp_kalman(&self) -> shared_types::Result<Option<KalmanFilter1D>> {
+        let (process, measurement) = match (self.temp_kalman_process_var, self.temp_kalman_measurement_var) {
+            (None, None) => return Ok(None),
+            (Some(process), Some(measurement)) => (process, measurement),
+            _ => {
+                return Err(shared_types::Error::InvalidParameter(
+                    "TEMP_KALMAN_PROCESS_VAR and TEMP_KALMAN_MEASUREMENT_VAR must be set together".into(),
+                ))
+            }
+        };
+        for (name, value) in [("TEMP_KALMAN_PROCESS_VAR", process), ("TEMP_KALMAN_MEASUREMENT_VAR", measurement)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(shared_types::Error::InvalidParameter(format!("{name} must be a positive number, got {value}")));
+            }
+        }
+        Ok(Some(KalmanFilter1D::new(process, measurement)))
+    }
+
+    /// İki yayın arasındaki maksimum süre (`max_silence_secs`)
+    ///
+    /// `TimeDelta`'ya sığmayan değerler hata döner.
+    pub fn max_silence(&self) -> shared_types::Result<chrono::TimeDelta> {
+        i64::try_from(self.max_silence_secs)
+            .ok()
+            .and_then(chrono::TimeDelta::try_seconds)
+            .ok_or_else(|| shared_types::Error::InvalidParameter(format!("max_silence_secs out of range: {}", self.max_silence_secs)))
+    }
+
+    /// Yumuşatma ayarlarını sensör tipine göre parse et
+    /// 
+    /// `none` veya ayarlanmamış sensörler listede yer almaz.
+    pub fn smoothers(&self) -> shared_types::Result<BTreeMap<String, Smoother>> {
+        let mut smoothers = BTreeMap::new();
+        for (sensor_type, spec) in [("temperature", &self.temp_smoothing), ("humidity", &self.humidity_smoothing)] {
+            let Some(spec) = spec else { continue };
+            let smoother: Smoother = spec.parse()?;
+            if smoother != Smoother::None {
+                smoothers.insert(sensor_type.to_string(), smoother);
+            }
+        }
+        Ok(smoothers)
+    }
+
+    /// Env'de tanımlı kalibrasyonları sensör tipine göre topla
+    pub fn parse_calibration(&self) -> BTreeMap<String, Calibration> {
+        [
+            ("temperature", Calibration::from_parts(self.temp_offset, self.temp_scale)),
+            ("humidity", Calibration::from_parts(self.humidity_offset, self.humidity_scale)),
+        ]
+        .into_iter()
+        .filter_map(|(sensor_type, cal)| Some((sensor_type.to_string(), cal?)))
+        .collect()
+    }
+}
+
+/// Virgülle ayrılmış listeyi parse et
+fn parse_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mdns_sd::{ServiceDaemon, ServiceInfo};
+
+    /// Ortam değişkeni olmadan (tüm varsayılanlarla) config
+    fn empty_config() -> Config {
+        envy::from_iter(Vec::<(String, String)>::new()).unwrap()
+    }
+
+    fn unique_service_type() -> String {
+        format!("_rf{}._tcp.local.", &Uuid::new_v4().simple().to_string()[..8])
+    }
+
+    #[tokio::test]
+    async fn test_discovers_broker_and_api_from_loopback_announcements() {
+        let (mqtt_service, api_service) = (unique_service_type(), unique_service_type());
+        let publisher = ServiceDaemon::new().unwrap();
+        for (service_type, port) in [(&mqtt_service, 18831), (&api_service, 18300)] {
+            let info = ServiceInfo::new(service_type, "rustyflow-test", "rustyflow-test.local.", "127.0.0.1", port, None::<std::collections::HashMap<String, String>>)
+                .unwrap();
+            publisher.register(info).unwrap();
+        }
+
+        let mut cfg = empty_config();
+        assert!(cfg.mqtt_broker_host.is_empty() && cfg.api_server_url.is_empty());
+        cfg.discover_services_with(&mqtt_service, &api_service).await;
+
+        assert_eq!((cfg.mqtt_broker_host.as_str(), cfg.mqtt_broker_port), ("127.0.0.1", 18831));
+        assert_eq!(cfg.api_server_url, "http://127.0.0.1:18300");
+        let _ = publisher.shutdown();
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_localhost_and_keeps_explicit_values() {
+        let mut cfg = Config {
+            mqtt_broker_host: "broker.lan".into(),
+            mqtt_discovery_timeout_secs: 1,
+            ..empty_config()
+        };
+        cfg.discover_services_with(&unique_service_type(), &unique_service_type()).await;
+
+        assert_eq!((cfg.mqtt_broker_host.as_str(), cfg.mqtt_broker_port), ("broker.lan", 1883));
+        assert_eq!(cfg.api_server_url, "http://localhost:3000");
+    }
+
+    fn example_file() -> &'static Path {
+        Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/rustyflow.example.toml"))
+    }
+
+    #[test]
+    fn test_from_file_env_takes_precedence() {
+        let env = [("SENSOR_INTERVAL_SECS".to_string(), "10".to_string())];
+        let cfg = Config::from_file_with_env(example_file(), env).unwrap();
+
+        assert_eq!(cfg.sensor_interval_secs, 10);
+        assert_eq!(cfg.device_id, "550e8400-e29b-41d4-a716-446655440000".parse::<Uuid>().unwrap());
+        assert_eq!(cfg.device_name, "raspberry-pi-01");
+        assert_eq!(cfg.payload_format, PayloadFormat::Cbor);
+        assert_eq!(cfg.temp_offset, Some(-1.5));
+        assert_eq!(cfg.humidity_offset, None);
+        let session = cfg.mqtt_session().unwrap();
+        assert_eq!((session.keep_alive, session.clean_session), (Duration::from_secs(60), false));
+    }
+
+    #[test]
+    fn test_mqtt_session_env_parsing_and_bounds() {
+        let env = |pairs: &[(&str, &str)]| -> Config {
+            envy::from_iter(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string()))).unwrap()
+        };
+        let cfg = env(&[("MQTT_KEEP_ALIVE_SECS", "45"), ("MQTT_MAX_INFLIGHT", "20"), ("MQTT_REQUEST_CHANNEL_CAPACITY", "32")]);
+        let session = cfg.mqtt_session().unwrap();
+        assert_eq!((session.keep_alive, session.clean_session), (Duration::from_secs(45), true));
+        assert_eq!((session.max_inflight, session.request_channel_capacity), (20, 32));
+
+        assert!(env(&[("MQTT_KEEP_ALIVE_SECS", "1")]).mqtt_session().is_err());
+        assert!(env(&[("MQTT_MAX_INFLIGHT", "0")]).mqtt_session().is_err());
+        assert!(env(&[("MQTT_MAX_INFLIGHT", "100000")]).mqtt_session().is_err());
+    }
+
+    #[test]
+    fn test_temp_kalman_requires_both_positive_variances() {
+        let env = |pairs: &[(&str, &str)]| -> Config {
+            envy::from_iter(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string()))).unwrap()
+        };
+        let filter = env(&[("TEMP_KALMAN_PROCESS_VAR", "0.01"), ("TEMP_KALMAN_MEASUREMENT_VAR", "0.25")]).temp_kalman().unwrap().unwrap();
+        assert_eq!((filter.process_variance, filter.measurement_variance), (0.01, 0.25));
+        assert!(env(&[]).temp_kalman().unwrap().is_none());
+
+        // Eksik veya geçersiz ayar başlangıcı durdurur
+        let err = env(&[("TEMP_KALMAN_PROCESS_VAR", "0.01")]).temp_kalman().unwrap_err();
+        assert!(err.to_string().contains("TEMP_KALMAN_MEASUREMENT_VAR"), "{err}");
+        for (process, measurement) in [("0", "0.25"), ("0.01", "-1"), ("NaN", "0.25"), ("0.01", "inf")] {
+            let cfg = env(&[("TEMP_KALMAN_PROCESS_VAR", process), ("TEMP_KALMAN_MEASUREMENT_VAR", measurement)]);
+            assert!(cfg.temp_kalman().is_err(), "{process} / {measurement}");
+        }
+    }
+
+    #[test]
+    fn test_smoothers_parse_per_sensor() {
+        let env = |pairs: &[(&str, &str)]| -> Config {
+            envy::from_iter(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string()))).unwrap()
+        };
+        let smoothers = env(&[("TEMP_SMOOTHING", "ema:0.3"), ("HUMIDITY_SMOOTHING", "none")]).smoothers().unwrap();
+        assert_eq!(smoothers.len(), 1);
+        assert_eq!(smoothers["temperature"], Smoother::Ema(0.3, None));
+        assert!(env(&[]).smoothers().unwrap().is_empty());
+
+        let err = env(&[("HUMIDITY_SMOOTHING", "sma:0")]).smoothers().unwrap_err();
+        assert!(err.to_string().contains("sma:0"), "{err}");
+    }
+
+    #[test]
+    fn test_max_silence_rejects_out_of_range() {
+        let env = |secs: &str| -> Config { envy::from_iter([("MAX_SILENCE_SECS".to_string(), secs.to_string())]).unwrap() };
+        assert_eq!(env("90").max_silence().unwrap(), chrono::TimeDelta::seconds(90));
+        for secs in [u64::MAX.to_string(), (i64::MAX as u64).to_string()] {
+            let err = env(&secs).max_silence().unwrap_err();
+            assert!(err.to_string().contains("max_silence_secs"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_env_device_id_overrides_file() {
+        let id = Uuid::new_v4();
+        let env = [("DEVICE_ID".to_string(), id.to_string())];
+        assert_eq!(Config::from_file_with_env(example_file(), env).unwrap().device_id, id);
+    }
+
+    #[test]
+    fn test_merge_takes_non_default_override_fields() {
+        let base = Config { device_name: "rpi-kitchen".into(), mqtt_qos: 1, ..empty_config() };
+        let override_ = Config { mqtt_qos: 2, ..empty_config() };
+
+        let merged = Config::merge(base, override_);
+        assert_eq!((merged.device_name.as_str(), merged.mqtt_qos), ("rpi-kitchen", 2));
+    }
+
+    #[test]
+    fn test_compress_payload_suffixes_topic_only_when_smaller() {
+        let (topic, bytes) = compress_payload("sensors/rpi/temperature".into(), b"{}".to_vec());
+        assert_eq!((topic.as_str(), bytes.as_slice()), ("sensors/rpi/temperature", b"{}".as_slice()));
+
+        let reading = MqttMessage::new("temperature_reading".into(), serde_json::json!({"value": "23.5"}), uuid::Uuid::new_v4());
+        let batch = serde_json::to_vec(&vec![reading; 12]).unwrap();
+        let (topic, bytes) = compress_payload("sensors/rpi/temperature".into(), batch.clone());
+        assert_eq!(topic, "sensors/rpi/temperature/lz4");
+        assert!(bytes.len() < batch.len());
+        assert_eq!(lz4::decompress(&bytes).unwrap(), batch);
     }
 }
@@ -0,0 +1,251 @@
+//! MQTT Bağlantı Durumu
+//!
+//! Event loop task'ı bağlantı hatalarında exponential backoff + jitter ile
+//! bekler ve güncel durumu bir `watch` kanalıyla yayınlar. Sensör döngüsü
+//! bu durumu okur: bağlantı yokken okumalar `client.publish` yerine yerel
+//! buffer'a (`OfflineBuffer`) yazılır, bağlantı gelince sırayla gönderilir.
+
+use std::collections::VecDeque;
+
+use rand::Rng;
+use rumqttc::QoS;
+use shared_types::mqtt::ReadingProperties;
+use tokio::sync::watch;
+use tokio::time::Duration;
+
+/// İlk yeniden deneme beklemesi
+pub const RECONNECT_BASE: Duration = Duration::from_secs(1);
+
+/// Yeniden deneme beklemesinin üst sınırı
+pub const RECONNECT_MAX: Duration = Duration::from_secs(60);
+
+/// Bağlantı yokken saklanan maksimum mesaj (dolunca en eskisi atılır)
+pub const OFFLINE_BUFFER_CAPACITY: usize = 1000;
+
+/// Broker bağlantısının durumu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnState {
+    /// ConnAck alındı, publish'ler broker'a gider
+    Connected,
+    /// Henüz bağlanmadı veya bağlantı koptu
+    Disconnected,
+}
+
+/// Durum değişikliği (her geçiş bir kez loglanır)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Connected,
+    Disconnected,
+}
+
+/// Yeniden bağlanma durum makinesi
+///
+/// Ardışık hata sayısını tutar; durum sadece değiştiğinde `Transition` döner.
+#[derive(Debug)]
+pub struct Reconnect {
+    state: ConnState,
+    failures: u32,
+    tx: watch::Sender<ConnState>,
+}
+
+impl Reconnect {
+    /// `Disconnected` ile başlar; alıcı sensör döngüsüne verilir
+    pub fn new() -> (Self, watch::Receiver<ConnState>) {
+        let (tx, rx) = watch::channel(ConnState::Disconnected);
+        (Self { state: ConnState::Disconnected, failures: 0, tx }, rx)
+    }
+
+    /// ConnAck geldi: hata sayacı sıfırlanır
+    pub fn on_connected(&mut self) -> Option<Transition> {
+        self.failures = 0;
+        self.set(ConnState::Connected).then_some(Transition::Connected)
+    }
+
+    /// Bağlantı hatası: geçiş (varsa) ve bir sonraki denemeden önceki bekleme
+    ///
+    /// `jitter` [0, 1] aralığında olmalı (bkz. `backoff`).
+    pub fn on_error(&mut self, jitter: f64) -> (Option<Transition>, Duration) {
+        self.failures = self.failures.saturating_add(1);
+        let transition = self.set(ConnState::Disconnected).then_some(Transition::Disconnected);
+        (transition, backoff(self.failures, jitter))
+    }
+
+    /// Ardışık hata sayısı (loglama için)
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    fn set(&mut self, state: ConnState) -> bool {
+        if self.state == state {
+            return false;
+        }
+        self.state = state;
+        // Alıcı kalmadıysa (sensör döngüsü bitti) yapılacak bir şey yok
+        let _ = self.tx.send(state);
+        true
+    }
+}
+
+/// `attempt`. hatadan sonraki bekleme: `RECONNECT_BASE * 2^(attempt-1)`, en
+/// fazla `RECONNECT_MAX`
+///
+/// Aynı anda kopan cihazlar broker'a birlikte yüklenmesin diye beklemenin
+/// yarısı sabit, yarısı `jitter` ile ölçeklenir.
+pub fn backoff(attempt: u32, jitter: f64) -> Duration {
+    let exp = RECONNECT_BASE.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+    let delay = exp.min(RECONNECT_MAX);
+    delay / 2 + (delay / 2).mul_f64(jitter.clamp(0.0, 1.0))
+}
+
+/// Rastgele jitter (`backoff` için)
+pub fn random_jitter() -> f64 {
+    rand::thread_rng().gen_range(0.0..=1.0)
+}
+
+/// Okumanın gideceği yer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    Publish,
+    Buffer,
+}
+
+/// Bağlantı durumuna göre okumanın yolunu seç
+pub fn route(state: ConnState) -> Route {
+    match state {
+        ConnState::Connected => Route::Publish,
+        ConnState::Disconnected => Route::Buffer,
+    }
+}
+
+/// Bağlantı yokken biriken mesaj
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferedMessage {
+    pub topic: String,
+    pub qos: QoS,
+    pub payload: Vec<u8>,
+    /// v5 user property'leri (geç teslim edilecekleri için expiry verilmez)
+    pub properties: ReadingProperties,
+}
+
+/// Bağlantı yokken okumaları tutan sınırlı FIFO (sadece bellekte)
+#[derive(Debug)]
+pub struct OfflineBuffer {
+    messages: VecDeque<BufferedMessage>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl OfflineBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self { messages: VecDeque::new(), capacity, dropped: 0 }
+    }
+
+    /// Mesajı ekle; buffer doluysa en eskisini at
+    pub fn push(&mut self, message: BufferedMessage) {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.messages.len() == self.capacity {
+            self.messages.pop_front();
+            self.dropped += 1;
+        }
+        self.messages.push_back(message);
+    }
+
+    /// Biriken mesajları geliş sırasıyla al
+    pub fn drain(&mut self) -> impl Iterator<Item = BufferedMessage> + '_ {
+        self.messages.drain(..)
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Kapasite aşıldığı için atılan toplam mesaj
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(topic: &str) -> BufferedMessage {
+        BufferedMessage {
+            topic: topic.into(),
+            qos: QoS::AtMostOnce,
+            payload: vec![],
+            properties: ReadingProperties::new(uuid::Uuid::nil(), "temperature"),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_with_cap_and_jitter() {
+        assert_eq!(backoff(1, 1.0), Duration::from_secs(1));
+        assert_eq!(backoff(2, 1.0), Duration::from_secs(2));
+        assert_eq!(backoff(4, 1.0), Duration::from_secs(8));
+        assert_eq!(backoff(7, 1.0), RECONNECT_MAX);
+        assert_eq!(backoff(u32::MAX, 1.0), RECONNECT_MAX);
+        // Jitter beklemenin sadece yarısını etkiler
+        assert_eq!(backoff(4, 0.0), Duration::from_secs(4));
+        assert_eq!(backoff(4, 0.5), Duration::from_secs(6));
+        assert!((0..1000).map(|_| backoff(3, random_jitter())).all(|d| (2..=4).contains(&d.as_secs())));
+    }
+
+    #[test]
+    fn test_state_machine_reports_each_transition_once() {
+        let (mut reconnect, rx) = Reconnect::new();
+        assert_eq!(*rx.borrow(), ConnState::Disconnected);
+
+        // Henüz hiç bağlanmadı: ilk hatalar geçiş değil, sadece backoff artar
+        assert_eq!(reconnect.on_error(1.0), (None, Duration::from_secs(1)));
+        assert_eq!(reconnect.on_error(1.0), (None, Duration::from_secs(2)));
+
+        assert_eq!(reconnect.on_connected(), Some(Transition::Connected));
+        assert_eq!(reconnect.on_connected(), None);
+        assert_eq!(*rx.borrow(), ConnState::Connected);
+
+        // Kopma bir kez raporlanır; backoff baştan başlar
+        assert_eq!(reconnect.on_error(1.0), (Some(Transition::Disconnected), Duration::from_secs(1)));
+        assert_eq!(reconnect.on_error(1.0), (None, Duration::from_secs(2)));
+        assert_eq!(reconnect.failures(), 2);
+        assert_eq!(*rx.borrow(), ConnState::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_watch_receiver_sees_only_changes() {
+        let (mut reconnect, mut rx) = Reconnect::new();
+        reconnect.on_error(0.0);
+        assert!(!rx.has_changed().unwrap());
+
+        reconnect.on_connected();
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow_and_update(), ConnState::Connected);
+        reconnect.on_connected();
+        assert!(!rx.has_changed().unwrap());
+    }
+
+    #[test]
+    fn test_routes_to_buffer_when_disconnected() {
+        assert_eq!(route(ConnState::Connected), Route::Publish);
+        assert_eq!(route(ConnState::Disconnected), Route::Buffer);
+    }
+
+    #[test]
+    fn test_offline_buffer_keeps_newest_in_order() {
+        let mut buffer = OfflineBuffer::new(2);
+        for topic in ["a", "b", "c"] {
+            buffer.push(message(topic));
+        }
+        assert_eq!(buffer.dropped(), 1);
+        let topics: Vec<_> = buffer.drain().map(|m| m.topic).collect();
+        assert_eq!(topics, ["b", "c"]);
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+//! mDNS Servis Keşfi
+//!
+//! `MQTT_BROKER_HOST` veya `API_SERVER_URL` ayarlanmamışsa yerel ağda
+//! mDNS (Bonjour/Avahi) ile aranır:
+//! - MQTT broker: `_mqtt._tcp.local.` (Mosquitto/Avahi'nin standart servis tipi)
+//! - API server: `_rustyflow._tcp.local.` (`MDNS_ADVERTISE=true` ile yayınlanır)
+//!
+//! Bulunan ilk servisin IPv4 adresi (yoksa host adı) ve portu kullanılır.
+
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use tokio::time::{timeout_at, Duration, Instant};
+use tracing::{debug, warn};
+
+/// MQTT broker servis tipi
+pub const MQTT_SERVICE_TYPE: &str = "_mqtt._tcp.local.";
+
+/// RustyFlow API server servis tipi
+pub const API_SERVICE_TYPE: &str = "_rustyflow._tcp.local.";
+
+/// Keşfedilen servis adresi
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredService {
+    pub host: String,
+    pub port: u16,
+}
+
+/// `service_type` servisini en fazla `timeout` süre ara
+///
+/// mDNS daemon başlatılamazsa veya süre içinde servis çözülemezse `None` döner.
+pub async fn discover(service_type: &str, timeout: Duration) -> Option<DiscoveredService> {
+    let daemon = match ServiceDaemon::new() {
+        Ok(daemon) => daemon,
+        Err(e) => {
+            warn!("mDNS daemon could not start: {}", e);
+            return None;
+        }
+    };
+    let found = browse(&daemon, service_type, timeout).await;
+    let _ = daemon.shutdown();
+    found
+}
+
+async fn browse(daemon: &ServiceDaemon, service_type: &str, timeout: Duration) -> Option<DiscoveredService> {
+    let events = match daemon.browse(service_type) {
+        Ok(events) => events,
+        Err(e) => {
+            warn!("mDNS browse for {} failed: {}", service_type, e);
+            return None;
+        }
+    };
+
+    let deadline = Instant::now() + timeout;
+    while let Ok(Ok(event)) = timeout_at(deadline, events.recv_async()).await {
+        if let ServiceEvent::ServiceResolved(service) = event {
+            let addresses: Vec<_> = service.get_addresses().iter().map(|ip| ip.to_ip_addr()).collect();
+            let host = addresses
+                .iter()
+                .find(|ip| ip.is_ipv4())
+                .or(addresses.first())
+                .map(|ip| ip.to_string())
+                .unwrap_or_else(|| service.get_hostname().trim_end_matches('.').to_string());
+            debug!("mDNS resolved {} at {}:{}", service.get_fullname(), host, service.get_port());
+            return Some(DiscoveredService { host, port: service.get_port() });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mdns_sd::ServiceInfo;
+
+    /// Loopback üzerinden servis yayınla (test başına benzersiz servis tipi)
+    fn announce(service_type: &str, port: u16) -> ServiceDaemon {
+        let daemon = ServiceDaemon::new().unwrap();
+        let info = ServiceInfo::new(service_type, "rustyflow-test", "rustyflow-test.local.", "127.0.0.1", port, None::<std::collections::HashMap<String, String>>)
+            .unwrap();
+        daemon.register(info).unwrap();
+        daemon
+    }
+
+    fn unique_service_type() -> String {
+        format!("_rf{}._tcp.local.", &uuid::Uuid::new_v4().simple().to_string()[..8])
+    }
+
+    #[tokio::test]
+    #[ignore = "requires multicast"]
+    async fn test_discovers_loopback_announcement() {
+        let service_type = unique_service_type();
+        let publisher = announce(&service_type, 18830);
+
+        let found = discover(&service_type, Duration::from_secs(5)).await;
+        assert_eq!(found, Some(DiscoveredService { host: "127.0.0.1".into(), port: 18830 }));
+        let _ = publisher.shutdown();
+    }
+
+    #[tokio::test]
+    async fn test_missing_service_times_out() {
+        let started = Instant::now();
+        assert_eq!(discover(&unique_service_type(), Duration::from_millis(300)).await, None);
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+}
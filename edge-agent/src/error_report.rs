@@ -0,0 +1,138 @@
+//! Hata Raporları
+//!
+//! Başarısız komutlar ve sürücü (kamera, kalibrasyon) hataları
+//! `devices/{id}/status`'a `error_report` `DeviceMessage`'ı olarak publish
+//! edilir. Gateway bunları API server'a iletir; sahadaki cihazların
+//! hataları `GET /api/devices/{id}/errors` ile görülebilir.
+//!
+//! Her okumada tekrar oluşan hatalar (`report_fault`) sadece başladıklarında
+//! raporlanır; `clear_fault` ile düzelince bir sonraki oluşum yine raporlanır.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use rumqttc::QoS;
+use shared_types::messages::{DeviceCommand, DeviceCommandResponse, DeviceMessage};
+use shared_types::mqtt::MqttClient;
+use shared_types::{conventions, ErrorLevel, ErrorReport};
+use tracing::warn;
+use uuid::Uuid;
+
+/// Hata raporlarını durum topic'ine publish eder
+#[derive(Clone)]
+pub struct ErrorReporter {
+    client: MqttClient,
+    topic: String,
+    device_id: Uuid,
+    faults: Arc<Mutex<ActiveFaults>>,
+}
+
+impl ErrorReporter {
+    pub fn new(client: MqttClient, device_id: Uuid) -> Self {
+        Self { client, topic: conventions::status_topic(device_id), device_id, faults: Default::default() }
+    }
+
+    /// Süregelen `key` hatasını raporla (zaten raporlandıysa atlanır)
+    pub fn report_fault(&self, key: &str, report: ErrorReport) {
+        if self.faults.lock().unwrap_or_else(|e| e.into_inner()).raise(key) {
+            self.report(report);
+        }
+    }
+
+    /// `key` hatası düzeldi; tekrar oluşursa yine raporlanır
+    pub fn clear_fault(&self, key: &str) {
+        self.faults.lock().unwrap_or_else(|e| e.into_inner()).clear(key);
+    }
+
+    /// Raporu publish et (beklemez; bağlantı kuyruğu doluysa loglanıp atılır)
+    pub fn report(&self, report: ErrorReport) {
+        let payload = match payload(self.device_id, report) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize error report: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.client.try_publish(&self.topic, QoS::AtLeastOnce, false, payload) {
+            warn!("Failed to publish error report: {}", e);
+        }
+    }
+}
+
+/// Raporlanmış ve henüz düzelmemiş hatalar
+#[derive(Debug, Default)]
+struct ActiveFaults(HashSet<String>);
+
+impl ActiveFaults {
+    /// `key` hatası oluştu; yeni başladıysa `true` (raporlanmalı)
+    fn raise(&mut self, key: &str) -> bool {
+        self.0.insert(key.to_string())
+    }
+
+    fn clear(&mut self, key: &str) {
+        self.0.remove(key);
+    }
+}
+
+/// Raporun MQTT payload'u (büyük mesajlar cihazda kırpılır)
+fn payload(device_id: Uuid, mut report: ErrorReport) -> serde_json::Result<Vec<u8>> {
+    report.truncate();
+    serde_json::to_vec(&DeviceMessage::error_report(device_id, &report))
+}
+
+/// Başarısız komut yanıtının hata raporu (başarılı yanıtta `None`)
+pub fn command_failure(cmd: &DeviceCommand, response: &DeviceCommandResponse) -> Option<ErrorReport> {
+    if response.success {
+        return None;
+    }
+    let message = response.message.as_deref().unwrap_or("command failed");
+    let report = ErrorReport::new(ErrorLevel::Error, format!("{}: {}", cmd.command_name, message)).with_context(serde_json::json!({
+        "source": "command",
+        "command": cmd.command_name,
+        "correlation_id": cmd.correlation_id,
+    }));
+    Some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::device_error::MAX_ERROR_MESSAGE_BYTES;
+    use shared_types::messages::DeviceCommandKind;
+
+    #[test]
+    fn test_command_failure_reports_command_context() {
+        let cmd = DeviceCommand::new(Uuid::new_v4(), "control".into(), "led".into());
+        assert_eq!(command_failure(&cmd, &DeviceCommandResponse::ok(&cmd)), None);
+
+        let report = command_failure(&cmd, &DeviceCommandResponse::error(&cmd, "GPIO17 busy")).unwrap();
+        assert_eq!(report.level, ErrorLevel::Error);
+        assert_eq!(report.message, "led: GPIO17 busy");
+        let context = report.context.unwrap();
+        assert_eq!((context["source"].as_str(), context["command"].as_str()), (Some("command"), Some("led")));
+        assert_eq!(context["correlation_id"], cmd.correlation_id.to_string());
+    }
+
+    #[test]
+    fn test_faults_reported_on_transition_only() {
+        let mut faults = ActiveFaults::default();
+        assert!(faults.raise("calibration:temperature"));
+        assert!(!faults.raise("calibration:temperature"));
+        assert!(faults.raise("camera"));
+
+        faults.clear("calibration:temperature");
+        assert!(faults.raise("calibration:temperature"));
+    }
+
+    #[test]
+    fn test_payload_is_truncated_error_report_message() {
+        let device_id = Uuid::new_v4();
+        let report = ErrorReport::new(ErrorLevel::Critical, "x".repeat(MAX_ERROR_MESSAGE_BYTES + 1));
+
+        let msg: DeviceMessage = serde_json::from_slice(&payload(device_id, report).unwrap()).unwrap();
+        assert_eq!((msg.device_id, msg.command), (device_id, DeviceCommandKind::ErrorReport));
+        let sent: ErrorReport = serde_json::from_value(msg.data).unwrap();
+        assert_eq!(sent.level, ErrorLevel::Critical);
+        assert!(sent.message.len() <= MAX_ERROR_MESSAGE_BYTES && sent.message.ends_with('…'));
+    }
+}
@@ -0,0 +1,115 @@
+//! HTTP Sensör Gönderimi
+//!
+//! `SENSOR_TRANSPORT=http` ile okumalar MQTT + gateway yerine doğrudan
+//! API server'a `POST /api/sensors` ile gönderilir. Payload, gateway'in
+//! forward ettiği formatın aynısıdır.
+//!
+//! `api_client` agent'ın API server'a yaptığı tüm HTTP isteklerinde
+//! (okuma gönderimi, gölge senkronizasyonu, fotoğraf yükleme) cihaz
+//! token'ını `Authorization: Bearer` olarak gönderen client'ı kurar.
+
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::sensors::SensorData;
+
+/// Gateway ile aynı trace header'ı
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// `POST /api/sensors` body'si
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiSensorData {
+    pub device_id: String,
+    pub sensor_type: String,
+    pub value: f64,
+    pub unit: String,
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl ApiSensorData {
+    /// Agent okumasını API formatına çevir (sayısal olmayan değer → 0, gateway gibi)
+    pub fn from_reading(device_id: Uuid, data: &SensorData) -> Self {
+        Self {
+            device_id: device_id.to_string(),
+            sensor_type: data.sensor_type.clone(),
+            value: data.reading.value.parse().unwrap_or(0.0),
+            unit: data.unit.clone(),
+            timestamp: data.reading.timestamp.to_rfc3339(),
+            metadata: data.reading.metadata.clone(),
+        }
+    }
+}
+
+/// Cihaz token'ını her istekte gönderen HTTP client (`DEVICE_TOKEN`)
+///
+/// Token yoksa (API server'da `AUTH_ENABLED=false`) header eklenmez.
+/// Header hassas işaretlenir; loglarda görünmez.
+pub fn api_client(device_token: Option<&str>) -> anyhow::Result<reqwest::Client> {
+    let mut headers = HeaderMap::new();
+    if let Some(token) = device_token.filter(|t| !t.is_empty()) {
+        let mut value = HeaderValue::from_str(&format!("Bearer {token}"))
+            .map_err(|_| anyhow::anyhow!("DEVICE_TOKEN contains characters not allowed in a header"))?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+    Ok(reqwest::Client::builder().default_headers(headers).build()?)
+}
+
+/// Okumayı API server'a gönder
+///
+/// Hatalar sadece loglanır; sonraki tick yeni okumayla devam eder. Token
+/// reddi (401/403) her okumada hata olarak loglanır.
+pub async fn post_reading(client: &reqwest::Client, api_url: &str, data: &ApiSensorData, trace_id: Uuid) {
+    let url = format!("{}/api/sensors", api_url.trim_end_matches('/'));
+    match client
+        .post(&url)
+        .header(REQUEST_ID_HEADER, trace_id.to_string())
+        .json(data)
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => {
+            info!(%trace_id, "📤 Posted {} reading to {}", data.sensor_type, url);
+        }
+        Ok(response) if matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+            error!(%trace_id, "API server refused {} reading: {} (check DEVICE_TOKEN)", data.sensor_type, response.status());
+        }
+        Ok(response) => warn!(%trace_id, "API server rejected {} reading: {}", data.sensor_type, response.status()),
+        Err(e) => warn!(%trace_id, "Failed to post to {}: {}", url, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use shared_types::sensor::SensorReading;
+
+    #[test]
+    fn test_from_reading_matches_gateway_format() {
+        let device_id = Uuid::new_v4();
+        let data = SensorData {
+            reading: SensorReading {
+                sensor_id: Uuid::new_v4(),
+                value: "22.50".into(),
+                timestamp: Utc.with_ymd_and_hms(2024, 1, 20, 10, 30, 0).unwrap(),
+                is_valid: true,
+                metadata: None,
+            },
+            sensor_type: "temperature".into(),
+            unit: "°C".into(),
+            statistics: None,
+        };
+
+        let api = ApiSensorData::from_reading(device_id, &data);
+        assert_eq!(api.device_id, device_id.to_string());
+        assert_eq!(api.value, 22.5);
+        assert_eq!(api.timestamp, "2024-01-20T10:30:00+00:00");
+        assert!(serde_json::to_value(&api).unwrap().get("metadata").is_none());
+    }
+}
@@ -0,0 +1,171 @@
+//! Canlılık Durumu ve Last Will
+//!
+//! Cihaz durum topic'ine (`devices/{id}/status`) iki tür `DeviceStatusMessage`
+//! gider:
+//! - Çevrimiçi: her bağlantı kurulduğunda ve `WILL_INTERVAL_SECS` aralıkla
+//!   (sensör döngüsünde kontrol edilir; okuma aralığından sık olamaz)
+//! - Çevrimdışı (LWT): bağlantı beklenmedik şekilde koparsa broker yayınlar
+//!
+//! Çevrimiçi durumu cihazın uyguladığı runtime ayarlarını (`config`) taşır;
+//! ayarlar değişince aralık beklenmeden yeniden yayınlanır. Gateway bunları
+//! gölgenin `reported` bölümüne yazar ve `desired` ile olan delta kapanır.
+//!
+//! Will CONNECT paketinde gönderildiği için içindeki `uptime` bağlantı
+//! kurulurken dondurulur; event loop her yeniden bağlanmadan önce will'i
+//! güncel uptime ile yeniler.
+
+use rumqttc::{LastWill, QoS};
+use shared_types::conventions::status_topic;
+use shared_types::device::RemoteConfig;
+use shared_types::messages::{DeviceMessage, DeviceStatusMessage, MessagePriority};
+use shared_types::mqtt::{MqttClient, MqttError};
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Bağlantı beklenmedik şekilde koparsa broker'ın yayınladığı çevrimdışı mesajı
+///
+/// Gateway will mesajlarını diğer her şeyden önce işler (`Critical`).
+/// Retained değildir: cihaz yeniden bağlandığında eski "çevrimdışı" durumu
+/// gateway'e tekrar teslim edilmez.
+pub fn last_will(device_id: Uuid, uptime: u64) -> LastWill {
+    let message = DeviceMessage::device_status(device_id, &DeviceStatusMessage::offline(uptime))
+        .with_priority(MessagePriority::Critical);
+    LastWill::new(status_topic(device_id), serde_json::to_vec(&message).unwrap_or_default(), QoS::AtLeastOnce, false)
+}
+
+/// Çevrimiçi durumunu durum topic'ine gönder
+///
+/// `try_publish` kullanılır: bağlantı yokken sensör döngüsü bloklanmaz,
+/// kaçan durum bir sonraki aralıkta veya bağlanınca tekrar gönderilir.
+pub fn publish_status(client: &MqttClient, device_id: Uuid, uptime: u64, config: &RemoteConfig) -> Result<(), MqttError> {
+    client.try_publish(&status_topic(device_id), QoS::AtMostOnce, false, status_payload(device_id, uptime, config))
+}
+
+fn status_payload(device_id: Uuid, uptime: u64, config: &RemoteConfig) -> Vec<u8> {
+    let status = DeviceStatusMessage::online(uptime).with_config(config.clone());
+    serde_json::to_vec(&DeviceMessage::device_status(device_id, &status)).unwrap_or_default()
+}
+
+/// `WILL_INTERVAL_SECS` aralıkla (ve ayarlar değişince) çevrimiçi durumunu yayınlar
+pub struct KeepAlivePublisher {
+    device_id: Uuid,
+    /// 0: periyodik yayın kapalı
+    interval: Duration,
+    started_at: Instant,
+    last_will_update: Instant,
+    /// Son yayınlanan ayarlar
+    last_config: Option<RemoteConfig>,
+}
+
+impl KeepAlivePublisher {
+    /// `started_at`: uptime'ın başlangıcı (bağlanınca gönderilen durum da aynı saati kullanır)
+    ///
+    /// `config`: bağlanınca gönderilen durumdaki ayarlar
+    pub fn new(device_id: Uuid, interval: Duration, started_at: Instant, config: RemoteConfig) -> Self {
+        Self { device_id, interval, started_at, last_will_update: started_at, last_config: Some(config) }
+    }
+
+    pub fn uptime_secs(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.started_at).as_secs()
+    }
+
+    /// Son yayından bu yana aralık doldu mu?
+    pub fn due(&self, now: Instant) -> bool {
+        !self.interval.is_zero() && now.saturating_duration_since(self.last_will_update) >= self.interval
+    }
+
+    /// Aralık dolduysa veya ayarlar değiştiyse durumu yayınla; yayınlandıysa `true`
+    ///
+    /// Publish başarısız olsa da sayaç sıfırlanır (bağlantı yokken her
+    /// okumada yeniden denenmez); ayar değişikliği ise başarılı olana kadar
+    /// tekrar denenir.
+    pub fn tick(&mut self, client: &MqttClient, now: Instant, config: &RemoteConfig) -> bool {
+        let config_changed = self.last_config.as_ref() != Some(config);
+        if !config_changed && !self.due(now) {
+            return false;
+        }
+        self.last_will_update = now;
+        match publish_status(client, self.device_id, self.uptime_secs(now), config) {
+            Ok(()) => {
+                self.last_config = Some(config.clone());
+                true
+            }
+            Err(e) => {
+                tracing::debug!("Keep-alive status not published: {}", e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::messages::DeviceCommandKind;
+    use shared_types::mqtt::{self, ConnectOptions, MqttEventLoop, MqttProtocol, SessionOptions};
+
+    fn client() -> (MqttClient, MqttEventLoop) {
+        let options = ConnectOptions {
+            client_id: "edge-test".into(),
+            host: "localhost".into(),
+            port: 1883,
+            session: SessionOptions::default(),
+            last_will: None,
+        };
+        // Event loop çalışmaz; publish'ler kanalda bekler
+        mqtt::connect(options, MqttProtocol::V4)
+    }
+
+    #[test]
+    fn test_will_and_status_share_message_type() {
+        let device_id = Uuid::new_v4();
+        let will = last_will(device_id, 120);
+        assert_eq!((will.topic.as_str(), will.qos, will.retain), (status_topic(device_id).as_str(), QoS::AtLeastOnce, false));
+
+        let offline: DeviceMessage = serde_json::from_slice(&will.message).unwrap();
+        assert_eq!((offline.device_id, offline.command), (device_id, DeviceCommandKind::StatusUpdate));
+        assert_eq!(offline.priority, MessagePriority::Critical);
+        assert_eq!(serde_json::from_value::<DeviceStatusMessage>(offline.data).unwrap(), DeviceStatusMessage::offline(120));
+
+        let config = RemoteConfig { sensor_interval_secs: Some(5), ..RemoteConfig::default() };
+        let online: DeviceMessage = serde_json::from_slice(&status_payload(device_id, 120, &config)).unwrap();
+        assert_eq!(online.command, DeviceCommandKind::StatusUpdate);
+        assert_eq!(
+            serde_json::from_value::<DeviceStatusMessage>(online.data).unwrap(),
+            DeviceStatusMessage::online(120).with_config(config)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_publishes_once_per_interval() {
+        let (client, _eventloop) = client();
+        let start = Instant::now();
+        let config = RemoteConfig::default();
+        let mut keepalive = KeepAlivePublisher::new(Uuid::new_v4(), Duration::from_secs(30), start, config.clone());
+
+        assert!(!keepalive.tick(&client, start + Duration::from_secs(29), &config));
+        assert!(keepalive.tick(&client, start + Duration::from_secs(31), &config));
+        assert_eq!(keepalive.uptime_secs(start + Duration::from_secs(31)), 31);
+        // Aralık son yayından itibaren sayılır
+        assert!(!keepalive.tick(&client, start + Duration::from_secs(60), &config));
+        assert!(keepalive.tick(&client, start + Duration::from_secs(61), &config));
+    }
+
+    #[tokio::test]
+    async fn test_config_change_publishes_immediately() {
+        let (client, _eventloop) = client();
+        let start = Instant::now();
+        let mut keepalive = KeepAlivePublisher::new(Uuid::new_v4(), Duration::from_secs(30), start, RemoteConfig::default());
+
+        let changed = RemoteConfig { sensor_interval_secs: Some(10), ..RemoteConfig::default() };
+        assert!(keepalive.tick(&client, start + Duration::from_secs(1), &changed));
+        assert!(!keepalive.tick(&client, start + Duration::from_secs(2), &changed));
+    }
+
+    #[test]
+    fn test_zero_interval_disables() {
+        let start = Instant::now();
+        let keepalive = KeepAlivePublisher::new(Uuid::new_v4(), Duration::ZERO, start, RemoteConfig::default());
+        assert!(!keepalive.due(start + Duration::from_secs(3600)));
+    }
+}
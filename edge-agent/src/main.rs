@@ -2,78 +2,532 @@
 //!
 //! Raspberry Pi veya diğer edge cihazlarda çalışan IoT agent.
 //! - Mock sensörlerden veri okur (temperature, humidity, motion)
-//! - MQTT broker'a periyodik olarak veri gönderir
+//! - MQTT broker'a periyodik olarak veri gönderir (`SENSOR_TRANSPORT=http` ile doğrudan API server'a)
+//! - Okuma ve publish ayrı task'larda çalışır; yavaş broker okuma aralığını kaydırmaz
 //! - shared-types formatında mesaj üretir
+//! - `config_update` komutu veya SIGHUP ile runtime config'i yeniden yükler
+//! - Başlangıçta device shadow'daki `delta`'yı uygular
+//! - Sensör okumalarını kalibre eder (offset/scale, `calibrate` komutu)
+//! - Okumaları isteğe bağlı SMA/EMA ile yumuşatır (`TEMP_SMOOTHING`, `HUMIDITY_SMOOTHING`)
+//! - Başarısız komutları ve sürücü hatalarını `error_report` olarak bildirir
+//! - `take_photo` komutuyla veya hareket algılanınca fotoğraf çeker (Media kaydı)
+//! - `SIMULATE_DEVICES=N` ile tek process'te N sanal cihaz çalıştırır (yük testi)
 //! - Gerçek sensörler için rppal veya embedded-hal kullanılabilir
 
+mod acks;
+mod actuators;
+mod calibration;
+mod camera;
+mod commands;
 mod config;
+mod connection;
+mod discovery;
+mod error_report;
+mod http_transport;
+mod keepalive;
+mod provisioning;
+mod publish_queue;
+mod runtime_config;
 mod sensors;
+mod shadow;
+mod simulation;
+mod smoothing;
 
-use rumqttc::{AsyncClient, MqttOptions, QoS};
-use tokio::time::{interval, Duration};
-use tracing::{info, warn, error};
-use config::Config;
-use sensors::SensorController;
-use shared_types::messages::MqttMessage;
+use std::{path::PathBuf, sync::Arc};
+use rumqttc::{Event, Packet, QoS};
+use shared_types::mqtt::{self, ConnectOptions, ReadingProperties};
+use tokio::sync::RwLock;
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{info, warn, error, debug};
+use acks::{AckBackpressure, Backpressure, PendingAcks};
+use actuators::LedActuator;
+use camera::{Camera, MotionTrigger};
+use commands::CommandHandler;
+use error_report::ErrorReporter;
+use http_transport::ApiSensorData;
+use keepalive::KeepAlivePublisher;
+use config::{Config, SensorTransport};
+use connection::{BufferedMessage, ConnState, OfflineBuffer, Reconnect, Route, Transition};
+use publish_queue::{Enqueued, PUBLISH_QUEUE_CAPACITY};
+use runtime_config::{RuntimeConfig, RuntimeConfigPatch, SharedRuntimeConfig};
+use sensors::{filter_changed, AdaptiveSampler, SensorController, SensorReadingCache, SensorStatistics};
+use shared_types::messages::{DeviceCommand, DeviceMessage, MessagePriority, MqttMessage};
+use shared_types::{conventions, ErrorLevel, ErrorReport};
 use chrono::Utc;
+use uuid::Uuid;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // ========== 1. KONFIGURASYON ==========
-    let cfg = Config::load();
+    let mut cfg = Config::load()?;
 
     // ========== 2. LOGGING ==========
     tracing_subscriber::fmt()
         .with_env_filter(cfg.log_level.clone())
         .init();
 
+    // Geçersiz yumuşatma, Kalman veya sessizlik ayarı agent'ı başlatmaz
+    let mut smoothers = cfg.smoothers()?;
+    let temp_kalman = cfg.temp_kalman()?;
+    let max_silence = cfg.max_silence()?;
+
+    // ========== 2.2 SERVİS KEŞFİ (mDNS) ==========
+    // MQTT_BROKER_HOST / API_SERVER_URL ayarlanmamışsa yerel ağda aranır
+    cfg.discover_services().await;
+
+    // ========== 2.3 CİHAZ KAYDI ==========
+    // DEVICE_ID verilmemişse kayıtlı ID'yi kullan veya seri numarasıyla kayıt ol
+    // (simülasyonda sanal cihaz ID'leri seed'den türetilir, kayıt yapılmaz)
+    let mut initial_config = None;
+    if cfg.simulate_devices == 0 && !Config::device_id_configured() {
+        if let Some(provisioned) = provisioning::resolve(&cfg).await? {
+            cfg.device_id = provisioned.device_id;
+            // DEVICE_TOKEN verilmişse o öncelikli
+            cfg.device_token = cfg.device_token.take().or(provisioned.device_token);
+            initial_config = provisioned.initial_config;
+        }
+    }
+
     info!("🤖 Edge Agent starting...");
     info!("📱 Device: {} ({})", cfg.device_name, cfg.device_id);
     info!("📡 MQTT Broker: {}:{}", cfg.mqtt_broker_host, cfg.mqtt_broker_port);
-    info!("⏱️  Sensor interval: {} seconds", cfg.sensor_interval_secs);
+
+    // ========== 2.5 RUNTIME CONFIG ==========
+    // .env değerleri + diskte kalıcı hale getirilmiş komut override'ları
+    let overrides_path = PathBuf::from(&cfg.runtime_config_path);
+    let mut initial = RuntimeConfig::from_config(&cfg);
+    initial.apply_persisted(&overrides_path);
+    if let Some(remote) = &initial_config {
+        if let Err(e) = initial.apply(&RuntimeConfigPatch::from(remote)) {
+            warn!("Ignoring provisioned initial config: {}", e);
+        }
+    }
+    info!("⏱️  Sensor interval: {} seconds", initial.sensor_interval_secs);
+    let runtime: SharedRuntimeConfig = Arc::new(RwLock::new(initial));
 
     // ========== 3. MQTT CLIENT ==========
-    let client_id = format!("edge-{}", cfg.device_id);
-    let mut mqttoptions = MqttOptions::new(
+    // Simülasyonda tüm sanal cihazlar tek bağlantıyı paylaşır; client-id process'e özgü
+    let client_id = if cfg.simulate_devices > 0 {
+        format!("edge-sim-{}-{}", std::process::id(), cfg.device_id)
+    } else {
+        format!("edge-{}", cfg.device_id)
+    };
+    // Will'deki ve çevrimiçi durumundaki uptime buradan sayılır
+    let started_at = Instant::now();
+    let options = ConnectOptions {
         client_id,
-        cfg.mqtt_broker_host.clone(),
-        cfg.mqtt_broker_port
-    );
-    mqttoptions.set_keep_alive(Duration::from_secs(5));
-    mqttoptions.set_clean_session(true);
+        host: cfg.mqtt_broker_host.clone(),
+        port: cfg.mqtt_broker_port,
+        session: cfg.mqtt_session()?,
+        // Sanal cihazların ortak bağlantısı tek bir cihazın çevrimdışı olduğunu bildirmez
+        last_will: (cfg.simulate_devices == 0).then(|| keepalive::last_will(cfg.device_id, 0)),
+    };
+    info!("🔗 MQTT protocol: {}", cfg.mqtt_protocol);
 
-    let (client, mut eventloop) = AsyncClient::new(mqttoptions, 10);
+    let (client, mut eventloop) = mqtt::connect(options, cfg.mqtt_protocol);
+
+    // ========== 3.5 SİMÜLASYON MODU ==========
+    if cfg.simulate_devices > 0 {
+        return simulation::run(&cfg, client, eventloop, runtime).await;
+    }
 
     // ========== 4. SENSÖR CONTROLLER ==========
     let mut sensors = SensorController::new();
+    let mut statistics = SensorStatistics::new();
+    if let Some(filter) = temp_kalman {
+        sensors.temperature = sensors.temperature.with_kalman(filter);
+        info!("🔧 Temperature Kalman filter: Q={} R={}", filter.process_variance, filter.measurement_variance);
+    }
+    for (sensor_type, smoother) in &smoothers {
+        info!("🔧 {} smoothing: {:?}", sensor_type, smoother);
+    }
+    let mut cache = SensorReadingCache::new(cfg.sensor_change_threshold)
+        .with_max_silence(max_silence);
+    if let Some(deadband) = cfg.temp_deadband {
+        cache = cache.with_deadband("temperature", deadband);
+    }
+    if let Some(deadband) = cfg.humidity_deadband {
+        cache = cache.with_deadband("humidity", deadband);
+    }
     info!("🔧 Initialized {} mock sensors", 3);
 
     // ========== 5. EVENT LOOP ==========
     // MQTT connection handling task
+    // Komut topic'ine her ConnAck'te yeniden subscribe olunur (clean session)
+    // Hatalarda exponential backoff + jitter; bağlantı yokken okumalar buffer'a yazılır
+    // API server'a giden her istek cihaz token'ını taşır (DEVICE_TOKEN)
+    let api_client = http_transport::api_client(cfg.device_token.as_deref())?;
+    let camera = Arc::new(Camera::new(&cfg.api_server_url, &cfg.camera_capture_dir, cfg.device_id).with_client(api_client.clone()));
+    let handler = Arc::new(
+        CommandHandler::new(runtime.clone(), overrides_path.clone(), LedActuator::new(&cfg.led_pins))
+            .with_camera(camera.clone()),
+    );
+
+    // Cihaz kapalıyken gölgeye yazılan istenen durumu uygula
+    {
+        let handler = handler.clone();
+        let api_client = api_client.clone();
+        let api_url = cfg.api_server_url.clone();
+        let device_id = cfg.device_id;
+        tokio::spawn(async move {
+            shadow::sync_on_startup(&api_client, &api_url, device_id, &handler).await;
+        });
+    }
+
+    // QoS 1/2 publish'leri PubAck/PubComp gelene kadar bekleyen sayılır
+    let acks = PendingAcks::new();
+
+    let command_topic = conventions::command_topic(cfg.device_id);
+    let response_topic = conventions::response_topic(cfg.device_id);
+    let status_topic = conventions::status_topic(cfg.device_id);
+    let device_id = cfg.device_id;
+    let cmd_client = client.clone();
+    // Komut ve sürücü hataları durum topic'ine raporlanır (gateway → API server)
+    let reporter = ErrorReporter::new(client.clone(), cfg.device_id);
+    let cmd_reporter = reporter.clone();
+    let event_acks = acks.clone();
+    // Bağlantı durumu sensör döngüsüne watch kanalıyla iletilir
+    let (mut reconnect, conn_state) = Reconnect::new();
+    let status_runtime = runtime.clone();
     tokio::spawn(async move {
         loop {
-            match eventloop.poll().await {
+            let event = eventloop.poll().await.map(|(event, _)| event);
+            if let Ok(event) = &event {
+                event_acks.on_event(event);
+            }
+            match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    if reconnect.on_connected() == Some(Transition::Connected) {
+                        info!("🔌 MQTT connected");
+                        // Gateway cihazın tekrar çevrimiçi olduğunu shadow'a yazar
+                        match serde_json::to_vec(&DeviceMessage::heartbeat(device_id)) {
+                            Ok(json) => {
+                                if let Err(e) = cmd_client.try_publish(&status_topic, QoS::AtMostOnce, false, json) {
+                                    warn!("Failed to publish heartbeat: {}", e);
+                                }
+                            }
+                            Err(e) => error!("Failed to serialize heartbeat: {}", e),
+                        }
+                        let config = status_runtime.read().await.reported();
+                        if let Err(e) = keepalive::publish_status(&cmd_client, device_id, started_at.elapsed().as_secs(), &config) {
+                            warn!("Failed to publish online status: {}", e);
+                        }
+                    }
+                    if let Err(e) = cmd_client.try_subscribe(&command_topic, QoS::AtLeastOnce) {
+                        warn!("Failed to subscribe to {}: {}", command_topic, e);
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) if publish.topic == command_topic => {
+                    let cmd = match serde_json::from_slice::<DeviceCommand>(&publish.payload) {
+                        Ok(cmd) => cmd,
+                        Err(e) => {
+                            warn!("Invalid command payload: {}", e);
+                            continue;
+                        }
+                    };
+                    // Yanıt ayrı task'ta publish edilir; event loop bloklanmaz
+                    let handler = handler.clone();
+                    let client = cmd_client.clone();
+                    let topic = response_topic.clone();
+                    let acks = event_acks.clone();
+                    let reporter = cmd_reporter.clone();
+                    tokio::spawn(async move {
+                        let response = handler.handle(&cmd).await;
+                        if let Some(report) = error_report::command_failure(&cmd, &response) {
+                            reporter.report(report);
+                        }
+                        match serde_json::to_vec(&response) {
+                            Ok(json) => {
+                                acks.on_publish(QoS::AtLeastOnce);
+                                if let Err(e) = client.publish(&topic, QoS::AtLeastOnce, false, json).await {
+                                    acks.on_publish_failed(QoS::AtLeastOnce);
+                                    warn!("Failed to publish command response: {}", e);
+                                }
+                            }
+                            Err(e) => error!("Failed to serialize command response: {}", e),
+                        }
+                    });
+                }
                 Ok(_) => {},
                 Err(e) => {
-                    error!("MQTT connection error: {}", e);
-                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    let (transition, delay) = reconnect.on_error(connection::random_jitter());
+                    if transition == Some(Transition::Disconnected) {
+                        error!("MQTT connection lost: {} (buffering readings)", e);
+                    } else if reconnect.failures() == 1 {
+                        // Başlangıçta hiç bağlanamadı (geçiş yok ama bir kez bildir)
+                        warn!("MQTT broker unreachable: {} (buffering readings)", e);
+                    } else {
+                        debug!("MQTT reconnect attempt {} failed: {} (retry in {:?})", reconnect.failures(), e, delay);
+                    }
+                    // Sonraki CONNECT'in will'i güncel uptime'ı taşır
+                    eventloop.set_last_will(keepalive::last_will(device_id, started_at.elapsed().as_secs()));
+                    tokio::time::sleep(delay).await;
                 }
             }
         }
     });
 
+    // ========== 5.5 SIGHUP ==========
+    // .env dosyasını yeniden okur; diskteki komut override'ları üzerine tekrar uygulanır
+    #[cfg(unix)]
+    {
+        let runtime = runtime.clone();
+        let overrides_path = overrides_path.clone();
+        tokio::spawn(async move {
+            use tokio::signal::unix::{signal, SignalKind};
+            let mut hup = match signal(SignalKind::hangup()) {
+                Ok(hup) => hup,
+                Err(e) => {
+                    warn!("SIGHUP handler could not be installed: {}", e);
+                    return;
+                }
+            };
+            while hup.recv().await.is_some() {
+                let mut reloaded = match RuntimeConfig::reload_from_env() {
+                    Ok(reloaded) => reloaded,
+                    Err(e) => {
+                        error!("SIGHUP: config reload failed, keeping current runtime config: {}", e);
+                        continue;
+                    }
+                };
+                reloaded.apply_persisted(&overrides_path);
+                info!("🔄 SIGHUP: runtime config reloaded: {:?}", reloaded);
+                *runtime.write().await = reloaded;
+            }
+        });
+    }
+
+    // ========== 5.7 PUBLISHER ==========
+    // Sensör döngüsü okumaları kuyruğa yazar, bu task sırayla gönderir
+    // (kuyruk doluyken aynı sensörün bekleyen okuması yenisiyle değişir)
+    let (readings_tx, mut readings_rx) = publish_queue::channel(PUBLISH_QUEUE_CAPACITY);
+    let status_client = client.clone();
+    let status_conn = conn_state.clone();
+    {
+        let runtime = runtime.clone();
+        let acks = acks.clone();
+        let mut offline = OfflineBuffer::new(connection::OFFLINE_BUFFER_CAPACITY);
+        let device_id = cfg.device_id;
+        let device_name = cfg.device_name.clone();
+        let payload_format = cfg.payload_format;
+        let compress = cfg.mqtt_compress;
+        // Heartbeat aralığından eski okuma downstream için zaten bayat (v5 message expiry)
+        let staleness_budget = Duration::from_secs(cfg.max_silence_secs);
+        let transport = cfg.sensor_transport;
+        let api_url = cfg.api_server_url.clone();
+        let http_client = api_client.clone();
+        match transport {
+            SensorTransport::Mqtt => info!("📦 Payload format: {:?} (lz4: {})", payload_format, compress),
+            SensorTransport::Http => info!("📦 Posting readings to {}/api/sensors", api_url),
+        }
+        tokio::spawn(async move {
+            loop {
+                let data = readings_rx.recv().await;
+                let (message_qos, qos) = {
+                    let rt = runtime.read().await;
+                    (rt.qos, rt.mqtt_qos())
+                };
+
+                // HTTP transport: her okuma ayrı POST (gateway atlanır)
+                if transport == SensorTransport::Http {
+                    let body = ApiSensorData::from_reading(device_id, &data);
+                    http_transport::post_reading(&http_client, &api_url, &body, Uuid::new_v4()).await;
+                    continue;
+                }
+
+                // Bağlantı geri geldiyse önce biriken okumaları sırayla gönder
+                let state = *conn_state.borrow();
+                if state == ConnState::Connected && !offline.is_empty() {
+                    info!("📤 Flushing {} buffered readings", offline.len());
+                    for message in offline.drain() {
+                        acks.on_publish(message.qos);
+                        if let Err(e) = client.publish_reading(&message.topic, message.qos, message.payload, &message.properties, None).await {
+                            acks.on_publish_failed(message.qos);
+                            warn!("Failed to publish buffered reading to {}: {}", message.topic, e);
+                        }
+                    }
+                }
+
+                // Her sensör için ayrı MQTT mesajı gönder
+                let topic = payload_format.topic(&conventions::sensor_topic(&device_name, &data.sensor_type));
+
+                // MqttMessage formatında payload oluştur
+                // Her okuma kendi trace_id'sini taşır (gateway → api-server X-Request-Id)
+                let trace_id = Uuid::new_v4();
+                let message = MqttMessage {
+                    message_type: format!("{}_reading", data.sensor_type),
+                    payload: serde_json::to_value(&data.reading).unwrap_or_default(),
+                    timestamp: Utc::now(),
+                    device_id,
+                    qos: message_qos,
+                    trace_id: Some(trace_id),
+                    priority: MessagePriority::Normal,
+                };
+
+                // JSON veya CBOR olarak serialize et (MQTT_COMPRESS: LZ4 + `/lz4` topic)
+                let bytes = match payload_format.encode(&message) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        error!(%trace_id, "Failed to serialize message: {}", e);
+                        continue;
+                    }
+                };
+                let (topic, bytes) = if compress {
+                    config::compress_payload(topic, bytes)
+                } else {
+                    (topic, bytes)
+                };
+                let properties = ReadingProperties::new(device_id, data.sensor_type.as_str());
+                match connection::route(state) {
+                    // MQTT'ye publish et (v5: user property'ler + bayatlama süresi kadar expiry)
+                    Route::Publish => {
+                        acks.on_publish(qos);
+                        if let Err(e) = client.publish_reading(&topic, qos, bytes, &properties, Some(staleness_budget)).await {
+                            acks.on_publish_failed(qos);
+                            warn!(%trace_id, "Failed to publish to {}: {}", topic, e);
+                        } else {
+                            info!(%trace_id, "📤 Published to '{}'", topic);
+                        }
+                    }
+                    // Bağlantı yok: ölü client'a publish etme, buffer'a yaz
+                    Route::Buffer => {
+                        offline.push(BufferedMessage { topic, qos, payload: bytes, properties });
+                        debug!(%trace_id, "💾 Buffered reading ({} buffered, {} dropped)", offline.len(), offline.dropped());
+                    }
+                }
+            }
+        });
+    }
+
     // ========== 6. SENSOR DATA LOOP ==========
-    let mut timer = interval(Duration::from_secs(cfg.sensor_interval_secs));
-    let device_id = cfg.device_id;
-    let device_name = cfg.device_name.clone();
+    // Okuma aralığı değişim hızına göre adaptif olarak ayarlanır
+    let mut interval_secs = runtime.read().await.sensor_interval_secs;
+    let mut sampler = AdaptiveSampler::new(
+        interval_secs,
+        cfg.min_sensor_interval_secs,
+        cfg.max_sensor_interval_secs,
+        cfg.change_rate_threshold,
+    );
+    let mut last_read = Instant::now();
+    let mut keepalive = KeepAlivePublisher::new(
+        cfg.device_id,
+        Duration::from_secs(cfg.will_interval_secs),
+        started_at,
+        runtime.read().await.reported(),
+    );
+    let mut backpressure = AckBackpressure::new(cfg.max_pending_acks);
+    let mut motion_trigger = cfg
+        .motion_capture
+        .then(|| MotionTrigger::new(Duration::from_secs(cfg.motion_capture_cooldown_secs)));
+    info!("📈 Adaptive sampling: {}s..{}s (rate threshold: {}/s)",
+        sampler.min_interval_secs, sampler.max_interval_secs, sampler.change_rate_threshold
+    );
 
     info!("✅ Edge agent ready, starting sensor readings...");
 
     loop {
-        timer.tick().await;
+        sleep(Duration::from_secs(sampler.current_interval)).await;
+
+        // Runtime config'in güncel halini al
+        let rt = runtime.read().await.clone();
+        // Bağlantı yokken kuyruğa yazılmaz (yeniden bağlanınca zaten yayınlanır)
+        if *status_conn.borrow() == ConnState::Connected {
+            keepalive.tick(&status_client, Instant::now(), &rt.reported());
+        }
+        if rt.sensor_interval_secs != interval_secs {
+            info!("⏱️  Sensor interval changed: {}s → {}s", interval_secs, rt.sensor_interval_secs);
+            interval_secs = rt.sensor_interval_secs;
+            sampler.reset(interval_secs);
+        }
+
+        // Broker onayları birikmişse bu aralıkta okuma yapma
+        match backpressure.observe(acks.count(), Instant::now()) {
+            Backpressure::Clear => {}
+            Backpressure::Throttle => {
+                debug!("⏸️  {} publishes awaiting broker ack, skipping this reading", acks.count());
+                continue;
+            }
+            Backpressure::Stalled(for_how_long) => {
+                warn!("{} publishes awaiting broker ack for {:?} (max: {}), readings paused",
+                    acks.count(), for_how_long, cfg.max_pending_acks
+                );
+                continue;
+            }
+        }
 
-        // Tüm sensörlerden veri oku
-        let sensor_data = sensors.read_all();
+        // Aktif sensörlerden veri oku
+        let mut enabled: Vec<_> = sensors
+            .read_all()
+            .into_iter()
+            .filter(|data| rt.is_enabled(&data.sensor_type))
+            .collect();
+        // Ham değerleri kalibre et (ham değer metadata.raw_value'da kalır)
+        for data in &mut enabled {
+            if let Some(cal) = rt.calibration.get(&data.sensor_type) {
+                cal.apply_to(data);
+                let fault = format!("calibration:{}", data.sensor_type);
+                if data.reading.is_valid {
+                    reporter.clear_fault(&fault);
+                } else {
+                    warn!("Calibrated {} reading out of range, clamped to {}", data.sensor_type, data.reading.value);
+                    let report = ErrorReport::new(ErrorLevel::Warning, format!("calibrated {} reading out of range", data.sensor_type))
+                        .with_context(serde_json::json!({
+                            "source": "calibration",
+                            "sensor_type": data.sensor_type,
+                            "value": data.reading.value,
+                        }));
+                    reporter.report_fault(&fault, report);
+                }
+            }
+            // Deadband'den önce yumuşat (yumuşatılmamış değer metadata.raw_value'da kalır)
+            if let Some(smoother) = smoothers.get_mut(&data.sensor_type) {
+                smoother.apply_to(data);
+            }
+            // İstatistikler yayınlanacak (kalibre edilmiş ve yumuşatılmış) değerlerden
+            statistics.observe(data);
+        }
+        // Hareket algılandıysa (cooldown dolmuşsa) fotoğraf çek
+        if let Some(trigger) = &mut motion_trigger {
+            let motion = enabled.iter().find_map(|data| camera::is_motion(&data.sensor_type, &data.reading.value));
+            if motion.is_some_and(|motion| trigger.observe(motion, Instant::now())) {
+                let camera = camera.clone();
+                let reporter = reporter.clone();
+                tokio::spawn(async move {
+                    match camera.take_photo().await {
+                        Ok(_) => reporter.clear_fault("camera"),
+                        Err(e) => {
+                            warn!("Motion capture failed: {}", e);
+                            let report = ErrorReport::new(ErrorLevel::Error, format!("motion capture failed: {e}"))
+                                .with_context(serde_json::json!({ "source": "camera" }));
+                            reporter.report_fault("camera", report);
+                        }
+                    }
+                });
+            }
+        }
+
+        // Sayısal okumaların değişim hızına göre sonraki aralığı belirle
+        let elapsed_secs = last_read.elapsed().as_secs_f64();
+        last_read = Instant::now();
+        let previous_interval = sampler.current_interval;
+        let numeric = enabled
+            .iter()
+            .filter(|data| data.sensor_type != "motion")
+            .filter_map(|data| Some((data.sensor_type.as_str(), data.reading.value.parse::<f64>().ok()?)));
+        let next_interval = sampler.observe(numeric, elapsed_secs);
+        if next_interval != previous_interval {
+            debug!("📈 Sampling interval: {}s → {}s", previous_interval, next_interval);
+        }
+
+        // Deadband içinde kalan okumaları atla
+        let read_count = enabled.len();
+        let sensor_data = filter_changed(enabled, &mut cache);
+        if sensor_data.len() < read_count {
+            info!("🔇 Suppressed {} unchanged readings (total: {})",
+                read_count - sensor_data.len(),
+                cache.total_suppressed()
+            );
+        }
         
         info!("📊 Read {} sensor values:", sensor_data.len());
         for data in &sensor_data {
@@ -83,36 +537,23 @@ async fn main() -> anyhow::Result<()> {
                 data.reading.value, 
                 data.unit
             );
+            // Son STATS_WINDOW okumanın istatistikleri (pencere dolunca)
+            if let Some(stats) = &data.statistics {
+                debug!("     last {}: min {:?} max {:?} mean {:?} σ {:?}",
+                    stats.count, stats.min, stats.max, stats.mean, stats.std_dev
+                );
+            }
         }
 
-        // Her sensör için ayrı MQTT mesajı gönder
+        // Okumalar publisher task'ına verilir; yavaş broker sonraki okumayı geciktirmez
         for data in sensor_data {
-            let topic = format!("sensors/{}/{}", device_name, data.sensor_type);
-            
-            // MqttMessage formatında payload oluştur
-            let message = MqttMessage {
-                message_type: format!("{}_reading", data.sensor_type),
-                payload: serde_json::to_value(&data.reading).unwrap_or_default(),
-                timestamp: Utc::now(),
-                device_id,
-                qos: 0,
-            };
-
-            // JSON serialize et
-            match serde_json::to_string(&message) {
-                Ok(json) => {
-                    // MQTT'ye publish et
-                    if let Err(e) = client.publish(&topic, QoS::AtMostOnce, false, json.as_bytes()).await {
-                        warn!("Failed to publish to {}: {}", topic, e);
-                    } else {
-                        info!("📤 Published to '{}'", topic);
-                    }
-                }
-                Err(e) => {
-                    error!("Failed to serialize message: {}", e);
-                }
+            if readings_tx.send(data) == Enqueued::DroppedOldest {
+                debug!("📬 Publish queue full, dropped oldest reading");
             }
         }
+        info!("📬 Publish queue: {} pending ({} replaced, {} dropped)",
+            readings_tx.depth(), readings_tx.replaced(), readings_tx.dropped()
+        );
 
         info!("---");
     }
@@ -0,0 +1,273 @@
+//! Cihaz Kaydı (Provisioning)
+//!
+//! `DEVICE_ID` verilmemişse agent ilk açılışta API server'a seri
+//! numarasıyla kayıt olur (`POST /v1/provision`); dönen ID'yi
+//! `DEVICE_ID_FILE`'a, cihaz token'ını `DEVICE_TOKEN_FILE`'a (sadece sahibi
+//! okuyabilir, 0600) yazar. Sonraki açılışlarda ikisi de dosyadan okunur;
+//! token dosyası yoksa yeniden kayıt olunur (sunucu aynı seri numarasına
+//! aynı ID'yi ve yeni bir token döner).
+//!
+//! API server'da `AUTH_ENABLED=true` iken kayıt isteği `PROVISIONING_TOKEN`
+//! ile gönderilir; token reddedilirse agent başlamaz.
+
+use std::io::Write;
+use std::path::Path;
+
+use reqwest::StatusCode;
+use shared_types::{DeviceProvisioningRequest, DeviceProvisioningResponse, RemoteConfig};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::Config;
+
+/// Raspberry Pi'da seri numarası ve modelin okunduğu dosya
+const CPUINFO_PATH: &str = "/proc/cpuinfo";
+
+/// Kayıt sonucu
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provisioned {
+    pub device_id: Uuid,
+    /// API isteklerinde gönderilecek cihaz token'ı (kayıtlı değilse `None`)
+    pub device_token: Option<String>,
+    /// Sunucunun verdiği başlangıç ayarları (dosyadan okunduysa `None`)
+    pub initial_config: Option<RemoteConfig>,
+}
+
+/// Cihaz ID'sini ve token'ını belirle: önce kayıtlı dosyalar, yoksa sunucuya kayıt
+///
+/// Seri numarası bulunamazsa veya sunucuya ulaşılamazsa kayıtlı ID (token'sız)
+/// veya `Ok(None)` döner (uyarı loglanır); ikincisinde agent rastgele ID ile
+/// devam eder. Sunucu kaydı yetkisiz sayarsa (401/403) hata döner: token'sız
+/// bir cihaz hiçbir isteği gönderemezdi.
+pub async fn resolve(cfg: &Config) -> anyhow::Result<Option<Provisioned>> {
+    let (id_path, token_path) = (Path::new(&cfg.device_id_file), Path::new(&cfg.device_token_file));
+    let stored_id = load_device_id(id_path);
+    if let Some(device_id) = stored_id {
+        if let Some(device_token) = load_device_token(token_path) {
+            return Ok(Some(Provisioned { device_id, device_token: Some(device_token), initial_config: None }));
+        }
+        info!("No device token in {}, provisioning again", token_path.display());
+    }
+    // Kayıt yapılamazsa: kayıtlı ID token'sız, o da yoksa rastgele ID
+    let fallback = stored_id.map(|device_id| Provisioned { device_id, device_token: None, initial_config: None });
+
+    let cpuinfo = std::fs::read_to_string(CPUINFO_PATH).unwrap_or_default();
+    let Some(serial_number) = cfg.device_serial.clone().or_else(|| parse_cpuinfo(&cpuinfo, "Serial")) else {
+        if fallback.is_none() {
+            warn!("No DEVICE_ID and no device serial (DEVICE_SERIAL or {}); using a random device id", CPUINFO_PATH);
+        }
+        return Ok(fallback);
+    };
+    let request = DeviceProvisioningRequest {
+        serial_number,
+        firmware_version: env!("CARGO_PKG_VERSION").to_string(),
+        model: parse_cpuinfo(&cpuinfo, "Model").unwrap_or_else(|| "unknown".into()),
+        public_key_pem: None,
+    };
+
+    let response = match provision(&cfg.api_server_url, cfg.provisioning_token.as_deref(), &request).await {
+        Ok(response) => response,
+        Err(e) if e.status().is_some_and(|s| s == StatusCode::UNAUTHORIZED || s == StatusCode::FORBIDDEN) => {
+            anyhow::bail!("API server refused device provisioning: {e} (check PROVISIONING_TOKEN)");
+        }
+        Err(e) => {
+            warn!("Device provisioning failed: {} (continuing without a device token)", e);
+            return Ok(fallback);
+        }
+    };
+    info!("🆔 Provisioned as {} (serial {})", response.device_id, request.serial_number);
+    if let Err(e) = store_device_id(id_path, response.device_id) {
+        warn!("Failed to store device id in {}: {}", id_path.display(), e);
+    }
+    if let Err(e) = store_device_token(token_path, &response.token) {
+        warn!("Failed to store device token in {}: {}", token_path.display(), e);
+    }
+    Ok(Some(Provisioned {
+        device_id: response.device_id,
+        device_token: Some(response.token),
+        initial_config: Some(response.initial_config),
+    }))
+}
+
+/// `POST {api_url}/v1/provision` (`token` varsa `Authorization: Bearer`)
+async fn provision(
+    api_url: &str,
+    token: Option<&str>,
+    request: &DeviceProvisioningRequest,
+) -> reqwest::Result<DeviceProvisioningResponse> {
+    let url = format!("{}/v1/provision", api_url.trim_end_matches('/'));
+    let mut builder = reqwest::Client::new().post(url).json(request);
+    if let Some(token) = token.filter(|t| !t.is_empty()) {
+        builder = builder.bearer_auth(token);
+    }
+    builder.send().await?.error_for_status()?.json().await
+}
+
+/// `/proc/cpuinfo`'dan `key : value` satırının değeri
+///
+/// Raspberry Pi'da `Serial` ve `Model` satırları bulunur.
+pub fn parse_cpuinfo(cpuinfo: &str, key: &str) -> Option<String> {
+    cpuinfo
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Kayıtlı device ID'yi oku (dosya yoksa veya geçersizse `None`)
+pub fn load_device_id(path: &Path) -> Option<Uuid> {
+    let raw = std::fs::read_to_string(path).ok()?;
+    match raw.trim().parse() {
+        Ok(id) => Some(id),
+        Err(e) => {
+            warn!("Ignoring invalid device id file {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Device ID'yi dosyaya yaz
+pub fn store_device_id(path: &Path, device_id: Uuid) -> std::io::Result<()> {
+    std::fs::write(path, format!("{device_id}\n"))
+}
+
+/// Kayıtlı cihaz token'ını oku (dosya yoksa veya boşsa `None`)
+pub fn load_device_token(path: &Path) -> Option<String> {
+    let raw = std::fs::read_to_string(path).ok()?;
+    Some(raw.trim().to_string()).filter(|token| !token.is_empty())
+}
+
+/// Cihaz token'ını sadece sahibinin okuyabileceği (0600) dosyaya yaz
+pub fn store_device_token(path: &Path, token: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // `mode` sadece yeni dosyada uygulanır; var olan dosyanın izinleri de daraltılır
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    writeln!(file, "{token}")?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        http::{header::AUTHORIZATION, HeaderMap},
+        routing::post,
+        Json, Router,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    const CPUINFO: &str = "processor\t: 0\nBogoMIPS\t: 108.00\n\nHardware\t: BCM2835\nRevision\t: c03114\nSerial\t\t: 10000000abcdef01\nModel\t\t: Raspberry Pi 4 Model B Rev 1.4\n";
+
+    #[test]
+    fn test_parse_cpuinfo_serial_and_model() {
+        assert_eq!(parse_cpuinfo(CPUINFO, "Serial").as_deref(), Some("10000000abcdef01"));
+        assert_eq!(parse_cpuinfo(CPUINFO, "Model").as_deref(), Some("Raspberry Pi 4 Model B Rev 1.4"));
+        // x86 cpuinfo'da Serial yok
+        assert_eq!(parse_cpuinfo("processor\t: 0\nmodel name\t: Intel\n", "Serial"), None);
+    }
+
+    /// Her kayıtta sayacı artıran ve sabit ID dönen sahte API server
+    ///
+    /// `PROVISIONING_TOKEN`'ı (`provisioning-token`) bekler, yoksa 401 döner.
+    async fn fake_api(device_id: Uuid) -> (String, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/v1/provision",
+            post(move |headers: HeaderMap, Json(request): Json<DeviceProvisioningRequest>| async move {
+                if headers.get(AUTHORIZATION).is_none_or(|v| v != "Bearer provisioning-token") {
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
+                assert_eq!(request.serial_number, "10000000abcdef01");
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(Json(DeviceProvisioningResponse {
+                    device_id,
+                    mqtt_broker: "localhost".into(),
+                    mqtt_port: 1883,
+                    initial_config: RemoteConfig { sensor_interval_secs: Some(10), ..RemoteConfig::default() },
+                    token: "rfd_token".into(),
+                    token_expires_at: None,
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, calls)
+    }
+
+    #[tokio::test]
+    async fn test_provisioned_id_and_token_stored_and_reused() {
+        let device_id = Uuid::new_v4();
+        let (api_server_url, calls) = fake_api(device_id).await;
+        let dir = std::env::temp_dir().join(format!("provisioning-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (path, token_path) = (dir.join("device_id"), dir.join("device_token"));
+        let cfg = Config {
+            api_server_url,
+            device_serial: Some("10000000abcdef01".into()),
+            device_id_file: path.to_string_lossy().into_owned(),
+            device_token_file: token_path.to_string_lossy().into_owned(),
+            provisioning_token: Some("provisioning-token".into()),
+            ..Config::default()
+        };
+
+        let first = resolve(&cfg).await.unwrap().unwrap();
+        assert_eq!((first.device_id, first.device_token.as_deref()), (device_id, Some("rfd_token")));
+        assert_eq!(first.initial_config.unwrap().sensor_interval_secs, Some(10));
+        assert_eq!(load_device_id(&path), Some(device_id));
+        assert_eq!(load_device_token(&token_path).as_deref(), Some("rfd_token"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&token_path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        // İkinci açılış: sunucuya gidilmez
+        let again = resolve(&cfg).await.unwrap().unwrap();
+        let stored = Provisioned { device_id, device_token: Some("rfd_token".into()), initial_config: None };
+        assert_eq!(again, stored);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Token dosyası kaybolduysa yeniden kayıt olunur (aynı ID)
+        std::fs::remove_file(&token_path).unwrap();
+        let reprovisioned = resolve(&cfg).await.unwrap().unwrap();
+        assert_eq!((reprovisioned.device_id, reprovisioned.device_token), (device_id, Some("rfd_token".into())));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        std::fs::write(&path, "not-a-uuid").unwrap();
+        assert_eq!(load_device_id(&path), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejected_provisioning_token_is_an_error() {
+        let (api_server_url, calls) = fake_api(Uuid::new_v4()).await;
+        let path = std::env::temp_dir().join(format!("device-id-{}", Uuid::new_v4()));
+        let token_path = std::env::temp_dir().join(format!("device-token-{}", Uuid::new_v4()));
+        let cfg = Config {
+            api_server_url,
+            device_serial: Some("10000000abcdef01".into()),
+            device_id_file: path.to_string_lossy().into_owned(),
+            device_token_file: token_path.to_string_lossy().into_owned(),
+            ..Config::default()
+        };
+
+        for token in [None, Some("wrong-token".to_string())] {
+            let cfg = Config { provisioning_token: token, ..cfg.clone() };
+            let err = resolve(&cfg).await.unwrap_err();
+            assert!(err.to_string().contains("PROVISIONING_TOKEN"), "{err}");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert!(!path.exists() && !token_path.exists());
+    }
+}
@@ -0,0 +1,216 @@
+//! Okuma → Publish Kuyruğu
+//!
+//! Sensör döngüsü okumaları sınırlı bir kuyruğa yazar; ayrı bir publisher
+//! task'ı kuyruğu boşaltıp MQTT'ye (veya `SENSOR_TRANSPORT=http` ile API
+//! server'a) gönderir. Yavaş broker okuma zamanlayıcısını geciktirmez.
+//!
+//! Kuyruk doluyken yeni okuma beklemez: aynı sensörün kuyruktaki son
+//! okuması yenisiyle değiştirilir (keep-latest). O sensörün kuyrukta okuması
+//! yoksa en eski okuma atılır. `tokio::sync::mpsc` kuyruktaki bir öğenin
+//! değiştirilmesine izin vermediği için kanal `Mutex<VecDeque>` + `Notify`
+//! ile yazılmıştır.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::Notify;
+
+use crate::sensors::SensorData;
+
+/// Kuyruktaki maksimum okuma (birkaç dakikalık okuma; sonrası keep-latest)
+pub const PUBLISH_QUEUE_CAPACITY: usize = 64;
+
+/// `send` sonucu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enqueued {
+    /// Kuyruğun sonuna eklendi
+    Queued,
+    /// Kuyruk dolu; aynı sensörün kuyruktaki okuması değiştirildi
+    Replaced,
+    /// Kuyruk dolu ve sensörün okuması yok; en eski okuma atıldı
+    DroppedOldest,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    readings: VecDeque<SensorData>,
+    replaced: u64,
+    dropped: u64,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<QueueState>,
+    notify: Notify,
+    capacity: usize,
+}
+
+impl Shared {
+    fn state(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        // Kilit altında panic olacak kod yok; zehirlenmiş kilit de kullanılabilir
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Sınırlı, keep-latest okuma kanalı oluştur (kapasite en az 1)
+pub fn channel(capacity: usize) -> (ReadingSender, ReadingReceiver) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(QueueState::default()),
+        notify: Notify::new(),
+        capacity: capacity.max(1),
+    });
+    (ReadingSender { shared: shared.clone() }, ReadingReceiver { shared })
+}
+
+/// Sensör döngüsü tarafı; `send` hiçbir zaman beklemez
+#[derive(Debug, Clone)]
+pub struct ReadingSender {
+    shared: Arc<Shared>,
+}
+
+impl ReadingSender {
+    /// Okumayı kuyruğa yaz
+    pub fn send(&self, data: SensorData) -> Enqueued {
+        let outcome = {
+            let mut state = self.shared.state();
+            if state.readings.len() < self.shared.capacity {
+                state.readings.push_back(data);
+                Enqueued::Queued
+            } else if let Some(queued) = state.readings.iter_mut().rev().find(|q| q.sensor_type == data.sensor_type) {
+                // Sensörün en yeni okuması değişir; sensör içi sıra korunur
+                *queued = data;
+                state.replaced += 1;
+                Enqueued::Replaced
+            } else {
+                state.readings.pop_front();
+                state.readings.push_back(data);
+                state.dropped += 1;
+                Enqueued::DroppedOldest
+            }
+        };
+        self.shared.notify.notify_one();
+        outcome
+    }
+
+    /// Kuyrukta bekleyen okuma sayısı
+    pub fn depth(&self) -> usize {
+        self.shared.state().readings.len()
+    }
+
+    /// Yenisiyle değiştirilen okuma sayısı (toplam)
+    pub fn replaced(&self) -> u64 {
+        self.shared.state().replaced
+    }
+
+    /// Kuyruk dolu olduğu için atılan okuma sayısı (toplam)
+    pub fn dropped(&self) -> u64 {
+        self.shared.state().dropped
+    }
+}
+
+/// Publisher task'ı tarafı
+#[derive(Debug)]
+pub struct ReadingReceiver {
+    shared: Arc<Shared>,
+}
+
+impl ReadingReceiver {
+    /// Sıradaki okumayı al; kuyruk boşsa yeni okuma gelene kadar bekle
+    pub async fn recv(&mut self) -> SensorData {
+        loop {
+            if let Some(data) = self.shared.state().readings.pop_front() {
+                return data;
+            }
+            // `notify_one` bekleyen yoksa izni saklar; kaçan bildirim olmaz
+            self.shared.notify.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::SensorReading;
+    use tokio::time::{sleep, Duration, Instant};
+
+    fn reading(sensor_type: &str, value: f64) -> SensorData {
+        SensorData {
+            reading: SensorReading::new(uuid::Uuid::new_v4(), value.to_string()),
+            sensor_type: sensor_type.into(),
+            unit: String::new(),
+            statistics: None,
+        }
+    }
+
+    fn queued(shared: &Shared) -> Vec<(String, String)> {
+        let state = shared.state();
+        state.readings.iter().map(|d| (d.sensor_type.clone(), d.reading.value.clone())).collect()
+    }
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(t, v)| (t.to_string(), v.to_string())).collect()
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_keeps_latest_reading_per_sensor() {
+        let (tx, mut rx) = channel(3);
+        assert_eq!(tx.send(reading("temperature", 20.0)), Enqueued::Queued);
+        assert_eq!(tx.send(reading("humidity", 50.0)), Enqueued::Queued);
+        assert_eq!(tx.send(reading("temperature", 21.0)), Enqueued::Queued);
+
+        // Dolu: sıcaklığın en yeni okuması değişir, sıra korunur
+        assert_eq!(tx.send(reading("temperature", 22.0)), Enqueued::Replaced);
+        assert_eq!(tx.send(reading("humidity", 51.0)), Enqueued::Replaced);
+        assert_eq!(queued(&rx.shared), pairs(&[("temperature", "20"), ("humidity", "51"), ("temperature", "22")]));
+        assert_eq!((tx.depth(), tx.replaced(), tx.dropped()), (3, 2, 0));
+
+        // Kuyrukta okuması olmayan sensör en eskiyi iter
+        assert_eq!(tx.send(reading("motion", 1.0)), Enqueued::DroppedOldest);
+        assert_eq!(queued(&rx.shared), pairs(&[("humidity", "51"), ("temperature", "22"), ("motion", "1")]));
+        assert_eq!(tx.dropped(), 1);
+
+        assert_eq!(rx.recv().await.reading.value, "51");
+        assert_eq!(tx.depth(), 2);
+        assert_eq!(tx.send(reading("humidity", 52.0)), Enqueued::Queued);
+    }
+
+    #[tokio::test]
+    async fn test_recv_waits_for_next_reading() {
+        let (tx, mut rx) = channel(0);
+        let publisher = tokio::spawn(async move { rx.recv().await.sensor_type });
+        tokio::task::yield_now().await;
+        tx.send(reading("temperature", 20.0));
+        assert_eq!(publisher.await.unwrap(), "temperature");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stalled_publisher_does_not_delay_readings() {
+        let (tx, mut rx) = channel(4);
+        // İlk okumayı alıp publish'te takılan publisher
+        tokio::spawn(async move {
+            let _first = rx.recv().await;
+            sleep(Duration::from_secs(3600)).await;
+        });
+
+        let interval = Duration::from_secs(5);
+        let start = Instant::now();
+        let mut ticks = Vec::new();
+        for i in 0..20 {
+            sleep(interval).await;
+            ticks.push(start.elapsed());
+            for sensor_type in ["temperature", "humidity"] {
+                tx.send(reading(sensor_type, f64::from(i)));
+            }
+            tokio::task::yield_now().await;
+        }
+
+        let expected: Vec<_> = (1..=20).map(|i| interval * i).collect();
+        assert_eq!(ticks, expected);
+        // Takılmadan önceki ilk okumalar ve her sensörün son okuması kuyrukta
+        assert_eq!(
+            queued(&tx.shared),
+            pairs(&[("humidity", "0"), ("temperature", "1"), ("humidity", "19"), ("temperature", "19")])
+        );
+        assert_eq!((tx.replaced(), tx.dropped()), (35, 0));
+    }
+}
@@ -0,0 +1,382 @@
+//! Runtime Yapılandırması (Hot-Reload)
+//!
+//! Agent yeniden başlatılmadan değiştirilebilen ayarlar.
+//! İki yoldan güncellenir:
+//! - `config_update` DeviceCommand'ı (uzaktan, MQTT üzerinden)
+//! - SIGHUP sinyali (.env dosyası yeniden okunur)
+//!
+//! Komutla uygulanan override'lar diske yazılır; yeniden başlatmada ve
+//! SIGHUP'ta .env değerlerinin üzerine tekrar uygulanır.
+
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use rumqttc::QoS;
+use serde::{Deserialize, Serialize};
+use shared_types::{Error, RemoteConfig, Result};
+use tokio::sync::RwLock;
+
+use crate::calibration::Calibration;
+use crate::config::Config;
+
+/// Bilinen sensör tipleri
+pub const KNOWN_SENSORS: &[&str] = &["temperature", "humidity", "motion"];
+
+/// Runtime'da değiştirilemeyen alanlar
+///
+/// Bu alanlar bağlantı kimliğini belirler; değişmeleri yeniden başlatma gerektirir.
+const IMMUTABLE_FIELDS: &[&str] = &[
+    "device_id",
+    "device_name",
+    "mqtt_broker_host",
+    "mqtt_broker_port",
+];
+
+/// Sensör task'ları tarafından paylaşılan runtime config
+pub type SharedRuntimeConfig = Arc<RwLock<RuntimeConfig>>;
+
+/// Runtime'da değiştirilebilen ayarlar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    /// Sensör okuma aralığı (saniye)
+    pub sensor_interval_secs: u64,
+
+    /// Aktif sensör tipleri
+    pub enabled_sensors: Vec<String>,
+
+    /// Sensör mesajları için MQTT QoS seviyesi
+    pub qos: u8,
+
+    /// Sensör tipi → kalibrasyon (yoksa ham değer yayınlanır)
+    #[serde(default)]
+    pub calibration: BTreeMap<String, Calibration>,
+}
+
+/// Kısmi runtime config güncellemesi
+///
+/// `config_update` komutunun `parameters` alanı bu formattadır:
+/// ```json
+/// {
+///   "sensor_interval_secs": 10,
+///   "enabled_sensors": ["temperature", "motion"],
+///   "qos": 1,
+///   "calibration": {"temperature": {"offset": -1.5, "scale": 1.02}}
+/// }
+/// ```
+///
+/// `calibration` sensör bazında birleştirilir: verilen sensörün kalibrasyonu
+/// tamamen değiştirilir, diğer sensörlerinki korunur.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuntimeConfigPatch {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensor_interval_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled_sensors: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qos: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<BTreeMap<String, Calibration>>,
+}
+
+impl RuntimeConfig {
+    /// Başlangıç yapılandırmasından runtime config oluştur
+    pub fn from_config(cfg: &Config) -> Self {
+        Self {
+            sensor_interval_secs: cfg.sensor_interval_secs,
+            enabled_sensors: cfg.parse_enabled_sensors(),
+            qos: cfg.mqtt_qos.min(2),
+            calibration: cfg.parse_calibration(),
+        }
+    }
+
+    /// .env dosyasını yeniden okuyarak runtime config oluştur (SIGHUP)
+    ///
+    /// `dotenv_override` kullanılır, böylece dosyadaki yeni değerler
+    /// process'in mevcut ortam değişkenlerinin üzerine yazılır.
+    /// Config dosyası okunamazsa hata döner.
+    pub fn reload_from_env() -> shared_types::Result<Self> {
+        let _ = dotenvy::dotenv_override();
+        Ok(Self::from_config(&Config::load()?))
+    }
+
+    /// Diskteki komut override'larını uygula (başlangıç ve SIGHUP)
+    ///
+    /// Dosya yoksa bir şey yapmaz. Geçersiz override'lar uyarıyla atlanır.
+    pub fn apply_persisted(&mut self, path: &Path) {
+        let Some(overrides) = RuntimeConfigPatch::load(path) else {
+            return;
+        };
+        match self.apply(&overrides) {
+            Ok(()) => tracing::info!("♻️  Restored runtime overrides from {}", path.display()),
+            Err(e) => tracing::warn!("Ignoring persisted runtime overrides: {}", e),
+        }
+    }
+
+    /// Patch'i doğrula ve uygula
+    ///
+    /// Doğrulama başarısız olursa config değişmez.
+    pub fn apply(&mut self, patch: &RuntimeConfigPatch) -> Result<()> {
+        patch.validate()?;
+        if let Some(secs) = patch.sensor_interval_secs {
+            self.sensor_interval_secs = secs;
+        }
+        if let Some(sensors) = &patch.enabled_sensors {
+            self.enabled_sensors = sensors.clone();
+        }
+        if let Some(qos) = patch.qos {
+            self.qos = qos;
+        }
+        if let Some(calibration) = &patch.calibration {
+            self.calibration.extend(calibration.clone());
+        }
+        Ok(())
+    }
+
+    /// Sensör tipi aktif mi?
+    pub fn is_enabled(&self, sensor_type: &str) -> bool {
+        self.enabled_sensors.iter().any(|s| s == sensor_type)
+    }
+
+    /// rumqttc QoS değerine dönüştür
+    pub fn mqtt_qos(&self) -> QoS {
+        match self.qos {
+            0 => QoS::AtMostOnce,
+            1 => QoS::AtLeastOnce,
+            _ => QoS::ExactlyOnce,
+        }
+    }
+
+    /// Gölgeye raporlanan ayarlar (`desired`'daki alanlarla aynı şekil)
+    pub fn reported(&self) -> RemoteConfig {
+        RemoteConfig {
+            sensor_interval_secs: Some(self.sensor_interval_secs),
+            enabled_sensors: Some(self.enabled_sensors.clone()),
+            qos: Some(self.qos),
+        }
+    }
+}
+
+impl From<&RemoteConfig> for RuntimeConfigPatch {
+    /// Kayıtta sunucudan gelen başlangıç ayarları
+    fn from(remote: &RemoteConfig) -> Self {
+        Self {
+            sensor_interval_secs: remote.sensor_interval_secs,
+            enabled_sensors: remote.enabled_sensors.clone(),
+            qos: remote.qos,
+            calibration: None,
+        }
+    }
+}
+
+impl RuntimeConfigPatch {
+    /// DeviceCommand parametrelerinden patch oluştur
+    ///
+    /// Değiştirilemeyen alanlar (device_id, broker host vb.) ve
+    /// bilinmeyen alanlar hata ile reddedilir.
+    pub fn from_parameters(parameters: &serde_json::Value) -> Result<Self> {
+        let obj = parameters.as_object().ok_or_else(|| {
+            Error::InvalidParameter("config_update parameters must be an object".into())
+        })?;
+
+        if let Some(field) = obj.keys().find(|k| IMMUTABLE_FIELDS.contains(&k.as_str())) {
+            return Err(Error::InvalidParameter(format!("field `{field}` is immutable")));
+        }
+
+        serde_json::from_value(parameters.clone())
+            .map_err(|e| Error::InvalidParameter(e.to_string()))
+    }
+
+    /// Değerlerin geçerli aralıkta olduğunu kontrol et
+    pub fn validate(&self) -> Result<()> {
+        if self.sensor_interval_secs == Some(0) {
+            return Err(Error::InvalidParameter(
+                "sensor_interval_secs must be greater than 0".into(),
+            ));
+        }
+        if let Some(qos) = self.qos {
+            if qos > 2 {
+                return Err(Error::InvalidParameter(format!("qos must be 0, 1 or 2, got {qos}")));
+            }
+        }
+        if let Some(sensors) = &self.enabled_sensors {
+            if let Some(unknown) = sensors.iter().find(|s| !KNOWN_SENSORS.contains(&s.as_str())) {
+                return Err(Error::InvalidParameter(format!("unknown sensor `{unknown}`")));
+            }
+        }
+        if let Some(calibration) = &self.calibration {
+            for (sensor_type, cal) in calibration {
+                cal.validate(sensor_type)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Başka bir patch'i bunun üzerine birleştir (yeni değerler kazanır)
+    pub fn merge(&mut self, other: &RuntimeConfigPatch) {
+        if other.sensor_interval_secs.is_some() {
+            self.sensor_interval_secs = other.sensor_interval_secs;
+        }
+        if other.enabled_sensors.is_some() {
+            self.enabled_sensors = other.enabled_sensors.clone();
+        }
+        if other.qos.is_some() {
+            self.qos = other.qos;
+        }
+        if let Some(calibration) = &other.calibration {
+            self.calibration.get_or_insert_with(BTreeMap::new).extend(calibration.clone());
+        }
+    }
+
+    /// Diske kaydedilmiş override'ları yükle
+    ///
+    /// Dosya yoksa veya okunamazsa `None` döner.
+    pub fn load(path: &Path) -> Option<Self> {
+        let raw = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&raw) {
+            Ok(patch) => Some(patch),
+            Err(e) => {
+                tracing::warn!("Ignoring invalid runtime config file {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Override'ları diske kaydet
+    pub fn persist(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        std::fs::write(path, json).map_err(|e| Error::InternalError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> RuntimeConfig {
+        RuntimeConfig {
+            sensor_interval_secs: 5,
+            enabled_sensors: KNOWN_SENSORS.iter().map(|s| s.to_string()).collect(),
+            qos: 0,
+            calibration: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_patch_applies_only_given_fields() {
+        let mut rt = base();
+        let patch = RuntimeConfigPatch::from_parameters(&serde_json::json!({
+            "sensor_interval_secs": 30
+        }))
+        .unwrap();
+
+        rt.apply(&patch).unwrap();
+        assert_eq!(rt.sensor_interval_secs, 30);
+        assert_eq!(rt.enabled_sensors.len(), 3);
+        assert_eq!(rt.qos, 0);
+    }
+
+    #[test]
+    fn test_immutable_and_unknown_fields_rejected() {
+        let immutable = serde_json::json!({"device_id": "x", "qos": 1});
+        assert!(matches!(
+            RuntimeConfigPatch::from_parameters(&immutable),
+            Err(Error::InvalidParameter(msg)) if msg.contains("device_id")
+        ));
+
+        let unknown = serde_json::json!({"foo": 1});
+        assert!(RuntimeConfigPatch::from_parameters(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_invalid_values_leave_config_untouched() {
+        let mut rt = base();
+        for params in [
+            serde_json::json!({"qos": 3}),
+            serde_json::json!({"sensor_interval_secs": 0}),
+            serde_json::json!({"enabled_sensors": ["pressure"]}),
+        ] {
+            let patch = RuntimeConfigPatch::from_parameters(&params).unwrap();
+            assert!(rt.apply(&patch).is_err());
+        }
+        assert_eq!(rt, base());
+    }
+
+    #[test]
+    fn test_persist_and_reload_overrides() {
+        let path = std::env::temp_dir().join(format!("rt-{}.json", uuid::Uuid::new_v4()));
+
+        let mut overrides = RuntimeConfigPatch { qos: Some(1), ..Default::default() };
+        overrides.merge(&RuntimeConfigPatch {
+            enabled_sensors: Some(vec!["motion".into()]),
+            ..Default::default()
+        });
+        overrides.persist(&path).unwrap();
+
+        let loaded = RuntimeConfigPatch::load(&path).unwrap();
+        assert_eq!(loaded, overrides);
+
+        let mut rt = base();
+        rt.apply(&loaded).unwrap();
+        assert_eq!(rt.qos, 1);
+        assert!(rt.is_enabled("motion"));
+        assert!(!rt.is_enabled("temperature"));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(RuntimeConfigPatch::load(&path).is_none());
+    }
+
+    #[test]
+    fn test_persisted_overrides_survive_reload() {
+        let path = std::env::temp_dir().join(format!("rt-{}.json", uuid::Uuid::new_v4()));
+        RuntimeConfigPatch { sensor_interval_secs: Some(30), ..Default::default() }.persist(&path).unwrap();
+
+        // SIGHUP: .env'den yeniden kurulan config'e uzaktan override'lar tekrar uygulanır
+        let mut reloaded = RuntimeConfig { qos: 1, ..base() };
+        reloaded.apply_persisted(&path);
+        assert_eq!((reloaded.sensor_interval_secs, reloaded.qos), (30, 1));
+        assert!(path.exists());
+
+        // Geçersiz dosya yok sayılır
+        std::fs::write(&path, r#"{"qos": 7}"#).unwrap();
+        let mut rt = base();
+        rt.apply_persisted(&path);
+        assert_eq!(rt, base());
+        std::fs::remove_file(&path).unwrap();
+        rt.apply_persisted(&path);
+        assert_eq!(rt, base());
+    }
+
+    #[test]
+    fn test_calibration_patch_merges_per_sensor_and_persists() {
+        let path = std::env::temp_dir().join(format!("rt-{}.json", uuid::Uuid::new_v4()));
+        let temp = Calibration { offset: -1.5, scale: 1.02 };
+        let hum = Calibration { offset: 3.0, scale: 1.0 };
+
+        let mut overrides = RuntimeConfigPatch::from_parameters(&serde_json::json!({
+            "calibration": {"temperature": {"offset": -1.5, "scale": 1.02}}
+        }))
+        .unwrap();
+        overrides.merge(&RuntimeConfigPatch {
+            calibration: Some(BTreeMap::from([("humidity".to_string(), hum)])),
+            ..Default::default()
+        });
+        overrides.persist(&path).unwrap();
+
+        let mut rt = base();
+        rt.apply(&RuntimeConfigPatch::load(&path).unwrap()).unwrap();
+        assert_eq!(rt.calibration.get("temperature"), Some(&temp));
+        assert_eq!(rt.calibration.get("humidity"), Some(&hum));
+
+        // Kalibre edilemeyen sensör ve sıfır scale reddedilir
+        for params in [
+            serde_json::json!({"calibration": {"motion": {"offset": 1.0}}}),
+            serde_json::json!({"calibration": {"humidity": {"scale": 0.0}}}),
+        ] {
+            let patch = RuntimeConfigPatch::from_parameters(&params).unwrap();
+            assert!(rt.apply(&patch).is_err());
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
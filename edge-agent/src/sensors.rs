@@ -4,26 +4,65 @@
 //! Raspberry Pi'da gerçek sensörler olsaydı, bunların yerine
 //! rppal veya embedded-hal kullanarak gerçek okumalar yapılırdı.
 
+use std::collections::HashMap;
 use rand::Rng;
-use shared_types::sensor::SensorReading;
-use chrono::Utc;
+use shared_types::filter::KalmanFilter1D;
+use shared_types::sensor::{SensorReading, SensorReadingBuffer, SensorStats, SensorType};
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+/// İstatistik penceresi: sensör başına son kaç okuma
+pub const STATS_WINDOW: usize = 60;
+
 /// Sensör okuması ve tip bilgisi
 #[derive(Debug, Clone)]
 pub struct SensorData {
     pub reading: SensorReading,
     pub sensor_type: String,
     pub unit: String,
+    /// Son `STATS_WINDOW` okumanın istatistikleri (pencere dolunca;
+    /// kalibrasyon ve yumuşatma sonrası değerler, bkz. [`SensorStatistics`])
+    pub statistics: Option<SensorStats>,
+}
+
+/// Sensör tipi başına istatistik pencereleri
+///
+/// Kalibrasyon ve yumuşatmadan sonra çağrılır; böylece istatistikler
+/// yayınlanan değerleri özetler. Hareket sensöründe pencere ortalaması son
+/// okumalardaki hareket oranıdır.
+#[derive(Debug, Default)]
+pub struct SensorStatistics {
+    windows: HashMap<String, SensorReadingBuffer<STATS_WINDOW>>,
+}
+
+impl SensorStatistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Okumayı sensör tipinin penceresine ekle; pencere dolduysa
+    /// `data.statistics` doldurulur
+    ///
+    /// Sayısal olmayan değerler pencereye girmez.
+    pub fn observe(&mut self, data: &mut SensorData) {
+        let window = self.windows.entry(data.sensor_type.clone()).or_default();
+        if let Ok(value) = data.reading.value.parse() {
+            window.push(value);
+        }
+        data.statistics = window.is_full().then(|| window.stats());
+    }
 }
 
 /// Sıcaklık sensörü (mock)
 /// 
 /// 18-30°C arasında rastgele değerler üretir.
 /// Gerçek kullanımda: DHT22, DS18B20 vb. sensörlerden okuma yapılır.
+/// `kalman` ayarlıysa okumalar yayınlanmadan önce yumuşatılır
+/// (`TEMP_KALMAN_PROCESS_VAR` / `TEMP_KALMAN_MEASUREMENT_VAR`).
 pub struct TemperatureSensor {
     sensor_id: Uuid,
     last_value: f64,
+    kalman: Option<KalmanFilter1D>,
 }
 
 impl TemperatureSensor {
@@ -32,9 +71,16 @@ impl TemperatureSensor {
         Self {
             sensor_id: Uuid::new_v4(),
             last_value: 22.0, // Başlangıç değeri (oda sıcaklığı)
+            kalman: None,
         }
     }
 
+    /// Okumaları Kalman filtresiyle yumuşat
+    pub fn with_kalman(mut self, filter: KalmanFilter1D) -> Self {
+        self.kalman = Some(filter);
+        self
+    }
+
     /// Mock sıcaklık verisi üret
     /// 
     /// Gerçekçi olması için son değere yakın bir değer üretir (±2°C)
@@ -45,16 +91,22 @@ impl TemperatureSensor {
         let change: f64 = rng.gen_range(-2.0..2.0);
         self.last_value = (self.last_value + change).clamp(18.0, 30.0);
 
+        let mut reading = SensorReading {
+            sensor_id: self.sensor_id,
+            value: format!("{:.2}", self.last_value),
+            timestamp: Utc::now(),
+            is_valid: true,
+            metadata: None,
+        };
+        if let Some(filter) = &mut self.kalman {
+            reading.kalman_smooth(filter);
+        }
+
         SensorData {
-            reading: SensorReading {
-                sensor_id: self.sensor_id,
-                value: format!("{:.2}", self.last_value),
-                timestamp: Utc::now(),
-                is_valid: true,
-                metadata: None,
-            },
-            sensor_type: "temperature".to_string(),
-            unit: "celsius".to_string(),
+            statistics: None,
+            reading,
+            sensor_type: SensorType::Temperature.to_string(),
+            unit: SensorType::Temperature.default_unit().to_string(),
         }
     }
 }
@@ -85,16 +137,19 @@ impl HumiditySensor {
         let change: f64 = rng.gen_range(-5.0..5.0);
         self.last_value = (self.last_value + change).clamp(30.0, 80.0);
 
+        let reading = SensorReading {
+            sensor_id: self.sensor_id,
+            value: format!("{:.1}", self.last_value),
+            timestamp: Utc::now(),
+            is_valid: true,
+            metadata: None,
+        };
+
         SensorData {
-            reading: SensorReading {
-                sensor_id: self.sensor_id,
-                value: format!("{:.1}", self.last_value),
-                timestamp: Utc::now(),
-                is_valid: true,
-                metadata: None,
-            },
-            sensor_type: "humidity".to_string(),
-            unit: "percent".to_string(),
+            statistics: None,
+            reading,
+            sensor_type: SensorType::Humidity.to_string(),
+            unit: SensorType::Humidity.default_unit().to_string(),
         }
     }
 }
@@ -123,20 +178,23 @@ impl MotionSensor {
         let mut rng = rand::thread_rng();
         let motion_detected = rng.gen_bool(0.2); // %20 olasılık
 
-        SensorData {
-            reading: SensorReading {
-                sensor_id: self.sensor_id,
-                value: if motion_detected { "1".to_string() } else { "0".to_string() },
-                timestamp: Utc::now(),
-                is_valid: true,
-                metadata: if motion_detected {
-                    Some(serde_json::json!({"event": "motion_detected"}))
-                } else {
-                    None
-                },
+        let reading = SensorReading {
+            sensor_id: self.sensor_id,
+            value: if motion_detected { "1".to_string() } else { "0".to_string() },
+            timestamp: Utc::now(),
+            is_valid: true,
+            metadata: if motion_detected {
+                Some(serde_json::json!({"event": "motion_detected"}))
+            } else {
+                None
             },
-            sensor_type: "motion".to_string(),
-            unit: "boolean".to_string(),
+        };
+
+        SensorData {
+            statistics: None,
+            reading,
+            sensor_type: SensorType::Motion.to_string(),
+            unit: SensorType::Motion.default_unit().to_string(),
         }
     }
 }
@@ -162,9 +220,29 @@ impl SensorController {
         }
     }
 
+    /// RNG'den türetilen sensör ID'leri ve başlangıç değerleriyle controller oluştur
+    /// 
+    /// Simülasyon modunda her sanal cihazın kendi random walk'unu farklı
+    /// bir noktadan başlatması için kullanılır.
+    pub fn seeded(rng: &mut impl Rng) -> Self {
+        Self {
+            temperature: TemperatureSensor {
+                sensor_id: seeded_uuid(rng),
+                last_value: rng.gen_range(18.0..30.0),
+                kalman: None,
+            },
+            humidity: HumiditySensor {
+                sensor_id: seeded_uuid(rng),
+                last_value: rng.gen_range(30.0..80.0),
+            },
+            motion: MotionSensor { sensor_id: seeded_uuid(rng) },
+        }
+    }
+
     /// Tüm sensörlerden veri oku
     /// 
     /// Her sensörden bir okuma yapar ve SensorData vector'ü döner.
+    #[tracing::instrument(skip(self))]
     pub fn read_all(&mut self) -> Vec<SensorData> {
         vec![
             self.temperature.read(),
@@ -173,3 +251,385 @@ impl SensorController {
         ]
     }
 }
+
+/// RNG'den UUID (v4 formatında) üret
+/// 
+/// Aynı seed ile aynı UUID'ler üretilir.
+pub fn seeded_uuid(rng: &mut impl Rng) -> Uuid {
+    uuid::Builder::from_random_bytes(rng.gen()).into_uuid()
+}
+
+/// Son yayınlanan okumaları tutan cache (deadband filtresi)
+/// 
+/// Değişmeyen değerleri tekrar publish etmemek için kullanılır (bandwidth tasarrufu).
+/// Sadece yayınlanan okumalar kaydedilir; böylece yavaş kayma (drift) da
+/// eşiği geçtiğinde yakalanır.
+/// 
+/// - Her sensör tipi için ayrı deadband tanımlanabilir (yoksa `change_threshold`)
+/// - Hareket sensörü sadece durum değiştiğinde yayınlanır
+/// - `max_silence` süresince yayın yapılmadıysa değer değişmese de yayınlanır
+///   (downstream staleness tespiti için heartbeat)
+pub struct SensorReadingCache {
+    /// sensor_id → (son yayınlanan değer, zamanı)
+    pub last: HashMap<Uuid, (String, DateTime<Utc>)>,
+    /// Yayın için gereken varsayılan minimum değişim (örn: 0.1°C / 0.1%)
+    pub change_threshold: f64,
+    /// Sensör tipine özel deadband'ler (örn: "temperature" → 0.2)
+    pub deadbands: HashMap<String, f64>,
+    /// İki yayın arasındaki maksimum sessizlik süresi
+    pub max_silence: Option<chrono::Duration>,
+    /// Sensör tipi → bastırılan (yayınlanmayan) okuma sayısı
+    pub suppressed: HashMap<String, u64>,
+}
+
+impl SensorReadingCache {
+    /// Yeni boş cache oluştur
+    pub fn new(change_threshold: f64) -> Self {
+        Self {
+            last: HashMap::new(),
+            change_threshold,
+            deadbands: HashMap::new(),
+            max_silence: None,
+            suppressed: HashMap::new(),
+        }
+    }
+
+    /// Sensör tipi için deadband ayarla
+    pub fn with_deadband(mut self, sensor_type: &str, deadband: f64) -> Self {
+        self.deadbands.insert(sensor_type.to_string(), deadband);
+        self
+    }
+
+    /// Maksimum sessizlik süresini ayarla (heartbeat)
+    pub fn with_max_silence(mut self, max_silence: chrono::Duration) -> Self {
+        self.max_silence = Some(max_silence);
+        self
+    }
+
+    /// Sensör tipi için geçerli deadband
+    /// 
+    /// Hareket sensörü boolean olduğu için 0 kullanılır (her durum değişimi yayınlanır).
+    pub fn threshold_for(&self, sensor_type: &str) -> f64 {
+        if sensor_type == "motion" {
+            return 0.0;
+        }
+        self.deadbands.get(sensor_type).copied().unwrap_or(self.change_threshold)
+    }
+
+    /// Okuma yayınlanmalı mı?
+    /// 
+    /// - Sensör ilk kez görülüyorsa `true`
+    /// - Değer son yayınlanandan en az `threshold` kadar (ve sıfırdan fazla) değiştiyse `true`
+    /// - Son yayından bu yana `max_silence` geçtiyse `true` (heartbeat)
+    /// - Sayısal olmayan değerler string eşitliği ile karşılaştırılır
+    /// - `true` dönerse okuma yeni referans değer olarak kaydedilir
+    pub fn has_changed(&mut self, reading: &SensorReading, threshold: f64) -> bool {
+        let changed = match self.last.get(&reading.sensor_id) {
+            None => true,
+            Some((prev, published_at)) => {
+                let value_changed = match (prev.parse::<f64>(), reading.value.parse::<f64>()) {
+                    (Ok(a), Ok(b)) => {
+                        let diff = (b - a).abs();
+                        diff > 0.0 && diff >= threshold
+                    }
+                    _ => *prev != reading.value,
+                };
+                let silent_too_long = self
+                    .max_silence
+                    .is_some_and(|max| reading.timestamp - *published_at >= max);
+                value_changed || silent_too_long
+            }
+        };
+
+        if changed {
+            self.last.insert(reading.sensor_id, (reading.value.clone(), reading.timestamp));
+        }
+        changed
+    }
+
+    /// Toplam bastırılan okuma sayısı
+    pub fn total_suppressed(&self) -> u64 {
+        self.suppressed.values().sum()
+    }
+}
+
+/// Sadece yayınlanması gereken okumaları bırak
+/// 
+/// Bastırılan okumalar sensör tipine göre `cache.suppressed`'a sayılır.
+pub fn filter_changed(readings: Vec<SensorData>, cache: &mut SensorReadingCache) -> Vec<SensorData> {
+    readings
+        .into_iter()
+        .filter(|data| {
+            let threshold = cache.threshold_for(&data.sensor_type);
+            let publish = cache.has_changed(&data.reading, threshold);
+            if !publish {
+                *cache.suppressed.entry(data.sensor_type.clone()).or_default() += 1;
+            }
+            publish
+        })
+        .collect()
+}
+
+/// Değişim hızına göre okuma aralığını ayarlayan örnekleyici
+///
+/// Her okumada `|yeni - son| / geçen_süre` hesaplanır:
+/// - Hız `change_rate_threshold`'u aşarsa aralık yarıya iner (en az `min_interval_secs`)
+/// - Hız eşiğin altındaysa aralık iki katına çıkar (en fazla `max_interval_secs`)
+///
+/// Böylece hızlı değişen ortamlar sık, durağan ortamlar seyrek örneklenir.
+#[derive(Debug, Clone)]
+pub struct AdaptiveSampler {
+    pub base_interval_secs: u64,
+    pub min_interval_secs: u64,
+    pub max_interval_secs: u64,
+    pub change_rate_threshold: f64,
+    pub current_interval: u64,
+    /// Sensör tipi → son okunan değer
+    last_values: HashMap<String, f64>,
+}
+
+impl AdaptiveSampler {
+    /// Yeni örnekleyici oluştur (başlangıç aralığı = `base_interval_secs`)
+    ///
+    /// Sınırlar tutarsızsa düzeltilir: `min >= 1`, `max >= min`, base bu aralığa sıkıştırılır.
+    pub fn new(base_interval_secs: u64, min_interval_secs: u64, max_interval_secs: u64, change_rate_threshold: f64) -> Self {
+        let min_interval_secs = min_interval_secs.max(1);
+        let max_interval_secs = max_interval_secs.max(min_interval_secs);
+        let base_interval_secs = base_interval_secs.clamp(min_interval_secs, max_interval_secs);
+        Self {
+            base_interval_secs,
+            min_interval_secs,
+            max_interval_secs,
+            change_rate_threshold,
+            current_interval: base_interval_secs,
+            last_values: HashMap::new(),
+        }
+    }
+
+    /// Temel aralığı değiştir ve mevcut aralığı ona sıfırla
+    ///
+    /// Runtime config ile `sensor_interval_secs` değiştiğinde kullanılır.
+    pub fn reset(&mut self, base_interval_secs: u64) {
+        self.base_interval_secs = base_interval_secs.clamp(self.min_interval_secs, self.max_interval_secs);
+        self.current_interval = self.base_interval_secs;
+    }
+
+    /// Bir tur okumayı işle ve sonraki aralığı döndür
+    ///
+    /// En hızlı değişen sensör belirleyicidir. İlk kez görülen sensörlerin
+    /// hızı hesaplanamaz; hiç hız yoksa (veya `elapsed_secs` sıfırsa) aralık değişmez.
+    pub fn observe<'a>(&mut self, values: impl IntoIterator<Item = (&'a str, f64)>, elapsed_secs: f64) -> u64 {
+        let mut max_rate: Option<f64> = None;
+        for (sensor_type, value) in values {
+            if let Some(last) = self.last_values.insert(sensor_type.to_string(), value) {
+                if elapsed_secs > 0.0 {
+                    let rate = (value - last).abs() / elapsed_secs;
+                    max_rate = Some(max_rate.map_or(rate, |m| m.max(rate)));
+                }
+            }
+        }
+
+        if let Some(rate) = max_rate {
+            self.current_interval = if rate > self.change_rate_threshold {
+                (self.current_interval / 2).max(self.min_interval_secs)
+            } else {
+                self.current_interval.saturating_mul(2).min(self.max_interval_secs)
+            };
+        }
+        self.current_interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(sensor_id: Uuid, value: &str) -> SensorReading {
+        SensorReading::new(sensor_id, value.to_string())
+    }
+
+    fn reading_at(sensor_id: Uuid, value: &str, secs: i64) -> SensorReading {
+        let mut r = reading(sensor_id, value);
+        r.timestamp = DateTime::from_timestamp(secs, 0).unwrap();
+        r
+    }
+
+    fn data(sensor_type: &str, reading: SensorReading) -> SensorData {
+        SensorData { reading, sensor_type: sensor_type.to_string(), unit: String::new(), statistics: None }
+    }
+
+    #[test]
+    fn test_statistics_after_full_window() {
+        let mut sensor = HumiditySensor::new();
+        let mut statistics = SensorStatistics::new();
+        let mut values = Vec::new();
+        for _ in 1..STATS_WINDOW {
+            let mut data = sensor.read();
+            statistics.observe(&mut data);
+            assert!(data.statistics.is_none());
+            values.push(data.reading.value.parse::<f64>().unwrap());
+        }
+
+        // Pencere doldu; sonraki her okumada son 60 değerin istatistikleri
+        for _ in 0..5 {
+            let mut data = sensor.read();
+            statistics.observe(&mut data);
+            values.push(data.reading.value.parse().unwrap());
+            let window = &values[values.len() - STATS_WINDOW..];
+            let stats = data.statistics.unwrap();
+            assert_eq!(stats.count, STATS_WINDOW as u64);
+            assert_eq!(stats.min, window.iter().copied().reduce(f64::min));
+            assert_eq!(stats.max, window.iter().copied().reduce(f64::max));
+            let mean = window.iter().sum::<f64>() / STATS_WINDOW as f64;
+            assert!((stats.mean.unwrap() - mean).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_statistics_use_calibrated_values() {
+        // Ham 20.0 okumalar +5 offset ile 25.0 olarak yayınlanır;
+        // istatistikler yayınlanan değeri özetler
+        let cal = crate::calibration::Calibration { offset: 5.0, ..Default::default() };
+        let mut statistics = SensorStatistics::new();
+        let id = Uuid::new_v4();
+        for i in 0..STATS_WINDOW {
+            let mut temp = data("temperature", reading(id, "20.0"));
+            cal.apply_to(&mut temp);
+            statistics.observe(&mut temp);
+            // Diğer sensör tipleri kendi penceresindedir
+            statistics.observe(&mut data("humidity", reading(id, "50")));
+            assert_eq!(temp.statistics.is_some(), i + 1 == STATS_WINDOW);
+            if let Some(stats) = temp.statistics {
+                assert_eq!(stats.min, Some(25.0));
+                assert_eq!(stats.max, Some(25.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_temperature_kalman_smooths_readings() {
+        let mut sensor = TemperatureSensor::new().with_kalman(KalmanFilter1D::new(1e-6, 1.0));
+        let values: Vec<f64> = (0..100).map(|_| sensor.read().reading.value.parse().unwrap()).collect();
+
+        // Ham değer adım başına 2°C'ye kadar oynar; yerleşmiş filtre çok daha az
+        let max_step = values[50..].windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f64::max);
+        assert!(max_step < 0.5, "{max_step}");
+        assert!(values.iter().all(|v| (18.0..=30.0).contains(v)));
+    }
+
+    #[test]
+    fn test_first_reading_always_published() {
+        let mut cache = SensorReadingCache::new(0.1);
+        let id = Uuid::new_v4();
+
+        assert!(cache.has_changed(&reading(id, "22.00"), 0.1));
+        assert!(!cache.has_changed(&reading(id, "22.00"), 0.1));
+    }
+
+    #[test]
+    fn test_threshold_boundary() {
+        let mut cache = SensorReadingCache::new(0.5);
+        let id = Uuid::new_v4();
+        cache.has_changed(&reading(id, "20.0"), 0.5);
+
+        // Deadband'den küçük değişim bastırılır, eşit veya büyük yayınlanır
+        assert!(!cache.has_changed(&reading(id, "20.4"), 0.5));
+        assert!(cache.has_changed(&reading(id, "20.5"), 0.5));
+        // Referans 20.5'e güncellendi
+        assert!(!cache.has_changed(&reading(id, "20.1"), 0.5));
+    }
+
+    #[test]
+    fn test_per_sensor_deadband_and_suppressed_count() {
+        let mut cache = SensorReadingCache::new(0.1)
+            .with_deadband("temperature", 0.2)
+            .with_deadband("humidity", 1.0);
+        let (temp, hum) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let first = vec![data("temperature", reading(temp, "22.0")), data("humidity", reading(hum, "50.0"))];
+        assert_eq!(filter_changed(first, &mut cache).len(), 2);
+
+        let second = vec![data("temperature", reading(temp, "22.3")), data("humidity", reading(hum, "50.5"))];
+        let published = filter_changed(second, &mut cache);
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].sensor_type, "temperature");
+        assert_eq!(cache.suppressed.get("humidity"), Some(&1));
+        assert_eq!(cache.total_suppressed(), 1);
+    }
+
+    #[test]
+    fn test_forced_heartbeat_publish() {
+        let mut cache = SensorReadingCache::new(1.0).with_max_silence(chrono::Duration::seconds(60));
+        let id = Uuid::new_v4();
+
+        assert!(cache.has_changed(&reading_at(id, "20.0", 0), 1.0));
+        assert!(!cache.has_changed(&reading_at(id, "20.0", 30), 1.0));
+        assert!(!cache.has_changed(&reading_at(id, "20.0", 59), 1.0));
+        // 60 saniye sessizlik: değer aynı olsa da yayınla
+        assert!(cache.has_changed(&reading_at(id, "20.0", 60), 1.0));
+        // Heartbeat sayacı sıfırlandı
+        assert!(!cache.has_changed(&reading_at(id, "20.0", 100), 1.0));
+    }
+
+    #[test]
+    fn test_motion_publishes_only_on_state_change() {
+        let mut cache = SensorReadingCache::new(0.1).with_max_silence(chrono::Duration::seconds(60));
+        let id = Uuid::new_v4();
+        let motion = |value: &str, secs| vec![data("motion", reading_at(id, value, secs))];
+
+        assert_eq!(filter_changed(motion("0", 0), &mut cache).len(), 1);
+        assert_eq!(filter_changed(motion("0", 5), &mut cache).len(), 0);
+        assert_eq!(filter_changed(motion("1", 10), &mut cache).len(), 1);
+        assert_eq!(filter_changed(motion("1", 15), &mut cache).len(), 0);
+        assert_eq!(filter_changed(motion("1", 70), &mut cache).len(), 1);
+        assert_eq!(cache.suppressed.get("motion"), Some(&2));
+    }
+    #[test]
+    fn test_sampler_stable_readings_back_off_to_max() {
+        let mut sampler = AdaptiveSampler::new(5, 1, 60, 0.1);
+
+        // İlk okuma referans: aralık değişmez
+        assert_eq!(sampler.observe([("temperature", 22.0)], 5.0), 5);
+        let intervals: Vec<u64> = (0..5)
+            .map(|_| {
+                let elapsed = sampler.current_interval as f64;
+                sampler.observe([("temperature", 22.0)], elapsed)
+            })
+            .collect();
+        assert_eq!(intervals, vec![10, 20, 40, 60, 60]);
+    }
+
+    #[test]
+    fn test_sampler_changing_readings_speed_up_to_min() {
+        let mut sampler = AdaptiveSampler::new(16, 2, 60, 0.1);
+        sampler.observe([("temperature", 20.0)], 16.0);
+
+        // Her turda 5°C değişim: hız eşiğin çok üzerinde
+        let mut value = 20.0;
+        let intervals: Vec<u64> = (0..5)
+            .map(|_| {
+                value += 5.0;
+                let elapsed = sampler.current_interval as f64;
+                sampler.observe([("temperature", value)], elapsed)
+            })
+            .collect();
+        assert_eq!(intervals, vec![8, 4, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_sampler_fastest_sensor_wins_and_reset() {
+        let mut sampler = AdaptiveSampler::new(10, 1, 100, 0.5);
+        sampler.observe([("temperature", 22.0), ("humidity", 50.0)], 10.0);
+
+        // Sıcaklık durağan, nem hızlı değişiyor (1%/s) → hızlan
+        assert_eq!(sampler.observe([("temperature", 22.0), ("humidity", 60.0)], 10.0), 5);
+        // Değişim hızı eşiğin altında (0.2%/s) → yavaşla
+        assert_eq!(sampler.observe([("temperature", 22.0), ("humidity", 61.0)], 5.0), 10);
+
+        sampler.reset(30);
+        assert_eq!(sampler.current_interval, 30);
+        // Base sınırların dışındaysa sıkıştırılır
+        sampler.reset(500);
+        assert_eq!(sampler.current_interval, 100);
+    }
+}
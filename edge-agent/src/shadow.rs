@@ -0,0 +1,95 @@
+//! Device Shadow Senkronizasyonu
+//!
+//! Agent başlarken API server'dan kendi gölgesini (`DeviceShadow`) çeker.
+//! `delta` içindeki runtime config alanları `config_update` komutu olarak
+//! uygulanır; böylece cihaz kapalıyken yapılan değişiklikler kaybolmaz.
+
+use serde_json::{Map, Value};
+use shared_types::messages::DeviceCommand;
+use shared_types::DeviceShadow;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::commands::CommandHandler;
+
+/// `config_update` ile uygulanabilen delta alanları (`RuntimeConfigPatch`)
+const CONFIG_FIELDS: &[&str] = &["sensor_interval_secs", "enabled_sensors", "qos"];
+
+/// Gölgeyi çek ve `delta`'yı uygula
+///
+/// `client` cihaz token'ını gönderir (`http_transport::api_client`).
+/// Hatalar sadece loglanır: API server'a ulaşılamaması agent'ı durdurmamalı.
+pub async fn sync_on_startup(client: &reqwest::Client, api_url: &str, device_id: Uuid, handler: &CommandHandler) {
+    let url = format!("{}/v1/devices/{}/shadow", api_url.trim_end_matches('/'), device_id);
+
+    let shadow = match fetch_shadow(client, &url).await {
+        Ok(Some(shadow)) => shadow,
+        Ok(None) => {
+            debug!("No shadow for this device yet");
+            return;
+        }
+        Err(e) => {
+            warn!("Shadow sync skipped: {}", e);
+            return;
+        }
+    };
+
+    let Some(cmd) = delta_to_config_update(device_id, &shadow.delta) else {
+        info!("🪞 Shadow v{} in sync", shadow.version);
+        return;
+    };
+
+    let response = handler.handle(&cmd).await;
+    if response.success {
+        info!("🪞 Applied shadow v{} delta: {}", shadow.version, cmd.parameters.unwrap_or_default());
+    } else {
+        warn!("Shadow delta rejected: {}", response.message.unwrap_or_default());
+    }
+}
+
+/// Gölgeyi HTTP ile al (404 → `None`)
+async fn fetch_shadow(client: &reqwest::Client, url: &str) -> anyhow::Result<Option<DeviceShadow>> {
+    let response = client.get(url).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(response.error_for_status()?.json().await?))
+}
+
+/// Delta'dan runtime config alanlarını seçip `config_update` komutu oluştur
+///
+/// Uygulanacak alan yoksa `None` döner. Diğer alanlar (LED vb.) atlanır.
+pub fn delta_to_config_update(device_id: Uuid, delta: &Value) -> Option<DeviceCommand> {
+    let fields: Map<String, Value> = delta
+        .as_object()?
+        .iter()
+        .filter(|(key, _)| CONFIG_FIELDS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+
+    (!fields.is_empty()).then(|| {
+        DeviceCommand::new(device_id, "config".to_string(), "config_update".to_string())
+            .with_parameters(Value::Object(fields))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_delta_to_config_update_keeps_config_fields() {
+        let delta = json!({"sensor_interval_secs": 10, "led": {"led_01": "on"}});
+        let cmd = delta_to_config_update(Uuid::new_v4(), &delta).unwrap();
+
+        assert_eq!(cmd.command_name, "config_update");
+        assert_eq!(cmd.parameters, Some(json!({"sensor_interval_secs": 10})));
+    }
+
+    #[test]
+    fn test_delta_without_config_fields() {
+        assert!(delta_to_config_update(Uuid::new_v4(), &json!({})).is_none());
+        assert!(delta_to_config_update(Uuid::new_v4(), &json!({"led": "on"})).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+//! Çoklu Sanal Cihaz Simülasyonu
+//!
+//! Gateway ve API'yi yük altında test etmek için tek process içinde N sanal
+//! cihaz çalıştırır (`SIMULATE_DEVICES=N`).
+//! - Her cihazın ID'si ve adı `SIMULATE_SEED`'den deterministik türetilir
+//! - Her cihazın kendi sensör seti ve bağımsız random walk'u vardır
+//! - Başlangıçlar aralığa yayılır; publish'ler aynı anda olmaz
+//! - MQTT client paylaşılır (tek bağlantı, process başına benzersiz client-id)
+
+use rand::{rngs::StdRng, SeedableRng};
+use shared_types::mqtt::{MqttClient, MqttEventLoop, ReadingProperties};
+use shared_types::conventions;
+use shared_types::messages::{MessagePriority, MqttMessage};
+use tokio::task::JoinSet;
+use tokio::time::{interval_at, sleep, Duration, Instant};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+use chrono::Utc;
+
+use crate::config::{Config, PayloadFormat};
+use crate::runtime_config::SharedRuntimeConfig;
+use crate::sensors::{seeded_uuid, SensorController};
+
+/// Tek bir sanal cihaz
+pub struct VirtualDevice {
+    pub device_id: Uuid,
+    pub device_name: String,
+    pub sensors: SensorController,
+    /// İlk okumadan önceki bekleme (publish'leri kaydırmak için)
+    pub start_offset: Duration,
+}
+
+/// Sanal cihazları oluştur
+///
+/// Cihaz `i`'nin RNG'si (`seed`, `i`) çiftinin hash'inden türetilir; aynı
+/// seed ile aynı ID'ler üretilir, farklı seed'ler birbirinin cihazlarını
+/// üretmez. Başlangıçlar `interval` boyunca eşit aralıklarla yayılır.
+pub fn virtual_devices(base_name: &str, count: usize, seed: u64, interval: Duration) -> Vec<VirtualDevice> {
+    (0..count)
+        .map(|i| {
+            let mut rng = StdRng::seed_from_u64(device_seed(seed, i as u64));
+            VirtualDevice {
+                device_id: seeded_uuid(&mut rng),
+                device_name: format!("{}-sim-{:03}", base_name, i),
+                sensors: SensorController::seeded(&mut rng),
+                start_offset: interval.mul_f64(i as f64 / count as f64),
+            }
+        })
+        .collect()
+}
+
+/// Cihaz `i`'nin RNG seed'i (SplitMix64 ile karıştırılmış `seed` ve `i`)
+///
+/// `seed + i` kullanılsaydı seed 1'in ilk cihazı seed 0'ın ikinci cihazı olurdu.
+fn device_seed(seed: u64, i: u64) -> u64 {
+    splitmix64(splitmix64(seed) ^ i)
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Hızlandırılmış okuma aralığı (`SIMULATE_RATE_MULTIPLIER`)
+///
+/// Geçersiz çarpan (≤ 0 veya sonlu değil) yok sayılır. Aralık sıfıra
+/// yuvarlanıyor veya `Duration`'a sığmıyorsa hata döner (`interval_at`
+/// sıfır aralıkta panikler).
+pub fn scaled_interval(interval_secs: u64, rate_multiplier: f64) -> anyhow::Result<Duration> {
+    let multiplier = if rate_multiplier.is_finite() && rate_multiplier > 0.0 { rate_multiplier } else { 1.0 };
+    match Duration::try_from_secs_f64(interval_secs as f64 / multiplier) {
+        Ok(interval) if !interval.is_zero() => Ok(interval),
+        _ => anyhow::bail!("invalid simulation interval: {}s at rate multiplier {}", interval_secs, rate_multiplier),
+    }
+}
+
+/// Simülasyonu çalıştır (process sonlanana kadar döner)
+///
+/// Normal moddaki komut işleme, deadband ve adaptif örnekleme yapılmaz:
+/// her sanal cihaz her turda tüm okumalarını publish eder.
+pub async fn run(
+    cfg: &Config,
+    client: MqttClient,
+    mut eventloop: MqttEventLoop,
+    runtime: SharedRuntimeConfig,
+) -> anyhow::Result<()> {
+    let rt = runtime.read().await.clone();
+    let interval = scaled_interval(rt.sensor_interval_secs, cfg.simulate_rate_multiplier)?;
+    let devices = virtual_devices(&cfg.device_name, cfg.simulate_devices, cfg.simulate_seed, interval);
+    info!("🧪 Simulating {} devices (interval: {:?}, seed: {})", devices.len(), interval, cfg.simulate_seed);
+
+    // Paylaşılan bağlantının event loop'u
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = eventloop.poll().await {
+                error!("MQTT connection error: {}", e);
+                sleep(Duration::from_secs(5)).await;
+            }
+        }
+    });
+
+    let mut tasks = JoinSet::new();
+    for device in devices {
+        let client = client.clone();
+        let payload_format = cfg.payload_format;
+        let compress = cfg.mqtt_compress;
+        let qos = rt.qos;
+        let mqtt_qos = rt.mqtt_qos();
+        tasks.spawn(async move {
+            let mut device = device;
+            let mut timer = interval_at(Instant::now() + device.start_offset, interval);
+            loop {
+                timer.tick().await;
+                for data in device.sensors.read_all() {
+                    let topic = payload_format.topic(&conventions::sensor_topic(&device.device_name, &data.sensor_type));
+                    let message = MqttMessage {
+                        message_type: format!("{}_reading", data.sensor_type),
+                        payload: serde_json::to_value(&data.reading).unwrap_or_default(),
+                        timestamp: Utc::now(),
+                        device_id: device.device_id,
+                        qos,
+                        trace_id: Some(Uuid::new_v4()),
+                        priority: MessagePriority::Normal,
+                    };
+                    let properties = ReadingProperties::new(device.device_id, data.sensor_type.as_str());
+                    publish(&client, payload_format, compress, mqtt_qos, topic, &message, &properties).await;
+                }
+            }
+        });
+    }
+
+    while let Some(result) = tasks.join_next().await {
+        if let Err(e) = result {
+            error!("Virtual device task failed: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Mesajı serialize edip (istenirse LZ4 ile sıkıştırıp) publish et (hatalar loglanır)
+async fn publish(
+    client: &MqttClient,
+    format: PayloadFormat,
+    compress: bool,
+    qos: rumqttc::QoS,
+    topic: String,
+    message: &MqttMessage,
+    properties: &ReadingProperties,
+) {
+    match format.encode(message) {
+        Ok(bytes) => {
+            let (topic, bytes) = if compress { crate::config::compress_payload(topic, bytes) } else { (topic, bytes) };
+            match client.publish_reading(&topic, qos, bytes, properties, None).await {
+                Ok(()) => debug!("📤 Published to '{}'", topic),
+                Err(e) => warn!("Failed to publish to {}: {}", topic, e),
+            }
+        }
+        Err(e) => error!("Failed to serialize message: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_five_devices_produce_fifteen_streams() {
+        let mut devices = virtual_devices("load", 5, 7, Duration::from_secs(5));
+
+        let ids: HashSet<_> = devices.iter().map(|d| d.device_id).collect();
+        assert_eq!(ids.len(), 5);
+
+        let streams: HashSet<_> = devices
+            .iter_mut()
+            .flat_map(|d| {
+                let device_id = d.device_id;
+                d.sensors.read_all().into_iter().map(move |data| (device_id, data.reading.sensor_id))
+            })
+            .collect();
+        assert_eq!(streams.len(), 15);
+
+        let names: HashSet<_> = devices.iter().map(|d| d.device_name.as_str()).collect();
+        assert!(names.contains("load-sim-000") && names.contains("load-sim-004"));
+    }
+
+    #[test]
+    fn test_devices_are_deterministic_and_staggered() {
+        let ids = |seed| -> Vec<Uuid> {
+            virtual_devices("load", 3, seed, Duration::from_secs(6)).iter().map(|d| d.device_id).collect()
+        };
+        assert_eq!(ids(1), ids(1));
+        assert_ne!(ids(1), ids(2));
+        // Komşu seed'ler cihaz paylaşmaz
+        assert!(ids(1).iter().all(|id| !ids(0).contains(id)));
+
+        let offsets: Vec<_> = virtual_devices("load", 3, 1, Duration::from_secs(6))
+            .iter()
+            .map(|d| d.start_offset)
+            .collect();
+        assert_eq!(offsets, vec![Duration::ZERO, Duration::from_secs(2), Duration::from_secs(4)]);
+    }
+
+    #[test]
+    fn test_rate_multiplier_scales_interval() {
+        assert_eq!(scaled_interval(5, 10.0).unwrap(), Duration::from_millis(500));
+        assert_eq!(scaled_interval(5, 0.0).unwrap(), Duration::from_secs(5));
+        assert_eq!(scaled_interval(5, f64::NAN).unwrap(), Duration::from_secs(5));
+        assert_eq!(scaled_interval(5, f64::INFINITY).unwrap(), Duration::from_secs(5));
+
+        assert!(scaled_interval(0, 1.0).is_err());
+        assert!(scaled_interval(5, 1e12).is_err());
+        assert!(scaled_interval(u64::MAX, f64::MIN_POSITIVE).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Okuma Yumuşatma
+//!
+//! Gürültülü sensörlerde her okumanın yayınlanması deadband'i sık sık
+//! aşar ve grafikleri dalgalandırır. Sensör başına isteğe bağlı bir
+//! hareketli ortalama (SMA) veya üstel hareketli ortalama (EMA) kalibrasyondan
+//! sonra, deadband ve publish'ten önce uygulanır:
+//!
+//! - `sma:5` — son 5 okumanın ortalaması; pencere dolana kadar ham değer yayınlanır
+//! - `ema:0.3` — `yeni = α * ham + (1 - α) * önceki`; ilk okuma ham yayınlanır
+//! - `none` (veya boş) — yumuşatma yok
+//!
+//! Geçersiz okumalar yumuşatıcıyı sıfırlar. Yumuşatılmamış değer
+//! `metadata.raw_value` içinde korunur.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use serde_json::{Map, Value};
+use shared_types::Error;
+
+use crate::sensors::SensorData;
+
+/// Tek bir sensörün yumuşatma durumu
+#[derive(Debug, Clone, PartialEq)]
+pub enum Smoother {
+    None,
+    /// Son okumalar ve pencere boyutu
+    Sma(VecDeque<f64>, usize),
+    /// α ve son yumuşatılmış değer
+    Ema(f64, Option<f64>),
+}
+
+impl FromStr for Smoother {
+    type Err = Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| {
+            Error::Config(format!("invalid smoothing `{raw}`: {reason} (expected `sma:<window>`, `ema:<alpha>` or `none`)"))
+        };
+        let spec = raw.trim().to_ascii_lowercase();
+        if spec.is_empty() || spec == "none" {
+            return Ok(Self::None);
+        }
+        match spec.split_once(':') {
+            Some(("sma", window)) => match window.trim().parse::<usize>() {
+                Ok(window) if window >= 1 => Ok(Self::Sma(VecDeque::with_capacity(window), window)),
+                _ => Err(invalid("window must be a positive integer")),
+            },
+            Some(("ema", alpha)) => match alpha.trim().parse::<f64>() {
+                Ok(alpha) if alpha > 0.0 && alpha <= 1.0 => Ok(Self::Ema(alpha, None)),
+                _ => Err(invalid("alpha must be in (0, 1]")),
+            },
+            _ => Err(invalid("unknown algorithm")),
+        }
+    }
+}
+
+impl Smoother {
+    /// Yeni ham değeri ekle ve yayınlanacak değeri döndür
+    pub fn apply(&mut self, raw: f64) -> f64 {
+        match self {
+            Self::None => raw,
+            Self::Sma(window, size) => {
+                if window.len() == *size {
+                    window.pop_front();
+                }
+                window.push_back(raw);
+                if window.len() < *size {
+                    return raw;
+                }
+                window.iter().sum::<f64>() / *size as f64
+            }
+            Self::Ema(alpha, last) => {
+                let smoothed = last.map_or(raw, |prev| *alpha * raw + (1.0 - *alpha) * prev);
+                *last = Some(smoothed);
+                smoothed
+            }
+        }
+    }
+
+    /// Biriken durumu at; sonraki okuma baştan ısınır
+    pub fn reset(&mut self) {
+        match self {
+            Self::None => {}
+            Self::Sma(window, _) => window.clear(),
+            Self::Ema(_, last) => *last = None,
+        }
+    }
+
+    /// Okumayı yerinde yumuşat
+    ///
+    /// - Geçersiz (`is_valid = false`) veya sayısal olmayan okumalar
+    ///   yumuşatıcıyı sıfırlar ve değiştirilmeden geçer
+    /// - Değer okumayla aynı ondalık hassasiyette formatlanır
+    /// - Yumuşatılmamış değer `metadata.raw_value`'ya yazılır; kalibrasyon
+    ///   zaten yazmışsa sensörün asıl ham değeri korunur
+    pub fn apply_to(&mut self, data: &mut SensorData) {
+        if matches!(self, Self::None) {
+            return;
+        }
+        let raw = match data.reading.value.parse::<f64>() {
+            Ok(raw) if data.reading.is_valid && raw.is_finite() => raw,
+            _ => {
+                self.reset();
+                return;
+            }
+        };
+        let decimals = data.reading.value.split_once('.').map_or(0, |(_, frac)| frac.len());
+
+        data.reading.value = format!("{:.*}", decimals, self.apply(raw));
+        let metadata = data.reading.metadata.get_or_insert_with(|| Value::Object(Map::new()));
+        if !metadata.is_object() {
+            *metadata = Value::Object(Map::new());
+        }
+        if let Value::Object(map) = metadata {
+            map.entry("raw_value").or_insert_with(|| Value::from(raw));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::sensor::SensorReading;
+
+    fn data(value: &str) -> SensorData {
+        SensorData {
+            reading: SensorReading::new(uuid::Uuid::new_v4(), value.to_string()),
+            sensor_type: "temperature".to_string(),
+            unit: String::new(),
+            statistics: None,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("none".parse::<Smoother>().unwrap(), Smoother::None);
+        assert_eq!("".parse::<Smoother>().unwrap(), Smoother::None);
+        assert_eq!("SMA:5".parse::<Smoother>().unwrap(), Smoother::Sma(VecDeque::new(), 5));
+        assert_eq!(" ema:0.3 ".parse::<Smoother>().unwrap(), Smoother::Ema(0.3, None));
+        assert_eq!("ema:1".parse::<Smoother>().unwrap(), Smoother::Ema(1.0, None));
+
+        for invalid in ["sma:0", "sma:-2", "sma:x", "ema:0", "ema:1.5", "ema:nan", "median:3", "sma"] {
+            let err = invalid.parse::<Smoother>().unwrap_err().to_string();
+            assert!(err.contains(invalid), "{invalid}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_sma_publishes_raw_until_window_fills() {
+        let mut smoother: Smoother = "sma:3".parse().unwrap();
+        assert_eq!(smoother.apply(10.0), 10.0);
+        assert_eq!(smoother.apply(20.0), 20.0);
+        assert_eq!(smoother.apply(30.0), 20.0);
+        // Pencere kayar: (20 + 30 + 70) / 3
+        assert_eq!(smoother.apply(70.0), 40.0);
+    }
+
+    #[test]
+    fn test_ema() {
+        let mut smoother: Smoother = "ema:0.5".parse().unwrap();
+        assert_eq!(smoother.apply(10.0), 10.0);
+        assert_eq!(smoother.apply(20.0), 15.0);
+        assert_eq!(smoother.apply(20.0), 17.5);
+
+        // α = 1 ham değeri aynen geçirir
+        let mut passthrough: Smoother = "ema:1".parse().unwrap();
+        passthrough.apply(10.0);
+        assert_eq!(passthrough.apply(42.0), 42.0);
+    }
+
+    #[test]
+    fn test_apply_to_keeps_raw_value_and_precision() {
+        let mut smoother: Smoother = "ema:0.5".parse().unwrap();
+        smoother.apply_to(&mut data("20.00"));
+
+        let mut reading = data("21.00");
+        smoother.apply_to(&mut reading);
+        assert_eq!(reading.reading.value, "20.50");
+        assert_eq!(reading.reading.metadata.unwrap()["raw_value"], 21.0);
+
+        // Kalibrasyonun yazdığı ham değer korunur
+        let mut calibrated = data("22.00");
+        calibrated.reading.metadata = Some(serde_json::json!({ "raw_value": 23.5 }));
+        smoother.apply_to(&mut calibrated);
+        assert_eq!(calibrated.reading.value, "21.25");
+        assert_eq!(calibrated.reading.metadata.unwrap()["raw_value"], 23.5);
+    }
+
+    #[test]
+    fn test_invalid_reading_resets() {
+        let mut smoother: Smoother = "sma:2".parse().unwrap();
+        smoother.apply_to(&mut data("10.0"));
+        let mut reading = data("20.0");
+        smoother.apply_to(&mut reading);
+        assert_eq!(reading.reading.value, "15.0");
+
+        let mut invalid = data("80.0");
+        invalid.reading.is_valid = false;
+        smoother.apply_to(&mut invalid);
+        assert_eq!(invalid.reading.value, "80.0");
+        assert!(invalid.reading.metadata.is_none());
+
+        // Sıfırlandıktan sonra pencere yeniden ısınır
+        let mut warmup = data("30.0");
+        smoother.apply_to(&mut warmup);
+        assert_eq!(warmup.reading.value, "30.0");
+
+        smoother.apply_to(&mut data("not-a-number"));
+        let mut after_garbage = data("40.0");
+        smoother.apply_to(&mut after_garbage);
+        assert_eq!(after_garbage.reading.value, "40.0");
+    }
+}
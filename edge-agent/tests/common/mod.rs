@@ -0,0 +1,154 @@
+//! Entegrasyon testleri için ortak yardımcılar
+//!
+//! `MockSensorServer`, gerçek API server yerine rastgele bir portta
+//! `POST /api/sensors` dinler ve gelen okumaları kaydeder. Agent'ın
+//! `API_SERVER_URL`'i `mock.url()`'e yönlendirilir; ağda gerçek servis gerekmez.
+//!
+//! `start_with_auth` API server'ın `AUTH_ENABLED=true` davranışını taklit
+//! eder: `POST /v1/provision` kayıt token'ı, `POST /api/sensors` cihaz
+//! token'ı ister; token'sız istekler 401 alır ve kaydedilmez.
+
+#![allow(dead_code)]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// API server'ın `POST /api/sensors` body'si
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SensorData {
+    pub device_id: String,
+    pub sensor_type: String,
+    pub value: f64,
+    pub unit: String,
+    pub timestamp: String,
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// `start_with_auth` ayarları
+#[derive(Debug, Clone)]
+pub struct MockAuth {
+    /// `POST /v1/provision`'ın beklediği token
+    pub provisioning_token: String,
+    /// Kayıtta dönen ve `POST /api/sensors`'ın beklediği cihaz token'ı
+    pub device_token: String,
+    /// Kayıtta dönen cihaz ID'si
+    pub device_id: Uuid,
+}
+
+#[derive(Clone)]
+struct Recorder {
+    tx: mpsc::UnboundedSender<SensorData>,
+    count: Arc<AtomicUsize>,
+    rejected: Arc<AtomicUsize>,
+    auth: Option<MockAuth>,
+}
+
+impl Recorder {
+    /// Auth kapalıysa veya `Authorization: Bearer <expected>` ise `true`
+    fn authorized(&self, headers: &HeaderMap, expected: impl Fn(&MockAuth) -> &str) -> bool {
+        let Some(auth) = &self.auth else { return true };
+        let ok = headers.get(AUTHORIZATION).is_some_and(|v| *v == format!("Bearer {}", expected(auth)));
+        if !ok {
+            self.rejected.fetch_add(1, Ordering::SeqCst);
+        }
+        ok
+    }
+}
+
+/// `POST /api/sensors` çağrılarını kaydeden sahte API server
+///
+/// Diğer route'lar 404 döner (ör. shadow senkronizasyonu "gölge yok" görür).
+pub struct MockSensorServer {
+    port: u16,
+    rx: Mutex<mpsc::UnboundedReceiver<SensorData>>,
+    count: Arc<AtomicUsize>,
+    rejected: Arc<AtomicUsize>,
+}
+
+impl MockSensorServer {
+    /// `127.0.0.1`'de rastgele bir portta başlat
+    pub async fn start() -> Self {
+        Self::spawn(None).await
+    }
+
+    /// Token isteyen sahte API server (`AUTH_ENABLED=true` gibi)
+    pub async fn start_with_auth(auth: MockAuth) -> Self {
+        Self::spawn(Some(auth)).await
+    }
+
+    async fn spawn(auth: Option<MockAuth>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let count = Arc::new(AtomicUsize::new(0));
+        let rejected = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/api/sensors", post(record))
+            .route("/v1/provision", post(provision))
+            .with_state(Recorder { tx, count: count.clone(), rejected: rejected.clone(), auth });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        Self { port, rx: Mutex::new(rx), count, rejected }
+    }
+
+    /// Agent'ın `API_SERVER_URL`'i
+    pub fn url(&self) -> String {
+        format!("http://localhost:{}", self.port)
+    }
+
+    /// Başlangıçtan beri alınan okuma sayısı (`drain` sıfırlamaz)
+    pub fn received_count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Token'ı eksik veya yanlış olduğu için 401 dönen istek sayısı
+    pub fn rejected_count(&self) -> usize {
+        self.rejected.load(Ordering::SeqCst)
+    }
+
+    /// Henüz alınmamış okumaları geliş sırasıyla al
+    pub fn drain(&self) -> Vec<SensorData> {
+        let mut rx = self.rx.lock().unwrap();
+        std::iter::from_fn(|| rx.try_recv().ok()).collect()
+    }
+}
+
+async fn record(State(recorder): State<Recorder>, headers: HeaderMap, Json(data): Json<SensorData>) -> StatusCode {
+    if !recorder.authorized(&headers, |auth| &auth.device_token) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    // Sayaç kanaldan önce artmaz: received_count() görünen her okuma drain'de de var
+    let _ = recorder.tx.send(data);
+    recorder.count.fetch_add(1, Ordering::SeqCst);
+    StatusCode::OK
+}
+
+/// Kayıt: `MockAuth`'taki cihaz ID'sini ve token'ını döner (auth yoksa 404)
+async fn provision(State(recorder): State<Recorder>, headers: HeaderMap) -> Result<Json<Value>, StatusCode> {
+    let auth = recorder.auth.clone().ok_or(StatusCode::NOT_FOUND)?;
+    if !recorder.authorized(&headers, |auth| &auth.provisioning_token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(Json(json!({
+        "device_id": auth.device_id,
+        "mqtt_broker": "127.0.0.1",
+        "mqtt_port": 1,
+        "initial_config": {},
+        "token": auth.device_token,
+        "token_expires_at": "2099-01-01T00:00:00Z",
+    })))
+}
@@ -0,0 +1,113 @@
+//! Sensör döngüsü entegrasyon testi
+//!
+//! Agent binary'si `SENSOR_TRANSPORT=http` ile `MockSensorServer`'a karşı
+//! çalıştırılır. MQTT broker erişilemez bir porta yönlendirilir; agent
+//! bağlantı hatalarını loglayıp okuma göndermeye devam etmelidir.
+//! Auth'lu senaryoda agent önce `PROVISIONING_TOKEN` ile kayıt olur, sonra
+//! okumaları kayıtta aldığı cihaz token'ıyla gönderir.
+
+mod common;
+
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+
+use common::{MockAuth, MockSensorServer};
+use tokio::time::{sleep, Duration, Instant};
+use uuid::Uuid;
+
+/// Test bitince (panic dahil) agent process'ini öldür ve çalışma dizinini sil
+struct AgentProcess {
+    child: Child,
+    work_dir: PathBuf,
+}
+
+impl Drop for AgentProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.work_dir);
+    }
+}
+
+fn spawn_agent(api_url: &str, device_id: Uuid) -> AgentProcess {
+    spawn_agent_with(api_url, device_id, &[("DEVICE_ID", &device_id.to_string())])
+}
+
+/// `env` agent'ın ortamına eklenir; `DEVICE_ID` verilmezse agent kayıt olur
+fn spawn_agent_with(api_url: &str, test_id: Uuid, env: &[(&str, &str)]) -> AgentProcess {
+    // Çalışma dizininde .env / rustyflow.toml olmasın
+    let work_dir = std::env::temp_dir().join(format!("edge-agent-test-{test_id}"));
+    std::fs::create_dir_all(&work_dir).unwrap();
+
+    let child = Command::new(env!("CARGO_BIN_EXE_edge-agent"))
+        .current_dir(&work_dir)
+        .env_remove("RUSTYFLOW_CONFIG_FILE")
+        .env_remove("DEVICE_ID")
+        .envs(env.iter().copied())
+        .env("API_SERVER_URL", api_url)
+        .env("SENSOR_TRANSPORT", "http")
+        .env("MQTT_BROKER_HOST", "127.0.0.1")
+        .env("MQTT_BROKER_PORT", "1")
+        .env("ENABLED_SENSORS", "temperature")
+        .env("SENSOR_INTERVAL_SECS", "1")
+        .env("MIN_SENSOR_INTERVAL_SECS", "1")
+        .env("MAX_SENSOR_INTERVAL_SECS", "1")
+        // Her tick bir okuma göndersin (deadband kapalı)
+        .env("SENSOR_CHANGE_THRESHOLD", "0")
+        .env("RUST_LOG", "warn")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    AgentProcess { child, work_dir }
+}
+
+#[tokio::test]
+async fn test_sensor_loop_posts_each_tick_to_api_server() {
+    let mock = MockSensorServer::start().await;
+    let device_id = Uuid::new_v4();
+    let _agent = spawn_agent(&mock.url(), device_id);
+
+    let deadline = Instant::now() + Duration::from_secs(20);
+    while mock.received_count() < 3 {
+        assert!(Instant::now() < deadline, "only {} readings received", mock.received_count());
+        sleep(Duration::from_millis(50)).await;
+    }
+
+    let received = mock.drain();
+    assert_eq!(received.len(), 3);
+    for data in &received {
+        assert_eq!(data.device_id, device_id.to_string());
+        assert_eq!(data.sensor_type, "temperature");
+        assert_eq!(data.unit, "°C");
+        assert!((18.0..=30.0).contains(&data.value), "{}", data.value);
+    }
+}
+
+#[tokio::test]
+async fn test_agent_provisions_and_posts_with_tokens_when_auth_enabled() {
+    let auth = MockAuth {
+        provisioning_token: "provisioning-token".into(),
+        device_token: "rfd_device-token".into(),
+        device_id: Uuid::new_v4(),
+    };
+    let mock = MockSensorServer::start_with_auth(auth.clone()).await;
+    // DEVICE_TOKEN yok: kayıtta alınan token kullanılır ve saklanır
+    let agent = spawn_agent_with(
+        &mock.url(),
+        Uuid::new_v4(),
+        &[("DEVICE_SERIAL", "10000000abcdef01"), ("PROVISIONING_TOKEN", &auth.provisioning_token)],
+    );
+
+    let deadline = Instant::now() + Duration::from_secs(20);
+    while mock.received_count() < 2 {
+        assert!(Instant::now() < deadline, "only {} readings received", mock.received_count());
+        sleep(Duration::from_millis(50)).await;
+    }
+
+    // Okumalar kayıtta dönen ID ile ve hiçbiri token'sız gelmedi
+    assert!(mock.drain().iter().all(|data| data.device_id == auth.device_id.to_string()));
+    assert_eq!(mock.rejected_count(), 0);
+    let stored = std::fs::read_to_string(agent.work_dir.join("device_token")).unwrap();
+    assert_eq!(stored.trim(), auth.device_token);
+}
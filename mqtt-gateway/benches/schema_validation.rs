@@ -0,0 +1,78 @@
+//! Şema doğrulama benchmark'ı
+//!
+//! Bir `*_reading` mesajının sadece parse edilmesini, parse + `schemas/`
+//! ile doğrulanmasını karşılaştırır (geçerli ve geçersiz payload). Doğrulamalı
+//! yol saniyede 100 bin mesajın altına düşerse benchmark başarısız olur.
+//!
+//! ```text
+//! cargo bench -p mqtt-gateway --bench schema_validation
+//! ```
+
+use std::path::Path;
+use std::time::Instant;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use shared_types::MqttMessage;
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/schema.rs"]
+mod schema;
+
+use schema::SchemaRegistry;
+
+/// Doğrulamalı yolun altına düşmemesi gereken hız (mesaj/s)
+const MIN_MESSAGES_PER_SEC: f64 = 100_000.0;
+
+fn message(is_valid: serde_json::Value) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "message_id": "550e8400-e29b-41d4-a716-446655440000",
+        "device_id": "550e8400-e29b-41d4-a716-446655440001",
+        "timestamp": "2024-11-13T21:30:00Z",
+        "message_type": "temperature_reading",
+        "payload": {
+            "sensor_id": "550e8400-e29b-41d4-a716-446655440002",
+            "value": "23.5",
+            "timestamp": "2024-11-13T21:30:00Z",
+            "is_valid": is_valid
+        }
+    }))
+    .unwrap()
+}
+
+/// Gateway'in yaptığı gibi parse et ve doğrula
+fn decode_and_validate(registry: &SchemaRegistry, raw: &[u8]) -> bool {
+    let msg: MqttMessage = serde_json::from_slice(raw).unwrap();
+    registry.validate(&msg.message_type, &msg.payload).is_ok()
+}
+
+fn bench_schema_validation(c: &mut Criterion) {
+    let schema_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+    let registry = SchemaRegistry::load(&schema_dir, []).unwrap();
+    let valid = message(serde_json::json!(true));
+    let invalid = message(serde_json::json!("true"));
+    assert!(decode_and_validate(&registry, &valid));
+    assert!(!decode_and_validate(&registry, &invalid));
+
+    const N: u32 = 100_000;
+    let start = Instant::now();
+    for _ in 0..N {
+        black_box(decode_and_validate(&registry, black_box(&valid)));
+    }
+    let rate = f64::from(N) / start.elapsed().as_secs_f64();
+    println!("decode + validate: {rate:.0} msg/s");
+    assert!(rate > MIN_MESSAGES_PER_SEC, "{rate:.0} msg/s is below {MIN_MESSAGES_PER_SEC}");
+
+    let mut group = c.benchmark_group("schema_validation");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("decode", |b| {
+        b.iter(|| serde_json::from_slice::<MqttMessage>(black_box(&valid)).unwrap())
+    });
+    group.bench_function("decode_validate", |b| b.iter(|| decode_and_validate(&registry, black_box(&valid))));
+    group.bench_function("decode_validate_invalid", |b| {
+        b.iter(|| decode_and_validate(&registry, black_box(&invalid)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_schema_validation);
+criterion_main!(benches);
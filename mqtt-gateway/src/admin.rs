@@ -0,0 +1,191 @@
+//! Yönetim HTTP Sunucusu (`GATEWAY_ADMIN_PORT`)
+//!
+//! Gateway'in MQTT dışında tek HTTP arayüzü. Sayaçlar event loop ve
+//! `handle_message` ile paylaşılır:
+//! - `GET /health` → broker bağlantısı ve mesaj sayaçları
+//! - `GET /clock-drift` → cihaz başına saat kayması histogramları (bkz. `drift`)
+//!
+//! Sayaçlar process başladığından beri birikir (sıfırlanmaz).
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
+use axum::{extract::State, routing::get, Json, Router};
+use serde::Serialize;
+use tokio::net::TcpListener;
+
+use crate::drift::{ClockDrift, ClockDriftReport};
+
+/// Gateway sayaçları
+///
+/// Klonlar aynı sayaçları paylaşır; event loop, batch task'ı ve admin
+/// sunucusu birer kopya tutar.
+#[derive(Debug, Clone, Default)]
+pub struct GatewayStats {
+    broker_connected: Arc<AtomicBool>,
+    messages_processed: Arc<AtomicU64>,
+    messages_forwarded: Arc<AtomicU64>,
+    messages_failed: Arc<AtomicU64>,
+    messages_invalid: Arc<AtomicU64>,
+    messages_oversized: Arc<AtomicU64>,
+    clock_drift: ClockDrift,
+}
+
+impl GatewayStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Event loop: ConnAck → `true`, bağlantı hatası → `false`
+    pub fn set_broker_connected(&self, connected: bool) {
+        self.broker_connected.store(connected, Ordering::Relaxed);
+    }
+
+    /// Broker'dan gelen her publish (filtrelenenler dahil)
+    pub fn message_processed(&self) {
+        self.messages_processed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// API server'ın kabul ettiği okumalar
+    pub fn forwarded(&self, count: u64) {
+        self.messages_forwarded.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// API server'a ulaşmayan veya reddedilen okumalar
+    pub fn failed(&self, count: u64) {
+        self.messages_failed.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Şema doğrulamasından geçemeyip dead-letter'a giden mesajlar
+    pub fn invalid(&self) {
+        self.messages_invalid.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `MAX_PAYLOAD_BYTES`'ı aştığı için forward edilmeyen mesajlar
+    pub fn oversized(&self) {
+        self.messages_oversized.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Cihaz başına saat kayması histogramları (`handle_message` yazar)
+    pub fn clock_drift(&self) -> &ClockDrift {
+        &self.clock_drift
+    }
+
+    /// Anlık değerler
+    pub fn health(&self) -> Health {
+        Health {
+            status: "ok",
+            broker_connected: self.broker_connected.load(Ordering::Relaxed),
+            messages_processed: self.messages_processed.load(Ordering::Relaxed),
+            messages_forwarded: self.messages_forwarded.load(Ordering::Relaxed),
+            messages_failed: self.messages_failed.load(Ordering::Relaxed),
+            messages_invalid: self.messages_invalid.load(Ordering::Relaxed),
+            messages_oversized: self.messages_oversized.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// `GET /health` response'u
+///
+/// Process ayaktaysa `status` her zaman "ok"dur; broker bağlantısı ayrıca
+/// `broker_connected` ile raporlanır.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Health {
+    pub status: &'static str,
+    pub broker_connected: bool,
+    pub messages_processed: u64,
+    pub messages_forwarded: u64,
+    pub messages_failed: u64,
+    pub messages_invalid: u64,
+    pub messages_oversized: u64,
+}
+
+/// Yönetim route'ları
+pub fn router(stats: GatewayStats) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/clock-drift", get(clock_drift))
+        .with_state(stats)
+}
+
+async fn health(State(stats): State<GatewayStats>) -> Json<Health> {
+    Json(stats.health())
+}
+
+async fn clock_drift(State(stats): State<GatewayStats>) -> Json<ClockDriftReport> {
+    Json(stats.clock_drift().snapshot())
+}
+
+/// Yönetim sunucusunu çalıştır
+///
+/// `main.rs` tarafından `tokio::spawn` ile başlatılır.
+pub async fn serve(listener: TcpListener, stats: GatewayStats) -> std::io::Result<()> {
+    axum::serve(listener, router(stats)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_health_reports_shared_counters() {
+        let stats = GatewayStats::new();
+        let app = router(stats.clone());
+
+        stats.set_broker_connected(true);
+        for _ in 0..3 {
+            stats.message_processed();
+        }
+        stats.forwarded(2);
+        stats.failed(1);
+        stats.invalid();
+        stats.oversized();
+
+        let response = app.oneshot(Request::get("/health").body(Body::empty()).unwrap()).await.unwrap();
+        assert!(response.status().is_success());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!({
+                "status": "ok",
+                "broker_connected": true,
+                "messages_processed": 3,
+                "messages_forwarded": 2,
+                "messages_failed": 1,
+                "messages_invalid": 1,
+                "messages_oversized": 1,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clock_drift_reports_per_device_histograms() {
+        let stats = GatewayStats::new();
+        let app = router(stats.clone());
+        let device = uuid::Uuid::new_v4();
+        let now = chrono::Utc::now();
+        stats.clock_drift().record(device, now, now + chrono::Duration::seconds(2));
+
+        let response = app.oneshot(Request::get("/clock-drift").body(Body::empty()).unwrap()).await.unwrap();
+        assert!(response.status().is_success());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["bucket_bounds_ms"][0], -60_000);
+        assert_eq!(
+            report["devices"][device.to_string()],
+            serde_json::json!({
+                "buckets": [0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+                "count": 1,
+                "sum_ms": -2000,
+                "min_ms": -2000,
+                "max_ms": -2000,
+                "last_ms": -2000,
+                "last_received_at": now,
+            })
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Toplu Okuma Gönderimi (`GATEWAY_BATCH_INGEST=true`)
+//!
+//! Her okuma için ayrı `POST /api/sensors` yerine okumalar biriktirilir ve
+//! `POST /v1/sensors/ingest`'e batch olarak gönderilir:
+//! - Batch `BATCH_MAX_ITEMS` okumaya ulaşınca hemen gönderilir
+//! - Aksi halde `GATEWAY_BATCH_FLUSH_MS` aralıkla ne birikmişse gönderilir
+//!
+//! API server 429, 5xx döndürürse veya ulaşılamazsa batch kuyruğa geri
+//! konur ve exponential backoff ile (`GATEWAY_BATCH_FLUSH_MS`'ten başlayıp
+//! `RETRY_MAX`'a kadar) yeniden denenir. Bekleyen okumalar
+//! `MAX_PENDING_ITEMS`'ı aşarsa en eskiler atılır.
+//!
+//! Okumaların trace_id'leri batch isteğine taşınmaz. `DEVICE_TOKENS`'ta
+//! token'ı olan cihazların okumaları kendi token'larıyla ayrı istekte
+//! gönderilir (API server token'lı batch'te başka cihazın okumasını reddeder).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use reqwest::{Client as HttpClient, StatusCode};
+use shared_types::IngestResult;
+use tokio::sync::mpsc;
+use tokio::time::{interval_at, sleep_until, Duration, Instant, MissedTickBehavior};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::admin::GatewayStats;
+use crate::SensorData;
+
+/// API server'ın tek istekte kabul ettiği maksimum okuma
+pub const BATCH_MAX_ITEMS: usize = 100;
+
+/// Yeniden denenecek okumaların üst sınırı (aşılırsa en eskiler atılır)
+pub const MAX_PENDING_ITEMS: usize = BATCH_MAX_ITEMS * 50;
+
+/// Yeniden deneme beklemesinin üst sınırı
+const RETRY_MAX: Duration = Duration::from_secs(60);
+
+/// Okumaları biriktirip API server'a toplu gönderen döngü
+///
+/// `main.rs` tarafından `tokio::spawn` ile başlatılır. Kanal kapandığında
+/// kalan okumaları bir kez daha gönderip döner. Gönderim sonuçları
+/// `stats`'a yazılır.
+pub async fn run(
+    mut rx: mpsc::Receiver<SensorData>,
+    http_client: HttpClient,
+    api_url: String,
+    device_tokens: Arc<HashMap<Uuid, String>>,
+    flush_every: Duration,
+    stats: GatewayStats,
+) {
+    let url = format!("{}/v1/sensors/ingest", api_url);
+    // İlk flush bir aralık sonra (interval'in anında dönen ilk tick'i atlanır)
+    let mut ticker = interval_at(Instant::now() + flush_every, flush_every);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut pending = Vec::with_capacity(BATCH_MAX_ITEMS);
+    let mut backoff = Backoff::new(flush_every);
+
+    loop {
+        let retry_at = backoff.until.unwrap_or_else(Instant::now);
+        tokio::select! {
+            item = rx.recv() => match item {
+                Some(data) => {
+                    pending.push(data);
+                    drop_oldest(&mut pending, &stats);
+                    if pending.len() >= BATCH_MAX_ITEMS && backoff.until.is_none() {
+                        let result = flush(&http_client, &url, &device_tokens, &mut pending, &stats).await;
+                        backoff.record(result, Instant::now());
+                    }
+                }
+                None => break,
+            },
+            _ = ticker.tick(), if backoff.until.is_none() => {
+                let result = flush(&http_client, &url, &device_tokens, &mut pending, &stats).await;
+                backoff.record(result, Instant::now());
+            }
+            _ = sleep_until(retry_at), if backoff.until.is_some() => {
+                let result = flush(&http_client, &url, &device_tokens, &mut pending, &stats).await;
+                backoff.record(result, Instant::now());
+            }
+        }
+    }
+    if flush(&http_client, &url, &device_tokens, &mut pending, &stats).await.is_err() {
+        warn!("⚠️  Dropping {} unsent readings on shutdown", pending.len());
+        stats.failed(pending.len() as u64);
+    }
+}
+
+/// Başarısız gönderimlerden sonra ne zaman yeniden deneneceği
+#[derive(Debug)]
+struct Backoff {
+    base: Duration,
+    attempts: u32,
+    /// Bu zamana kadar gönderim yapılmaz (`None` = bekleme yok)
+    until: Option<Instant>,
+}
+
+impl Backoff {
+    fn new(base: Duration) -> Self {
+        Self { base, attempts: 0, until: None }
+    }
+
+    /// Flush sonucunu kaydet: başarılıysa sıfırla, değilse beklemeyi iki katına çıkar
+    fn record(&mut self, result: Result<(), Requeued>, now: Instant) {
+        match result {
+            Ok(()) => {
+                self.attempts = 0;
+                self.until = None;
+            }
+            Err(Requeued) => {
+                self.attempts = self.attempts.saturating_add(1);
+                let delay = self.base.saturating_mul(2u32.saturating_pow(self.attempts - 1)).min(RETRY_MAX);
+                self.until = Some(now + delay);
+            }
+        }
+    }
+}
+
+/// Bekleyen okumalar `MAX_PENDING_ITEMS`'ı aştıysa en eskileri at
+fn drop_oldest(pending: &mut Vec<SensorData>, stats: &GatewayStats) {
+    let excess = pending.len().saturating_sub(MAX_PENDING_ITEMS);
+    if excess > 0 {
+        pending.drain(..excess);
+        warn!("⚠️  Batch queue full, dropped {} oldest readings", excess);
+        stats.failed(excess as u64);
+    }
+}
+
+/// Gönderilemeyen okumalar kuyruğa geri kondu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Requeued;
+
+/// Biriken okumaları gönder
+///
+/// Okumalar cihaz token'ına göre gruplanır: token'sız cihazlar tek istekte,
+/// token'ı olan her cihaz kendi isteğinde gönderilir (en fazla
+/// `BATCH_MAX_ITEMS`'lık parçalar). Yeniden denenebilir bir hata olursa
+/// kalan okumalar gönderilmeden `pending`'e geri konur.
+async fn flush(
+    http_client: &HttpClient,
+    url: &str,
+    device_tokens: &HashMap<Uuid, String>,
+    pending: &mut Vec<SensorData>,
+    stats: &GatewayStats,
+) -> Result<(), Requeued> {
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let mut groups: Vec<(Option<&str>, Vec<SensorData>)> = Vec::new();
+    for data in std::mem::take(pending) {
+        let token = data.device_id.parse::<Uuid>().ok().and_then(|id| device_tokens.get(&id)).map(String::as_str);
+        match groups.iter_mut().find(|(group, _)| *group == token) {
+            Some((_, items)) => items.push(data),
+            None => groups.push((token, vec![data])),
+        }
+    }
+    let batches = groups
+        .into_iter()
+        .flat_map(|(token, items)| items.chunks(BATCH_MAX_ITEMS).map(|chunk| (token, chunk.to_vec())).collect::<Vec<_>>());
+
+    let mut result = Ok(());
+    for (token, batch) in batches {
+        if result.is_err() {
+            pending.extend(batch);
+        } else if let Err(batch) = send(http_client, url, token, batch, stats).await {
+            pending.extend(batch);
+            result = Err(Requeued);
+        }
+    }
+    if result.is_err() {
+        warn!("⚠️  Requeued {} readings for retry", pending.len());
+    }
+    result
+}
+
+/// Tek batch isteği gönder, sonucu `stats`'a yaz
+///
+/// 429, 5xx veya bağlantı hatasında batch yeniden denenmek üzere geri döner;
+/// diğer hatalarda (ör. 400) okumalar başarısız sayılıp atılır.
+async fn send(
+    http_client: &HttpClient,
+    url: &str,
+    token: Option<&str>,
+    batch: Vec<SensorData>,
+    stats: &GatewayStats,
+) -> Result<(), Vec<SensorData>> {
+    let mut request = http_client.post(url);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    match request.json(&batch).send().await {
+        Ok(response) if response.status().is_success() => match response.json::<IngestResult>().await {
+            Ok(result) => {
+                info!("✅ Batch forwarded to API server: {} accepted", result.accepted);
+                stats.forwarded(result.accepted as u64);
+                stats.failed(result.rejected.len() as u64);
+                for (index, reason) in result.rejected {
+                    warn!("⚠️  Reading {} rejected: {}", index, reason);
+                }
+            }
+            Err(e) => {
+                // 2xx döndü: okumalar kabul edilmiş sayılır
+                warn!("⚠️  Unexpected ingest response: {}", e);
+                stats.forwarded(batch.len() as u64);
+            }
+        },
+        Ok(response) if is_retryable(response.status()) => {
+            warn!("⚠️  API server returned {} for batch of {}, will retry", response.status(), batch.len());
+            return Err(batch);
+        }
+        Ok(response) => {
+            warn!("⚠️  API server returned error for batch of {}: {}", batch.len(), response.status());
+            stats.failed(batch.len() as u64);
+        }
+        Err(e) => {
+            error!("❌ Failed to forward batch to API server: {}", e);
+            return Err(batch);
+        }
+    }
+    debug!("📦 Flushed {} readings", batch.len());
+    Ok(())
+}
+
+/// Yanıt yeniden denemeye değer mi? (rate limit veya sunucu hatası)
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderMap, response::IntoResponse, routing::post, Json, Router};
+    use std::sync::Mutex;
+
+    /// Gelen batch'ler: (Authorization header'ı, okuma sayısı)
+    type Received = Arc<Mutex<Vec<(Option<String>, usize)>>>;
+
+    fn reading(i: usize) -> SensorData {
+        reading_from("edge-agent-001", i)
+    }
+
+    fn reading_from(device_id: &str, i: usize) -> SensorData {
+        SensorData {
+            device_id: device_id.into(),
+            sensor_type: "temperature".into(),
+            value: i as f64,
+            unit: "°C".into(),
+            timestamp: "2024-01-20T10:30:00Z".into(),
+            metadata: None,
+            gateway_received_at: None,
+        }
+    }
+
+    /// Gelen batch'leri kaydeden sahte ingest endpoint'i
+    async fn mock_api() -> (String, Received) {
+        let received = Received::default();
+        let recorded = received.clone();
+        let app = Router::new().route(
+            "/v1/sensors/ingest",
+            post(move |headers: HeaderMap, Json(batch): Json<Vec<SensorData>>| async move {
+                let auth = headers.get("authorization").map(|v| v.to_str().unwrap().to_string());
+                recorded.lock().unwrap().push((auth, batch.len()));
+                Json(IngestResult { accepted: batch.len(), ..Default::default() })
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    /// İlk istekleri `statuses` ile reddeden, sonrakileri kabul eden ingest endpoint'i
+    ///
+    /// Kabul edilen okuma sayısı ve toplam istek sayısı döner.
+    async fn flaky_api(statuses: Vec<u16>) -> (String, Arc<Mutex<(usize, usize)>>) {
+        let counts = Arc::new(Mutex::new((0, 0)));
+        let recorded = counts.clone();
+        let statuses = Arc::new(Mutex::new(statuses.into_iter()));
+        let app = Router::new().route(
+            "/v1/sensors/ingest",
+            post(move |Json(batch): Json<Vec<SensorData>>| async move {
+                let mut counts = recorded.lock().unwrap();
+                counts.1 += 1;
+                match statuses.lock().unwrap().next() {
+                    Some(status) => axum::http::StatusCode::from_u16(status).unwrap().into_response(),
+                    None => {
+                        counts.0 += batch.len();
+                        Json(IngestResult { accepted: batch.len(), ..Default::default() }).into_response()
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, counts)
+    }
+
+    fn sizes(received: &Received) -> Vec<usize> {
+        received.lock().unwrap().iter().map(|(_, len)| *len).collect()
+    }
+
+    #[tokio::test]
+    async fn test_full_batches_sent_immediately_and_rest_on_close() {
+        let (url, received) = mock_api().await;
+        let (tx, rx) = mpsc::channel(16);
+        // Flush aralığı testten uzun: sadece dolu batch'ler ve kapanış gönderir
+        let stats = GatewayStats::new();
+        let task = tokio::spawn(run(rx, HttpClient::new(), url, Arc::default(), Duration::from_secs(3600), stats.clone()));
+
+        for i in 0..250 {
+            tx.send(reading(i)).await.unwrap();
+        }
+        drop(tx);
+        task.await.unwrap();
+        assert_eq!(sizes(&received), vec![100, 100, 50]);
+        assert_eq!((stats.health().messages_forwarded, stats.health().messages_failed), (250, 0));
+    }
+
+    #[tokio::test]
+    async fn test_partial_batch_flushed_on_interval() {
+        let (url, received) = mock_api().await;
+        let (tx, rx) = mpsc::channel(16);
+        tokio::spawn(run(rx, HttpClient::new(), url, Arc::default(), Duration::from_millis(50), GatewayStats::new()));
+
+        for i in 0..3 {
+            tx.send(reading(i)).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(sizes(&received), vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_and_failed_batches_retried() {
+        let (url, counts) = flaky_api(vec![429, 503]).await;
+        let (tx, rx) = mpsc::channel(16);
+        let stats = GatewayStats::new();
+        tokio::spawn(run(rx, HttpClient::new(), url, Arc::default(), Duration::from_millis(20), stats.clone()));
+
+        for i in 0..5 {
+            tx.send(reading(i)).await.unwrap();
+        }
+        // 20 ms tick + 20 ms + 40 ms backoff
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(*counts.lock().unwrap(), (5, 3));
+        assert_eq!((stats.health().messages_forwarded, stats.health().messages_failed), (5, 0));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_batch_not_retried() {
+        let (url, counts) = flaky_api(vec![400]).await;
+        let (tx, rx) = mpsc::channel(16);
+        let stats = GatewayStats::new();
+        tokio::spawn(run(rx, HttpClient::new(), url, Arc::default(), Duration::from_millis(20), stats.clone()));
+
+        tx.send(reading(0)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(*counts.lock().unwrap(), (0, 1));
+        assert_eq!(stats.health().messages_failed, 1);
+    }
+
+    #[test]
+    fn test_backoff_doubles_until_cap_and_resets() {
+        let now = Instant::now();
+        let mut backoff = Backoff::new(Duration::from_secs(1));
+        let mut delays = Vec::new();
+        for _ in 0..8 {
+            backoff.record(Err(Requeued), now);
+            delays.push(backoff.until.unwrap() - now);
+        }
+        assert_eq!(delays.iter().map(Duration::as_secs).collect::<Vec<_>>(), [1, 2, 4, 8, 16, 32, 60, 60]);
+
+        backoff.record(Ok(()), now);
+        assert_eq!((backoff.attempts, backoff.until), (0, None));
+    }
+
+    #[test]
+    fn test_queue_overflow_drops_oldest() {
+        let stats = GatewayStats::new();
+        let mut pending: Vec<_> = (0..MAX_PENDING_ITEMS + 3).map(reading).collect();
+        drop_oldest(&mut pending, &stats);
+        assert_eq!(pending.len(), MAX_PENDING_ITEMS);
+        assert_eq!(pending[0].value, 3.0);
+        assert_eq!(stats.health().messages_failed, 3);
+    }
+
+    #[tokio::test]
+    async fn test_readings_grouped_by_device_token() {
+        let (url, received) = mock_api().await;
+        let (tx, rx) = mpsc::channel(16);
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let tokens = Arc::new(HashMap::from([(a, "rfd_a".to_string()), (b, "rfd_b".to_string())]));
+        let task = tokio::spawn(run(rx, HttpClient::new(), url, tokens, Duration::from_secs(3600), GatewayStats::new()));
+
+        for (i, device) in [a.to_string(), "edge-agent-001".into(), b.to_string(), a.to_string(), Uuid::new_v4().to_string()]
+            .iter()
+            .enumerate()
+        {
+            tx.send(reading_from(device, i)).await.unwrap();
+        }
+        drop(tx);
+        task.await.unwrap();
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![(Some("Bearer rfd_a".into()), 2), (None, 2), (Some("Bearer rfd_b".into()), 1)]
+        );
+    }
+}
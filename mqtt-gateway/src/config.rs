@@ -1,8 +1,19 @@
 //! MQTT Gateway Yapılandırması
 //!
 //! MQTT broker bağlantı bilgileri ve gateway ayarları.
+//! Env var'lardan ve (varsa) TOML config dosyasından okunur; env var'lar önceliklidir.
+
+use std::collections::HashMap;
 
 use serde::Deserialize;
+use shared_types::conventions::{GATEWAY_STATUS_TOPIC_FILTER, RESPONSE_TOPIC_FILTER, SENSOR_TOPIC_FILTER, STATUS_TOPIC_FILTER};
+use shared_types::config::FileConfig;
+use shared_types::merge_config;
+use shared_types::mqtt::{MqttError, MqttProtocol, SessionOptions};
+use uuid::Uuid;
+
+/// MQTT paket başlığı, topic ve v5 property'leri için payload sınırına eklenen pay (byte)
+pub const MQTT_PACKET_OVERHEAD: usize = 1024;
 
 /// MQTT Gateway yapılandırması
 /// 
@@ -13,10 +24,32 @@ use serde::Deserialize;
 /// MQTT_BROKER_HOST=localhost
 /// MQTT_BROKER_PORT=1883
 /// MQTT_CLIENT_ID=rustyflow-gateway
-/// MQTT_TOPICS=sensors/#,devices/#
+/// MQTT_TOPICS=sensors/#,devices/+/status,devices/+/responses,rustyflow/gateway/+/status
+/// MQTT_PROTOCOL=v4
+/// MQTT_KEEP_ALIVE_SECS=5
+/// MQTT_CLEAN_SESSION=true
+/// MQTT_MAX_INFLIGHT=100
+/// MQTT_REQUEST_CHANNEL_CAPACITY=10
+/// GATEWAY_BATCH_INGEST=false
+/// GATEWAY_BATCH_FLUSH_MS=1000
+/// GATEWAY_QUEUE_CAPACITY=1000
+/// DEVICE_ALLOWLIST=
+/// DEVICE_BLOCKLIST=
+/// DEVICE_FILTER_FILE=
+/// DEVICE_TOKENS=
+/// GATEWAY_ADMIN_PORT=9090
+/// GATEWAY_STATUS_INTERVAL_SECS=30
+/// SCHEMA_DIR=schemas
+/// SCHEMA_SKIP_TYPES=
+/// DEAD_LETTER_TOPIC=rustyflow/dead-letter
+/// MAX_PAYLOAD_BYTES=65536
+/// OVERSIZED_DEAD_LETTER=false
 /// RUST_LOG=info
 /// ```
-#[derive(Debug, Clone, Deserialize)]
+/// 
+/// Aynı ayarlar TOML dosyasında küçük harfli alan adlarıyla verilebilir
+/// (bkz. `rustyflow.example.toml`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct Config {
     /// MQTT broker adresi
     /// 
@@ -48,12 +81,213 @@ pub struct Config {
     /// 
     /// Wildcard destekler: # (tüm alt seviyeler), + (tek seviye)
     /// 
-    /// Varsayılan: "sensors/#"
+    /// `rustyflow/gateway/+/status` gateway'lerin kendi durum mesajlarıdır
+    /// (bkz. `status`); çıkarılırsa gateway'ler shadow'da görünmez.
+    /// 
+    /// Varsayılan: "sensors/#,devices/+/status,devices/+/responses,rustyflow/gateway/+/status"
     /// 
     /// Örnek: `MQTT_TOPICS=sensors/#,devices/+/status`
     #[serde(default = "default_topics")]
     pub mqtt_topics: String,
 
+    /// MQTT protokol sürümü
+    /// 
+    /// `v5`'te okumaların `device_id`/`sensor_type` user property'leri
+    /// varsa cihaz filtresi payload parse edilmeden uygulanır ve sensör
+    /// tipi topic yerine property'den alınır. Broker v5'i reddederse v4
+    /// ile yeniden bağlanılır.
+    /// 
+    /// Varsayılan: v4
+    /// 
+    /// Örnek: `MQTT_PROTOCOL=v5`
+    #[serde(default)]
+    pub mqtt_protocol: MqttProtocol,
+
+    /// Broker'ın bağlantıyı canlı sayması için ping aralığı (saniye, en az 5)
+    /// 
+    /// Hücresel gibi gecikmeli bağlantılarda kısa keep-alive sürekli
+    /// yeniden bağlanmaya yol açar; 30-60 önerilir.
+    /// 
+    /// Varsayılan: 5
+    /// 
+    /// Örnek: `MQTT_KEEP_ALIVE_SECS=60`
+    #[serde(default = "default_keep_alive_secs")]
+    pub mqtt_keep_alive_secs: u64,
+
+    /// Her bağlantıda temiz oturum açılsın mı?
+    /// 
+    /// `false` ile broker aboneliklerini ve gateway çevrimdışıyken gelen
+    /// QoS 1/2 mesajları saklar, yeniden bağlanınca teslim eder. Bunun için
+    /// `MQTT_CLIENT_ID` sabit kalmalıdır; broker kendi kuyruk sınırından
+    /// fazlasını atar. Gateway abonelikleri QoS 0 olduğundan okumalar
+    /// kuyruklanmaz, sadece abonelikler korunur.
+    /// 
+    /// Varsayılan: true
+    /// 
+    /// Örnek: `MQTT_CLEAN_SESSION=false`
+    #[serde(default = "default_clean_session")]
+    pub mqtt_clean_session: bool,
+
+    /// Onay bekleyebilecek en fazla QoS 1/2 publish (1..=65535)
+    /// 
+    /// Varsayılan: 100
+    /// 
+    /// Örnek: `MQTT_MAX_INFLIGHT=500`
+    #[serde(default = "default_max_inflight")]
+    pub mqtt_max_inflight: u32,
+
+    /// Client'tan event loop'a giden istek kanalının kapasitesi (en az 1)
+    /// 
+    /// Dolunca publish/subscribe çağrıları event loop yer açana kadar bekler.
+    /// 
+    /// Varsayılan: 10
+    /// 
+    /// Örnek: `MQTT_REQUEST_CHANNEL_CAPACITY=100`
+    #[serde(default = "default_request_channel_capacity")]
+    pub mqtt_request_channel_capacity: usize,
+
+    /// Okumalar toplu mu gönderilsin?
+    /// 
+    /// `true` ise okumalar biriktirilip `POST /v1/sensors/ingest`'e
+    /// batch olarak gönderilir; `false` ise her biri `POST /api/sensors`'a.
+    /// 
+    /// Varsayılan: false
+    /// 
+    /// Örnek: `GATEWAY_BATCH_INGEST=true`
+    #[serde(default)]
+    pub gateway_batch_ingest: bool,
+
+    /// Batch modunda biriken okumaların gönderilme aralığı (milisaniye)
+    /// 
+    /// Batch 100 okumaya ulaşırsa beklemeden gönderilir.
+    /// 
+    /// Varsayılan: 1000
+    /// 
+    /// Örnek: `GATEWAY_BATCH_FLUSH_MS=500`
+    #[serde(default = "default_batch_flush_ms")]
+    pub gateway_batch_flush_ms: u64,
+
+    /// Öncelik başına işlenmeyi bekleyen en fazla mesaj
+    /// 
+    /// Event loop mesajları önceliklerine göre ayrı kuyruklara yazar;
+    /// yüksek öncelikliler önce işlenir (bkz. `priority`). Kuyruk doluysa
+    /// event loop yer açılana kadar bekler.
+    /// 
+    /// Varsayılan: 1000
+    /// 
+    /// Örnek: `GATEWAY_QUEUE_CAPACITY=5000`
+    #[serde(default = "default_queue_capacity")]
+    pub gateway_queue_capacity: usize,
+
+    /// Sadece bu cihazlardan gelen mesajlar işlenir (virgülle ayrılmış)
+    /// 
+    /// Her öğe bir device UUID'si veya topic'teki cihaz segmentine
+    /// (`sensors/{cihaz}/...`) uygulanan glob'dur (`*`, `?`).
+    /// 
+    /// Varsayılan: "" (boş = tüm cihazlar)
+    /// 
+    /// Örnek: `DEVICE_ALLOWLIST=rpi-*,550e8400-e29b-41d4-a716-446655440000`
+    #[serde(default)]
+    pub device_allowlist: String,
+
+    /// Bu cihazlardan gelen mesajlar atılır (allowlist'ten önceliklidir)
+    /// 
+    /// Varsayılan: ""
+    /// 
+    /// Örnek: `DEVICE_BLOCKLIST=test-*`
+    #[serde(default)]
+    pub device_blocklist: String,
+
+    /// Allowlist/blocklist'in okunduğu ve değişiklikte yeniden yüklendiği TOML dosyası
+    /// 
+    /// Ayarlıysa `DEVICE_ALLOWLIST`/`DEVICE_BLOCKLIST` yerine kullanılır
+    /// (bkz. `filter::DeviceLists`).
+    /// 
+    /// Örnek: `DEVICE_FILTER_FILE=device_filter.toml`
+    pub device_filter_file: Option<String>,
+
+    /// Cihaz başına API token'ları (`<device_id>:<token>`, virgülle ayrılmış)
+    /// 
+    /// Token'lar `POST /v1/devices/{id}/tokens` ile alınır. Listedeki
+    /// cihazların okumaları, shadow ve hata raporları kendi token'larıyla
+    /// (`Authorization: Bearer`) gönderilir; API server isteği o cihaza
+    /// bağlar. Listede olmayan cihazlar token'sız gönderilir.
+    /// 
+    /// Varsayılan: "" (token yok)
+    /// 
+    /// Örnek: `DEVICE_TOKENS=550e8400-e29b-41d4-a716-446655440000:rfd_4f3c...`
+    #[serde(default)]
+    pub device_tokens: String,
+
+    /// Yönetim HTTP sunucusunun portu (`GET /health`)
+    /// 
+    /// Varsayılan: 9090
+    /// 
+    /// Örnek: `GATEWAY_ADMIN_PORT=9191`
+    #[serde(default = "default_admin_port")]
+    pub gateway_admin_port: u16,
+
+    /// Gateway durum mesajının publish aralığı (saniye)
+    /// 
+    /// Durum `rustyflow/gateway/{MQTT_CLIENT_ID}/status`'a retained olarak
+    /// gider; bağlantı koparsa broker aynı topic'e "çevrimdışı" LWT'si yayınlar.
+    /// 
+    /// Varsayılan: 30
+    /// 
+    /// Örnek: `GATEWAY_STATUS_INTERVAL_SECS=10`
+    #[serde(default = "default_status_interval_secs")]
+    pub gateway_status_interval_secs: u64,
+
+    /// Payload şemalarının okunduğu dizin (`<mesaj tipi>.json`)
+    /// 
+    /// Mesajlar forward edilmeden önce doğrulanır, geçersizler
+    /// `DEAD_LETTER_TOPIC`'e gider (bkz. `schema`). Ayarlı değilse
+    /// shared-types'a gömülü şemalar kullanılır (`temperature_reading`,
+    /// `humidity_reading`, `motion_reading`, `status_update`).
+    /// 
+    /// Örnek: `SCHEMA_DIR=schemas`
+    pub schema_dir: Option<String>,
+
+    /// Doğrulanmayacak mesaj tipleri (virgülle ayrılmış)
+    /// 
+    /// `sensor_reading` tüm `*_reading` tiplerini atlar.
+    /// 
+    /// Varsayılan: ""
+    /// 
+    /// Örnek: `SCHEMA_SKIP_TYPES=motion_reading,status_update`
+    #[serde(default)]
+    pub schema_skip_types: String,
+
+    /// Şema doğrulamasından geçemeyen mesajların publish edildiği topic
+    /// 
+    /// Varsayılan: "rustyflow/dead-letter"
+    /// 
+    /// Örnek: `DEAD_LETTER_TOPIC=gateway-001/dead-letter`
+    #[serde(default = "default_dead_letter_topic")]
+    pub dead_letter_topic: String,
+
+    /// Forward edilecek en büyük payload (byte; LZ4 için başlıktaki açılmış boyut)
+    /// 
+    /// Daha büyük mesajlar parse edilmeden atılır ve `/health`'te
+    /// `messages_oversized` olarak sayılır. MQTT client'ın en büyük paket
+    /// boyutu da bu değerden türetilir (bkz. `mqtt_session`).
+    /// 
+    /// Varsayılan: 65536 (64 KB)
+    /// 
+    /// Örnek: `MAX_PAYLOAD_BYTES=16384`
+    #[serde(default = "default_max_payload_bytes")]
+    pub max_payload_bytes: usize,
+
+    /// Büyük payload'lar `DEAD_LETTER_TOPIC`'e de yazılsın mı?
+    /// 
+    /// Kayıtta payload'un tamamı değil, sadece başı bulunur.
+    /// 
+    /// Varsayılan: false
+    /// 
+    /// Örnek: `OVERSIZED_DEAD_LETTER=true`
+    #[serde(default)]
+    pub oversized_dead_letter: bool,
+
     /// Logging seviyesi
     /// 
     /// Geçerli değerler: error, warn, info, debug, trace
@@ -69,55 +303,254 @@ pub struct Config {
 fn default_broker_host() -> String { "localhost".into() }
 fn default_broker_port() -> u16 { 1883 }
 fn default_client_id() -> String { "rustyflow-gateway".into() }
-fn default_topics() -> String { "sensors/#".into() }
+fn default_topics() -> String {
+    [SENSOR_TOPIC_FILTER, STATUS_TOPIC_FILTER, RESPONSE_TOPIC_FILTER, GATEWAY_STATUS_TOPIC_FILTER].join(",")
+}
+fn default_keep_alive_secs() -> u64 { 5 }
+fn default_clean_session() -> bool { true }
+fn default_max_inflight() -> u32 { 100 }
+fn default_request_channel_capacity() -> usize { 10 }
+fn default_batch_flush_ms() -> u64 { 1000 }
+fn default_queue_capacity() -> usize { 1000 }
+fn default_admin_port() -> u16 { 9090 }
+fn default_status_interval_secs() -> u64 { 30 }
+fn default_dead_letter_topic() -> String { "rustyflow/dead-letter".into() }
+fn default_max_payload_bytes() -> usize { 64 * 1024 }
 fn default_log() -> String { "info".into() }
 
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            mqtt_broker_host: default_broker_host(),
+            mqtt_broker_port: default_broker_port(),
+            mqtt_client_id: default_client_id(),
+            mqtt_topics: default_topics(),
+            mqtt_protocol: MqttProtocol::default(),
+            mqtt_keep_alive_secs: default_keep_alive_secs(),
+            mqtt_clean_session: default_clean_session(),
+            mqtt_max_inflight: default_max_inflight(),
+            mqtt_request_channel_capacity: default_request_channel_capacity(),
+            gateway_batch_ingest: false,
+            gateway_batch_flush_ms: default_batch_flush_ms(),
+            gateway_queue_capacity: default_queue_capacity(),
+            device_allowlist: String::new(),
+            device_blocklist: String::new(),
+            device_filter_file: None,
+            device_tokens: String::new(),
+            gateway_admin_port: default_admin_port(),
+            gateway_status_interval_secs: default_status_interval_secs(),
+            schema_dir: None,
+            schema_skip_types: String::new(),
+            dead_letter_topic: default_dead_letter_topic(),
+            max_payload_bytes: default_max_payload_bytes(),
+            oversized_dead_letter: false,
+            log_level: default_log(),
+        }
+    }
+}
+
+impl FileConfig for Config {
+    fn merge(base: Self, override_: Self) -> Self {
+        merge_config!(
+            Config {
+                mqtt_broker_host,
+                mqtt_broker_port,
+                mqtt_client_id,
+                mqtt_topics,
+                mqtt_protocol,
+                mqtt_keep_alive_secs,
+                mqtt_clean_session,
+                mqtt_max_inflight,
+                mqtt_request_channel_capacity,
+                gateway_batch_ingest,
+                gateway_batch_flush_ms,
+                gateway_queue_capacity,
+                device_allowlist,
+                device_blocklist,
+                device_filter_file,
+                device_tokens,
+                gateway_admin_port,
+                gateway_status_interval_secs,
+                schema_dir,
+                schema_skip_types,
+                dead_letter_topic,
+                max_payload_bytes,
+                oversized_dead_letter,
+                log_level,
+            },
+            base,
+            override_,
+        )
+    }
+}
+
 impl Config {
     /// Yapılandırmayı yükle
     /// 
     /// Yükleme sırası:
     /// 1. .env dosyasını oku
-    /// 2. Ortam değişkenlerini parse et
-    /// 3. Varsayılan değerleri kullan
+    /// 2. Config dosyası varsa (`RUSTYFLOW_CONFIG_FILE` / `./rustyflow.toml`) `from_file` ile oku
+    ///    (okunamazsa hata döner, gateway başlamaz)
+    /// 3. Yoksa ortam değişkenlerini parse et
+    /// 4. Varsayılan değerleri kullan
     /// 
     /// # Örnek
     /// ```ignore
-    /// let config = Config::load();
+    /// let config = Config::load()?;
     /// println!("MQTT Broker: {}:{}", config.mqtt_broker_host, config.mqtt_broker_port);
     /// ```
-    pub fn load() -> Self {
+    pub fn load() -> shared_types::Result<Self> {
         // .env dosyasını yükle (hata görmezden gel)
         let _ = dotenvy::dotenv();
 
-        // Ortam değişkenlerini Config struct'ına dönüştür
-        let mut cfg: Config = envy::from_env().unwrap_or_else(|_| Config {
-            mqtt_broker_host: default_broker_host(),
-            mqtt_broker_port: default_broker_port(),
-            mqtt_client_id: default_client_id(),
-            mqtt_topics: default_topics(),
-            log_level: default_log(),
-        });
+        // Config dosyası + ortam değişkenleri
+        // Okunamayan config dosyası hata döner (servis başlamaz)
+        let mut cfg = match shared_types::config::config_file_path() {
+            Some(path) => Self::from_file(&path)?,
+            None => envy::from_env().unwrap_or_default(),
+        };
 
         // RUST_LOG özel işlemi
         if let Ok(level) = std::env::var("RUST_LOG") {
             cfg.log_level = level;
         }
 
-        cfg
+        Ok(cfg)
     }
 
     /// Topic listesini parse et (virgülle ayrılmış → Vec<String>)
     /// 
     /// # Örnek
     /// ```ignore
-    /// let config = Config::load();
+    /// let config = Config::load()?;
     /// let topics = config.parse_topics(); // ["sensors/#", "devices/+/status"]
     /// ```
     pub fn parse_topics(&self) -> Vec<String> {
-        self.mqtt_topics
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect()
+        split_list(&self.mqtt_topics)
+    }
+
+    /// MQTT oturum ayarları (keep-alive, clean session, inflight, istek kanalı)
+    /// 
+    /// Sınır dışı değerler hata döner (bkz. `SessionOptions::from_config`).
+    /// En büyük paket boyutu `MAX_PAYLOAD_BYTES` + `MQTT_PACKET_OVERHEAD`'dir:
+    /// sınırı biraz aşan mesajlar bağlantıyı koparmadan gelir ve
+    /// `handle_message`'da sayılarak atılır.
+    pub fn mqtt_session(&self) -> Result<SessionOptions, MqttError> {
+        SessionOptions::from_config(
+            self.mqtt_keep_alive_secs,
+            self.mqtt_clean_session,
+            self.mqtt_max_inflight,
+            self.mqtt_request_channel_capacity,
+        )
+        .map(|session| session.with_max_packet_size(self.max_payload_bytes.saturating_add(MQTT_PACKET_OVERHEAD)))
+    }
+
+    /// Cihaz token'larını parse et (`DEVICE_TOKENS`)
+    /// 
+    /// Geçersiz veya tekrarlanan cihaz ID'si ve boş token hata döner; hata
+    /// mesajlarına token yazılmaz.
+    pub fn parse_device_tokens(&self) -> Result<HashMap<Uuid, String>, String> {
+        let mut tokens = HashMap::new();
+        for entry in split_list(&self.device_tokens) {
+            let (id, token) = entry.split_once(':').unwrap_or((&entry, ""));
+            let id = id.trim().parse::<Uuid>().map_err(|e| format!("DEVICE_TOKENS: invalid device id {:?}: {e}", id.trim()))?;
+            let token = token.trim();
+            if token.is_empty() {
+                return Err(format!("DEVICE_TOKENS: missing token for {id} (expected <device_id>:<token>)"));
+            }
+            if tokens.insert(id, token.to_string()).is_some() {
+                return Err(format!("DEVICE_TOKENS: duplicate device id {id}"));
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Doğrulanmayacak mesaj tiplerini parse et (`SCHEMA_SKIP_TYPES`)
+    pub fn parse_schema_skip_types(&self) -> Vec<String> {
+        split_list(&self.schema_skip_types)
+    }
+}
+
+/// Virgülle ayrılmış listeyi parse et (boş öğeler atlanır)
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_from_file_env_takes_precedence() {
+        let path = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/rustyflow.example.toml"));
+        let env = [("MQTT_BROKER_HOST".to_string(), "mqtt.lan".to_string())];
+        let cfg = Config::from_file_with_env(path, env).unwrap();
+
+        assert_eq!(cfg.mqtt_broker_host, "mqtt.lan");
+        assert_eq!(cfg.mqtt_client_id, "gateway-001");
+        assert_eq!(cfg.parse_topics(), ["sensors/#", "devices/+/status", "devices/+/responses", "rustyflow/gateway/+/status"]);
+        assert!(cfg.gateway_batch_ingest);
+        assert_eq!(cfg.gateway_batch_flush_ms, 1000);
+        assert_eq!(cfg.gateway_queue_capacity, 2000);
+        let session = cfg.mqtt_session().unwrap();
+        assert_eq!((session.keep_alive.as_secs(), session.clean_session), (30, false));
+        assert_eq!((session.max_inflight, session.request_channel_capacity), (100, 10));
+    }
+
+    #[test]
+    fn test_mqtt_session_env_parsing_and_bounds() {
+        let env = |pairs: &[(&str, &str)]| -> Config {
+            envy::from_iter(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string()))).unwrap()
+        };
+        let cfg = env(&[
+            ("MQTT_KEEP_ALIVE_SECS", "60"),
+            ("MQTT_CLEAN_SESSION", "false"),
+            ("MQTT_MAX_INFLIGHT", "65535"),
+            ("MQTT_REQUEST_CHANNEL_CAPACITY", "256"),
+        ]);
+        let session = cfg.mqtt_session().unwrap();
+        assert_eq!(session.keep_alive, std::time::Duration::from_secs(60));
+        assert!(!session.clean_session);
+        assert_eq!((session.max_inflight, session.request_channel_capacity), (65535, 256));
+
+        assert_eq!(
+            env(&[]).mqtt_session().unwrap(),
+            SessionOptions::default().with_max_packet_size(64 * 1024 + MQTT_PACKET_OVERHEAD)
+        );
+        let cfg = env(&[("MAX_PAYLOAD_BYTES", "1024"), ("OVERSIZED_DEAD_LETTER", "true")]);
+        assert!(cfg.oversized_dead_letter);
+        assert_eq!(cfg.mqtt_session().unwrap().max_packet_size, Some(1024 + MQTT_PACKET_OVERHEAD));
+        assert!(env(&[("MQTT_KEEP_ALIVE_SECS", "4")]).mqtt_session().is_err());
+        assert!(env(&[("MQTT_MAX_INFLIGHT", "65536")]).mqtt_session().is_err());
+        assert!(env(&[("MQTT_REQUEST_CHANNEL_CAPACITY", "0")]).mqtt_session().is_err());
+    }
+
+    #[test]
+    fn test_merge_takes_non_default_override_fields() {
+        let base = Config { mqtt_client_id: "gateway-001".into(), mqtt_broker_port: 8883, ..Config::default() };
+        let override_ = Config { mqtt_broker_port: 1884, ..Config::default() };
+
+        let merged = Config::merge(base, override_);
+        assert_eq!((merged.mqtt_client_id.as_str(), merged.mqtt_broker_port), ("gateway-001", 1884));
+    }
+
+    #[test]
+    fn test_parse_device_tokens() {
+        let tokens = |list: &str| Config { device_tokens: list.into(), ..Config::default() }.parse_device_tokens();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(tokens("").unwrap().is_empty());
+        let parsed = tokens(&format!("{a}:rfd_a, {b} : rfd_b,")).unwrap();
+        assert_eq!(parsed, HashMap::from([(a, "rfd_a".to_string()), (b, "rfd_b".to_string())]));
+
+        assert!(tokens("pi-01:rfd_a").unwrap_err().contains("invalid device id"));
+        assert!(tokens(&a.to_string()).unwrap_err().contains("missing token"));
+        assert!(tokens(&format!("{a}:")).unwrap_err().contains("missing token"));
+        let duplicate = tokens(&format!("{a}:rfd_secret,{a}:rfd_other")).unwrap_err();
+        assert!(duplicate.contains("duplicate") && !duplicate.contains("rfd_"));
     }
 }
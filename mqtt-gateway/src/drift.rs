@@ -0,0 +1,180 @@
+//! Cihaz saat kayması (clock drift)
+//!
+//! Her okuma için `gateway_received_at - timestamp` (ms) cihaz başına bir
+//! histograma yazılır ve `GET /clock-drift` ile sunulur. Pozitif değer
+//! cihaz saatinin geride kaldığını (veya okumanın gecikmeli geldiğini),
+//! negatif değer cihaz saatinin ileride olduğunu gösterir.
+//!
+//! Histogramlar process başladığından beri birikir (sıfırlanmaz). En fazla
+//! `MAX_TRACKED_DEVICES` cihaz izlenir; yeni bir cihaz geldiğinde en uzun
+//! süredir okuma gelmeyen cihazın histogramı atılır.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Kova üst sınırları (ms, dahil); son kova `+Inf`
+pub const BUCKET_BOUNDS_MS: [i64; 10] = [-60_000, -5_000, -1_000, 0, 100, 500, 1_000, 5_000, 60_000, 3_600_000];
+
+/// Bu değeri (mutlak, ms) aşan kayma `warn` ile loglanır
+pub const WARN_DRIFT_MS: i64 = 5_000;
+
+/// Histogramı tutulan en fazla cihaz
+pub const MAX_TRACKED_DEVICES: usize = 10_000;
+
+/// Gateway'in aldığı zamandan cihazın bildirdiği zamanı çıkar (ms)
+pub fn clock_drift_ms(gateway_received_at: DateTime<Utc>, device_timestamp: DateTime<Utc>) -> i64 {
+    (gateway_received_at - device_timestamp).num_milliseconds()
+}
+
+/// Tek cihazın kayma histogramı
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DriftHistogram {
+    /// `BUCKET_BOUNDS_MS` sırasıyla kova sayıları, son eleman `+Inf`
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum_ms: i64,
+    pub min_ms: i64,
+    pub max_ms: i64,
+    /// Son okumanın kayması
+    pub last_ms: i64,
+    /// Son okumanın gateway'e geldiği zaman
+    pub last_received_at: DateTime<Utc>,
+}
+
+impl DriftHistogram {
+    fn new(drift_ms: i64, received_at: DateTime<Utc>) -> Self {
+        let mut histogram = Self {
+            buckets: vec![0; BUCKET_BOUNDS_MS.len() + 1],
+            count: 0,
+            sum_ms: 0,
+            min_ms: drift_ms,
+            max_ms: drift_ms,
+            last_ms: drift_ms,
+            last_received_at: received_at,
+        };
+        histogram.record(drift_ms, received_at);
+        histogram
+    }
+
+    fn record(&mut self, drift_ms: i64, received_at: DateTime<Utc>) {
+        let bucket = BUCKET_BOUNDS_MS.iter().position(|&bound| drift_ms <= bound).unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum_ms = self.sum_ms.saturating_add(drift_ms);
+        self.min_ms = self.min_ms.min(drift_ms);
+        self.max_ms = self.max_ms.max(drift_ms);
+        self.last_ms = drift_ms;
+        self.last_received_at = self.last_received_at.max(received_at);
+    }
+}
+
+/// Cihaz başına kayma histogramları
+///
+/// Klonlar aynı histogramları paylaşır (`GatewayStats` ile birlikte taşınır).
+#[derive(Debug, Clone)]
+pub struct ClockDrift {
+    devices: Arc<Mutex<BTreeMap<Uuid, DriftHistogram>>>,
+    max_devices: usize,
+}
+
+impl Default for ClockDrift {
+    fn default() -> Self {
+        Self::with_max_devices(MAX_TRACKED_DEVICES)
+    }
+}
+
+impl ClockDrift {
+    /// En fazla `max_devices` cihaz izleyen histogramlar
+    pub fn with_max_devices(max_devices: usize) -> Self {
+        Self { devices: Default::default(), max_devices: max_devices.max(1) }
+    }
+
+    /// Kaymayı hesapla, cihazın histogramına yaz ve döndür
+    pub fn record(&self, device_id: Uuid, gateway_received_at: DateTime<Utc>, device_timestamp: DateTime<Utc>) -> i64 {
+        let drift_ms = clock_drift_ms(gateway_received_at, device_timestamp);
+        let mut devices = self.devices.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(histogram) = devices.get_mut(&device_id) {
+            histogram.record(drift_ms, gateway_received_at);
+            return drift_ms;
+        }
+        if devices.len() >= self.max_devices {
+            let stalest = devices.iter().min_by_key(|(_, histogram)| histogram.last_received_at).map(|(id, _)| *id);
+            if let Some(stalest) = stalest {
+                devices.remove(&stalest);
+            }
+        }
+        devices.insert(device_id, DriftHistogram::new(drift_ms, gateway_received_at));
+        drift_ms
+    }
+
+    /// `GET /clock-drift` response'u
+    pub fn snapshot(&self) -> ClockDriftReport {
+        ClockDriftReport {
+            bucket_bounds_ms: BUCKET_BOUNDS_MS.to_vec(),
+            devices: self.devices.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+}
+
+/// `GET /clock-drift` response'u
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClockDriftReport {
+    pub bucket_bounds_ms: Vec<i64>,
+    pub devices: BTreeMap<Uuid, DriftHistogram>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_clock_drift_ms() {
+        let received = DateTime::parse_from_rfc3339("2025-01-01T12:00:01.250Z").unwrap().with_timezone(&Utc);
+        assert_eq!(clock_drift_ms(received, received - Duration::milliseconds(1_250)), 1_250);
+        assert_eq!(clock_drift_ms(received, received), 0);
+        // Cihaz saati ileride: negatif kayma
+        assert_eq!(clock_drift_ms(received, received + Duration::seconds(3)), -3_000);
+    }
+
+    #[test]
+    fn test_histogram_buckets_negative_and_positive_drift() {
+        let drift = ClockDrift::default();
+        let device = Uuid::new_v4();
+        let now = Utc::now();
+
+        for offset_ms in [-120_000, -2_000, 0, 50, 7_200_000] {
+            assert_eq!(drift.record(device, now, now - Duration::milliseconds(offset_ms)), offset_ms);
+        }
+
+        let report = drift.snapshot();
+        let histogram = &report.devices[&device];
+        // -120s → ≤-60s, -2s → ≤-1s, 0 → ≤0, 50ms → ≤100ms, 2 saat → +Inf
+        assert_eq!(histogram.buckets, vec![1, 0, 1, 1, 1, 0, 0, 0, 0, 0, 1]);
+        assert_eq!((histogram.count, histogram.sum_ms), (5, 7_078_050));
+        assert_eq!((histogram.min_ms, histogram.max_ms, histogram.last_ms), (-120_000, 7_200_000, 7_200_000));
+        assert_eq!(report.bucket_bounds_ms.len() + 1, histogram.buckets.len());
+    }
+
+    #[test]
+    fn test_tracked_devices_capped_by_last_reading() {
+        let drift = ClockDrift::with_max_devices(2);
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+
+        drift.record(a, now - Duration::seconds(30), now - Duration::seconds(30));
+        drift.record(b, now - Duration::seconds(20), now - Duration::seconds(20));
+        // a en son okuyan olur; c gelince en eski okuması olan b atılır
+        drift.record(a, now - Duration::seconds(10), now - Duration::seconds(10));
+        drift.record(c, now, now);
+
+        let devices = drift.snapshot().devices;
+        assert_eq!(devices.len(), 2);
+        assert!(devices.contains_key(&a) && devices.contains_key(&c));
+        assert_eq!(devices[&a].count, 2);
+    }
+}
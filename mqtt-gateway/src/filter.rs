@@ -0,0 +1,334 @@
+//! Cihaz Filtresi (Allowlist / Blocklist)
+//!
+//! Paylaşılan broker'larda bize ait olmayan cihazların mesajları parse
+//! edilmeden ve API server'a gönderilmeden atılır:
+//! - Topic gelir gelmez cihaz segmentine (`sensors/{cihaz}/...`) bakılır
+//! - Payload parse edildikten sonra `device_id` ile tekrar bakılır
+//!
+//! Kurallar:
+//! - Liste öğesi UUID ise `device_id` (veya UUID olan cihaz segmenti) ile eşleşir,
+//!   değilse cihaz segmentine glob olarak (`*`, `?`) uygulanır
+//! - Blocklist allowlist'ten önceliklidir
+//! - Boş allowlist = tüm cihazlar
+//!
+//! `DEVICE_FILTER_FILE` ayarlıysa listeler o dosyadan okunur ve dosya
+//! değiştikçe (`watch`) yeniden yüklenir.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use shared_types::conventions;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::Config;
+
+/// Filtre dosyası formatı
+///
+/// ```toml
+/// allowlist = ["rpi-*", "550e8400-e29b-41d4-a716-446655440000"]
+/// blocklist = ["test-*"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeviceLists {
+    #[serde(default)]
+    allowlist: Vec<String>,
+    #[serde(default)]
+    blocklist: Vec<String>,
+}
+
+/// Tek liste öğesi
+#[derive(Debug, Clone, PartialEq)]
+enum DevicePattern {
+    Id(Uuid),
+    Glob(String),
+}
+
+impl DevicePattern {
+    fn parse(raw: &str) -> Self {
+        match raw.parse::<Uuid>() {
+            Ok(id) => Self::Id(id),
+            Err(_) => Self::Glob(raw.to_string()),
+        }
+    }
+
+    fn matches(&self, device: Option<&str>, device_id: Option<Uuid>) -> bool {
+        match self {
+            Self::Id(id) => device_id == Some(*id) || device.and_then(|d| d.parse::<Uuid>().ok()) == Some(*id),
+            Self::Glob(pattern) => device.is_some_and(|d| glob_match(pattern, d)),
+        }
+    }
+}
+
+/// Derlenmiş allowlist + blocklist
+#[derive(Debug, Default)]
+struct Rules {
+    allow: Vec<DevicePattern>,
+    block: Vec<DevicePattern>,
+}
+
+impl Rules {
+    fn new<S: AsRef<str>>(allow: &[S], block: &[S]) -> Self {
+        let compile = |list: &[S]| {
+            list.iter()
+                .map(|s| s.as_ref().trim())
+                .filter(|s| !s.is_empty())
+                .map(DevicePattern::parse)
+                .collect()
+        };
+        Self { allow: compile(allow), block: compile(block) }
+    }
+
+    /// Cihaz geçebilir mi?
+    ///
+    /// `device_id` henüz bilinmiyorsa (topic aşaması) ve allowlist'te UUID
+    /// varsa, allowlist kararı parse sonrasına bırakılır.
+    fn allows(&self, device: Option<&str>, device_id: Option<Uuid>) -> bool {
+        let matches = |list: &[DevicePattern]| list.iter().any(|p| p.matches(device, device_id));
+        if matches(&self.block) {
+            return false;
+        }
+        let undecided = device_id.is_none() && self.allow.iter().any(|p| matches!(p, DevicePattern::Id(_)));
+        self.allow.is_empty() || undecided || matches(&self.allow)
+    }
+}
+
+/// Filtre tarafından atılan mesaj sayıları
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterStats {
+    /// Topic aşamasında (parse etmeden) atılanlar
+    pub rejected_by_topic: u64,
+    /// `device_id` parse edildikten sonra atılanlar
+    pub rejected_by_device_id: u64,
+}
+
+/// Paylaşılan cihaz filtresi
+///
+/// `main.rs` bir kez oluşturur; `watch` listeleri yerinde günceller.
+#[derive(Debug, Default)]
+pub struct DeviceFilter {
+    rules: RwLock<Rules>,
+    rejected_by_topic: AtomicU64,
+    rejected_by_device_id: AtomicU64,
+}
+
+impl DeviceFilter {
+    /// Virgülle ayrılmış listelerden filtre oluştur
+    pub fn new(allowlist: &str, blocklist: &str) -> Self {
+        let split = |raw: &str| raw.split(',').map(str::to_string).collect::<Vec<_>>();
+        Self { rules: RwLock::new(Rules::new(&split(allowlist), &split(blocklist))), ..Self::default() }
+    }
+
+    /// Config'ten filtre oluştur
+    ///
+    /// `device_filter_file` okunamazsa env listeleriyle devam edilir.
+    pub fn from_config(cfg: &Config) -> Self {
+        let filter = Self::new(&cfg.device_allowlist, &cfg.device_blocklist);
+        if let Some(path) = &cfg.device_filter_file {
+            if let Err(e) = filter.reload(Path::new(path)) {
+                warn!("⚠️  Device filter file {} not loaded: {}", path, e);
+            }
+        }
+        filter
+    }
+
+    /// Listeleri dosyadan yeniden yükle
+    ///
+    /// Hata olursa mevcut listeler korunur.
+    pub fn reload(&self, path: &Path) -> Result<(), String> {
+        let raw = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let lists: DeviceLists = toml::from_str(&raw).map_err(|e| e.to_string())?;
+        let rules = Rules::new(&lists.allowlist, &lists.blocklist);
+        info!("🛡️  Device filter loaded: {} allowed, {} blocked patterns", rules.allow.len(), rules.block.len());
+        *self.rules.write().unwrap() = rules;
+        Ok(())
+    }
+
+    /// Topic aşaması: cihaz segmentine göre karar ver
+    pub fn check_topic(&self, topic: &str) -> bool {
+        let allowed = self.rules.read().unwrap().allows(conventions::topic_device(topic), None);
+        if !allowed {
+            self.rejected_by_topic.fetch_add(1, Ordering::Relaxed);
+        }
+        allowed
+    }
+
+    /// Parse sonrası: cihaz segmenti ve `device_id` ile karar ver
+    pub fn check_device(&self, topic: &str, device_id: Uuid) -> bool {
+        let allowed = self.rules.read().unwrap().allows(conventions::topic_device(topic), Some(device_id));
+        if !allowed {
+            self.rejected_by_device_id.fetch_add(1, Ordering::Relaxed);
+        }
+        allowed
+    }
+
+    /// Atılan mesaj sayıları
+    pub fn stats(&self) -> FilterStats {
+        FilterStats {
+            rejected_by_topic: self.rejected_by_topic.load(Ordering::Relaxed),
+            rejected_by_device_id: self.rejected_by_device_id.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Filtre dosyasını izle ve değiştikçe yeniden yükle
+///
+/// `main.rs` tarafından `tokio::spawn` ile başlatılır. Dosyanın değiştirilme
+/// zamanı ve boyutu `every` aralıkla kontrol edilir. İlk durum çağrı anında
+/// alınır; spawn ile görevin başlaması arasındaki değişiklikler kaçmaz.
+pub fn watch(filter: Arc<DeviceFilter>, path: PathBuf, every: Duration) -> impl Future<Output = ()> {
+    let mut last = fingerprint(&path);
+    async move {
+        let mut ticker = interval(every);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            let current = fingerprint(&path);
+            if current.is_none() || current == last {
+                continue;
+            }
+            last = current;
+            if let Err(e) = filter.reload(&path) {
+                warn!("⚠️  Device filter reload from {} failed: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Dosyanın değiştirilme zamanı ve boyutu (yoksa `None`)
+fn fingerprint(path: &Path) -> Option<(Option<SystemTime>, u64)> {
+    std::fs::metadata(path).ok().map(|m| (m.modified().ok(), m.len()))
+}
+
+/// Atılan mesaj sayılarını değiştikçe logla
+pub async fn log_stats(filter: Arc<DeviceFilter>, every: Duration) {
+    let mut last = FilterStats::default();
+    let mut ticker = interval(every);
+    loop {
+        ticker.tick().await;
+        let stats = filter.stats();
+        if stats != last {
+            info!(
+                "📊 Device filter: {} rejected by topic, {} rejected by device_id",
+                stats.rejected_by_topic, stats.rejected_by_device_id
+            );
+            last = stats;
+        }
+    }
+}
+
+/// `*` (herhangi bir dizi) ve `?` (tek karakter) destekli glob eşleme
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    // Son `*`'ın konumu ve o noktada eşleşen metin konumu (backtracking için)
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star_pi, star_ti)) = star {
+            pi = star_pi + 1;
+            ti = star_ti + 1;
+            star = Some((star_pi, star_ti + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "550e8400-e29b-41d4-a716-446655440000";
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("rpi-*", "rpi-kitchen"));
+        assert!(glob_match("rpi-*", "rpi-"));
+        assert!(glob_match("*-0?", "edge-01"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("rpi-*", "esp-kitchen"));
+        assert!(!glob_match("edge-0?", "edge-010"));
+        assert!(!glob_match("a*b", "axxbc"));
+    }
+
+    #[test]
+    fn test_glob_patterns_on_topic() {
+        let filter = DeviceFilter::new("rpi-*", "");
+        assert!(filter.check_topic("sensors/rpi-kitchen/temperature"));
+        assert!(!filter.check_topic("sensors/neighbour/temperature"));
+        assert!(!filter.check_topic("sensors"));
+        assert_eq!(filter.stats(), FilterStats { rejected_by_topic: 2, rejected_by_device_id: 0 });
+    }
+
+    #[test]
+    fn test_uuid_patterns_decided_after_parse() {
+        let filter = DeviceFilter::new(ID, "");
+        let id: Uuid = ID.parse().unwrap();
+
+        // Topic'te isim var: karar parse sonrasına kalır
+        assert!(filter.check_topic("sensors/edge-agent/temperature"));
+        assert!(filter.check_device("sensors/edge-agent/temperature", id));
+        assert!(!filter.check_device("sensors/edge-agent/temperature", Uuid::new_v4()));
+        // Topic'teki UUID segmenti de eşleşir
+        assert!(filter.check_device(&format!("devices/{ID}/status"), id));
+        assert_eq!(filter.stats(), FilterStats { rejected_by_topic: 0, rejected_by_device_id: 1 });
+
+        // UUID blocklist topic aşamasında da uygulanır
+        let filter = DeviceFilter::new("", &ID.to_uppercase());
+        assert!(!filter.check_topic(&format!("devices/{ID}/status")));
+        assert!(!filter.check_device("sensors/edge-agent/temperature", id));
+    }
+
+    #[test]
+    fn test_blocklist_takes_precedence_over_allowlist() {
+        let filter = DeviceFilter::new(&format!("rpi-*,{ID}"), &format!("rpi-test*,{ID}"));
+        assert!(filter.check_topic("sensors/rpi-kitchen/temperature"));
+        assert!(!filter.check_topic("sensors/rpi-test-01/temperature"));
+        assert!(!filter.check_device("sensors/rpi-kitchen/temperature", ID.parse().unwrap()));
+
+        // Boş allowlist = tüm cihazlar
+        let filter = DeviceFilter::new("", "test-*");
+        assert!(filter.check_device("sensors/anything/temperature", Uuid::new_v4()));
+    }
+
+    #[tokio::test]
+    async fn test_lists_reload_when_file_changes() {
+        let path = std::env::temp_dir().join(format!("rustyflow-filter-{}.toml", Uuid::new_v4()));
+        std::fs::write(&path, "allowlist = [\"rpi-*\"]\n").unwrap();
+        let cfg = Config { device_allowlist: "esp-*".into(), device_filter_file: Some(path.display().to_string()), ..Config::default() };
+        let filter = Arc::new(DeviceFilter::from_config(&cfg));
+        // Dosya env listesinin yerini alır
+        assert!(filter.check_topic("sensors/rpi-01/temperature"));
+        assert!(!filter.check_topic("sensors/esp-01/temperature"));
+
+        let watcher = tokio::spawn(watch(filter.clone(), path.clone(), Duration::from_millis(20)));
+        std::fs::write(&path, "allowlist = [\"rpi-*\"]\nblocklist = [\"rpi-01\"]\n").unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!filter.check_topic("sensors/rpi-01/temperature"));
+        assert!(filter.check_topic("sensors/rpi-02/temperature"));
+
+        // Geçersiz dosya mevcut listeleri bozmaz
+        std::fs::write(&path, "allowlist = \"not-a-list\"\n").unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!filter.check_topic("sensors/rpi-01/temperature"));
+
+        watcher.abort();
+        std::fs::remove_file(path).unwrap();
+    }
+}
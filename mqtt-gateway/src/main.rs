@@ -3,23 +3,72 @@
 //! MQTT broker'a bağlanıp sensör verilerini dinleyen gateway servisi.
 //! - Mosquitto MQTT broker'a bağlanır
 //! - Topic'leri subscribe eder (sensors/#, devices/# vb.)
-//! - Gelen mesajları shared-types formatında parse eder
-//! - İleride: API server'a forward edebilir
+//! - `DEVICE_ALLOWLIST`/`DEVICE_BLOCKLIST` dışındaki cihazların mesajlarını atar
+//! - Gelen mesajları önceliklerine göre kuyruklar; will/durum mesajları okumalardan önce işlenir
+//! - Gelen mesajları shared-types formatında parse eder (JSON veya CBOR)
+//! - `MAX_PAYLOAD_BYTES`'ı aşan payload'ları parse etmeden atar (istenirse başını dead-letter'a yazar)
+//! - Payload'ları JSON Schema ile doğrular (`SCHEMA_DIR`, yoksa gömülü şemalar), geçersizleri dead-letter topic'ine gönderir
+//! - Sensör verilerini API server'a forward eder (tek tek veya `GATEWAY_BATCH_INGEST` ile toplu)
+//! - Sensör okumaları ve durum mesajlarıyla device shadow'un `reported` bölümünü günceller
+//! - Cihazların `error_report` mesajlarını `POST /api/devices/{id}/errors`'a iletir
+//! - `DEVICE_TOKENS`'taki cihazların isteklerini kendi token'larıyla gönderir
+//! - `GATEWAY_ADMIN_PORT` üzerinden `GET /health` ile bağlantı durumu ve sayaçları sunar
+//! - Kendi durumunu `rustyflow/gateway/{client_id}/status`'a retained publish eder (LWT: çevrimdışı)
 
+mod admin;
+mod batch;
 mod config;
+mod drift;
+mod filter;
+mod priority;
+mod schema;
+mod status;
 
-use rumqttc::{AsyncClient, MqttOptions, QoS, Event, Packet};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rumqttc::{QoS, Event, Packet};
+use tokio::sync::mpsc;
 use tokio::time::Duration;
-use tracing::{info, warn, error, debug};
+use tracing::{info, warn, error, debug, Instrument};
+use admin::GatewayStats;
 use config::Config;
-use shared_types::messages::MqttMessage;
-use reqwest::Client as HttpClient;
+use filter::DeviceFilter;
+use priority::{PriorityReceiver, PrioritySender, QueuedPublish};
+use schema::{DeadLetter, SchemaRegistry};
+use shared_types::cbor::looks_like_cbor;
+use shared_types::conventions;
+use shared_types::lz4;
+use shared_types::mqtt::{self, ConnectOptions, ReadingProperties};
+use shared_types::messages::{DeviceCommandKind, DeviceCommandResponse, DeviceMessage, MqttMessage};
+use shared_types::ErrorReport;
+use shared_types::Cbor;
+use shared_types::SensorType;
+use reqwest::{Client as HttpClient, RequestBuilder};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+/// Okumanın trace_id'sini API server'a taşıyan header
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// `DEVICE_FILTER_FILE` değişiklik kontrol aralığı
+const FILTER_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Filtre istatistiklerinin loglanma aralığı
+const FILTER_STATS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Publish edilmeyi bekleyen en fazla dead-letter kaydı (fazlası atılır)
+const DEAD_LETTER_QUEUE: usize = 100;
+
+/// Büyük payload'ların dead-letter kaydına yazılan baş kısmı (byte)
+const OVERSIZED_PREFIX_BYTES: usize = 256;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // ========== 1. KONFIGURASYON ==========
     // .env dosyasından ayarları yükle
-    let cfg = Config::load();
+    let cfg = Config::load()?;
 
     // ========== 2. LOGGING SISTEMI ==========
     // Structured logging'i başlat
@@ -33,20 +82,19 @@ async fn main() -> anyhow::Result<()> {
 
     // ========== 3. MQTT CLIENT OLUŞTUR ==========
     // MQTT bağlantı seçeneklerini ayarla
-    let mut mqttoptions = MqttOptions::new(
-        cfg.mqtt_client_id.clone(),
-        cfg.mqtt_broker_host.clone(),
-        cfg.mqtt_broker_port
-    );
-    
-    // Keep-alive: 5 saniye (bağlantının canlı olduğunu kontrol et)
-    mqttoptions.set_keep_alive(Duration::from_secs(5));
-    
-    // Clean session: true (her başlangıçta temiz başla)
-    mqttoptions.set_clean_session(true);
+    let options = ConnectOptions {
+        client_id: cfg.mqtt_client_id.clone(),
+        host: cfg.mqtt_broker_host.clone(),
+        port: cfg.mqtt_broker_port,
+        // Keep-alive, clean session, inflight ve istek kanalı (MQTT_* ayarları)
+        session: cfg.mqtt_session()?,
+        // Bağlantı koparsa broker gateway'i çevrimdışı olarak yayınlar
+        last_will: Some(status::last_will(&cfg.mqtt_client_id)),
+    };
+    info!("🔗 MQTT protocol: {}", cfg.mqtt_protocol);
 
-    // Async MQTT client ve event loop oluştur
-    let (client, mut eventloop) = AsyncClient::new(mqttoptions, 10);
+    // Async MQTT client ve event loop oluştur (v5 reddedilirse v4'e döner)
+    let (client, mut eventloop) = mqtt::connect(options, cfg.mqtt_protocol);
 
     // ========== 4. TOPIC'LERE SUBSCRIBE OL ==========
     // Config'den topic listesini al
@@ -60,28 +108,94 @@ async fn main() -> anyhow::Result<()> {
 
     info!("✅ Gateway ready, listening for messages...");
 
-    // ========== 5. HTTP CLIENT ==========
+    // ========== 5. YÖNETİM SUNUCUSU ==========
+    // GET /health: broker bağlantısı ve mesaj sayaçları
+    let stats = GatewayStats::new();
+    let admin_listener = tokio::net::TcpListener::bind(("0.0.0.0", cfg.gateway_admin_port)).await?;
+    info!("🩺 Admin server listening on {}", admin_listener.local_addr()?);
+    tokio::spawn({
+        let stats = stats.clone();
+        async move {
+            if let Err(e) = admin::serve(admin_listener, stats).await {
+                error!("❌ Admin server stopped: {}", e);
+            }
+        }
+    });
+
+    // ========== 6. HTTP CLIENT ==========
     // API server'a veri göndermek için HTTP client oluştur
     let http_client = HttpClient::new();
     let api_url = std::env::var("API_SERVER_URL")
         .unwrap_or_else(|_| "http://localhost:3000".to_string());
-    let sensor_endpoint = format!("{}/api/sensors", api_url);
-    info!("🌐 API server: {}", sensor_endpoint);
+    info!("🌐 API server: {}", api_url);
+    // Cihaz token'ları: isteği API server'da o cihaza bağlar (DEVICE_TOKENS)
+    let device_tokens = Arc::new(cfg.parse_device_tokens().map_err(|e| anyhow::anyhow!(e))?);
+    if !device_tokens.is_empty() {
+        info!("🔑 Using device tokens for {} devices", device_tokens.len());
+    }
+
+    // Batch modu: okumalar biriktirilip /v1/sensors/ingest'e gönderilir
+    let batch_tx = cfg.gateway_batch_ingest.then(|| {
+        let (tx, rx) = mpsc::channel(batch::BATCH_MAX_ITEMS * 10);
+        let flush_every = Duration::from_millis(cfg.gateway_batch_flush_ms.max(1));
+        info!("📦 Batch ingest enabled (flush every {:?})", flush_every);
+        tokio::spawn(batch::run(rx, http_client.clone(), api_url.clone(), device_tokens.clone(), flush_every, stats.clone()));
+        tx
+    });
+
+    // Cihaz filtresi (allowlist / blocklist)
+    let device_filter = Arc::new(DeviceFilter::from_config(&cfg));
+    if let Some(path) = &cfg.device_filter_file {
+        info!("🛡️  Watching device filter file: {}", path);
+        tokio::spawn(filter::watch(device_filter.clone(), PathBuf::from(path), FILTER_RELOAD_INTERVAL));
+    }
+    tokio::spawn(filter::log_stats(device_filter.clone(), FILTER_STATS_INTERVAL));
+
+    // Payload şemaları: geçersiz mesajlar dead-letter topic'ine publish edilir
+    let schemas = match &cfg.schema_dir {
+        Some(dir) => SchemaRegistry::load(Path::new(dir), cfg.parse_schema_skip_types()),
+        None => SchemaRegistry::builtin(cfg.parse_schema_skip_types()),
+    }
+    .map_err(|e| anyhow::anyhow!("Cannot load schemas: {e}"))?;
+    info!("📐 Validating payloads: {:?} (dead letters → {})", schemas.message_types(), cfg.dead_letter_topic);
+    // Gateway durumu (retained): uptime, bağlantı, sayaçlar, forward kuyruğu
+    let status_every = Duration::from_secs(cfg.gateway_status_interval_secs.max(1));
+    info!("📣 Publishing gateway status every {:?}", status_every);
+    let reporter = status::StatusReporter::new(&cfg.mqtt_client_id, stats.clone(), batch_tx.clone());
+    tokio::spawn(reporter.run(client.clone(), status_every));
+
+    let (dead_letter_tx, dead_letter_rx) = mpsc::channel(DEAD_LETTER_QUEUE);
+    tokio::spawn(schema::publish_dead_letters(dead_letter_rx, client.clone(), cfg.dead_letter_topic.clone()));
 
-    // ========== 6. EVENT LOOP - MESAJLARI DİNLE ==========
-    // MQTT broker'dan gelen tüm event'leri işle
+    let gateway = Gateway {
+        http_client,
+        api_url,
+        device_tokens,
+        batch: batch_tx,
+        filter: device_filter,
+        stats: stats.clone(),
+        schemas,
+        dead_letters: dead_letter_tx,
+        max_payload_bytes: cfg.max_payload_bytes,
+        dead_letter_oversized: cfg.oversized_dead_letter,
+    };
+    info!("📏 Max payload: {} bytes", cfg.max_payload_bytes);
+
+    // Öncelikli kuyruklar: tek dispatcher critical → high → normal → low sırasıyla işler
+    let (queue, queue_rx) = priority::channel(cfg.gateway_queue_capacity);
+    info!("🚦 Priority queues: {} messages per priority", cfg.gateway_queue_capacity.max(1));
+    tokio::spawn(dispatch(queue_rx, gateway));
+
+    // ========== 7. EVENT LOOP - MESAJLARI DİNLE ==========
+    // MQTT broker'dan gelen tüm event'leri kuyruklara yaz
     loop {
         match eventloop.poll().await {
-            Ok(notification) => {
-                debug!("📥 Event: {:?}", notification);
-                
-                // Sadece gelen mesajları işle (Publish event'leri)
-                if let Event::Incoming(Packet::Publish(publish)) = notification {
-                    handle_message(&publish.topic, &publish.payload, &http_client, &sensor_endpoint).await;
-                }
+            Ok((notification, properties)) => {
+                handle_event(notification, properties, &stats, &queue, cfg.max_payload_bytes).await;
             }
             Err(e) => {
                 error!("❌ Connection error: {}", e);
+                stats.set_broker_connected(false);
                 // Bağlantı hatası olursa 5 saniye bekle ve tekrar dene
                 tokio::time::sleep(Duration::from_secs(5)).await;
             }
@@ -99,6 +213,136 @@ struct SensorData {
     timestamp: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     metadata: Option<serde_json::Value>,
+    /// Gateway'in MQTT mesajını aldığı zaman (API server ayrı kolonda saklar)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gateway_received_at: Option<DateTime<Utc>>,
+}
+
+/// Mesaj işlemenin paylaşılan bağımlılıkları
+/// 
+/// `main` bir kez oluşturur; event loop her mesajda referansını geçirir.
+struct Gateway {
+    /// API server'a request göndermek için HTTP client
+    http_client: HttpClient,
+    /// API server'ın base URL'i (kimlik bilgisi içerebilir, loglanmaz)
+    api_url: String,
+    /// Cihaz başına API token'ları (`DEVICE_TOKENS`, loglanmaz)
+    device_tokens: Arc<HashMap<Uuid, String>>,
+    /// Batch modunda okumaların biriktirildiği kanal (`None` ise tek tek POST)
+    batch: Option<mpsc::Sender<SensorData>>,
+    /// Cihaz allowlist/blocklist'i
+    filter: Arc<DeviceFilter>,
+    /// `/health` sayaçları (işlenen, iletilen, başarısız, geçersiz)
+    stats: GatewayStats,
+    /// Payload şemaları (`SCHEMA_DIR` veya gömülü şemalar)
+    schemas: SchemaRegistry,
+    /// `DEAD_LETTER_TOPIC`'e publish edilecek geçersiz mesajlar
+    dead_letters: mpsc::Sender<DeadLetter>,
+    /// Forward edilecek en büyük payload (`MAX_PAYLOAD_BYTES`)
+    max_payload_bytes: usize,
+    /// Büyük payload'ların başı dead-letter'a yazılsın mı (`OVERSIZED_DEAD_LETTER`)
+    dead_letter_oversized: bool,
+}
+
+impl Gateway {
+    /// Cihazın `DEVICE_TOKENS`'taki token'ı
+    fn device_token(&self, device_id: Uuid) -> Option<&str> {
+        self.device_tokens.get(&device_id).map(String::as_str)
+    }
+
+    /// Payload'u mesaj tipinin şemasıyla doğrula
+    /// 
+    /// Geçersizse mesaj hatalarıyla birlikte dead-letter kuyruğuna yazılır ve
+    /// `false` döner. Kuyruk doluysa kayıt atılır; event loop beklemez.
+    fn validate(&self, topic: &str, message_type: &str, payload: &serde_json::Value, message: &impl serde::Serialize) -> bool {
+        let Err(errors) = self.schemas.validate(message_type, payload) else {
+            return true;
+        };
+        warn!("🧾 Invalid '{}' payload on {}: {}", message_type, topic, errors.join("; "));
+        self.stats.invalid();
+
+        let letter = DeadLetter {
+            topic: topic.to_string(),
+            message_type: message_type.to_string(),
+            errors,
+            message: serde_json::to_value(message).unwrap_or_default(),
+        };
+        if let Err(e) = self.dead_letters.try_send(letter) {
+            warn!("⚠️  Dead letter dropped: {}", e);
+        }
+        false
+    }
+
+    /// Payload `MAX_PAYLOAD_BYTES` sınırında mı?
+    /// 
+    /// Büyükse sayılır, `OVERSIZED_DEAD_LETTER` açıksa ilk
+    /// `OVERSIZED_PREFIX_BYTES` byte'ı (UTF-8'e kayıplı çevrilerek)
+    /// dead-letter'a yazılır ve `false` döner. Payload'un tamamı hiçbir
+    /// yere kopyalanmaz.
+    fn check_size(&self, topic: &str, payload: &[u8]) -> bool {
+        self.check_declared_size(topic, payload.len(), payload)
+    }
+
+    /// `check_size`, boyut payload'un kendisinden değil `size`'dan okunur
+    /// (LZ4 boyut başlığı; dead-letter'a sıkıştırılmış payload'un başı yazılır)
+    fn check_declared_size(&self, topic: &str, size: usize, payload: &[u8]) -> bool {
+        if size <= self.max_payload_bytes {
+            return true;
+        }
+        warn!("📏 Oversized payload on {}: {} bytes (max {})", topic, size, self.max_payload_bytes);
+        self.stats.oversized();
+
+        if self.dead_letter_oversized {
+            let prefix = &payload[..OVERSIZED_PREFIX_BYTES.min(payload.len())];
+            let letter = DeadLetter {
+                topic: topic.to_string(),
+                message_type: "oversized".to_string(),
+                errors: vec![format!("payload is {} bytes (max {})", size, self.max_payload_bytes)],
+                message: serde_json::Value::String(String::from_utf8_lossy(prefix).into_owned()),
+            };
+            if let Err(e) = self.dead_letters.try_send(letter) {
+                warn!("⚠️  Dead letter dropped: {}", e);
+            }
+        }
+        false
+    }
+}
+
+/// Event loop'tan gelen olayı işle
+/// 
+/// ConnAck broker bağlantısını `/health`'te açık gösterir; sadece gelen
+/// publish'ler (v5 okuma property'leriyle birlikte) önceliklerinin kuyruğuna
+/// yazılır. Kuyruk doluysa dispatcher yer açana kadar beklenir.
+async fn handle_event(
+    event: Event,
+    properties: Option<ReadingProperties>,
+    stats: &GatewayStats,
+    queue: &PrioritySender<QueuedPublish>,
+    max_payload_bytes: usize,
+) {
+    debug!("📥 Event: {:?}", event);
+
+    match event {
+        Event::Incoming(Packet::ConnAck(_)) => stats.set_broker_connected(true),
+        Event::Incoming(Packet::Publish(publish)) => {
+            let priority = priority::classify(&publish.topic, &publish.payload, max_payload_bytes);
+            if queue.send(priority, (publish, properties)).await.is_err() {
+                error!("❌ Message dispatcher stopped, dropping message");
+            }
+            debug!("🚦 {:?} message queued ({} pending)", priority, queue.depth());
+        }
+        _ => {}
+    }
+}
+
+/// Kuyruklardaki publish'leri öncelik sırasıyla `handle_message`'a ver
+/// 
+/// Mesajlar tek task'ta sırayla işlenir; aynı öncelikteki mesajların
+/// sırası korunur. Tüm gönderenler kapanınca döner.
+async fn dispatch(mut queue: PriorityReceiver<QueuedPublish>, gw: Gateway) {
+    while let Some((publish, properties)) = queue.recv().await {
+        handle_message(&publish.topic, &publish.payload, properties.as_ref(), &gw).await;
+    }
 }
 
 /// Gelen MQTT mesajını işle ve API server'a forward et
@@ -106,84 +350,938 @@ struct SensorData {
 /// # Parametreler
 /// - `topic`: Mesajın geldiği MQTT topic (örn: "sensors/edge-agent/temperature")
 /// - `payload`: Mesaj içeriği (byte array)
-/// - `http_client`: API server'a request göndermek için HTTP client
-/// - `sensor_endpoint`: API server'ın sensor endpoint'i
+/// - `properties`: MQTT v5 okuma user property'leri (v4'te veya eksikse `None`)
+/// - `gw`: HTTP client, API URL'i, batch kanalı, cihaz filtresi, sayaçlar ve şemalar
 /// 
 /// # İşlem Adımları
-/// 1. Payload'u String'e dönüştür
-/// 2. JSON parse et (shared-types::MqttMessage formatında)
-/// 3. SensorReading'i SensorData'ya çevir
-/// 4. API server'a POST et
-async fn handle_message(topic: &str, payload: &[u8], http_client: &HttpClient, sensor_endpoint: &str) {
-    // Payload'u String'e çevir
-    let payload_str = match std::str::from_utf8(payload) {
-        Ok(s) => s,
-        Err(e) => {
-            warn!("⚠️  Invalid UTF-8 in payload from {}: {}", topic, e);
+/// 0. Payload `MAX_PAYLOAD_BYTES`'tan büyükse parse etmeden at (bkz. `Gateway::check_size`).
+///    Topic'teki cihaz filtreden geçmiyorsa parse etmeden at (parse sonrası `device_id` ile tekrar bakılır;
+///    v5 property'leri varsa `device_id` filtresi de parse etmeden uygulanır)
+/// 1. `/lz4` ile biten topic'in payload'unu aç (bozuksa veya boyut başlığı sınırı aşıyorsa açmadan at), ardından
+///    formatı belirle: `/cbor` ile biten topic veya CBOR map başlangıcı → CBOR, aksi halde JSON
+/// 2. Parse et (shared-types::MqttMessage formatında)
+/// 3. Payload'u `message_type`'ın şemasıyla doğrula (geçersizse dead-letter, bkz. `schema`)
+/// 4. SensorReading'i SensorData'ya çevir (sensör tipi property'den, yoksa topic'in son segmentinden)
+/// 5. API server'a POST et ve shadow'un `reported` bölümünü güncelle
+/// 
+/// Mesajın `trace_id`'si varsa işlem o ID ile bir span içinde yürür ve
+/// API server'a `X-Request-Id` olarak gönderilir.
+/// 
+/// MqttMessage değilse `DeviceMessage` olarak denenir (`data` `command`'ın
+/// şemasıyla doğrulanır; `status_update` ve `heartbeat` shadow'a yazılır,
+/// `error_report` `POST /api/devices/{id}/errors`'a iletilir, diğerleri
+/// loglanıp atılır).
+/// 
+/// Span'in `device_id` alanı payload parse edildikten sonra doldurulur.
+/// `api_url` kimlik bilgisi içerebileceği için span'e yazılmaz.
+#[tracing::instrument(skip(payload, properties, gw), fields(topic = %topic, device_id))]
+async fn handle_message(topic: &str, payload: &[u8], properties: Option<&ReadingProperties>, gw: &Gateway) {
+    let received_at = Utc::now();
+    gw.stats.message_processed();
+    let received_on = topic;
+
+    if !gw.check_size(topic, payload) {
+        return;
+    }
+
+    if !gw.filter.check_topic(topic) {
+        debug!("🚫 Device filtered out by topic: {}", topic);
+        return;
+    }
+
+    // Desteklenen sürümdeki v5 property'leri: cihaz filtresi için parse gerekmez
+    let properties = properties.filter(|p| p.schema_version == mqtt::SCHEMA_VERSION);
+    if let Some(props) = properties {
+        tracing::Span::current().record("device_id", tracing::field::display(props.device_id));
+        if !gw.filter.check_device(topic, props.device_id) {
+            debug!("🚫 Device filtered out by properties: {}", props.device_id);
+            return;
+        }
+    }
+
+    // Sıkıştırılmış payload'lar önce açılır (`.../cbor/lz4` → `.../cbor`)
+    // Boyut başlığı açmadan önce kontrol edilir: küçük bir payload büyük bir tampon ayırtmasın
+    if let Some(size) = topic.ends_with(lz4::TOPIC_SUFFIX).then(|| lz4::decompressed_size(payload)).flatten() {
+        if !gw.check_declared_size(topic, size, payload) {
             return;
         }
+    }
+    let decompressed;
+    let (topic, payload) = match topic.strip_suffix(lz4::TOPIC_SUFFIX) {
+        Some(base) => match lz4::decompress_limited(payload, gw.max_payload_bytes) {
+            Ok(bytes) => {
+                decompressed = bytes;
+                (base, decompressed.as_slice())
+            }
+            Err(e) => {
+                warn!("⚠️  Invalid LZ4 payload from {}: {}", topic, e);
+                return;
+            }
+        },
+        None => (topic, payload),
+    };
+
+    // CBOR topic'lerinde suffix atılır (sensör tipi son segmentten okunur)
+    let (topic, is_cbor) = match topic.strip_suffix("/cbor") {
+        Some(base) => (base, true),
+        None => (topic, looks_like_cbor(payload)),
+    };
+
+    let payload_str = if is_cbor {
+        format!("<{} bytes CBOR>", payload.len())
+    } else {
+        match std::str::from_utf8(payload) {
+            Ok(s) => s.to_string(),
+            Err(e) => {
+                warn!("⚠️  Invalid UTF-8 in payload from {}: {}", topic, e);
+                return;
+            }
+        }
     };
 
     info!("📨 Message on '{}': {}", topic, payload_str);
 
-    // JSON parse et (shared-types::MqttMessage formatı)
-    match serde_json::from_str::<MqttMessage>(payload_str) {
+    // Komut yanıtı mı? (devices/{id}/responses)
+    if topic.ends_with(conventions::RESPONSE_TOPIC_SUFFIX) {
+        match decode::<DeviceCommandResponse>(payload, is_cbor) {
+            Ok(response) => {
+                tracing::Span::current().record("device_id", tracing::field::display(response.device_id));
+                if !gw.filter.check_device(topic, response.device_id) {
+                    debug!("🚫 Device filtered out: {}", response.device_id);
+                    return;
+                }
+                forward_command_response(&response, gw).await;
+            }
+            Err(e) => warn!("⚠️  Invalid command response on {}: {}", topic, e),
+        }
+        return;
+    }
+
+    // Parse et (shared-types::MqttMessage formatı)
+    match decode::<MqttMessage>(payload, is_cbor) {
         Ok(msg) => {
-            info!("✅ Parsed message:");
-            info!("   Device ID: {}", msg.device_id);
-            info!("   Message type: {:?}", msg.message_type);
-            
-            // SensorReading'i payload'dan parse et
-            if let Ok(reading) = serde_json::from_value::<shared_types::sensor::SensorReading>(msg.payload.clone()) {
-                // Sensör tipini topic'ten al
-                let sensor_type = topic.split('/').last().unwrap_or("unknown").to_string();
-                
-                // String değeri f64'e çevir
-                let value = reading.value.parse::<f64>().unwrap_or(0.0);
-                
-                // Unit'i sensör tipine göre belirle
-                let unit = match sensor_type.as_str() {
-                    "temperature" => "°C".to_string(),
-                    "humidity" => "%".to_string(),
-                    "motion" => "bool".to_string(),
-                    _ => "".to_string(),
-                };
-                
-                let sensor_data = SensorData {
-                    device_id: msg.device_id.to_string(),
-                    sensor_type,
-                    value,
-                    unit,
-                    timestamp: reading.timestamp.to_rfc3339(),
-                    metadata: reading.metadata.clone(),
-                };
-
-                debug!("📦 Sensor data to forward: {:?}", sensor_data);
-
-                // API server'a POST request
-                match http_client.post(sensor_endpoint)
-                    .json(&sensor_data)
-                    .send()
-                    .await
-                {
-                    Ok(response) => {
-                        if response.status().is_success() {
-                            info!("✅ Forwarded to API server: {}", sensor_data.sensor_type);
-                        } else {
-                            warn!("⚠️  API server returned error: {}", response.status());
-                        }
+            tracing::Span::current().record("device_id", tracing::field::display(msg.device_id));
+            if !gw.filter.check_device(topic, msg.device_id) {
+                debug!("🚫 Device filtered out: {}", msg.device_id);
+                return;
+            }
+            if !gw.validate(received_on, &msg.message_type, &msg.payload, &msg) {
+                return;
+            }
+            let span = tracing::info_span!(
+                "reading",
+                trace_id = %msg.trace_id.map(|id| id.to_string()).unwrap_or_else(|| "-".into())
+            );
+            let sensor_type = match properties {
+                Some(props) => props.sensor_type.as_str(),
+                None => conventions::sensor_topic_parts(topic).map_or("unknown", |(_, sensor_type)| sensor_type),
+            };
+            forward_mqtt_message(sensor_type, msg, received_at, gw).instrument(span).await;
+        }
+        Err(e) => {
+            // Durum mesajı mı? (devices/{id}/status)
+            match decode::<DeviceMessage>(payload, is_cbor) {
+                Ok(msg) => {
+                    tracing::Span::current().record("device_id", tracing::field::display(msg.device_id));
+                    if !gw.filter.check_device(topic, msg.device_id) {
+                        debug!("🚫 Device filtered out: {}", msg.device_id);
+                        return;
                     }
-                    Err(e) => {
-                        error!("❌ Failed to forward to API server: {}", e);
+                    if !gw.validate(received_on, msg.command.as_str(), &msg.data, &msg) {
+                        return;
                     }
+                    handle_device_message(msg, gw).await;
                 }
+                Err(_) => {
+                    // JSON parse başarısız (farklı format olabilir, sorun değil)
+                    debug!("ℹ️  Not a MqttMessage format: {} (raw: {})", e, payload_str);
+                }
+            }
+        }
+    }
+}
+
+/// `DeviceMessage`'ı türüne göre işle
+async fn handle_device_message(msg: DeviceMessage, gw: &Gateway) {
+    let patch = match &msg.command {
+        DeviceCommandKind::StatusUpdate => {
+            info!("📟 Status update from {}", msg.device_id);
+            status_patch(msg.data)
+        }
+        DeviceCommandKind::Heartbeat => {
+            debug!("💓 Heartbeat from {}", msg.device_id);
+            serde_json::json!({ "last_heartbeat": msg.timestamp })
+        }
+        DeviceCommandKind::ErrorReport => {
+            warn!("⚠️  Error report from {}: {}", msg.device_id, msg.data);
+            forward_error_report(msg, gw).await;
+            return;
+        }
+        DeviceCommandKind::SensorReading | DeviceCommandKind::Other(_) => {
+            // Okumalar MqttMessage formatında gelir
+            debug!("ℹ️  Ignoring '{}' device message from {}", msg.command, msg.device_id);
+            return;
+        }
+    };
+    let token = gw.device_token(msg.device_id);
+    report_shadow(&gw.http_client, &gw.api_url, msg.device_id, None, token, &patch).await;
+}
+
+/// Hata raporunu `POST /api/devices/{id}/errors` ile API server'a gönder
+///
+/// Raporun zamanı yoksa mesajın zamanı kullanılır.
+async fn forward_error_report(msg: DeviceMessage, gw: &Gateway) {
+    let mut report = match serde_json::from_value::<ErrorReport>(msg.data) {
+        Ok(report) => report,
+        Err(e) => {
+            warn!("⚠️  Invalid error report from {}: {}", msg.device_id, e);
+            return;
+        }
+    };
+    report.timestamp.get_or_insert(msg.timestamp);
+
+    let url = format!("{}/api/devices/{}/errors", gw.api_url, msg.device_id);
+    let request = with_device_token(gw.http_client.post(&url), gw.device_token(msg.device_id));
+    match request.json(&report).send().await {
+        Ok(response) if response.status().is_success() => {
+            debug!("🧯 Error report of {} stored", msg.device_id);
+        }
+        Ok(response) => warn!("⚠️  Error report returned error: {}", response.status()),
+        Err(e) => error!("❌ Failed to forward error report: {}", e),
+    }
+}
+
+/// Komut yanıtını `POST /v1/devices/{id}/commands/ack` ile API server'a gönder
+///
+/// API server yanıtı `device_commands`'taki komutla eşleştirir (komut
+/// geçmişi). Gateway'in göndermediği komutlar 404 döner, loglanıp geçilir.
+async fn forward_command_response(response: &DeviceCommandResponse, gw: &Gateway) {
+    let url = format!("{}/v1/devices/{}/commands/ack", gw.api_url, response.device_id);
+    let request = with_device_token(gw.http_client.post(&url), gw.device_token(response.device_id));
+    match request.json(response).send().await {
+        Ok(r) if r.status().is_success() => {
+            debug!("📬 Command {} of {} acknowledged", response.correlation_id, response.device_id);
+        }
+        Ok(r) => warn!("⚠️  Command ack returned error: {}", r.status()),
+        Err(e) => error!("❌ Failed to forward command response: {}", e),
+    }
+}
+
+/// Durum mesajından shadow `reported` patch'i oluştur
+/// 
+/// `data` `status` altına yazılır; cihaz türü bildirdiyse (gateway'ler
+/// `"kind": "gateway"` gönderir) shadow'un `kind` alanına da taşınır.
+/// Edge agent'ın uyguladığı ayarlar (`config`) kök seviyeye taşınır; böylece
+/// `desired`'daki alanlarla aynı yerde karşılaştırılır ve delta kapanır.
+fn status_patch(mut data: serde_json::Value) -> serde_json::Value {
+    let mut patch = serde_json::json!({});
+    if let Some(kind) = data.get("kind").filter(|k| k.is_string()) {
+        patch["kind"] = kind.clone();
+    }
+    if let Some(serde_json::Value::Object(config)) = data.as_object_mut().and_then(|d| d.remove("config")) {
+        for (key, value) in config {
+            patch[key] = value;
+        }
+    }
+    patch["status"] = data;
+    patch
+}
+
+/// Payload'u JSON veya CBOR olarak çöz
+fn decode<T: Cbor>(payload: &[u8], is_cbor: bool) -> Result<T, String> {
+    if is_cbor {
+        T::from_cbor(payload).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_slice(payload).map_err(|e| e.to_string())
+    }
+}
+
+/// Parse edilmiş MqttMessage'ı API server'a forward et
+/// 
+/// `received_at` mesajın broker'dan alındığı an; okumaya eklenir ve cihazın
+/// saat kayması histogramına yazılır.
+async fn forward_mqtt_message(sensor_type: &str, msg: MqttMessage, received_at: DateTime<Utc>, gw: &Gateway) {
+    let (http_client, api_url, stats) = (&gw.http_client, gw.api_url.as_str(), &gw.stats);
+    info!("✅ Parsed message:");
+    info!("   Device ID: {}", msg.device_id);
+    info!("   Message type: {:?}", msg.message_type);
+    
+    // SensorReading'i payload'dan parse et
+    if let Ok(reading) = serde_json::from_value::<shared_types::sensor::SensorReading>(msg.payload.clone()) {
+        let Ok(sensor_type) = sensor_type.parse::<SensorType>();
+        
+        // String değeri f64'e çevir
+        let value = reading.value.parse::<f64>().unwrap_or(0.0);
+
+        let drift_ms = stats.clock_drift().record(msg.device_id, received_at, reading.timestamp);
+        if drift_ms.abs() > drift::WARN_DRIFT_MS {
+            warn!("⏱️  Clock drift of {} ms for device {}", drift_ms, msg.device_id);
+        } else {
+            debug!("⏱️  Clock drift: {} ms", drift_ms);
+        }
+        
+        let sensor_data = SensorData {
+            device_id: msg.device_id.to_string(),
+            // Unit sensör tipinin varsayılan birimi
+            unit: sensor_type.default_unit().to_string(),
+            sensor_type: sensor_type.to_string(),
+            value,
+            timestamp: reading.timestamp.to_rfc3339(),
+            metadata: reading.metadata.clone(),
+            gateway_received_at: Some(received_at),
+        };
+
+        debug!("📦 Sensor data to forward: {:?}", sensor_data);
+
+        let patch = reported_sensor_patch(&sensor_data.sensor_type, value);
+        let token = gw.device_token(msg.device_id);
+
+        if let Some(batch) = &gw.batch {
+            // Batch modu: batch::run toplu gönderir (sayaçları da o günceller)
+            if batch.send(sensor_data).await.is_err() {
+                error!("❌ Batch ingest task stopped, reading dropped");
+                stats.failed(1);
+            }
+        } else if forward_sensor_data(&sensor_data, msg.trace_id, token, http_client, api_url).await {
+            stats.forwarded(1);
+        } else {
+            stats.failed(1);
+        }
+
+        report_shadow(http_client, api_url, msg.device_id, msg.trace_id, token, &patch).await;
+    } else {
+        debug!("ℹ️  Payload is not a SensorReading");
+    }
+}
+
+/// Tek okumayı `POST /api/sensors` ile API server'a gönder
+/// 
+/// API server okumayı kabul ettiyse `true` döner.
+async fn forward_sensor_data(
+    sensor_data: &SensorData,
+    trace_id: Option<Uuid>,
+    token: Option<&str>,
+    http_client: &HttpClient,
+    api_url: &str,
+) -> bool {
+    // API server'a POST request
+    let request = http_client.post(format!("{}/api/sensors", api_url));
+    match with_device_token(with_request_id(request, trace_id), token)
+        .json(sensor_data)
+        .send()
+        .await
+    {
+        Ok(response) => {
+            if response.status().is_success() {
+                info!("✅ Forwarded to API server: {}", sensor_data.sensor_type);
+                true
             } else {
-                debug!("ℹ️  Payload is not a SensorReading");
+                warn!("⚠️  API server returned error: {}", response.status());
+                false
             }
         }
         Err(e) => {
-            // JSON parse başarısız (farklı format olabilir, sorun değil)
-            debug!("ℹ️  Not a MqttMessage format: {} (raw: {})", e, payload_str);
+            error!("❌ Failed to forward to API server: {}", e);
+            false
+        }
+    }
+}
+
+/// trace_id varsa request'e `X-Request-Id` header'ı ekle
+fn with_request_id(request: RequestBuilder, trace_id: Option<Uuid>) -> RequestBuilder {
+    match trace_id {
+        Some(id) => request.header(REQUEST_ID_HEADER, id.to_string()),
+        None => request,
+    }
+}
+
+/// Cihaz token'ı varsa request'e `Authorization: Bearer` header'ı ekle
+fn with_device_token(request: RequestBuilder, token: Option<&str>) -> RequestBuilder {
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Sensör okumasından shadow `reported` patch'i oluştur
+/// 
+/// Sonuç: `{"sensors": {"temperature": 22.5}}`
+fn reported_sensor_patch(sensor_type: &str, value: f64) -> serde_json::Value {
+    serde_json::json!({ "sensors": { sensor_type: value } })
+}
+
+/// Device shadow'un `reported` bölümünü güncelle
+/// 
+/// API server `desired` ile farkı (`delta`) yeniden hesaplar.
+async fn report_shadow(
+    http_client: &HttpClient,
+    api_url: &str,
+    device_id: Uuid,
+    trace_id: Option<Uuid>,
+    token: Option<&str>,
+    patch: &serde_json::Value,
+) {
+    let url = format!("{}/v1/devices/{}/shadow/reported", api_url, device_id);
+    let request = with_device_token(with_request_id(http_client.post(&url), trace_id), token);
+    match request.json(patch).send().await {
+        Ok(response) if response.status().is_success() => {
+            debug!("🪞 Shadow reported state updated for {}", device_id);
+        }
+        Ok(response) => warn!("⚠️  Shadow update returned error: {}", response.status()),
+        Err(e) => error!("❌ Failed to update shadow: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::ErrorLevel;
+
+    /// Şemasız test gateway'i ve dead-letter kuyruğu
+    fn gateway(api_url: &str) -> (Gateway, mpsc::Receiver<DeadLetter>) {
+        let (dead_letters, rx) = mpsc::channel(DEAD_LETTER_QUEUE);
+        let gw = Gateway {
+            http_client: HttpClient::new(),
+            api_url: api_url.to_string(),
+            device_tokens: Arc::new(HashMap::new()),
+            batch: None,
+            filter: Arc::new(DeviceFilter::default()),
+            stats: GatewayStats::new(),
+            schemas: SchemaRegistry::default(),
+            dead_letters,
+            max_payload_bytes: 64 * 1024,
+            dead_letter_oversized: false,
+        };
+        (gw, rx)
+    }
+
+    #[test]
+    fn test_reported_sensor_patch() {
+        assert_eq!(
+            reported_sensor_patch("temperature", 22.5),
+            serde_json::json!({"sensors": {"temperature": 22.5}})
+        );
+    }
+
+    #[test]
+    fn test_status_patch_carries_device_kind() {
+        let data = serde_json::json!({"uptime": 3600});
+        assert_eq!(status_patch(data.clone()), serde_json::json!({"status": data}));
+
+        let gateway = serde_json::to_value(shared_types::GatewayStatus::offline("0.1.0")).unwrap();
+        assert_eq!(status_patch(gateway.clone()), serde_json::json!({"kind": "gateway", "status": gateway}));
+    }
+
+    #[test]
+    fn test_status_patch_lifts_config_to_match_desired() {
+        let config = shared_types::RemoteConfig { sensor_interval_secs: Some(10), qos: Some(1), ..Default::default() };
+        let data = serde_json::to_value(shared_types::DeviceStatusMessage::online(60).with_config(config)).unwrap();
+        let patch = status_patch(data);
+        assert_eq!(
+            patch,
+            serde_json::json!({"sensor_interval_secs": 10, "qos": 1, "status": {"connected": true, "uptime": 60}})
+        );
+
+        let mut shadow = shared_types::DeviceShadow::new(uuid::Uuid::new_v4());
+        shadow.update_desired(&serde_json::json!({"sensor_interval_secs": 10, "qos": 1}));
+        shadow.update_reported(&patch);
+        assert_eq!(shadow.delta, serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_decode_json_and_cbor_payloads() {
+        let reading = shared_types::SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+        let msg = MqttMessage::new("temperature_reading".to_string(), serde_json::to_value(&reading).unwrap(), Uuid::new_v4());
+
+        let json = serde_json::to_vec(&msg).unwrap();
+        let cbor = msg.to_cbor().unwrap();
+        assert!(!looks_like_cbor(&json) && looks_like_cbor(&cbor));
+
+        let from_json = decode::<MqttMessage>(&json, false).unwrap();
+        let from_cbor = decode::<MqttMessage>(&cbor, true).unwrap();
+        assert_eq!(from_json, msg);
+        assert_eq!(from_cbor, msg);
+
+        assert!(decode::<MqttMessage>(&json, true).is_err());
+    }
+
+    #[test]
+    fn test_with_request_id_sets_header() {
+        let client = HttpClient::new();
+        let trace_id = Uuid::new_v4();
+
+        let request = with_request_id(client.post("http://localhost/api/sensors"), Some(trace_id))
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[REQUEST_ID_HEADER], trace_id.to_string().as_str());
+
+        let request = with_request_id(client.post("http://localhost/api/sensors"), None)
+            .build()
+            .unwrap();
+        assert!(!request.headers().contains_key(REQUEST_ID_HEADER));
+    }
+
+    #[test]
+    fn test_with_device_token_sets_bearer() {
+        let client = HttpClient::new();
+        let request = with_device_token(client.post("http://localhost/api/sensors"), Some("rfd_a")).build().unwrap();
+        assert_eq!(request.headers()[reqwest::header::AUTHORIZATION], "Bearer rfd_a");
+
+        let request = with_device_token(client.post("http://localhost/api/sensors"), None).build().unwrap();
+        assert!(!request.headers().contains_key(reqwest::header::AUTHORIZATION));
+
+        let device_id = Uuid::new_v4();
+        let (gw, _dead_letters) = gateway("http://localhost");
+        let gw = Gateway { device_tokens: Arc::new(HashMap::from([(device_id, "rfd_a".to_string())])), ..gw };
+        assert_eq!((gw.device_token(device_id), gw.device_token(Uuid::new_v4())), (Some("rfd_a"), None));
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_handle_message_span_records_device_id() {
+        let reading = shared_types::SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+        let msg = MqttMessage::new("temperature_reading".to_string(), serde_json::to_value(&reading).unwrap(), Uuid::new_v4());
+        let payload = serde_json::to_vec(&msg).unwrap();
+
+        // API server yok: forward başarısız olur, span yine de loglarda görünür
+        handle_message("sensors/test/temperature", &payload, None, &gateway("http://127.0.0.1:9").0).await;
+
+        assert!(logs_contain("handle_message{"));
+        assert!(logs_contain(&format!("device_id={}", msg.device_id)));
+        assert!(logs_contain("topic=sensors/test/temperature"));
+        // Kimlik bilgisi içerebilecek argümanlar span alanı olarak yazılmaz
+        assert!(!logs_contain("api_url=") && !logs_contain("payload="));
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_handle_message_forwards_only_sensor_readings() {
+        use axum::{extract::State, routing::post, Json, Router};
+        use std::sync::atomic::{AtomicU16, Ordering};
+        use std::sync::Mutex;
+
+        // POST /api/sensors gövdelerini kaydeden, durum kodu ayarlanabilen sahte API server
+        #[derive(Clone, Default)]
+        struct MockApi {
+            bodies: Arc<Mutex<Vec<serde_json::Value>>>,
+            status: Arc<AtomicU16>,
+        }
+        let mock = MockApi { status: Arc::new(AtomicU16::new(201)), ..MockApi::default() };
+        let app = Router::new()
+            .route("/api/sensors", post(|State(mock): State<MockApi>, Json(body): Json<serde_json::Value>| async move {
+                mock.bodies.lock().unwrap().push(body);
+                axum::http::StatusCode::from_u16(mock.status.load(Ordering::SeqCst)).unwrap()
+            }))
+            .route("/v1/devices/{id}/shadow/reported", post(|| async { axum::http::StatusCode::OK }))
+            .with_state(mock.clone());
+        let api = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", api.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(api, app).await.unwrap() });
+        let (gw, _dead_letters) = gateway(&api_url);
+        let topic = "sensors/edge/temperature";
+
+        // Geçersiz UTF-8 ve MqttMessage olmayan JSON: POST yok
+        handle_message(topic, &[0x7b, 0xff, 0xfe, 0x7d], None, &gw).await;
+        handle_message(topic, br#"{"temperature": 22.5}"#, None, &gw).await;
+        // SensorReading taşımayan MqttMessage: POST yok
+        let msg = MqttMessage::new("temperature_reading".to_string(), serde_json::json!({"celsius": 22.5}), Uuid::new_v4());
+        handle_message(topic, &serde_json::to_vec(&msg).unwrap(), None, &gw).await;
+        assert!(mock.bodies.lock().unwrap().is_empty());
+        assert!(logs_contain("Invalid UTF-8 in payload"));
+        assert!(logs_contain("Payload is not a SensorReading"));
+
+        // Tam zincir: tek POST, SensorData gövdesiyle
+        let reading = shared_types::SensorReading::new(Uuid::new_v4(), "22.5".to_string());
+        let msg = MqttMessage::new("temperature_reading".to_string(), serde_json::to_value(&reading).unwrap(), Uuid::new_v4());
+        let before = chrono::Utc::now();
+        handle_message(topic, &serde_json::to_vec(&msg).unwrap(), None, &gw).await;
+        // Gateway'in alış zamanı mesaj işlenirken doldurulur
+        let gateway_received_at = mock.bodies.lock().unwrap()[0].as_object_mut().unwrap().remove("gateway_received_at").unwrap();
+        let gateway_received_at: DateTime<Utc> = serde_json::from_value(gateway_received_at).unwrap();
+        assert!(before <= gateway_received_at && gateway_received_at <= Utc::now());
+        let drift = gw.stats.clock_drift().snapshot();
+        assert_eq!(drift.devices[&msg.device_id].last_ms, drift::clock_drift_ms(gateway_received_at, reading.timestamp));
+        assert_eq!(
+            *mock.bodies.lock().unwrap(),
+            [serde_json::json!({
+                "device_id": msg.device_id.to_string(),
+                "sensor_type": "temperature",
+                "value": 22.5,
+                "unit": "°C",
+                "timestamp": reading.timestamp.to_rfc3339(),
+            })]
+        );
+        assert_eq!((gw.stats.health().messages_forwarded, gw.stats.health().messages_failed), (1, 0));
+
+        // API server 500 dönerse hata loglanır ve sayılır, panic yok
+        mock.status.store(500, Ordering::SeqCst);
+        handle_message(topic, &serde_json::to_vec(&msg).unwrap(), None, &gw).await;
+        assert_eq!(mock.bodies.lock().unwrap().len(), 2);
+        assert_eq!(gw.stats.health().messages_failed, 1);
+        assert!(logs_contain("API server returned error: 500"));
+    }
+
+    #[tokio::test]
+    async fn test_device_messages_dispatched_by_command() {
+        use axum::{extract::Path, routing::post, Json, Router};
+        use std::sync::Mutex;
+
+        // Shadow patch'lerini, hata raporlarını ve komut yanıtlarını kaydeden sahte API server
+        let patches = Arc::new(Mutex::new(Vec::new()));
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let acks = Arc::new(Mutex::new(Vec::new()));
+        let (recorded, recorded_errors, recorded_acks) = (patches.clone(), errors.clone(), acks.clone());
+        let app = Router::new()
+            .route(
+                "/v1/devices/{id}/shadow/reported",
+                post(move |Path(id): Path<Uuid>, Json(patch): Json<serde_json::Value>| async move {
+                    recorded.lock().unwrap().push((id, patch));
+                    axum::http::StatusCode::OK
+                }),
+            )
+            .route(
+                "/api/devices/{id}/errors",
+                post(move |Path(id): Path<Uuid>, Json(report): Json<ErrorReport>| async move {
+                    recorded_errors.lock().unwrap().push((id, report));
+                    axum::http::StatusCode::CREATED
+                }),
+            )
+            .route(
+                "/v1/devices/{id}/commands/ack",
+                post(move |Path(id): Path<Uuid>, Json(response): Json<DeviceCommandResponse>| async move {
+                    recorded_acks.lock().unwrap().push((id, response));
+                    axum::http::StatusCode::NO_CONTENT
+                }),
+            );
+        let api = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", api.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(api, app).await.unwrap() });
+
+        let device_id = Uuid::new_v4();
+        let heartbeat = DeviceMessage::heartbeat(device_id);
+        let unknown = DeviceMessage::new(device_id, DeviceCommandKind::Other("firmware_progress".into()), serde_json::json!({}));
+        let report = ErrorReport::new(ErrorLevel::Error, "sensor bus timeout").with_context(serde_json::json!({"source": "i2c"}));
+        let error_report = DeviceMessage::error_report(device_id, &report);
+        let (gw, _dead_letters) = gateway(&api_url);
+        for msg in [&heartbeat, &unknown, &error_report] {
+            let payload = serde_json::to_vec(msg).unwrap();
+            handle_message("devices/x/status", &payload, None, &gw).await;
+        }
+        let command = shared_types::messages::DeviceCommand::new(device_id, "control".into(), "led".into());
+        let response = DeviceCommandResponse::error(&command, "unknown LED `led_09`");
+        handle_message("devices/x/responses", &serde_json::to_vec(&response).unwrap(), None, &gw).await;
+        handle_message("devices/x/responses", &response.to_cbor().unwrap(), None, &gw).await;
+        handle_message("devices/x/responses", b"{\"not\": \"a response\"}", None, &gw).await;
+
+        // Sadece heartbeat shadow'a yazılır
+        let patches = patches.lock().unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].0, device_id);
+        assert_eq!(patches[0].1, serde_json::json!({"last_heartbeat": heartbeat.timestamp}));
+
+        // Hata raporu mesajın zamanıyla iletilir
+        let errors = errors.lock().unwrap();
+        assert_eq!(*errors, vec![(device_id, ErrorReport { timestamp: Some(error_report.timestamp), ..report })]);
+
+        // Komut yanıtları (JSON ve CBOR) aynen iletilir, geçersizler atlanır
+        let acks = acks.lock().unwrap();
+        assert_eq!(acks.len(), 2);
+        assert!(acks.iter().all(|(id, ack)| *id == device_id && ack.correlation_id == command.correlation_id && !ack.success));
+        assert_eq!(acks[1].1.message.as_deref(), Some("unknown LED `led_09`"));
+    }
+
+    #[tokio::test]
+    async fn test_health_counts_event_loop_messages() {
+        use axum::{routing::post, Router};
+        use rumqttc::{ConnAck, ConnectReturnCode, Publish};
+
+        // Sahte API server: okuma ve shadow isteklerini kabul eder
+        let app = Router::new()
+            .route("/api/sensors", post(|| async { axum::http::StatusCode::CREATED }))
+            .route("/v1/devices/{id}/shadow/reported", post(|| async { axum::http::StatusCode::OK }));
+        let api = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", api.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(api, app).await.unwrap() });
+
+        let stats = GatewayStats::new();
+        let admin_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let health_url = format!("http://{}/health", admin_listener.local_addr().unwrap());
+        tokio::spawn(admin::serve(admin_listener, stats.clone()));
+
+        let reading = shared_types::SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+        let msg = MqttMessage::new("temperature_reading".to_string(), serde_json::to_value(&reading).unwrap(), Uuid::new_v4());
+        let publish = Publish::new("sensors/test/temperature", QoS::AtMostOnce, serde_json::to_vec(&msg).unwrap());
+
+        let (gw, _dead_letters) = gateway(&api_url);
+        let gw = Gateway { stats: stats.clone(), ..gw };
+        let http_client = gw.http_client.clone();
+        let (queue, queue_rx) = priority::channel(10);
+        let dispatcher = tokio::spawn(dispatch(queue_rx, gw));
+        for event in [
+            Event::Incoming(Packet::ConnAck(ConnAck::new(ConnectReturnCode::Success, false))),
+            Event::Incoming(Packet::Publish(publish)),
+        ] {
+            handle_event(event, None, &stats, &queue, usize::MAX).await;
+        }
+        drop(queue);
+        dispatcher.await.unwrap();
+
+        let health: serde_json::Value = http_client.get(&health_url).send().await.unwrap().json().await.unwrap();
+        assert_eq!(
+            health,
+            serde_json::json!({
+                "status": "ok",
+                "broker_connected": true,
+                "messages_processed": 1,
+                "messages_forwarded": 1,
+                "messages_failed": 0,
+                "messages_invalid": 0,
+                "messages_oversized": 0,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_will_processed_before_queued_low_priority_readings() {
+        use axum::{routing::post, Json, Router};
+        use rumqttc::Publish;
+        use shared_types::MessagePriority;
+        use std::sync::Mutex;
+
+        // İstekleri geliş sırasıyla kaydeden sahte API server
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let (readings, patches) = (requests.clone(), requests.clone());
+        let app = Router::new()
+            .route(
+                "/api/sensors",
+                post(move || async move {
+                    readings.lock().unwrap().push(serde_json::json!("reading"));
+                    axum::http::StatusCode::CREATED
+                }),
+            )
+            .route(
+                "/v1/devices/{id}/shadow/reported",
+                post(move |Json(patch): Json<serde_json::Value>| async move {
+                    patches.lock().unwrap().push(patch);
+                    axum::http::StatusCode::OK
+                }),
+            );
+        let api = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", api.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(api, app).await.unwrap() });
+
+        // Dispatcher başlamadan kuyruğu düşük öncelikli okumalarla doldur, sonra will gelsin
+        let (queue, queue_rx) = priority::channel(100);
+        let stats = GatewayStats::new();
+        let reading = shared_types::SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+        for _ in 0..50 {
+            let msg = MqttMessage::new("temperature_reading".to_string(), serde_json::to_value(&reading).unwrap(), Uuid::new_v4())
+                .with_priority(MessagePriority::Low);
+            let publish = Publish::new("sensors/test/temperature", QoS::AtMostOnce, serde_json::to_vec(&msg).unwrap());
+            handle_event(Event::Incoming(Packet::Publish(publish)), None, &stats, &queue, usize::MAX).await;
+        }
+        let will = DeviceMessage::offline(Uuid::new_v4()).with_priority(MessagePriority::Critical);
+        let publish = Publish::new("devices/x/status", QoS::AtLeastOnce, serde_json::to_vec(&will).unwrap());
+        handle_event(Event::Incoming(Packet::Publish(publish)), None, &stats, &queue, usize::MAX).await;
+        assert_eq!(queue.depth(), 51);
+
+        let (gw, _dead_letters) = gateway(&api_url);
+        let dispatcher = tokio::spawn(dispatch(queue_rx, gw));
+        drop(queue);
+        dispatcher.await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0], serde_json::json!({"status": {"connected": false}}));
+        // Her okuma: POST /api/sensors + shadow patch
+        assert_eq!(requests.len(), 1 + 50 * 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_payloads_dead_lettered_not_forwarded() {
+        use axum::{routing::post, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Okuma ve shadow isteklerini sayan sahte API server
+        let posts = Arc::new(AtomicUsize::new(0));
+        let (readings, shadows) = (posts.clone(), posts.clone());
+        let app = Router::new()
+            .route("/api/sensors", post(move || async move {
+                readings.fetch_add(1, Ordering::SeqCst);
+                axum::http::StatusCode::CREATED
+            }))
+            .route("/v1/devices/{id}/shadow/reported", post(move || async move {
+                shadows.fetch_add(1, Ordering::SeqCst);
+                axum::http::StatusCode::OK
+            }));
+        let api = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", api.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(api, app).await.unwrap() });
+
+        let schema_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+        let (gw, mut dead_letters) = gateway(&api_url);
+        let gw = Gateway { schemas: SchemaRegistry::load(&schema_dir, []).unwrap(), ..gw };
+
+        // String boolean: forward edilmez, dead-letter'a düşer
+        let reading = shared_types::SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+        let mut payload = serde_json::to_value(&reading).unwrap();
+        payload["is_valid"] = serde_json::json!("true");
+        let invalid = MqttMessage::new("temperature_reading".to_string(), payload, Uuid::new_v4());
+        handle_message("sensors/test/temperature", &serde_json::to_vec(&invalid).unwrap(), None, &gw).await;
+
+        let letter = dead_letters.try_recv().unwrap();
+        assert_eq!(letter.topic, "sensors/test/temperature");
+        assert_eq!(letter.message_type, "temperature_reading");
+        assert!(letter.errors[0].starts_with("/is_valid:"), "{:?}", letter.errors);
+        assert_eq!(letter.message, serde_json::to_value(&invalid).unwrap());
+
+        let status = DeviceMessage::new(Uuid::new_v4(), DeviceCommandKind::StatusUpdate, serde_json::json!({"uptime": "1h"}));
+        handle_message("devices/x/status", &serde_json::to_vec(&status).unwrap(), None, &gw).await;
+        assert_eq!(dead_letters.try_recv().unwrap().message_type, "status_update");
+        assert_eq!(posts.load(Ordering::SeqCst), 0);
+
+        // Geçerli okuma forward edilir (okuma + shadow)
+        let valid = MqttMessage::new("temperature_reading".to_string(), serde_json::to_value(&reading).unwrap(), Uuid::new_v4());
+        handle_message("sensors/test/temperature", &serde_json::to_vec(&valid).unwrap(), None, &gw).await;
+        assert!(dead_letters.try_recv().is_err());
+        assert_eq!(posts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_payloads_counted_not_forwarded() {
+        use axum::{routing::post, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let posts = Arc::new(AtomicUsize::new(0));
+        let readings = posts.clone();
+        let app = Router::new()
+            .route("/api/sensors", post(move || async move {
+                readings.fetch_add(1, Ordering::SeqCst);
+                axum::http::StatusCode::CREATED
+            }))
+            .route("/v1/devices/{id}/shadow/reported", post(|| async { axum::http::StatusCode::OK }));
+        let api = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", api.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(api, app).await.unwrap() });
+
+        // Metadata ile tam sınıra kadar doldurulmuş okuma
+        let reading = shared_types::SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+        let msg = MqttMessage::new("temperature_reading".to_string(), serde_json::to_value(&reading).unwrap(), Uuid::new_v4());
+        let payload = serde_json::to_vec(&msg).unwrap();
+        let (gw, mut dead_letters) = gateway(&api_url);
+        let gw = Gateway { max_payload_bytes: payload.len(), dead_letter_oversized: true, ..gw };
+
+        // Sınırda: forward edilir
+        handle_message("sensors/test/temperature", &payload, None, &gw).await;
+        assert_eq!(posts.load(Ordering::SeqCst), 1);
+        assert_eq!(gw.stats.health().messages_oversized, 0);
+
+        // Bir byte fazlası: parse edilmeden atılır, başı dead-letter'a yazılır
+        let mut oversized = payload.clone();
+        oversized.push(b' ');
+        handle_message("sensors/test/temperature", &oversized, None, &gw).await;
+        assert_eq!(posts.load(Ordering::SeqCst), 1);
+        assert_eq!(gw.stats.health().messages_oversized, 1);
+        let letter = dead_letters.try_recv().unwrap();
+        assert_eq!((letter.topic.as_str(), letter.message_type.as_str()), ("sensors/test/temperature", "oversized"));
+        assert_eq!(letter.errors, [format!("payload is {} bytes (max {})", payload.len() + 1, payload.len())]);
+        let prefix = letter.message.as_str().unwrap();
+        assert_eq!(prefix.len(), OVERSIZED_PREFIX_BYTES);
+        assert!(payload.starts_with(prefix.as_bytes()));
+
+        // Açılınca sınırı aşan LZ4 payload da atılır (boyut başlığından, açılmadan)
+        handle_message("sensors/test/temperature/lz4", &lz4::compress(&oversized), None, &gw).await;
+        assert_eq!(posts.load(Ordering::SeqCst), 1);
+        assert_eq!(gw.stats.health().messages_oversized, 2);
+        let mut bomb = lz4::compress(b"{}");
+        bomb[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        handle_message("sensors/test/temperature/lz4", &bomb, None, &gw).await;
+        assert_eq!(gw.stats.health().messages_oversized, 3);
+        let letters: Vec<_> = std::iter::from_fn(|| dead_letters.try_recv().ok()).collect();
+        assert_eq!(letters.last().unwrap().errors, [format!("payload is {} bytes (max {})", u32::MAX, payload.len())]);
+
+        // Dead-letter kapalıyken sadece sayılır
+        let gw = Gateway { dead_letter_oversized: false, ..gw };
+        handle_message("sensors/test/temperature", &oversized, None, &gw).await;
+        assert_eq!(gw.stats.health().messages_oversized, 4);
+        assert!(dead_letters.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lz4_payloads_decompressed_before_forwarding() {
+        use axum::{routing::post, Json, Router};
+        use std::sync::Mutex;
+
+        // Forward edilen okumaları kaydeden sahte API server
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let recorded = bodies.clone();
+        let app = Router::new()
+            .route("/api/sensors", post(move |Json(body): Json<serde_json::Value>| async move {
+                recorded.lock().unwrap().push(body);
+                axum::http::StatusCode::CREATED
+            }))
+            .route("/v1/devices/{id}/shadow/reported", post(|| async { axum::http::StatusCode::OK }));
+        let api = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", api.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(api, app).await.unwrap() });
+
+        let reading = shared_types::SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+        let msg = MqttMessage::new("temperature_reading".to_string(), serde_json::to_value(&reading).unwrap(), Uuid::new_v4());
+        let (gw, _dead_letters) = gateway(&api_url);
+
+        let json = lz4::compress(&serde_json::to_vec(&msg).unwrap());
+        handle_message("sensors/test/temperature/lz4", &json, None, &gw).await;
+        let cbor = lz4::compress(&msg.to_cbor().unwrap());
+        handle_message("sensors/test/temperature/cbor/lz4", &cbor, None, &gw).await;
+        // Bozuk payload forward edilmez
+        handle_message("sensors/test/temperature/lz4", &json[..json.len() / 2], None, &gw).await;
+
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(bodies.len(), 2);
+        for body in bodies.iter() {
+            assert_eq!(body["sensor_type"], "temperature");
+            assert_eq!(body["device_id"], msg.device_id.to_string());
+            assert_eq!(body["value"], 23.5);
         }
     }
+
+    #[tokio::test]
+    async fn test_v5_properties_filter_and_route_without_topic() {
+        use axum::{routing::post, Json, Router};
+        use std::sync::Mutex;
+
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let recorded = bodies.clone();
+        let app = Router::new()
+            .route("/api/sensors", post(move |Json(body): Json<serde_json::Value>| async move {
+                recorded.lock().unwrap().push(body);
+                axum::http::StatusCode::CREATED
+            }))
+            .route("/v1/devices/{id}/shadow/reported", post(|| async { axum::http::StatusCode::OK }));
+        let api = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", api.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(api, app).await.unwrap() });
+
+        let blocked = Uuid::new_v4();
+        let (gw, _dead_letters) = gateway(&api_url);
+        let gw = Gateway { filter: Arc::new(DeviceFilter::new("", &blocked.to_string())), ..gw };
+
+        // Engelli cihaz: payload parse edilmeden atılır
+        let props = ReadingProperties::new(blocked, "temperature");
+        handle_message("sensors/rpi/temperature", b"not a reading", Some(&props), &gw).await;
+        assert_eq!(gw.filter.stats().rejected_by_device_id, 1);
+
+        // Sensör tipi topic yerine property'den
+        let reading = shared_types::SensorReading::new(Uuid::new_v4(), "55".to_string());
+        let msg = MqttMessage::new("humidity_reading".to_string(), serde_json::to_value(&reading).unwrap(), Uuid::new_v4());
+        let props = ReadingProperties::new(msg.device_id, "humidity");
+        handle_message("sensors/rpi/reading", &serde_json::to_vec(&msg).unwrap(), Some(&props), &gw).await;
+
+        // Bilinmeyen şema sürümü: property'ler yok sayılır, topic kullanılır
+        let future = ReadingProperties { schema_version: mqtt::SCHEMA_VERSION + 1, ..props };
+        handle_message("sensors/rpi/temperature", &serde_json::to_vec(&msg).unwrap(), Some(&future), &gw).await;
+
+        let bodies = bodies.lock().unwrap();
+        let types: Vec<_> = bodies.iter().map(|b| b["sensor_type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["humidity", "temperature"]);
+    }
 }
@@ -0,0 +1,185 @@
+//! Öncelikli Mesaj Kuyruğu
+//!
+//! Event loop gelen publish'leri işlemeden önce önceliklerine göre dört
+//! kuyruktan birine yazar (`tokio::sync::mpsc`, öncelik başına bir kanal).
+//! Tek bir dispatcher task'ı kuyrukları `Critical` → `High` → `Normal` → `Low`
+//! sırasıyla boşaltır; binlerce rutin okumanın arkasına düşen bir will
+//! mesajı veya çevrimdışı durumu önce işlenir.
+//!
+//! Öncelik mesajın `priority` alanından okunur (bkz. `MessagePriority`);
+//! alan yoksa veya payload okunamıyorsa `Normal`. Kuyruk doluysa event loop
+//! bekler (broker'a backpressure), mesaj atılmaz.
+
+use rumqttc::Publish;
+use serde::{Deserialize, Serialize};
+use shared_types::cbor::looks_like_cbor;
+use shared_types::lz4;
+use shared_types::mqtt::ReadingProperties;
+use shared_types::{Cbor, MessagePriority};
+use tokio::sync::mpsc;
+
+/// Dispatcher'a giden publish ve v5 okuma property'leri
+pub type QueuedPublish = (Publish, Option<ReadingProperties>);
+
+/// Öncelik başına bir kanallı kuyruk oluştur (`capacity` her kuyruk için, en az 1)
+pub fn channel<T>(capacity: usize) -> (PrioritySender<T>, PriorityReceiver<T>) {
+    let capacity = capacity.max(1);
+    let [(low_tx, low_rx), (normal_tx, normal_rx), (high_tx, high_rx), (critical_tx, critical_rx)] =
+        std::array::from_fn(|_| mpsc::channel(capacity));
+    (
+        PrioritySender { queues: [low_tx, normal_tx, high_tx, critical_tx] },
+        PriorityReceiver { queues: [low_rx, normal_rx, high_rx, critical_rx] },
+    )
+}
+
+/// Event loop tarafı (`MessagePriority` değeriyle indekslenir)
+#[derive(Debug)]
+pub struct PrioritySender<T> {
+    queues: [mpsc::Sender<T>; 4],
+}
+
+impl<T> Clone for PrioritySender<T> {
+    fn clone(&self) -> Self {
+        Self { queues: self.queues.clone() }
+    }
+}
+
+impl<T> PrioritySender<T> {
+    /// Öğeyi önceliğinin kuyruğuna yaz (kuyruk doluysa yer açılana kadar bekler)
+    pub async fn send(&self, priority: MessagePriority, item: T) -> Result<(), mpsc::error::SendError<T>> {
+        self.queues[priority as usize].send(item).await
+    }
+
+    /// Tüm kuyruklarda bekleyen öğe sayısı
+    pub fn depth(&self) -> usize {
+        self.queues.iter().map(|q| q.max_capacity() - q.capacity()).sum()
+    }
+}
+
+/// Dispatcher tarafı
+#[derive(Debug)]
+pub struct PriorityReceiver<T> {
+    queues: [mpsc::Receiver<T>; 4],
+}
+
+impl<T> PriorityReceiver<T> {
+    /// En yüksek öncelikli bekleyen öğeyi al
+    ///
+    /// Hepsi boşsa herhangi bir kuyruğa öğe gelene kadar bekler; tüm
+    /// gönderenler kapandıysa `None`.
+    pub async fn recv(&mut self) -> Option<T> {
+        for priority in MessagePriority::DESCENDING {
+            if let Ok(item) = self.queues[priority as usize].try_recv() {
+                return Some(item);
+            }
+        }
+        let [low, normal, high, critical] = &mut self.queues;
+        tokio::select! {
+            biased;
+            Some(item) = critical.recv() => Some(item),
+            Some(item) = high.recv() => Some(item),
+            Some(item) = normal.recv() => Some(item),
+            Some(item) = low.recv() => Some(item),
+            else => None,
+        }
+    }
+}
+
+/// Önceliği okumak için mesajın sadece `priority` alanı
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    #[serde(default)]
+    priority: MessagePriority,
+}
+
+impl Cbor for Envelope {}
+
+/// Publish'in önceliği (`MqttMessage` ve `DeviceMessage` için aynı alan)
+///
+/// Format `handle_message`'daki gibi belirlenir: `/lz4` topic'leri açılır
+/// (`max_payload_bytes`'tan büyükse açılmaz, `Normal`), `/cbor` topic'i veya
+/// CBOR map başlangıcı CBOR, aksi halde JSON.
+pub fn classify(topic: &str, payload: &[u8], max_payload_bytes: usize) -> MessagePriority {
+    let decompressed;
+    let (topic, payload) = match topic.strip_suffix(lz4::TOPIC_SUFFIX) {
+        Some(base) => match lz4::decompress_limited(payload, max_payload_bytes) {
+            Ok(bytes) => {
+                decompressed = bytes;
+                (base, decompressed.as_slice())
+            }
+            Err(_) => return MessagePriority::Normal,
+        },
+        None => (topic, payload),
+    };
+    let envelope = if topic.ends_with("/cbor") || looks_like_cbor(payload) {
+        Envelope::from_cbor(payload).ok()
+    } else {
+        serde_json::from_slice::<Envelope>(payload).ok()
+    };
+    envelope.map_or(MessagePriority::Normal, |e| e.priority)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::messages::{DeviceMessage, MqttMessage};
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_critical_sent_after_low_flood_is_received_first() {
+        let (tx, mut rx) = channel(1000);
+        for i in 0..500 {
+            tx.send(MessagePriority::Low, format!("low-{i}")).await.unwrap();
+        }
+        tx.send(MessagePriority::Normal, "normal".to_string()).await.unwrap();
+        tx.send(MessagePriority::Critical, "critical".to_string()).await.unwrap();
+        tx.send(MessagePriority::High, "high".to_string()).await.unwrap();
+        assert_eq!(tx.depth(), 503);
+
+        assert_eq!(rx.recv().await.as_deref(), Some("critical"));
+        assert_eq!(rx.recv().await.as_deref(), Some("high"));
+        assert_eq!(rx.recv().await.as_deref(), Some("normal"));
+        // Aynı öncelikte sıra korunur
+        assert_eq!(rx.recv().await.as_deref(), Some("low-0"));
+        assert_eq!(rx.recv().await.as_deref(), Some("low-1"));
+        assert_eq!(tx.depth(), 498);
+
+        drop(tx);
+        let mut remaining = 0;
+        while rx.recv().await.is_some() {
+            remaining += 1;
+        }
+        assert_eq!(remaining, 498);
+    }
+
+    #[tokio::test]
+    async fn test_recv_waits_for_any_queue() {
+        let (tx, mut rx) = channel::<u8>(1);
+        let dispatcher = tokio::spawn(async move { rx.recv().await });
+        tokio::task::yield_now().await;
+        tx.send(MessagePriority::Low, 7).await.unwrap();
+        assert_eq!(dispatcher.await.unwrap(), Some(7));
+    }
+
+    #[test]
+    fn test_classify_reads_priority_field() {
+        let device_id = Uuid::new_v4();
+        let reading = MqttMessage::new("temperature_reading".into(), serde_json::json!({}), device_id);
+        let json = serde_json::to_vec(&reading).unwrap();
+        assert_eq!(classify("sensors/rpi/temperature", &json, usize::MAX), MessagePriority::Normal);
+
+        let low = serde_json::to_vec(&reading.clone().with_priority(MessagePriority::Low)).unwrap();
+        assert_eq!(classify("sensors/rpi/temperature", &low, usize::MAX), MessagePriority::Low);
+        let (topic, compressed) = (format!("sensors/rpi/temperature{}", lz4::TOPIC_SUFFIX), lz4::compress(&low));
+        assert_eq!(classify(&topic, &compressed, usize::MAX), MessagePriority::Low);
+        assert_eq!(classify(&topic, &compressed, low.len() - 1), MessagePriority::Normal);
+
+        let offline = DeviceMessage::offline(device_id);
+        assert_eq!(classify("devices/x/status", &serde_json::to_vec(&offline).unwrap(), usize::MAX), MessagePriority::High);
+        let will = offline.with_priority(MessagePriority::Critical).to_cbor().unwrap();
+        assert_eq!(classify("devices/x/status/cbor", &will, usize::MAX), MessagePriority::Critical);
+
+        assert_eq!(classify("sensors/rpi/temperature", b"not json", usize::MAX), MessagePriority::Normal);
+        assert_eq!(classify("sensors/rpi/temperature/lz4", b"not lz4", usize::MAX), MessagePriority::Normal);
+    }
+}
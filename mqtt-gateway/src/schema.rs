@@ -0,0 +1,249 @@
+//! Payload Şema Doğrulaması (`SCHEMA_DIR`)
+//!
+//! Sahadaki cihazlar bazen hatalı payload gönderir (eksik `timestamp`,
+//! `"true"` gibi string boolean'lar). `SCHEMA_DIR` ayarlıysa buradaki
+//! `<mesaj tipi>.json` dosyaları, değilse shared-types'a gömülü şemalar
+//! (`MqttMessageSchema::builtin`) JSON Schema olarak yüklenir ve her mesaj
+//! forward edilmeden önce doğrulanır:
+//! - `MqttMessage`: `payload`, `message_type`'ın şemasıyla
+//! - `DeviceMessage`: `data`, `command`'ın şemasıyla (örn. `status_update`)
+//!
+//! `<tip>.json` yoksa ve tip `_reading` ile bitiyorsa (`temperature_reading`)
+//! `sensor_reading.json` kullanılır. Şeması olmayan tipler ve
+//! `SCHEMA_SKIP_TYPES`'taki tipler doğrulanmaz.
+//!
+//! Geçersiz mesajlar forward edilmez; doğrulama hatalarıyla birlikte
+//! `DEAD_LETTER_TOPIC`'e publish edilir (bkz. `DeadLetter`).
+//!
+//! `schemas/` altındaki şemalar shared-types'taki tiplerden üretilir
+//! (`shared_types::schema`). Gömülü şemalar sensör tipine özeldir (ör.
+//! `motion_reading` değeri `0`/`1`) ama `sensor_reading` içermez; diğer
+//! `*_reading` tipleri sadece `SCHEMA_DIR` ile doğrulanır.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use rumqttc::QoS;
+use shared_types::messages::{schema_errors, MqttMessageSchema};
+use shared_types::mqtt::MqttClient;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::error;
+
+/// `*_reading` tiplerinin ortak şeması
+const SENSOR_READING_SCHEMA: &str = "sensor_reading";
+
+/// Mesaj tipi → derlenmiş şema
+#[derive(Debug, Default)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, jsonschema::Validator>,
+    skip: HashSet<String>,
+}
+
+impl SchemaRegistry {
+    /// `dir` altındaki `*.json` dosyalarını yükle (dosya adı = mesaj tipi)
+    ///
+    /// Okunamayan veya geçerli JSON Schema olmayan dosyada hata döner.
+    pub fn load(dir: &Path, skip: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut schemas = Vec::new();
+        let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        for entry in entries {
+            let path = entry.map_err(|e| format!("{}: {e}", dir.display()))?.path();
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let raw = std::fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+            let schema = serde_json::from_str(&raw).map_err(|e| format!("{}: {e}", path.display()))?;
+            schemas.push(MqttMessageSchema::new(name, schema));
+        }
+        Self::from_schemas(schemas, skip)
+    }
+
+    /// shared-types'a gömülü şemalar (`SCHEMA_DIR` ayarlı değilse)
+    pub fn builtin(skip: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        Self::from_schemas(MqttMessageSchema::builtin(), skip)
+    }
+
+    /// Şemaları derle
+    pub fn from_schemas(
+        schemas: impl IntoIterator<Item = MqttMessageSchema>,
+        skip: impl IntoIterator<Item = String>,
+    ) -> Result<Self, String> {
+        let schemas = schemas
+            .into_iter()
+            .map(|schema| Ok((schema.message_type.clone(), schema.validator()?)))
+            .collect::<Result<_, String>>()?;
+        Ok(Self { schemas, skip: skip.into_iter().collect() })
+    }
+
+    /// Şeması yüklenmiş mesaj tipleri (sıralı)
+    pub fn message_types(&self) -> Vec<&str> {
+        let mut types: Vec<_> = self.schemas.keys().map(String::as_str).collect();
+        types.sort_unstable();
+        types
+    }
+
+    /// `message_type`'ın şeması (yoksa veya atlanıyorsa `None`)
+    fn schema_for(&self, message_type: &str) -> Option<&jsonschema::Validator> {
+        if self.skip.contains(message_type) {
+            return None;
+        }
+        self.schemas.get(message_type).or_else(|| {
+            let shared = message_type.ends_with("_reading") && !self.skip.contains(SENSOR_READING_SCHEMA);
+            shared.then(|| self.schemas.get(SENSOR_READING_SCHEMA)).flatten()
+        })
+    }
+
+    /// Payload'u doğrula; geçersizse hatalar (`<JSON pointer>: <açıklama>`)
+    pub fn validate(&self, message_type: &str, payload: &serde_json::Value) -> Result<(), Vec<String>> {
+        match self.schema_for(message_type) {
+            Some(schema) => schema_errors(schema, payload),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Dead-letter topic'ine publish edilen kayıt
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "topic": "sensors/rpi-01/temperature",
+///   "message_type": "temperature_reading",
+///   "errors": ["/is_valid: \"true\" is not of type \"boolean\"", ": \"timestamp\" is a required property"],
+///   "message": {"message_type": "temperature_reading", "payload": {...}, ...}
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeadLetter {
+    /// Mesajın geldiği topic
+    pub topic: String,
+    /// Doğrulamada kullanılan tip (`message_type` veya `command`)
+    pub message_type: String,
+    /// Doğrulama hataları
+    pub errors: Vec<String>,
+    /// Parse edilmiş mesajın tamamı (CBOR mesajlar da JSON olarak)
+    pub message: serde_json::Value,
+}
+
+/// Dead-letter kayıtlarını `topic`'e publish et
+///
+/// `main.rs` tarafından `tokio::spawn` ile başlatılır; kanal kapanınca biter.
+pub async fn publish_dead_letters(mut rx: mpsc::Receiver<DeadLetter>, client: MqttClient, topic: String) {
+    while let Some(letter) = rx.recv().await {
+        let payload = match serde_json::to_vec(&letter) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("❌ Cannot serialize dead letter: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = client.publish(&topic, QoS::AtLeastOnce, false, payload).await {
+            error!("❌ Failed to publish dead letter to {}: {}", topic, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/schemas");
+
+    fn default_registry(skip: &[&str]) -> SchemaRegistry {
+        SchemaRegistry::load(Path::new(SCHEMA_DIR), skip.iter().map(|s| s.to_string())).unwrap()
+    }
+
+    /// `schemas/*.json` shared-types tiplerinden üretilenle aynı olmalı
+    ///
+    /// `UPDATE_SCHEMAS=1` ile dosyaları yeniden yazar.
+    #[test]
+    fn test_default_schemas_match_shared_types() {
+        for (name, schema) in shared_types::schema::default_schemas() {
+            let path = Path::new(SCHEMA_DIR).join(format!("{name}.json"));
+            let expected = serde_json::to_string_pretty(&schema).unwrap() + "\n";
+            if std::env::var_os("UPDATE_SCHEMAS").is_some() {
+                std::fs::write(&path, &expected).unwrap();
+            }
+            let actual = std::fs::read_to_string(&path).unwrap_or_default();
+            assert_eq!(actual, expected, "{} is stale, rerun with UPDATE_SCHEMAS=1", path.display());
+        }
+    }
+
+    #[test]
+    fn test_sensor_readings_validated_against_shared_schema() {
+        let registry = default_registry(&[]);
+        assert_eq!(registry.message_types(), ["error_report", "sensor_reading", "status_update"]);
+
+        let valid = json!({
+            "sensor_id": "550e8400-e29b-41d4-a716-446655440001",
+            "value": "23.5",
+            "timestamp": "2024-11-13T21:30:00Z",
+            "is_valid": true
+        });
+        assert_eq!(registry.validate("temperature_reading", &valid), Ok(()));
+
+        // Eksik timestamp + string boolean
+        let mut invalid = valid.clone();
+        invalid.as_object_mut().unwrap().remove("timestamp");
+        invalid["is_valid"] = json!("true");
+        let errors = registry.validate("humidity_reading", &invalid).unwrap_err();
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors.iter().any(|e| e.contains("timestamp")));
+        assert!(errors.iter().any(|e| e.starts_with("/is_valid:")));
+
+        // status_update tipleri kontrol edilir, şeması olmayan tipler geçer
+        assert!(registry.validate("status_update", &json!({"uptime": "1h"})).is_err());
+        assert_eq!(registry.validate("status_update", &json!({"uptime": 3600, "fan": "on"})), Ok(()));
+        assert_eq!(registry.validate("heartbeat", &json!("anything")), Ok(()));
+
+        // error_report: mesaj zorunlu, seviye bilinen değerlerden biri
+        assert_eq!(registry.validate("error_report", &json!({"level": "critical", "message": "i2c timeout"})), Ok(()));
+        assert!(registry.validate("error_report", &json!({"level": "fatal", "message": "i2c timeout"})).is_err());
+        assert!(registry.validate("error_report", &json!({"error": "i2c timeout"})).is_err());
+    }
+
+    #[test]
+    fn test_skipped_types_are_not_validated() {
+        let invalid = json!({"value": 23.5});
+
+        let registry = default_registry(&["temperature_reading"]);
+        assert_eq!(registry.validate("temperature_reading", &invalid), Ok(()));
+        assert!(registry.validate("humidity_reading", &invalid).is_err());
+
+        // Ortak şemayı atlamak tüm *_reading tiplerini atlar
+        let registry = default_registry(&["sensor_reading"]);
+        assert_eq!(registry.validate("humidity_reading", &invalid), Ok(()));
+    }
+
+    #[test]
+    fn test_builtin_schemas_are_type_specific() {
+        let registry = SchemaRegistry::builtin([]).unwrap();
+        assert_eq!(registry.message_types(), ["humidity_reading", "motion_reading", "status_update", "temperature_reading"]);
+
+        let reading = |value: &str| json!({
+            "sensor_id": "550e8400-e29b-41d4-a716-446655440001",
+            "value": value,
+            "timestamp": "2024-11-13T21:30:00Z",
+            "is_valid": true
+        });
+        assert_eq!(registry.validate("motion_reading", &reading("1")), Ok(()));
+        assert_eq!(registry.validate("temperature_reading", &reading("22.50")), Ok(()));
+        let errors = registry.validate("motion_reading", &reading("22.50")).unwrap_err();
+        assert!(errors[0].starts_with("/value:"), "{errors:?}");
+        assert!(registry.validate("humidity_reading", &reading("wet")).is_err());
+        // Ortak sensor_reading şeması gömülü değil: diğer tipler geçer
+        assert_eq!(registry.validate("pressure_reading", &json!({})), Ok(()));
+
+        let registry = SchemaRegistry::builtin(["motion_reading".to_string()]).unwrap();
+        assert_eq!(registry.validate("motion_reading", &reading("22.50")), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_schema_rejected_on_load() {
+        let err = SchemaRegistry::from_schemas([MqttMessageSchema::new("broken", json!({"type": 12}))], []).unwrap_err();
+        assert!(err.starts_with("broken:"), "{err}");
+    }
+}
@@ -0,0 +1,173 @@
+//! Gateway Durum Yayını (`rustyflow/gateway/{client_id}/status`)
+//!
+//! Gateway kendi durumunu `GATEWAY_STATUS_INTERVAL_SECS` aralıkla retained
+//! `status_update` mesajı (`GatewayStatus`) olarak publish eder: uptime,
+//! broker bağlantısı, `/health` sayaçları, forward kuyruğu ve sürüm.
+//! Bağlantı beklenmedik koparsa broker aynı topic'e retained LWT
+//! (`connected: false`) yayınlar.
+//!
+//! Gateway'ler `rustyflow/gateway/+/status`'u da dinler; mesajlar diğer
+//! durum güncellemeleri gibi shadow'a yazılır (`kind: gateway`). Shadow
+//! ID'si client ID'den türetilir (`gateway_device_id`).
+
+use std::time::Instant;
+
+use rumqttc::{LastWill, QoS};
+use shared_types::conventions::gateway_status_topic;
+use shared_types::messages::{gateway_device_id, DeviceMessage, GatewayCounters, GatewayStatus, MessagePriority};
+use shared_types::DeviceStatus;
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::admin::GatewayStats;
+use crate::SensorData;
+
+/// Durum mesajlarında raporlanan sürüm
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Durum mesajı ve LWT QoS'u (retained mesaj kaybolmasın)
+const STATUS_QOS: QoS = QoS::AtLeastOnce;
+
+/// Broker'a bırakılan "çevrimdışı" mesajı (diğer gateway'lerde `Critical` öncelikli)
+pub fn last_will(client_id: &str) -> LastWill {
+    let message = DeviceMessage::gateway_status(gateway_device_id(client_id), &GatewayStatus::offline(VERSION))
+        .with_priority(MessagePriority::Critical);
+    LastWill::new(gateway_status_topic(client_id), serde_json::to_vec(&message).unwrap_or_default(), STATUS_QOS, true)
+}
+
+/// Periyodik durum mesajını üreten gateway durumu
+pub struct StatusReporter {
+    device_id: Uuid,
+    topic: String,
+    started_at: Instant,
+    stats: GatewayStats,
+    /// Batch modunda okumaların beklediği kanal (`None`: okumalar beklemeden POST edilir)
+    queue: Option<mpsc::Sender<SensorData>>,
+}
+
+impl StatusReporter {
+    pub fn new(client_id: &str, stats: GatewayStats, queue: Option<mpsc::Sender<SensorData>>) -> Self {
+        Self {
+            device_id: gateway_device_id(client_id),
+            topic: gateway_status_topic(client_id),
+            started_at: Instant::now(),
+            stats,
+            queue,
+        }
+    }
+
+    /// Anlık durum
+    pub fn status(&self) -> GatewayStatus {
+        let health = self.stats.health();
+        let queue_depth = self.queue.as_ref().map_or(0, |tx| tx.max_capacity() - tx.capacity());
+        GatewayStatus {
+            connected: health.broker_connected,
+            status: DeviceStatus { uptime: Some(self.started_at.elapsed().as_secs()), ..DeviceStatus::default() },
+            counters: Some(GatewayCounters {
+                processed: health.messages_processed,
+                forwarded: health.messages_forwarded,
+                failed: health.messages_failed,
+                invalid: health.messages_invalid,
+                oversized: health.messages_oversized,
+            }),
+            forward_queue_depth: Some(queue_depth as u64),
+            ..GatewayStatus::offline(VERSION)
+        }
+    }
+
+    /// Publish edilecek `status_update` mesajı
+    pub fn message(&self) -> DeviceMessage {
+        DeviceMessage::gateway_status(self.device_id, &self.status())
+    }
+
+    /// Durumu `every` aralıkla retained publish et
+    ///
+    /// `main.rs` tarafından `tokio::spawn` ile başlatılır. Broker bağlı
+    /// değilken tur atlanır; bağlanınca ilk turda güncel durum gider.
+    pub async fn run(self, client: shared_types::mqtt::MqttClient, every: Duration) {
+        let mut ticker = interval(every);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if !self.stats.health().broker_connected {
+                continue;
+            }
+            let payload = match serde_json::to_vec(&self.message()) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!("⚠️  Cannot encode gateway status: {}", e);
+                    continue;
+                }
+            };
+            match client.publish(&self.topic, STATUS_QOS, true, payload).await {
+                Ok(()) => debug!("📣 Gateway status published to {}", self.topic),
+                Err(e) => warn!("⚠️  Gateway status publish failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::{DeviceCommandKind, DeviceKind};
+
+    #[tokio::test]
+    async fn test_status_payload_from_stats_and_queue() {
+        let stats = GatewayStats::new();
+        stats.set_broker_connected(true);
+        for _ in 0..4 {
+            stats.message_processed();
+        }
+        stats.forwarded(3);
+        stats.invalid();
+
+        let (tx, _rx) = mpsc::channel(10);
+        for i in 0..2 {
+            let reading = SensorData {
+                device_id: format!("dev-{i}"),
+                sensor_type: "temperature".into(),
+                value: 21.0,
+                unit: "°C".into(),
+                timestamp: "2024-01-20T10:30:00Z".into(),
+                metadata: None,
+                gateway_received_at: None,
+            };
+            tx.try_send(reading).unwrap();
+        }
+
+        let reporter = StatusReporter::new("gateway-001", stats, Some(tx));
+        let message = reporter.message();
+        assert_eq!(message.device_id, gateway_device_id("gateway-001"));
+        assert_eq!(message.command, DeviceCommandKind::StatusUpdate);
+
+        let status: GatewayStatus = serde_json::from_value(message.data).unwrap();
+        assert_eq!(status.kind, DeviceKind::Gateway);
+        assert!(status.connected);
+        assert_eq!(status.version, VERSION);
+        assert_eq!(status.status.uptime, Some(0));
+        assert_eq!(status.counters, Some(GatewayCounters { processed: 4, forwarded: 3, failed: 0, invalid: 1, oversized: 0 }));
+        assert_eq!(status.forward_queue_depth, Some(2));
+
+        // Batch modu kapalı: kuyruk her zaman boş
+        let reporter = StatusReporter::new("gateway-001", GatewayStats::new(), None);
+        let status = reporter.status();
+        assert_eq!((status.connected, status.forward_queue_depth), (false, Some(0)));
+    }
+
+    #[test]
+    fn test_last_will_marks_gateway_offline() {
+        let will = last_will("gateway-001");
+        assert_eq!(will.topic, "rustyflow/gateway/gateway-001/status");
+        assert_eq!((will.qos, will.retain), (QoS::AtLeastOnce, true));
+
+        let message: DeviceMessage = serde_json::from_slice(&will.message).unwrap();
+        assert_eq!(message.device_id, gateway_device_id("gateway-001"));
+        assert_eq!(message.command, DeviceCommandKind::StatusUpdate);
+        assert_eq!(message.priority, MessagePriority::Critical);
+        let status: GatewayStatus = serde_json::from_value(message.data).unwrap();
+        assert_eq!(status, GatewayStatus::offline(VERSION));
+    }
+}
@@ -0,0 +1,208 @@
+//! Alert Types
+//!
+//! Sensör okumalarının eşik (threshold) kontrolü ve üretilen alarmlar.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+#[cfg(feature = "sqlx-support")]
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+/// Sensör tipi için izin verilen değer aralığı
+///
+/// Sınırlardan biri `None` ise o yönde kontrol yapılmaz.
+///
+/// SQL'de `threshold_min` / `threshold_max` kolonlarından okunur (`min`/`max`
+/// aggregate fonksiyon adlarıyla karışmasın).
+///
+/// # Örnek JSON
+/// ```json
+/// {"min": 10.0, "max": 35.0}
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct AlertThreshold {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "sqlx-support", sqlx(rename = "threshold_min"))]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "sqlx-support", sqlx(rename = "threshold_max"))]
+    pub max: Option<f64>,
+}
+
+impl AlertThreshold {
+    /// Değer aralık dışındaysa aşılan sınırı döndür
+    pub fn breached_bound(&self, value: f64) -> Option<f64> {
+        match (self.min, self.max) {
+            (Some(min), _) if value < min => Some(min),
+            (_, Some(max)) if value > max => Some(max),
+            _ => None,
+        }
+    }
+}
+
+/// Eşik aşıldığında üretilen alarm
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "id": "550e8400-e29b-41d4-a716-446655440000",
+///   "device_id": "edge-agent-001",
+///   "sensor_type": "temperature",
+///   "value": 41.2,
+///   "threshold": {"max": 35.0},
+///   "message": "temperature 41.2 above max 35",
+///   "triggered_at": "2024-01-20T10:30:00Z"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct SensorAlert {
+    pub id: Uuid,
+    pub device_id: String,
+    pub sensor_type: String,
+    pub value: f64,
+    /// SQL'de `threshold_min` / `threshold_max` kolonları
+    #[cfg_attr(feature = "sqlx-support", sqlx(flatten))]
+    pub threshold: AlertThreshold,
+    pub message: String,
+    pub triggered_at: DateTime<Utc>,
+}
+
+/// Okumayı eşikle karşılaştır, aşıldıysa alarm üret
+///
+/// ```
+/// use shared_types::alert::{check_threshold, AlertThreshold};
+///
+/// let threshold = AlertThreshold { min: None, max: Some(35.0) };
+/// assert!(check_threshold("dev-1", "temperature", 30.0, &threshold).is_none());
+///
+/// let alert = check_threshold("dev-1", "temperature", 41.2, &threshold).unwrap();
+/// assert_eq!(alert.message, "temperature 41.2 above max 35");
+/// ```
+pub fn check_threshold(device_id: &str, sensor_type: &str, value: f64, threshold: &AlertThreshold) -> Option<SensorAlert> {
+    let bound = threshold.breached_bound(value)?;
+    let direction = if value < bound { "below min" } else { "above max" };
+    Some(SensorAlert {
+        id: Uuid::new_v4(),
+        device_id: device_id.to_string(),
+        sensor_type: sensor_type.to_string(),
+        value,
+        threshold: *threshold,
+        message: format!("{sensor_type} {value} {direction} {bound}"),
+        triggered_at: Utc::now(),
+    })
+}
+
+/// Geçmiş okumaları bir eşikle yeniden oynatma isteği (alarm back-test)
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "device_id": "edge-agent-001",
+///   "sensor_type": "temperature",
+///   "from": "2024-01-20T00:00:00Z",
+///   "to": "2024-01-21T00:00:00Z",
+///   "threshold": {"max": 30.0}
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayRequest {
+    pub device_id: String,
+    pub sensor_type: String,
+    /// Başlangıç zamanı (dahil)
+    pub from: DateTime<Utc>,
+    /// Bitiş zamanı (dahil)
+    pub to: DateTime<Utc>,
+    pub threshold: AlertThreshold,
+}
+
+/// Replay sonucu
+///
+/// Alarmların `triggered_at`'i tetikleyen okumanın zamanıdır.
+///
+/// # Örnek JSON
+/// ```json
+/// {"alerts_triggered": [...], "total_readings": 1440, "breach_count": 3}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayResult {
+    pub alerts_triggered: Vec<SensorAlert>,
+    pub total_readings: u64,
+    pub breach_count: u64,
+}
+
+impl ReplayResult {
+    /// Bir okumayı isteğin eşiğiyle kontrol et ve sonuca ekle
+    pub fn push(&mut self, request: &ReplayRequest, value: f64, timestamp: DateTime<Utc>) {
+        self.total_readings += 1;
+        if let Some(mut alert) = check_threshold(&request.device_id, &request.sensor_type, value, &request.threshold) {
+            alert.triggered_at = timestamp;
+            self.alerts_triggered.push(alert);
+            self.breach_count += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_threshold_bounds() {
+        let range = AlertThreshold { min: Some(10.0), max: Some(35.0) };
+        assert_eq!(range.breached_bound(9.9), Some(10.0));
+        assert_eq!(range.breached_bound(35.1), Some(35.0));
+        assert_eq!(range.breached_bound(10.0), None);
+        assert_eq!(range.breached_bound(35.0), None);
+        assert_eq!(AlertThreshold::default().breached_bound(1e9), None);
+    }
+
+    #[test]
+    fn test_check_threshold_below_min() {
+        let threshold = AlertThreshold { min: Some(20.0), max: None };
+        let alert = check_threshold("dev-1", "humidity", 12.5, &threshold).unwrap();
+        assert_eq!(alert.device_id, "dev-1");
+        assert_eq!(alert.message, "humidity 12.5 below min 20");
+        assert_eq!(alert.threshold, threshold);
+    }
+
+    #[test]
+    fn test_replay_result_counts_breaches() {
+        let from = "2024-01-20T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let request = ReplayRequest {
+            device_id: "dev-1".into(),
+            sensor_type: "temperature".into(),
+            from,
+            to: from + chrono::Duration::hours(1),
+            threshold: AlertThreshold { min: Some(15.0), max: Some(30.0) },
+        };
+
+        let mut result = ReplayResult::default();
+        for (minute, value) in [20.0, 31.5, 25.0, 14.0, 30.0, 15.0, 42.0].into_iter().enumerate() {
+            result.push(&request, value, from + chrono::Duration::minutes(minute as i64));
+        }
+        assert_eq!((result.total_readings, result.breach_count), (7, 3));
+        let values: Vec<_> = result.alerts_triggered.iter().map(|a| a.value).collect();
+        assert_eq!(values, [31.5, 14.0, 42.0]);
+        assert_eq!(result.alerts_triggered[1].triggered_at, from + chrono::Duration::minutes(3));
+    }
+
+    proptest! {
+        /// Aralık dışındaki her değer (ve sadece onlar) bir sınırı aşar
+        #[test]
+        fn prop_breached_bound_iff_out_of_range(
+            a in -1e6f64..1e6,
+            b in -1e6f64..1e6,
+            value in -2e6f64..2e6,
+        ) {
+            let (min, max) = (a.min(b), a.max(b));
+            let threshold = AlertThreshold { min: Some(min), max: Some(max) };
+            let out_of_range = value < min || value > max;
+
+            prop_assert_eq!(threshold.breached_bound(value).is_some(), out_of_range);
+            prop_assert_eq!(check_threshold("dev-1", "temperature", value, &threshold).is_some(), out_of_range);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! CBOR Serializasyonu
+//!
+//! `cbor` feature'ı ile açılır. JSON'a göre daha küçük payload üretir:
+//! alan adları aynı kalır ama UUID'ler 16 byte, sayılar binary kodlanır.
+//! Pille çalışan cihazlar için MQTT mesaj boyutunu azaltır.
+//!
+//! # Örnek
+//! ```
+//! use shared_types::{Cbor, SensorReading};
+//! use uuid::Uuid;
+//!
+//! let reading = SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+//! let bytes = reading.to_cbor().unwrap();
+//! let decoded = SensorReading::from_cbor(&bytes).unwrap();
+//! assert_eq!(decoded.value, "23.5");
+//! ```
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::messages::{DeviceCommandResponse, DeviceMessage, MqttMessage};
+use crate::sensor::SensorReading;
+use crate::{Error, Result};
+
+/// CBOR encode/decode helper'ları
+///
+/// MQTT üzerinden taşınan mesaj tipleri için implement edilmiştir.
+pub trait Cbor: Serialize + DeserializeOwned {
+    /// CBOR byte dizisine çevir
+    fn to_cbor(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(self, &mut buf)
+            .map_err(|e| Error::SerializationError(e.to_string()))?;
+        Ok(buf)
+    }
+
+    /// CBOR byte dizisinden oku
+    fn from_cbor(bytes: &[u8]) -> Result<Self> {
+        ciborium::from_reader(bytes).map_err(|e| Error::SerializationError(e.to_string()))
+    }
+}
+
+impl Cbor for MqttMessage {}
+impl Cbor for DeviceMessage {}
+impl Cbor for DeviceCommandResponse {}
+impl Cbor for SensorReading {}
+
+/// Payload CBOR gibi görünüyor mu? (ilk byte sezgisi)
+///
+/// Mesajlarımız CBOR'da her zaman map ile başlar (major type 5: `0xA0..=0xBF`).
+/// JSON ise `{`, `[` veya boşlukla başlar; bu aralıktaki byte'lar geçerli
+/// UTF-8 başlangıcı da değildir, dolayısıyla karışıklık olmaz.
+pub fn looks_like_cbor(payload: &[u8]) -> bool {
+    matches!(payload.first(), Some(0xA0..=0xBF))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::DeviceCommandKind;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn reading() -> SensorReading {
+        SensorReading::new(Uuid::new_v4(), "23.5".to_string())
+            .with_metadata(json!({"duration_ms": 500, "tags": ["indoor", null, true], "calibration": -0.25}))
+    }
+
+    #[test]
+    fn test_sensor_reading_roundtrip_and_size() {
+        let reading = reading();
+        let cbor = reading.to_cbor().unwrap();
+        let json = serde_json::to_vec(&reading).unwrap();
+
+        let decoded = SensorReading::from_cbor(&cbor).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&reading).unwrap());
+        assert!(cbor.len() < json.len(), "cbor {} >= json {}", cbor.len(), json.len());
+        assert!(looks_like_cbor(&cbor));
+        assert!(!looks_like_cbor(&json));
+    }
+
+    #[test]
+    fn test_mqtt_message_roundtrip_with_value_payload() {
+        let msg = MqttMessage::new(
+            "temperature_reading".to_string(),
+            serde_json::to_value(reading()).unwrap(),
+            Uuid::new_v4(),
+        )
+        .with_trace_id(Uuid::new_v4());
+
+        let cbor = msg.to_cbor().unwrap();
+        let json = serde_json::to_vec(&msg).unwrap();
+        let decoded = MqttMessage::from_cbor(&cbor).unwrap();
+
+        assert_eq!(decoded, msg);
+        assert!(cbor.len() < json.len(), "cbor {} >= json {}", cbor.len(), json.len());
+    }
+
+    #[test]
+    fn test_device_message_roundtrip() {
+        let data = json!({"uptime": 3600, "cpu_temp": 45.2, "memory_free": 512, "nested": {"ok": false}});
+        let msg = DeviceMessage::new(Uuid::new_v4(), DeviceCommandKind::StatusUpdate, data);
+
+        let cbor = msg.to_cbor().unwrap();
+        let decoded = DeviceMessage::from_cbor(&cbor).unwrap();
+
+        assert_eq!(decoded.data, msg.data);
+        assert_eq!(decoded.command, DeviceCommandKind::StatusUpdate);
+        assert!(cbor.len() < serde_json::to_vec(&msg).unwrap().len());
+    }
+
+    #[test]
+    fn test_from_cbor_rejects_garbage() {
+        assert!(MqttMessage::from_cbor(b"{\"not\": \"cbor\"}").is_err());
+        assert!(matches!(SensorReading::from_cbor(&[0xA1]), Err(Error::SerializationError(_))));
+    }
+}
@@ -0,0 +1,315 @@
+//! Device Command History Types
+//!
+//! API server cihaza gönderdiği her `DeviceCommand`'ı komutu verenle
+//! birlikte denetim kaydına (`device_commands` tablosu) yazar. Edge agent'ın
+//! `devices/{id}/responses`'a publish ettiği `DeviceCommandResponse`'u
+//! gateway `POST /v1/devices/{id}/commands/ack` ile iletir ve durum geçişi
+//! olarak eklenir.
+//!
+//! Aynı kayıt iki görünümle sunulur: `CommandAuditEntry` (geçişleriyle,
+//! `GET /v1/devices/{id}/commands`) ve `CommandHistoryEntry` (son sonuç,
+//! `GET /v1/devices/{id}/commands/history`).
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::messages::DeviceCommand;
+use crate::{Error, Result};
+
+/// Cihazın komuta verdiği sonuç
+///
+/// Yanıt gelmemiş komutlar beklemededir (`ack_status: None`). Cihaz yanıt
+/// vermeden kaldırılırsa (`DELETE /v1/devices/{id}`) komut `cancelled` olur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AckStatus {
+    Executed,
+    Failed,
+    Cancelled,
+}
+
+impl AckStatus {
+    /// `DeviceCommandResponse.success`'ten
+    pub fn from_success(success: bool) -> Self {
+        if success { Self::Executed } else { Self::Failed }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Executed => "executed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl fmt::Display for AckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AckStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "executed" => Ok(Self::Executed),
+            "failed" => Ok(Self::Failed),
+            "cancelled" => Ok(Self::Cancelled),
+            _ => Err(Error::InvalidParameter(format!("unknown ack status: {s}"))),
+        }
+    }
+}
+
+/// Gönderilmiş bir komut ve sonucu
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "correlation_id": "550e8400-e29b-41d4-a716-446655440003",
+///   "command_name": "led",
+///   "parameters": {"led_id": "led_01", "state": "on"},
+///   "dispatched_at": "2024-01-20T10:30:00Z",
+///   "ack_status": "failed",
+///   "ack_message": "unknown LED `led_09`",
+///   "acked_at": "2024-01-20T10:30:01Z"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandHistoryEntry {
+    pub correlation_id: Uuid,
+    pub command_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+    /// API server'ın komutu MQTT'ye verdiği zaman
+    pub dispatched_at: DateTime<Utc>,
+    /// Yanıt gelmediyse `None` (beklemede)
+    pub ack_status: Option<AckStatus>,
+    /// Cihazın açıklaması veya hata mesajı
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ack_message: Option<String>,
+    /// Cihazın yanıtı oluşturduğu zaman
+    pub acked_at: Option<DateTime<Utc>>,
+}
+
+impl From<&CommandAuditEntry> for CommandHistoryEntry {
+    /// Denetim kaydının son sonucu (yanıt gelmediyse beklemede)
+    fn from(entry: &CommandAuditEntry) -> Self {
+        let ack = entry.transitions.last().and_then(|transition| {
+            let status = match transition.status {
+                CommandStatus::Dispatched => return None,
+                CommandStatus::Executed => AckStatus::Executed,
+                CommandStatus::Failed => AckStatus::Failed,
+                CommandStatus::Cancelled => AckStatus::Cancelled,
+            };
+            Some((status, transition))
+        });
+        Self {
+            correlation_id: entry.correlation_id,
+            command_name: entry.command_name.clone(),
+            parameters: entry.parameters.clone(),
+            dispatched_at: entry.issued_at,
+            ack_status: ack.map(|(status, _)| status),
+            ack_message: ack.and_then(|(_, transition)| transition.message.clone()),
+            acked_at: ack.map(|(_, transition)| transition.at),
+        }
+    }
+}
+
+/// Denetim kaydındaki komutun güncel durumu
+///
+/// Komut `dispatched` olarak kaydedilir; cihazın yanıtı `executed` veya
+/// `failed`'a geçirir. Yanıt gelmeden cihaz kaldırılırsa `cancelled` olur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandStatus {
+    Dispatched,
+    Executed,
+    Failed,
+    Cancelled,
+}
+
+impl CommandStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dispatched => "dispatched",
+            Self::Executed => "executed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl From<AckStatus> for CommandStatus {
+    fn from(status: AckStatus) -> Self {
+        match status {
+            AckStatus::Executed => Self::Executed,
+            AckStatus::Failed => Self::Failed,
+            AckStatus::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+impl fmt::Display for CommandStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CommandStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "dispatched" => Ok(Self::Dispatched),
+            "executed" => Ok(Self::Executed),
+            "failed" => Ok(Self::Failed),
+            "cancelled" => Ok(Self::Cancelled),
+            _ => Err(Error::InvalidParameter(format!("unknown command status: {s}"))),
+        }
+    }
+}
+
+/// Komutun bir durum geçişi
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandTransition {
+    pub status: CommandStatus,
+    /// Geçişin zamanı (yanıtlarda cihazın yanıtı oluşturduğu an)
+    pub at: DateTime<Utc>,
+    /// Cihazın açıklaması veya hata mesajı
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Denetim kaydındaki bir komut: kim, neyi, ne zaman istedi ve ne oldu
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "correlation_id": "550e8400-e29b-41d4-a716-446655440003",
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "command_type": "control",
+///   "command_name": "relay",
+///   "parameters": {"relay_id": "relay_01", "state": "open"},
+///   "issuer": "admin:ops@example.com",
+///   "issued_at": "2024-01-20T10:30:00Z",
+///   "status": "executed",
+///   "transitions": [
+///     {"status": "dispatched", "at": "2024-01-20T10:30:00Z"},
+///     {"status": "executed", "at": "2024-01-20T10:30:01Z"}
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandAuditEntry {
+    pub correlation_id: Uuid,
+    pub device_id: Uuid,
+    pub command_type: String,
+    pub command_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+    /// Komutu veren kimlik (`{rol}:{subject}`, auth kapalıyken `anonymous`)
+    pub issuer: String,
+    /// API server'ın komutu kabul ettiği zaman
+    pub issued_at: DateTime<Utc>,
+    /// Son geçişin durumu
+    pub status: CommandStatus,
+    /// Eskiden yeniye durum geçişleri (ilki her zaman `dispatched`)
+    pub transitions: Vec<CommandTransition>,
+}
+
+impl CommandAuditEntry {
+    /// Kabul edilen komutun ilk kaydı (`dispatched`)
+    pub fn issued(command: &DeviceCommand, issuer: impl Into<String>) -> Self {
+        Self {
+            correlation_id: command.correlation_id,
+            device_id: command.device_id,
+            command_type: command.command_type.clone(),
+            command_name: command.command_name.clone(),
+            parameters: command.parameters.clone(),
+            issuer: issuer.into(),
+            issued_at: command.timestamp,
+            status: CommandStatus::Dispatched,
+            transitions: vec![CommandTransition { status: CommandStatus::Dispatched, at: command.timestamp, message: None }],
+        }
+    }
+
+    /// Geçişi ekle ve durumu güncelle
+    ///
+    /// Aynı geçişin tekrarı (QoS 1 ile iki kez gelen yanıt) eklenmez;
+    /// eklendiyse `true` döner.
+    pub fn transition(&mut self, transition: CommandTransition) -> bool {
+        if self.transitions.contains(&transition) {
+            return false;
+        }
+        self.status = transition.status;
+        self.transitions.push(transition);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ack_status_round_trip() {
+        for status in [AckStatus::Executed, AckStatus::Failed, AckStatus::Cancelled] {
+            assert_eq!(serde_json::to_value(status).unwrap(), status.as_str());
+            assert_eq!(status.as_str().parse::<AckStatus>().unwrap(), status);
+        }
+        assert_eq!(AckStatus::from_success(true), AckStatus::Executed);
+        assert_eq!(AckStatus::from_success(false), AckStatus::Failed);
+        assert_eq!("pending".parse::<AckStatus>().unwrap_err().status_code(), 400);
+    }
+
+    #[test]
+    fn test_pending_entry_serializes_null_status() {
+        let entry = CommandHistoryEntry {
+            correlation_id: Uuid::nil(),
+            command_name: "take_photo".into(),
+            parameters: None,
+            dispatched_at: DateTime::parse_from_rfc3339("2024-01-20T10:30:00Z").unwrap().with_timezone(&Utc),
+            ack_status: None,
+            ack_message: None,
+            acked_at: None,
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["ack_status"], serde_json::Value::Null);
+        assert!(json.get("parameters").is_none());
+        assert_eq!(serde_json::from_value::<CommandHistoryEntry>(json).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_audit_entry_records_transitions_once() {
+        let command = DeviceCommand::new(Uuid::new_v4(), "control".into(), "relay".into())
+            .with_parameters(serde_json::json!({"relay_id": "relay_01", "state": "open"}));
+        let mut entry = CommandAuditEntry::issued(&command, "admin:ops");
+        assert_eq!((entry.status, entry.issued_at, entry.transitions.len()), (CommandStatus::Dispatched, command.timestamp, 1));
+        let pending = CommandHistoryEntry::from(&entry);
+        assert_eq!((pending.ack_status, pending.acked_at, pending.dispatched_at), (None, None, command.timestamp));
+
+        let failed = CommandTransition {
+            status: AckStatus::Failed.into(),
+            at: command.timestamp + chrono::TimeDelta::seconds(1),
+            message: Some("relay stuck".into()),
+        };
+        assert!(entry.transition(failed.clone()));
+        assert!(!entry.transition(failed));
+        assert_eq!(entry.status, CommandStatus::Failed);
+        assert_eq!(entry.transitions.len(), 2);
+        let history = CommandHistoryEntry::from(&entry);
+        assert_eq!(history.ack_status, Some(AckStatus::Failed));
+        assert_eq!((history.ack_message.as_deref(), history.acked_at), (Some("relay stuck"), Some(command.timestamp + chrono::TimeDelta::seconds(1))));
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["transitions"][0], serde_json::json!({"status": "dispatched", "at": json["issued_at"]}));
+        assert_eq!(serde_json::from_value::<CommandAuditEntry>(json).unwrap(), entry);
+        assert_eq!("pending".parse::<CommandStatus>().unwrap_err().status_code(), 400);
+    }
+}
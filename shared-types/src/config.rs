@@ -0,0 +1,120 @@
+//! Config File Lookup
+//!
+//! Servisler env var'lara ek olarak TOML dosyasından da yapılandırılabilir.
+//! Dosyanın yeri tüm servislerde aynı kuralla bulunur:
+//! 1. `RUSTYFLOW_CONFIG_FILE` ayarlıysa o dosya
+//! 2. Yoksa çalışma dizinindeki `rustyflow.toml` (varsa)
+//!
+//! Dosya `config-file` feature'ı ile `FileConfig::from_file` üzerinden
+//! okunur ve env var'larla birleştirilir (env var'lar önceliklidir); servisler
+//! sadece alan listesini `merge_config!` ile verir.
+
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "config-file")]
+use serde::de::DeserializeOwned;
+
+#[cfg(feature = "config-file")]
+use crate::{Error, Result};
+
+/// Config dosyasının yolunu veren env var
+pub const CONFIG_FILE_ENV: &str = "RUSTYFLOW_CONFIG_FILE";
+
+/// Çalışma dizininde aranan varsayılan dosya adı
+pub const DEFAULT_CONFIG_FILE: &str = "rustyflow.toml";
+
+/// Kullanılacak config dosyası (yoksa `None`)
+///
+/// `RUSTYFLOW_CONFIG_FILE` ayarlıysa dosyanın varlığına bakılmaz;
+/// okunamazsa hata `from_file`'da görünür.
+pub fn config_file_path() -> Option<PathBuf> {
+    resolve(std::env::var_os(CONFIG_FILE_ENV).map(PathBuf::from), Path::new("."))
+}
+
+fn resolve(explicit: Option<PathBuf>, work_dir: &Path) -> Option<PathBuf> {
+    if let Some(path) = explicit.filter(|p| !p.as_os_str().is_empty()) {
+        return Some(path);
+    }
+    let default = work_dir.join(DEFAULT_CONFIG_FILE);
+    default.is_file().then_some(default)
+}
+
+/// TOML dosyasından okunup env var'larla birleştirilen servis yapılandırması
+#[cfg(feature = "config-file")]
+pub trait FileConfig: DeserializeOwned {
+    /// İki yapılandırmayı alan alan birleştir (bkz. `merge_config!`)
+    fn merge(base: Self, override_: Self) -> Self;
+
+    /// TOML config dosyasını oku ve ortam değişkenleriyle birleştir
+    ///
+    /// Dosyada olmayan alanlar varsayılan değerini alır. Varsayılandan farklı
+    /// env var değerleri dosyadakini ezer (bkz. `merge`).
+    fn from_file(path: &Path) -> Result<Self> {
+        Self::from_file_with_env(path, std::env::vars())
+    }
+
+    /// `from_file`, env var'lar verilen listeden okunur
+    fn from_file_with_env(path: &Path, env: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        Ok(Self::merge(read_file(path)?, from_env(env)?))
+    }
+}
+
+/// TOML dosyasını parse et (hata mesajında dosya yolu bulunur)
+#[cfg(feature = "config-file")]
+pub fn read_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let raw = std::fs::read_to_string(path).map_err(|e| Error::Config(format!("{}: {e}", path.display())))?;
+    toml::from_str(&raw).map_err(|e| Error::Config(format!("{}: {e}", path.display())))
+}
+
+/// Env var listesinden yapılandırma oku (`envy`)
+#[cfg(feature = "config-file")]
+pub fn from_env<T: DeserializeOwned>(env: impl IntoIterator<Item = (String, String)>) -> Result<T> {
+    envy::from_iter(env).map_err(|e| Error::Config(e.to_string()))
+}
+
+/// `FileConfig::merge` gövdesi: her alan `override_`'da varsayılan
+/// değerinden farklıysa oradan, değilse `base`'den alınır
+///
+/// Bu yüzden bir alan `override_` ile varsayılan değerine geri çekilemez.
+/// Struct literal'i kurulduğu için listede eksik alan derleme hatasıdır.
+///
+/// ```
+/// use shared_types::merge_config;
+///
+/// #[derive(Default)]
+/// struct Config { host: String, port: u16 }
+///
+/// let base = Config { host: "broker".into(), port: 1883 };
+/// let merged = merge_config!(Config { host, port }, base, Config { port: 8883, ..Default::default() });
+/// assert_eq!((merged.host.as_str(), merged.port), ("broker", 8883));
+/// ```
+#[macro_export]
+macro_rules! merge_config {
+    ($config:ident { $($field:ident),* $(,)? }, $base:expr, $override_:expr $(,)?) => {{
+        let default = <$config as ::std::default::Default>::default();
+        let (base, override_) = ($base, $override_);
+        $config {
+            $($field: if override_.$field != default.$field { override_.$field } else { base.$field },)*
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_prefers_env_then_working_dir() {
+        let dir = std::env::temp_dir().join(format!("rustyflow-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(resolve(None, &dir), None);
+        assert_eq!(resolve(Some(PathBuf::new()), &dir), None);
+
+        std::fs::write(dir.join(DEFAULT_CONFIG_FILE), "").unwrap();
+        assert_eq!(resolve(None, &dir), Some(dir.join(DEFAULT_CONFIG_FILE)));
+        assert_eq!(resolve(Some("/etc/rustyflow/api.toml".into()), &dir), Some("/etc/rustyflow/api.toml".into()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+//! Servisler Arası İsim Sözleşmeleri
+//!
+//! MQTT topic'leri ve Redis key'leri birden fazla servis tarafından üretilir
+//! ve ayrıştırılır: edge agent publish eder, gateway abone olur ve topic'ten
+//! cihaz/sensör tipini okur, API server cihaza komut gönderir ve son değerleri
+//! Redis'te tutar. Formatlar sadece burada tanımlanır; birinde değişiklik
+//! diğerlerini sessizce bozmasın.
+//!
+//! ```text
+//! sensors/{cihaz}/{sensor_type}     okumalar (edge agent → gateway)
+//! devices/{id}/commands             komutlar (API server → edge agent)
+//! devices/{id}/responses            komut yanıtları (edge agent → gateway)
+//! devices/{id}/status               durum, heartbeat, hata raporu, LWT
+//! rustyflow/gateway/{client}/status gateway durumu (retained)
+//! sensor:{device_id}:{sensor_type}  son okuma (Redis, REDIS_SENSOR_TTL_SECS)
+//! device:last_seen:{device_id}      son okumanın zamanı (Redis, epoch ms, PostgreSQL yokken)
+//! ```
+//!
+//! Bu formatlar wire format'ın parçasıdır; sahada çalışan cihazlar
+//! güncellenmeden değiştirilemez (testler birebir değerleri kontrol eder).
+
+use std::fmt::Display;
+
+/// Son sensör değerlerinin Redis key prefix'i
+pub const REDIS_SENSOR_PREFIX: &str = "sensor:";
+
+/// Redis'teki son değerlerin varsayılan ömrü (1 saat)
+pub const REDIS_SENSOR_TTL_SECS: u64 = 3600;
+
+/// Cihazların son görülme zamanlarının Redis key prefix'i
+pub const REDIS_LAST_SEEN_PREFIX: &str = "device:last_seen:";
+
+/// Tüm sensör okumalarının abonelik filtresi
+pub const SENSOR_TOPIC_FILTER: &str = "sensors/#";
+
+/// Cihaz durum mesajlarının abonelik filtresi
+pub const STATUS_TOPIC_FILTER: &str = "devices/+/status";
+
+/// Komut yanıtlarının abonelik filtresi
+pub const RESPONSE_TOPIC_FILTER: &str = "devices/+/responses";
+
+/// Komut yanıtı topic'lerinin son segmenti
+pub const RESPONSE_TOPIC_SUFFIX: &str = "/responses";
+
+/// Gateway durum topic'lerinin abonelik filtresi
+pub const GATEWAY_STATUS_TOPIC_FILTER: &str = "rustyflow/gateway/+/status";
+
+// Key'ler `{prefix}{device}:{sensor_type}`, topic'ler `{base}{suffix}` olarak birleştirilir
+const _: () = assert!(REDIS_SENSOR_PREFIX.as_bytes()[REDIS_SENSOR_PREFIX.len() - 1] == b':');
+const _: () = assert!(RESPONSE_TOPIC_SUFFIX.as_bytes()[0] == b'/');
+
+/// Cihazın bir sensörünün son değerinin Redis key'i
+pub fn redis_sensor_key(device: &str, sensor_type: &str) -> String {
+    format!("{REDIS_SENSOR_PREFIX}{device}:{sensor_type}")
+}
+
+/// Cihazın tüm sensör key'lerine uyan Redis `KEYS` pattern'i
+pub fn redis_device_pattern(device: &str) -> String {
+    format!("{REDIS_SENSOR_PREFIX}{device}:*")
+}
+
+/// Cihazın son görülme zamanının Redis key'i
+pub fn redis_last_seen_key(device: &str) -> String {
+    format!("{REDIS_LAST_SEEN_PREFIX}{device}")
+}
+
+/// Okumanın publish edildiği topic (CBOR/LZ4 suffix'leri hariç)
+pub fn sensor_topic(device_name: &str, sensor_type: &str) -> String {
+    format!("sensors/{device_name}/{sensor_type}")
+}
+
+/// Cihazın komutları dinlediği topic
+pub fn command_topic(device_name: impl Display) -> String {
+    format!("devices/{device_name}/commands")
+}
+
+/// Cihazın komut yanıtlarını publish ettiği topic
+pub fn response_topic(device_name: impl Display) -> String {
+    format!("devices/{device_name}{RESPONSE_TOPIC_SUFFIX}")
+}
+
+/// Cihazın durum mesajlarını (ve LWT'sini) publish ettiği topic
+pub fn status_topic(device_name: impl Display) -> String {
+    format!("devices/{device_name}/status")
+}
+
+/// Okuma topic'inin cihaz adı ve sensör tipi (`sensor_topic`'in tersi)
+///
+/// Sensör tipinden sonraki segmentler (`/cbor`, `/lz4`) yok sayılır.
+pub fn sensor_topic_parts(topic: &str) -> Option<(&str, &str)> {
+    let (device_name, rest) = topic.strip_prefix("sensors/")?.split_once('/')?;
+    let sensor_type = rest.split('/').next().unwrap_or_default();
+    (!device_name.is_empty() && !sensor_type.is_empty()).then_some((device_name, sensor_type))
+}
+
+/// Cihaz topic'inin cihaz segmenti (`sensors/{cihaz}/...`, `devices/{id}/...`)
+pub fn topic_device(topic: &str) -> Option<&str> {
+    let rest = topic.strip_prefix("sensors/").or_else(|| topic.strip_prefix("devices/"))?;
+    rest.split('/').next().filter(|s| !s.is_empty())
+}
+
+/// Gateway'in kendi durumunu (retained) publish ettiği topic
+pub fn gateway_status_topic(client_id: &str) -> String {
+    format!("rustyflow/gateway/{client_id}/status")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    // Formatlar sahadaki cihazlarla uyumlu kalmalı: değerler birebir yazılır
+
+    #[test]
+    fn test_legacy_redis_format() {
+        assert_eq!(REDIS_SENSOR_PREFIX, "sensor:");
+        assert_eq!(REDIS_SENSOR_TTL_SECS, 3600);
+        assert_eq!(redis_sensor_key("edge-1", "temperature"), "sensor:edge-1:temperature");
+        assert_eq!(redis_device_pattern("edge-1"), "sensor:edge-1:*");
+        assert_eq!(redis_last_seen_key("edge-1"), "device:last_seen:edge-1");
+    }
+
+    #[test]
+    fn test_legacy_topic_format() {
+        let id: Uuid = "550e8400-e29b-41d4-a716-446655440000".parse().unwrap();
+        assert_eq!(sensor_topic("edge-agent", "temperature"), "sensors/edge-agent/temperature");
+        assert_eq!(command_topic(id), "devices/550e8400-e29b-41d4-a716-446655440000/commands");
+        assert_eq!(response_topic(id), "devices/550e8400-e29b-41d4-a716-446655440000/responses");
+        assert_eq!(status_topic("rpi-01"), "devices/rpi-01/status");
+        assert_eq!(gateway_status_topic("gateway-001"), "rustyflow/gateway/gateway-001/status");
+
+        assert_eq!(
+            [SENSOR_TOPIC_FILTER, STATUS_TOPIC_FILTER, RESPONSE_TOPIC_FILTER, GATEWAY_STATUS_TOPIC_FILTER],
+            ["sensors/#", "devices/+/status", "devices/+/responses", "rustyflow/gateway/+/status"]
+        );
+        assert!(response_topic("x").ends_with(RESPONSE_TOPIC_SUFFIX));
+    }
+
+    #[test]
+    fn test_topic_parsing_inverts_formats() {
+        let id = Uuid::new_v4();
+        assert_eq!(sensor_topic_parts(&sensor_topic("rpi-01", "temperature")), Some(("rpi-01", "temperature")));
+        assert_eq!(sensor_topic_parts("sensors/rpi-01/temperature/cbor/lz4"), Some(("rpi-01", "temperature")));
+        assert_eq!(topic_device(&sensor_topic("rpi-01", "temperature")), Some("rpi-01"));
+        assert_eq!(topic_device(&status_topic(id)), Some(id.to_string().as_str()));
+        assert_eq!(topic_device(&response_topic(id)), Some(id.to_string().as_str()));
+
+        for topic in ["sensors", "sensors/rpi-01", "sensors//temperature", "sensors/rpi-01/", "devices/x/status"] {
+            assert_eq!(sensor_topic_parts(topic), None, "{topic}");
+        }
+        assert_eq!(topic_device(&gateway_status_topic("gateway-001")), None);
+        assert_eq!(topic_device("devices//status"), None);
+    }
+}
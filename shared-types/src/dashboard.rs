@@ -0,0 +1,45 @@
+//! Dashboard Types
+//!
+//! Web dashboard'un başlık çubuğu için tek seferde hesaplanan özet.
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// Dashboard başlık özeti (`GET /api/summary`)
+///
+/// Her alan ilgili backend (Redis, PostgreSQL) yoksa `None` olur;
+/// dashboard bu alanları "—" olarak gösterir.
+///
+/// # Alanlar
+///
+/// - `devices_online`: Son 5 dakikada okuma gönderen cihaz sayısı
+/// - `devices_total`: Kayıtlı (gölgesi olan) cihaz sayısı
+/// - `gateways_online`: Son durumu bağlı (`connected: true`) olan gateway sayısı
+/// - `sensors_reporting`: Son bir saatte okuma gönderen cihaz + sensör tipi çifti sayısı
+/// - `readings_last_hour`: Son bir saatteki okuma sayısı
+/// - `alerts_active`: Onaylanmamış (aktif) alarm sayısı (PostgreSQL yoksa `None`)
+/// - `last_ingest_at`: En son alınan okumanın zamanı
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "devices_online": 3,
+///   "devices_total": 4,
+///   "gateways_online": 1,
+///   "sensors_reporting": 9,
+///   "readings_last_hour": 2160,
+///   "alerts_active": 2,
+///   "last_ingest_at": "2024-01-20T10:30:00Z"
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DashboardSummary {
+    pub devices_online: Option<u64>,
+    pub devices_total: Option<u64>,
+    #[serde(default)]
+    pub gateways_online: Option<u64>,
+    pub sensors_reporting: Option<u64>,
+    pub readings_last_hour: Option<u64>,
+    pub alerts_active: Option<u64>,
+    pub last_ingest_at: Option<DateTime<Utc>>,
+}
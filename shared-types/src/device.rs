@@ -0,0 +1,644 @@
+//! Device Types
+//!
+//! Cihazların sunucu tarafındaki temsili (device twin / shadow) ve
+//! cihaz kaydı (provisioning) mesajları.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+#[cfg(feature = "sqlx-support")]
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+/// Cihaz türü
+///
+/// Gölgenin `reported.kind` alanında taşınır; alan yoksa cihaz `Edge`
+/// sayılır. Gateway'ler kendi durumlarını `"kind": "gateway"` ile raporlar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum DeviceKind {
+    /// Sensör okuyan edge agent
+    #[default]
+    Edge,
+    /// MQTT gateway
+    Gateway,
+}
+
+impl DeviceKind {
+    /// Türün string karşılığı (serde ile aynı)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Edge => "edge",
+            Self::Gateway => "gateway",
+        }
+    }
+}
+
+impl FromStr for DeviceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "edge" => Ok(Self::Edge),
+            "gateway" => Ok(Self::Gateway),
+            other => Err(format!("unknown device kind '{other}' (expected edge or gateway)")),
+        }
+    }
+}
+
+impl fmt::Display for DeviceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Cihaz gölgesi (device shadow / twin)
+///
+/// Cihazın son bilinen durumunu ve sunucunun istediği durumu tutar.
+/// Cihaz yeniden başladığında `delta`'yı uygulayarak senkronize olur.
+///
+/// # Alanlar
+///
+/// - `desired`: Sunucunun/kullanıcının istediği durum
+/// - `reported`: Cihazın en son raporladığı durum (gateway günceller)
+/// - `delta`: `desired` içinde olup `reported`'da farklı/eksik olan alanlar
+/// - `version`: Her değişiklikte artar
+/// - `last_seen`: Cihazın son okumasının zamanı (sadece `GET /v1/devices`
+///   doldurur; bilinmiyorsa yazılmaz)
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "desired": {"sensor_interval_secs": 10},
+///   "reported": {"sensor_interval_secs": 5, "sensors": {"temperature": 22.5}},
+///   "delta": {"sensor_interval_secs": 10},
+///   "version": 7,
+///   "updated_at": "2024-11-13T21:30:00Z"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct DeviceShadow {
+    pub device_id: Uuid,
+    pub desired: Value,
+    pub reported: Value,
+    pub delta: Value,
+    #[cfg_attr(feature = "sqlx-support", sqlx(try_from = "i64"))]
+    pub version: u64,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "sqlx-support", sqlx(default))]
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl DeviceShadow {
+    /// Boş bir gölge oluştur (version 0)
+    pub fn new(device_id: Uuid) -> Self {
+        Self {
+            device_id,
+            desired: Value::Object(Map::new()),
+            reported: Value::Object(Map::new()),
+            delta: Value::Object(Map::new()),
+            version: 0,
+            updated_at: Utc::now(),
+            last_seen: None,
+        }
+    }
+
+    /// Raporlanan `kind` (yoksa veya tanınmıyorsa `Edge`)
+    pub fn kind(&self) -> DeviceKind {
+        self.reported
+            .get("kind")
+            .and_then(Value::as_str)
+            .and_then(|kind| kind.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// İstenen durumu güncelle (JSON merge patch, `null` alanı siler)
+    ///
+    /// Patch durumu değiştirmezse versiyon artmaz; dönüş değeri değişiklik olup olmadığıdır.
+    pub fn update_desired(&mut self, patch: &Value) -> bool {
+        let changed = apply_patch(&mut self.desired, patch);
+        if changed {
+            self.touch();
+        }
+        changed
+    }
+
+    /// Raporlanan durumu güncelle (JSON merge patch, `null` alanı siler)
+    ///
+    /// Aynı değeri tekrar raporlamak (ör. her okumada gelen sensör değeri)
+    /// versiyonu artırmaz; dönüş değeri değişiklik olup olmadığıdır.
+    pub fn update_reported(&mut self, patch: &Value) -> bool {
+        let changed = apply_patch(&mut self.reported, patch);
+        if changed {
+            self.touch();
+        }
+        changed
+    }
+
+    /// Delta'yı yeniden hesapla, versiyonu artır
+    fn touch(&mut self) {
+        self.delta = Self::compute_delta(&self.desired, &self.reported);
+        self.version += 1;
+        self.updated_at = Utc::now();
+    }
+
+    /// `desired` ile `reported` arasındaki farkı hesapla (recursive)
+    ///
+    /// Sonuç, `desired`'ın `reported`'da karşılığı olmayan veya farklı olan
+    /// alanlarını içeren bir JSON object'tir. Fark yoksa boş object döner.
+    /// Sayılar değerce karşılaştırılır (`10` ile `10.0` eşittir).
+    ///
+    /// ```
+    /// use shared_types::device::DeviceShadow;
+    /// use serde_json::json;
+    ///
+    /// let delta = DeviceShadow::compute_delta(
+    ///     &json!({"led": {"state": "on", "brightness": 100}, "interval": 5}),
+    ///     &json!({"led": {"state": "off", "brightness": 100}, "interval": 5, "uptime": 30}),
+    /// );
+    /// assert_eq!(delta, json!({"led": {"state": "on"}}));
+    /// ```
+    pub fn compute_delta(desired: &Value, reported: &Value) -> Value {
+        diff(desired, reported).unwrap_or_else(|| Value::Object(Map::new()))
+    }
+}
+
+/// Fark yoksa `None`, varsa desired tarafındaki farklı kısmı döner
+fn diff(desired: &Value, reported: &Value) -> Option<Value> {
+    match (desired, reported) {
+        (Value::Object(want), Value::Object(have)) => {
+            let delta: Map<String, Value> = want
+                .iter()
+                .filter_map(|(key, value)| {
+                    let changed = match have.get(key) {
+                        Some(current) => diff(value, current),
+                        None => Some(value.clone()),
+                    };
+                    changed.map(|v| (key.clone(), v))
+                })
+                .collect();
+            (!delta.is_empty()).then_some(Value::Object(delta))
+        }
+        _ if same_value(desired, reported) => None,
+        _ => Some(desired.clone()),
+    }
+}
+
+/// JSON eşitliği; sayılar temsil biçiminden bağımsız (`10` == `10.0`) karşılaştırılır
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x == y || x.as_f64() == y.as_f64(),
+        (Value::Array(xs), Value::Array(ys)) => {
+            xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| same_value(x, y))
+        }
+        (Value::Object(xs), Value::Object(ys)) => {
+            xs.len() == ys.len()
+                && xs.iter().all(|(key, x)| ys.get(key).is_some_and(|y| same_value(x, y)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Merge patch'i uygula; hedef değerce değiştiyse `true`
+fn apply_patch(target: &mut Value, patch: &Value) -> bool {
+    let mut patched = target.clone();
+    merge_patch(&mut patched, patch);
+    if same_value(target, &patched) {
+        return false;
+    }
+    *target = patched;
+    true
+}
+
+/// RFC 7386 JSON merge patch uygula
+///
+/// - Object'ler recursive birleştirilir
+/// - `null` değer ilgili alanı siler
+/// - Diğer değerler üzerine yazılır
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch_map) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target_map) = target {
+        for (key, value) in patch_map {
+            if value.is_null() {
+                target_map.remove(key);
+            } else {
+                merge_patch(target_map.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Kayıtlı cihaz
+///
+/// `POST /v1/provision` ile seri numarasına göre oluşturulur; aynı seri
+/// numarasıyla tekrar kayıt aynı `id`'yi döner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct Device {
+    pub id: Uuid,
+    pub serial_number: String,
+    pub model: String,
+    pub firmware_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key_pem: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Device {
+    /// Kayıt isteğinden yeni cihaz oluştur
+    pub fn new(request: &DeviceProvisioningRequest) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            serial_number: request.serial_number.trim().to_string(),
+            model: request.model.clone(),
+            firmware_version: request.firmware_version.clone(),
+            public_key_pem: request.public_key_pem.clone(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Tekrar kayıtta model/firmware'i güncelle (verilmemişse public key korunur)
+    pub fn update(&mut self, request: &DeviceProvisioningRequest) {
+        self.model = request.model.clone();
+        self.firmware_version = request.firmware_version.clone();
+        if request.public_key_pem.is_some() {
+            self.public_key_pem = request.public_key_pem.clone();
+        }
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Cihaz kayıt isteği (`POST /v1/provision`)
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "serial_number": "10000000abcdef01",
+///   "firmware_version": "0.1.0",
+///   "model": "Raspberry Pi 4 Model B Rev 1.4"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceProvisioningRequest {
+    /// Donanım seri numarası (Raspberry Pi'da `/proc/cpuinfo` → `Serial`)
+    pub serial_number: String,
+    pub firmware_version: String,
+    pub model: String,
+    /// Cihazın imza anahtarı (PEM), varsa
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key_pem: Option<String>,
+}
+
+/// Cihaz kayıt yanıtı
+///
+/// `token`, sadece bu cihaz için geçerli kalıcı bir cihaz token'ıdır
+/// (`rfd_...`, bkz. `DeviceToken`); agent saklayıp sonraki isteklerinde
+/// `Authorization: Bearer` olarak gönderir. `token_expires_at` yoksa token
+/// iptal edilene kadar geçerlidir.
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "mqtt_broker": "mqtt.local",
+///   "mqtt_port": 1883,
+///   "initial_config": {"sensor_interval_secs": 10},
+///   "token": "rfd_0123456789abcdef..."
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceProvisioningResponse {
+    pub device_id: Uuid,
+    pub mqtt_broker: String,
+    pub mqtt_port: u16,
+    pub initial_config: RemoteConfig,
+    pub token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_expires_at: Option<DateTime<Utc>>,
+}
+
+/// Cihaza özel kalıcı API token'ı
+///
+/// `POST /v1/devices/{id}/tokens` ile verilir. Token'ın kendisi saklanmaz,
+/// sadece SHA-256 hash'i; `token_hash` response'larda gösterilmez.
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "id": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "label": "raspberry-pi-01",
+///   "created_at": "2024-11-13T21:30:00Z",
+///   "expires_at": "2025-11-13T21:30:00Z",
+///   "last_used_at": null
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct DeviceToken {
+    pub id: Uuid,
+    pub device_id: Uuid,
+    pub label: Option<String>,
+    #[serde(skip_serializing, default)]
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    /// `None`: süresiz (iptal edilene kadar geçerli)
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl DeviceToken {
+    /// `now` itibarıyla süresi dolmuş mu?
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|exp| exp <= now)
+    }
+}
+
+/// Cihaz token'ı oluşturma isteği (`POST /v1/devices/{id}/tokens`)
+///
+/// # Örnek JSON
+/// ```json
+/// {"label": "raspberry-pi-01", "expires_at": "2025-11-13T21:30:00Z"}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NewDeviceToken {
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Yeni verilen cihaz token'ı
+///
+/// `token` sadece bu yanıtta döner; kaybedilirse yenisi oluşturulmalıdır.
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "token": "rfd_4f3c2a...",
+///   "id": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "label": "raspberry-pi-01",
+///   "created_at": "2024-11-13T21:30:00Z",
+///   "expires_at": null,
+///   "last_used_at": null
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssuedDeviceToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: DeviceToken,
+}
+
+/// Cihaz verisi silme isteği (`DELETE /v1/devices/{id}/data`)
+///
+/// `confirm: true` olmadan hiçbir şey silinmez. `include_metadata` ile
+/// cihaz kaydı ve gölgesi de silinir.
+///
+/// # Örnek JSON
+/// ```json
+/// {"confirm": true, "include_metadata": false}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeleteDataRequest {
+    #[serde(default)]
+    pub confirm: bool,
+    #[serde(default)]
+    pub include_metadata: bool,
+}
+
+/// Cihaz verisi silme sonucu
+///
+/// # Örnek JSON
+/// ```json
+/// {"readings_deleted": 2160, "alerts_deleted": 3, "device_deleted": false, "commands_deleted": 12, "tokens_revoked": 0}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeleteDataResult {
+    pub readings_deleted: u64,
+    pub alerts_deleted: u64,
+    /// Cihaz kaydı silindi mi? (`include_metadata` verilmediyse her zaman `false`)
+    pub device_deleted: bool,
+    /// Silinen komut kayıtları (`device_commands`)
+    #[serde(default)]
+    pub commands_deleted: u64,
+    /// İptal edilen cihaz token'ları (`include_metadata` verilmediyse her zaman 0)
+    #[serde(default)]
+    pub tokens_revoked: u64,
+}
+
+/// Cihaz silme parametreleri (`DELETE /v1/devices/{id}?purge_history=true`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceTeardownParams {
+    /// Okuma, alarm ve olay geçmişi de silinsin mi? (varsayılan: geçmiş kalır)
+    #[serde(default)]
+    pub purge_history: bool,
+}
+
+/// Cihaz silme özeti
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "device_deleted": true,
+///   "shadow_deleted": true,
+///   "pending_changes_cancelled": 1,
+///   "readings_deleted": 2160,
+///   "alerts_deleted": 3,
+///   "cache_keys_deleted": 2,
+///   "tokens_revoked": 1
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceTeardownResult {
+    /// Cihaz kaydı (`devices`) silindi mi?
+    pub device_deleted: bool,
+    /// Gölge silindi mi?
+    pub shadow_deleted: bool,
+    /// Cihazın henüz uygulamadığı `desired` alanları (gölgenin `delta`'sı)
+    pub pending_changes_cancelled: u64,
+    /// `purge_history` verilmediyse 0
+    pub readings_deleted: u64,
+    pub alerts_deleted: u64,
+    /// Silinen `sensor:{device}:*` cache key'leri (`None`: Redis yok veya temizlik başarısız)
+    pub cache_keys_deleted: Option<u64>,
+    /// İptal edilen cihaz token'ları (`/v1/devices/{id}/tokens`)
+    #[serde(default)]
+    pub tokens_revoked: u64,
+    /// Yanıtı beklenirken `cancelled` olarak kapatılan komutlar (`device_commands`)
+    #[serde(default)]
+    pub commands_cancelled: u64,
+}
+
+/// Sunucunun cihaza verdiği runtime ayarları
+///
+/// Edge agent'ın `config_update` komutundaki alanların aynısıdır; verilmeyen
+/// alanlarda cihaz kendi değerini korur.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RemoteConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensor_interval_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled_sensors: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qos: Option<u8>,
+}
+
+impl RemoteConfig {
+    /// Gölgenin `desired` bölümünden ayarları al
+    ///
+    /// Diğer alanlar (LED vb.) ve tipi uymayan değerler atlanır.
+    pub fn from_desired(desired: &Value) -> Self {
+        fn field<T: DeserializeOwned>(desired: &Value, key: &str) -> Option<T> {
+            desired.get(key).and_then(|value| T::deserialize(value).ok())
+        }
+        Self {
+            sensor_interval_secs: field(desired, "sensor_interval_secs"),
+            enabled_sensors: field(desired, "enabled_sensors"),
+            qos: field(desired, "qos"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_compute_delta_nested() {
+        let desired = json!({"config": {"interval": 10, "qos": 1}, "led": "on"});
+        let reported = json!({"config": {"interval": 5, "qos": 1}, "led": "on", "uptime": 3600});
+
+        assert_eq!(
+            DeviceShadow::compute_delta(&desired, &reported),
+            json!({"config": {"interval": 10}})
+        );
+    }
+
+    #[test]
+    fn test_compute_delta_missing_and_in_sync() {
+        assert_eq!(
+            DeviceShadow::compute_delta(&json!({"led": "on"}), &json!({})),
+            json!({"led": "on"})
+        );
+        assert_eq!(
+            DeviceShadow::compute_delta(&json!({"led": "on"}), &json!({"led": "on"})),
+            json!({})
+        );
+        // Tip uyuşmazlığında desired değer delta olur
+        assert_eq!(
+            DeviceShadow::compute_delta(&json!({"cfg": {"a": 1}}), &json!({"cfg": 1})),
+            json!({"cfg": {"a": 1}})
+        );
+    }
+
+    #[test]
+    fn test_remote_config_from_desired() {
+        let desired = json!({"sensor_interval_secs": 10, "qos": "high", "led": {"led_01": "on"}});
+        assert_eq!(
+            RemoteConfig::from_desired(&desired),
+            RemoteConfig { sensor_interval_secs: Some(10), ..RemoteConfig::default() }
+        );
+        assert_eq!(serde_json::to_value(RemoteConfig::default()).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let mut target = json!({"a": 1, "b": {"c": 2, "d": 3}});
+        merge_patch(&mut target, &json!({"a": null, "b": {"c": 5}, "e": [1]}));
+        assert_eq!(target, json!({"b": {"c": 5, "d": 3}, "e": [1]}));
+    }
+
+    #[test]
+    fn test_shadow_updates_recompute_delta() {
+        let mut shadow = DeviceShadow::new(Uuid::new_v4());
+        shadow.update_desired(&json!({"sensor_interval_secs": 10}));
+        assert_eq!(shadow.delta, json!({"sensor_interval_secs": 10}));
+        assert_eq!(shadow.version, 1);
+
+        shadow.update_reported(&json!({"sensor_interval_secs": 10, "sensors": {"temperature": 22.5}}));
+        assert_eq!(shadow.delta, json!({}));
+        assert_eq!(shadow.version, 2);
+    }
+
+    #[test]
+    fn test_unchanged_report_keeps_version() {
+        let mut shadow = DeviceShadow::new(Uuid::new_v4());
+        assert!(shadow.update_reported(&json!({"sensors": {"temperature": 22.5}})));
+        let updated_at = shadow.updated_at;
+
+        assert!(!shadow.update_reported(&json!({"sensors": {"temperature": 22.5}})));
+        assert!(!shadow.update_reported(&json!({"missing": null})));
+        assert_eq!(shadow.version, 1);
+        assert_eq!(shadow.updated_at, updated_at);
+
+        assert!(shadow.update_reported(&json!({"sensors": {"temperature": 23.0}})));
+        assert_eq!(shadow.version, 2);
+    }
+
+    #[test]
+    fn test_delta_compares_numbers_by_value() {
+        let mut shadow = DeviceShadow::new(Uuid::new_v4());
+        shadow.update_desired(&json!({"sensor_interval_secs": 10.0, "enabled_sensors": ["temperature"]}));
+        shadow.update_reported(&json!({"sensor_interval_secs": 10, "enabled_sensors": ["temperature"]}));
+        assert_eq!(shadow.delta, json!({}));
+
+        // Aynı değerin farklı temsili değişiklik sayılmaz
+        assert!(!shadow.update_reported(&json!({"sensor_interval_secs": 10.0})));
+    }
+
+    #[test]
+    fn test_shadow_kind_from_reported() {
+        let mut shadow = DeviceShadow::new(Uuid::new_v4());
+        assert_eq!(shadow.kind(), DeviceKind::Edge);
+
+        shadow.update_reported(&json!({"kind": "gateway"}));
+        assert_eq!(shadow.kind(), DeviceKind::Gateway);
+        assert_eq!(serde_json::to_value(shadow.kind()).unwrap(), json!("gateway"));
+
+        // Tanınmayan değer varsayılana düşer; parse ise hata verir
+        shadow.update_reported(&json!({"kind": "camera"}));
+        assert_eq!(shadow.kind(), DeviceKind::Edge);
+        assert!("camera".parse::<DeviceKind>().is_err());
+    }
+
+    #[test]
+    fn test_issued_device_token_hides_hash_and_expires() {
+        let now = Utc::now();
+        let info = DeviceToken {
+            id: Uuid::new_v4(),
+            device_id: Uuid::new_v4(),
+            label: Some("pi-01".into()),
+            token_hash: "abc".into(),
+            created_at: now,
+            expires_at: Some(now + chrono::Duration::hours(1)),
+            last_used_at: None,
+        };
+        let issued = serde_json::to_value(IssuedDeviceToken { token: "rfd_x".into(), info: info.clone() }).unwrap();
+        assert_eq!(issued["token"], "rfd_x");
+        assert_eq!(issued["label"], "pi-01");
+        assert!(issued.get("token_hash").is_none());
+
+        assert!(!info.is_expired(now));
+        assert!(info.is_expired(now + chrono::Duration::hours(1)));
+        assert!(!DeviceToken { expires_at: None, ..info }.is_expired(now + chrono::Duration::days(365)));
+    }
+}
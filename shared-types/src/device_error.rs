@@ -0,0 +1,212 @@
+//! Device Error Report Types
+//!
+//! Edge agent komut handler'ı ve sürücü (kamera, LED, sensör) hatalarını
+//! `error_report` `DeviceMessage`'ı olarak publish eder; gateway bunları
+//! `POST /api/devices/{id}/errors` ile API server'a iletir, API server
+//! `device_errors` tablosunda saklar (`GET /api/devices/{id}/errors`).
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+#[cfg(feature = "sqlx-support")]
+use sqlx::FromRow;
+
+use crate::{Error, Result};
+
+/// Saklanan hata mesajının maksimum uzunluğu (byte); fazlası kırpılır
+pub const MAX_ERROR_MESSAGE_BYTES: usize = 2048;
+
+/// `context` JSON'unun maksimum boyutu (byte); aşarsa özetle değiştirilir
+pub const MAX_ERROR_CONTEXT_BYTES: usize = 8192;
+
+/// Hatanın önem derecesi (artan sırada)
+///
+/// `?level=warning` filtresi `warning` ve üstünü döner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorLevel {
+    Info,
+    #[serde(alias = "warn")]
+    Warning,
+    #[default]
+    Error,
+    /// Cihaz çalışmaya devam edemiyor (ör. sürücü başlatılamadı)
+    Critical,
+}
+
+impl ErrorLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for ErrorLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ErrorLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "info" => Ok(Self::Info),
+            "warning" | "warn" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            "critical" => Ok(Self::Critical),
+            _ => Err(Error::InvalidParameter(format!("unknown error level: {s}"))),
+        }
+    }
+}
+
+/// SQL'de `level` TEXT kolonundan okunur
+impl TryFrom<String> for ErrorLevel {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+/// `error_report` mesajının `data` alanı (ve `POST /api/devices/{id}/errors` body'si)
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "level": "error",
+///   "message": "libcamera capture failed: timeout",
+///   "context": {"source": "camera"},
+///   "timestamp": "2024-01-20T10:30:00Z"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ErrorReport {
+    /// Verilmezse `error`
+    #[serde(default)]
+    pub level: ErrorLevel,
+
+    pub message: String,
+
+    /// Hataya özgü ek bilgi (komut adı, sensör tipi...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+
+    /// Hatanın cihazda oluştuğu zaman (gateway `DeviceMessage.timestamp`'ten doldurur)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl ErrorReport {
+    pub fn new(level: ErrorLevel, message: impl fmt::Display) -> Self {
+        Self { level, message: message.to_string(), context: None, timestamp: None }
+    }
+
+    /// Ek bilgi ekle
+    pub fn with_context(mut self, context: serde_json::Value) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Büyük mesajı ve bağlamı sınırlara indir; bir şey kırpıldıysa `true`
+    ///
+    /// Mesaj karakter sınırında kırpılıp `…` ile biter. `MAX_ERROR_CONTEXT_BYTES`'ı
+    /// aşan bağlam `{"truncated": true, "bytes": <boyut>}` ile değiştirilir.
+    pub fn truncate(&mut self) -> bool {
+        let mut truncated = false;
+        if self.message.len() > MAX_ERROR_MESSAGE_BYTES {
+            let mut end = MAX_ERROR_MESSAGE_BYTES - '…'.len_utf8();
+            while !self.message.is_char_boundary(end) {
+                end -= 1;
+            }
+            self.message.truncate(end);
+            self.message.push('…');
+            truncated = true;
+        }
+        let context_bytes = self.context.as_ref().map_or(0, |c| c.to_string().len());
+        if context_bytes > MAX_ERROR_CONTEXT_BYTES {
+            self.context = Some(serde_json::json!({ "truncated": true, "bytes": context_bytes }));
+            truncated = true;
+        }
+        truncated
+    }
+}
+
+/// Saklanan cihaz hatası (`GET /api/devices/{id}/errors`)
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "id": 42,
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "level": "error",
+///   "message": "libcamera capture failed: timeout",
+///   "context": {"source": "camera"},
+///   "reported_at": "2024-01-20T10:30:00Z",
+///   "received_at": "2024-01-20T10:30:01Z"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct DeviceError {
+    pub id: i64,
+    pub device_id: Uuid,
+    #[cfg_attr(feature = "sqlx-support", sqlx(try_from = "String"))]
+    pub level: ErrorLevel,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+    /// Cihazda oluştuğu zaman
+    pub reported_at: DateTime<Utc>,
+    /// API server'a ulaştığı zaman
+    pub received_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_are_ordered_and_parse() {
+        assert!(ErrorLevel::Info < ErrorLevel::Warning && ErrorLevel::Error < ErrorLevel::Critical);
+        for level in [ErrorLevel::Info, ErrorLevel::Warning, ErrorLevel::Error, ErrorLevel::Critical] {
+            assert_eq!(serde_json::to_value(level).unwrap(), level.as_str());
+            assert_eq!(level.as_str().parse::<ErrorLevel>().unwrap(), level);
+        }
+        assert_eq!("warn".parse::<ErrorLevel>().unwrap(), ErrorLevel::Warning);
+        assert_eq!("fatal".parse::<ErrorLevel>().unwrap_err().status_code(), 400);
+    }
+
+    #[test]
+    fn test_report_defaults_to_error_level() {
+        let report: ErrorReport = serde_json::from_value(serde_json::json!({"message": "i2c timeout"})).unwrap();
+        assert_eq!(report, ErrorReport::new(ErrorLevel::Error, "i2c timeout"));
+        assert_eq!(serde_json::to_value(&report).unwrap(), serde_json::json!({"level": "error", "message": "i2c timeout"}));
+    }
+
+    #[test]
+    fn test_truncate_oversized_message_and_context() {
+        let mut report = ErrorReport::new(ErrorLevel::Warning, "ğ".repeat(MAX_ERROR_MESSAGE_BYTES))
+            .with_context(serde_json::json!({"dump": "x".repeat(MAX_ERROR_CONTEXT_BYTES)}));
+        assert!(report.truncate());
+        assert!(report.message.len() <= MAX_ERROR_MESSAGE_BYTES);
+        assert!(report.message.ends_with("ğ…"));
+        assert_eq!(report.context.as_ref().unwrap()["truncated"], true);
+
+        // Sınırın altındaki rapor değişmez
+        assert!(!report.truncate());
+        let mut small = ErrorReport::new(ErrorLevel::Info, "ok").with_context(serde_json::json!({"source": "led"}));
+        assert!(!small.truncate());
+        assert_eq!(small.message, "ok");
+    }
+}
@@ -50,6 +50,10 @@ pub enum Error {
     /// Erişim reddedildi
     #[error("Forbidden: {0}")]
     Forbidden(String),
+
+    /// Yapılandırma okunamadı (config dosyası veya env var)
+    #[error("Configuration error: {0}")]
+    Config(String),
 }
 
 /// RustyFlow Result tipi
@@ -69,7 +73,7 @@ impl Error {
             Error::Unauthorized(_) => 401,
             Error::Forbidden(_) => 403,
             Error::Database(_) | Error::MqttError(_) => 503,
-            Error::SerializationError(_) | Error::InternalError(_) => 500,
+            Error::SerializationError(_) | Error::InternalError(_) | Error::Config(_) => 500,
         }
     }
 }
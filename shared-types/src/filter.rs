@@ -0,0 +1,121 @@
+//! Sensor Filters
+//!
+//! Gürültülü sensör okumalarını yumuşatan filtreler. Hareketli ortalama
+//! gecikme (lag) ekler; bilinen bir gürültü modeli için Kalman filtresi
+//! gecikmesiz ve optimal gürültü azaltımı sağlar.
+
+use serde::{Deserialize, Serialize};
+
+/// Tek boyutlu Kalman filtresi (sabit değer + rastgele yürüyüş modeli)
+///
+/// # Alanlar
+///
+/// - `process_variance` (Q): Gerçek değerin adım başına ne kadar değişebileceği.
+///   Büyükse filtre değişimlere hızlı uyar, küçükse daha çok yumuşatır.
+/// - `measurement_variance` (R): Sensör gürültüsünün varyansı (ör. ±0.5°C → 0.25)
+/// - `estimated_error` (P): Mevcut tahminin hata varyansı
+/// - `current_estimate` (x): Mevcut tahmin
+///
+/// `estimated_error` sonsuzken (`new` ile oluşturulunca) ilk ölçüm tahmin
+/// olarak alınır; böylece başlangıç değeri bilinmeyen sensörlerde ilk
+/// okumalar 0'dan yavaşça yükselmez.
+///
+/// # Örnek
+/// ```
+/// use shared_types::filter::KalmanFilter1D;
+///
+/// let mut filter = KalmanFilter1D::new(0.01, 0.25);
+/// assert_eq!(filter.update(22.4), 22.4);
+/// let smoothed = filter.update(23.4);
+/// assert!(smoothed > 22.4 && smoothed < 23.4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KalmanFilter1D {
+    pub process_variance: f64,
+    pub measurement_variance: f64,
+    pub estimated_error: f64,
+    pub current_estimate: f64,
+}
+
+impl KalmanFilter1D {
+    /// Yeni filtre oluştur (ilk ölçüm başlangıç tahmini olur)
+    pub fn new(process_variance: f64, measurement_variance: f64) -> Self {
+        Self {
+            process_variance,
+            measurement_variance,
+            estimated_error: f64::INFINITY,
+            current_estimate: 0.0,
+        }
+    }
+
+    /// Ölçümü filtreye ver, güncel tahmini döndür
+    ///
+    /// Standart 1D Kalman adımı:
+    /// - Tahmin: `P = P + Q`
+    /// - Kazanç: `K = P / (P + R)`
+    /// - Güncelleme: `x = x + K (z - x)`, `P = (1 - K) P`
+    ///
+    /// Sonlu olmayan ölçümler (NaN, ∞) yok sayılır, tahmin değişmez.
+    pub fn update(&mut self, measurement: f64) -> f64 {
+        if !measurement.is_finite() {
+            return self.current_estimate;
+        }
+        if !self.estimated_error.is_finite() {
+            self.current_estimate = measurement;
+            self.estimated_error = self.measurement_variance;
+            return measurement;
+        }
+
+        let predicted_error = self.estimated_error + self.process_variance;
+        let gain = predicted_error / (predicted_error + self.measurement_variance);
+        self.current_estimate += gain * (measurement - self.current_estimate);
+        self.estimated_error = (1.0 - gain) * predicted_error;
+        self.current_estimate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ±1.0 civarında gürültülü, gerçek değeri 20.0 olan sabit sinyal
+    const NOISY: [f64; 20] = [
+        20.9, 19.2, 20.6, 19.5, 21.1, 18.8, 20.4, 19.7, 20.8, 19.1,
+        20.3, 19.6, 21.0, 19.0, 20.5, 19.8, 20.2, 19.4, 20.7, 19.9,
+    ];
+
+    #[test]
+    fn test_converges_to_true_value() {
+        let mut filter = KalmanFilter1D::new(1e-5, 0.5);
+        let estimates: Vec<f64> = NOISY.iter().map(|&z| filter.update(z)).collect();
+
+        // İlk ölçüm aynen alınır, sonra tahmin gerçek değere yaklaşır
+        assert_eq!(estimates[0], 20.9);
+        let last = *estimates.last().unwrap();
+        assert!((last - 20.0).abs() < 0.15, "{last}");
+        // Son tahminler ham ölçümlerden çok daha az dalgalanır
+        let spread = |v: &[f64]| v.iter().cloned().fold(f64::MIN, f64::max) - v.iter().cloned().fold(f64::MAX, f64::min);
+        assert!(spread(&estimates[10..]) < spread(&NOISY[10..]) / 5.0);
+        // Hata varyansı her adımda küçülür
+        assert!(filter.estimated_error < 0.5 / 10.0);
+    }
+
+    #[test]
+    fn test_follows_step_change() {
+        let mut filter = KalmanFilter1D::new(0.1, 0.5);
+        for _ in 0..20 {
+            filter.update(20.0);
+        }
+        let after_step = (0..20).map(|_| filter.update(25.0)).last().unwrap();
+        assert!((after_step - 25.0).abs() < 0.05, "{after_step}");
+    }
+
+    #[test]
+    fn test_ignores_non_finite_measurements() {
+        let mut filter = KalmanFilter1D::new(0.01, 0.25);
+        assert_eq!(filter.update(f64::NAN), 0.0);
+        assert_eq!(filter.update(21.0), 21.0);
+        assert_eq!(filter.update(f64::INFINITY), 21.0);
+        assert_eq!(filter.estimated_error, 0.25);
+    }
+}
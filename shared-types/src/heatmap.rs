@@ -0,0 +1,227 @@
+//! Heatmap Types
+//!
+//! Bir cihazın birden çok sensör tipindeki okumalarının zaman bucket'ı ×
+//! sensör tipi matrisi (`GET /v1/sensors/heatmap`). D3 gibi grafik
+//! kütüphaneleri heatmap'i doğrudan bu matristen çizer.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{Error, Result};
+
+/// Tek sorguda en fazla bucket (matris satırı) sayısı
+pub const HEATMAP_MAX_BUCKETS: usize = 1000;
+/// Tek sorguda en fazla sensör tipi (matris sütunu) sayısı
+pub const HEATMAP_MAX_SENSOR_TYPES: usize = 20;
+
+/// Heatmap sorgu parametreleri
+///
+/// Bucket'lar Unix epoch'a hizalıdır (`floor(epoch / genişlik) * genişlik`);
+/// ilk bucket `from`'u, son bucket `to`'yu içerir.
+///
+/// # Örnek
+/// ```text
+/// GET /v1/sensors/heatmap?device_id=edge-agent-001&sensor_types=temperature,humidity
+///     &from=2024-01-20T00:00:00Z&to=2024-01-21T00:00:00Z&bucket_minutes=60
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeatmapParams {
+    pub device_id: String,
+    /// Virgülle ayrılmış sensör tipleri (matris sütunlarının sırası)
+    #[serde(deserialize_with = "comma_separated")]
+    pub sensor_types: Vec<String>,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Bucket genişliği (dakika)
+    pub bucket_minutes: u32,
+}
+
+/// `"temperature, humidity"` → `["temperature", "humidity"]` (boş parçalar atlanır)
+fn comma_separated<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<String>, D::Error> {
+    let raw = String::deserialize(deserializer)?;
+    Ok(raw.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect())
+}
+
+impl HeatmapParams {
+    /// Bucket genişliği (saniye)
+    pub fn bucket_secs(&self) -> i64 {
+        i64::from(self.bucket_minutes) * 60
+    }
+
+    /// Parametreleri doğrula; tekrarlanan sensör tipleri ilk sırasında tutulur
+    ///
+    /// `InvalidParameter`: sensör tipi yok veya `HEATMAP_MAX_SENSOR_TYPES`'tan
+    /// fazla, `bucket_minutes` 0, `to` `from`'dan önce veya aralık
+    /// `HEATMAP_MAX_BUCKETS`'tan fazla bucket gerektiriyor.
+    pub fn validate(&mut self) -> Result<()> {
+        let mut seen = std::collections::HashSet::new();
+        self.sensor_types.retain(|t| seen.insert(t.clone()));
+
+        if self.sensor_types.is_empty() || self.sensor_types.len() > HEATMAP_MAX_SENSOR_TYPES {
+            return Err(Error::InvalidParameter(format!(
+                "sensor_types must list 1-{HEATMAP_MAX_SENSOR_TYPES} types"
+            )));
+        }
+        if self.bucket_minutes == 0 {
+            return Err(Error::InvalidParameter("bucket_minutes must be positive".into()));
+        }
+        if self.to < self.from {
+            return Err(Error::InvalidParameter("to is before from".into()));
+        }
+        let buckets = (self.bucket_start(self.to) - self.bucket_start(self.from)).num_seconds() / self.bucket_secs() + 1;
+        if buckets > HEATMAP_MAX_BUCKETS as i64 {
+            return Err(Error::InvalidParameter(format!(
+                "{buckets} buckets requested, at most {HEATMAP_MAX_BUCKETS} allowed"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Zamanın düştüğü bucket'ın başlangıcı
+    pub fn bucket_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let secs = at.timestamp().div_euclid(self.bucket_secs()) * self.bucket_secs();
+        DateTime::from_timestamp(secs, 0).unwrap_or(at)
+    }
+
+    /// `from`'dan `to`'ya tüm bucket başlangıçları (matris satırları)
+    ///
+    /// `validate`'ten sonra çağrılmalıdır.
+    pub fn buckets(&self) -> Vec<DateTime<Utc>> {
+        let (first, last) = (self.bucket_start(self.from), self.bucket_start(self.to));
+        let step = Duration::seconds(self.bucket_secs());
+        std::iter::successors(Some(first), |b| Some(*b + step))
+            .take_while(|b| *b <= last)
+            .take(HEATMAP_MAX_BUCKETS)
+            .collect()
+    }
+}
+
+/// Heatmap matrisi
+///
+/// `values[satır][sütun]`: `buckets[satır]` bucket'ında `sensor_types[sütun]`
+/// okumalarının ortalaması; okuma yoksa `null`.
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "buckets": ["2024-01-20T10:00:00Z", "2024-01-20T11:00:00Z"],
+///   "sensor_types": ["temperature", "humidity"],
+///   "values": [[22.4, 58.0], [null, 61.5]]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeatmapResponse {
+    pub buckets: Vec<DateTime<Utc>>,
+    pub sensor_types: Vec<String>,
+    pub values: Vec<Vec<Option<f64>>>,
+}
+
+impl HeatmapResponse {
+    /// Seyrek `(bucket, sensor_type, ortalama)` hücrelerinden yoğun matris
+    ///
+    /// Satır veya sütunu olmayan hücreler atlanır.
+    pub fn from_cells(
+        buckets: Vec<DateTime<Utc>>,
+        sensor_types: Vec<String>,
+        cells: impl IntoIterator<Item = (DateTime<Utc>, String, f64)>,
+    ) -> Self {
+        let mut values = vec![vec![None; sensor_types.len()]; buckets.len()];
+        for (bucket, sensor_type, value) in cells {
+            let row = buckets.binary_search(&bucket).ok();
+            let col = sensor_types.iter().position(|t| *t == sensor_type);
+            if let (Some(row), Some(col)) = (row, col) {
+                values[row][col] = Some(value);
+            }
+        }
+        Self { buckets, sensor_types, values }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(raw: &str) -> DateTime<Utc> {
+        raw.parse().unwrap()
+    }
+
+    fn params(sensor_types: &[&str], from: &str, to: &str, bucket_minutes: u32) -> HeatmapParams {
+        HeatmapParams {
+            device_id: "edge-agent-001".into(),
+            sensor_types: sensor_types.iter().map(|t| t.to_string()).collect(),
+            from: at(from),
+            to: at(to),
+            bucket_minutes,
+        }
+    }
+
+    #[test]
+    fn test_query_string_sensor_types_are_comma_separated() {
+        let json = serde_json::json!({
+            "device_id": "edge-agent-001",
+            "sensor_types": "temperature, humidity,,motion",
+            "from": "2024-01-20T00:00:00Z",
+            "to": "2024-01-21T00:00:00Z",
+            "bucket_minutes": 60
+        });
+        let params: HeatmapParams = serde_json::from_value(json).unwrap();
+        assert_eq!(params.sensor_types, vec!["temperature", "humidity", "motion"]);
+    }
+
+    #[test]
+    fn test_buckets_are_epoch_aligned_and_inclusive() {
+        let mut p = params(&["temperature"], "2024-01-20T10:07:00Z", "2024-01-20T10:45:00Z", 15);
+        p.validate().unwrap();
+        assert_eq!(
+            p.buckets(),
+            vec![at("2024-01-20T10:00:00Z"), at("2024-01-20T10:15:00Z"), at("2024-01-20T10:30:00Z"), at("2024-01-20T10:45:00Z")]
+        );
+        assert_eq!(p.bucket_start(at("2024-01-20T10:29:59Z")), at("2024-01-20T10:15:00Z"));
+
+        // 1 günlük bucket'lar UTC gün başına hizalı
+        let mut p = params(&["temperature"], "2024-01-20T13:00:00Z", "2024-01-22T01:00:00Z", 24 * 60);
+        p.validate().unwrap();
+        assert_eq!(p.buckets(), vec![at("2024-01-20T00:00:00Z"), at("2024-01-21T00:00:00Z"), at("2024-01-22T00:00:00Z")]);
+    }
+
+    #[test]
+    fn test_validation_limits() {
+        let ok = params(&["temperature", "humidity", "temperature"], "2024-01-20T00:00:00Z", "2024-01-20T16:39:00Z", 1);
+        let mut deduped = ok.clone();
+        deduped.validate().unwrap();
+        assert_eq!(deduped.sensor_types, vec!["temperature", "humidity"]);
+        assert_eq!(deduped.buckets().len(), HEATMAP_MAX_BUCKETS);
+
+        let too_many_types: Vec<String> = (0..=HEATMAP_MAX_SENSOR_TYPES).map(|i| format!("type-{i}")).collect();
+        for mut bad in [
+            HeatmapParams { to: at("2024-01-20T16:40:00Z"), ..ok.clone() },
+            HeatmapParams { sensor_types: vec![], ..ok.clone() },
+            HeatmapParams { sensor_types: too_many_types, ..ok.clone() },
+            HeatmapParams { bucket_minutes: 0, ..ok.clone() },
+            HeatmapParams { to: at("2024-01-19T00:00:00Z"), ..ok.clone() },
+        ] {
+            let err = bad.validate().unwrap_err();
+            assert_eq!(err.status_code(), 400, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_sparse_cells_fill_dense_matrix() {
+        let buckets = vec![at("2024-01-20T10:00:00Z"), at("2024-01-20T11:00:00Z"), at("2024-01-20T12:00:00Z")];
+        let sensor_types = vec!["temperature".to_string(), "humidity".to_string()];
+        let cells = vec![
+            (at("2024-01-20T10:00:00Z"), "humidity".to_string(), 58.0),
+            (at("2024-01-20T12:00:00Z"), "temperature".to_string(), 22.4),
+            // Matriste olmayan tip ve bucket atlanır
+            (at("2024-01-20T12:00:00Z"), "motion".to_string(), 1.0),
+            (at("2024-01-20T13:00:00Z"), "temperature".to_string(), 23.0),
+        ];
+
+        let heatmap = HeatmapResponse::from_cells(buckets, sensor_types, cells);
+        assert_eq!(heatmap.values, vec![vec![None, Some(58.0)], vec![None, None], vec![Some(22.4), None]]);
+        assert_eq!(
+            serde_json::to_value(&heatmap).unwrap()["values"],
+            serde_json::json!([[null, 58.0], [null, null], [22.4, null]])
+        );
+    }
+}
@@ -17,12 +17,55 @@
 //! - Maintenance'i kolaylaştır
 
 pub mod media;
+pub mod alert;
+pub mod command_history;
+pub mod config;
+pub mod conventions;
+pub mod device;
+pub mod device_error;
+pub mod dashboard;
 pub mod error;
+pub mod filter;
+pub mod heatmap;
 pub mod sensor;
 pub mod messages;
+pub mod motion;
+pub mod timeline;
+pub mod unit;
+pub mod webhook;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "lz4")]
+pub mod lz4;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+#[cfg(feature = "schemars")]
+pub mod schema;
 
 // Re-export sık kullanılan tipler
-pub use media::{Media, NewMedia, UpdateMedia};
+pub use media::{BulkCreatedMedia, Media, MediaDeleteOutcome, MediaDeleteResult, MediaQuery, MimeType, NewMedia, SortField, SortOrder, SortParams, UpdateMedia, UploadMediaParams};
 pub use error::{Result, Error};
-pub use sensor::{Sensor, SensorReading};
-pub use messages::{MqttMessage, DeviceMessage};
+pub use filter::KalmanFilter1D;
+pub use sensor::{IngestResult, NewSensorGroup, Sensor, SensorGroup, SensorGroupMember, SensorGroupReadings, SensorReading, SensorReadingBuffer, SensorReadingValidator, SensorStats, SensorSummary, SensorType};
+pub use messages::{DeviceCommandKind, DeviceMessage, DeviceStatus, DeviceStatusMessage, GatewayCounters, GatewayStatus, MessagePriority, MqttMessage};
+pub use motion::MotionEvent;
+pub use timeline::{DeviceTimeline, TimelineEvent, TimelineEventType};
+pub use device::{
+    DeleteDataRequest, DeleteDataResult, Device, DeviceKind, DeviceProvisioningRequest, DeviceProvisioningResponse, DeviceShadow,
+    DeviceTeardownParams, DeviceTeardownResult, DeviceToken, IssuedDeviceToken, NewDeviceToken, RemoteConfig,
+};
+pub use command_history::{AckStatus, CommandAuditEntry, CommandHistoryEntry, CommandStatus, CommandTransition};
+pub use dashboard::DashboardSummary;
+pub use device_error::{DeviceError, ErrorLevel, ErrorReport};
+pub use heatmap::{HeatmapParams, HeatmapResponse};
+pub use alert::{AlertThreshold, ReplayRequest, ReplayResult, SensorAlert};
+pub use webhook::{NewWebhook, Webhook, WebhookEvent};
+pub use unit::Unit;
+#[cfg(feature = "cbor")]
+pub use cbor::Cbor;
+#[cfg(feature = "msgpack")]
+pub use msgpack::MsgPack;
+#[cfg(feature = "jsonschema")]
+pub use messages::MqttMessageSchema;
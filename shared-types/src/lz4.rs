@@ -0,0 +1,120 @@
+//! LZ4 Payload Sıkıştırması
+//!
+//! `lz4` feature'ı ile açılır. Edge agent `MQTT_COMPRESS=true` ile
+//! serialize edilmiş (JSON veya CBOR) payload'u sıkıştırır ve topic'e
+//! `/lz4` ekler; gateway suffix'i görünce parse etmeden önce açar.
+//! Format: 4 byte little-endian orijinal boyut + LZ4 block
+//! (`lz4_flex::compress_prepend_size`).
+//!
+//! # Örnek
+//! ```
+//! use shared_types::lz4;
+//!
+//! let json = br#"{"value":"23.5","value2":"23.5","value3":"23.5"}"#;
+//! let compressed = lz4::compress(json);
+//! assert_eq!(lz4::decompress(&compressed).unwrap(), json);
+//! ```
+
+use crate::{Error, Result};
+
+/// Sıkıştırılmış payload'ların topic suffix'i (`sensors/dev/temp/lz4`)
+pub const TOPIC_SUFFIX: &str = "/lz4";
+
+/// Açılmasına izin verilen en büyük payload (byte)
+///
+/// Boyut başlığı güvenilmez girdidir; sahte bir başlık gigabyte'larca
+/// bellek ayırtmasın diye açmadan önce kontrol edilir.
+pub const MAX_DECOMPRESSED_SIZE: usize = 1024 * 1024;
+
+/// Payload'u sıkıştır (boyut başlığı eklenir)
+pub fn compress(bytes: &[u8]) -> Vec<u8> {
+    lz4_flex::compress_prepend_size(bytes)
+}
+
+/// `compress` çıktısını aç
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>> {
+    decompress_limited(bytes, MAX_DECOMPRESSED_SIZE)
+}
+
+/// Boyut başlığındaki orijinal boyut (başlık yoksa `None`)
+pub fn decompressed_size(bytes: &[u8]) -> Option<usize> {
+    let header: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+    Some(u32::from_le_bytes(header) as usize)
+}
+
+/// `decompress`, başlık `max_size`'tan (en fazla `MAX_DECOMPRESSED_SIZE`)
+/// büyük bir boyut bildiriyorsa bellek ayırmadan hata döner
+pub fn decompress_limited(bytes: &[u8], max_size: usize) -> Result<Vec<u8>> {
+    let size = decompressed_size(bytes)
+        .ok_or_else(|| Error::SerializationError("lz4 payload is missing its size header".into()))?;
+    let max_size = max_size.min(MAX_DECOMPRESSED_SIZE);
+    if size > max_size {
+        return Err(Error::SerializationError(format!("lz4 payload claims {size} bytes (max {max_size})")));
+    }
+    let out = lz4_flex::decompress_size_prepended(bytes).map_err(|e| Error::SerializationError(e.to_string()))?;
+    // Kesilmiş bir block hatasız ama eksik açılabilir
+    if out.len() != size {
+        return Err(Error::SerializationError(format!("lz4 payload decoded to {} bytes, expected {size}", out.len())));
+    }
+    Ok(out)
+}
+
+/// Sıkıştırma oranı (orijinal / sıkıştırılmış; 2.0 = yarı boyut)
+pub fn ratio(original: usize, compressed: usize) -> f64 {
+    original as f64 / compressed.max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::MqttMessage;
+    use crate::sensor::SensorReading;
+    use uuid::Uuid;
+
+    /// Bir cihazın bir dakikalık okumaları (JSON array)
+    fn sensor_batch() -> Vec<u8> {
+        let device_id = Uuid::new_v4();
+        let sensor_id = Uuid::new_v4();
+        let messages: Vec<MqttMessage> = (0..12)
+            .map(|i| {
+                let reading = SensorReading::new(sensor_id, format!("{:.1}", 21.0 + (i % 5) as f64 * 0.3));
+                MqttMessage::new("temperature_reading".to_string(), serde_json::to_value(reading).unwrap(), device_id)
+            })
+            .collect();
+        serde_json::to_vec(&messages).unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip_and_ratio() {
+        let batch = sensor_batch();
+        let compressed = compress(&batch);
+        assert_eq!(decompress(&compressed).unwrap(), batch);
+
+        let ratio = ratio(batch.len(), compressed.len());
+        assert!(ratio > 1.5, "ratio {ratio:.2} ({} → {} bytes)", batch.len(), compressed.len());
+    }
+
+    #[test]
+    fn test_rejects_truncated_and_oversized_payloads() {
+        assert!(decompress(&[1, 0]).is_err());
+
+        // Başlık 4 GiB'a yakın bir boyut iddia ediyor: bellek ayrılmadan reddedilir
+        let mut bomb = compress(b"tiny");
+        bomb[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = decompress(&bomb).unwrap_err().to_string();
+        assert!(err.contains("max 1048576"), "{err}");
+
+        // Çağıranın sınırı başlıkla karşılaştırılır
+        let payload = compress(&[b'x'; 600]);
+        assert_eq!(decompressed_size(&payload), Some(600));
+        assert_eq!(decompress_limited(&payload, 600).unwrap().len(), 600);
+        let err = decompress_limited(&payload, 599).unwrap_err().to_string();
+        assert!(err.contains("claims 600 bytes (max 599)"), "{err}");
+
+        // Her kesme noktasında reddedilir (eksik açılan payload geçmez)
+        let compressed = compress(&sensor_batch());
+        for len in 4..compressed.len() {
+            assert!(decompress(&compressed[..len]).is_err(), "accepted {len}/{} bytes", compressed.len());
+        }
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! Fotoğraf, video ve diğer medya dosyalarını temsil eden veri yapıları.
 
-use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 #[cfg(feature = "sqlx-support")]
 use sqlx::FromRow;
@@ -22,6 +26,9 @@ use chrono::{DateTime, Utc};
 /// - `size_bytes`: Dosya boyutu (bytes cinsinden)
 /// - `created_at`: Oluşturulma tarihi (ISO 8601)
 /// - `updated_at`: Son güncellenme tarihi (ISO 8601)
+/// - `version`: Her güncellemede bir artan sürüm (optimistic concurrency)
+/// - `checksum_sha256`: İçeriğin SHA-256'sı (sadece `POST /v1/media/upload` ile yüklenenlerde)
+/// - `rank`: Arama skoru (sadece `?q=` ile listelemede dolu)
 /// 
 /// # Serializasyon
 /// 
@@ -39,10 +46,11 @@ use chrono::{DateTime, Utc};
 ///   "mime_type": "image/png",
 ///   "size_bytes": 24576,
 ///   "created_at": "2024-11-13T21:30:00Z",
-///   "updated_at": "2024-11-13T21:30:00Z"
+///   "updated_at": "2024-11-13T21:30:00Z",
+///   "version": 1
 /// }
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "sqlx-support", derive(FromRow))]
 pub struct Media {
     pub id: Uuid,
@@ -52,6 +60,23 @@ pub struct Media {
     pub size_bytes: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Kaydın sürümü (ilk oluşturmada 1)
+    /// 
+    /// `version` alanı olmayan eski JSON'lar (örn. snapshot dosyaları) 1 kabul edilir.
+    #[serde(default = "default_version")]
+    pub version: i32,
+    /// İçeriğin SHA-256 özeti (küçük harf hex)
+    /// 
+    /// Upload sırasında hesaplanır; aynı içerik ikinci kez kaydedilmez.
+    /// Sadece metadata ile oluşturulan kayıtlarda `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum_sha256: Option<String>,
+    /// Full-text arama skoru (PostgreSQL `ts_rank`)
+    /// 
+    /// Sorguda `rank` kolonu yoksa `None` kalır.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "sqlx-support", sqlx(default))]
+    pub rank: Option<f32>,
 }
 
 /// Yeni medya oluştururken gönderilen request body
@@ -67,10 +92,13 @@ pub struct Media {
 ///   "size_bytes": 2048576
 /// }
 /// ```
-#[derive(Debug, Deserialize)]
+/// 
+/// `mime_type` deserialize edilirken doğrulanır (bkz. `MimeType::parse`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewMedia {
     pub name: String,
     pub path: String,
+    #[serde(deserialize_with = "deserialize_mime_type")]
     pub mime_type: String,
     pub size_bytes: i64,
 }
@@ -94,12 +122,416 @@ pub struct NewMedia {
 ///   "path": "/uploads/photos/archive/new-location.jpg"
 /// }
 /// ```
-#[derive(Debug, Deserialize)]
+/// 
+/// # Örnek 3: Sadece kayıt hâlâ 3. sürümdeyse güncelle
+/// ```json
+/// {
+///   "name": "new-name.jpg",
+///   "expected_version": 3
+/// }
+/// ```
+#[derive(Debug, Default, Deserialize)]
 pub struct UpdateMedia {
     pub name: Option<String>,
     pub path: Option<String>,
+    /// Verilirse `NewMedia::mime_type` gibi doğrulanır
+    #[serde(default, deserialize_with = "deserialize_optional_mime_type")]
     pub mime_type: Option<String>,
     pub size_bytes: Option<i64>,
+    /// Client'ın gördüğü sürüm; kayıt başka bir sürümdeyse güncelleme reddedilir
+    #[serde(default)]
+    pub expected_version: Option<i32>,
+}
+
+/// Doğrulanmış MIME type (`type/subtype[;params]`)
+/// 
+/// Üst tip `MIME_TOP_LEVEL_TYPES`'tan biri olmalıdır; tip ve alt tip RFC 6838
+/// karakterlerinden oluşur, joker (`image/*`) kabul edilmez. Tip ve alt tip
+/// küçük harfe çevrilir, parametreler olduğu gibi korunur:
+/// 
+/// ```
+/// use shared_types::MimeType;
+/// 
+/// let mime = MimeType::parse("Image/JPEG; quality=high").unwrap();
+/// assert_eq!(mime.as_str(), "image/jpeg; quality=high");
+/// assert!(mime.is_image());
+/// assert!(MimeType::parse("foobar").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MimeType {
+    value: String,
+    /// `value` içindeki `/` konumu
+    slash: usize,
+    /// Parametrelerden önceki kısmın (`type/subtype`) uzunluğu
+    essence_len: usize,
+}
+
+/// Kabul edilen üst tipler
+pub const MIME_TOP_LEVEL_TYPES: &[&str] = &["image", "video", "audio", "application", "text"];
+
+/// MIME type'ın en fazla uzunluğu (parametreler dahil)
+const MAX_MIME_TYPE_LEN: usize = 255;
+
+impl MimeType {
+    /// MIME type'ı doğrula ve normalize et
+    /// 
+    /// Geçersizse `Error::InvalidParameter` (mesaj nedenini söyler).
+    pub fn parse(s: &str) -> crate::Result<Self> {
+        let invalid = |reason: &str| crate::Error::InvalidParameter(format!("invalid mime_type {s:?}: {reason}"));
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(invalid("empty"));
+        }
+        if s.len() > MAX_MIME_TYPE_LEN {
+            return Err(invalid("too long"));
+        }
+
+        let (essence, params) = s.split_once(';').map_or((s, None), |(e, p)| (e, Some(p)));
+        let (top, sub) = essence.trim().split_once('/').ok_or_else(|| invalid("expected type/subtype"))?;
+        if !is_restricted_name(top) || !is_restricted_name(sub) {
+            return Err(invalid("type and subtype must be non-empty tokens (no wildcards)"));
+        }
+        let top = top.to_ascii_lowercase();
+        if !MIME_TOP_LEVEL_TYPES.contains(&top.as_str()) {
+            return Err(invalid(&format!("top-level type must be one of {}", MIME_TOP_LEVEL_TYPES.join(", "))));
+        }
+
+        let mut value = format!("{top}/{}", sub.to_ascii_lowercase());
+        let essence_len = value.len();
+        for param in params.into_iter().flat_map(|p| p.split(';')) {
+            let (name, val) = param.trim().split_once('=').ok_or_else(|| invalid("parameters must be name=value"))?;
+            if !is_restricted_name(name.trim()) || val.trim().is_empty() || val.chars().any(char::is_control) {
+                return Err(invalid("parameters must be name=value"));
+            }
+            value.push_str(&format!("; {}={}", name.trim(), val.trim()));
+        }
+        Ok(Self { slash: top.len(), value, essence_len })
+    }
+
+    /// Normalize edilmiş MIME type (`image/jpeg; charset=utf-8`)
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// Parametresiz kısım (`image/jpeg`)
+    pub fn essence(&self) -> &str {
+        &self.value[..self.essence_len]
+    }
+
+    /// Üst tip (`image`)
+    pub fn top_level(&self) -> &str {
+        &self.value[..self.slash]
+    }
+
+    /// Alt tip (`jpeg`)
+    pub fn subtype(&self) -> &str {
+        &self.value[self.slash + 1..self.essence_len]
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.top_level() == "image"
+    }
+
+    pub fn is_video(&self) -> bool {
+        self.top_level() == "video"
+    }
+
+    pub fn is_audio(&self) -> bool {
+        self.top_level() == "audio"
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.top_level() == "text"
+    }
+
+    pub fn is_application(&self) -> bool {
+        self.top_level() == "application"
+    }
+}
+
+impl FromStr for MimeType {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for MimeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl From<MimeType> for String {
+    fn from(mime: MimeType) -> Self {
+        mime.value
+    }
+}
+
+/// RFC 6838 `restricted-name`: harf/rakamla başlar, en fazla 127 karakter
+fn is_restricted_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && s.len() <= 127
+        && chars.all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+}
+
+/// `NewMedia::mime_type`: doğrulanmış ve normalize edilmiş MIME type
+fn deserialize_mime_type<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    let raw = String::deserialize(deserializer)?;
+    MimeType::parse(&raw).map(String::from).map_err(serde::de::Error::custom)
+}
+
+/// `UpdateMedia::mime_type`: `null` veya doğrulanmış MIME type
+fn deserialize_optional_mime_type<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<String>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|raw| MimeType::parse(&raw).map(String::from).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Medya listeleme filtreleri ve sayfalama (query string)
+/// 
+/// `GET /v1/media` tarafından kullanılır. Tüm filtreler opsiyoneldir ve
+/// AND ile birleştirilir.
+/// 
+/// # Örnek
+/// ```text
+/// GET /v1/media?mime_type=image/%&min_size=1024&name_contains=vacation&limit=20&offset=40
+/// ```
+/// 
+/// - `mime_type`: SQL LIKE pattern'i (`%` = herhangi dizi, `_` = tek karakter)
+/// - `min_size` / `max_size`: Boyut aralığı (bytes, dahil)
+/// - `name_contains`: Büyük/küçük harf duyarsız isim araması
+/// - `created_after` / `created_before`: Oluşturulma zamanı aralığı (hariç)
+/// - `q`: Full-text arama (kelimeler AND ile birleşir, sonuçlar skora göre sıralanır)
+/// - `checksum`: İçeriği bu SHA-256'ya sahip kayıt (büyük/küçük harf duyarsız hex)
+/// - `limit` / `offset`: Sayfalama
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MediaQuery {
+    pub q: Option<String>,
+    pub mime_type: Option<String>,
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    pub name_contains: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub checksum: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl MediaQuery {
+    /// Media nesnesi filtrelere uyuyor mu? (in-memory store için)
+    /// 
+    /// PostgreSQL WHERE clause'u ile aynı semantiğe sahiptir.
+    /// Sayfalama (`limit`/`offset`) burada uygulanmaz.
+    pub fn matches(&self, media: &Media) -> bool {
+        if let Some(pattern) = &self.mime_type {
+            if !like_match(pattern, &media.mime_type) {
+                return false;
+            }
+        }
+        if self.min_size.is_some_and(|min| media.size_bytes < min) {
+            return false;
+        }
+        if self.max_size.is_some_and(|max| media.size_bytes > max) {
+            return false;
+        }
+        if let Some(needle) = &self.name_contains {
+            if !media.name.to_lowercase().contains(&needle.to_lowercase()) {
+                return false;
+            }
+        }
+        if self.created_after.is_some_and(|after| media.created_at <= after) {
+            return false;
+        }
+        if self.created_before.is_some_and(|before| media.created_at >= before) {
+            return false;
+        }
+        if let Some(checksum) = self.checksum_filter() {
+            if media.checksum_sha256.as_deref() != Some(checksum.as_str()) {
+                return false;
+            }
+        }
+        let terms = self.search_terms();
+        if !terms.is_empty() && Self::search_rank(&terms, media).is_none() {
+            return false;
+        }
+        true
+    }
+
+    /// `checksum` parametresi, kayıtlardaki biçimde (küçük harf)
+    pub fn checksum_filter(&self) -> Option<String> {
+        self.checksum.as_deref().map(|c| c.trim().to_ascii_lowercase())
+    }
+
+    /// `q` parametresini arama kelimelerine ayır
+    /// 
+    /// Harf/rakam olmayan karakterler ayraç sayılır (`plainto_tsquery` gibi);
+    /// kelime kalmazsa (boş veya sadece noktalama) arama yapılmaz.
+    pub fn search_terms(&self) -> Vec<String> {
+        self.q
+            .as_deref()
+            .unwrap_or_default()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|t| !t.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    /// In-memory arama skoru (tüm kelimeler isimde geçmiyorsa `None`)
+    /// 
+    /// Skor, eşleşen kelimelerin ismin ne kadarını kapladığıdır (0..=1);
+    /// kısa ve tam eşleşen isimler öne çıkar.
+    pub fn search_rank(terms: &[String], media: &Media) -> Option<f32> {
+        let name = media.name.to_lowercase();
+        if !terms.iter().all(|t| name.contains(t.as_str())) {
+            return None;
+        }
+        let matched: usize = terms.iter().map(|t| t.chars().count()).sum();
+        Some((matched as f32 / name.chars().count().max(1) as f32).min(1.0))
+    }
+}
+
+/// Ham içerik upload'unun query parametreleri
+/// 
+/// `POST /v1/media/upload` tarafından kullanılır; içerik request body'sidir,
+/// MIME type `Content-Type` header'ından alınır.
+/// 
+/// # Örnek
+/// ```text
+/// POST /v1/media/upload?name=vacation.jpg&dedup=true
+/// ```
+/// 
+/// - `name`: Medya dosyasının adı
+/// - `dedup`: Aynı içerik zaten varsa 409 yerine mevcut kaydı döndür
+#[derive(Debug, Clone, Deserialize)]
+pub struct UploadMediaParams {
+    pub name: String,
+    #[serde(default)]
+    pub dedup: bool,
+}
+
+/// `POST /v1/media/bulk` response'u
+/// 
+/// Oluşturulan kayıtların ID'leri, request'teki sırayla.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkCreatedMedia {
+    pub ids: Vec<Uuid>,
+}
+
+/// Toplu silmede tek bir ID'nin sonucu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaDeleteOutcome {
+    Deleted,
+    /// Kayıt yoktu (veya aynı istekte daha önce silindi)
+    NotFound,
+}
+
+/// `POST /v1/media/bulk-delete` response'unun bir elemanı
+/// 
+/// # Örnek
+/// ```json
+/// {"id": "550e8400-e29b-41d4-a716-446655440000", "outcome": "not_found"}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaDeleteResult {
+    pub id: Uuid,
+    pub outcome: MediaDeleteOutcome,
+}
+
+/// Medya listesinin sıralama parametreleri (query string)
+/// 
+/// # Örnek
+/// ```text
+/// GET /v1/media?sort_by=size_bytes&order=desc
+/// ```
+/// 
+/// `sort_by` verilmezse varsayılan sıra geçerlidir: `created_at` (artan),
+/// `q` varsa arama skoru (azalan). Eşit değerler her zaman `id` ile sıralanır,
+/// böylece sayfalama sayfalar arasında kayıt atlamaz.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct SortParams {
+    pub sort_by: Option<SortField>,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+/// Sıralanabilir media alanları
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    Name,
+    SizeBytes,
+    CreatedAt,
+    UpdatedAt,
+}
+
+/// Sıralama yönü
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortField {
+    /// İki medyayı bu alana göre karşılaştır (artan)
+    pub fn compare(self, a: &Media, b: &Media) -> Ordering {
+        match self {
+            Self::Name => a.name.cmp(&b.name),
+            Self::SizeBytes => a.size_bytes.cmp(&b.size_bytes),
+            Self::CreatedAt => a.created_at.cmp(&b.created_at),
+            Self::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+        }
+    }
+}
+
+impl SortParams {
+    /// `sort_by` alanına ve yönüne göre karşılaştır (`sort_by` yoksa `Equal`)
+    /// 
+    /// `id` eşitlik bozucusu burada uygulanmaz; çağıran en sona ekler.
+    pub fn compare(&self, a: &Media, b: &Media) -> Ordering {
+        let Some(field) = self.sort_by else {
+            return Ordering::Equal;
+        };
+        let ordering = field.compare(a, b);
+        match self.order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    }
+}
+
+/// SQL LIKE eşleştirmesi (`%` ve `_` wildcard'ları, büyük/küçük harf duyarlı)
+///
+/// İki işaretçili, geri izlemesi son `%`'a sınırlı eşleştirme: kullanıcının
+/// verdiği `mime_type` deseninde `O(desen * değer)` adımı geçmez.
+fn like_match(pattern: &str, value: &str) -> bool {
+    let (p, v): (Vec<char>, Vec<char>) = (pattern.chars().collect(), value.chars().collect());
+    let (mut pi, mut vi) = (0, 0);
+    // Son `%`'ın konumu ve o noktada eşleşen değer konumu (backtracking için)
+    let mut percent: Option<(usize, usize)> = None;
+
+    while vi < v.len() {
+        if pi < p.len() && (p[pi] == '_' || (p[pi] != '%' && p[pi] == v[vi])) {
+            pi += 1;
+            vi += 1;
+        } else if pi < p.len() && p[pi] == '%' {
+            percent = Some((pi, vi));
+            pi += 1;
+        } else if let Some((percent_pi, percent_vi)) = percent {
+            pi = percent_pi + 1;
+            vi = percent_vi + 1;
+            percent = Some((percent_pi, percent_vi + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '%')
 }
 
 impl Media {
@@ -126,12 +558,16 @@ impl Media {
             size_bytes,
             created_at: now,
             updated_at: now,
+            version: default_version(),
+            checksum_sha256: None,
+            rank: None,
         }
     }
 
     /// Media nesnesini UpdateMedia ile güncelle (partial)
     /// 
     /// null olmayan değerleri günceller, null olanları korur.
+    /// `version` bir artar (`expected_version` burada kontrol edilmez).
     pub fn apply_update(&mut self, update: &UpdateMedia) {
         if let Some(name) = &update.name {
             self.name = name.clone();
@@ -146,12 +582,18 @@ impl Media {
             self.size_bytes = size_bytes;
         }
         self.updated_at = Utc::now();
+        self.version += 1;
     }
 }
 
+fn default_version() -> i32 {
+    1
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_new_media() {
@@ -178,13 +620,210 @@ mod tests {
         
         let update = UpdateMedia {
             name: Some("new-name.jpg".to_string()),
-            path: None,
-            mime_type: None,
-            size_bytes: None,
+            ..Default::default()
         };
         
         media.apply_update(&update);
         assert_eq!(media.name, "new-name.jpg");
         assert_eq!(media.path, "/uploads/test.jpg"); // Değiştirilmedi
+        assert_eq!(media.version, 2);
+    }
+
+    #[test]
+    fn test_version_defaults_for_old_json() {
+        let media = Media::new("a.jpg".into(), "/uploads/a.jpg".into(), "image/jpeg".into(), 1);
+        let mut json = serde_json::to_value(&media).unwrap();
+        assert_eq!(json["version"], 1);
+
+        json.as_object_mut().unwrap().remove("version");
+        let old: Media = serde_json::from_value(json).unwrap();
+        assert_eq!(old.version, 1);
+    }
+
+    #[test]
+    fn test_media_query_matches() {
+        let media = Media::new(
+            "Vacation-2024.jpg".to_string(),
+            "/uploads/vacation.jpg".to_string(),
+            "image/jpeg".to_string(),
+            2048,
+        );
+
+        assert!(MediaQuery::default().matches(&media));
+
+        let query = MediaQuery {
+            mime_type: Some("image/%".to_string()),
+            min_size: Some(1024),
+            max_size: Some(2048),
+            name_contains: Some("vacation".to_string()),
+            ..Default::default()
+        };
+        assert!(query.matches(&media));
+
+        let video = MediaQuery { mime_type: Some("video/%".to_string()), ..Default::default() };
+        assert!(!video.matches(&media));
+
+        let too_small = MediaQuery { max_size: Some(2047), ..Default::default() };
+        assert!(!too_small.matches(&media));
+
+        let future = MediaQuery { created_after: Some(Utc::now()), ..Default::default() };
+        assert!(!future.matches(&media));
+
+        let by_checksum = |c: &str| MediaQuery { checksum: Some(c.to_string()), ..Default::default() };
+        assert!(!by_checksum("ab12").matches(&media));
+        let uploaded = Media { checksum_sha256: Some("ab12".to_string()), ..media };
+        assert!(by_checksum("AB12").matches(&uploaded));
+        assert!(!by_checksum("ab13").matches(&uploaded));
+    }
+
+    #[test]
+    fn test_media_query_search_terms() {
+        let query = |q: &str| MediaQuery { q: Some(q.to_string()), ..Default::default() };
+
+        assert_eq!(query("Summer Vacation").search_terms(), ["summer", "vacation"]);
+        assert_eq!(query("beach & sun!'; DROP--").search_terms(), ["beach", "sun", "drop"]);
+        assert!(query("  ").search_terms().is_empty());
+        assert!(query("%_!").search_terms().is_empty());
+        assert!(MediaQuery::default().search_terms().is_empty());
+    }
+
+    #[test]
+    fn test_media_query_search_rank() {
+        let media = Media::new(
+            "vacation-beach.jpg".to_string(),
+            "/uploads/vacation-beach.jpg".to_string(),
+            "image/jpeg".to_string(),
+            1024,
+        );
+        let query = MediaQuery { q: Some("Beach vacation".to_string()), ..Default::default() };
+        let terms = query.search_terms();
+
+        assert!(query.matches(&media));
+        assert!(MediaQuery::search_rank(&terms, &media).unwrap() > 0.0);
+
+        let missing = MediaQuery { q: Some("vacation mountain".to_string()), ..Default::default() };
+        assert!(!missing.matches(&media));
+    }
+
+    #[test]
+    fn test_mime_type_valid() {
+        let jpeg = MimeType::parse("image/jpeg").unwrap();
+        assert_eq!((jpeg.top_level(), jpeg.subtype(), jpeg.essence()), ("image", "jpeg", "image/jpeg"));
+        assert!(jpeg.is_image() && !jpeg.is_video() && !jpeg.is_application());
+
+        let json = MimeType::parse("application/json").unwrap();
+        assert!(json.is_application());
+        assert_eq!(json.to_string(), "application/json");
+
+        for (raw, normalized) in [
+            ("video/mp4", "video/mp4"),
+            ("audio/ogg", "audio/ogg"),
+            ("  Text/Plain ", "text/plain"),
+            ("text/plain;charset=UTF-8", "text/plain; charset=UTF-8"),
+            ("application/vnd.api+json; ext=\"a\" ; profile=b", "application/vnd.api+json; ext=\"a\"; profile=b"),
+            ("image/svg+xml", "image/svg+xml"),
+        ] {
+            let mime: MimeType = raw.parse().unwrap();
+            assert_eq!(mime.as_str(), normalized, "{raw}");
+        }
+        let with_params = MimeType::parse("text/html; charset=utf-8").unwrap();
+        assert_eq!((with_params.essence(), with_params.subtype()), ("text/html", "html"));
+        assert!(with_params.is_text());
+    }
+
+    #[test]
+    fn test_mime_type_invalid() {
+        for raw in [
+            "foobar",
+            "",
+            "   ",
+            "image/*",
+            "*/*",
+            "image/",
+            "/jpeg",
+            "image/jpeg/extra",
+            "font/woff2",
+            "x-custom/thing",
+            "image/jp eg",
+            "image/jpeg;",
+            "image/jpeg; charset",
+            "image/jpeg; =utf-8",
+            "image/jpeg; charset=",
+        ] {
+            let err = MimeType::parse(raw).unwrap_err();
+            assert_eq!(err.status_code(), 400, "{raw}");
+        }
+        assert!(MimeType::parse(&format!("image/{}", "a".repeat(300))).is_err());
+        assert!(MimeType::parse("foobar").unwrap_err().to_string().contains("expected type/subtype"));
+    }
+
+    #[test]
+    fn test_media_requests_validate_mime_type() {
+        let new: NewMedia =
+            serde_json::from_str(r#"{"name": "a.jpg", "path": "/a.jpg", "mime_type": "Image/JPEG", "size_bytes": 1}"#).unwrap();
+        assert_eq!(new.mime_type, "image/jpeg");
+        let err = serde_json::from_str::<NewMedia>(r#"{"name": "a", "path": "/a", "mime_type": "foobar", "size_bytes": 1}"#).unwrap_err();
+        assert!(err.to_string().contains("invalid mime_type"), "{err}");
+
+        let update: UpdateMedia = serde_json::from_str(r#"{"name": "b.jpg"}"#).unwrap();
+        assert_eq!(update.mime_type, None);
+        let update: UpdateMedia = serde_json::from_str(r#"{"mime_type": null}"#).unwrap();
+        assert_eq!(update.mime_type, None);
+        let update: UpdateMedia = serde_json::from_str(r#"{"mime_type": "video/mp4"}"#).unwrap();
+        assert_eq!(update.mime_type.as_deref(), Some("video/mp4"));
+        assert!(serde_json::from_str::<UpdateMedia>(r#"{"mime_type": "image/*"}"#).is_err());
+    }
+
+    #[test]
+    fn test_like_match() {
+        assert!(like_match("image/%", "image/png"));
+        assert!(like_match("image/_pg", "image/jpg"));
+        assert!(!like_match("image/_pg", "image/jpeg"));
+        assert!(like_match("%", ""));
+        assert!(!like_match("image/%", "video/mp4"));
+        assert!(like_match("%a%b%c", "xxaxxbyyc"));
+        assert!(!like_match("%a%b", "xxabxc"));
+    }
+
+    #[test]
+    fn test_like_match_pathological_pattern_is_bounded() {
+        // Geri izlemeli eşleştiricide üstel süre alan desen
+        let pattern = format!("{}b", "%a".repeat(40));
+        let value = "a".repeat(200);
+        let started = std::time::Instant::now();
+        assert!(!like_match(&pattern, &value));
+        assert!(like_match(&pattern, &format!("{value}b")));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_media_json_round_trip(
+            id in any::<u128>(),
+            name in any::<String>(),
+            path in any::<String>(),
+            mime_type in "[a-z]{1,10}/[a-z0-9.+-]{1,20}",
+            size_bytes in any::<i64>(),
+            created in 0i64..4_102_444_800,
+            updated in 0i64..4_102_444_800,
+            version in 1i32..i32::MAX,
+            checksum_sha256 in prop::option::of("[0-9a-f]{64}"),
+            rank in prop::option::of(-1e6f32..1e6f32),
+        ) {
+            let media = Media {
+                id: Uuid::from_u128(id),
+                name,
+                path,
+                mime_type,
+                size_bytes,
+                created_at: DateTime::from_timestamp(created, 0).unwrap(),
+                updated_at: DateTime::from_timestamp(updated, 0).unwrap(),
+                version,
+                checksum_sha256,
+                rank,
+            };
+            let json = serde_json::to_string(&media).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Media>(&json).unwrap(), media);
+        }
     }
 }
@@ -3,10 +3,72 @@
 //! MQTT gateway ve edge agents arasında iletişim için kullanılan message tipler.
 //! JSON formatında serializasyon destekler.
 
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+use crate::device::{DeviceKind, RemoteConfig};
+use crate::device_error::ErrorReport;
+use crate::sensor::SensorReading;
+
+pub use crate::conventions::{gateway_status_topic, GATEWAY_STATUS_TOPIC_FILTER};
+
+/// Gateway'in device shadow ID'si
+///
+/// Client ID'den türetilen UUID v5; aynı client ID yeniden başlatmalarda
+/// aynı gölgeyi günceller.
+pub fn gateway_device_id(client_id: &str) -> Uuid {
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, gateway_status_topic(client_id).as_bytes())
+}
+
+/// Gateway'de mesajın işlenme önceliği
+///
+/// JSON/CBOR'da tamsayı olarak taşınır (`0` = `Low` … `3` = `Critical`).
+/// Alan yoksa `Normal`; `Normal` mesajlarda alan yazılmaz. Gateway her
+/// öncelik için ayrı kuyruk tutar ve yüksek öncelikli kuyrukları önce boşaltır.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum MessagePriority {
+    Low = 0,
+    #[default]
+    Normal = 1,
+    /// Durum değişiklikleri (ör. `DeviceMessage::offline`)
+    High = 2,
+    /// MQTT will (LWT) mesajları
+    Critical = 3,
+}
+
+impl MessagePriority {
+    /// Yüksekten düşüğe tüm öncelikler
+    pub const DESCENDING: [Self; 4] = [Self::Critical, Self::High, Self::Normal, Self::Low];
+
+    pub fn is_normal(&self) -> bool {
+        *self == Self::Normal
+    }
+}
+
+impl From<MessagePriority> for u8 {
+    fn from(priority: MessagePriority) -> Self {
+        priority as u8
+    }
+}
+
+impl TryFrom<u8> for MessagePriority {
+    type Error = crate::Error;
+
+    fn try_from(value: u8) -> crate::Result<Self> {
+        match value {
+            0 => Ok(Self::Low),
+            1 => Ok(Self::Normal),
+            2 => Ok(Self::High),
+            3 => Ok(Self::Critical),
+            _ => Err(crate::Error::InvalidParameter(format!("unknown message priority: {value}"))),
+        }
+    }
+}
+
 /// MQTT üzerinden gönderilen genel mesaj
 /// 
 /// MQTT topic'lerine publish edilen mesajların yapısı.
@@ -16,7 +78,7 @@ use chrono::{DateTime, Utc};
 /// - Sensör verisi: `/devices/rpi-01/sensors/temp`
 /// - Device kontrol: `/devices/rpi-01/commands/led-on`
 /// - Durum güncelleme: `/devices/rpi-01/status`
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MqttMessage {
     /// Mesajın türü
     pub message_type: String,
@@ -33,6 +95,16 @@ pub struct MqttMessage {
     /// MQTT QoS seviyesi (0, 1, veya 2)
     #[serde(default)]
     pub qos: u8,
+
+    /// Uçtan uca izleme ID'si (edge agent → gateway → api-server)
+    ///
+    /// Gateway bunu API server'a `X-Request-Id` header'ı olarak taşır.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<Uuid>,
+
+    /// Gateway'deki işlenme önceliği (varsayılan `Normal`)
+    #[serde(default, skip_serializing_if = "MessagePriority::is_normal")]
+    pub priority: MessagePriority,
 }
 
 /// Edge agent'lar tarafından gönderilen device mesajı
@@ -71,15 +143,197 @@ pub struct DeviceMessage {
     /// Mesajı gönderen cihazın ID'si
     pub device_id: Uuid,
     
-    /// Komut veya mesaj türü
-    /// Örnekler: "sensor_reading", "status_update", "error_report", "heartbeat"
-    pub command: String,
+    /// Komut veya mesaj türü (bkz. `DeviceCommandKind`)
+    pub command: DeviceCommandKind,
     
     /// Mesajın içeriği (JSON, yapı flexible)
     pub data: serde_json::Value,
     
     /// Mesajın oluşturulduğu zaman
     pub timestamp: DateTime<Utc>,
+
+    /// Gateway'deki işlenme önceliği (varsayılan `Normal`)
+    #[serde(default, skip_serializing_if = "MessagePriority::is_normal")]
+    pub priority: MessagePriority,
+}
+
+/// `DeviceMessage`'ın türü
+/// 
+/// JSON'da snake_case string olarak taşınır (`"status_update"` vb.).
+/// Tanınmayan türler `Other` içinde olduğu gibi korunur; parse hiçbir
+/// zaman başarısız olmaz. Eski yazımlar (`"status"`, `"error"`) alias
+/// olarak kabul edilir, serialize edilirken standart isim yazılır.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceCommandKind {
+    SensorReading,
+    #[serde(alias = "status")]
+    StatusUpdate,
+    #[serde(alias = "error")]
+    ErrorReport,
+    Heartbeat,
+    /// Bilinmeyen tür (ham değer)
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl DeviceCommandKind {
+    /// Türün string karşılığı (serde ile aynı)
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::SensorReading => "sensor_reading",
+            Self::StatusUpdate => "status_update",
+            Self::ErrorReport => "error_report",
+            Self::Heartbeat => "heartbeat",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for DeviceCommandKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `status_update` mesajının `data` alanı
+/// 
+/// Cihaz bildiği alanları gönderir; hepsi opsiyoneldir, bilinmeyen alanlar
+/// da kabul edilir. Gateway bu yapıdan üretilen şemayla doğrular
+/// (`SCHEMA_DIR`) ve `data`'yı shadow'un `reported.status`'una yazar.
+/// 
+/// # Örnek JSON
+/// ```json
+/// {"uptime": 3600, "cpu_temp": 45.2, "memory_free": 512}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeviceStatus {
+    /// Açılıştan beri geçen süre (saniye)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime: Option<u64>,
+
+    /// CPU sıcaklığı (°C)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_temp: Option<f64>,
+
+    /// Boş bellek (MB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_free: Option<u64>,
+}
+
+/// Edge agent'ın `status_update` mesajının `data` alanı
+///
+/// Aynı tip hem bağlantı kurulunca ve `WILL_INTERVAL_SECS` aralıkla
+/// yayınlanan "çevrimiçi" durumunda hem de broker'ın bağlantı koparsa
+/// yayınladığı LWT'de kullanılır; gateway ikisini de `reported.status`'a yazar.
+/// `DeviceStatus` alanları düz taşınır. `config` verilirse gateway bu alanları
+/// `reported`'ın kök seviyesine taşır; böylece `desired` ile aynı şekilde
+/// karşılaştırılır ve delta kapanır.
+///
+/// # Örnek JSON
+/// ```json
+/// {"connected": true, "uptime": 3600, "config": {"sensor_interval_secs": 10, "qos": 1}}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceStatusMessage {
+    /// Broker bağlantısı açık mı? (LWT'de `false`)
+    pub connected: bool,
+
+    /// Ortak durum alanları
+    #[serde(flatten)]
+    pub status: DeviceStatus,
+
+    /// Cihazın uyguladığı runtime ayarları
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<RemoteConfig>,
+}
+
+impl DeviceStatusMessage {
+    /// Çevrimiçi durum (`uptime` saniye)
+    pub fn online(uptime: u64) -> Self {
+        Self { connected: true, status: DeviceStatus { uptime: Some(uptime), ..DeviceStatus::default() }, config: None }
+    }
+
+    /// Uygulanan runtime ayarlarını ekle
+    pub fn with_config(mut self, config: RemoteConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// LWT payload'u: son bilinen `uptime` ile çevrimdışı
+    pub fn offline(uptime: u64) -> Self {
+        Self { connected: false, ..Self::online(uptime) }
+    }
+}
+
+/// Gateway'in `status_update` mesajının `data` alanı
+/// 
+/// Gateway `GATEWAY_STATUS_INTERVAL_SECS` aralıkla `gateway_status_topic`'e
+/// retained olarak publish eder; bağlantı koparsa broker LWT olarak
+/// `GatewayStatus::offline` yayınlar. `DeviceStatus` alanları (`uptime`)
+/// düz olarak taşınır; `status_update` şemasıyla uyumludur.
+/// 
+/// # Örnek JSON
+/// ```json
+/// {
+///   "kind": "gateway",
+///   "connected": true,
+///   "version": "0.1.0",
+///   "uptime": 3600,
+///   "counters": {"processed": 1200, "forwarded": 1180, "failed": 2, "invalid": 18},
+///   "forward_queue_depth": 0
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GatewayStatus {
+    /// Her zaman `gateway` (shadow'da cihaz türü olarak saklanır)
+    #[serde(default)]
+    pub kind: DeviceKind,
+
+    /// Broker bağlantısı açık mı? (LWT'de `false`)
+    pub connected: bool,
+
+    /// Gateway sürümü
+    pub version: String,
+
+    /// Ortak durum alanları
+    #[serde(flatten)]
+    pub status: DeviceStatus,
+
+    /// Mesaj sayaçları (process başladığından beri)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counters: Option<GatewayCounters>,
+
+    /// API server'a gönderilmeyi bekleyen okuma sayısı (batch modu kapalıysa 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forward_queue_depth: Option<u64>,
+}
+
+/// Gateway mesaj sayaçları (`/health` ile aynı değerler)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GatewayCounters {
+    pub processed: u64,
+    pub forwarded: u64,
+    pub failed: u64,
+    pub invalid: u64,
+    /// `MAX_PAYLOAD_BYTES`'ı aşan mesajlar (eski gateway'lerde yok)
+    #[serde(default)]
+    pub oversized: u64,
+}
+
+impl GatewayStatus {
+    /// LWT payload'u: bağlantı yok, sayaç yok
+    pub fn offline(version: impl Into<String>) -> Self {
+        Self {
+            kind: DeviceKind::Gateway,
+            connected: false,
+            version: version.into(),
+            status: DeviceStatus::default(),
+            counters: None,
+            forward_queue_depth: None,
+        }
+    }
 }
 
 /// API server'dan edge agent'a gönderilen komut
@@ -122,6 +376,99 @@ pub struct DeviceCommand {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Edge agent'ın bir `DeviceCommand`'a verdiği yanıt
+///
+/// `correlation_id` ile orijinal komutla eşleştirilir.
+///
+/// # Örnek JSON (Hata)
+/// ```json
+/// {
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "correlation_id": "550e8400-e29b-41d4-a716-446655440003",
+///   "success": false,
+///   "message": "field `device_id` is immutable",
+///   "timestamp": "2024-11-13T21:30:01Z"
+/// }
+/// ```
+///
+/// # Örnek JSON (`take_photo`)
+/// ```json
+/// {
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "correlation_id": "550e8400-e29b-41d4-a716-446655440004",
+///   "success": true,
+///   "data": {"media_id": "550e8400-e29b-41d4-a716-446655440005"},
+///   "timestamp": "2024-11-13T21:30:02Z"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceCommandResponse {
+    /// Yanıtı gönderen cihaz
+    pub device_id: Uuid,
+
+    /// Yanıtlanan komutun correlation ID'si
+    pub correlation_id: Uuid,
+
+    /// Komut başarıyla uygulandı mı?
+    pub success: bool,
+
+    /// Açıklama veya hata mesajı
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// Komuta özgü sonuç (örn. `take_photo`: oluşturulan media'nın ID'si)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+
+    /// Yanıtın oluşturulduğu zaman
+    pub timestamp: DateTime<Utc>,
+}
+
+/// LED'in hedef durumu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LedState {
+    On,
+    Off,
+    /// Mevcut durumu tersine çevir
+    Toggle,
+}
+
+/// LED kontrol komutu
+/// 
+/// `POST /v1/devices/{id}/commands/led` body'si. `DeviceCommand` içinde
+/// `command_name = "led"` ile parametre olarak taşınır.
+/// 
+/// # Örnek JSON
+/// ```json
+/// {
+///   "led_id": "led_01",
+///   "state": "on",
+///   "brightness": 200,
+///   "duration_ms": 5000
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedCommand {
+    /// Kontrol edilecek LED
+    pub led_id: String,
+
+    /// Hedef durum
+    pub state: LedState,
+
+    /// Parlaklık (0-255, PWM destekleniyorsa)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u8>,
+
+    /// Bu süreden sonra önceki duruma dön (ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u32>,
+
+    /// Bu kadar kez yanıp sön, sonra `state`'te kal (edge-agent en fazla 20 kabul eder)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blink: Option<u32>,
+}
+
 impl MqttMessage {
     /// Yeni bir MQTT mesajı oluştur
     pub fn new(
@@ -135,21 +482,100 @@ impl MqttMessage {
             timestamp: Utc::now(),
             device_id,
             qos: 1, // Default: At-least-once delivery
+            trace_id: None,
+            priority: MessagePriority::Normal,
         }
     }
 
+    /// Gateway önceliğini ayarla
+    pub fn with_priority(mut self, priority: MessagePriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// İzleme ID'si ekle
+    pub fn with_trace_id(mut self, trace_id: Uuid) -> Self {
+        self.trace_id = Some(trace_id);
+        self
+    }
+
     /// QoS değerini ayarla
     pub fn with_qos(mut self, qos: u8) -> Self {
         self.qos = qos.min(2); // Max QoS: 2
         self
     }
+
+    /// `payload`'u şemayla doğrula; geçersizse hatalar (`<JSON pointer>: <açıklama>`)
+    ///
+    /// Şema her çağrıda derlenir; çok sayıda mesaj doğrulayanlar
+    /// `MqttMessageSchema::validator` ile bir kez derleyip `schema_errors`
+    /// kullanmalıdır (gateway böyle yapar). Şemanın `message_type`'ı
+    /// kontrol edilmez, doğru şemayı seçmek çağıranın işidir.
+    #[cfg(feature = "jsonschema")]
+    pub fn validate_payload(&self, schema: &MqttMessageSchema) -> Result<(), Vec<String>> {
+        let validator = schema.validator().map_err(|e| vec![e])?;
+        schema_errors(&validator, &self.payload)
+    }
+}
+
+/// Gömülü payload şemaları (mesaj tipi → `schemas/<tip>.json`)
+#[cfg(feature = "jsonschema")]
+const BUILTIN_SCHEMAS: [(&str, &str); 4] = [
+    ("temperature_reading", include_str!("../schemas/temperature_reading.json")),
+    ("humidity_reading", include_str!("../schemas/humidity_reading.json")),
+    ("motion_reading", include_str!("../schemas/motion_reading.json")),
+    ("status_update", include_str!("../schemas/status_update.json")),
+];
+
+/// Bir mesaj tipinin payload'unun JSON Schema'sı
+///
+/// `MqttMessage`'ta `payload`'a, `DeviceMessage`'ta `data`'ya uygulanır
+/// (`message_type` veya `command` ile eşleşir).
+#[cfg(feature = "jsonschema")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttMessageSchema {
+    pub message_type: String,
+    pub schema: serde_json::Value,
+}
+
+#[cfg(feature = "jsonschema")]
+impl MqttMessageSchema {
+    pub fn new(message_type: impl Into<String>, schema: serde_json::Value) -> Self {
+        Self { message_type: message_type.into(), schema }
+    }
+
+    /// Crate'e gömülü şemalar
+    ///
+    /// `temperature_reading`, `humidity_reading`, `motion_reading` (`SensorReading`,
+    /// tipe göre `value` kısıtıyla) ve `status_update` (`DeviceStatus`).
+    pub fn builtin() -> Vec<Self> {
+        BUILTIN_SCHEMAS
+            .iter()
+            .map(|(message_type, raw)| Self::new(*message_type, serde_json::from_str(raw).expect("built-in schema is valid JSON")))
+            .collect()
+    }
+
+    /// Şemayı derle (geçerli bir JSON Schema değilse hata)
+    pub fn validator(&self) -> Result<jsonschema::Validator, String> {
+        jsonschema::validator_for(&self.schema).map_err(|e| format!("{}: {e}", self.message_type))
+    }
+}
+
+/// Derlenmiş şemayla doğrula; geçersizse hatalar (`<JSON pointer>: <açıklama>`)
+#[cfg(feature = "jsonschema")]
+pub fn schema_errors(validator: &jsonschema::Validator, payload: &serde_json::Value) -> Result<(), Vec<String>> {
+    // Geçerli mesajlar için hata listesi oluşturulmaz
+    if validator.is_valid(payload) {
+        return Ok(());
+    }
+    Err(validator.iter_errors(payload).map(|e| format!("{}: {e}", e.instance_path())).collect())
 }
 
 impl DeviceMessage {
     /// Yeni bir device mesajı oluştur
     pub fn new(
         device_id: Uuid,
-        command: String,
+        command: DeviceCommandKind,
         data: serde_json::Value,
     ) -> Self {
         Self {
@@ -157,8 +583,70 @@ impl DeviceMessage {
             command,
             data,
             timestamp: Utc::now(),
+            priority: MessagePriority::Normal,
         }
     }
+
+    /// Gateway önceliğini ayarla
+    pub fn with_priority(mut self, priority: MessagePriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sensör okuması mesajı (`data` = okuma)
+    pub fn sensor_reading(device_id: Uuid, reading: &SensorReading) -> Self {
+        Self::new(
+            device_id,
+            DeviceCommandKind::SensorReading,
+            serde_json::to_value(reading).unwrap_or_default(),
+        )
+    }
+
+    /// Canlılık mesajı (`data` boş)
+    pub fn heartbeat(device_id: Uuid) -> Self {
+        Self::new(device_id, DeviceCommandKind::Heartbeat, serde_json::json!({}))
+    }
+
+    /// Çevrimdışı durum mesajı (`data` = `{"connected": false}`, öncelik `High`)
+    pub fn offline(device_id: Uuid) -> Self {
+        Self::new(
+            device_id,
+            DeviceCommandKind::StatusUpdate,
+            serde_json::json!({ "connected": false }),
+        )
+        .with_priority(MessagePriority::High)
+    }
+
+    /// Edge agent durum mesajı (`data` = `DeviceStatusMessage`)
+    ///
+    /// Çevrimdışı durum `DeviceMessage::offline` gibi `High` önceliklidir.
+    pub fn device_status(device_id: Uuid, status: &DeviceStatusMessage) -> Self {
+        let priority = if status.connected { MessagePriority::Normal } else { MessagePriority::High };
+        Self::new(
+            device_id,
+            DeviceCommandKind::StatusUpdate,
+            serde_json::to_value(status).unwrap_or_default(),
+        )
+        .with_priority(priority)
+    }
+
+    /// Gateway durum mesajı (`data` = `GatewayStatus`)
+    pub fn gateway_status(device_id: Uuid, status: &GatewayStatus) -> Self {
+        Self::new(
+            device_id,
+            DeviceCommandKind::StatusUpdate,
+            serde_json::to_value(status).unwrap_or_default(),
+        )
+    }
+
+    /// Hata raporu (`data` = `ErrorReport`)
+    pub fn error_report(device_id: Uuid, report: &ErrorReport) -> Self {
+        Self::new(
+            device_id,
+            DeviceCommandKind::ErrorReport,
+            serde_json::to_value(report).unwrap_or_default(),
+        )
+    }
 }
 
 impl DeviceCommand {
@@ -183,11 +671,52 @@ impl DeviceCommand {
         self.parameters = Some(parameters);
         self
     }
+
+    /// LED komutunu `control/led` DeviceCommand'ına sar
+    pub fn led(device_id: Uuid, led: &LedCommand) -> Self {
+        Self::new(device_id, "control".to_string(), "led".to_string())
+            .with_parameters(serde_json::to_value(led).unwrap_or_default())
+    }
+}
+
+impl LedCommand {
+    /// DeviceCommand parametrelerinden LED komutunu çöz
+    pub fn from_command(command: &DeviceCommand) -> crate::Result<Self> {
+        let params = command.parameters.clone().ok_or_else(|| {
+            crate::Error::InvalidParameter("led command requires parameters".to_string())
+        })?;
+        serde_json::from_value(params).map_err(|e| crate::Error::InvalidParameter(e.to_string()))
+    }
+}
+
+impl DeviceCommandResponse {
+    /// Başarılı yanıt oluştur
+    pub fn ok(command: &DeviceCommand) -> Self {
+        Self {
+            device_id: command.device_id,
+            correlation_id: command.correlation_id,
+            success: true,
+            message: None,
+            data: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Hata yanıtı oluştur
+    pub fn error(command: &DeviceCommand, message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            message: Some(message.into()),
+            ..Self::ok(command)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use crate::device_error::ErrorLevel;
 
     #[test]
     fn test_mqtt_message() {
@@ -198,18 +727,224 @@ mod tests {
         assert_eq!(msg.message_type, "sensor_data");
         assert_eq!(msg.device_id, device_id);
         assert_eq!(msg.qos, 1);
+        assert!(msg.trace_id.is_none());
+    }
+
+    #[cfg(feature = "jsonschema")]
+    fn builtin_schema(message_type: &str) -> MqttMessageSchema {
+        MqttMessageSchema::builtin().into_iter().find(|s| s.message_type == message_type).unwrap()
+    }
+
+    #[cfg(feature = "jsonschema")]
+    #[test]
+    fn test_builtin_schemas_accept_compliant_payloads() {
+        let message = |message_type: &str, payload| MqttMessage::new(message_type.to_string(), payload, Uuid::new_v4());
+        let reading = |value: &str| serde_json::to_value(SensorReading::new(Uuid::new_v4(), value.to_string())).unwrap();
+
+        let types: Vec<_> = MqttMessageSchema::builtin().into_iter().map(|s| s.message_type).collect();
+        assert_eq!(types, ["temperature_reading", "humidity_reading", "motion_reading", "status_update"]);
+
+        for (message_type, value) in [("temperature_reading", "22.50"), ("temperature_reading", "-3.2"), ("humidity_reading", "55.0"), ("motion_reading", "1")] {
+            let msg = message(message_type, reading(value));
+            assert_eq!(msg.validate_payload(&builtin_schema(message_type)), Ok(()), "{message_type} {value}");
+        }
+        // Çevrimiçi durumu ve LWT de `status_update` şemasına uyar
+        let status = serde_json::to_value(DeviceStatusMessage::online(3600).with_config(RemoteConfig::default())).unwrap();
+        assert_eq!(message("status_update", status).validate_payload(&builtin_schema("status_update")), Ok(()));
+    }
+
+    #[cfg(feature = "jsonschema")]
+    #[test]
+    fn test_builtin_schemas_reject_non_compliant_payloads() {
+        let message = |message_type: &str, payload| MqttMessage::new(message_type.to_string(), payload, Uuid::new_v4());
+        let mut reading = serde_json::to_value(SensorReading::new(Uuid::new_v4(), "warm".to_string())).unwrap();
+
+        let errors = message("temperature_reading", reading.clone()).validate_payload(&builtin_schema("temperature_reading")).unwrap_err();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].starts_with("/value:"), "{errors:?}");
+
+        reading["value"] = "2".into();
+        reading.as_object_mut().unwrap().remove("timestamp");
+        let errors = message("motion_reading", reading).validate_payload(&builtin_schema("motion_reading")).unwrap_err();
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors.iter().any(|e| e.contains("timestamp")));
+
+        let status = message("status_update", serde_json::json!({"uptime": "1h"}));
+        assert!(status.validate_payload(&builtin_schema("status_update")).is_err());
+
+        // Geçersiz şema da hata olarak döner
+        let broken = MqttMessageSchema::new("broken", serde_json::json!({"type": 12}));
+        let errors = status.validate_payload(&broken).unwrap_err();
+        assert!(errors[0].starts_with("broken:"), "{errors:?}");
+    }
+
+    /// Gömülü şemalar shared-types tipleriyle uyumlu kalmalı
+    #[cfg(all(feature = "jsonschema", feature = "schemars"))]
+    #[test]
+    fn test_builtin_schemas_match_shared_types() {
+        let generated = crate::schema::default_schemas();
+        let generated = |name: &str| generated.iter().find(|(n, _)| *n == name).unwrap().1.clone();
+
+        assert_eq!(builtin_schema("status_update").schema, generated("status_update"));
+        let reading = generated("sensor_reading");
+        for message_type in ["temperature_reading", "humidity_reading", "motion_reading"] {
+            let schema = builtin_schema(message_type).schema;
+            assert_eq!(schema["required"], reading["required"], "{message_type}");
+            let keys = |schema: &serde_json::Value| schema["properties"].as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+            assert_eq!(keys(&schema), keys(&reading), "{message_type}");
+        }
+    }
+
+    #[test]
+    fn test_mqtt_message_trace_id_roundtrip() {
+        let trace_id = Uuid::new_v4();
+        let msg = MqttMessage::new("sensor_data".to_string(), serde_json::json!({}), Uuid::new_v4())
+            .with_trace_id(trace_id);
+
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["trace_id"], trace_id.to_string());
+
+        // Eski mesajlar (trace_id'siz) hâlâ parse edilir
+        let mut legacy = json;
+        legacy.as_object_mut().unwrap().remove("trace_id");
+        let parsed: MqttMessage = serde_json::from_value(legacy).unwrap();
+        assert!(parsed.trace_id.is_none());
     }
 
     #[test]
     fn test_device_message() {
         let device_id = Uuid::new_v4();
         let data = serde_json::json!({"value": "on"});
-        let msg = DeviceMessage::new(device_id, "status".to_string(), data);
+        let msg = DeviceMessage::new(device_id, DeviceCommandKind::StatusUpdate, data);
         
-        assert_eq!(msg.command, "status");
+        assert_eq!(msg.command, DeviceCommandKind::StatusUpdate);
         assert_eq!(msg.device_id, device_id);
     }
 
+    #[test]
+    fn test_device_command_kind_serde() {
+        for (kind, raw) in [
+            (DeviceCommandKind::SensorReading, "sensor_reading"),
+            (DeviceCommandKind::StatusUpdate, "status_update"),
+            (DeviceCommandKind::ErrorReport, "error_report"),
+            (DeviceCommandKind::Heartbeat, "heartbeat"),
+            (DeviceCommandKind::Other("reboot_notice".into()), "reboot_notice"),
+        ] {
+            assert_eq!(serde_json::to_value(&kind).unwrap(), raw);
+            assert_eq!(serde_json::from_value::<DeviceCommandKind>(raw.into()).unwrap(), kind);
+            assert_eq!(kind.to_string(), raw);
+        }
+
+        // Eski yazımlar standart isme çevrilir
+        assert_eq!(serde_json::from_value::<DeviceCommandKind>("status".into()).unwrap(), DeviceCommandKind::StatusUpdate);
+        assert_eq!(serde_json::from_value::<DeviceCommandKind>("error".into()).unwrap(), DeviceCommandKind::ErrorReport);
+    }
+
+    #[test]
+    fn test_device_message_unknown_command_roundtrip() {
+        let json = serde_json::json!({
+            "device_id": Uuid::nil(),
+            "command": "firmware_progress",
+            "data": {"percent": 40},
+            "timestamp": "2024-11-13T21:30:00Z",
+        });
+        let msg: DeviceMessage = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(msg.command, DeviceCommandKind::Other("firmware_progress".into()));
+        assert_eq!(serde_json::to_value(&msg).unwrap(), json);
+    }
+
+    #[test]
+    fn test_device_message_constructors() {
+        let device_id = Uuid::new_v4();
+        let reading = SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+
+        let msg = DeviceMessage::sensor_reading(device_id, &reading);
+        assert_eq!(msg.command, DeviceCommandKind::SensorReading);
+        assert_eq!(serde_json::from_value::<SensorReading>(msg.data).unwrap(), reading);
+
+        let msg = DeviceMessage::heartbeat(device_id);
+        assert_eq!((msg.command, msg.data), (DeviceCommandKind::Heartbeat, serde_json::json!({})));
+
+        let msg = DeviceMessage::offline(device_id);
+        assert_eq!(msg.priority, MessagePriority::High);
+        assert_eq!((msg.command, msg.data), (DeviceCommandKind::StatusUpdate, serde_json::json!({"connected": false})));
+
+        let report = ErrorReport::new(ErrorLevel::Warning, crate::Error::MqttError("timeout".into()))
+            .with_context(serde_json::json!({"source": "mqtt"}));
+        let msg = DeviceMessage::error_report(device_id, &report);
+        assert_eq!(msg.command, DeviceCommandKind::ErrorReport);
+        assert_eq!(
+            msg.data,
+            serde_json::json!({"level": "warning", "message": "MQTT connection error: timeout", "context": {"source": "mqtt"}})
+        );
+        assert_eq!(serde_json::from_value::<ErrorReport>(msg.data.clone()).unwrap(), report);
+        assert_eq!(serde_json::to_value(&msg).unwrap()["command"], "error_report");
+    }
+
+    #[test]
+    fn test_gateway_status_message() {
+        let device_id = gateway_device_id("gateway-001");
+        assert_eq!(device_id, gateway_device_id("gateway-001"));
+        assert_ne!(device_id, gateway_device_id("gateway-002"));
+        assert_eq!(gateway_status_topic("gateway-001"), "rustyflow/gateway/gateway-001/status");
+
+        let status = GatewayStatus {
+            connected: true,
+            status: DeviceStatus { uptime: Some(90), ..DeviceStatus::default() },
+            counters: Some(GatewayCounters { processed: 5, ..GatewayCounters::default() }),
+            forward_queue_depth: Some(3),
+            ..GatewayStatus::offline("0.1.0")
+        };
+        let msg = DeviceMessage::gateway_status(device_id, &status);
+        assert_eq!(msg.command, DeviceCommandKind::StatusUpdate);
+        assert_eq!(msg.data["kind"], "gateway");
+        // DeviceStatus alanları düz taşınır
+        assert_eq!(msg.data["uptime"], 90);
+        assert_eq!(serde_json::from_value::<GatewayStatus>(msg.data).unwrap(), status);
+
+        let offline = serde_json::to_value(GatewayStatus::offline("0.1.0")).unwrap();
+        assert_eq!(offline, serde_json::json!({"kind": "gateway", "connected": false, "version": "0.1.0"}));
+    }
+
+    #[test]
+    fn test_device_status_message() {
+        let device_id = Uuid::new_v4();
+        let online = DeviceMessage::device_status(device_id, &DeviceStatusMessage::online(90));
+        assert_eq!(online.command, DeviceCommandKind::StatusUpdate);
+        assert_eq!(online.priority, MessagePriority::Normal);
+        assert_eq!(online.data, serde_json::json!({"connected": true, "uptime": 90}));
+
+        // LWT: `DeviceMessage::offline` ile aynı alan ve öncelik, ek olarak uptime
+        let offline = DeviceMessage::device_status(device_id, &DeviceStatusMessage::offline(90));
+        assert_eq!(offline.priority, DeviceMessage::offline(device_id).priority);
+        assert_eq!(offline.data, serde_json::json!({"connected": false, "uptime": 90}));
+        assert_eq!(serde_json::from_value::<DeviceStatusMessage>(offline.data).unwrap(), DeviceStatusMessage::offline(90));
+
+        let config = RemoteConfig { sensor_interval_secs: Some(10), qos: Some(1), ..RemoteConfig::default() };
+        let status = DeviceMessage::device_status(device_id, &DeviceStatusMessage::online(90).with_config(config));
+        assert_eq!(
+            status.data,
+            serde_json::json!({"connected": true, "uptime": 90, "config": {"sensor_interval_secs": 10, "qos": 1}})
+        );
+    }
+
+    #[test]
+    fn test_priority_serialized_as_integer() {
+        let msg = MqttMessage::new("temperature_reading".into(), serde_json::json!({}), Uuid::new_v4());
+        let json = serde_json::to_value(&msg).unwrap();
+        assert!(json.get("priority").is_none(), "Normal is omitted");
+        assert_eq!(serde_json::from_value::<MqttMessage>(json).unwrap().priority, MessagePriority::Normal);
+
+        let json = serde_json::to_value(msg.with_priority(MessagePriority::Critical)).unwrap();
+        assert_eq!(json["priority"], 3);
+        assert_eq!(serde_json::from_value::<MqttMessage>(json.clone()).unwrap().priority, MessagePriority::Critical);
+
+        let mut invalid = json;
+        invalid["priority"] = serde_json::json!(4);
+        assert!(serde_json::from_value::<MqttMessage>(invalid).is_err());
+        assert!(MessagePriority::Low < MessagePriority::Normal && MessagePriority::High < MessagePriority::Critical);
+    }
+
     #[test]
     fn test_device_command() {
         let device_id = Uuid::new_v4();
@@ -222,4 +957,76 @@ mod tests {
         assert_eq!(cmd.command_name, "led_on");
         assert_eq!(cmd.device_id, device_id);
     }
+
+    #[test]
+    fn test_led_command_roundtrip() {
+        let led = LedCommand {
+            led_id: "led_01".to_string(),
+            state: LedState::Toggle,
+            brightness: Some(128),
+            duration_ms: None,
+            blink: None,
+        };
+        let cmd = DeviceCommand::led(Uuid::new_v4(), &led);
+
+        assert_eq!(cmd.command_type, "control");
+        assert_eq!(cmd.command_name, "led");
+        assert_eq!(
+            cmd.parameters,
+            Some(serde_json::json!({"led_id": "led_01", "state": "toggle", "brightness": 128}))
+        );
+
+        let json = serde_json::to_string(&cmd).unwrap();
+        let parsed: DeviceCommand = serde_json::from_str(&json).unwrap();
+        assert_eq!(LedCommand::from_command(&parsed).unwrap(), led);
+    }
+
+    #[test]
+    fn test_led_command_invalid_state() {
+        let mut cmd = DeviceCommand::new(Uuid::new_v4(), "control".to_string(), "led".to_string());
+        assert!(LedCommand::from_command(&cmd).is_err());
+
+        cmd.parameters = Some(serde_json::json!({"led_id": "led_01", "state": "blink"}));
+        assert!(LedCommand::from_command(&cmd).is_err());
+    }
+
+    #[test]
+    fn test_device_command_response() {
+        let cmd = DeviceCommand::new(
+            Uuid::new_v4(),
+            "config".to_string(),
+            "config_update".to_string(),
+        );
+        let resp = DeviceCommandResponse::error(&cmd, "bad field");
+
+        assert_eq!(resp.correlation_id, cmd.correlation_id);
+        assert!(!resp.success);
+        assert_eq!(resp.message.as_deref(), Some("bad field"));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_mqtt_message_json_round_trip(
+            message_type in "[a-z_]{1,20}",
+            reading in any::<crate::sensor::SensorReading>(),
+            secs in 0i64..4_102_444_800,
+            micros in 0u32..1_000_000,
+            device_id in any::<u128>(),
+            qos in 0u8..=2,
+            trace_id in prop::option::of(any::<u128>()),
+            priority in 0u8..=3,
+        ) {
+            let msg = MqttMessage {
+                message_type,
+                payload: serde_json::to_value(&reading).unwrap(),
+                timestamp: DateTime::from_timestamp(secs, micros * 1000).unwrap(),
+                device_id: Uuid::from_u128(device_id),
+                qos,
+                trace_id: trace_id.map(Uuid::from_u128),
+                priority: MessagePriority::try_from(priority).unwrap(),
+            };
+            let json = serde_json::to_string(&msg).unwrap();
+            prop_assert_eq!(serde_json::from_str::<MqttMessage>(&json).unwrap(), msg);
+        }
+    }
 }
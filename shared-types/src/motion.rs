@@ -0,0 +1,162 @@
+//! Motion Event Types
+//!
+//! Hareket gibi ikili (0/1) sensörlerin okumaları tek bir "son değer" olarak
+//! değil, olay olarak saklanır: 0→1 geçişi bir olayı başlatır, 1→0 bitirir.
+//! Böylece "son bir saatte hareket oldu mu?" sorusu cevaplanabilir.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "sqlx-support")]
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+/// Sensörün aktif kaldığı bir aralık
+///
+/// `ended_at` `None` ise olay hâlâ sürüyor (1'i izleyen 0 henüz gelmedi).
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "device_id": "edge-agent-001",
+///   "sensor_type": "motion",
+///   "started_at": "2024-01-20T10:30:00Z",
+///   "ended_at": "2024-01-20T10:30:42Z",
+///   "duration_secs": 42.0
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct MotionEvent {
+    pub device_id: String,
+    pub sensor_type: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Olayın süresi (saniye); sürüyorsa `None`
+    pub duration_secs: Option<f64>,
+}
+
+impl MotionEvent {
+    pub fn new(device_id: &str, sensor_type: &str, started_at: DateTime<Utc>, ended_at: Option<DateTime<Utc>>) -> Self {
+        Self {
+            device_id: device_id.to_string(),
+            sensor_type: sensor_type.to_string(),
+            started_at,
+            ended_at,
+            duration_secs: ended_at.map(|end| (end - started_at).num_milliseconds() as f64 / 1000.0),
+        }
+    }
+
+    /// Olay hâlâ sürüyor mu?
+    pub fn is_ongoing(&self) -> bool {
+        self.ended_at.is_none()
+    }
+
+    /// Olayın `[from, to]` aralığıyla kesişip kesişmediği
+    pub fn overlaps(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> bool {
+        let starts_before_end = to.is_none_or(|to| self.started_at <= to);
+        let ends_after_start = match (self.ended_at, from) {
+            (Some(end), Some(from)) => end >= from,
+            _ => true,
+        };
+        starts_before_end && ends_after_start
+    }
+}
+
+/// İkili okuma değeri aktif mi? (`1` = hareket var)
+pub fn is_active(value: f64) -> bool {
+    value > 0.0
+}
+
+/// Okumaları zamana göre sıralayıp 0→1 / 1→0 geçişlerini olaylara eşle
+///
+/// - Geç gelen (sırasız) okumalar timestamp'e göre yerine oturur
+/// - Art arda gelen aynı değerler (1, 1) yeni olay başlatmaz
+/// - Eşi olmayan son 1 sürmekte olan bir olay üretir
+/// - Olay başlamadan gelen 0'lar yok sayılır
+///
+/// Aynı timestamp'li okumalar geliş sırasını korur.
+pub fn pair_transitions(
+    device_id: &str,
+    sensor_type: &str,
+    readings: impl IntoIterator<Item = (DateTime<Utc>, f64)>,
+) -> Vec<MotionEvent> {
+    let mut readings: Vec<_> = readings.into_iter().collect();
+    readings.sort_by_key(|(timestamp, _)| *timestamp);
+
+    let mut events = Vec::new();
+    let mut started_at = None;
+    for (timestamp, value) in readings {
+        match (started_at, is_active(value)) {
+            (None, true) => started_at = Some(timestamp),
+            (Some(start), false) => {
+                events.push(MotionEvent::new(device_id, sensor_type, start, Some(timestamp)));
+                started_at = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = started_at {
+        events.push(MotionEvent::new(device_id, sensor_type, start, None));
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(1_705_746_600 + secs, 0).unwrap()
+    }
+
+    fn pair(readings: &[(i64, f64)]) -> Vec<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+        pair_transitions("dev-1", "motion", readings.iter().map(|&(t, v)| (at(t), v)))
+            .into_iter()
+            .map(|e| (e.started_at, e.ended_at))
+            .collect()
+    }
+
+    #[test]
+    fn test_pairs_transitions_with_duration() {
+        let events = pair_transitions("dev-1", "motion", [(at(0), 1.0), (at(42), 0.0)]);
+        assert_eq!(events, vec![MotionEvent::new("dev-1", "motion", at(0), Some(at(42)))]);
+        assert_eq!(events[0].duration_secs, Some(42.0));
+    }
+
+    #[test]
+    fn test_unpaired_start_is_ongoing() {
+        let events = pair_transitions("dev-1", "motion", [(at(0), 1.0), (at(10), 0.0), (at(20), 1.0)]);
+        assert_eq!(events.len(), 2);
+        assert!(events[1].is_ongoing());
+        assert_eq!(events[1].duration_secs, None);
+        // Olay başlamadan gelen 0 yok sayılır
+        assert!(pair(&[(0, 0.0)]).is_empty());
+    }
+
+    #[test]
+    fn test_rapid_flapping_and_repeats() {
+        let readings = [(0, 1.0), (1, 0.0), (1, 1.0), (2, 1.0), (3, 0.0), (3, 0.0), (4, 1.0), (4, 0.0)];
+        assert_eq!(
+            pair(&readings),
+            vec![(at(0), Some(at(1))), (at(1), Some(at(3))), (at(4), Some(at(4)))]
+        );
+    }
+
+    #[test]
+    fn test_out_of_order_readings() {
+        // 10'daki 0, 5'teki 1'den önce gelir
+        let readings = [(0, 1.0), (10, 0.0), (5, 0.0), (7, 1.0), (20, 1.0)];
+        assert_eq!(pair(&readings), vec![(at(0), Some(at(5))), (at(7), Some(at(10))), (at(20), None)]);
+    }
+
+    #[test]
+    fn test_overlaps_range() {
+        let event = MotionEvent::new("dev-1", "motion", at(10), Some(at(20)));
+        assert!(event.overlaps(None, None));
+        assert!(event.overlaps(Some(at(15)), Some(at(30))));
+        assert!(event.overlaps(Some(at(20)), None));
+        assert!(!event.overlaps(Some(at(21)), None));
+        assert!(!event.overlaps(None, Some(at(9))));
+        assert!(MotionEvent::new("dev-1", "motion", at(10), None).overlaps(Some(at(1000)), None));
+    }
+}
@@ -0,0 +1,714 @@
+//! MQTT Bağlantısı (v3.1.1 / v5)
+//!
+//! `mqtt` feature'ı ile açılır; edge agent ve gateway aynı sarmalayıcıyı
+//! kullanır. `MQTT_PROTOCOL=v5` ile:
+//! - Okumalar `device_id`, `sensor_type` ve `schema_version` user
+//!   property'leriyle publish edilir; broker ve diğer tüketiciler JSON
+//!   parse etmeden yönlendirebilir (`ReadingProperties`)
+//! - Okumaya bayatlama süresi kadar message expiry verilir; broker geç
+//!   kalan okumayı teslim etmez
+//! - Broker v5'i reddederse bağlantı bir kez v4 ile yeniden kurulur
+//!   (`ProtocolNegotiator`), abonelikler yeni client'a tekrar yazılır
+//!
+//! Event loop olayları her iki modda da v4 (`rumqttc::Event`) tipinde
+//! döner; v5 publish'lerinin user property'leri yanında verilir.
+
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use rumqttc::v5::mqttbytes::v5::{
+    ConnectReturnCode as V5ConnectReturnCode, LastWill as V5LastWill, Packet as V5Packet, PublishProperties,
+};
+use rumqttc::v5::mqttbytes::QoS as V5QoS;
+use rumqttc::v5::{ConnectionError as V5ConnectionError, StateError as V5StateError};
+use rumqttc::{ConnAck, ConnectReturnCode, Event, LastWill, Packet, PubAck, PubComp, PubRec, Publish, QoS};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// User property'lerle taşınan okuma formatının sürümü
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// MQTT protokol sürümü (`MQTT_PROTOCOL`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MqttProtocol {
+    /// MQTT 3.1.1 (rumqttc'de "v4")
+    #[default]
+    V4,
+    /// MQTT 5: user property'ler ve message expiry
+    V5,
+}
+
+impl FromStr for MqttProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "v4" => Ok(Self::V4),
+            "v5" => Ok(Self::V5),
+            other => Err(format!("unknown MQTT protocol '{other}' (expected v4 or v5)")),
+        }
+    }
+}
+
+impl fmt::Display for MqttProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::V4 => "v4",
+            Self::V5 => "v5",
+        })
+    }
+}
+
+/// Okuma publish'lerine eklenen v5 user property'leri
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadingProperties {
+    pub device_id: Uuid,
+    pub sensor_type: String,
+    pub schema_version: u32,
+}
+
+impl ReadingProperties {
+    pub const DEVICE_ID: &'static str = "device_id";
+    pub const SENSOR_TYPE: &'static str = "sensor_type";
+    pub const SCHEMA_VERSION: &'static str = "schema_version";
+
+    /// Güncel `SCHEMA_VERSION` ile
+    pub fn new(device_id: Uuid, sensor_type: impl Into<String>) -> Self {
+        Self { device_id, sensor_type: sensor_type.into(), schema_version: SCHEMA_VERSION }
+    }
+
+    /// User property listesi (sıra sabit)
+    pub fn to_user_properties(&self) -> Vec<(String, String)> {
+        vec![
+            (Self::DEVICE_ID.into(), self.device_id.to_string()),
+            (Self::SENSOR_TYPE.into(), self.sensor_type.clone()),
+            (Self::SCHEMA_VERSION.into(), self.schema_version.to_string()),
+        ]
+    }
+
+    /// User property listesinden oku
+    ///
+    /// Üç anahtardan biri eksik veya değeri geçersizse `None` (mesaj
+    /// payload'dan parse edilir). Bilinmeyen anahtarlar yok sayılır.
+    pub fn from_user_properties(properties: &[(String, String)]) -> Option<Self> {
+        let get = |key: &str| properties.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        Some(Self {
+            device_id: get(Self::DEVICE_ID)?.parse().ok()?,
+            sensor_type: get(Self::SENSOR_TYPE).filter(|s| !s.is_empty())?.to_string(),
+            schema_version: get(Self::SCHEMA_VERSION)?.parse().ok()?,
+        })
+    }
+
+    /// v5 publish property'leri (`expiry` saniyeye yukarı yuvarlanır)
+    pub fn to_publish_properties(&self, expiry: Option<Duration>) -> PublishProperties {
+        PublishProperties {
+            message_expiry_interval: expiry.map(expiry_secs),
+            user_properties: self.to_user_properties(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Message expiry (saniye, en az 1; 0 "hemen bayat" değil "hiç" anlamına gelmesin)
+fn expiry_secs(expiry: Duration) -> u32 {
+    let secs = expiry.as_secs() + u64::from(expiry.subsec_nanos() > 0);
+    secs.clamp(1, u64::from(u32::MAX)) as u32
+}
+
+/// v5 → v4 geri dönüş kararı
+///
+/// Sadece hiç v5 `ConnAck` alınmamışken ve hata broker'ın protokolü
+/// reddettiğini gösteriyorsa geri dönülür: açıkça reddedilen sürüm,
+/// v5 olarak çözülemeyen (3.1.1 formatında) `ConnAck` veya el sıkışma
+/// sırasında bağlantının karşı taraftan kapatılması. Broker'a hiç
+/// ulaşılamıyorsa (bağlantı reddi, timeout) v5 ile denemeye devam edilir.
+#[derive(Debug, Clone)]
+pub struct ProtocolNegotiator {
+    protocol: MqttProtocol,
+    connected: bool,
+}
+
+impl ProtocolNegotiator {
+    pub fn new(requested: MqttProtocol) -> Self {
+        Self { protocol: requested, connected: false }
+    }
+
+    /// Şu an kullanılan sürüm
+    pub fn protocol(&self) -> MqttProtocol {
+        self.protocol
+    }
+
+    /// Broker bağlantıyı kabul etti; bundan sonra geri dönülmez
+    pub fn on_connected(&mut self) {
+        self.connected = true;
+    }
+
+    /// v5 bağlantı hatası; v4'e geçilecekse `true` (sürüm güncellenir)
+    pub fn on_v5_error(&mut self, error: &V5ConnectionError) -> bool {
+        if self.protocol != MqttProtocol::V5 || self.connected || !rejects_v5(error) {
+            return false;
+        }
+        self.protocol = MqttProtocol::V4;
+        true
+    }
+}
+
+/// Hata broker'ın v5'i desteklemediğini mi gösteriyor?
+fn rejects_v5(error: &V5ConnectionError) -> bool {
+    match error {
+        V5ConnectionError::ConnectionRefused(
+            V5ConnectReturnCode::UnsupportedProtocolVersion | V5ConnectReturnCode::RefusedProtocolVersion,
+        ) => true,
+        V5ConnectionError::MqttState(V5StateError::Deserialization(_)) | V5ConnectionError::NotConnAck(_) => true,
+        V5ConnectionError::Io(e) | V5ConnectionError::MqttState(V5StateError::Io(e)) => matches!(
+            e.kind(),
+            io::ErrorKind::UnexpectedEof | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted
+        ),
+        _ => false,
+    }
+}
+
+/// Client ve bağlantı hataları (iki protokolün hata tipleri farklı)
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct MqttError(String);
+
+/// En kısa keep-alive (rumqttc v5 daha kısasını kabul etmez)
+pub const MIN_KEEP_ALIVE_SECS: u64 = 5;
+
+/// Bağlantı seçenekleri (iki protokolde ortak olanlar)
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    pub client_id: String,
+    pub host: String,
+    pub port: u16,
+    pub session: SessionOptions,
+    /// Bağlantı beklenmedik koparsa broker'ın yayınlayacağı mesaj (LWT)
+    pub last_will: Option<LastWill>,
+}
+
+/// Oturum ve akış kontrolü ayarları
+///
+/// Gateway ve edge agent config'lerindeki `MQTT_KEEP_ALIVE_SECS`,
+/// `MQTT_CLEAN_SESSION`, `MQTT_MAX_INFLIGHT` ve
+/// `MQTT_REQUEST_CHANNEL_CAPACITY`'den kurulur (`from_config`).
+///
+/// `clean_session: false` ile broker oturumu bağlantı kopunca saklar:
+/// abonelikler korunur ve çevrimdışıyken gelen QoS 1/2 mesajlar yeniden
+/// bağlanınca teslim edilir. Bunun için client ID sabit olmalıdır; broker
+/// bekleyen mesajları kendi sınırına kadar tutar (Mosquitto:
+/// `max_queued_messages`). Client'ın gönderip onay alamadığı publish'ler
+/// sadece process ayaktayken yeniden gönderilir, restart'ta kaybolur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionOptions {
+    pub keep_alive: Duration,
+    pub clean_session: bool,
+    /// Onay bekleyebilecek en fazla QoS 1/2 publish (v5'te üst sınır)
+    pub max_inflight: u16,
+    /// `MqttClient` → event loop istek kanalının kapasitesi
+    pub request_channel_capacity: usize,
+    /// Gelen/giden MQTT paketlerinin en büyük boyutu (byte)
+    ///
+    /// `None` ise rumqttc varsayılanı kullanılır (v4: 10 KB, v5: sınır
+    /// bildirilmez). v5'te CONNECT özelliği olarak broker'a da bildirilir;
+    /// broker bu sınırı aşan mesajları client'a hiç göndermez.
+    pub max_packet_size: Option<usize>,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            keep_alive: Duration::from_secs(MIN_KEEP_ALIVE_SECS),
+            clean_session: true,
+            max_inflight: 100,
+            request_channel_capacity: 10,
+            max_packet_size: None,
+        }
+    }
+}
+
+impl SessionOptions {
+    /// Config değerlerini doğrulayıp ayarları kur
+    ///
+    /// Keep-alive en az `MIN_KEEP_ALIVE_SECS`, inflight 1..=65535, istek
+    /// kanalı en az 1 olmalıdır.
+    pub fn from_config(
+        keep_alive_secs: u64,
+        clean_session: bool,
+        max_inflight: u32,
+        request_channel_capacity: usize,
+    ) -> Result<Self, MqttError> {
+        if keep_alive_secs < MIN_KEEP_ALIVE_SECS {
+            return Err(MqttError(format!("MQTT_KEEP_ALIVE_SECS must be at least {MIN_KEEP_ALIVE_SECS}, got {keep_alive_secs}")));
+        }
+        let max_inflight = u16::try_from(max_inflight)
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| MqttError(format!("MQTT_MAX_INFLIGHT must be between 1 and 65535, got {max_inflight}")))?;
+        if request_channel_capacity == 0 {
+            return Err(MqttError("MQTT_REQUEST_CHANNEL_CAPACITY must be at least 1".into()));
+        }
+        Ok(Self {
+            keep_alive: Duration::from_secs(keep_alive_secs),
+            clean_session,
+            max_inflight,
+            request_channel_capacity,
+            max_packet_size: None,
+        })
+    }
+
+    /// En büyük MQTT paket boyutunu ayarla
+    pub fn with_max_packet_size(mut self, bytes: usize) -> Self {
+        self.max_packet_size = Some(bytes);
+        self
+    }
+}
+
+#[derive(Clone)]
+enum ClientInner {
+    V4(rumqttc::AsyncClient),
+    V5(rumqttc::v5::AsyncClient),
+}
+
+/// v4/v5 `AsyncClient` sarmalayıcısı
+///
+/// Klonlar aynı bağlantıyı paylaşır; v4'e geri dönüldüğünde hepsi yeni
+/// client'ı kullanır.
+#[derive(Clone)]
+pub struct MqttClient {
+    inner: Arc<RwLock<ClientInner>>,
+    /// Geri dönüşte yeni client'a tekrar yazılan abonelikler
+    subscriptions: Arc<Mutex<Vec<(String, QoS)>>>,
+}
+
+impl MqttClient {
+    fn current(&self) -> ClientInner {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn remember(&self, topic: &str, qos: QoS) {
+        let mut subscriptions = self.subscriptions.lock().unwrap_or_else(|e| e.into_inner());
+        match subscriptions.iter_mut().find(|(t, _)| t == topic) {
+            Some(existing) => existing.1 = qos,
+            None => subscriptions.push((topic.to_string(), qos)),
+        }
+    }
+
+    pub async fn publish(&self, topic: &str, qos: QoS, retain: bool, payload: Vec<u8>) -> Result<(), MqttError> {
+        match self.current() {
+            ClientInner::V4(client) => client.publish(topic, qos, retain, payload).await.map_err(error),
+            ClientInner::V5(client) => client.publish(topic, v5_qos(qos), retain, payload).await.map_err(error),
+        }
+    }
+
+    pub fn try_publish(&self, topic: &str, qos: QoS, retain: bool, payload: Vec<u8>) -> Result<(), MqttError> {
+        match self.current() {
+            ClientInner::V4(client) => client.try_publish(topic, qos, retain, payload).map_err(error),
+            ClientInner::V5(client) => client.try_publish(topic, v5_qos(qos), retain, payload).map_err(error),
+        }
+    }
+
+    /// Okuma publish'i; v5'te user property'ler ve message expiry eklenir
+    /// (v4'te `properties` ve `expiry` yok sayılır)
+    pub async fn publish_reading(
+        &self,
+        topic: &str,
+        qos: QoS,
+        payload: Vec<u8>,
+        properties: &ReadingProperties,
+        expiry: Option<Duration>,
+    ) -> Result<(), MqttError> {
+        match self.current() {
+            ClientInner::V4(client) => client.publish(topic, qos, false, payload).await.map_err(error),
+            ClientInner::V5(client) => client
+                .publish_with_properties(topic, v5_qos(qos), false, payload, properties.to_publish_properties(expiry))
+                .await
+                .map_err(error),
+        }
+    }
+
+    pub async fn subscribe(&self, topic: &str, qos: QoS) -> Result<(), MqttError> {
+        self.remember(topic, qos);
+        match self.current() {
+            ClientInner::V4(client) => client.subscribe(topic, qos).await.map_err(error),
+            ClientInner::V5(client) => client.subscribe(topic, v5_qos(qos)).await.map_err(error),
+        }
+    }
+
+    pub fn try_subscribe(&self, topic: &str, qos: QoS) -> Result<(), MqttError> {
+        self.remember(topic, qos);
+        match self.current() {
+            ClientInner::V4(client) => client.try_subscribe(topic, qos).map_err(error),
+            ClientInner::V5(client) => client.try_subscribe(topic, v5_qos(qos)).map_err(error),
+        }
+    }
+}
+
+enum EventLoopInner {
+    V4(Box<rumqttc::EventLoop>),
+    V5(Box<rumqttc::v5::EventLoop>),
+}
+
+/// v4/v5 `EventLoop` sarmalayıcısı
+pub struct MqttEventLoop {
+    inner: EventLoopInner,
+    client: MqttClient,
+    options: ConnectOptions,
+    cap: usize,
+    negotiator: ProtocolNegotiator,
+}
+
+/// Client ve event loop oluştur
+pub fn connect(options: ConnectOptions, protocol: MqttProtocol) -> (MqttClient, MqttEventLoop) {
+    let cap = options.session.request_channel_capacity;
+    let (client, inner) = match protocol {
+        MqttProtocol::V4 => {
+            let (client, eventloop) = rumqttc::AsyncClient::new(v4_options(&options), cap);
+            (ClientInner::V4(client), EventLoopInner::V4(Box::new(eventloop)))
+        }
+        MqttProtocol::V5 => {
+            let (client, eventloop) = rumqttc::v5::AsyncClient::new(v5_options(&options), cap);
+            (ClientInner::V5(client), EventLoopInner::V5(Box::new(eventloop)))
+        }
+    };
+    let client = MqttClient {
+        inner: Arc::new(RwLock::new(client)),
+        subscriptions: Arc::default(),
+    };
+    let eventloop = MqttEventLoop {
+        inner,
+        client: client.clone(),
+        options,
+        cap,
+        negotiator: ProtocolNegotiator::new(protocol),
+    };
+    (client, eventloop)
+}
+
+impl MqttEventLoop {
+    /// Şu an kullanılan sürüm (geri dönüş sonrası `V4`)
+    pub fn protocol(&self) -> MqttProtocol {
+        self.negotiator.protocol()
+    }
+
+    /// Sonraki olayı bekle
+    ///
+    /// v5 olayları v4 karşılıklarına çevrilir; publish'lerin okuma
+    /// property'leri varsa yanında döner. Karşılığı olmayan v5 paketleri
+    /// (SubAck, Disconnect, Auth...) atlanır.
+    pub async fn poll(&mut self) -> Result<(Event, Option<ReadingProperties>), MqttError> {
+        loop {
+            let result = match &mut self.inner {
+                EventLoopInner::V4(eventloop) => return eventloop.poll().await.map(|event| (event, None)).map_err(error),
+                EventLoopInner::V5(eventloop) => eventloop.poll().await,
+            };
+            match result {
+                Ok(event) => {
+                    if let Some(converted) = from_v5(event) {
+                        if let Event::Incoming(Packet::ConnAck(_)) = converted.0 {
+                            self.negotiator.on_connected();
+                        }
+                        return Ok(converted);
+                    }
+                }
+                Err(e) => {
+                    if self.negotiator.on_v5_error(&e) {
+                        self.fall_back();
+                        return Err(MqttError(format!("{e} (broker rejected MQTT v5, falling back to v4)")));
+                    }
+                    return Err(error(e));
+                }
+            }
+        }
+    }
+
+    /// Sonraki bağlantılarda kullanılacak LWT'yi değiştir
+    ///
+    /// Açık bağlantının will'i CONNECT paketinde gönderildiği için değişmez;
+    /// yeni değer bir sonraki (yeniden) bağlanmada ve v4'e geri dönüşte geçerlidir.
+    pub fn set_last_will(&mut self, will: LastWill) {
+        match &mut self.inner {
+            EventLoopInner::V4(eventloop) => {
+                eventloop.mqtt_options.set_last_will(will.clone());
+            }
+            EventLoopInner::V5(eventloop) => {
+                eventloop.options.set_last_will(v5_will(&will));
+            }
+        }
+        self.options.last_will = Some(will);
+    }
+
+    /// v4 client'ı kur, paylaşılan client'ı değiştir ve abonelikleri tekrar yaz
+    fn fall_back(&mut self) {
+        let subscriptions = self.client.subscriptions.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let (client, eventloop) = rumqttc::AsyncClient::new(v4_options(&self.options), self.cap.max(subscriptions.len() + 1));
+        for (topic, qos) in subscriptions {
+            // Kanal yeni ve yeterince büyük; bağlanınca gönderilir
+            let _ = client.try_subscribe(topic, qos);
+        }
+        *self.client.inner.write().unwrap_or_else(|e| e.into_inner()) = ClientInner::V4(client);
+        self.inner = EventLoopInner::V4(Box::new(eventloop));
+    }
+}
+
+fn error(e: impl fmt::Display) -> MqttError {
+    MqttError(e.to_string())
+}
+
+fn v4_options(options: &ConnectOptions) -> rumqttc::MqttOptions {
+    let mut mqttoptions = rumqttc::MqttOptions::new(options.client_id.clone(), options.host.clone(), options.port);
+    mqttoptions.set_keep_alive(options.session.keep_alive);
+    mqttoptions.set_clean_session(options.session.clean_session);
+    mqttoptions.set_inflight(options.session.max_inflight);
+    if let Some(bytes) = options.session.max_packet_size {
+        mqttoptions.set_max_packet_size(bytes, bytes);
+    }
+    if let Some(will) = &options.last_will {
+        mqttoptions.set_last_will(will.clone());
+    }
+    mqttoptions
+}
+
+fn v5_options(options: &ConnectOptions) -> rumqttc::v5::MqttOptions {
+    let mut mqttoptions = rumqttc::v5::MqttOptions::new(options.client_id.clone(), options.host.clone(), options.port);
+    mqttoptions.set_keep_alive(options.session.keep_alive);
+    mqttoptions.set_clean_start(options.session.clean_session);
+    mqttoptions.set_outgoing_inflight_upper_limit(options.session.max_inflight);
+    if let Some(bytes) = options.session.max_packet_size {
+        mqttoptions.set_max_packet_size(Some(u32::try_from(bytes).unwrap_or(u32::MAX)));
+    }
+    if let Some(will) = &options.last_will {
+        mqttoptions.set_last_will(v5_will(will));
+    }
+    mqttoptions
+}
+
+fn v5_will(will: &LastWill) -> V5LastWill {
+    V5LastWill::new(will.topic.clone(), will.message.to_vec(), v5_qos(will.qos), will.retain, None)
+}
+
+fn v5_qos(qos: QoS) -> V5QoS {
+    match qos {
+        QoS::AtMostOnce => V5QoS::AtMostOnce,
+        QoS::AtLeastOnce => V5QoS::AtLeastOnce,
+        QoS::ExactlyOnce => V5QoS::ExactlyOnce,
+    }
+}
+
+fn v4_qos(qos: V5QoS) -> QoS {
+    match qos {
+        V5QoS::AtMostOnce => QoS::AtMostOnce,
+        V5QoS::AtLeastOnce => QoS::AtLeastOnce,
+        V5QoS::ExactlyOnce => QoS::ExactlyOnce,
+    }
+}
+
+/// v5 olayını v4 karşılığına çevir (karşılığı yoksa `None`)
+fn from_v5(event: rumqttc::v5::Event) -> Option<(Event, Option<ReadingProperties>)> {
+    let packet = match event {
+        rumqttc::v5::Event::Outgoing(outgoing) => return Some((Event::Outgoing(outgoing), None)),
+        rumqttc::v5::Event::Incoming(packet) => packet,
+    };
+    let converted = match packet {
+        V5Packet::ConnAck(ack) => Packet::ConnAck(ConnAck::new(ConnectReturnCode::Success, ack.session_present)),
+        V5Packet::Publish(publish) => {
+            let properties = publish
+                .properties
+                .as_ref()
+                .and_then(|p| ReadingProperties::from_user_properties(&p.user_properties));
+            let topic = String::from_utf8_lossy(&publish.topic).into_owned();
+            let mut converted = Publish::from_bytes(topic, v4_qos(publish.qos), publish.payload);
+            converted.pkid = publish.pkid;
+            converted.retain = publish.retain;
+            converted.dup = publish.dup;
+            return Some((Event::Incoming(Packet::Publish(converted)), properties));
+        }
+        V5Packet::PubAck(ack) => Packet::PubAck(PubAck::new(ack.pkid)),
+        V5Packet::PubRec(rec) => Packet::PubRec(PubRec::new(rec.pkid)),
+        V5Packet::PubComp(comp) => Packet::PubComp(PubComp::new(comp.pkid)),
+        V5Packet::PingResp(_) => Packet::PingResp,
+        _ => return None,
+    };
+    Some((Event::Incoming(converted), None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rumqttc::v5::mqttbytes::v5::Publish as V5Publish;
+    use rumqttc::v5::mqttbytes::Error as V5ParseError;
+
+    #[test]
+    fn test_reading_properties_roundtrip() {
+        let props = ReadingProperties::new(Uuid::new_v4(), "temperature");
+        let encoded = props.to_publish_properties(Some(Duration::from_millis(1500)));
+        assert_eq!(encoded.message_expiry_interval, Some(2));
+        assert_eq!(
+            encoded.user_properties.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>(),
+            ["device_id", "sensor_type", "schema_version"]
+        );
+        assert_eq!(ReadingProperties::from_user_properties(&encoded.user_properties), Some(props.clone()));
+
+        // Sıfır expiry "hiç süresi dolmaz" olarak gitmez
+        assert_eq!(props.to_publish_properties(Some(Duration::ZERO)).message_expiry_interval, Some(1));
+        assert_eq!(props.to_publish_properties(None).message_expiry_interval, None);
+    }
+
+    #[test]
+    fn test_incomplete_user_properties_ignored() {
+        let device_id = Uuid::new_v4().to_string();
+        let pairs = |items: &[(&str, &str)]| items.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<Vec<_>>();
+
+        let extra = pairs(&[("trace", "x"), ("device_id", &device_id), ("sensor_type", "humidity"), ("schema_version", "1")]);
+        assert_eq!(ReadingProperties::from_user_properties(&extra).unwrap().sensor_type, "humidity");
+
+        for invalid in [
+            pairs(&[("device_id", &device_id), ("sensor_type", "humidity")]),
+            pairs(&[("device_id", "rpi-kitchen"), ("sensor_type", "humidity"), ("schema_version", "1")]),
+            pairs(&[("device_id", &device_id), ("sensor_type", ""), ("schema_version", "1")]),
+            pairs(&[("device_id", &device_id), ("sensor_type", "humidity"), ("schema_version", "one")]),
+        ] {
+            assert_eq!(ReadingProperties::from_user_properties(&invalid), None, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_protocol_parsing() {
+        assert_eq!("v5".parse::<MqttProtocol>(), Ok(MqttProtocol::V5));
+        assert_eq!("V4".parse::<MqttProtocol>(), Ok(MqttProtocol::V4));
+        assert!("5".parse::<MqttProtocol>().is_err());
+        assert_eq!(MqttProtocol::default().to_string(), "v4");
+    }
+
+    #[test]
+    fn test_last_will_applied_for_both_protocols() {
+        let mut options = ConnectOptions {
+            client_id: "gateway-001".into(),
+            host: "localhost".into(),
+            port: 1883,
+            session: SessionOptions::default(),
+            last_will: None,
+        };
+        assert!(v4_options(&options).last_will().is_none());
+        assert!(v5_options(&options).last_will().is_none());
+
+        options.last_will = Some(LastWill::new("gateway/status", b"offline".to_vec(), QoS::AtLeastOnce, true));
+        let v4 = v4_options(&options).last_will().unwrap();
+        assert_eq!((v4.topic.as_str(), &v4.message[..], v4.qos, v4.retain), ("gateway/status", &b"offline"[..], QoS::AtLeastOnce, true));
+        let v5 = v5_options(&options).last_will().unwrap();
+        assert_eq!((&v5.topic[..], &v5.message[..], v5.qos, v5.retain), (&b"gateway/status"[..], &b"offline"[..], V5QoS::AtLeastOnce, true));
+    }
+
+    #[test]
+    fn test_set_last_will_updates_next_connection() {
+        for protocol in [MqttProtocol::V4, MqttProtocol::V5] {
+            let options = ConnectOptions {
+                client_id: "edge-1".into(),
+                host: "localhost".into(),
+                port: 1883,
+                session: SessionOptions::default(),
+                last_will: Some(LastWill::new("devices/x/status", b"uptime 0".to_vec(), QoS::AtLeastOnce, false)),
+            };
+            let (_client, mut eventloop) = connect(options, protocol);
+            eventloop.set_last_will(LastWill::new("devices/x/status", b"uptime 90".to_vec(), QoS::AtLeastOnce, false));
+
+            let message = match &eventloop.inner {
+                EventLoopInner::V4(eventloop) => eventloop.mqtt_options.last_will().unwrap().message,
+                EventLoopInner::V5(eventloop) => eventloop.options.last_will().unwrap().message,
+            };
+            assert_eq!(&message[..], b"uptime 90", "{protocol}");
+            // v4'e geri dönüşte de yeni will kullanılır
+            assert_eq!(&v4_options(&eventloop.options).last_will().unwrap().message[..], b"uptime 90");
+        }
+    }
+
+    #[test]
+    fn test_session_options_validated_and_applied() {
+        assert!(SessionOptions::from_config(4, true, 100, 10).is_err());
+        assert!(SessionOptions::from_config(5, true, 0, 10).is_err());
+        assert!(SessionOptions::from_config(5, true, 65536, 10).is_err());
+        assert!(SessionOptions::from_config(5, true, 100, 0).is_err());
+        assert_eq!(SessionOptions::from_config(5, true, 100, 10).unwrap(), SessionOptions::default());
+
+        let options = ConnectOptions {
+            client_id: "edge-1".into(),
+            host: "localhost".into(),
+            port: 1883,
+            session: SessionOptions::from_config(60, false, 65535, 64).unwrap(),
+            last_will: None,
+        };
+        let v4 = v4_options(&options);
+        assert_eq!((v4.keep_alive(), v4.clean_session(), v4.inflight()), (Duration::from_secs(60), false, 65535));
+        let v5 = v5_options(&options);
+        assert_eq!((v5.keep_alive(), v5.clean_start(), v5.get_outgoing_inflight_upper_limit()), (Duration::from_secs(60), false, Some(65535)));
+
+        assert_eq!((v4.max_packet_size(), v5.max_packet_size()), (10 * 1024, None));
+
+        let (_client, eventloop) = connect(options.clone(), MqttProtocol::V4);
+        assert_eq!(eventloop.cap, 64);
+
+        let options = ConnectOptions { session: options.session.with_max_packet_size(65 * 1024), ..options };
+        assert_eq!(v4_options(&options).max_packet_size(), 65 * 1024);
+        assert_eq!(v5_options(&options).max_packet_size(), Some(65 * 1024));
+    }
+
+    #[test]
+    fn test_negotiator_falls_back_only_on_v5_rejection_before_connack() {
+        let refused = || V5ConnectionError::ConnectionRefused(V5ConnectReturnCode::UnsupportedProtocolVersion);
+        let v3_connack = || V5ConnectionError::MqttState(V5StateError::Deserialization(V5ParseError::MalformedPacket));
+        let closed = || V5ConnectionError::Io(io::ErrorKind::UnexpectedEof.into());
+        let unreachable = || V5ConnectionError::Io(io::ErrorKind::ConnectionRefused.into());
+        let not_authorized = || V5ConnectionError::ConnectionRefused(V5ConnectReturnCode::NotAuthorized);
+
+        for rejection in [refused(), v3_connack(), closed()] {
+            let mut negotiator = ProtocolNegotiator::new(MqttProtocol::V5);
+            assert!(negotiator.on_v5_error(&rejection), "{rejection}");
+            assert_eq!(negotiator.protocol(), MqttProtocol::V4);
+            // Bir kez geri dönülür
+            assert!(!negotiator.on_v5_error(&rejection));
+        }
+
+        // Broker'a ulaşılamıyor veya başka bir nedenle reddediyor: v5'te kal
+        let mut negotiator = ProtocolNegotiator::new(MqttProtocol::V5);
+        assert!(!negotiator.on_v5_error(&unreachable()));
+        assert!(!negotiator.on_v5_error(&not_authorized()));
+        assert_eq!(negotiator.protocol(), MqttProtocol::V5);
+
+        // v5 bir kez kabul edildiyse sonraki kopmalar geri dönüş sebebi değil
+        negotiator.on_connected();
+        assert!(!negotiator.on_v5_error(&closed()));
+        assert_eq!(negotiator.protocol(), MqttProtocol::V5);
+
+        let mut negotiator = ProtocolNegotiator::new(MqttProtocol::V4);
+        assert!(!negotiator.on_v5_error(&refused()));
+    }
+
+    #[test]
+    fn test_v5_events_converted_with_properties() {
+        let props = ReadingProperties::new(Uuid::new_v4(), "temperature");
+        let publish = V5Publish::new(
+            "sensors/rpi/temperature",
+            V5QoS::AtLeastOnce,
+            b"{}".to_vec(),
+            Some(props.to_publish_properties(None)),
+        );
+        let (event, decoded) = from_v5(rumqttc::v5::Event::Incoming(V5Packet::Publish(publish))).unwrap();
+        let Event::Incoming(Packet::Publish(publish)) = event else { panic!("{event:?}") };
+        assert_eq!((publish.topic.as_str(), publish.qos, &publish.payload[..]), ("sensors/rpi/temperature", QoS::AtLeastOnce, &b"{}"[..]));
+        assert_eq!(decoded, Some(props));
+
+        // Property'siz v5 publish
+        let plain = V5Publish::new("sensors/rpi/humidity", V5QoS::AtMostOnce, b"{}".to_vec(), None);
+        assert_eq!(from_v5(rumqttc::v5::Event::Incoming(V5Packet::Publish(plain))).unwrap().1, None);
+
+        let ack = rumqttc::v5::mqttbytes::v5::PubAck::new(7, None);
+        assert_eq!(
+            from_v5(rumqttc::v5::Event::Incoming(V5Packet::PubAck(ack))).unwrap().0,
+            Event::Incoming(Packet::PubAck(PubAck::new(7)))
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! MessagePack Serializasyonu
+//!
+//! `msgpack` feature'ı ile açılır. Gömülü HTTP client'ları toplu okumaları
+//! JSON yerine binary gövdeyle göndererek bant genişliğinden tasarruf eder
+//! (`Content-Type: application/msgpack`).
+//!
+//! Struct'lar alan adlarıyla (map olarak) kodlanır: başka dillerdeki
+//! client'lar da aynı alan adlarını görür ve `skip_serializing_if` ile
+//! atlanan alanlar sırayı bozmaz.
+//!
+//! # Örnek
+//! ```
+//! use shared_types::{MsgPack, SensorReading};
+//! use uuid::Uuid;
+//!
+//! let reading = SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+//! let bytes = reading.to_msgpack().unwrap();
+//! let decoded = SensorReading::from_msgpack(&bytes).unwrap();
+//! assert_eq!(decoded.value, "23.5");
+//! ```
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Error, Result};
+
+/// MessagePack content type'ı
+pub const CONTENT_TYPE: &str = "application/msgpack";
+
+/// MessagePack encode/decode helper'ları
+///
+/// Tüm serde tipleri için implement edilmiştir (api-server'ın kendi
+/// gövdeleri ve `Vec<T>` batch'leri dahil).
+pub trait MsgPack: Serialize + DeserializeOwned {
+    /// MessagePack byte dizisine çevir
+    fn to_msgpack(&self) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(self).map_err(|e| Error::SerializationError(e.to_string()))
+    }
+
+    /// MessagePack byte dizisinden oku
+    fn from_msgpack(bytes: &[u8]) -> Result<Self> {
+        rmp_serde::from_slice(bytes).map_err(|e| Error::SerializationError(e.to_string()))
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> MsgPack for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MqttMessage, SensorReading};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn reading() -> SensorReading {
+        SensorReading::new(Uuid::new_v4(), "23.5".to_string())
+            .with_metadata(json!({"duration_ms": 500, "tags": ["indoor", null, true], "calibration": -0.25}))
+    }
+
+    #[test]
+    fn test_sensor_reading_roundtrip_and_size() {
+        let readings = vec![reading(), reading(), reading()];
+        let msgpack = readings.to_msgpack().unwrap();
+        let json = serde_json::to_vec(&readings).unwrap();
+
+        let decoded = Vec::<SensorReading>::from_msgpack(&msgpack).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&readings).unwrap());
+        assert!(msgpack.len() < json.len(), "msgpack {} >= json {}", msgpack.len(), json.len());
+    }
+
+    #[test]
+    fn test_json_value_roundtrip_keeps_field_names() {
+        let value = json!({"device_id": "edge-agent-001", "value": 21.5, "count": 3, "stale": false, "metadata": null});
+        let bytes = value.to_msgpack().unwrap();
+
+        assert_eq!(serde_json::Value::from_msgpack(&bytes).unwrap(), value);
+        // Alanlar map olarak kodlanır (fixmap, 5 alan)
+        assert_eq!(bytes[0], 0x85);
+    }
+
+    #[test]
+    fn test_from_msgpack_rejects_garbage() {
+        assert!(matches!(MqttMessage::from_msgpack(b"{\"not\": \"msgpack\"}"), Err(Error::SerializationError(_))));
+        assert!(SensorReading::from_msgpack(&[0x81]).is_err());
+    }
+}
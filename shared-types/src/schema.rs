@@ -0,0 +1,39 @@
+//! JSON Schema Üretimi
+//!
+//! `schemars` feature'ı ile açılır. MQTT gateway'in `SCHEMA_DIR`'den
+//! yüklediği varsayılan şemalar (`mqtt-gateway/schemas/*.json`) buradaki
+//! tiplerden üretilir; tipler değişince dosyalar da yeniden üretilmelidir:
+//!
+//! ```text
+//! UPDATE_SCHEMAS=1 cargo test -p mqtt-gateway default_schemas
+//! ```
+
+use crate::device_error::ErrorReport;
+use crate::messages::DeviceStatus;
+use crate::sensor::SensorReading;
+
+/// Mesaj tipi → payload'un JSON Schema'sı
+///
+/// - `sensor_reading`: `SensorReading` (`*_reading` mesajlarının `payload`'u)
+/// - `status_update`: `DeviceStatus` (`status_update` mesajlarının `data`'sı)
+/// - `error_report`: `ErrorReport` (`error_report` mesajlarının `data`'sı)
+pub fn default_schemas() -> Vec<(&'static str, serde_json::Value)> {
+    vec![
+        ("sensor_reading", schemars::schema_for!(SensorReading).to_value()),
+        ("status_update", schemars::schema_for!(DeviceStatus).to_value()),
+        ("error_report", schemars::schema_for!(ErrorReport).to_value()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensor_reading_schema_requires_timestamp_and_bool() {
+        let (_, schema) = default_schemas().into_iter().find(|(name, _)| *name == "sensor_reading").unwrap();
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.contains(&"timestamp".into()) && !required.contains(&"metadata".into()));
+        assert_eq!(schema["properties"]["is_valid"]["type"], "boolean");
+    }
+}
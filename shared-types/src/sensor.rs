@@ -5,7 +5,13 @@
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+#[cfg(feature = "sqlx-support")]
+use sqlx::FromRow;
 use chrono::{DateTime, Utc};
+use std::{collections::HashMap, convert::Infallible, fmt, hash::{Hash, Hasher}, str::FromStr};
+
+use crate::filter::KalmanFilter1D;
+use crate::unit::{self, Unit};
 
 /// Sensör cihazının tanımlanması
 /// 
@@ -32,7 +38,10 @@ use chrono::{DateTime, Utc};
 ///   "location": "bedroom"
 /// }
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 
+/// Hash sadece `id` üzerinden hesaplanır.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
 pub struct Sensor {
     pub id: Uuid,
     pub device_id: Uuid,
@@ -76,7 +85,11 @@ pub struct Sensor {
 ///   "metadata": {"duration_ms": 500}
 /// }
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 
+/// Hash `sensor_id` + `timestamp` üzerinden hesaplanır (`metadata` JSON'u
+/// hash'lenemez); aynı sensörün aynı andaki kopyaları `HashSet`'te tekilleşir.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SensorReading {
     pub sensor_id: Uuid,
     pub value: String,
@@ -86,7 +99,345 @@ pub struct SensorReading {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// Farklı cihazlardaki sensörlerin mantıksal grubu
+/// 
+/// Örneğin hangi Raspberry Pi'a bağlı olduklarından bağımsız olarak tüm
+/// "bedroom" sensörleri. Üyeler `sensor_ids` ile tutulur; API'de okumalar
+/// `device_id` + `sensor_type` ile saklandığından üye ID'leri
+/// `Sensor::id_for` ile bu çiftten türetilir.
+/// 
+/// # Örnek JSON
+/// ```json
+/// {
+///   "id": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+///   "name": "bedroom",
+///   "description": "Yatak odasındaki tüm sensörler",
+///   "sensor_ids": ["550e8400-e29b-41d4-a716-446655440001"]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct SensorGroup {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Üye sensörler (sıralı, tekrarsız)
+    #[serde(default)]
+    pub sensor_ids: Vec<Uuid>,
+}
+
+/// `POST /v1/sensor-groups` request body'si
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewSensorGroup {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// `PUT /v1/sensor-groups/{id}/members` body'sindeki bir üye
+/// 
+/// Sensör ID'si veya okumaların saklandığı `device_id` + `sensor_type`
+/// çifti verilebilir; çift `Sensor::id_for` ile ID'ye çevrilir.
+/// 
+/// # Örnek JSON
+/// ```json
+/// ["550e8400-e29b-41d4-a716-446655440001", {"device_id": "edge-agent-001", "sensor_type": "temperature"}]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SensorGroupMember {
+    Id(Uuid),
+    Sensor { device_id: String, sensor_type: String },
+}
+
+impl SensorGroupMember {
+    /// Üyenin sensör ID'si
+    pub fn sensor_id(&self) -> Uuid {
+        match self {
+            Self::Id(id) => *id,
+            Self::Sensor { device_id, sensor_type } => Sensor::id_for(device_id, sensor_type),
+        }
+    }
+}
+
+impl From<Uuid> for SensorGroupMember {
+    fn from(id: Uuid) -> Self {
+        Self::Id(id)
+    }
+}
+
+/// Grubun üye sensörlerinin son okumaları
+/// 
+/// `GET /v1/sensor-groups/{id}/readings/latest` response'u. Henüz okuması
+/// olmayan üyeler `missing`'de listelenir; `average` geçerli sayısal
+/// okumaların ortalamasıdır (bkz. `SensorGroup::average_reading`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorGroupReadings {
+    pub group_id: Uuid,
+    pub readings: Vec<SensorReading>,
+    pub missing: Vec<Uuid>,
+    pub average: Option<f64>,
+}
+
+/// Bir zaman dilimindeki (bucket) okumaların özeti
+/// 
+/// API server'ın arka plan görevi `sensor_readings` tablosunu saatlik
+/// özetler halinde `sensor_summaries` tablosuna yazar. Dashboard'lar ham
+/// okumalar yerine bu özetleri çeker.
+/// 
+/// # Alanlar
+/// 
+/// - `bucket_start`: Dilimin başlangıcı (saat veya gün başı, UTC)
+/// - `min_value` / `max_value` / `avg_value`: Dilimdeki okumaların istatistikleri
+/// - `count`: Dilimdeki okuma sayısı
+/// 
+/// # Örnek JSON
+/// ```json
+/// {
+///   "device_id": "edge-agent-001",
+///   "sensor_type": "temperature",
+///   "bucket_start": "2024-01-20T10:00:00Z",
+///   "min_value": 21.2,
+///   "max_value": 23.9,
+///   "avg_value": 22.4,
+///   "count": 60
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct SensorSummary {
+    pub device_id: String,
+    pub sensor_type: String,
+    pub bucket_start: DateTime<Utc>,
+    pub min_value: f64,
+    pub max_value: f64,
+    pub avg_value: f64,
+    pub count: i64,
+}
+
+/// Bir okuma kümesinin istatistikleri
+///
+/// Değerler tek geçişte, hepsi bellekte tutulmadan hesaplanır (Welford
+/// algoritması); milyonlarca okuma için de sabit bellek kullanır. Okuma
+/// yoksa `count` 0, diğer alanlar `null`'dır. `std_dev` popülasyon standart
+/// sapmasıdır.
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "count": 1440,
+///   "min": 19.8,
+///   "max": 24.1,
+///   "mean": 21.93,
+///   "std_dev": 0.87
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SensorStats {
+    pub count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub std_dev: Option<f64>,
+    /// Ortalamadan sapmaların karelerinin toplamı (Welford `M2`)
+    #[serde(skip)]
+    m2: f64,
+}
+
+/// Son `N` okumanın sabit kapasiteli halka tamponu
+///
+/// Edge tarafında pencere istatistikleri (min/max/ortalama/varyans) için:
+/// tüm okumaları biriktirmeden son `N` değeri tutar, yeni değer en eskisinin
+/// yerine yazılır. Heap kullanmaz.
+///
+/// # Örnek
+/// ```
+/// use shared_types::SensorReadingBuffer;
+///
+/// let mut window = SensorReadingBuffer::<3>::new();
+/// for value in [1.0, 2.0, 3.0, 4.0] {
+///     window.push(value);
+/// }
+/// assert_eq!(window.min(), Some(2.0));
+/// assert_eq!(window.mean(), Some(3.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorReadingBuffer<const N: usize> {
+    buf: [Option<f64>; N],
+    /// Bir sonraki değerin yazılacağı index
+    head: usize,
+    /// Tampondaki değer sayısı (en fazla `N`)
+    count: usize,
+}
+
+/// Toplu okuma gönderiminin (`POST /v1/sensors/ingest`) sonucu
+/// 
+/// # Alanlar
+/// 
+/// - `accepted`: Kaydedilen okuma sayısı
+/// - `rejected`: Reddedilen okumalar (`(batch'teki index, sebep)`)
+/// - `clamped`: Kabul edilen ama `timestamp`'i eksik veya fazla ileri
+///   tarihli olduğu için sunucunun alış zamanıyla değiştirilen okumaların index'leri
+/// 
+/// # Örnek JSON
+/// ```json
+/// {
+///   "accepted": 2,
+///   "rejected": [[1, "invalid timestamp: yesterday"]],
+///   "clamped": [2]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestResult {
+    pub accepted: usize,
+    pub rejected: Vec<(usize, String)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clamped: Vec<usize>,
+}
+
+/// Bilinen sensör tipleri
+/// 
+/// JSON'da ve MQTT topic'lerinde küçük harfli string olarak taşınır
+/// (`"temperature"`, `"humidity"`, `"motion"`). Tanınmayan tipler
+/// `Other` içinde olduğu gibi korunur; parse hiçbir zaman başarısız olmaz.
+/// 
+/// # Örnek
+/// ```
+/// use shared_types::sensor::SensorType;
+/// 
+/// let t: SensorType = "temperature".parse().unwrap();
+/// assert_eq!(t.default_unit(), "°C");
+/// assert_eq!(t.display_name(), "Temperature");
+/// assert_eq!(t.to_string(), "temperature");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SensorType {
+    Temperature,
+    Humidity,
+    Motion,
+    /// Bilinmeyen tip (küçük harfe çevrilmiş ham değer)
+    Other(String),
+}
+
+impl SensorType {
+    /// Tipin varsayılan ölçüm birimi
+    /// 
+    /// Bilinmeyen tipler için boş string döner.
+    pub fn default_unit(&self) -> &'static str {
+        match self {
+            Self::Temperature => "°C",
+            Self::Humidity => "%",
+            Self::Motion => "bool",
+            Self::Other(_) => "",
+        }
+    }
+
+    /// Dashboard'da gösterilecek isim
+    pub fn display_name(&self) -> String {
+        match self {
+            Self::Temperature => "Temperature".to_string(),
+            Self::Humidity => "Humidity".to_string(),
+            Self::Motion => "Motion".to_string(),
+            Self::Other(name) => {
+                let mut chars = name.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+        }
+    }
+
+    /// Okumaları 0/1 olan ikili sensör mü? (olay olarak saklanır, bkz. `motion`)
+    pub fn is_binary(&self) -> bool {
+        matches!(self, Self::Motion)
+    }
+
+    /// Tipin string karşılığı (`Display` ve serde ile aynı)
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Temperature => "temperature",
+            Self::Humidity => "humidity",
+            Self::Motion => "motion",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl FromStr for SensorType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        Ok(match name.as_str() {
+            "temperature" => Self::Temperature,
+            "humidity" => Self::Humidity,
+            "motion" => Self::Motion,
+            _ => Self::Other(name),
+        })
+    }
+}
+
+impl fmt::Display for SensorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for SensorType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SensorType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Ok(sensor_type) = String::deserialize(deserializer)?.parse();
+        Ok(sensor_type)
+    }
+}
+
+/// Eski birim yazımlarını standart birime çevir
+/// 
+/// Edge agent'ın eski sürümleri `celsius`, `percent` ve `boolean` gönderir;
+/// bu yazımlar deprecated'dır ama kabul edilmeye devam eder.
+/// Diğer birimler olduğu gibi döner.
+/// 
+/// | Eski (deprecated) | Standart |
+/// |-------------------|----------|
+/// | `celsius`         | `°C`     |
+/// | `percent`         | `%`      |
+/// | `boolean`         | `bool`   |
+pub fn normalize_unit(unit: &str) -> &str {
+    match unit.trim().to_lowercase().as_str() {
+        "celsius" => "°C",
+        "percent" => "%",
+        "boolean" => "bool",
+        _ => unit,
+    }
+}
+
+/// `Sensor::id_for` UUID v5 namespace'i
+const SENSOR_ID_NAMESPACE: Uuid = Uuid::from_u128(0x6f2c_1d7e_93a4_4b5c_8e21_d0c7_a9f3_5b18);
+
 impl Sensor {
+    /// `device_id` + `sensor_type` çiftinin kalıcı sensör ID'si
+    /// 
+    /// API okumaları bu çiftle saklar; aynı çift her zaman aynı UUID'yi
+    /// (v5, `"<device_id>/<sensor_type>"`) verir. Sensör grupları üyelerini
+    /// bu ID'lerle tutar.
+    pub fn id_for(device_id: &str, sensor_type: &str) -> Uuid {
+        Uuid::new_v5(&SENSOR_ID_NAMESPACE, format!("{device_id}/{sensor_type}").as_bytes())
+    }
+
+    /// Verilen sensörlerden (farklı cihazlardan olabilir) yeni bir grup oluştur
+    pub fn group<'a>(name: &str, sensors: impl IntoIterator<Item = &'a Sensor>) -> SensorGroup {
+        let mut group = SensorGroup { id: Uuid::new_v4(), name: name.to_string(), description: String::new(), sensor_ids: vec![] };
+        group.set_members(sensors.into_iter().map(|s| s.id));
+        group
+    }
+
     /// Yeni bir Sensor oluştur
     pub fn new(
         device_id: Uuid,
@@ -104,6 +455,225 @@ impl Sensor {
             location,
         }
     }
+
+    /// Okuma bu sensöre mi ait? (`sensor_id` eşleşmesi)
+    pub fn is_compatible_with(&self, reading: &SensorReading) -> bool {
+        self.id == reading.sensor_id
+    }
+
+    /// Okumayı bu sensör için doğrula
+    /// 
+    /// Okuma başka bir sensörünse veya değeri sensörün tipine uymuyorsa
+    /// (bkz. `SensorReadingValidator`) `Error::InvalidParameter` döner.
+    pub fn validate_reading(&self, reading: &SensorReading) -> crate::Result<()> {
+        if !self.is_compatible_with(reading) {
+            return Err(crate::Error::InvalidParameter(format!(
+                "reading of sensor {} does not belong to sensor {}",
+                reading.sensor_id, self.id
+            )));
+        }
+        let Ok(sensor_type) = self.sensor_type.parse::<SensorType>();
+        SensorReadingValidator::default().validate(&sensor_type, reading)
+    }
+}
+
+/// Okuma değerinin sensör tipine uygunluğu
+/// 
+/// - Değer sonlu bir sayı olmalı; ikili sensörlerde (motion) 0 veya 1,
+///   ya da sayı olmayan bir olay adı (`"motion_detected"`)
+/// - Sıcaklık `temperature_range` (°C), nem `humidity_range` (%) içinde
+/// - Birim verilmişse tipin birimi olmalı; sıcaklıkta °F ve K °C'ye
+///   çevrilerek denetlenir. Nem sensörüne gelen °C'lik okuma reddedilir.
+/// 
+/// Bilinmeyen tipler (`SensorType::Other`) için sadece sayı kontrolü yapılır.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorReadingValidator {
+    pub temperature_range: (f64, f64),
+    pub humidity_range: (f64, f64),
+}
+
+impl Default for SensorReadingValidator {
+    /// DS18B20'nin ölçüm aralığı (-55..125 °C) ve bağıl nem (0..100 %)
+    fn default() -> Self {
+        Self { temperature_range: (-55.0, 125.0), humidity_range: (0.0, 100.0) }
+    }
+}
+
+impl SensorReadingValidator {
+    /// `SensorReading`'i doğrula (değer string'dir, birimi yoktur)
+    pub fn validate(&self, sensor_type: &SensorType, reading: &SensorReading) -> crate::Result<()> {
+        match reading.value.trim().parse::<f64>() {
+            Ok(value) => self.validate_value(sensor_type, value, ""),
+            Err(_) if sensor_type.is_binary() && !reading.value.trim().is_empty() => Ok(()),
+            Err(_) => Err(invalid(format!("{sensor_type} value '{}' is not a number", reading.value))),
+        }
+    }
+
+    /// Sayısal değeri ve birimini doğrula (`unit` boşsa birim kontrolü yapılmaz)
+    pub fn validate_value(&self, sensor_type: &SensorType, value: f64, unit: &str) -> crate::Result<()> {
+        if !value.is_finite() {
+            return Err(invalid(format!("{sensor_type} value is not a finite number")));
+        }
+        let unit = normalize_unit(unit).trim();
+        let expected = sensor_type.default_unit();
+        let value = match sensor_type {
+            SensorType::Temperature if !unit.is_empty() && unit != expected => unit
+                .parse::<Unit>()
+                .and_then(|from| unit::convert(value, from, Unit::Celsius))
+                .map_err(|_| invalid(format!("unit '{unit}' is not a temperature unit")))?,
+            SensorType::Other(_) => return Ok(()),
+            _ if !unit.is_empty() && unit != expected => {
+                return Err(invalid(format!("unit '{unit}' does not match {sensor_type} sensor (expected '{expected}')")));
+            }
+            _ => value,
+        };
+
+        let range = match sensor_type {
+            SensorType::Temperature => self.temperature_range,
+            SensorType::Humidity => self.humidity_range,
+            SensorType::Motion => (0.0, 1.0),
+            SensorType::Other(_) => return Ok(()),
+        };
+        if sensor_type.is_binary() && value != 0.0 && value != 1.0 {
+            return Err(invalid(format!("{sensor_type} value must be 0 or 1, got {value}")));
+        }
+        if value < range.0 || value > range.1 {
+            return Err(invalid(format!("{sensor_type} value {value} {expected} is outside {}..={}", range.0, range.1)));
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: String) -> crate::Error {
+    crate::Error::InvalidParameter(message)
+}
+
+impl SensorStats {
+    /// Değerlerin istatistikleri
+    pub fn from_readings(values: impl IntoIterator<Item = f64>) -> Self {
+        let mut stats = Self::default();
+        values.into_iter().for_each(|value| stats.push(value));
+        stats
+    }
+
+    /// Bir değer ekle
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        let mean = self.mean.unwrap_or_default();
+        let delta = value - mean;
+        let mean = mean + delta / self.count as f64;
+        self.m2 += delta * (value - mean);
+
+        self.mean = Some(mean);
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+        self.std_dev = Some((self.m2 / self.count as f64).sqrt());
+    }
+
+    /// İstatistikleri başka birime çevir (`std_dev`'e sadece ölçek uygulanır)
+    ///
+    /// Sonradan `push` edilen değerler yeni birimde olmalıdır.
+    pub fn convert_unit(&mut self, from: Unit, to: Unit) -> crate::Result<()> {
+        let convert = |value: Option<f64>| value.map(|v| unit::convert(v, from, to)).transpose();
+        let scale = unit::convert_delta(1.0, from, to)?;
+        self.min = convert(self.min)?;
+        self.max = convert(self.max)?;
+        self.mean = convert(self.mean)?;
+        self.std_dev = self.std_dev.map(|v| v * scale);
+        self.m2 *= scale * scale;
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for SensorReadingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> SensorReadingBuffer<N> {
+    /// Boş tampon
+    pub const fn new() -> Self {
+        Self { buf: [None; N], head: 0, count: 0 }
+    }
+
+    /// Değer ekle (tampon doluysa en eski değerin yerine)
+    ///
+    /// `N` 0 ise hiçbir şey tutulmaz.
+    pub fn push(&mut self, value: f64) {
+        if N == 0 {
+            return;
+        }
+        self.buf[self.head] = Some(value);
+        self.head = (self.head + 1) % N;
+        self.count = (self.count + 1).min(N);
+    }
+
+    /// Tampondaki değer sayısı
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Son `N` okuma birikti mi?
+    pub fn is_full(&self) -> bool {
+        N > 0 && self.count == N
+    }
+
+    /// Değerler, en eskiden en yeniye
+    pub fn iter(&self) -> impl Iterator<Item = f64> + '_ {
+        let start = (self.head + N - self.count) % N.max(1);
+        (0..self.count).filter_map(move |i| self.buf[(start + i) % N])
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.iter().reduce(f64::min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.iter().reduce(f64::max)
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (!self.is_empty()).then(|| self.iter().sum::<f64>() / self.count as f64)
+    }
+
+    /// Popülasyon varyansı (`SensorStats::std_dev`'in karesi)
+    pub fn variance(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        Some(self.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / self.count as f64)
+    }
+
+    /// Penceredeki değerlerin istatistikleri
+    pub fn stats(&self) -> SensorStats {
+        SensorStats::from_readings(self.iter())
+    }
+}
+
+impl SensorSummary {
+    /// Özeti başka birime çevir
+    pub fn convert_unit(&mut self, from: Unit, to: Unit) -> crate::Result<()> {
+        self.min_value = unit::convert(self.min_value, from, to)?;
+        self.max_value = unit::convert(self.max_value, from, to)?;
+        self.avg_value = unit::convert(self.avg_value, from, to)?;
+        Ok(())
+    }
+}
+
+impl Hash for Sensor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl Hash for SensorReading {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sensor_id.hash(state);
+        self.timestamp.hash(state);
+    }
 }
 
 impl SensorReading {
@@ -129,11 +699,61 @@ impl SensorReading {
         self.is_valid = false;
         self
     }
+
+    /// Değeri sayı olarak oku (sayısal olmayan veya sonlu olmayan değerlerde `None`)
+    pub fn value_as_f64(&self) -> Option<f64> {
+        self.value.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+    }
+
+    /// Değeri Kalman filtresinden geçir
+    /// 
+    /// Filtrelenmiş değer ham değerin ondalık hassasiyetiyle (en az 2 basamak)
+    /// yazılır. Sayısal olmayan okumalar (`"motion_detected"`) değişmez ve
+    /// filtreyi etkilemez.
+    pub fn kalman_smooth(&mut self, filter: &mut KalmanFilter1D) {
+        let Some(raw) = self.value_as_f64() else {
+            return;
+        };
+        let decimals = self.value.trim().split_once('.').map_or(0, |(_, frac)| frac.len()).max(2);
+        self.value = format!("{:.*}", decimals, filter.update(raw));
+    }
+}
+
+impl SensorGroup {
+    /// Üye listesini değiştir (sıralanır, tekrarlar atılır)
+    pub fn set_members(&mut self, sensor_ids: impl IntoIterator<Item = Uuid>) {
+        let mut ids: Vec<Uuid> = sensor_ids.into_iter().collect();
+        ids.sort();
+        ids.dedup();
+        self.sensor_ids = ids;
+    }
+
+    /// Sensör bu grubun üyesi mi?
+    pub fn contains(&self, sensor_id: Uuid) -> bool {
+        self.sensor_ids.binary_search(&sensor_id).is_ok()
+    }
+
+    /// Üye sensörlerin okumalarının ortalaması
+    /// 
+    /// Sadece üyelerin geçerli (`is_valid`) ve sayısal okumaları sayılır;
+    /// hiç yoksa `None`.
+    pub fn average_reading(&self, readings: &HashMap<Uuid, SensorReading>) -> Option<f64> {
+        let values: Vec<f64> = self
+            .sensor_ids
+            .iter()
+            .filter_map(|id| readings.get(id))
+            .filter(|r| r.is_valid)
+            .filter_map(|r| r.value.trim().parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+            .collect();
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_new_sensor() {
@@ -159,4 +779,329 @@ mod tests {
         assert_eq!(reading.value, "23.5");
         assert!(reading.is_valid);
     }
+
+    fn sensor(sensor_type: &str) -> Sensor {
+        Sensor::new(Uuid::new_v4(), "sensor".into(), sensor_type.into(), String::new(), "kitchen".into())
+    }
+
+    #[test]
+    fn test_kalman_smooth_updates_value() {
+        let id = Uuid::new_v4();
+        let mut filter = KalmanFilter1D::new(1e-5, 0.5);
+        let smoothed: Vec<f64> = ["20.9", "19.2", "20.6", "19.5", "21.1", "18.8", "20.4", "19.7", "20.8", "19.1"]
+            .into_iter()
+            .map(|raw| {
+                let mut reading = SensorReading::new(id, raw.into());
+                reading.kalman_smooth(&mut filter);
+                reading.value_as_f64().unwrap()
+            })
+            .collect();
+        assert_eq!(smoothed[0], 20.9);
+        assert!((smoothed[9] - 20.0).abs() < 0.2, "{smoothed:?}");
+
+        // Sayısal olmayan değerler değişmez, filtre durumu korunur
+        let before = filter;
+        let mut motion = SensorReading::new(id, "motion_detected".into());
+        motion.kalman_smooth(&mut filter);
+        assert_eq!((motion.value.as_str(), filter), ("motion_detected", before));
+        assert_eq!(SensorReading::new(id, "NaN".into()).value_as_f64(), None);
+    }
+
+    #[test]
+    fn test_sensor_id_for_is_stable() {
+        let id = Sensor::id_for("edge-agent-001", "temperature");
+        assert_eq!(id, Sensor::id_for("edge-agent-001", "temperature"));
+        assert_ne!(id, Sensor::id_for("edge-agent-001", "humidity"));
+        assert_ne!(id, Sensor::id_for("edge-agent-002", "temperature"));
+    }
+
+    #[test]
+    fn test_sensor_group_members() {
+        let (a, b) = (sensor("temperature"), sensor("humidity"));
+        let mut group = Sensor::group("bedroom", [&a, &b, &a]);
+        assert_eq!(group.sensor_ids.len(), 2);
+        assert!(group.contains(a.id) && group.contains(b.id));
+
+        group.set_members([b.id]);
+        assert!(!group.contains(a.id));
+        assert_eq!(group.sensor_ids, vec![b.id]);
+    }
+
+    #[test]
+    fn test_sensor_group_member_accepts_id_or_pair() {
+        let id = Sensor::id_for("edge-agent-001", "temperature");
+        let members: Vec<SensorGroupMember> = serde_json::from_value(serde_json::json!([
+            id,
+            {"device_id": "edge-agent-001", "sensor_type": "humidity"},
+        ]))
+        .unwrap();
+        let ids: Vec<_> = members.iter().map(SensorGroupMember::sensor_id).collect();
+        assert_eq!(ids, vec![id, Sensor::id_for("edge-agent-001", "humidity")]);
+
+        assert!(serde_json::from_value::<SensorGroupMember>(serde_json::json!({"device_id": "edge-agent-001"})).is_err());
+    }
+
+    #[test]
+    fn test_sensor_group_average_reading() {
+        let (a, b, c, outsider) = (sensor("temperature"), sensor("temperature"), sensor("motion"), sensor("temperature"));
+        let group = Sensor::group("bedroom", [&a, &b, &c]);
+        assert_eq!(group.average_reading(&HashMap::new()), None);
+
+        let readings: HashMap<Uuid, SensorReading> = [
+            SensorReading::new(a.id, "20.0".into()),
+            SensorReading::new(b.id, "23.0".into()),
+            // Sayısal olmayan, geçersiz ve üye olmayan okumalar sayılmaz
+            SensorReading::new(c.id, "motion_detected".into()),
+            SensorReading::new(outsider.id, "99.0".into()),
+        ]
+        .into_iter()
+        .map(|r| (r.sensor_id, r))
+        .collect();
+        assert_eq!(group.average_reading(&readings), Some(21.5));
+
+        let mut readings = readings;
+        readings.insert(b.id, SensorReading::new(b.id, "23.0".into()).mark_invalid());
+        assert_eq!(group.average_reading(&readings), Some(20.0));
+    }
+
+    #[test]
+    fn test_validate_reading_compatible() {
+        let temperature = sensor("temperature");
+        let reading = SensorReading::new(temperature.id, "23.5".into());
+        assert!(temperature.is_compatible_with(&reading));
+        assert!(temperature.validate_reading(&reading).is_ok());
+
+        let motion = sensor("motion");
+        for value in ["1", "0", "motion_detected"] {
+            assert!(motion.validate_reading(&SensorReading::new(motion.id, value.into())).is_ok(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_validate_reading_incompatible() {
+        let (temperature, humidity) = (sensor("temperature"), sensor("humidity"));
+        let humidity_reading = SensorReading::new(humidity.id, "58.2".into());
+        assert!(!temperature.is_compatible_with(&humidity_reading));
+        let err = temperature.validate_reading(&humidity_reading).unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidParameter(ref m) if m.contains("does not belong")), "{err}");
+
+        for value in ["140", "-60", "NaN", "warm", ""] {
+            assert!(temperature.validate_reading(&SensorReading::new(temperature.id, value.into())).is_err(), "{value}");
+        }
+        assert!(humidity.validate_reading(&SensorReading::new(humidity.id, "101".into())).is_err());
+        let motion = sensor("motion");
+        assert!(motion.validate_reading(&SensorReading::new(motion.id, "0.5".into())).is_err());
+    }
+
+    #[test]
+    fn test_validate_value_units_and_unknown_types() {
+        let validator = SensorReadingValidator::default();
+        assert!(validator.validate_value(&SensorType::Temperature, 77.0, "°F").is_ok());
+        assert!(validator.validate_value(&SensorType::Temperature, 300.0, "°F").is_err());
+        assert!(validator.validate_value(&SensorType::Temperature, 22.0, "celsius").is_ok());
+        // Nem sensörüne sıcaklık birimiyle gelen okuma
+        assert!(validator.validate_value(&SensorType::Humidity, 22.0, "°C").is_err());
+        assert!(validator.validate_value(&SensorType::Temperature, 40.0, "%").is_err());
+
+        // Tipi bilinmeyen sensör (metadata yok): sadece sayı kontrolü
+        let other: SensorType = "pressure".parse().unwrap();
+        assert!(validator.validate_value(&other, 1013.0, "hPa").is_ok());
+        assert!(validator.validate_value(&other, f64::INFINITY, "").is_err());
+        let untyped = sensor("");
+        assert!(untyped.validate_reading(&SensorReading::new(untyped.id, "-9999".into())).is_ok());
+    }
+
+    #[test]
+    fn test_sensor_reading_hash_set_dedup() {
+        use std::collections::HashSet;
+
+        let reading = SensorReading::new(Uuid::new_v4(), "23.5".to_string());
+        let later = SensorReading { timestamp: reading.timestamp + chrono::Duration::seconds(5), ..reading.clone() };
+        let other_sensor = SensorReading { sensor_id: Uuid::new_v4(), ..reading.clone() };
+
+        let set: HashSet<_> = [reading.clone(), reading.clone(), later, other_sensor, reading.clone()].into_iter().collect();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&reading));
+
+        // Aynı sensör + zaman ama farklı değer: aynı hash, farklı kayıt
+        let corrected = SensorReading { value: "23.6".to_string(), ..reading.clone() };
+        assert!(!set.contains(&corrected));
+    }
+
+    #[test]
+    fn test_sensor_hash_uses_id() {
+        use std::collections::HashSet;
+
+        let sensor = Sensor::new(Uuid::new_v4(), "temp".into(), "temperature".into(), "°C".into(), "bedroom".into());
+        let set: HashSet<_> = [sensor.clone(), sensor.clone()].into_iter().collect();
+        assert_eq!(set.len(), 1);
+        assert!(set.contains(&sensor));
+    }
+
+    #[test]
+    fn test_sensor_type_round_trip() {
+        for (sensor_type, raw) in [
+            (SensorType::Temperature, "temperature"),
+            (SensorType::Humidity, "humidity"),
+            (SensorType::Motion, "motion"),
+            (SensorType::Other("pressure".into()), "pressure"),
+        ] {
+            assert_eq!(sensor_type.to_string(), raw);
+            assert_eq!(raw.parse::<SensorType>().unwrap(), sensor_type);
+
+            let json = serde_json::to_value(&sensor_type).unwrap();
+            assert_eq!(json, serde_json::json!(raw));
+            assert_eq!(serde_json::from_value::<SensorType>(json).unwrap(), sensor_type);
+        }
+        assert_eq!(" Temperature ".parse::<SensorType>().unwrap(), SensorType::Temperature);
+    }
+
+    #[test]
+    fn test_sensor_type_units_and_names() {
+        assert_eq!(SensorType::Temperature.default_unit(), "°C");
+        assert_eq!(SensorType::Humidity.default_unit(), "%");
+        assert_eq!(SensorType::Motion.default_unit(), "bool");
+        assert_eq!(SensorType::Other("pressure".into()).default_unit(), "");
+
+        assert_eq!(SensorType::Humidity.display_name(), "Humidity");
+        assert_eq!(SensorType::Other("pressure".into()).display_name(), "Pressure");
+    }
+
+    #[test]
+    fn test_deprecated_unit_aliases() {
+        assert_eq!(normalize_unit("celsius"), SensorType::Temperature.default_unit());
+        assert_eq!(normalize_unit("Percent"), SensorType::Humidity.default_unit());
+        assert_eq!(normalize_unit("boolean"), SensorType::Motion.default_unit());
+        // Standart ve bilinmeyen birimler değişmez
+        assert_eq!(normalize_unit("°C"), "°C");
+        assert_eq!(normalize_unit("hPa"), "hPa");
+    }
+
+    /// Mikrosaniye hassasiyetli, 1970-2100 arası UTC zaman damgası
+    fn timestamps() -> impl Strategy<Value = DateTime<Utc>> {
+        (0i64..4_102_444_800, 0u32..1_000_000)
+            .prop_map(|(secs, micros)| DateTime::from_timestamp(secs, micros * 1000).unwrap())
+    }
+
+    impl Arbitrary for SensorReading {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            (
+                any::<u128>(),
+                prop::string::string_regex("[0-9]{1,3}\\.[0-9]{1,2}").unwrap(),
+                timestamps(),
+                any::<bool>(),
+                prop::option::of(any::<String>()).prop_map(|m| m.map(serde_json::Value::String)),
+            )
+                .prop_map(|(id, value, timestamp, is_valid, metadata)| SensorReading {
+                    sensor_id: Uuid::from_u128(id),
+                    value,
+                    timestamp,
+                    is_valid,
+                    metadata,
+                })
+                .boxed()
+        }
+    }
+
+    #[test]
+    fn test_sensor_stats_matches_two_pass_computation() {
+        assert_eq!(SensorStats::from_readings([]), SensorStats::default());
+
+        let values: Vec<f64> = (0..1000).map(|i| 1e6 + (i % 7) as f64 * 0.5).collect();
+        let stats = SensorStats::from_readings(values.iter().copied());
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+
+        assert_eq!(stats.count, 1000);
+        assert_eq!((stats.min, stats.max), (Some(1e6), Some(1e6 + 3.0)));
+        assert!((stats.mean.unwrap() - mean).abs() < 1e-9);
+        assert!((stats.std_dev.unwrap() - variance.sqrt()).abs() < 1e-9);
+
+        let json = serde_json::to_value(SensorStats::from_readings([21.5])).unwrap();
+        assert_eq!(json, serde_json::json!({"count": 1, "min": 21.5, "max": 21.5, "mean": 21.5, "std_dev": 0.0}));
+    }
+
+    #[test]
+    fn test_stats_and_summary_unit_conversion() {
+        let celsius = [10.0, 20.0, 30.0];
+        let mut stats = SensorStats::from_readings(celsius);
+        stats.convert_unit(Unit::Celsius, Unit::Fahrenheit).unwrap();
+        let fahrenheit = SensorStats::from_readings(celsius.map(unit::celsius_to_fahrenheit));
+        for (a, b) in [(stats.min, fahrenheit.min), (stats.max, fahrenheit.max), (stats.mean, fahrenheit.mean), (stats.std_dev, fahrenheit.std_dev)] {
+            assert!((a.unwrap() - b.unwrap()).abs() < 1e-9, "{a:?} != {b:?}");
+        }
+        assert!(SensorStats::default().convert_unit(Unit::Celsius, Unit::Fahrenheit).is_ok());
+        assert!(stats.convert_unit(Unit::Fahrenheit, Unit::Psi).is_err());
+
+        let mut summary = SensorSummary {
+            device_id: "edge-agent-001".into(),
+            sensor_type: "temperature".into(),
+            bucket_start: Utc::now(),
+            min_value: 0.0,
+            max_value: 100.0,
+            avg_value: 50.0,
+            count: 60,
+        };
+        summary.convert_unit(Unit::Celsius, Unit::Fahrenheit).unwrap();
+        for (actual, expected) in [(summary.min_value, 32.0), (summary.max_value, 212.0), (summary.avg_value, 122.0)] {
+            assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+        }
+        assert_eq!(summary.count, 60);
+    }
+
+    #[test]
+    fn test_reading_buffer_wraps_around() {
+        let mut window = SensorReadingBuffer::<3>::new();
+        assert!(window.is_empty());
+        assert_eq!((window.min(), window.max(), window.mean(), window.variance()), (None, None, None, None));
+
+        window.push(5.0);
+        window.push(1.0);
+        assert_eq!((window.len(), window.is_full()), (2, false));
+        assert_eq!(window.iter().collect::<Vec<_>>(), [5.0, 1.0]);
+
+        for value in [2.0, 9.0, 4.0] {
+            window.push(value);
+        }
+        // 5 ve 1 üzerine yazıldı; sıra en eskiden en yeniye
+        assert_eq!((window.len(), window.is_full()), (3, true));
+        assert_eq!(window.iter().collect::<Vec<_>>(), [2.0, 9.0, 4.0]);
+        assert_eq!((window.min(), window.max(), window.mean()), (Some(2.0), Some(9.0), Some(5.0)));
+        assert_eq!(window.variance(), Some(26.0 / 3.0));
+
+        let mut empty = SensorReadingBuffer::<0>::default();
+        empty.push(1.0);
+        assert!(empty.is_empty() && !empty.is_full());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_reading_buffer_matches_reference(values in prop::collection::vec(-1e6..1e6f64, 1..200)) {
+            let mut window = SensorReadingBuffer::<60>::new();
+            values.iter().for_each(|&value| window.push(value));
+
+            // Referans: son 60 değer üzerinde doğrudan hesap
+            let last = &values[values.len().saturating_sub(60)..];
+            let mean = last.iter().sum::<f64>() / last.len() as f64;
+            let variance = last.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / last.len() as f64;
+            prop_assert_eq!(window.iter().collect::<Vec<_>>(), last.to_vec());
+            prop_assert_eq!(window.min(), last.iter().copied().reduce(f64::min));
+            prop_assert_eq!(window.max(), last.iter().copied().reduce(f64::max));
+            prop_assert!((window.mean().unwrap() - mean).abs() <= 1e-9 * mean.abs().max(1.0));
+            prop_assert!((window.variance().unwrap() - variance).abs() <= 1e-9 * variance.max(1.0));
+
+            let stats = window.stats();
+            prop_assert_eq!(stats.count, last.len() as u64);
+            prop_assert!((stats.std_dev.unwrap() - variance.sqrt()).abs() <= 1e-6 * variance.sqrt().max(1.0));
+        }
+
+        #[test]
+        fn prop_sensor_reading_json_round_trip(reading in any::<SensorReading>()) {
+            let json = serde_json::to_string(&reading).unwrap();
+            prop_assert_eq!(serde_json::from_str::<SensorReading>(&json).unwrap(), reading);
+        }
+    }
 }
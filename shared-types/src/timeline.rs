@@ -0,0 +1,151 @@
+//! Device Timeline Types
+//!
+//! Bir cihazın tüm sensör tiplerindeki etkinliğini (okumalar, alarmlar,
+//! heartbeat'ler, durum değişiklikleri) tek bir zaman sırasında gösterir
+//! (`GET /v1/devices/{id}/timeline`).
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+#[cfg(feature = "sqlx-support")]
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+/// Zaman çizelgesi olayının türü
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEventType {
+    /// Sensör okuması (`sensor_readings`)
+    Reading,
+    /// Eşik alarmı (`sensor_alerts`)
+    Alert,
+    /// Cihazın çevrimiçi sinyali (`device_heartbeats`)
+    Heartbeat,
+    /// Cihazın raporladığı durum (`device_status_changes`)
+    StatusChange,
+    /// Cihazda çalıştırılan komut
+    CommandExecuted,
+}
+
+impl TimelineEventType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reading => "reading",
+            Self::Alert => "alert",
+            Self::Heartbeat => "heartbeat",
+            Self::StatusChange => "status_change",
+            Self::CommandExecuted => "command_executed",
+        }
+    }
+}
+
+impl fmt::Display for TimelineEventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TimelineEventType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Reading, Self::Alert, Self::Heartbeat, Self::StatusChange, Self::CommandExecuted]
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| format!("unknown timeline event type: {s}"))
+    }
+}
+
+/// SQL'de `event_type` TEXT kolonundan okunur
+impl TryFrom<String> for TimelineEventType {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Zaman çizelgesindeki tek olay
+///
+/// Alanlar olay türüne göre dolar: okumalarda `sensor_type` + `value`,
+/// alarmlarda ayrıca `message`, durum değişikliklerinde `message`
+/// (raporlanan durum JSON'u).
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "timestamp": "2024-01-20T10:30:00Z",
+///   "event_type": "alert",
+///   "sensor_type": "temperature",
+///   "value": 41.2,
+///   "message": "temperature 41.2 above max 35"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct TimelineEvent {
+    pub timestamp: DateTime<Utc>,
+    #[cfg_attr(feature = "sqlx-support", sqlx(try_from = "String"))]
+    pub event_type: TimelineEventType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensor_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Cihazın zaman çizelgesi (olaylar yeniden eskiye)
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "device_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "events": [
+///     {"timestamp": "2024-01-20T10:30:05Z", "event_type": "heartbeat"},
+///     {"timestamp": "2024-01-20T10:30:00Z", "event_type": "reading", "sensor_type": "temperature", "value": 22.5}
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceTimeline {
+    pub device_id: Uuid,
+    pub events: Vec<TimelineEvent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_type_names_match_serde() {
+        for event_type in [
+            TimelineEventType::Reading,
+            TimelineEventType::Alert,
+            TimelineEventType::Heartbeat,
+            TimelineEventType::StatusChange,
+            TimelineEventType::CommandExecuted,
+        ] {
+            assert_eq!(serde_json::to_value(event_type).unwrap(), event_type.as_str());
+            assert_eq!(event_type.as_str().parse::<TimelineEventType>(), Ok(event_type));
+        }
+        assert!("status".parse::<TimelineEventType>().is_err());
+    }
+
+    #[test]
+    fn test_empty_fields_omitted() {
+        let event = TimelineEvent {
+            timestamp: "2024-01-20T10:30:05Z".parse().unwrap(),
+            event_type: TimelineEventType::Heartbeat,
+            sensor_type: None,
+            value: None,
+            message: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"timestamp": "2024-01-20T10:30:05Z", "event_type": "heartbeat"})
+        );
+    }
+}
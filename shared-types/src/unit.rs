@@ -0,0 +1,274 @@
+//! Ölçüm Birimi Dönüşümleri
+//!
+//! Sıcaklık (°C/°F/K), basınç (hPa/kPa/inHg/psi) ve hız (m/s/km/h/mph)
+//! birimleri arasında dönüşüm. Okumalar standart birimle saklanır
+//! (bkz. `sensor::normalize_unit`); dönüşüm sadece gösterim içindir
+//! (`?unit=fahrenheit`).
+//!
+//! # Örnek
+//! ```
+//! use shared_types::unit::{convert, Unit};
+//!
+//! let from: Unit = "°C".parse().unwrap();
+//! let to: Unit = "fahrenheit".parse().unwrap();
+//! assert_eq!(convert(100.0, from, to).unwrap(), 212.0);
+//! assert!(convert(1.0, from, Unit::Hectopascal).is_err());
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use crate::{Error, Result};
+
+/// 1 inHg kaç hPa (0 °C'de cıva sütunu)
+const HPA_PER_INHG: f64 = 33.863_886_666_7;
+/// 1 psi kaç hPa
+const HPA_PER_PSI: f64 = 68.947_572_931_7;
+/// 1 mil kaç km
+const KM_PER_MILE: f64 = 1.609_344;
+
+/// Birimin ölçtüğü büyüklük (sadece aynı büyüklükteki birimler dönüşür)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Quantity {
+    Temperature,
+    Pressure,
+    Speed,
+}
+
+/// Dönüştürülebilen ölçüm birimleri
+///
+/// Parse büyük/küçük harf duyarsızdır; sembol ve isimler kabul edilir
+/// (`"°F"`, `"F"`, `"fahrenheit"`). JSON'daki `unit` alanına `as_str`
+/// (sembol) yazılır.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+    Hectopascal,
+    Kilopascal,
+    InchOfMercury,
+    Psi,
+    MetersPerSecond,
+    KilometersPerHour,
+    MilesPerHour,
+}
+
+impl Unit {
+    /// Birimin sembolü (`"°F"`, `"hPa"`, `"km/h"`...)
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+            Self::Kelvin => "K",
+            Self::Hectopascal => "hPa",
+            Self::Kilopascal => "kPa",
+            Self::InchOfMercury => "inHg",
+            Self::Psi => "psi",
+            Self::MetersPerSecond => "m/s",
+            Self::KilometersPerHour => "km/h",
+            Self::MilesPerHour => "mph",
+        }
+    }
+
+    /// Birimin ölçtüğü büyüklük
+    pub fn quantity(self) -> Quantity {
+        match self {
+            Self::Celsius | Self::Fahrenheit | Self::Kelvin => Quantity::Temperature,
+            Self::Hectopascal | Self::Kilopascal | Self::InchOfMercury | Self::Psi => Quantity::Pressure,
+            Self::MetersPerSecond | Self::KilometersPerHour | Self::MilesPerHour => Quantity::Speed,
+        }
+    }
+
+    /// Temel birim (°C, hPa, m/s) cinsinden 1 birimlik fark
+    fn scale(self) -> f64 {
+        match self {
+            Self::Celsius | Self::Kelvin | Self::Hectopascal | Self::MetersPerSecond => 1.0,
+            Self::Fahrenheit => 5.0 / 9.0,
+            Self::Kilopascal => 10.0,
+            Self::InchOfMercury => HPA_PER_INHG,
+            Self::Psi => HPA_PER_PSI,
+            Self::KilometersPerHour => 1.0 / 3.6,
+            Self::MilesPerHour => KM_PER_MILE / 3.6,
+        }
+    }
+
+    /// Değeri temel birime (°C, hPa, m/s) çevir
+    fn unit_to_base(self, value: f64) -> f64 {
+        match self {
+            Self::Fahrenheit => fahrenheit_to_celsius(value),
+            Self::Kelvin => value - 273.15,
+            _ => value * self.scale(),
+        }
+    }
+
+    /// Temel birimdeki değeri bu birime çevir
+    fn base_to_unit(self, value: f64) -> f64 {
+        match self {
+            Self::Fahrenheit => celsius_to_fahrenheit(value),
+            Self::Kelvin => value + 273.15,
+            _ => value / self.scale(),
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "°c" | "c" | "celsius" => Self::Celsius,
+            "°f" | "f" | "fahrenheit" => Self::Fahrenheit,
+            "k" | "kelvin" => Self::Kelvin,
+            "hpa" | "mbar" | "hectopascal" => Self::Hectopascal,
+            "kpa" | "kilopascal" => Self::Kilopascal,
+            "inhg" => Self::InchOfMercury,
+            "psi" => Self::Psi,
+            "m/s" | "mps" => Self::MetersPerSecond,
+            "km/h" | "kmh" | "kph" => Self::KilometersPerHour,
+            "mph" => Self::MilesPerHour,
+            _ => return Err(Error::InvalidParameter(format!("unknown unit: {s}"))),
+        })
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Değeri `from` biriminden `to` birimine çevir
+///
+/// Farklı büyüklükteki birimler (ör. °C → hPa) `InvalidParameter` döner.
+pub fn convert(value: f64, from: Unit, to: Unit) -> Result<f64> {
+    check_compatible(from, to)?;
+    if from == to {
+        return Ok(value);
+    }
+    Ok(to.base_to_unit(from.unit_to_base(value)))
+}
+
+/// Fark veya sapmayı (ör. standart sapma) çevir: sadece ölçek uygulanır
+///
+/// 1 °C'lik fark 1.8 °F'tır; ofset farklarda yoktur.
+pub fn convert_delta(delta: f64, from: Unit, to: Unit) -> Result<f64> {
+    check_compatible(from, to)?;
+    Ok(delta * from.scale() / to.scale())
+}
+
+fn check_compatible(from: Unit, to: Unit) -> Result<()> {
+    if from.quantity() != to.quantity() {
+        return Err(Error::InvalidParameter(format!("cannot convert {from} to {to}")));
+    }
+    Ok(())
+}
+
+/// °C → °F
+pub fn celsius_to_fahrenheit(celsius: f64) -> f64 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+/// °F → °C
+pub fn fahrenheit_to_celsius(fahrenheit: f64) -> f64 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+/// hPa → inHg
+pub fn hpa_to_inhg(hpa: f64) -> f64 {
+    hpa / HPA_PER_INHG
+}
+
+/// inHg → hPa
+pub fn inhg_to_hpa(inhg: f64) -> f64 {
+    inhg * HPA_PER_INHG
+}
+
+/// km/h → mph
+pub fn kmh_to_mph(kmh: f64) -> f64 {
+    kmh / KM_PER_MILE
+}
+
+/// mph → km/h
+pub fn mph_to_kmh(mph: f64) -> f64 {
+    mph * KM_PER_MILE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_temperature_pairs() {
+        assert_close(celsius_to_fahrenheit(0.0), 32.0);
+        assert_close(celsius_to_fahrenheit(-40.0), -40.0);
+        assert_close(fahrenheit_to_celsius(212.0), 100.0);
+        assert_close(fahrenheit_to_celsius(celsius_to_fahrenheit(23.5)), 23.5);
+    }
+
+    #[test]
+    fn test_pressure_and_speed_pairs() {
+        assert!((hpa_to_inhg(1013.25) - 29.92).abs() < 0.01);
+        assert_close(inhg_to_hpa(hpa_to_inhg(987.6)), 987.6);
+        assert_close(mph_to_kmh(60.0), 96.560_64);
+        assert_close(kmh_to_mph(mph_to_kmh(12.5)), 12.5);
+    }
+
+    #[test]
+    fn test_convert_matches_pairs() {
+        for c in [-40.0, 0.0, 21.5, 100.0] {
+            assert_close(convert(c, Unit::Celsius, Unit::Fahrenheit).unwrap(), celsius_to_fahrenheit(c));
+            assert_close(convert(celsius_to_fahrenheit(c), Unit::Fahrenheit, Unit::Celsius).unwrap(), c);
+        }
+        assert_close(convert(0.0, Unit::Celsius, Unit::Kelvin).unwrap(), 273.15);
+        assert_close(convert(32.0, Unit::Fahrenheit, Unit::Kelvin).unwrap(), 273.15);
+        assert_close(convert(1013.25, Unit::Hectopascal, Unit::InchOfMercury).unwrap(), hpa_to_inhg(1013.25));
+        assert!((convert(101.325, Unit::Kilopascal, Unit::Psi).unwrap() - 14.696).abs() < 0.001);
+        assert_close(convert(10.0, Unit::MetersPerSecond, Unit::KilometersPerHour).unwrap(), 36.0);
+        assert_close(convert(60.0, Unit::MilesPerHour, Unit::KilometersPerHour).unwrap(), mph_to_kmh(60.0));
+        assert!(convert(f64::NAN, Unit::Psi, Unit::Psi).unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_convert_delta_skips_offset() {
+        assert_close(convert_delta(1.0, Unit::Celsius, Unit::Fahrenheit).unwrap(), 1.8);
+        assert_close(convert_delta(1.8, Unit::Fahrenheit, Unit::Kelvin).unwrap(), 1.0);
+        assert_close(convert_delta(2.0, Unit::Kilopascal, Unit::Hectopascal).unwrap(), 20.0);
+    }
+
+    #[test]
+    fn test_incompatible_units_are_rejected() {
+        let err = convert(21.5, Unit::Celsius, Unit::Hectopascal).unwrap_err();
+        assert!(matches!(err, Error::InvalidParameter(_)));
+        assert_eq!(err.status_code(), 400);
+        assert!(convert_delta(1.0, Unit::MilesPerHour, Unit::Psi).is_err());
+    }
+
+    #[test]
+    fn test_parse_symbols_and_names() {
+        for (raw, unit) in [
+            ("°C", Unit::Celsius),
+            ("celsius", Unit::Celsius),
+            (" F ", Unit::Fahrenheit),
+            ("Fahrenheit", Unit::Fahrenheit),
+            ("K", Unit::Kelvin),
+            ("hPa", Unit::Hectopascal),
+            ("mbar", Unit::Hectopascal),
+            ("inHg", Unit::InchOfMercury),
+            ("PSI", Unit::Psi),
+            ("m/s", Unit::MetersPerSecond),
+            ("km/h", Unit::KilometersPerHour),
+            ("mph", Unit::MilesPerHour),
+        ] {
+            assert_eq!(raw.parse::<Unit>().unwrap(), unit, "{raw}");
+            assert_eq!(unit.as_str().parse::<Unit>().unwrap(), unit);
+        }
+        for raw in ["%", "bool", "", "rankine"] {
+            assert!(raw.parse::<Unit>().is_err(), "{raw}");
+        }
+        assert_eq!(Unit::Fahrenheit.to_string(), "°F");
+    }
+}
@@ -0,0 +1,156 @@
+//! Webhook Types
+//!
+//! Harici sistemlere olay bildirimi (push) için webhook kayıtları.
+//! Her teslimat `X-RustyFlow-Signature` header'ı ile imzalanır; alıcı
+//! `verify_signature` ile body'nin değiştirilmediğini doğrular.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+#[cfg(feature = "sqlx-support")]
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Sensör alarmı olay tipi
+pub const EVENT_SENSOR_ALERT: &str = "sensor.alert";
+
+/// İmza header'ının adı
+pub const SIGNATURE_HEADER: &str = "X-RustyFlow-Signature";
+
+/// Kayıtlı webhook
+///
+/// `secret` sadece imzalama için kullanılır ve response'larda gösterilmez.
+///
+/// # Örnek JSON
+/// ```json
+/// {
+///   "id": "550e8400-e29b-41d4-a716-446655440000",
+///   "url": "https://example.com/hooks/rustyflow",
+///   "event_types": ["sensor.alert"],
+///   "active": true,
+///   "created_at": "2024-11-13T21:30:00Z"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx-support", derive(FromRow))]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Yeni webhook oluşturma isteği
+///
+/// # Örnek JSON
+/// ```json
+/// {"url": "https://example.com/hooks/rustyflow", "secret": "s3cret", "event_types": ["sensor.alert"]}
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewWebhook {
+    pub url: String,
+    pub secret: String,
+    pub event_types: Vec<String>,
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+fn default_active() -> bool { true }
+
+/// Webhook'a POST edilen body
+///
+/// # Örnek JSON
+/// ```json
+/// {"event_type": "sensor.alert", "occurred_at": "2024-01-20T10:30:00Z", "data": {...}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    pub data: Value,
+}
+
+impl Webhook {
+    /// Webhook bu olay tipini dinliyor mu? (aktif olmalı)
+    pub fn subscribes_to(&self, event_type: &str) -> bool {
+        self.active && self.event_types.iter().any(|e| e == event_type)
+    }
+}
+
+/// Body'yi HMAC-SHA256 ile imzala
+///
+/// Header değeri `sha256=<hex>` formatındadır.
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// `X-RustyFlow-Signature` header'ını doğrula (sabit zamanlı karşılaştırma)
+///
+/// ```
+/// use shared_types::webhook::{sign_payload, verify_signature};
+///
+/// let header = sign_payload("s3cret", b"{\"ok\":true}");
+/// assert!(verify_signature("s3cret", b"{\"ok\":true}", &header));
+/// assert!(!verify_signature("wrong", b"{\"ok\":true}", &header));
+/// ```
+pub fn verify_signature(secret: &str, body: &[u8], header: &str) -> bool {
+    let Some(signature) = header.strip_prefix("sha256=").and_then(|hex_sig| hex::decode(hex_sig).ok()) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_roundtrip_and_tampering() {
+        let body = br#"{"event_type":"sensor.alert"}"#;
+        let header = sign_payload("s3cret", body);
+        assert!(header.starts_with("sha256="));
+        assert_eq!(header.len(), "sha256=".len() + 64);
+
+        assert!(verify_signature("s3cret", body, &header));
+        assert!(!verify_signature("s3cret", br#"{"event_type":"sensor.alarm"}"#, &header));
+        assert!(!verify_signature("other", body, &header));
+        assert!(!verify_signature("s3cret", body, "sha256=zz"));
+        assert!(!verify_signature("s3cret", body, &header["sha256=".len()..]));
+    }
+
+    #[test]
+    fn test_known_vector() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_secret_not_serialized_and_subscription() {
+        let hook = Webhook {
+            id: Uuid::new_v4(),
+            url: "http://localhost/hook".into(),
+            secret: "s3cret".into(),
+            event_types: vec![EVENT_SENSOR_ALERT.into()],
+            active: true,
+            created_at: Utc::now(),
+        };
+        let json = serde_json::to_value(&hook).unwrap();
+        assert!(json.get("secret").is_none());
+
+        assert!(hook.subscribes_to(EVENT_SENSOR_ALERT));
+        assert!(!hook.subscribes_to("device.offline"));
+        assert!(!Webhook { active: false, ..hook }.subscribes_to(EVENT_SENSOR_ALERT));
+    }
+}
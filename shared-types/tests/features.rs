@@ -0,0 +1,24 @@
+//! Feature kombinasyonları
+//!
+//! Workspace'te api-server `sqlx-support`'u açtığından feature'lar
+//! birleşir; crate'in sqlx olmadan (WASM dashboard'un kullandığı gibi)
+//! derlendiği ayrı bir `cargo check` ile doğrulanır.
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_compiles_without_sqlx_support() {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    // Ayrı target dizini: dıştaki `cargo test`'in kilidini beklemesin
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-default-features");
+
+    let output = Command::new(cargo)
+        .args(["check", "--quiet", "--lib", "--no-default-features", "--manifest-path"])
+        .arg(&manifest)
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -1,13 +1,19 @@
-/// API client modülü
-/// 
-/// Bu modül API server'dan veri çekmek için kullanılır.
-/// gloo-net ile HTTP request'leri yapar.
+//! API client modülü
+//! 
+//! Bu modül API server'dan veri çekmek için kullanılır.
+//! gloo-net ile HTTP request'leri yapar.
+
+use std::time::Duration;
 
 use gloo_net::http::Request;
+use leptos::set_timeout_with_handle;
 use serde::{Deserialize, Serialize};
+use web_sys::{AbortController, AbortSignal};
+use chrono::{DateTime, SecondsFormat, Utc};
+use shared_types::{sensor::normalize_unit, unit, CommandHistoryEntry, DashboardSummary, DeviceShadow, MotionEvent, SensorAlert, SensorType, Unit};
 
 /// Sensör verisi - API'den gelen format
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SensorData {
     pub device_id: String,
     pub sensor_type: String,
@@ -15,18 +21,190 @@ pub struct SensorData {
     pub unit: String,
     pub timestamp: String,
     pub metadata: Option<serde_json::Value>,
+    /// Sensör `stale_threshold_secs`'tir veri göndermiyor (değer son bilinen değer)
+    #[serde(default)]
+    pub stale: bool,
+}
+
+impl SensorData {
+    /// Okumanın sensör tipi
+    pub fn kind(&self) -> SensorType {
+        let Ok(kind) = self.sensor_type.parse();
+        kind
+    }
+}
+
+/// Kartın `For` key'i
+///
+/// Okuma (zaman veya birim) değişince key değişir ve kart yeniden çizilir;
+/// cihaz bölümü içinde sensör tipi zaten benzersizdir.
+pub fn sensor_key(sensor: &SensorData) -> String {
+    format!("{}@{}@{}", sensor.sensor_type, sensor.timestamp, sensor.unit)
+}
+
+/// Device ID'nin kısa hali (son `-` parçası, UUID'lerde son 12 hex)
+pub fn device_short_name(device_id: &str) -> &str {
+    device_id.split('-').next_back().filter(|s| !s.is_empty()).unwrap_or(device_id)
+}
+
+/// Bir cihazın sensörleri (dashboard'da tek bölüm)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceGroup {
+    pub device_id: String,
+    /// Sensör tipine göre sıralı
+    pub sensors: Vec<SensorData>,
+}
+
+impl DeviceGroup {
+    /// En az bir sensörü güncel veri gönderiyorsa çevrimiçi
+    pub fn online(&self) -> bool {
+        self.sensors.iter().any(|s| !s.stale)
+    }
+}
+
+/// Okumaları cihazlara göre grupla
+///
+/// Gruplar `device_id`'ye, her gruptaki sensörler sensör tipine göre
+/// sıralanır (stable sort: okumaların geliş sırası realtime güncellemelerde
+/// kartların yerini değiştirmez).
+pub fn group_by_device(sensors: &[SensorData]) -> Vec<DeviceGroup> {
+    let mut groups: Vec<DeviceGroup> = Vec::new();
+    for sensor in sensors {
+        match groups.iter_mut().find(|g| g.device_id == sensor.device_id) {
+            Some(group) => group.sensors.push(sensor.clone()),
+            None => groups.push(DeviceGroup { device_id: sensor.device_id.clone(), sensors: vec![sensor.clone()] }),
+        }
+    }
+    groups.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+    for group in &mut groups {
+        group.sensors.sort_by(|a, b| a.sensor_type.cmp(&b.sensor_type));
+    }
+    groups
+}
+
+/// Cihaz seçicinin seçenekleri: okumalardaki ve cihaz API'sindeki ID'ler (sıralı, tekrarsız)
+pub fn device_options(groups: &[DeviceGroup], known_devices: &[String]) -> Vec<String> {
+    let mut ids: Vec<String> = groups.iter().map(|g| g.device_id.clone()).chain(known_devices.iter().cloned()).collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Geçmiş grafiğinde seçilebilecek sensörler (cihaz, tip)
+///
+/// `device_id` verilirse sadece o cihazınkiler; motion gibi olay sensörleri
+/// grafiğe uygun olmadığından atlanır.
+pub fn chart_sensors(groups: &[DeviceGroup], device_id: Option<&str>) -> Vec<(String, String)> {
+    groups
+        .iter()
+        .filter(|g| device_id.is_none_or(|id| g.device_id == id))
+        .flat_map(|g| g.sensors.iter())
+        .filter(|s| s.kind() != SensorType::Motion)
+        .map(|s| (s.device_id.clone(), s.sensor_type.clone()))
+        .collect()
+}
+
+/// URL hash'indeki seçili cihaz (`#device=<id>`, yoksa tüm cihazlar)
+///
+/// Id `device_hash`'teki gibi percent-encode edilmiş olarak okunur.
+pub fn selected_device_from_hash(hash: &str) -> Option<String> {
+    hash.trim_start_matches('#')
+        .strip_prefix("device=")
+        .filter(|id| !id.is_empty())
+        .map(decode_component)
+}
+
+/// URL bileşenini percent-encode eder (RFC 3986 unreserved karakterler korunur)
+///
+/// Cihaz id'leri kullanıcı girdisidir; `/`, `&` veya `#` içeren bir id
+/// path'i ya da query'yi bölmemeli.
+pub fn encode_component(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// `encode_component`'in tersi
+///
+/// Geçersiz `%` dizileri olduğu gibi bırakılır; UTF-8 olmayan baytlar `�` olur.
+pub fn decode_component(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match (bytes[i], hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Seçili cihazın URL hash'i (paylaşılabilir link; tüm cihazlar için boş)
+pub fn device_hash(device_id: Option<&str>) -> String {
+    device_id.map(|id| format!("#device={}", encode_component(id))).unwrap_or_default()
+}
+
+/// Realtime akıştan gelen sıra numaralı okuma
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensorEvent {
+    pub sequence: u64,
+    #[serde(flatten)]
+    pub data: SensorData,
+}
+
+/// Sıcaklık gösterim birimi (°C/°F anahtarı)
+///
+/// Sunucu `?unit=` ile listeyi çevirir; realtime akışı çevrilmeden gelir,
+/// okumalar `convert_for_display` ile burada çevrilir.
+pub fn temperature_unit_param(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Fahrenheit => "fahrenheit",
+        _ => "celsius",
+    }
+}
+
+/// Sıcaklık okumasını seçili birime çevir (diğer okumalar değişmez)
+pub fn convert_for_display(sensor: &mut SensorData, to: Unit) {
+    let Ok(from) = normalize_unit(&sensor.unit).parse::<Unit>() else {
+        return;
+    };
+    if let Ok(value) = unit::convert(sensor.value, from, to) {
+        sensor.value = value;
+        sensor.unit = to.as_str().to_string();
+    }
+}
+
+/// `GET /api/sensors/realtime` yanıtı
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeBatch {
+    pub events: Vec<SensorEvent>,
+    /// Sonraki istekte `since` olarak gönderilir
+    pub next_sequence: u64,
 }
 
 /// API'den sensör verilerini çeker
 /// 
-/// Şu an için mock data döndürüyor çünkü henüz API endpoint'imiz yok.
-/// Gelecekte gerçek API endpoint'e bağlanacak.
-pub async fn fetch_sensor_data() -> Result<Vec<SensorData>, String> {
+/// Sıcaklıklar sunucuda `temperature_unit`'e çevrilir (`?unit=`).
+/// API cevap vermezse mock data döner.
+pub async fn fetch_sensor_data(temperature_unit: Unit) -> Result<Vec<SensorData>, String> {
     // API URL'i - development için localhost
-    let api_url = "http://localhost:3000/api/sensors";
+    let api_url = format!("http://localhost:3000/api/sensors?unit={}", temperature_unit_param(temperature_unit));
     
     // API'ye request at
-    let response = Request::get(api_url)
+    let response = Request::get(&api_url)
         .send()
         .await
         .map_err(|e| format!("Failed to fetch data: {}", e))?;
@@ -34,32 +212,37 @@ pub async fn fetch_sensor_data() -> Result<Vec<SensorData>, String> {
     // Eğer API cevap vermediyse, mock data dön
     if !response.ok() {
         // Mock data - gerçek sensör verileri gibi görünsün
-        return Ok(vec![
+        let mut mock = vec![
             SensorData {
                 device_id: "edge-agent-001".to_string(),
-                sensor_type: "temperature".to_string(),
+                sensor_type: SensorType::Temperature.to_string(),
                 value: 23.5,
-                unit: "°C".to_string(),
+                unit: SensorType::Temperature.default_unit().to_string(),
                 timestamp: "2024-01-20T10:30:00Z".to_string(),
                 metadata: None,
+                stale: false,
             },
             SensorData {
                 device_id: "edge-agent-001".to_string(),
-                sensor_type: "humidity".to_string(),
+                sensor_type: SensorType::Humidity.to_string(),
                 value: 58.2,
-                unit: "%".to_string(),
+                unit: SensorType::Humidity.default_unit().to_string(),
                 timestamp: "2024-01-20T10:30:00Z".to_string(),
                 metadata: None,
+                stale: false,
             },
             SensorData {
                 device_id: "edge-agent-001".to_string(),
-                sensor_type: "motion".to_string(),
+                sensor_type: SensorType::Motion.to_string(),
                 value: 1.0,
-                unit: "bool".to_string(),
+                unit: SensorType::Motion.default_unit().to_string(),
                 timestamp: "2024-01-20T10:30:00Z".to_string(),
                 metadata: Some(serde_json::json!({"event": "motion_detected"})),
+                stale: false,
             },
-        ]);
+        ];
+        mock.iter_mut().for_each(|sensor| convert_for_display(sensor, temperature_unit));
+        return Ok(mock);
     }
     
     // JSON response'u parse et
@@ -68,3 +251,676 @@ pub async fn fetch_sensor_data() -> Result<Vec<SensorData>, String> {
         .await
         .map_err(|e| format!("Failed to parse response: {}", e))
 }
+
+/// Yeni sensör okumalarını long-poll ile bekler
+/// 
+/// Sunucu yeni okuma gelene kadar (en fazla 2 saniye) yanıtı bekletir;
+/// böylece okumalar polling gecikmesi olmadan ekrana yansır.
+pub async fn fetch_realtime(since: u64) -> Result<RealtimeBatch, String> {
+    let api_url = format!("http://localhost:3000/api/sensors/realtime?since={}", since);
+
+    let response = Request::get(&api_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch data: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("Realtime request failed: {}", response.status()));
+    }
+
+    response
+        .json::<RealtimeBatch>()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+/// Realtime okumaları mevcut listeye uygula
+/// 
+/// Aynı cihaz + sensör tipi için son okuma eskisinin yerini alır.
+/// Sıcaklıklar `temperature_unit`'e çevrilir.
+pub fn merge_events(sensors: &mut Vec<SensorData>, events: Vec<SensorEvent>, temperature_unit: Unit) {
+    for event in events {
+        let mut data = event.data;
+        convert_for_display(&mut data, temperature_unit);
+        match sensors
+            .iter_mut()
+            .find(|s| s.device_id == data.device_id && s.sensor_type == data.sensor_type)
+        {
+            Some(existing) => *existing = data,
+            None => sensors.push(data),
+        }
+    }
+}
+
+/// Kayıtlı edge cihazlarının ID'lerini çeker (`GET /v1/devices?kind=edge`)
+///
+/// Henüz okuma göndermemiş cihazlar da seçicide görünür.
+pub async fn fetch_device_ids() -> Result<Vec<String>, String> {
+    let api_url = "http://localhost:3000/v1/devices?kind=edge";
+
+    let response = Request::get(api_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch devices: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("Devices request failed: {}", response.status()));
+    }
+
+    response
+        .json::<Vec<DeviceShadow>>()
+        .await
+        .map(|shadows| shadows.into_iter().map(|s| s.device_id.to_string()).collect())
+        .map_err(|e| format!("Failed to parse devices: {}", e))
+}
+
+/// Dashboard başlık özetini çeker (`GET /api/summary`)
+pub async fn fetch_summary() -> Result<DashboardSummary, String> {
+    let api_url = "http://localhost:3000/api/summary";
+
+    let response = Request::get(api_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch summary: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("Summary request failed: {}", response.status()));
+    }
+
+    response
+        .json::<DashboardSummary>()
+        .await
+        .map_err(|e| format!("Failed to parse summary: {}", e))
+}
+
+/// `GET /health` sonucu (bağlantı göstergesi)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthProbe {
+    /// 200 ve DB sorunu bildirilmedi
+    Healthy,
+    /// Sunucu cevap verdi ama HTTP hatası döndü veya `db_healthy: false`
+    Degraded,
+    /// Ağ hatası: sunucuya ulaşılamadı
+    Unreachable,
+}
+
+/// `GET /health` gövdesinden kullanılan alan
+#[derive(Debug, Deserialize)]
+struct Liveness {
+    /// DB yoksa alan gelmez
+    #[serde(default)]
+    db_healthy: Option<bool>,
+}
+
+/// API server'ın liveness endpoint'ini yoklar (`GET /health`)
+///
+/// `timeout` içinde cevap (gövde dahil) gelmezse istek `AbortController` ile
+/// iptal edilir ve sunucuya ulaşılamadı sayılır; askıda kalan bir istek
+/// sonraki yoklamaları engellemez.
+pub async fn check_health(timeout: Duration) -> HealthProbe {
+    let controller = AbortController::new().ok();
+    let abort = controller.clone().and_then(|controller| set_timeout_with_handle(move || controller.abort(), timeout).ok());
+    let probe = probe_health(controller.map(|controller| controller.signal()).as_ref()).await;
+    if let Some(abort) = abort {
+        abort.clear();
+    }
+    probe
+}
+
+async fn probe_health(signal: Option<&AbortSignal>) -> HealthProbe {
+    let Ok(response) = Request::get("http://localhost:3000/health").abort_signal(signal).send().await else {
+        return HealthProbe::Unreachable;
+    };
+    if !response.ok() {
+        return HealthProbe::Degraded;
+    }
+    match response.json::<Liveness>().await {
+        Ok(Liveness { db_healthy: Some(false) }) => HealthProbe::Degraded,
+        // Gövde okunurken zaman aşımı: ağ hatası gibi
+        Err(gloo_net::Error::JsError(_)) => HealthProbe::Unreachable,
+        Err(_) => HealthProbe::Degraded,
+        Ok(_) => HealthProbe::Healthy,
+    }
+}
+
+/// Alarm önem derecesi (bilinmeyen değerler `Warning` sayılır)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Info,
+    Critical,
+    #[default]
+    #[serde(other)]
+    Warning,
+}
+
+impl AlertSeverity {
+    /// Renklendirme için CSS class'ı
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Self::Info => "alert-info",
+            Self::Warning => "alert-warning",
+            Self::Critical => "alert-critical",
+        }
+    }
+}
+
+/// Alarm olayı - `GET /v1/alerts/events` formatı
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertEvent {
+    #[serde(flatten)]
+    pub alert: SensorAlert,
+    #[serde(default)]
+    pub severity: AlertSeverity,
+    #[serde(default)]
+    pub acknowledged: bool,
+}
+
+impl AlertEvent {
+    /// Aşılan eşiğin kısa açıklaması ("Above max 35")
+    pub fn breached_threshold(&self) -> String {
+        match self.alert.threshold.breached_bound(self.alert.value) {
+            Some(bound) if self.alert.value < bound => format!("Below min {}", bound),
+            Some(bound) => format!("Above max {}", bound),
+            None => self.alert.message.clone(),
+        }
+    }
+}
+
+/// Sensörün aktif alarmını bul (cihaz + sensör tipi eşleşmesi)
+pub fn active_alert_for<'a>(alerts: &'a [AlertEvent], device_id: &str, sensor_type: &str) -> Option<&'a AlertEvent> {
+    alerts
+        .iter()
+        .find(|a| a.alert.device_id == device_id && a.alert.sensor_type == sensor_type)
+}
+
+/// Aktif alarmları çeker (`GET /v1/alerts/events?active=true`)
+/// 
+/// Alarm endpoint'i olmayan eski (404) veya PostgreSQL'siz (501) API
+/// server'larda `None` döner; dashboard bu durumda alarm panelini gizler.
+pub async fn fetch_active_alerts() -> Result<Option<Vec<AlertEvent>>, String> {
+    let api_url = "http://localhost:3000/v1/alerts/events?active=true";
+
+    let response = Request::get(api_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch alerts: {}", e))?;
+
+    if matches!(response.status(), 404 | 501) {
+        return Ok(None);
+    }
+    if !response.ok() {
+        return Err(format!("Alerts request failed: {}", response.status()));
+    }
+
+    response
+        .json::<Vec<AlertEvent>>()
+        .await
+        .map(Some)
+        .map_err(|e| format!("Failed to parse alerts: {}", e))
+}
+
+/// Alarmı onayla (`POST /v1/alerts/events/{id}/ack`)
+pub async fn acknowledge_alert(event: &AlertEvent) -> Result<(), String> {
+    let api_url = format!("http://localhost:3000/v1/alerts/events/{}/ack", event.alert.id);
+
+    let response = Request::post(&api_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to acknowledge alert: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("Acknowledge request failed: {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Cihazın `since`'ten bu yana motion olaylarını çeker (`GET /api/sensors/events`)
+pub async fn fetch_motion_events(device_id: &str, since: DateTime<Utc>) -> Result<Vec<MotionEvent>, String> {
+    let api_url = format!(
+        "http://localhost:3000/api/sensors/events?device_id={}&sensor_type=motion&from={}",
+        encode_component(device_id),
+        since.to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+
+    let response = Request::get(&api_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch motion events: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("Motion events request failed: {}", response.status()));
+    }
+
+    response
+        .json::<Vec<MotionEvent>>()
+        .await
+        .map_err(|e| format!("Failed to parse motion events: {}", e))
+}
+
+/// Cihaza gönderilen son `limit` komutu çeker (`GET /v1/devices/{id}/commands/history`)
+///
+/// En yeni komut önce gelir; yanıtı gelmemiş komutların `ack_status`'u `None`'dır.
+pub async fn fetch_command_history(device_id: &str, limit: usize) -> Result<Vec<CommandHistoryEntry>, String> {
+    let api_url = format!("http://localhost:3000/v1/devices/{}/commands/history?limit={}", encode_component(device_id), limit);
+
+    let response = Request::get(&api_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch command history: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("Command history request failed: {}", response.status()));
+    }
+
+    response
+        .json::<Vec<CommandHistoryEntry>>()
+        .await
+        .map_err(|e| format!("Failed to parse command history: {}", e))
+}
+
+/// Geçmiş grafiğinin zaman aralığı ön ayarları
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HistoryRange {
+    #[default]
+    Hour,
+    Day,
+    Week,
+}
+
+impl HistoryRange {
+    /// Seçicide gösterilme sırası
+    pub const ALL: [Self; 3] = [Self::Hour, Self::Day, Self::Week];
+
+    /// Buton etiketi ("1h", "24h", "7d")
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hour => "1h",
+            Self::Day => "24h",
+            Self::Week => "7d",
+        }
+    }
+
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            Self::Hour => chrono::Duration::hours(1),
+            Self::Day => chrono::Duration::hours(24),
+            Self::Week => chrono::Duration::days(7),
+        }
+    }
+
+    /// Eksen etiketinin formatı (7 günlükte tarih de gösterilir)
+    fn label_format(self) -> &'static str {
+        match self {
+            Self::Week => "%m-%d %H:%M",
+            _ => "%H:%M",
+        }
+    }
+}
+
+/// Grafikte gösterilen en fazla nokta (sunucu LTTB ile indirir)
+pub const HISTORY_POINTS: usize = 100;
+
+/// LTTB'ye verilen en fazla okuma (7 günlük aralıkta da tüm aralık kapsansın)
+const HISTORY_SOURCE_LIMIT: usize = 20_000;
+
+/// `?downsample=lttb` history yanıtı (sadece okumalar kullanılır)
+#[derive(Debug, Clone, Deserialize)]
+struct DownsampledHistory {
+    readings: Vec<SensorData>,
+}
+
+/// `now`'dan geriye `range` kadarlık history URL'i
+///
+/// Okumalar zamana göre artan döner; `limit` aralığın başını keseceği için
+/// sunucu okumaları `HISTORY_POINTS` noktaya LTTB ile indirir.
+pub fn history_url(device_id: &str, sensor_type: &str, range: HistoryRange, now: DateTime<Utc>) -> String {
+    format!(
+        "http://localhost:3000/v1/sensors/{}/{}/history?from={}&to={}&limit={}&downsample=lttb&points={}",
+        encode_component(device_id),
+        encode_component(sensor_type),
+        (now - range.duration()).to_rfc3339_opts(SecondsFormat::Secs, true),
+        now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        HISTORY_SOURCE_LIMIT,
+        HISTORY_POINTS,
+    )
+}
+
+/// Sensörün son `range` kadarlık geçmişini çeker (`GET /v1/sensors/{id}/{type}/history`)
+///
+/// PostgreSQL bağlı olmayan API server'larda (501) hata döner.
+pub async fn fetch_sensor_history(device_id: &str, sensor_type: &str, range: HistoryRange) -> Result<Vec<SensorData>, String> {
+    let api_url = history_url(device_id, sensor_type, range, Utc::now());
+
+    let response = Request::get(&api_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch history: {}", e))?;
+
+    if !response.ok() {
+        return Err(format!("History request failed: {}", response.status()));
+    }
+
+    response
+        .json::<DownsampledHistory>()
+        .await
+        .map(|history| history.readings)
+        .map_err(|e| format!("Failed to parse history: {}", e))
+}
+
+/// Grafiğin eksen etiketleri (yerel saat) ve değerleri
+///
+/// Timestamp'i parse edilemeyen okumalarda etiket olarak ham değer kullanılır.
+pub fn chart_series(readings: &[SensorData], range: HistoryRange) -> (Vec<String>, Vec<f64>) {
+    readings
+        .iter()
+        .map(|r| {
+            let label = DateTime::parse_from_rfc3339(&r.timestamp)
+                .map(|t| t.with_timezone(&chrono::Local).format(range.label_format()).to_string())
+                .unwrap_or_else(|_| r.timestamp.clone());
+            (label, r.value)
+        })
+        .unzip()
+}
+
+/// Karşılaştırma grafiğindeki tek sensörün serisi
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonSeries {
+    /// Lejant etiketi; normalizasyon ölçeği gizlediği için gerçek aralığı da içerir
+    pub label: String,
+    /// Ortak eksen etiketleriyle hizalı 0–100 değerler (o anda okuması yoksa `None`)
+    pub values: Vec<Option<f64>>,
+}
+
+/// Değerleri 0–100 aralığına çeker (seri başına min-max)
+///
+/// Sabit seriler (tek değer dahil) ortada, 50'de çizilir.
+pub fn normalize_series(values: &[f64]) -> Vec<f64> {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| if max > min { (v - min) / (max - min) * 100.0 } else { 50.0 })
+        .collect()
+}
+
+/// Birden fazla sensörün geçmişini ortak zaman ekseninde birleştirir
+///
+/// Eksen tüm serilerin okuma zamanlarının birleşimidir; her seri kendi
+/// min-max'ına göre normalize edilir ve okuması olmayan zamanlarda boş
+/// (`None`) kalır. Timestamp'i parse edilemeyen okumalar atlanır.
+pub fn comparison_series(histories: &[(String, String, Vec<SensorData>)], range: HistoryRange) -> (Vec<String>, Vec<ComparisonSeries>) {
+    let parsed: Vec<Vec<(DateTime<Utc>, f64)>> = histories
+        .iter()
+        .map(|(_, _, readings)| {
+            readings
+                .iter()
+                .filter_map(|r| Some((DateTime::parse_from_rfc3339(&r.timestamp).ok()?.with_timezone(&Utc), r.value)))
+                .collect()
+        })
+        .collect();
+
+    let mut times: Vec<DateTime<Utc>> = parsed.iter().flatten().map(|(t, _)| *t).collect();
+    times.sort();
+    times.dedup();
+
+    let series = histories
+        .iter()
+        .zip(&parsed)
+        .map(|((device_id, sensor_type, readings), points)| {
+            let raw: Vec<f64> = points.iter().map(|(_, v)| *v).collect();
+            let mut values = vec![None; times.len()];
+            for ((time, _), normalized) in points.iter().zip(normalize_series(&raw)) {
+                if let Ok(i) = times.binary_search(time) {
+                    values[i] = Some(normalized);
+                }
+            }
+            let mut label = format!("{} · {}", device_short_name(device_id), sensor_type);
+            if !raw.is_empty() {
+                let min = raw.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = raw.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                let unit = readings.first().map_or("", |r| r.unit.as_str());
+                label.push_str(&format!(" ({:.1}–{:.1}{})", min, max, unit));
+            }
+            ComparisonSeries { label, values }
+        })
+        .collect();
+
+    let labels = times
+        .iter()
+        .map(|t| t.with_timezone(&chrono::Local).format(range.label_format()).to_string())
+        .collect();
+    (labels, series)
+}
+
+/// Son hareketi "last motion 12m ago" gibi göster
+/// 
+/// Sürmekte olan olay varsa "motion now"; hiç olay yoksa `None`.
+pub fn last_motion_label(events: &[MotionEvent], now: DateTime<Utc>) -> Option<String> {
+    let last = events.iter().max_by_key(|e| e.started_at)?;
+    let Some(ended_at) = last.ended_at else {
+        return Some("motion now".to_string());
+    };
+
+    let minutes = (now - ended_at).num_minutes().max(0);
+    let ago = match minutes {
+        0 => "just now".to_string(),
+        1..=59 => format!("{}m ago", minutes),
+        60..=1439 => format!("{}h ago", minutes / 60),
+        _ => format!("{}d ago", minutes / 1440),
+    };
+    Some(format!("last motion {}", ago))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(device_id: &str, sensor_type: &str, stale: bool) -> SensorData {
+        SensorData {
+            device_id: device_id.to_string(),
+            sensor_type: sensor_type.to_string(),
+            value: 1.0,
+            unit: String::new(),
+            timestamp: "2024-01-20T10:30:00Z".to_string(),
+            metadata: None,
+            stale,
+        }
+    }
+
+    #[test]
+    fn test_group_by_device_sorts_devices_and_sensor_types() {
+        let sensors = vec![
+            reading("edge-b", "temperature", true),
+            reading("edge-a", "motion", false),
+            reading("edge-b", "humidity", true),
+            reading("edge-a", "humidity", false),
+            reading("edge-a", "co2", false),
+        ];
+        let groups = group_by_device(&sensors);
+
+        let layout: Vec<(&str, Vec<&str>)> = groups
+            .iter()
+            .map(|g| (g.device_id.as_str(), g.sensors.iter().map(|s| s.sensor_type.as_str()).collect()))
+            .collect();
+        assert_eq!(layout, vec![("edge-a", vec!["co2", "humidity", "motion"]), ("edge-b", vec!["humidity", "temperature"])]);
+        assert_eq!((groups[0].online(), groups[1].online()), (true, false));
+
+        // Geliş sırası değişse de gruplar aynı kalır
+        let mut reversed = sensors.clone();
+        reversed.reverse();
+        assert_eq!(group_by_device(&reversed), groups);
+        assert!(group_by_device(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_device_options_merge_known_devices() {
+        let groups = group_by_device(&[reading("edge-b", "temperature", false)]);
+        let known = vec!["edge-c".to_string(), "edge-b".to_string(), "edge-a".to_string()];
+        assert_eq!(device_options(&groups, &known), vec!["edge-a", "edge-b", "edge-c"]);
+    }
+
+    #[test]
+    fn test_chart_sensors_skip_motion_and_other_devices() {
+        let groups = group_by_device(&[
+            reading("edge-b", "temperature", false),
+            reading("edge-a", "motion", false),
+            reading("edge-a", "humidity", false),
+        ]);
+        let pair = |d: &str, t: &str| (d.to_string(), t.to_string());
+        assert_eq!(chart_sensors(&groups, None), vec![pair("edge-a", "humidity"), pair("edge-b", "temperature")]);
+        assert_eq!(chart_sensors(&groups, Some("edge-b")), vec![pair("edge-b", "temperature")]);
+        assert!(chart_sensors(&groups, Some("edge-c")).is_empty());
+    }
+
+    #[test]
+    fn test_device_hash_round_trip() {
+        let id = "550e8400-e29b-41d4-a716-446655440000";
+        assert_eq!(device_hash(Some(id)), format!("#device={id}"));
+        assert_eq!(selected_device_from_hash(&device_hash(Some(id))).as_deref(), Some(id));
+        assert_eq!(device_hash(None), "");
+        for hash in ["", "#", "#device=", "#other=1"] {
+            assert_eq!(selected_device_from_hash(hash), None, "{hash}");
+        }
+        // `#`, `&` veya boşluk içeren id hash'i bozmaz
+        let odd = "kitchen #2&co ç";
+        assert_eq!(device_hash(Some(odd)), "#device=kitchen%20%232%26co%20%C3%A7");
+        assert_eq!(selected_device_from_hash(&device_hash(Some(odd))).as_deref(), Some(odd));
+        assert_eq!(device_short_name(id), "446655440000");
+        assert_eq!(device_short_name("raspberry"), "raspberry");
+    }
+
+    #[test]
+    fn test_encode_component() {
+        assert_eq!(encode_component("edge-a_1.x~"), "edge-a_1.x~");
+        assert_eq!(encode_component("a/b&c=d#e f"), "a%2Fb%26c%3Dd%23e%20f");
+        assert_eq!(encode_component("ç"), "%C3%A7");
+
+        assert_eq!(decode_component("a%2Fb%26c%3Dd%23e%20f"), "a/b&c=d#e f");
+        assert_eq!(decode_component("%C3%A7"), "ç");
+        // Geçersiz diziler olduğu gibi kalır
+        assert_eq!(decode_component("100%"), "100%");
+        assert_eq!(decode_component("%zz%4"), "%zz%4");
+        assert_eq!(decode_component("%FF"), "\u{FFFD}");
+    }
+
+    #[test]
+    fn test_history_url_covers_range() {
+        let now = DateTime::parse_from_rfc3339("2024-01-20T10:30:00Z").unwrap().with_timezone(&Utc);
+        let url = history_url("edge-a", "temperature", HistoryRange::Day, now);
+        assert!(url.starts_with("http://localhost:3000/v1/sensors/edge-a/temperature/history?"), "{url}");
+        assert!(url.contains("from=2024-01-19T10:30:00Z&to=2024-01-20T10:30:00Z"), "{url}");
+        assert!(url.ends_with(&format!("&downsample=lttb&points={}", HISTORY_POINTS)), "{url}");
+        assert_eq!(HistoryRange::ALL.map(HistoryRange::as_str), ["1h", "24h", "7d"]);
+
+        let url = history_url("edge/a#1", "temp?x", HistoryRange::Hour, now);
+        assert!(url.starts_with("http://localhost:3000/v1/sensors/edge%2Fa%231/temp%3Fx/history?"), "{url}");
+    }
+
+    #[test]
+    fn test_chart_series_labels_and_values() {
+        let readings = vec![
+            SensorData { value: 21.5, ..reading("edge-a", "temperature", false) },
+            SensorData { value: 22.0, timestamp: "garbage".to_string(), ..reading("edge-a", "temperature", false) },
+        ];
+        let (labels, values) = chart_series(&readings, HistoryRange::Week);
+        assert_eq!(values, vec![21.5, 22.0]);
+        assert_eq!(labels[0].len(), "01-20 10:30".len());
+        assert_eq!(labels[1], "garbage");
+        let (labels, _) = chart_series(&readings[..1], HistoryRange::Hour);
+        assert_eq!(labels[0].len(), "10:30".len());
+    }
+
+    #[test]
+    fn test_normalize_series() {
+        assert_eq!(normalize_series(&[10.0, 15.0, 20.0]), vec![0.0, 50.0, 100.0]);
+        assert_eq!(normalize_series(&[-5.0, 5.0]), vec![0.0, 100.0]);
+        // Sabit seri ortada
+        assert_eq!(normalize_series(&[21.0, 21.0]), vec![50.0, 50.0]);
+        assert!(normalize_series(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_comparison_series_aligns_timestamps() {
+        let at = |value: f64, timestamp: &str, sensor_type: &str| SensorData {
+            value,
+            timestamp: timestamp.to_string(),
+            unit: if sensor_type == "temperature" { "°C" } else { "%" }.to_string(),
+            ..reading("edge-a", sensor_type, false)
+        };
+        let histories = vec![
+            (
+                "edge-a".to_string(),
+                "temperature".to_string(),
+                vec![at(20.0, "2024-01-20T10:00:00Z", "temperature"), at(24.0, "2024-01-20T10:10:00Z", "temperature")],
+            ),
+            (
+                "edge-a".to_string(),
+                "humidity".to_string(),
+                vec![
+                    at(40.0, "2024-01-20T10:05:00Z", "humidity"),
+                    at(60.0, "2024-01-20T10:10:00Z", "humidity"),
+                    at(99.0, "garbage", "humidity"),
+                ],
+            ),
+        ];
+        let (labels, series) = comparison_series(&histories, HistoryRange::Hour);
+
+        assert_eq!(labels.len(), 3);
+        assert_eq!(series[0].values, vec![Some(0.0), None, Some(100.0)]);
+        assert_eq!(series[1].values, vec![None, Some(0.0), Some(100.0)]);
+        assert_eq!(series[0].label, "a · temperature (20.0–24.0°C)");
+        assert_eq!(series[1].label, "a · humidity (40.0–60.0%)");
+
+        // Okuması olmayan sensör boş seri olarak kalır
+        let (labels, series) = comparison_series(&[("edge-b".to_string(), "co2".to_string(), vec![])], HistoryRange::Hour);
+        assert!(labels.is_empty());
+        assert_eq!(series, vec![ComparisonSeries { label: "b · co2".to_string(), values: vec![] }]);
+    }
+
+    #[test]
+    fn test_sensor_key_changes_with_reading() {
+        let sensor = reading("edge-a", "temperature", false);
+        let newer = SensorData { timestamp: "2024-01-20T10:31:00Z".to_string(), ..sensor.clone() };
+        assert_ne!(sensor_key(&sensor), sensor_key(&newer));
+        assert_eq!(sensor_key(&sensor), sensor_key(&sensor.clone()));
+    }
+
+    fn alert_event(device_id: &str, sensor_type: &str, value: f64, min: Option<f64>, max: Option<f64>) -> AlertEvent {
+        // API server formatı (severity yok → varsayılan)
+        serde_json::from_value(serde_json::json!({
+            "id": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+            "device_id": device_id,
+            "sensor_type": sensor_type,
+            "value": value,
+            "threshold": {"min": min, "max": max},
+            "message": format!("{sensor_type} {value} out of range"),
+            "triggered_at": "2024-01-20T10:30:00Z",
+            "acknowledged": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_breached_threshold_describes_bound() {
+        assert_eq!(alert_event("edge-a", "temperature", 41.2, None, Some(35.0)).breached_threshold(), "Above max 35");
+        assert_eq!(alert_event("edge-a", "temperature", 4.5, Some(10.0), Some(35.0)).breached_threshold(), "Below min 10");
+        // Eşik artık aşılmıyorsa (ör. kayıt sonrası değişti) mesaj gösterilir
+        let inside = alert_event("edge-a", "humidity", 50.0, Some(10.0), Some(90.0));
+        assert_eq!(inside.breached_threshold(), "humidity 50 out of range");
+        assert_eq!(inside.severity, AlertSeverity::default());
+    }
+
+    #[test]
+    fn test_active_alert_for_matches_device_and_sensor_type() {
+        let alerts = vec![
+            alert_event("edge-a", "temperature", 41.2, None, Some(35.0)),
+            alert_event("edge-b", "humidity", 95.0, None, Some(90.0)),
+        ];
+        let found = active_alert_for(&alerts, "edge-b", "humidity").unwrap();
+        assert_eq!(found.alert.value, 95.0);
+        assert!(active_alert_for(&alerts, "edge-a", "humidity").is_none());
+        assert!(active_alert_for(&alerts, "edge-c", "temperature").is_none());
+        assert!(active_alert_for(&[], "edge-a", "temperature").is_none());
+    }
+}
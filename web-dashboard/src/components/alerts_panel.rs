@@ -0,0 +1,67 @@
+//! Alarm paneli component'i
+//!
+//! Aktif alarmları önem derecesine göre renklendirilmiş liste olarak gösterir.
+//! Veriler `GET /v1/alerts/events?active=true`'dan gelir; onaylanmamış
+//! alarmlar "Acknowledge" butonuyla onaylanabilir.
+
+use leptos::*;
+use crate::api::AlertEvent;
+
+#[component]
+pub fn AlertsPanel(
+    alerts: Vec<AlertEvent>,
+    /// Kullanıcı "Acknowledge"a bastığında çağrılır
+    #[prop(into)]
+    on_ack: Callback<AlertEvent>,
+) -> impl IntoView {
+    if alerts.is_empty() {
+        return view! {
+            <div class="alerts-panel">
+                <h2>"Alerts"</h2>
+                <div class="alerts-empty">"No active alerts"</div>
+            </div>
+        }
+        .into_view();
+    }
+
+    let items = alerts
+        .into_iter()
+        .map(|event| {
+            let item_class = format!("alert-item {}", event.severity.css_class());
+            let triggered = event.alert.triggered_at.format("%H:%M:%S").to_string();
+            let ack = if event.acknowledged {
+                view! { <span class="alert-acked">"Acknowledged"</span> }.into_view()
+            } else {
+                let event = event.clone();
+                view! {
+                    <button class="alert-ack" on:click=move |_| on_ack.call(event.clone())>
+                        "Acknowledge"
+                    </button>
+                }
+                .into_view()
+            };
+
+            view! {
+                <li class=item_class>
+                    <div class="alert-message">
+                        <strong>{event.alert.device_id.clone()}</strong>
+                        " · "
+                        {event.alert.message.clone()}
+                    </div>
+                    <div class="alert-meta">
+                        <span class="alert-time">{triggered}</span>
+                        {ack}
+                    </div>
+                </li>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div class="alerts-panel">
+            <h2>"Alerts"</h2>
+            <ul class="alert-list">{items}</ul>
+        </div>
+    }
+    .into_view()
+}
@@ -0,0 +1,162 @@
+//! Komut geçmişi paneli
+//!
+//! Seçili cihaza gönderilen son komutları ve cihazın verdiği sonucu
+//! (`GET /v1/devices/{id}/commands/history`) tablo olarak gösterir. Sonuç
+//! rengi: executed yeşil, failed kırmızı, yanıt bekleyenler sarı. Satıra
+//! tıklanınca komutun parametreleri açılır. Liste 10 saniyede bir yenilenir.
+
+use std::time::Duration;
+
+use leptos::*;
+use shared_types::{AckStatus, CommandHistoryEntry};
+use uuid::Uuid;
+use crate::api;
+
+/// Gösterilen komut sayısı
+const HISTORY_LIMIT: usize = 20;
+
+/// Yenileme aralığı
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+#[component]
+pub fn CommandHistory(
+    /// Komutları gösterilecek cihaz
+    #[prop(into)]
+    device_id: Signal<String>,
+) -> impl IntoView {
+    let (entries, set_entries) = create_signal(Vec::<CommandHistoryEntry>::new());
+    let (error, set_error) = create_signal(None::<String>);
+    // Parametreleri açık olan komut
+    let (expanded, set_expanded) = create_signal(None::<Uuid>);
+    // Sadece son isteğin yanıtı gösterilir (cihaz hızlı değiştirilirse)
+    let request = store_value(0u64);
+
+    let refresh = move || {
+        request.update_value(|n| *n += 1);
+        let current = request.get_value();
+        let device_id = device_id.get_untracked();
+        spawn_local(async move {
+            let result = api::fetch_command_history(&device_id, HISTORY_LIMIT).await;
+            if request.try_get_value() != Some(current) {
+                return;
+            }
+            match result {
+                Ok(history) => {
+                    set_entries.set(history);
+                    set_error.set(None);
+                }
+                Err(e) => set_error.set(Some(e)),
+            }
+        });
+    };
+
+    // Cihaz değişince liste sıfırlanır ve hemen çekilir
+    create_effect(move |_| {
+        device_id.track();
+        set_entries.set(Vec::new());
+        set_expanded.set(None);
+        refresh();
+    });
+    if let Ok(handle) = set_interval_with_handle(refresh, REFRESH_INTERVAL) {
+        on_cleanup(move || handle.clear());
+    }
+
+    let toggle = move |id: Uuid| set_expanded.update(|open| *open = if *open == Some(id) { None } else { Some(id) });
+
+    view! {
+        <section class="history-panel command-history">
+            <div class="history-panel-header">
+                <h2>"Command History"</h2>
+            </div>
+            {move || error.get().map(|message| view! { <div class="sensor-chart-status">{message}</div> })}
+            <Show
+                when=move || !entries.with(|entries| entries.is_empty())
+                fallback=move || view! { <div class="alerts-empty">"No commands sent to this device"</div> }
+            >
+                <table class="command-table">
+                    <thead>
+                        <tr>
+                            <th>"Command"</th>
+                            <th>"Sent"</th>
+                            <th>"Status"</th>
+                            <th>"Message"</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        <For
+                            each=move || entries.get()
+                            key=|entry| (entry.correlation_id, entry.ack_status, entry.acked_at)
+                            children=move |entry| {
+                                let id = entry.correlation_id;
+                                let (class, label) = ack_badge(entry.ack_status);
+                                let details = parameters_text(&entry);
+                                view! {
+                                    <tr class="command-row" on:click=move |_| toggle(id)>
+                                        <td>{entry.command_name.clone()}</td>
+                                        <td>{entry.dispatched_at.format("%Y-%m-%d %H:%M:%S").to_string()}</td>
+                                        <td><span class=format!("command-status {class}")>{label}</span></td>
+                                        <td>{entry.ack_message.clone().unwrap_or_default()}</td>
+                                    </tr>
+                                    <Show when=move || expanded.get() == Some(id)>
+                                        <tr class="command-details">
+                                            <td colspan="4"><pre>{details.clone()}</pre></td>
+                                        </tr>
+                                    </Show>
+                                }
+                            }
+                        />
+                    </tbody>
+                </table>
+            </Show>
+        </section>
+    }
+}
+
+/// Sonucun CSS class'ı ve etiketi
+fn ack_badge(status: Option<AckStatus>) -> (&'static str, &'static str) {
+    match status {
+        Some(AckStatus::Executed) => ("command-executed", "Executed"),
+        Some(AckStatus::Failed) => ("command-failed", "Failed"),
+        Some(AckStatus::Cancelled) => ("command-cancelled", "Cancelled"),
+        None => ("command-pending", "Pending"),
+    }
+}
+
+/// Açılan satırda gösterilen parametreler (okunaklı JSON)
+fn parameters_text(entry: &CommandHistoryEntry) -> String {
+    match &entry.parameters {
+        Some(parameters) => serde_json::to_string_pretty(parameters).unwrap_or_else(|_| parameters.to_string()),
+        None => "No parameters".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_ack_badge_per_status() {
+        assert_eq!(ack_badge(Some(AckStatus::Executed)), ("command-executed", "Executed"));
+        assert_eq!(ack_badge(Some(AckStatus::Failed)), ("command-failed", "Failed"));
+        assert_eq!(ack_badge(Some(AckStatus::Cancelled)), ("command-cancelled", "Cancelled"));
+        assert_eq!(ack_badge(None), ("command-pending", "Pending"));
+    }
+
+    #[test]
+    fn test_parameters_text() {
+        let mut entry = CommandHistoryEntry {
+            correlation_id: Uuid::nil(),
+            command_name: "led".into(),
+            parameters: None,
+            dispatched_at: Utc::now(),
+            ack_status: None,
+            ack_message: None,
+            acked_at: None,
+        };
+        assert_eq!(parameters_text(&entry), "No parameters");
+
+        entry.parameters = Some(serde_json::json!({"led_id": "led_01"}));
+        assert_eq!(parameters_text(&entry), "{\n  \"led_id\": \"led_01\"\n}");
+    }
+}
@@ -0,0 +1,238 @@
+//! Çoklu sensör karşılaştırma grafiği
+//!
+//! Seçilen sensörlerin (ör. sıcaklık ve nem) geçmişini aynı zaman ekseninde
+//! üst üste çizer. Birimler farklı olduğundan her seri kendi min-max'ına göre
+//! 0–100'e normalize edilir; gerçek aralık lejantta yazar. Geçmişler
+//! `GET /v1/sensors/{device_id}/{sensor_type}/history`'den paralel çekilir.
+//! `SensorSelector` grafiğe girecek sensörleri checkbox'larla seçtirir.
+
+use futures_util::future::join_all;
+use leptos::*;
+use wasm_bindgen::JsValue;
+use crate::api::{self, ComparisonSeries, HistoryRange};
+use crate::components::sensor_chart::{Chart, TimeRangePicker};
+
+/// Seri renkleri (sırayla, sonra başa döner)
+const SERIES_COLORS: [&str; 6] = ["#667eea", "#f5576c", "#43e97b", "#f6a609", "#38b2ac", "#9f7aea"];
+
+#[component]
+pub fn ComparisonChart(
+    /// Karşılaştırılacak sensörler (device_id, sensor_type)
+    #[prop(into)]
+    sensors: MaybeSignal<Vec<(String, String)>>,
+) -> impl IntoView {
+    let (range, set_range) = create_signal(HistoryRange::default());
+    let (refresh, set_refresh) = create_signal(0u32);
+    // Grafik yerine (veya üstünde) gösterilen durum mesajı
+    let (status, set_status) = create_signal(None::<String>);
+    let canvas = create_node_ref::<html::Canvas>();
+    let chart = store_value(None::<Chart>);
+    // Sadece son isteğin yanıtı çizilir (seçim hızlı değiştirilirse)
+    let request = store_value(0u64);
+
+    let clear_chart = move || {
+        chart.try_update_value(|chart| chart.take().map(|c| c.destroy()));
+    };
+
+    let draw = move |labels: Vec<String>, series: Vec<ComparisonSeries>| -> bool {
+        let Some(canvas) = canvas.get_untracked() else {
+            return false;
+        };
+        clear_chart();
+        let config = js_sys::JSON::parse(&chart_config(labels, series).to_string()).unwrap_or(JsValue::NULL);
+        match Chart::new(&canvas, &config) {
+            Ok(created) => {
+                chart.set_value(Some(created));
+                true
+            }
+            Err(_) => {
+                set_status.set(Some("Chart.js is not loaded".to_string()));
+                false
+            }
+        }
+    };
+
+    create_effect(move |_| {
+        let (sensors, range) = (sensors.get(), range.get());
+        refresh.track();
+        request.update_value(|n| *n += 1);
+        if sensors.is_empty() {
+            clear_chart();
+            set_status.set(Some("Select sensors to compare".to_string()));
+            return;
+        }
+        let current = request.get_value();
+        set_status.set(Some("Loading history...".to_string()));
+
+        spawn_local(async move {
+            let results = join_all(
+                sensors
+                    .iter()
+                    .map(|(device_id, sensor_type)| api::fetch_sensor_history(device_id, sensor_type, range)),
+            )
+            .await;
+            if request.try_get_value() != Some(current) {
+                return;
+            }
+
+            // Çekilemeyen sensörler atlanır, ilk hata gösterilir
+            let mut first_error = None;
+            let histories: Vec<_> = sensors
+                .into_iter()
+                .zip(results)
+                .filter_map(|((device_id, sensor_type), result)| match result {
+                    Ok(readings) => Some((device_id, sensor_type, readings)),
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                        None
+                    }
+                })
+                .collect();
+
+            let (labels, series) = api::comparison_series(&histories, range);
+            if labels.is_empty() {
+                clear_chart();
+                set_status.set(Some(first_error.unwrap_or_else(|| format!("No readings in the last {}", range.as_str()))));
+            } else if draw(labels, series) {
+                set_status.set(first_error);
+            }
+        });
+    });
+    on_cleanup(clear_chart);
+
+    view! {
+        <div class="sensor-chart comparison-chart">
+            <div class="sensor-chart-toolbar">
+                <TimeRangePicker range=range on_change=move |preset| set_range.set(preset)/>
+                <button class="sensor-chart-refresh" on:click=move |_| set_refresh.update(|n| *n += 1)>
+                    "Refresh"
+                </button>
+            </div>
+            {move || status.get().map(|message| view! { <div class="sensor-chart-status">{message}</div> })}
+            <div class="sensor-chart-canvas">
+                <canvas node_ref=canvas></canvas>
+            </div>
+        </div>
+    }
+}
+
+/// Karşılaştırılacak sensörlerin checkbox listesi
+#[component]
+pub fn SensorSelector(
+    /// Seçilebilecek sensörler (device_id, sensor_type)
+    #[prop(into)]
+    sensors: Signal<Vec<(String, String)>>,
+    #[prop(into)]
+    selected: Signal<Vec<(String, String)>>,
+    /// Bir checkbox değişince yeni seçimle çağrılır
+    #[prop(into)]
+    on_change: Callback<Vec<(String, String)>>,
+) -> impl IntoView {
+    view! {
+        <div class="sensor-selector">
+            <For
+                each=move || sensors.get()
+                key=|sensor| sensor.clone()
+                children=move |sensor| {
+                    let label = format!("{} · {}", api::device_short_name(&sensor.0), sensor.1);
+                    let checked = {
+                        let sensor = sensor.clone();
+                        move || selected.with(|selected| selected.contains(&sensor))
+                    };
+                    view! {
+                        <label class="sensor-selector-option">
+                            <input
+                                type="checkbox"
+                                prop:checked=checked
+                                on:change=move |_| on_change.call(toggled(&selected.get_untracked(), &sensor))
+                            />
+                            {label}
+                        </label>
+                    }
+                }
+            />
+        </div>
+    }
+}
+
+/// `sensor` seçiliyse çıkarılmış, değilse sona eklenmiş seçim
+fn toggled(selected: &[(String, String)], sensor: &(String, String)) -> Vec<(String, String)> {
+    if selected.contains(sensor) {
+        selected.iter().filter(|s| *s != sensor).cloned().collect()
+    } else {
+        selected.iter().cloned().chain([sensor.clone()]).collect()
+    }
+}
+
+/// Chart.js çok serili çizgi grafiği ayarları
+fn chart_config(labels: Vec<String>, series: Vec<ComparisonSeries>) -> serde_json::Value {
+    let datasets: Vec<_> = series
+        .into_iter()
+        .zip(SERIES_COLORS.iter().cycle())
+        .map(|(series, color)| {
+            serde_json::json!({
+                "label": series.label,
+                "data": series.values,
+                "borderColor": color,
+                "backgroundColor": color,
+                "fill": false,
+                "tension": 0.3,
+                "pointRadius": 0,
+                // Seriler farklı zamanlarda okunur; boşluklar çizgiyle birleştirilir
+                "spanGaps": true,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "type": "line",
+        "data": {"labels": labels, "datasets": datasets},
+        "options": {
+            "responsive": true,
+            "maintainAspectRatio": false,
+            "animation": false,
+            "interaction": {"mode": "index", "intersect": false},
+            "plugins": {"legend": {"display": true, "position": "bottom"}},
+            "scales": {
+                "x": {"ticks": {"maxTicksLimit": 8}},
+                "y": {"min": 0, "max": 100, "title": {"display": true, "text": "normalized (0–100)"}},
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sensor(device_id: &str, sensor_type: &str) -> (String, String) {
+        (device_id.to_string(), sensor_type.to_string())
+    }
+
+    #[test]
+    fn test_toggled_adds_and_removes() {
+        let temp = sensor("edge-a", "temperature");
+        let hum = sensor("edge-a", "humidity");
+
+        let selected = toggled(&[], &temp);
+        assert_eq!(selected, vec![temp.clone()]);
+        let selected = toggled(&selected, &hum);
+        assert_eq!(selected, vec![temp.clone(), hum.clone()]);
+        assert_eq!(toggled(&selected, &temp), vec![hum]);
+    }
+
+    #[test]
+    fn test_chart_config_one_dataset_per_series() {
+        let series = (0..7)
+            .map(|i| ComparisonSeries { label: format!("s{i}"), values: vec![Some(0.0), None] })
+            .collect();
+        let config = chart_config(vec!["10:00".into(), "10:05".into()], series);
+        let datasets = config["data"]["datasets"].as_array().unwrap();
+
+        assert_eq!(datasets.len(), 7);
+        assert_eq!(datasets[1]["label"], "s1");
+        assert_eq!(datasets[0]["data"], serde_json::json!([0.0, null]));
+        // Renkler paletin sonunda başa döner
+        assert_eq!(datasets[6]["borderColor"], datasets[0]["borderColor"]);
+        assert_eq!(config["options"]["scales"]["y"]["max"], 100);
+    }
+}
@@ -0,0 +1,165 @@
+//! API bağlantı durumu component'i
+//!
+//! Dashboard başlığında API server'a ulaşılıp ulaşılamadığını renkli bir nokta
+//! ve yazıyla gösterir. `GET /health` her 5 saniyede bir yoklanır; 5 saniyede
+//! cevap gelmeyen yoklama iptal edilir ve ağ hatası sayılır. Art arda
+//! iki ağ hatasında bağlantı kopmuş sayılır ve "veriler eski olabilir"
+//! uyarısı çıkar. Bağlantı geri gelince uyarı kalkar ve `on_reconnect`
+//! çağrılır (dashboard tüm verileri hemen yeniden çeker).
+
+use std::time::Duration;
+
+use leptos::*;
+use crate::api::{self, HealthProbe};
+use crate::utils::Debounce;
+
+/// `GET /health` yoklama aralığı (aynı zamanda yoklamanın zaman aşımı)
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Bağlantının kopmuş sayılması için art arda ağ hatası sayısı
+const DISCONNECT_AFTER_FAILURES: u32 = 2;
+
+/// API server bağlantı durumu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connected,
+    /// Sunucu cevap veriyor ama HTTP hatası dönüyor veya DB'ye ulaşamıyor
+    Degraded,
+    Disconnected,
+}
+
+impl ConnectionState {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Connected => "Connected",
+            Self::Degraded => "Degraded",
+            Self::Disconnected => "Disconnected",
+        }
+    }
+
+    /// Noktanın rengi (yeşil / sarı / kırmızı)
+    pub fn class(self) -> &'static str {
+        match self {
+            Self::Connected => "connection-dot connection-connected",
+            Self::Degraded => "connection-dot connection-degraded",
+            Self::Disconnected => "connection-dot connection-disconnected",
+        }
+    }
+}
+
+/// Yoklama sonuçlarından bağlantı durumunu çıkarır
+///
+/// HTTP cevabı (başarılı veya değil) durumu hemen belirler; ağ hataları
+/// `Debounce` ile süzülür, tek bir geçici hata durumu değiştirmez.
+#[derive(Debug, Clone)]
+pub struct ConnectionMonitor {
+    state: ConnectionState,
+    failures: Debounce,
+}
+
+impl Default for ConnectionMonitor {
+    fn default() -> Self {
+        Self { state: ConnectionState::Connected, failures: Debounce::new(DISCONNECT_AFTER_FAILURES) }
+    }
+}
+
+impl ConnectionMonitor {
+    /// Yoklama sonucunu uygula ve yeni durumu döndür
+    pub fn observe(&mut self, probe: HealthProbe) -> ConnectionState {
+        self.state = match probe {
+            HealthProbe::Healthy => {
+                self.failures.reset();
+                ConnectionState::Connected
+            }
+            HealthProbe::Degraded => {
+                self.failures.reset();
+                ConnectionState::Degraded
+            }
+            HealthProbe::Unreachable if self.failures.hit() => ConnectionState::Disconnected,
+            HealthProbe::Unreachable => self.state,
+        };
+        self.state
+    }
+}
+
+#[component]
+pub fn ConnectionStatus(
+    /// Bağlantı `Disconnected`'dan geri geldiğinde çağrılır
+    #[prop(into)]
+    on_reconnect: Callback<()>,
+) -> impl IntoView {
+    let (conn_status, set_conn_status) = create_signal(ConnectionState::Connected);
+    let monitor = store_value(ConnectionMonitor::default());
+    // Yavaş bir yoklama bitmeden yenisi başlatılmaz
+    let in_flight = store_value(false);
+
+    let poll = move || {
+        if in_flight.get_value() {
+            return;
+        }
+        in_flight.set_value(true);
+        spawn_local(async move {
+            let probe = api::check_health(POLL_INTERVAL).await;
+            let (Some(previous), Some(state)) = (
+                conn_status.try_get_untracked(),
+                monitor.try_update_value(|monitor| monitor.observe(probe)),
+            ) else {
+                // Component kaldırıldı
+                return;
+            };
+            in_flight.set_value(false);
+            if state != previous {
+                set_conn_status.set(state);
+                if previous == ConnectionState::Disconnected {
+                    on_reconnect.call(());
+                }
+            }
+        });
+    };
+
+    poll();
+    if let Ok(handle) = set_interval_with_handle(poll, POLL_INTERVAL) {
+        on_cleanup(move || handle.clear());
+    }
+
+    view! {
+        <div class="connection-status" title="API server (GET /health)">
+            <span class=move || conn_status.get().class()></span>
+            <span class="connection-label">{move || conn_status.get().label()}</span>
+        </div>
+        <Show when=move || conn_status.get() == ConnectionState::Disconnected>
+            <div class="connection-banner">"⚠️ Connection lost — data may be stale"</div>
+        </Show>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_network_failures_disconnect() {
+        let mut monitor = ConnectionMonitor::default();
+        assert_eq!(monitor.observe(HealthProbe::Unreachable), ConnectionState::Connected);
+        assert_eq!(monitor.observe(HealthProbe::Unreachable), ConnectionState::Disconnected);
+        assert_eq!(monitor.observe(HealthProbe::Unreachable), ConnectionState::Disconnected);
+        assert_eq!(monitor.observe(HealthProbe::Healthy), ConnectionState::Connected);
+
+        // Araya giren cevap seriyi sıfırlar
+        assert_eq!(monitor.observe(HealthProbe::Unreachable), ConnectionState::Connected);
+        assert_eq!(monitor.observe(HealthProbe::Healthy), ConnectionState::Connected);
+        assert_eq!(monitor.observe(HealthProbe::Unreachable), ConnectionState::Connected);
+    }
+
+    #[test]
+    fn test_http_errors_degrade_without_disconnecting() {
+        let mut monitor = ConnectionMonitor::default();
+        assert_eq!(monitor.observe(HealthProbe::Degraded), ConnectionState::Degraded);
+        // Tek ağ hatası son durumu korur
+        assert_eq!(monitor.observe(HealthProbe::Unreachable), ConnectionState::Degraded);
+        assert_eq!(monitor.observe(HealthProbe::Degraded), ConnectionState::Degraded);
+        assert_eq!(monitor.observe(HealthProbe::Unreachable), ConnectionState::Degraded);
+        assert_eq!(monitor.observe(HealthProbe::Unreachable), ConnectionState::Disconnected);
+        assert_eq!(monitor.observe(HealthProbe::Degraded), ConnectionState::Degraded);
+    }
+}
@@ -0,0 +1,71 @@
+//! Cihaz bölümü component'i
+//!
+//! Bir cihazın sensör kartlarını başlığı tıklanınca katlanan bir bölümde
+//! gösterir. Başlıkta cihazın kısa adı, sensör sayısı ve çevrimiçi durumu
+//! (en az bir sensör güncel veri gönderiyorsa "Online") yer alır.
+
+use leptos::*;
+use crate::api::{self, AlertEvent, DeviceGroup};
+use crate::components::sensor_card::SensorCard;
+
+#[component]
+pub fn DeviceSection(
+    device_id: String,
+    /// Cihazın güncel okumaları (`None`: henüz okuma yok)
+    #[prop(into)]
+    group: Signal<Option<DeviceGroup>>,
+    /// Aktif alarmlar (sensörlere cihaz + tip ile eşlenir)
+    #[prop(into)]
+    alerts: Signal<Option<Vec<AlertEvent>>>,
+) -> impl IntoView {
+    let (expanded, set_expanded) = create_signal(true);
+    let short_name = api::device_short_name(&device_id).to_string();
+
+    let online = move || group.with(|g| g.as_ref().is_some_and(DeviceGroup::online));
+    let sensor_count = move || group.with(|g| g.as_ref().map_or(0, |g| g.sensors.len()));
+    let has_sensors = move || sensor_count() != 0;
+    let sensors = move || group.get().map(|g| g.sensors).unwrap_or_default();
+
+    view! {
+        <section class="device-section">
+            <button class="device-section-header" title=device_id.clone() on:click=move |_| set_expanded.update(|e| *e = !*e)>
+                <span class="device-section-toggle">{move || if expanded.get() { "▾" } else { "▸" }}</span>
+                <span class="device-section-name">{short_name}</span>
+                <span class="device-section-count">
+                    {move || match sensor_count() {
+                        1 => "1 sensor".to_string(),
+                        n => format!("{} sensors", n),
+                    }}
+                </span>
+                {move || if online() {
+                    view! { <span class="sensor-status status-online">"Online"</span> }
+                } else {
+                    view! { <span class="sensor-status status-offline">"Offline"</span> }
+                }}
+            </button>
+
+            <Show when=move || expanded.get()>
+                <Show
+                    when=has_sensors
+                    fallback=|| view! { <div class="device-section-empty">"No readings from this device yet"</div> }
+                >
+                    <div class="sensor-grid">
+                        <For
+                            each=sensors
+                            key=api::sensor_key
+                            children=move |sensor| {
+                                let (device_id, sensor_type) = (sensor.device_id.clone(), sensor.sensor_type.clone());
+                                let alert = Signal::derive(move || {
+                                    alerts.get().and_then(|alerts| {
+                                        api::active_alert_for(&alerts, &device_id, &sensor_type).cloned()
+                                    })
+                                });
+                                view! { <SensorCard sensor=sensor alert=alert/> }
+                            }
+                        />
+                    </div>
+                </Show>
+            </Show>
+        </section>
+    }
+}
@@ -1 +1,8 @@
+pub mod alerts_panel;
+pub mod command_history;
+pub mod comparison_chart;
+pub mod connection_status;
+pub mod device_section;
 pub mod sensor_card;
+pub mod sensor_chart;
+pub mod summary_bar;
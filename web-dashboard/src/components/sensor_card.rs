@@ -1,18 +1,59 @@
-/// Sensör kartı component'i
-/// 
-/// Her bir sensör için ayrı bir kart gösterir.
-/// Sıcaklık, nem, hareket gibi farklı sensör tiplerini destekler.
+//! Sensör kartı component'i
+//! 
+//! Her bir sensör için ayrı bir kart gösterir.
+//! Sıcaklık, nem, hareket gibi farklı sensör tiplerini destekler.
+//! Sensörün aktif alarmı varsa kart kırmızı çerçeveyle ve aşılan eşikle gösterilir.
+//! Motion kartları son hareketin ne kadar önce olduğunu da gösterir.
+//! Sensör bir süredir veri göndermiyorsa (`stale`) durum "Stale" olur.
 
+use std::time::Duration;
+
+use chrono::Utc;
 use leptos::*;
-use crate::api::SensorData;
+use crate::api::{self, AlertEvent, SensorData};
+use shared_types::{sensor::normalize_unit, SensorType};
 
 #[component]
-pub fn SensorCard(sensor: SensorData) -> impl IntoView {
-    // Sensör tipine göre CSS class
-    let sensor_class = format!("sensor-card {}", sensor.sensor_type);
+pub fn SensorCard(
+    sensor: SensorData,
+    /// Bu sensörün aktif alarmı (cihaz + sensör tipi eşleşmesi)
+    #[prop(into, optional)]
+    alert: Signal<Option<AlertEvent>>,
+) -> impl IntoView {
+    // Sensör tipine göre CSS class (alarm varsa kırmızı çerçeve)
+    let base_class = match sensor.stale {
+        true => format!("sensor-card {} status-stale", sensor.sensor_type),
+        false => format!("sensor-card {}", sensor.sensor_type),
+    };
+    let sensor_class = move || match alert.get() {
+        Some(_) => format!("{} sensor-alert", base_class),
+        None => base_class.clone(),
+    };
+    let kind = sensor.kind();
+    let is_motion = kind == SensorType::Motion;
     
+    // Motion kartı: son 24 saatin olaylarından son hareket ("last motion 12m ago")
+    let (last_motion, set_last_motion) = create_signal(None::<String>);
+    if is_motion {
+        let device_id = sensor.device_id.clone();
+        let refresh = move || {
+            let device_id = device_id.clone();
+            spawn_local(async move {
+                let now = Utc::now();
+                if let Ok(events) = api::fetch_motion_events(&device_id, now - chrono::Duration::hours(24)).await {
+                    let label = api::last_motion_label(&events, now).unwrap_or_else(|| "no motion in 24h".to_string());
+                    set_last_motion.set(Some(label));
+                }
+            });
+        };
+        refresh();
+        if let Ok(handle) = set_interval_with_handle(refresh, Duration::from_secs(30)) {
+            on_cleanup(move || handle.clear());
+        }
+    }
+
     // Sensör değerini formatla
-    let formatted_value = if sensor.sensor_type == "motion" {
+    let formatted_value = if is_motion {
         if sensor.value > 0.0 {
             "DETECTED".to_string()
         } else {
@@ -22,36 +63,40 @@ pub fn SensorCard(sensor: SensorData) -> impl IntoView {
         format!("{:.1}", sensor.value)
     };
     
-    // Sensör ismini formatla (ilk harfi büyük)
-    let sensor_name = sensor.sensor_type
-        .chars()
-        .enumerate()
-        .map(|(i, c)| if i == 0 { c.to_uppercase().to_string() } else { c.to_string() })
-        .collect::<String>();
+    // Sensör ismi ve birimi (eski birim yazımları standart birime çevrilir)
+    let sensor_name = kind.display_name();
+    let unit = match normalize_unit(&sensor.unit) {
+        "" => kind.default_unit().to_string(),
+        unit => unit.to_string(),
+    };
     
     // Timestamp'i formatla (sadece saat:dakika)
     let formatted_time = sensor.timestamp
         .split('T')
         .nth(1)
-        .and_then(|t| Some(t.split(':').take(2).collect::<Vec<_>>().join(":")))
+        .map(|t| t.split(':').take(2).collect::<Vec<_>>().join(":"))
         .unwrap_or_else(|| "N/A".to_string());
     
     // Device ID'nin son kısmını al (static string için)
-    let device_short = sensor.device_id
-        .split('-')
-        .last()
-        .unwrap_or("N/A")
-        .to_string();
+    let device_short = api::device_short_name(&sensor.device_id).to_string();
 
     view! {
         <div class=sensor_class>
             <div class="sensor-header">
                 <div class="sensor-name">{sensor_name}</div>
-                <div class="sensor-status status-online">"Online"</div>
+                {if sensor.stale {
+                    view! { <div class="sensor-status status-stale">"Stale"</div> }
+                } else {
+                    view! { <div class="sensor-status status-online">"Online"</div> }
+                }}
             </div>
 
+            {move || alert.get().map(|event| view! {
+                <div class="sensor-alert-threshold">{"⚠️ "}{event.breached_threshold()}</div>
+            })}
+
             {
-                if sensor.sensor_type == "motion" {
+                if is_motion {
                     // Motion sensor için özel görünüm
                     let motion_class = if sensor.value > 0.0 {
                         "motion-indicator motion-detected"
@@ -67,6 +112,9 @@ pub fn SensorCard(sensor: SensorData) -> impl IntoView {
                             <div style="text-align: center; font-size: 1.5rem; font-weight: 600; color: #333;">
                                 {formatted_value}
                             </div>
+                            {move || last_motion.get().map(|label| view! {
+                                <div class="motion-last">{label}</div>
+                            })}
                         </div>
                     }.into_view()
                 } else {
@@ -74,7 +122,7 @@ pub fn SensorCard(sensor: SensorData) -> impl IntoView {
                     view! {
                         <div class="sensor-value">
                             {formatted_value}
-                            <span class="sensor-unit">{&sensor.unit}</span>
+                            <span class="sensor-unit">{unit}</span>
                         </div>
                     }.into_view()
                 }
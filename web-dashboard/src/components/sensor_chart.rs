@@ -0,0 +1,162 @@
+//! Sensör geçmişi grafiği component'i
+//!
+//! Bir sensörün son 1 saat / 24 saat / 7 günlük okumalarını Chart.js çizgi
+//! grafiğiyle gösterir. Chart.js `index.html`'deki `<script>` ile yüklenir ve
+//! `wasm_bindgen` üzerinden çağrılır. Veriler
+//! `GET /v1/sensors/{device_id}/{sensor_type}/history`'den gelir (PostgreSQL
+//! gerekir); cihaz, sensör veya aralık değişince ya da "Refresh"e basılınca
+//! yeniden çekilir.
+
+use leptos::*;
+use wasm_bindgen::prelude::*;
+use crate::api::{self, HistoryRange, SensorData};
+
+#[wasm_bindgen]
+extern "C" {
+    /// Chart.js grafiği (global `Chart` sınıfı)
+    pub(crate) type Chart;
+
+    /// Yüklenmemişse (`Chart` tanımsız) hata döner
+    #[wasm_bindgen(constructor, catch)]
+    pub(crate) fn new(canvas: &web_sys::HtmlCanvasElement, config: &JsValue) -> Result<Chart, JsValue>;
+
+    #[wasm_bindgen(method)]
+    pub(crate) fn destroy(this: &Chart);
+}
+
+#[component]
+pub fn SensorChart(
+    #[prop(into)]
+    device_id: MaybeSignal<String>,
+    #[prop(into)]
+    sensor_type: MaybeSignal<String>,
+) -> impl IntoView {
+    let (range, set_range) = create_signal(HistoryRange::default());
+    let (refresh, set_refresh) = create_signal(0u32);
+    // Grafik yerine gösterilen durum mesajı (yükleniyor, veri yok, hata)
+    let (status, set_status) = create_signal(Some("Loading history...".to_string()));
+    let canvas = create_node_ref::<html::Canvas>();
+    let chart = store_value(None::<Chart>);
+    // Sadece son isteğin yanıtı çizilir (aralık hızlı değiştirilirse)
+    let request = store_value(0u64);
+
+    let clear_chart = move || {
+        chart.try_update_value(|chart| chart.take().map(|c| c.destroy()));
+    };
+
+    let draw = move |label: String, readings: Vec<SensorData>, range: HistoryRange| {
+        let Some(canvas) = canvas.get_untracked() else {
+            return;
+        };
+        clear_chart();
+        let (labels, values) = api::chart_series(&readings, range);
+        let config = js_sys::JSON::parse(&chart_config(&label, labels, values).to_string()).unwrap_or(JsValue::NULL);
+        match Chart::new(&canvas, &config) {
+            Ok(created) => {
+                chart.set_value(Some(created));
+                set_status.set(None);
+            }
+            Err(_) => set_status.set(Some("Chart.js is not loaded".to_string())),
+        }
+    };
+
+    create_effect(move |_| {
+        let (device_id, sensor_type, range) = (device_id.get(), sensor_type.get(), range.get());
+        refresh.track();
+        if device_id.is_empty() || sensor_type.is_empty() {
+            return;
+        }
+        request.update_value(|n| *n += 1);
+        let current = request.get_value();
+        set_status.set(Some("Loading history...".to_string()));
+
+        spawn_local(async move {
+            let result = api::fetch_sensor_history(&device_id, &sensor_type, range).await;
+            if request.try_get_value() != Some(current) {
+                return;
+            }
+            match result {
+                Ok(readings) if readings.is_empty() => {
+                    clear_chart();
+                    set_status.set(Some(format!("No readings in the last {}", range.as_str())));
+                }
+                Ok(readings) => draw(sensor_type, readings, range),
+                Err(e) => {
+                    clear_chart();
+                    set_status.set(Some(e));
+                }
+            }
+        });
+    });
+    on_cleanup(clear_chart);
+
+    view! {
+        <div class="sensor-chart">
+            <div class="sensor-chart-toolbar">
+                <TimeRangePicker range=range on_change=move |preset| set_range.set(preset)/>
+                <button class="sensor-chart-refresh" on:click=move |_| set_refresh.update(|n| *n += 1)>
+                    "Refresh"
+                </button>
+            </div>
+            {move || status.get().map(|message| view! { <div class="sensor-chart-status">{message}</div> })}
+            <div class="sensor-chart-canvas">
+                <canvas node_ref=canvas></canvas>
+            </div>
+        </div>
+    }
+}
+
+/// "1h" / "24h" / "7d" ön ayar butonları
+#[component]
+pub fn TimeRangePicker(
+    #[prop(into)]
+    range: Signal<HistoryRange>,
+    /// Kullanıcı bir ön ayara bastığında çağrılır
+    #[prop(into)]
+    on_change: Callback<HistoryRange>,
+) -> impl IntoView {
+    view! {
+        <div class="time-range-picker">
+            {HistoryRange::ALL
+                .into_iter()
+                .map(|preset| {
+                    view! {
+                        <button
+                            class="time-range-button"
+                            class:active=move || range.get() == preset
+                            on:click=move |_| on_change.call(preset)
+                        >
+                            {preset.as_str()}
+                        </button>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
+
+/// Chart.js çizgi grafiği ayarları
+fn chart_config(label: &str, labels: Vec<String>, values: Vec<f64>) -> serde_json::Value {
+    serde_json::json!({
+        "type": "line",
+        "data": {
+            "labels": labels,
+            "datasets": [{
+                "label": label,
+                "data": values,
+                "borderColor": "#667eea",
+                "backgroundColor": "rgba(102, 126, 234, 0.15)",
+                "fill": true,
+                "tension": 0.3,
+                "pointRadius": 0,
+            }],
+        },
+        "options": {
+            "responsive": true,
+            "maintainAspectRatio": false,
+            "animation": false,
+            "plugins": {"legend": {"display": false}},
+            "scales": {"x": {"ticks": {"maxTicksLimit": 8}}},
+        },
+    })
+}
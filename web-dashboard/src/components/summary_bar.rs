@@ -0,0 +1,56 @@
+//! Özet çubuğu component'i
+//! 
+//! Dashboard başlığının altında cihaz, gateway, sensör ve okuma sayılarını gösterir.
+//! Veriler `GET /api/summary`'den gelir; eksik backend'ler "—" olarak görünür.
+
+use leptos::*;
+use shared_types::DashboardSummary;
+
+#[component]
+pub fn SummaryBar(summary: DashboardSummary) -> impl IntoView {
+    // Bilinmeyen değerleri "—" olarak göster
+    let count = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_else(|| "—".to_string());
+
+    let devices = match (summary.devices_online, summary.devices_total) {
+        (Some(online), Some(total)) => format!("{} / {}", online, total),
+        (online, _) => count(online),
+    };
+    let last_ingest = summary
+        .last_ingest_at
+        .map(|t| t.format("%H:%M:%S").to_string())
+        .unwrap_or_else(|| "—".to_string());
+    let alerts_class = if summary.alerts_active.unwrap_or(0) > 0 {
+        "summary-item summary-alert"
+    } else {
+        "summary-item"
+    };
+
+    view! {
+        <div class="summary-bar">
+            <div class="summary-item">
+                <div class="summary-value">{devices}</div>
+                <div class="summary-label">"Devices online"</div>
+            </div>
+            <div class="summary-item">
+                <div class="summary-value">{count(summary.gateways_online)}</div>
+                <div class="summary-label">"Gateways online"</div>
+            </div>
+            <div class="summary-item">
+                <div class="summary-value">{count(summary.sensors_reporting)}</div>
+                <div class="summary-label">"Sensors reporting"</div>
+            </div>
+            <div class="summary-item">
+                <div class="summary-value">{count(summary.readings_last_hour)}</div>
+                <div class="summary-label">"Readings (1h)"</div>
+            </div>
+            <div class=alerts_class>
+                <div class="summary-value">{count(summary.alerts_active)}</div>
+                <div class="summary-label">"Active alerts"</div>
+            </div>
+            <div class="summary-item">
+                <div class="summary-value">{last_ingest}</div>
+                <div class="summary-label">"Last reading"</div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,81 @@
+//! Leptos hook'ları
+//!
+//! Birden fazla component'in kullanabileceği, tarayıcı API'lerine bağlı
+//! reaktif durumlar.
+
+use gloo_storage::{LocalStorage, Storage};
+use leptos::*;
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+/// Kullanıcının tema tercihinin `localStorage` anahtarı
+pub const DARK_MODE_KEY: &str = "rustyflow.dark_mode";
+
+/// İşletim sistemi / tarayıcı koyu tema sorgusu
+const DARK_MODE_QUERY: &str = "(prefers-color-scheme: dark)";
+
+/// Koyu tema durumu ve değiştirici
+///
+/// Başlangıç değeri `localStorage`'daki tercihtir; tercih yoksa
+/// `prefers-color-scheme` kullanılır ve sistem teması değiştikçe (`change`
+/// olayı) izlenir. `toggle` temayı çevirir ve tercihi kaydeder; bundan sonra
+/// sistem teması yok sayılır.
+pub fn use_dark_mode() -> (ReadSignal<bool>, impl Fn() + Copy) {
+    let stored = LocalStorage::get::<bool>(DARK_MODE_KEY).ok();
+    let query = window().match_media(DARK_MODE_QUERY).ok().flatten();
+    let system_dark = query.as_ref().is_some_and(|q| q.matches());
+
+    let (dark_mode, set_dark_mode) = create_signal(stored.unwrap_or(system_dark));
+    let has_preference = store_value(stored.is_some());
+
+    if let Some(query) = query {
+        let on_change = Closure::<dyn Fn(web_sys::MediaQueryListEvent)>::new(move |ev: web_sys::MediaQueryListEvent| {
+            if has_preference.try_get_value() == Some(false) {
+                set_dark_mode.set(ev.matches());
+            }
+        });
+        let _ = query.add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref());
+        on_cleanup(move || {
+            let _ = query.remove_event_listener_with_callback("change", on_change.as_ref().unchecked_ref());
+        });
+    }
+
+    let toggle = move || {
+        let dark = !dark_mode.get_untracked();
+        has_preference.set_value(true);
+        if let Err(e) = LocalStorage::set(DARK_MODE_KEY, dark) {
+            logging::warn!("Failed to save theme preference: {e}");
+        }
+        set_dark_mode.set(dark);
+    };
+    (dark_mode, toggle)
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_toggle_persists_preference() {
+        LocalStorage::delete(DARK_MODE_KEY);
+        let runtime = create_runtime();
+
+        // Tercih yokken sistem teması
+        let system_dark = window().match_media(DARK_MODE_QUERY).unwrap().unwrap().matches();
+        let (dark_mode, toggle) = use_dark_mode();
+        assert_eq!(dark_mode.get_untracked(), system_dark);
+
+        toggle();
+        assert_eq!(dark_mode.get_untracked(), !system_dark);
+        assert_eq!(LocalStorage::get::<bool>(DARK_MODE_KEY).ok(), Some(!system_dark));
+
+        // Kaydedilen tercih bir sonraki açılışta sistem temasından önce gelir
+        let (reloaded, _) = use_dark_mode();
+        assert_eq!(reloaded.get_untracked(), !system_dark);
+
+        LocalStorage::delete(DARK_MODE_KEY);
+        runtime.dispose();
+    }
+}
@@ -1,25 +1,55 @@
 use leptos::*;
 use std::time::Duration;
+use shared_types::Unit;
 mod api;
 mod components;
+mod hooks;
+mod utils;
 
-use components::sensor_card::SensorCard;
+use components::alerts_panel::AlertsPanel;
+use components::command_history::CommandHistory;
+use components::comparison_chart::{ComparisonChart, SensorSelector};
+use components::connection_status::ConnectionStatus;
+use components::device_section::DeviceSection;
+use components::sensor_chart::SensorChart;
+use components::summary_bar::SummaryBar;
 
 /// Ana dashboard component'i
 /// 
 /// Bu component sensör verilerini API'den çeker ve ekranda gösterir.
-/// Her 2 saniyede bir otomatik olarak verileri yeniler.
+/// İlk yüklemede tüm sensörleri alır, sonra `/api/sensors/realtime`
+/// long-poll'u ile yeni okumaları neredeyse anında uygular.
+/// Kartlar cihaz bölümlerinde gruplanır; seçili cihaz URL hash'inde
+/// (`#device=<id>`) tutulur, böylece link paylaşılabilir. Kartların altında
+/// seçilen sensörün geçmiş grafiği ve açılır "Compare Sensors" bölümünde
+/// birden fazla sensörün normalize edilmiş karşılaştırması yer alır. Başlıktaki bağlantı göstergesi
+/// API server'a tekrar ulaşıldığında tüm verileri hemen yeniden çektirir.
+/// Tema sistemin `prefers-color-scheme`'ini izler; başlıktaki ☀️/🌙 butonu
+/// tercihi değiştirir ve `localStorage`'a kaydeder.
 #[component]
 fn App() -> impl IntoView {
     // API'den sensör verilerini çekmek için signal
     let (sensor_data, set_sensor_data) = create_signal(Vec::new());
     let (loading, set_loading) = create_signal(true);
     let (error, set_error) = create_signal(None::<String>);
+    let (summary, set_summary) = create_signal(None::<shared_types::DashboardSummary>);
+    // None: API server'da alarm endpoint'i yok (panel gizlenir)
+    let (alerts, set_alerts) = create_signal(None::<Vec<api::AlertEvent>>);
+    // Sıcaklık gösterim birimi (°C/°F anahtarı)
+    let (temperature_unit, set_temperature_unit) = create_signal(Unit::Celsius);
+    // Koyu tema (kök div'e .dark sınıfı)
+    let (dark_mode, toggle_dark_mode) = hooks::use_dark_mode();
+    // Cihaz seçici: None = tüm cihazlar; URL hash'inden başlar
+    let (selected_device, set_selected_device) = create_signal(api::selected_device_from_hash(&location_hash()));
+    // Cihaz API'sindeki ID'ler (henüz okuma göndermemiş cihazlar için)
+    let (known_devices, set_known_devices) = create_signal(Vec::<String>::new());
+    let groups = create_memo(move |_| api::group_by_device(&sensor_data.get()));
 
     // API'den veri çekme fonksiyonu
     let fetch_sensors = move || {
+        let unit = temperature_unit.get_untracked();
         spawn_local(async move {
-            match api::fetch_sensor_data().await {
+            match api::fetch_sensor_data(unit).await {
                 Ok(data) => {
                     set_sensor_data.set(data);
                     set_loading.set(false);
@@ -33,26 +63,172 @@ fn App() -> impl IntoView {
         });
     };
 
-    // Component yüklendiğinde veri çek
+    // Özet çubuğu; hata olursa son özet ekranda kalır
+    let fetch_summary = move || {
+        spawn_local(async move {
+            if let Ok(data) = api::fetch_summary().await {
+                set_summary.set(Some(data));
+            }
+        });
+    };
+
+    // Aktif alarmlar; ağ hatasında son liste ekranda kalır, 404'te panel gizlenir
+    let fetch_alerts = move || {
+        spawn_local(async move {
+            if let Ok(events) = api::fetch_active_alerts().await {
+                set_alerts.set(events);
+            }
+        });
+    };
+
+    // Alarmı onayla ve listede onaylandı olarak işaretle
+    let acknowledge = move |event: api::AlertEvent| {
+        spawn_local(async move {
+            if api::acknowledge_alert(&event).await.is_ok() {
+                set_alerts.update(|alerts| {
+                    if let Some(a) = alerts.iter_mut().flatten().find(|a| a.alert.id == event.alert.id) {
+                        a.acknowledged = true;
+                    }
+                });
+            }
+        });
+    };
+
+    // Özeti ve alarmları her 5 saniyede bir yenile (sunucu da birkaç saniye cache'ler)
+    create_effect(move |_| {
+        fetch_summary();
+        fetch_alerts();
+        set_interval(
+            move || {
+                fetch_summary();
+                fetch_alerts();
+            },
+            Duration::from_secs(5),
+        );
+    });
+
+    // Component yüklendiğinde veri çek, ardından realtime akışı dinle
     create_effect(move |_| {
         fetch_sensors();
+        poll_realtime(0, set_sensor_data, set_error, temperature_unit);
     });
 
-    // Her 2 saniyede bir otomatik yenile
+    // Cihaz listesi bir kez çekilir; endpoint yoksa seçici sadece okumalardan dolar
     create_effect(move |_| {
-        set_interval(
-            move || fetch_sensors(),
-            Duration::from_secs(2),
-        );
+        spawn_local(async move {
+            if let Ok(ids) = api::fetch_device_ids().await {
+                set_known_devices.set(ids);
+            }
+        });
     });
 
+    // Geri/ileri ve elle değiştirilen hash seçimi günceller
+    let hash_listener = window_event_listener(ev::hashchange, move |_| {
+        set_selected_device.set(api::selected_device_from_hash(&location_hash()));
+    });
+    on_cleanup(move || hash_listener.remove());
+
+    let select_device = move |value: String| {
+        let device = Some(value).filter(|v| !v.is_empty());
+        let _ = window().location().set_hash(&api::device_hash(device.as_deref()));
+        set_selected_device.set(device);
+    };
+
+    // Seçicideki cihazlar (hash'teki cihaz listede olmasa da görünür)
+    let device_options = move || {
+        let mut ids = groups.with(|groups| api::device_options(groups, &known_devices.get()));
+        if let Some(selected) = selected_device.get().filter(|id| !ids.contains(id)) {
+            ids.push(selected);
+        }
+        ids
+    };
+
+    // Gösterilen bölümler: seçili cihaz veya okuması olan tüm cihazlar
+    let visible_devices = move || match selected_device.get() {
+        Some(id) => vec![id],
+        None => groups.with(|groups| groups.iter().map(|g| g.device_id.clone()).collect()),
+    };
+
+    // Geçmiş grafiği: gösterilen cihazların sensörlerinden biri
+    let chart_sensors = create_memo(move |_| {
+        groups.with(|groups| api::chart_sensors(groups, selected_device.get().as_deref()))
+    });
+    let (chart_choice, set_chart_choice) = create_signal(None::<(String, String)>);
+    // Seçilen sensör listede yoksa (cihaz değişti) ilk sensör çizilir
+    let chart_target = create_memo(move |_| {
+        let sensors = chart_sensors.get();
+        chart_choice.get().filter(|choice| sensors.contains(choice)).or_else(|| sensors.into_iter().next())
+    });
+    let chart_device = Signal::derive(move || chart_target.get().map(|(device_id, _)| device_id).unwrap_or_default());
+    let chart_sensor_type = Signal::derive(move || chart_target.get().map(|(_, sensor_type)| sensor_type).unwrap_or_default());
+    let select_chart_sensor = move |value: String| {
+        let choice = value.rsplit_once('/').map(|(device_id, sensor_type)| (device_id.to_string(), sensor_type.to_string()));
+        set_chart_choice.set(choice);
+    };
+
+    // Karşılaştırma: tüm cihazların sensörleri; bölüm kapalıyken geçmiş çekilmez
+    let (compare_open, set_compare_open) = create_signal(false);
+    let compare_options = create_memo(move |_| groups.with(|groups| api::chart_sensors(groups, None)));
+    let (compare_choice, set_compare_choice) = create_signal(Vec::<(String, String)>::new());
+    // Kaybolan sensörler seçimden düşer
+    let compare_selected = create_memo(move |_| {
+        let options = compare_options.get();
+        compare_choice.get().into_iter().filter(|sensor| options.contains(sensor)).collect::<Vec<_>>()
+    });
+
+    // Bağlantı koptuktan sonra geri gelince her şey beklemeden yenilenir
+    let refetch_all = move |()| {
+        fetch_sensors();
+        fetch_summary();
+        fetch_alerts();
+    };
+
+    // °C/°F değişince liste sunucudan yeni birimle yeniden çekilir
+    let toggle_unit = move |_| {
+        set_temperature_unit.update(|unit| {
+            *unit = if *unit == Unit::Celsius { Unit::Fahrenheit } else { Unit::Celsius };
+        });
+        fetch_sensors();
+    };
+
     view! {
-        <div class="dashboard">
+        <div class="dashboard" class:dark=move || dark_mode.get()>
             <div class="dashboard-header">
                 <h1>"🦀 RustyFlow IoT Dashboard"</h1>
                 <p>"Real-time sensor monitoring with Rust + Leptos + WASM"</p>
+                <ConnectionStatus on_reconnect=refetch_all/>
+                <button class="unit-toggle" on:click=toggle_unit>
+                    {move || if temperature_unit.get() == Unit::Celsius { "°C → °F" } else { "°F → °C" }}
+                </button>
+                <button
+                    class="unit-toggle theme-toggle"
+                    title=move || if dark_mode.get() { "Switch to light mode" } else { "Switch to dark mode" }
+                    on:click=move |_| toggle_dark_mode()
+                >
+                    {move || if dark_mode.get() { "☀️" } else { "🌙" }}
+                </button>
+                <select class="device-selector" on:change=move |ev| select_device(event_target_value(&ev))>
+                    <option value="" selected=move || selected_device.get().is_none()>"All devices"</option>
+                    <For
+                        each=device_options
+                        key=|id| id.clone()
+                        children=move |id| {
+                            let label = api::device_short_name(&id).to_string();
+                            let value = id.clone();
+                            view! {
+                                <option value=value selected=move || selected_device.get().as_deref() == Some(id.as_str())>
+                                    {label}
+                                </option>
+                            }
+                        }
+                    />
+                </select>
             </div>
 
+            {move || summary.get().map(|summary| view! { <SummaryBar summary=summary/> })}
+
+            {move || alerts.get().map(|alerts| view! { <AlertsPanel alerts=alerts on_ack=acknowledge/> })}
+
             {move || {
                 if loading.get() && sensor_data.get().is_empty() {
                     view! {
@@ -67,13 +243,17 @@ fn App() -> impl IntoView {
                     }.into_view()
                 } else {
                     view! {
-                        <div class="sensor-grid">
+                        <div class="device-list">
                             <For
-                                each=move || sensor_data.get()
-                                key=|sensor| format!("{}-{}", sensor.device_id, sensor.sensor_type)
-                                children=move |sensor| {
+                                each=visible_devices
+                                key=|device_id| device_id.clone()
+                                children=move |device_id| {
+                                    let id = device_id.clone();
+                                    let group = Signal::derive(move || {
+                                        groups.with(|groups| groups.iter().find(|g| g.device_id == id).cloned())
+                                    });
                                     view! {
-                                        <SensorCard sensor=sensor/>
+                                        <DeviceSection device_id=device_id group=group alerts=alerts/>
                                     }
                                 }
                             />
@@ -81,10 +261,91 @@ fn App() -> impl IntoView {
                     }.into_view()
                 }
             }}
+
+            <Show when=move || chart_target.get().is_some()>
+                <section class="history-panel">
+                    <div class="history-panel-header">
+                        <h2>"History"</h2>
+                        <select class="history-sensor-selector" on:change=move |ev| select_chart_sensor(event_target_value(&ev))>
+                            <For
+                                each=move || chart_sensors.get()
+                                key=|sensor| sensor.clone()
+                                children=move |(device_id, sensor_type)| {
+                                    let label = format!("{} · {}", api::device_short_name(&device_id), sensor_type);
+                                    let value = format!("{}/{}", device_id, sensor_type);
+                                    let sensor = (device_id, sensor_type);
+                                    view! {
+                                        <option value=value selected=move || chart_target.get().as_ref() == Some(&sensor)>
+                                            {label}
+                                        </option>
+                                    }
+                                }
+                            />
+                        </select>
+                    </div>
+                    <SensorChart device_id=chart_device sensor_type=chart_sensor_type/>
+                </section>
+            </Show>
+
+            <Show when=move || selected_device.get().is_some()>
+                <CommandHistory device_id=Signal::derive(move || selected_device.get().unwrap_or_default())/>
+            </Show>
+
+            <Show when=move || !compare_options.with(|options| options.is_empty())>
+                <section class="history-panel comparison-panel">
+                    <button class="accordion-toggle" on:click=move |_| set_compare_open.update(|open| *open = !*open)>
+                        {move || if compare_open.get() { "▾ Compare Sensors" } else { "▸ Compare Sensors" }}
+                    </button>
+                    <Show when=move || compare_open.get()>
+                        <SensorSelector
+                            sensors=compare_options
+                            selected=compare_selected
+                            on_change=move |selection| set_compare_choice.set(selection)
+                        />
+                        <ComparisonChart sensors=compare_selected/>
+                    </Show>
+                </section>
+            </Show>
         </div>
     }
 }
 
+/// Tarayıcı URL'inin hash'i (`#device=...`)
+fn location_hash() -> String {
+    window().location().hash().unwrap_or_default()
+}
+
+/// Realtime long-poll döngüsü
+/// 
+/// Her yanıt geldiğinde hemen yeni istek atılır. Hata durumunda
+/// sunucuyu yormamak için 2 saniye beklenir. Sıcaklıklar seçili birime çevrilir.
+fn poll_realtime(
+    since: u64,
+    set_sensor_data: WriteSignal<Vec<api::SensorData>>,
+    set_error: WriteSignal<Option<String>>,
+    temperature_unit: ReadSignal<Unit>,
+) {
+    spawn_local(async move {
+        match api::fetch_realtime(since).await {
+            Ok(batch) => {
+                if !batch.events.is_empty() {
+                    let unit = temperature_unit.get_untracked();
+                    set_sensor_data.update(|sensors| api::merge_events(sensors, batch.events, unit));
+                }
+                set_error.set(None);
+                poll_realtime(batch.next_sequence, set_sensor_data, set_error, temperature_unit);
+            }
+            Err(e) => {
+                set_error.set(Some(e));
+                set_timeout(
+                    move || poll_realtime(since, set_sensor_data, set_error, temperature_unit),
+                    Duration::from_secs(2),
+                );
+            }
+        }
+    });
+}
+
 fn main() {
     // Panic mesajlarını browser console'a yazdır
     console_error_panic_hook::set_once();
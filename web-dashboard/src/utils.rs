@@ -0,0 +1,48 @@
+//! Component'lerden bağımsız küçük yardımcılar
+
+/// Art arda tekrarlanan olayları sayar, `threshold`'a ulaşınca tetiklenir
+///
+/// Geçici hataların durumu sürekli değiştirmesini (flapping) önler: tek bir
+/// başarısız istek yok sayılır, ancak `threshold` kadar art arda gelirse
+/// dikkate alınır. Araya giren başarılı sonuç seriyi `reset` ile sıfırlar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Debounce {
+    threshold: u32,
+    count: u32,
+}
+
+impl Debounce {
+    pub const fn new(threshold: u32) -> Self {
+        Self { threshold, count: 0 }
+    }
+
+    /// Olayı kaydet; seri `threshold`'a ulaştıysa `true`
+    pub fn hit(&mut self) -> bool {
+        self.count = self.count.saturating_add(1);
+        self.count >= self.threshold
+    }
+
+    /// Seriyi sıfırla
+    pub fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounce_triggers_after_consecutive_hits() {
+        let mut debounce = Debounce::new(2);
+        assert!(!debounce.hit());
+        assert!(debounce.hit());
+        // Seri sürdükçe tetiklenmiş kalır
+        assert!(debounce.hit());
+
+        debounce.reset();
+        assert!(!debounce.hit());
+        debounce.reset();
+        assert!(!debounce.hit());
+    }
+}